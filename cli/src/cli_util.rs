@@ -65,12 +65,19 @@ use jj_lib::config::ConfigNamePathBuf;
 use jj_lib::config::ConfigSource;
 use jj_lib::config::StackedConfig;
 use jj_lib::conflicts::ConflictMarkerStyle;
+use jj_lib::copies::CopyDetectionOptions;
 use jj_lib::fileset;
 use jj_lib::fileset::FilesetDiagnostics;
 use jj_lib::fileset::FilesetExpression;
+#[cfg(target_os = "linux")]
+use jj_lib::fsmonitor::native;
+#[cfg(target_os = "linux")]
+use jj_lib::fsmonitor::FsmonitorSettings;
 use jj_lib::gitignore::GitIgnoreError;
 use jj_lib::gitignore::GitIgnoreFile;
 use jj_lib::id_prefix::IdPrefixContext;
+#[cfg(target_os = "linux")]
+use jj_lib::local_working_copy::LocalWorkingCopy;
 use jj_lib::matchers::Matcher;
 use jj_lib::merge::MergedTreeValue;
 use jj_lib::merged_tree::MergedTree;
@@ -146,6 +153,8 @@ use crate::command_error::internal_error_with_message;
 use crate::command_error::print_parse_diagnostics;
 use crate::command_error::user_error;
 use crate::command_error::user_error_with_hint;
+#[cfg(target_os = "linux")]
+use crate::command_error::user_error_with_message;
 use crate::command_error::CommandError;
 use crate::commit_templater::CommitTemplateLanguage;
 use crate::commit_templater::CommitTemplateLanguageExtension;
@@ -337,8 +346,9 @@ impl CommandHelper {
     /// Settings for the current command and workspace.
     ///
     /// This may be different from the settings for new workspace created by
-    /// e.g. `jj git init`. There may be conditional variables and repo config
-    /// `.jj/repo/config.toml` loaded for the cwd workspace.
+    /// e.g. `jj git init`. There may be conditional variables, repo config
+    /// `.jj/repo/config.toml` and workspace config loaded for the cwd
+    /// workspace.
     pub fn settings(&self) -> &UserSettings {
         &self.data.settings
     }
@@ -353,6 +363,24 @@ impl CommandHelper {
         let repo_path = workspace_root.join(".jj").join("repo");
         config_env.reset_repo_path(&repo_path);
         config_env.reload_repo_config(&mut raw_config)?;
+        config_env.reset_workspace_path(workspace_root);
+        config_env.reload_workspace_config(&mut raw_config)?;
+        let mut config = config_env.resolve_config(&raw_config)?;
+        // No migration messages here, which would usually be emitted before.
+        jj_lib::config::migrate(&mut config, &self.data.config_migrations)?;
+        Ok(self.data.settings.with_new_config(config)?)
+    }
+
+    /// Resolves configuration for another workspace of the current repo
+    /// located at the specified path.
+    pub fn settings_for_workspace_of_repo(
+        &self,
+        workspace_root: &Path,
+    ) -> Result<UserSettings, CommandError> {
+        let mut config_env = self.data.config_env.clone();
+        let mut raw_config = self.data.raw_config.clone();
+        config_env.reset_workspace_path(workspace_root);
+        config_env.reload_workspace_config(&mut raw_config)?;
         let mut config = config_env.resolve_config(&raw_config)?;
         // No migration messages here, which would usually be emitted before.
         jj_lib::config::migrate(&mut config, &self.data.config_migrations)?;
@@ -453,6 +481,21 @@ impl CommandHelper {
         ui: &Ui,
     ) -> Result<WorkspaceCommandHelper, CommandError> {
         let workspace = self.load_workspace()?;
+        match workspace.moved_from() {
+            Ok(Some(old_root)) => {
+                writeln!(
+                    ui.warning_default(),
+                    "The workspace appears to have been moved from {}",
+                    old_root.display()
+                )?;
+                writeln!(
+                    ui.hint_default(),
+                    "Run `jj workspace repair` to update the paths pointing to it."
+                )?;
+            }
+            Ok(None) => {}
+            Err(err) => tracing::warn!(?err, "Failed to read workspace store"),
+        }
         let op_head = self.resolve_operation(ui, workspace.repo_loader())?;
         let repo = workspace.repo_loader().load_at(&op_head)?;
         let env = self.workspace_environment(ui, &workspace)?;
@@ -774,6 +817,7 @@ pub struct WorkspaceCommandEnvironment {
     immutable_heads_expression: Rc<UserRevsetExpression>,
     short_prefixes_expression: Option<Rc<UserRevsetExpression>>,
     conflict_marker_style: ConflictMarkerStyle,
+    copy_detection_options: CopyDetectionOptions,
 }
 
 impl WorkspaceCommandEnvironment {
@@ -796,6 +840,7 @@ impl WorkspaceCommandEnvironment {
             immutable_heads_expression: RevsetExpression::root(),
             short_prefixes_expression: None,
             conflict_marker_style: settings.get("ui.conflict-marker-style")?,
+            copy_detection_options: diff_util::copy_detection_options_from_settings(settings)?,
         };
         env.immutable_heads_expression = env.load_immutable_heads_expression(ui)?;
         env.short_prefixes_expression = env.load_short_prefixes_expression(ui)?;
@@ -858,6 +903,11 @@ impl WorkspaceCommandEnvironment {
         self.conflict_marker_style
     }
 
+    /// User-configured options for detecting copies and renames
+    pub fn copy_detection_options(&self) -> &CopyDetectionOptions {
+        &self.copy_detection_options
+    }
+
     fn load_immutable_heads_expression(
         &self,
         ui: &Ui,
@@ -965,6 +1015,7 @@ impl WorkspaceCommandEnvironment {
             id_prefix_context,
             self.immutable_expression(),
             self.conflict_marker_style,
+            self.copy_detection_options.clone(),
             &self.command.data.commit_template_extensions,
         )
     }
@@ -1428,6 +1479,7 @@ to the current parents may contain changes from multiple commits.
             self.repo().as_ref(),
             self.path_converter(),
             self.env.conflict_marker_style(),
+            self.env.copy_detection_options().clone(),
             formats,
         )
     }
@@ -1823,6 +1875,51 @@ to the current parents may contain changes from multiple commits.
         Err(error)
     }
 
+    /// Starts the built-in filesystem monitor in the background if it's enabled
+    /// with `core.native-fsmonitor.start-daemon` and isn't running yet.
+    #[cfg(target_os = "linux")]
+    fn start_native_fsmonitor(
+        &self,
+        fsmonitor_settings: &FsmonitorSettings,
+    ) -> Result<(), CommandError> {
+        use std::os::unix::process::CommandExt as _;
+        use std::process::Stdio;
+
+        let FsmonitorSettings::Native(config) = fsmonitor_settings else {
+            return Ok(());
+        };
+        let Some(wc) = self
+            .working_copy()
+            .as_any()
+            .downcast_ref::<LocalWorkingCopy>()
+        else {
+            return Ok(());
+        };
+        let fsmonitor = native::Fsmonitor::new(wc.state_path());
+        if !config.start_daemon
+            || fsmonitor.is_running().map_err(|err| {
+                user_error_with_message("Failed to access the filesystem monitor", err)
+            })?
+        {
+            return Ok(());
+        }
+        tracing::info!("Starting filesystem monitor...");
+        let start_error =
+            |err| user_error_with_message("Failed to start the filesystem monitor", err);
+        let exe = env::current_exe().map_err(start_error)?;
+        std::process::Command::new(exe)
+            .args(["debug", "fsmonitor", "run"])
+            .current_dir(self.workspace_root())
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            // Don't get interrupted along with the foreground command.
+            .process_group(0)
+            .spawn()
+            .map_err(start_error)?;
+        Ok(())
+    }
+
     #[instrument(skip_all)]
     fn snapshot_working_copy(
         &mut self,
@@ -1848,6 +1945,9 @@ to the current parents may contain changes from multiple commits.
         let options = self
             .snapshot_options_with_start_tracking_matcher(&auto_tracking_matcher)
             .map_err(snapshot_command_error)?;
+        #[cfg(target_os = "linux")]
+        self.start_native_fsmonitor(&options.fsmonitor_settings)
+            .map_err(snapshot_command_error)?;
 
         // Compare working-copy tree and operation with repo's, and reload as needed.
         let mut locked_ws = self
@@ -1906,6 +2006,9 @@ See https://jj-vcs.github.io/jj/latest/working-copy/#stale-working-copy \
                 }
                 Err(e) => return Err(snapshot_command_error(e)),
             };
+        if let Err(err) = locked_ws.backfill_workspace_store(repo.view()) {
+            tracing::warn!(?err, "Failed to record workspace location");
+        }
         self.user_repo = ReadonlyUserRepo::new(repo);
         let (new_tree_id, stats) = {
             let mut options = options;
@@ -2478,10 +2581,13 @@ jj git init --colocate",
                 user_error(message)
             }
         }
-        WorkspaceLoadError::RepoDoesNotExist(repo_dir) => user_error(format!(
-            "The repository directory at {} is missing. Was it moved?",
-            repo_dir.display(),
-        )),
+        WorkspaceLoadError::RepoDoesNotExist(repo_dir) => user_error_with_hint(
+            format!(
+                "The repository directory at {} is missing. Was it moved?",
+                repo_dir.display(),
+            ),
+            "If the repo was moved, run `jj workspace repair --repo <path>` in this workspace.",
+        ),
         WorkspaceLoadError::StoreLoadError(err @ StoreLoadError::UnsupportedType { .. }) => {
             internal_error_with_message(
                 "This version of the jj binary doesn't support this type of repo",
@@ -2492,6 +2598,9 @@ jj git init --colocate",
             err @ (StoreLoadError::ReadError { .. } | StoreLoadError::Backend(_)),
         ) => internal_error_with_message("The repository appears broken or inaccessible", err),
         WorkspaceLoadError::StoreLoadError(StoreLoadError::Signing(err)) => user_error(err),
+        WorkspaceLoadError::StoreLoadError(StoreLoadError::ContentMerger(err)) => {
+            config_error_with_message("Invalid `content-mergers` config", err)
+        }
         WorkspaceLoadError::WorkingCopyState(err) => internal_error(err),
         WorkspaceLoadError::NonUnicodePath | WorkspaceLoadError::Path(_) => user_error(err),
     }
@@ -2741,6 +2850,14 @@ Discard the conflicting changes with `jj restore --from {}`.",
             short_commit_hash(new_commit.id())
         )?;
     }
+    if stats.lfs_pointer_files != 0 {
+        writeln!(
+            ui.warning_default(),
+            "{} Git LFS files were checked out as pointer files because their contents \
+             couldn't be read.",
+            stats.lfs_pointer_files
+        )?;
+    }
     Ok(())
 }
 
@@ -3701,6 +3818,8 @@ impl CliRunner {
         if let Ok(loader) = &maybe_cwd_workspace_loader {
             config_env.reset_repo_path(loader.repo_path());
             config_env.reload_repo_config(&mut raw_config)?;
+            config_env.reset_workspace_path(loader.workspace_root());
+            config_env.reload_workspace_config(&mut raw_config)?;
         }
         let mut config = config_env.resolve_config(&raw_config)?;
         migrate_config(&mut config)?;
@@ -3742,6 +3861,8 @@ impl CliRunner {
                 .map_err(|err| map_workspace_load_error(err, Some(path)))?;
             config_env.reset_repo_path(loader.repo_path());
             config_env.reload_repo_config(&mut raw_config)?;
+            config_env.reset_workspace_path(loader.workspace_root());
+            config_env.reload_workspace_config(&mut raw_config)?;
             Ok(loader)
         } else {
             maybe_cwd_workspace_loader
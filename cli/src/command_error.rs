@@ -37,6 +37,8 @@ use jj_lib::op_heads_store::OpHeadsStoreError;
 use jj_lib::op_store::OpStoreError;
 use jj_lib::op_walk::OpsetEvaluationError;
 use jj_lib::op_walk::OpsetResolutionError;
+use jj_lib::patch::PatchApplyError;
+use jj_lib::patch::PatchParseError;
 use jj_lib::repo::CheckOutCommitError;
 use jj_lib::repo::EditCommitError;
 use jj_lib::repo::RepoLoaderError;
@@ -54,6 +56,8 @@ use jj_lib::working_copy::ResetError;
 use jj_lib::working_copy::SnapshotError;
 use jj_lib::working_copy::WorkingCopyStateError;
 use jj_lib::workspace::WorkspaceInitError;
+use jj_lib::workspace::WorkspaceMoveError;
+use jj_lib::workspace_store::WorkspaceStoreError;
 use thiserror::Error;
 
 use crate::cli_util::short_operation_hash;
@@ -350,6 +354,29 @@ impl From<WorkspaceInitError> for CommandError {
                 internal_error_with_message("Failed to access the repository", err)
             }
             WorkspaceInitError::SignInit(err) => user_error(err),
+            WorkspaceInitError::WorkspaceStore(err) => err.into(),
+        }
+    }
+}
+
+impl From<WorkspaceStoreError> for CommandError {
+    fn from(err: WorkspaceStoreError) -> Self {
+        internal_error_with_message("Failed to access the workspace store", err)
+    }
+}
+
+impl From<WorkspaceMoveError> for CommandError {
+    fn from(err: WorkspaceMoveError) -> Self {
+        match err {
+            WorkspaceMoveError::DestinationExists(_)
+            | WorkspaceMoveError::DestinationInsideWorkspace
+            | WorkspaceMoveError::InvalidDestination(_)
+            | WorkspaceMoveError::WorkspaceHasRepo
+            | WorkspaceMoveError::NonUnicodePath => user_error(err),
+            WorkspaceMoveError::Path(err) => {
+                user_error_with_message("Failed to update workspace paths", err)
+            }
+            WorkspaceMoveError::WorkspaceStore(err) => err.into(),
         }
     }
 }
@@ -417,6 +444,7 @@ impl From<DiffRenderError> for CommandError {
             DiffRenderError::Backend(err) => err.into(),
             DiffRenderError::AccessDenied { .. } => user_error(err),
             DiffRenderError::InvalidRepoPath(_) => user_error(err),
+            DiffRenderError::StructuralDiffUnsupported => user_error(err),
             DiffRenderError::Io(err) => err.into(),
         }
     }
@@ -484,6 +512,7 @@ mod git {
     use jj_lib::git::GitImportError;
     use jj_lib::git::GitPushError;
     use jj_lib::git::GitRemoteManagementError;
+    use jj_lib::git::GitSubmoduleError;
     use jj_lib::git::UnexpectedGitBackendError;
 
     use super::*;
@@ -540,6 +569,7 @@ jj currently does not support partial clones. To use jj with this repository, tr
                 GitFetchError::InvalidBranchPattern(_) => user_error(err),
                 GitFetchError::InternalGitError(err) => map_git2_error(err),
                 GitFetchError::Subprocess(_) => user_error(err),
+                GitFetchError::Gix(_) => user_error(err),
             }
         }
     }
@@ -570,6 +600,7 @@ jj currently does not support partial clones. To use jj with this repository, tr
                 GitPushError::RefUpdateRejected(_) => user_error(err),
                 GitPushError::InternalGitError(err) => map_git2_error(err),
                 GitPushError::Subprocess(_) => user_error(err),
+                GitPushError::Gix(_) => user_error(err),
                 GitPushError::UnexpectedBackend(_) => user_error(err),
             }
         }
@@ -587,6 +618,15 @@ jj currently does not support partial clones. To use jj with this repository, tr
         }
     }
 
+    impl From<GitSubmoduleError> for CommandError {
+        fn from(err: GitSubmoduleError) -> Self {
+            match err {
+                GitSubmoduleError::InternalGitError(err) => map_git2_error(err),
+                _ => user_error(err),
+            }
+        }
+    }
+
     impl From<UnexpectedGitBackendError> for CommandError {
         fn from(err: UnexpectedGitBackendError) -> Self {
             user_error(err)
@@ -721,6 +761,21 @@ impl From<AbsorbError> for CommandError {
     }
 }
 
+impl From<PatchParseError> for CommandError {
+    fn from(err: PatchParseError) -> Self {
+        user_error(err)
+    }
+}
+
+impl From<PatchApplyError> for CommandError {
+    fn from(err: PatchApplyError) -> Self {
+        match err {
+            PatchApplyError::InvalidTarget { .. } => user_error(err),
+            PatchApplyError::Backend(err) => err.into(),
+        }
+    }
+}
+
 fn find_source_parse_error_hint(err: &dyn error::Error) -> Option<String> {
     let source = err.source()?;
     if let Some(source) = source.downcast_ref() {
@@ -0,0 +1,252 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io;
+use std::io::Read as _;
+use std::io::Write as _;
+use std::path::Path;
+use std::path::PathBuf;
+
+use clap_complete::ArgValueCandidates;
+use itertools::Itertools as _;
+use jj_lib::backend::Signature;
+use jj_lib::object_id::ObjectId as _;
+use jj_lib::patch::apply_patch_to_tree;
+use jj_lib::patch::parse_patches;
+use jj_lib::patch::Patch;
+use jj_lib::patch::PatchApplyError;
+use jj_lib::patch::DEFAULT_FUZZ;
+use jj_lib::repo::Repo as _;
+use jj_lib::repo_path::RepoPathBuf;
+use pollster::FutureExt as _;
+use tracing::instrument;
+
+use crate::cli_util::CommandHelper;
+use crate::cli_util::RevisionArg;
+use crate::cli_util::WorkspaceCommandHelper;
+use crate::command_error::user_error_with_message;
+use crate::command_error::CommandError;
+use crate::complete;
+use crate::ui::Ui;
+
+/// Apply patches as new commits
+///
+/// Each file may contain a plain diff, or a series of email messages as
+/// generated by `jj export-patch` or `git format-patch`. Each patch becomes a
+/// new commit on top of the previous one. The author, date, and description
+/// are taken from the message headers if present.
+///
+/// With `--revision`, the changes are instead applied to the contents of an
+/// existing revision, e.g. `jj apply -r @ < fix.diff` applies a diff to the
+/// working copy.
+///
+/// Hunks are searched for near the line numbers recorded in the patch if the
+/// file has changed since the patch was made. Hunks that don't apply cleanly
+/// are recorded as conflicts instead of being rejected. Resolve them as usual,
+/// e.g. with `jj resolve`. Patches can't be applied to conflicted files, so the
+/// command stops if a later patch modifies a file that an earlier patch left
+/// conflicted.
+#[derive(clap::Args, Clone, Debug)]
+pub(crate) struct ApplyArgs {
+    /// The patch files to apply (default: read from stdin)
+    #[arg(value_hint = clap::ValueHint::FilePath)]
+    paths: Vec<PathBuf>,
+    /// The revision to apply the patches onto
+    #[arg(
+        long,
+        short,
+        default_value = "@",
+        value_name = "REVSET",
+        add = ArgValueCandidates::new(complete::all_revisions)
+    )]
+    destination: RevisionArg,
+    /// Apply the changes to this revision instead of creating new commits
+    #[arg(
+        long,
+        short,
+        conflicts_with = "destination",
+        value_name = "REVSET",
+        add = ArgValueCandidates::new(complete::mutable_revisions)
+    )]
+    revision: Option<RevisionArg>,
+    /// Maximum number of leading and trailing context lines that may be
+    /// ignored when locating a hunk
+    #[arg(long, default_value_t = DEFAULT_FUZZ, value_name = "LINES")]
+    fuzz: usize,
+}
+
+#[instrument(skip_all)]
+pub(crate) fn cmd_apply(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &ApplyArgs,
+) -> Result<(), CommandError> {
+    let mut workspace_command = command.workspace_helper(ui)?;
+    let patches: Vec<Patch> = if args.paths.is_empty() {
+        read_patches(Path::new("-"))?
+    } else {
+        args.paths
+            .iter()
+            .map(|path| read_patches(path))
+            .flatten_ok()
+            .try_collect()?
+    };
+    if patches.is_empty() {
+        writeln!(ui.status(), "No patches to apply.")?;
+        return Ok(());
+    }
+    if let Some(revision) = &args.revision {
+        return apply_to_revision(ui, workspace_command, revision, &patches, args.fuzz);
+    }
+    let mut parent = workspace_command.resolve_single_rev(ui, &args.destination)?;
+
+    let mut tx = workspace_command.start_transaction();
+    for (index, patch) in patches.iter().enumerate() {
+        let tree = parent.tree()?;
+        let applied = apply_patch_to_tree(tx.repo().store(), &tree, &patch.files, args.fuzz)
+            .block_on()
+            .map_err(|err| patch_apply_error(err, index, &patches))?;
+        let mut commit_builder = tx
+            .repo_mut()
+            .new_commit(vec![parent.id().clone()], applied.tree_id);
+        if let Some(header) = &patch.header {
+            let default_author = commit_builder.author().clone();
+            let (name, email) = header
+                .author
+                .clone()
+                .unwrap_or((default_author.name, default_author.email));
+            commit_builder = commit_builder
+                .set_description(&header.description)
+                .set_author(Signature {
+                    name,
+                    email,
+                    timestamp: header.author_timestamp.unwrap_or(default_author.timestamp),
+                });
+        }
+        let commit = commit_builder.write()?;
+        if let Some(mut formatter) = ui.status_formatter() {
+            write!(formatter, "Applied patch as ")?;
+            tx.write_commit_summary(formatter.as_mut(), &commit)?;
+            writeln!(formatter)?;
+        }
+        print_conflicted_paths(ui, tx.base_workspace_helper(), &applied.conflicted_paths)?;
+        parent = commit;
+    }
+    tx.finish(ui, format!("apply {} patches", patches.len()))?;
+    Ok(())
+}
+
+fn apply_to_revision(
+    ui: &mut Ui,
+    mut workspace_command: WorkspaceCommandHelper,
+    revision: &RevisionArg,
+    patches: &[Patch],
+    fuzz: usize,
+) -> Result<(), CommandError> {
+    let commit = workspace_command.resolve_single_rev(ui, revision)?;
+    workspace_command.check_rewritable([commit.id()])?;
+    let mut tree = commit.tree()?;
+    let mut conflicted_paths = vec![];
+    for (index, patch) in patches.iter().enumerate() {
+        let applied = apply_patch_to_tree(commit.store(), &tree, &patch.files, fuzz)
+            .block_on()
+            .map_err(|err| patch_apply_error(err, index, patches))?;
+        tree = commit.store().get_root_tree(&applied.tree_id)?;
+        conflicted_paths.extend(applied.conflicted_paths);
+    }
+    if tree.id() == *commit.tree_id() {
+        writeln!(ui.status(), "Nothing changed.")?;
+        return Ok(());
+    }
+
+    let mut tx = workspace_command.start_transaction();
+    let new_commit = tx
+        .repo_mut()
+        .rewrite_commit(&commit)
+        .set_tree_id(tree.id())
+        .write()?;
+    let num_rebased = tx.repo_mut().rebase_descendants()?;
+    if let Some(mut formatter) = ui.status_formatter() {
+        write!(formatter, "Applied patch to ")?;
+        tx.write_commit_summary(formatter.as_mut(), &new_commit)?;
+        writeln!(formatter)?;
+        if num_rebased > 0 {
+            writeln!(formatter, "Rebased {num_rebased} descendant commits")?;
+        }
+    }
+    conflicted_paths.sort();
+    conflicted_paths.dedup();
+    print_conflicted_paths(ui, tx.base_workspace_helper(), &conflicted_paths)?;
+    tx.finish(ui, format!("apply patch to commit {}", commit.id().hex()))?;
+    Ok(())
+}
+
+/// Adds the `index`-th patch to the error if the patch can't be applied to
+/// the tree.
+fn patch_apply_error(err: PatchApplyError, index: usize, patches: &[Patch]) -> CommandError {
+    match err {
+        PatchApplyError::InvalidTarget { .. } => {
+            let subject = patches[index]
+                .header
+                .as_ref()
+                .and_then(|header| header.description.lines().next())
+                .filter(|subject| !subject.is_empty());
+            let number = index + 1;
+            let total = patches.len();
+            let message = if let Some(subject) = subject {
+                format!("Failed to apply patch {number}/{total} ({subject})")
+            } else {
+                format!("Failed to apply patch {number}/{total}")
+            };
+            user_error_with_message(message, err)
+        }
+        PatchApplyError::Backend(err) => err.into(),
+    }
+}
+
+fn print_conflicted_paths(
+    ui: &Ui,
+    workspace_command: &WorkspaceCommandHelper,
+    paths: &[RepoPathBuf],
+) -> io::Result<()> {
+    if paths.is_empty() {
+        return Ok(());
+    }
+    writeln!(
+        ui.warning_default(),
+        "Some hunks did not apply cleanly and were recorded as conflicts in:"
+    )?;
+    for path in paths {
+        writeln!(
+            ui.warning_no_heading(),
+            "  {}",
+            workspace_command.format_file_path(path)
+        )?;
+    }
+    Ok(())
+}
+
+fn read_patches(path: &Path) -> Result<Vec<Patch>, CommandError> {
+    let input = if path == Path::new("-") {
+        let mut buffer = vec![];
+        io::stdin().read_to_end(&mut buffer)?;
+        buffer
+    } else {
+        std::fs::read(path).map_err(|err| {
+            user_error_with_message(format!("Failed to read {}", path.display()), err)
+        })?
+    };
+    parse_patches(&input)
+        .map_err(|err| user_error_with_message(format!("Failed to parse {}", path.display()), err))
+}
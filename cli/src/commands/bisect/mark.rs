@@ -0,0 +1,91 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use itertools::Itertools as _;
+use jj_lib::backend::CommitId;
+use jj_lib::op_store::BisectMark;
+use tracing::instrument;
+
+use super::mark_and_check_out_next;
+use crate::cli_util::CommandHelper;
+use crate::cli_util::RevisionArg;
+use crate::command_error::CommandError;
+use crate::ui::Ui;
+
+/// Mark revisions and check out the next revision to evaluate
+///
+/// `jj bisect good` marks revisions that don't have the property being
+/// searched for, `jj bisect bad` marks revisions that have it, and `jj bisect
+/// skip` marks revisions that can't be evaluated.
+#[derive(clap::Args, Clone, Debug)]
+pub(crate) struct BisectMarkArgs {
+    /// The revisions to mark
+    ///
+    /// Defaults to the parent of the working-copy commit, which is the
+    /// revision checked out by the last bisection step.
+    #[arg(default_value = "@-", value_name = "REVSETS")]
+    revisions: Vec<RevisionArg>,
+}
+
+#[instrument(skip_all)]
+pub(crate) fn cmd_bisect_good(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &BisectMarkArgs,
+) -> Result<(), CommandError> {
+    mark_revisions(ui, command, args, BisectMark::Good)
+}
+
+#[instrument(skip_all)]
+pub(crate) fn cmd_bisect_bad(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &BisectMarkArgs,
+) -> Result<(), CommandError> {
+    mark_revisions(ui, command, args, BisectMark::Bad)
+}
+
+#[instrument(skip_all)]
+pub(crate) fn cmd_bisect_skip(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &BisectMarkArgs,
+) -> Result<(), CommandError> {
+    mark_revisions(ui, command, args, BisectMark::Skipped)
+}
+
+fn mark_revisions(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &BisectMarkArgs,
+    mark: BisectMark,
+) -> Result<(), CommandError> {
+    let workspace_command = command.workspace_helper(ui)?;
+    let commit_ids: Vec<CommitId> = workspace_command
+        .parse_union_revsets(ui, &args.revisions)?
+        .evaluate_to_commit_ids()?
+        .try_collect()?;
+    let marks = commit_ids.into_iter().map(|id| (id, mark)).collect_vec();
+    let description = format!("mark {} revisions as {}", marks.len(), mark_name(mark));
+    mark_and_check_out_next(ui, workspace_command, &marks, false, &description)?;
+    Ok(())
+}
+
+pub(super) fn mark_name(mark: BisectMark) -> &'static str {
+    match mark {
+        BisectMark::Good => "good",
+        BisectMark::Bad => "bad",
+        BisectMark::Skipped => "skipped",
+    }
+}
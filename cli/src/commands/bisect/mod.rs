@@ -0,0 +1,160 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+mod mark;
+mod reset;
+mod start;
+
+use std::io::Write as _;
+
+use clap::Subcommand;
+use itertools::Itertools as _;
+use jj_lib::backend::CommitId;
+use jj_lib::bisect::BisectError;
+use jj_lib::bisect::BisectionStep;
+use jj_lib::bisect::Bisector;
+use jj_lib::op_store::BisectMark;
+use jj_lib::repo::Repo as _;
+use tracing::instrument;
+
+use self::mark::cmd_bisect_bad;
+use self::mark::cmd_bisect_good;
+use self::mark::cmd_bisect_skip;
+use self::mark::BisectMarkArgs;
+use self::reset::cmd_bisect_reset;
+use self::reset::BisectResetArgs;
+use self::start::cmd_bisect_start;
+use self::start::BisectStartArgs;
+use crate::cli_util::CommandHelper;
+use crate::cli_util::WorkspaceCommandHelper;
+use crate::command_error::user_error_with_hint;
+use crate::command_error::CommandError;
+use crate::ui::Ui;
+
+/// Find the first revision that introduced a bug or other property
+///
+/// Bisection narrows down a range of revisions by repeatedly checking out the
+/// revision in the middle of the range, and letting you mark it as good or
+/// bad. Start with `jj bisect start --good <REVSETS> --bad <REVSETS>`.
+///
+/// Each step creates a new working-copy commit on top of the revision to
+/// evaluate, and the revisions marked so far are recorded in the operation
+/// log, so `jj undo` reverts the last step.
+#[derive(Subcommand, Clone, Debug)]
+pub(crate) enum BisectCommand {
+    Bad(BisectMarkArgs),
+    Good(BisectMarkArgs),
+    Reset(BisectResetArgs),
+    Skip(BisectMarkArgs),
+    Start(BisectStartArgs),
+}
+
+#[instrument(skip_all)]
+pub(crate) fn cmd_bisect(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    subcommand: &BisectCommand,
+) -> Result<(), CommandError> {
+    match subcommand {
+        BisectCommand::Bad(args) => cmd_bisect_bad(ui, command, args),
+        BisectCommand::Good(args) => cmd_bisect_good(ui, command, args),
+        BisectCommand::Reset(args) => cmd_bisect_reset(ui, command, args),
+        BisectCommand::Skip(args) => cmd_bisect_skip(ui, command, args),
+        BisectCommand::Start(args) => cmd_bisect_start(ui, command, args),
+    }
+}
+
+/// Records the marks, then checks out the next revision to evaluate.
+///
+/// If `clear` is true, the marks of the previous bisection are discarded
+/// first. Returns the next step.
+fn mark_and_check_out_next(
+    ui: &mut Ui,
+    mut workspace_command: WorkspaceCommandHelper,
+    marks: &[(CommitId, BisectMark)],
+    clear: bool,
+    description: &str,
+) -> Result<BisectionStep, CommandError> {
+    let mut tx = workspace_command.start_transaction();
+    if clear {
+        let old_ids = tx
+            .repo()
+            .view()
+            .bisect_marks()
+            .keys()
+            .cloned()
+            .collect_vec();
+        for commit_id in &old_ids {
+            tx.repo_mut().set_bisect_mark(commit_id, None);
+        }
+    }
+    for (commit_id, mark) in marks {
+        tx.repo_mut().set_bisect_mark(commit_id, Some(*mark));
+    }
+    let step = Bisector::from_marks(tx.repo().view().bisect_marks())
+        .next_step(tx.repo())
+        .map_err(|err| match err {
+            BisectError::NoBadCommit => user_error_with_hint(
+                err,
+                "Mark a revision as bad with `jj bisect bad` or `jj bisect start --bad`.",
+            ),
+            BisectError::InconsistentMarks => user_error_with_hint(
+                err,
+                "Check the marks, or start over with `jj bisect reset`.",
+            ),
+            BisectError::RevsetEvaluation(err) => err.into(),
+        })?;
+    match &step {
+        BisectionStep::Evaluate {
+            commit_id,
+            remaining,
+        } => {
+            let commit = tx.repo().store().get_commit(commit_id)?;
+            if let Some(mut formatter) = ui.status_formatter() {
+                write!(formatter, "Now evaluating: ")?;
+                tx.write_commit_summary(formatter.as_mut(), &commit)?;
+                writeln!(formatter)?;
+                let steps = usize::BITS - remaining.leading_zeros();
+                writeln!(
+                    formatter,
+                    "{remaining} revisions left to evaluate (roughly {steps} steps)"
+                )?;
+            }
+            tx.check_out(&commit)?;
+        }
+        BisectionStep::Done(first_bad_ids) => {
+            if let Some(mut formatter) = ui.status_formatter() {
+                if let [first_bad_id] = &first_bad_ids[..] {
+                    let commit = tx.repo().store().get_commit(first_bad_id)?;
+                    write!(formatter, "The first bad revision is: ")?;
+                    tx.write_commit_summary(formatter.as_mut(), &commit)?;
+                    writeln!(formatter)?;
+                } else {
+                    writeln!(
+                        formatter,
+                        "Some revisions were skipped. The first bad revision could be any of:"
+                    )?;
+                    for commit_id in first_bad_ids {
+                        let commit = tx.repo().store().get_commit(commit_id)?;
+                        write!(formatter, "  ")?;
+                        tx.write_commit_summary(formatter.as_mut(), &commit)?;
+                        writeln!(formatter)?;
+                    }
+                }
+            }
+        }
+    }
+    tx.finish(ui, format!("bisect: {description}"))?;
+    Ok(step)
+}
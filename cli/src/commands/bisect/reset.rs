@@ -0,0 +1,54 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io::Write as _;
+
+use itertools::Itertools as _;
+use tracing::instrument;
+
+use crate::cli_util::CommandHelper;
+use crate::command_error::CommandError;
+use crate::ui::Ui;
+
+/// Forget the revisions marked by the current bisection
+///
+/// The working-copy commit is left unchanged.
+#[derive(clap::Args, Clone, Debug)]
+pub(crate) struct BisectResetArgs {}
+
+#[instrument(skip_all)]
+pub(crate) fn cmd_bisect_reset(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    _args: &BisectResetArgs,
+) -> Result<(), CommandError> {
+    let mut workspace_command = command.workspace_helper(ui)?;
+    let marked_ids = workspace_command
+        .repo()
+        .view()
+        .bisect_marks()
+        .keys()
+        .cloned()
+        .collect_vec();
+    if marked_ids.is_empty() {
+        writeln!(ui.status(), "No bisection in progress")?;
+        return Ok(());
+    }
+    let mut tx = workspace_command.start_transaction();
+    for commit_id in &marked_ids {
+        tx.repo_mut().set_bisect_mark(commit_id, None);
+    }
+    writeln!(ui.status(), "Forgot {} marked revisions", marked_ids.len())?;
+    tx.finish(ui, "bisect: reset")
+}
@@ -0,0 +1,146 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io::Write as _;
+
+use clap_complete::ArgValueCandidates;
+use itertools::Itertools as _;
+use jj_lib::backend::CommitId;
+use jj_lib::bisect::BisectionStep;
+use jj_lib::object_id::ObjectId as _;
+use jj_lib::op_store::BisectMark;
+use jj_lib::repo::Repo as _;
+use tracing::instrument;
+
+use super::mark::mark_name;
+use super::mark_and_check_out_next;
+use crate::cli_util::CommandHelper;
+use crate::cli_util::RevisionArg;
+use crate::command_error::user_error;
+use crate::command_error::user_error_with_hint;
+use crate::command_error::user_error_with_message;
+use crate::command_error::CommandError;
+use crate::commands::run::shell_command_in;
+use crate::complete;
+use crate::ui::Ui;
+
+/// Start a new bisection
+///
+/// Any revisions marked by a previous bisection are forgotten. The first
+/// revision to evaluate is checked out.
+///
+/// With `--command`, the bisection runs to completion without further input.
+/// The command is run in the workspace root after each revision is checked
+/// out. An exit code of 0 marks the revision as good, 125 marks it as skipped,
+/// and any other code between 1 and 127 marks it as bad. Any other exit status
+/// stops the bisection.
+#[derive(clap::Args, Clone, Debug)]
+pub(crate) struct BisectStartArgs {
+    /// Revisions known not to have the property being searched for
+    #[arg(
+        long,
+        short,
+        value_name = "REVSETS",
+        add = ArgValueCandidates::new(complete::all_revisions)
+    )]
+    good: Vec<RevisionArg>,
+    /// Revisions known to have the property being searched for
+    #[arg(
+        long,
+        short,
+        default_value = "@",
+        value_name = "REVSETS",
+        add = ArgValueCandidates::new(complete::all_revisions)
+    )]
+    bad: Vec<RevisionArg>,
+    /// Shell command to evaluate each revision with
+    #[arg(long)]
+    command: Option<String>,
+}
+
+#[instrument(skip_all)]
+pub(crate) fn cmd_bisect_start(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &BisectStartArgs,
+) -> Result<(), CommandError> {
+    let workspace_command = command.workspace_helper(ui)?;
+    let good_ids: Vec<CommitId> = if args.good.is_empty() {
+        vec![]
+    } else {
+        workspace_command
+            .parse_union_revsets(ui, &args.good)?
+            .evaluate_to_commit_ids()?
+            .try_collect()?
+    };
+    let bad_ids: Vec<CommitId> = workspace_command
+        .parse_union_revsets(ui, &args.bad)?
+        .evaluate_to_commit_ids()?
+        .try_collect()?;
+    let marks = itertools::chain(
+        good_ids.into_iter().map(|id| (id, BisectMark::Good)),
+        bad_ids.into_iter().map(|id| (id, BisectMark::Bad)),
+    )
+    .collect_vec();
+    let mut step = mark_and_check_out_next(ui, workspace_command, &marks, true, "start")?;
+
+    let Some(shell_command) = &args.command else {
+        return Ok(());
+    };
+    while let BisectionStep::Evaluate { commit_id, .. } = step {
+        let workspace_command = command.workspace_helper(ui)?;
+        let status = shell_command_in(workspace_command.workspace_root(), shell_command)
+            .status()
+            .map_err(|err| user_error_with_message("Failed to spawn command", err))?;
+        let mark = match status.code() {
+            Some(0) => BisectMark::Good,
+            Some(125) => BisectMark::Skipped,
+            Some(1..=127) => BisectMark::Bad,
+            _ => {
+                return Err(user_error(format!(
+                    "Stopping the bisection because the command exited with {status}"
+                )));
+            }
+        };
+        writeln!(
+            ui.status(),
+            "The revision is {} ({status})",
+            mark_name(mark)
+        )?;
+        // Snapshot whatever the command wrote, and stop rather than leave the
+        // changes behind in a working-copy commit on the evaluated revision.
+        let workspace_command = command.workspace_helper(ui)?;
+        if let Some(wc_commit_id) = workspace_command.get_wc_commit_id() {
+            let wc_commit = workspace_command.repo().store().get_commit(wc_commit_id)?;
+            if !wc_commit.is_empty(workspace_command.repo().as_ref())? {
+                return Err(user_error_with_hint(
+                    "Stopping the bisection because the command modified the working copy",
+                    format!(
+                        "Inspect or abandon the changes, then mark the revision as {}.",
+                        mark_name(mark)
+                    ),
+                ));
+            }
+        }
+        let description = format!("mark {} as {}", commit_id.hex(), mark_name(mark));
+        step = mark_and_check_out_next(
+            ui,
+            workspace_command,
+            &[(commit_id, mark)],
+            false,
+            &description,
+        )?;
+    }
+    Ok(())
+}
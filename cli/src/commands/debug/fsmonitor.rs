@@ -0,0 +1,126 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt::Debug;
+#[cfg(target_os = "linux")]
+use std::io::Write as _;
+
+use clap::Subcommand;
+#[cfg(target_os = "linux")]
+use jj_lib::fsmonitor::native;
+#[cfg(target_os = "linux")]
+use jj_lib::fsmonitor::FsmonitorSettings;
+
+#[cfg(target_os = "linux")]
+use super::check_local_disk_wc;
+use crate::cli_util::CommandHelper;
+#[cfg(not(target_os = "linux"))]
+use crate::command_error::user_error;
+#[cfg(target_os = "linux")]
+use crate::command_error::user_error_with_message;
+use crate::command_error::CommandError;
+use crate::ui::Ui;
+
+/// Control the built-in filesystem monitor (`core.fsmonitor = "native"`)
+#[derive(Subcommand, Clone, Debug)]
+pub enum DebugFsmonitorCommand {
+    /// Watch the working copy for changes in the foreground
+    ///
+    /// With `core.native-fsmonitor.start-daemon`, this is started in the
+    /// background by the first snapshot that finds it not running.
+    Run,
+    /// Stop the process watching the working copy, if any
+    Stop,
+    /// Check whether the built-in filesystem monitor is enabled and running
+    Status,
+    QueryChangedFiles,
+}
+
+#[cfg(target_os = "linux")]
+pub fn cmd_debug_fsmonitor(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    subcommand: &DebugFsmonitorCommand,
+) -> Result<(), CommandError> {
+    // Don't snapshot the working copy, which could start the monitor.
+    let workspace = command.load_workspace()?;
+    let workspace_root = workspace.workspace_root();
+    let wc = check_local_disk_wc(workspace.working_copy().as_any())?;
+    let fsmonitor = native::Fsmonitor::new(wc.state_path());
+    match subcommand {
+        DebugFsmonitorCommand::Run => {
+            let daemon = native::Daemon::new(workspace_root, wc.state_path())
+                .map_err(|err| user_error_with_message("Failed to start watching", err))?;
+            daemon
+                .run()
+                .map_err(|err| user_error_with_message("Failed to watch the working copy", err))?;
+        }
+        DebugFsmonitorCommand::Stop => {
+            fsmonitor
+                .stop()
+                .map_err(|err| user_error_with_message("Failed to stop watching", err))?;
+        }
+        DebugFsmonitorCommand::Status => {
+            match command.settings().fsmonitor_settings()? {
+                FsmonitorSettings::Native(config) => {
+                    writeln!(
+                        ui.stdout(),
+                        "The built-in filesystem monitor is enabled via `core.fsmonitor`."
+                    )?;
+                    writeln!(
+                        ui.stdout(),
+                        "Starting it automatically is {}. Use \
+                         `core.native-fsmonitor.start-daemon` to control it.",
+                        if config.start_daemon {
+                            "enabled"
+                        } else {
+                            "disabled"
+                        }
+                    )?;
+                }
+                _ => {
+                    writeln!(
+                        ui.stdout(),
+                        r#"The built-in filesystem monitor is disabled. Set `core.fsmonitor="native"` to enable."#
+                    )?;
+                }
+            }
+            let running = fsmonitor
+                .is_running()
+                .map_err(|err| user_error_with_message("Failed to query watcher status", err))?;
+            writeln!(
+                ui.stdout(),
+                "The working copy is currently {}.",
+                if running { "watched" } else { "not watched" }
+            )?;
+        }
+        DebugFsmonitorCommand::QueryChangedFiles => {
+            let (clock, changed_files) = wc.query_native_fsmonitor()?;
+            writeln!(ui.stdout(), "Clock: {clock:?}")?;
+            writeln!(ui.stdout(), "Changed files: {changed_files:?}")?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn cmd_debug_fsmonitor(
+    _ui: &mut Ui,
+    _command: &CommandHelper,
+    _subcommand: &DebugFsmonitorCommand,
+) -> Result<(), CommandError> {
+    Err(user_error(
+        "The built-in filesystem monitor is only supported on Linux",
+    ))
+}
@@ -25,7 +25,7 @@ use crate::cli_util::CommandHelper;
 use crate::cli_util::RevisionArg;
 use crate::command_error::CommandError;
 use crate::complete;
-use crate::diff_util::get_copy_records;
+use crate::diff_util::add_copy_records;
 use crate::diff_util::DiffFormatArgs;
 use crate::ui::Ui;
 
@@ -99,6 +99,7 @@ pub(crate) fn cmd_diff(
         workspace_command.resolve_single_rev(ui, r.as_ref().unwrap_or(&RevisionArg::AT))
     };
 
+    let copy_detection_options = workspace_command.env().copy_detection_options();
     let from_tree;
     let to_tree;
     let mut copy_records = CopyRecords::default();
@@ -108,8 +109,14 @@ pub(crate) fn cmd_diff(
         from_tree = from.tree()?;
         to_tree = to.tree()?;
 
-        let records = get_copy_records(repo.store(), from.id(), to.id(), &matcher)?;
-        copy_records.add_records(records)?;
+        add_copy_records(
+            &mut copy_records,
+            repo.store(),
+            from.id(),
+            to.id(),
+            &matcher,
+            copy_detection_options,
+        )?;
     } else {
         let to = resolve_revision(&args.revision)?;
         let parents: Vec<_> = to.parents().try_collect()?;
@@ -117,8 +124,14 @@ pub(crate) fn cmd_diff(
         to_tree = to.tree()?;
 
         for p in &parents {
-            let records = get_copy_records(repo.store(), p.id(), to.id(), &matcher)?;
-            copy_records.add_records(records)?;
+            add_copy_records(
+                &mut copy_records,
+                repo.store(),
+                p.id(),
+                to.id(),
+                &matcher,
+                copy_detection_options,
+            )?;
         }
     }
 
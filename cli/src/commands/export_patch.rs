@@ -0,0 +1,197 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io::Write as _;
+use std::path::PathBuf;
+
+use clap_complete::ArgValueCandidates;
+use itertools::Itertools as _;
+use jj_lib::commit::Commit;
+use jj_lib::matchers::EverythingMatcher;
+use jj_lib::object_id::ObjectId as _;
+use jj_lib::patch::encode_header_value;
+use jj_lib::repo::Repo as _;
+use tracing::instrument;
+
+use crate::cli_util::CommandHelper;
+use crate::cli_util::RevisionArg;
+use crate::cli_util::WorkspaceCommandHelper;
+use crate::command_error::user_error;
+use crate::command_error::user_error_with_message;
+use crate::command_error::CommandError;
+use crate::complete;
+use crate::diff_util::DiffFormat;
+use crate::diff_util::UnifiedDiffOptions;
+use crate::formatter::PlainTextFormatter;
+use crate::time_util::format_rfc2822_timestamp;
+use crate::ui::Ui;
+
+/// Export revisions as a series of patches
+///
+/// Each revision is formatted as an email message like `git format-patch`
+/// does. The author, date, and description are included in the message
+/// headers, and binary files are included as Git binary patches. The patches
+/// can be applied by `jj apply` or `git am`.
+#[derive(clap::Args, Clone, Debug)]
+pub(crate) struct ExportPatchArgs {
+    /// The revisions to export (default: @)
+    #[arg(
+        value_name = "REVSETS",
+        add = ArgValueCandidates::new(complete::all_revisions)
+    )]
+    revisions: Vec<RevisionArg>,
+    /// Write each patch to a numbered file in this directory instead of
+    /// printing the series to stdout
+    #[arg(long, short, value_name = "DIR", value_hint = clap::ValueHint::DirPath)]
+    output_directory: Option<PathBuf>,
+}
+
+#[instrument(skip_all)]
+pub(crate) fn cmd_export_patch(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &ExportPatchArgs,
+) -> Result<(), CommandError> {
+    let workspace_command = command.workspace_helper(ui)?;
+    let revisions = if args.revisions.is_empty() {
+        vec![RevisionArg::AT]
+    } else {
+        args.revisions.clone()
+    };
+    let mut commits: Vec<Commit> = workspace_command
+        .parse_union_revsets(ui, &revisions)?
+        .evaluate_to_commits()?
+        .try_collect()?;
+    commits.reverse(); // parents first
+    if commits.is_empty() {
+        writeln!(ui.status(), "No revisions to export.")?;
+        return Ok(());
+    }
+    let root_commit_id = workspace_command.repo().store().root_commit_id();
+    if commits.iter().any(|commit| commit.id() == root_commit_id) {
+        return Err(user_error("Cannot export the root commit"));
+    }
+
+    if let Some(dir) = &args.output_directory {
+        std::fs::create_dir_all(dir).map_err(|err| {
+            user_error_with_message(format!("Failed to create {}", dir.display()), err)
+        })?;
+    }
+    let version = command.app().get_version().unwrap_or_default().to_owned();
+    for (index, commit) in commits.iter().enumerate() {
+        let patch = format_patch(
+            ui,
+            &workspace_command,
+            commit,
+            index + 1,
+            commits.len(),
+            &version,
+        )?;
+        if let Some(dir) = &args.output_directory {
+            let path = dir.join(patch_file_name(index + 1, commit.description()));
+            std::fs::write(&path, patch).map_err(|err| {
+                user_error_with_message(format!("Failed to write {}", path.display()), err)
+            })?;
+            writeln!(ui.stdout(), "{}", path.display())?;
+        } else {
+            ui.stdout().write_all(&patch)?;
+        }
+    }
+    Ok(())
+}
+
+/// Formats `commit` as an email message containing Git diff.
+fn format_patch(
+    ui: &Ui,
+    workspace_command: &WorkspaceCommandHelper,
+    commit: &Commit,
+    number: usize,
+    total: usize,
+    version: &str,
+) -> Result<Vec<u8>, CommandError> {
+    let author = commit.author();
+    let date = format_rfc2822_timestamp(&author.timestamp)
+        .map_err(|err| user_error_with_message("Invalid author date", err))?;
+    // The whole first paragraph is the subject so the description can be
+    // restored by parsing the patch.
+    let (subject, body) = commit
+        .description()
+        .split_once("\n\n")
+        .unwrap_or((commit.description(), ""));
+    let subject = subject.trim();
+    let prefix = if total > 1 {
+        format!("[PATCH {number}/{total}]")
+    } else {
+        "[PATCH]".to_owned()
+    };
+
+    let mut output = Vec::new();
+    writeln!(
+        output,
+        "From {} Mon Sep 17 00:00:00 2001",
+        commit.id().hex()
+    )?;
+    writeln!(
+        output,
+        "From: {} <{}>",
+        encode_header_value(&author.name),
+        author.email
+    )?;
+    writeln!(output, "Date: {date}")?;
+    writeln!(output, "Subject: {prefix} {}", encode_header_value(subject))?;
+    writeln!(output, "MIME-Version: 1.0")?;
+    writeln!(output, "Content-Type: text/plain; charset=UTF-8")?;
+    writeln!(output, "Content-Transfer-Encoding: 8bit")?;
+    writeln!(output)?;
+    let body = body.trim();
+    if !body.is_empty() {
+        writeln!(output, "{body}")?;
+    }
+    writeln!(output, "---")?;
+
+    let mut options = UnifiedDiffOptions::from_settings(workspace_command.settings())?;
+    options.color_moved = false;
+    options.binary = true;
+    let diff_renderer = workspace_command.diff_renderer(vec![DiffFormat::Git(Box::new(options))]);
+    diff_renderer.show_patch(
+        ui,
+        &mut PlainTextFormatter::new(&mut output),
+        commit,
+        &EverythingMatcher,
+        ui.term_width(),
+    )?;
+    writeln!(output, "-- ")?;
+    writeln!(output, "jj {version}")?;
+    writeln!(output)?;
+    Ok(output)
+}
+
+/// Returns file name like "0001-subject-line.patch".
+fn patch_file_name(number: usize, description: &str) -> String {
+    let subject = description.lines().next().unwrap_or_default();
+    let slug = subject
+        .split(|c: char| !c.is_ascii_alphanumeric() && c != '_' && c != '.')
+        .filter(|word| !word.is_empty())
+        .join("-");
+    let mut slug = slug.trim_matches('.').to_owned();
+    if slug.len() > 52 {
+        slug.truncate(52);
+        slug = slug.trim_end_matches(['-', '.']).to_owned();
+    }
+    if slug.is_empty() {
+        format!("{number:04}.patch")
+    } else {
+        format!("{number:04}-{slug}.patch")
+    }
+}
@@ -15,6 +15,7 @@
 use clap_complete::ArgValueCandidates;
 use clap_complete::ArgValueCompleter;
 use jj_lib::annotate::get_annotation_for_file;
+use jj_lib::annotate::AnnotationOptions;
 use jj_lib::annotate::FileAnnotation;
 use jj_lib::repo::Repo;
 use jj_lib::revset::RevsetExpression;
@@ -50,6 +51,24 @@ pub(crate) struct FileAnnotateArgs {
         add = ArgValueCandidates::new(complete::all_revisions)
     )]
     revision: Option<RevisionArg>,
+    /// Follow the file across renames and copies detected by the backend
+    #[arg(long)]
+    follow_copies: bool,
+    /// Revisions whose changes should be ignored
+    ///
+    /// Lines modified by these revisions are attributed to the corresponding
+    /// lines in the parent revisions. This is useful for skipping mass
+    /// reformatting commits. Lines added by these revisions are still
+    /// attributed to them.
+    ///
+    /// If not specified, this defaults to the `revsets.annotate-ignore`
+    /// setting.
+    #[arg(
+        long,
+        value_name = "REVSET",
+        add = ArgValueCandidates::new(complete::all_revisions)
+    )]
+    ignore_revs: Option<RevisionArg>,
     /// Render each line using the given template
     ///
     /// All 0-argument methods of the [`AnnotationLine` type] are available as
@@ -103,12 +122,30 @@ pub(crate) fn cmd_file_annotate(
         CommitTemplateLanguage::wrap_annotation_line,
     )?;
 
+    let ignore_revs = match &args.ignore_revs {
+        Some(revs) => revs.clone(),
+        None => RevisionArg::from(
+            workspace_command
+                .settings()
+                .get_string("revsets.annotate-ignore")?,
+        ),
+    };
+    let options = AnnotationOptions {
+        follow_copies: args.follow_copies,
+        ignore_revs: workspace_command
+            .parse_revset(ui, &ignore_revs)?
+            .resolve()?,
+    };
+
     // TODO: Should we add an option to limit the domain to e.g. recent commits?
-    // Note that this is probably different from "--skip REVS", which won't
-    // exclude the revisions, but will ignore diffs in those revisions as if
-    // ancestor revisions had new content.
     let domain = RevsetExpression::all();
-    let annotation = get_annotation_for_file(repo.as_ref(), &starting_commit, &domain, &file_path)?;
+    let annotation = get_annotation_for_file(
+        repo.as_ref(),
+        &starting_commit,
+        &domain,
+        &file_path,
+        &options,
+    )?;
 
     render_file_annotation(repo.as_ref(), ui, &template, &annotation)?;
     Ok(())
@@ -72,7 +72,7 @@ pub enum GitCommand {
     Push(GitPushArgs),
     #[command(subcommand)]
     Remote(RemoteCommand),
-    #[command(subcommand, hide = true)]
+    #[command(subcommand)]
     Submodule(GitSubmoduleCommand),
 }
 
@@ -0,0 +1,162 @@
+// Copyright 2020-2023 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+mod print_gitmodules;
+mod status;
+mod sync;
+mod update;
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use clap::Subcommand;
+use jj_lib::backend::CommitId;
+use jj_lib::backend::TreeValue;
+use jj_lib::git;
+use jj_lib::git::SubmoduleConfig;
+use jj_lib::merged_tree::MergedTree;
+use jj_lib::repo::Repo;
+use jj_lib::repo_path::RepoPath;
+use jj_lib::repo_path::RepoPathBuf;
+
+use self::print_gitmodules::cmd_git_submodule_print_gitmodules;
+use self::print_gitmodules::GitSubmodulePrintGitmodulesArgs;
+use self::status::cmd_git_submodule_status;
+use self::status::GitSubmoduleStatusArgs;
+use self::sync::cmd_git_submodule_sync;
+use self::sync::GitSubmoduleSyncArgs;
+use self::update::cmd_git_submodule_update;
+use self::update::GitSubmoduleUpdateArgs;
+use crate::cli_util::CommandHelper;
+use crate::cli_util::WorkspaceCommandHelper;
+use crate::command_error::user_error;
+use crate::command_error::CommandError;
+use crate::ui::Ui;
+
+/// Manage Git submodules
+///
+/// Submodules are read from the `.gitmodules` file in the working-copy
+/// commit. Once a submodule is initialized by `jj git submodule update`, the
+/// working copy checks out the recorded commit in it, and the commit checked
+/// out in it is snapshotted like any other change.
+#[derive(Subcommand, Clone, Debug)]
+pub enum GitSubmoduleCommand {
+    PrintGitmodules(GitSubmodulePrintGitmodulesArgs),
+    Status(GitSubmoduleStatusArgs),
+    Sync(GitSubmoduleSyncArgs),
+    Update(GitSubmoduleUpdateArgs),
+}
+
+pub fn cmd_git_submodule(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    subcommand: &GitSubmoduleCommand,
+) -> Result<(), CommandError> {
+    match subcommand {
+        GitSubmoduleCommand::PrintGitmodules(args) => {
+            cmd_git_submodule_print_gitmodules(ui, command, args)
+        }
+        GitSubmoduleCommand::Status(args) => cmd_git_submodule_status(ui, command, args),
+        GitSubmoduleCommand::Sync(args) => cmd_git_submodule_sync(ui, command, args),
+        GitSubmoduleCommand::Update(args) => cmd_git_submodule_update(ui, command, args),
+    }
+}
+
+/// Submodule listed in `.gitmodules` of the working-copy commit.
+struct Submodule {
+    config: SubmoduleConfig,
+    path: RepoPathBuf,
+    disk_path: PathBuf,
+    /// Commit recorded in the working-copy commit, or `None` if there's no
+    /// submodule at the path.
+    commit_id: Option<CommitId>,
+}
+
+/// Parses `.gitmodules` in the `tree`. Returns `None` if there's no such
+/// file.
+fn read_gitmodules(
+    repo: &dyn Repo,
+    tree: &MergedTree,
+) -> Result<Option<BTreeMap<String, SubmoduleConfig>>, CommandError> {
+    let gitmodules_path = RepoPath::from_internal_string(".gitmodules");
+    let mut gitmodules_file = match tree.path_value(gitmodules_path)?.into_resolved() {
+        Ok(None) => return Ok(None),
+        Ok(Some(TreeValue::File { id, .. })) => repo.store().read_file(gitmodules_path, &id)?,
+        _ => {
+            return Err(user_error(".gitmodules is not a file."));
+        }
+    };
+    Ok(Some(git::parse_gitmodules(&mut gitmodules_file)?))
+}
+
+/// Finds submodules in the working-copy commit whose paths match the given
+/// filesets.
+fn find_submodules(
+    ui: &Ui,
+    workspace_command: &WorkspaceCommandHelper,
+    paths: &[String],
+) -> Result<Vec<Submodule>, CommandError> {
+    let repo = workspace_command.repo();
+    let wc_commit_id = workspace_command
+        .get_wc_commit_id()
+        .ok_or_else(|| user_error("This command requires a working copy"))?;
+    let tree = repo.store().get_commit(wc_commit_id)?.tree()?;
+    let matcher = workspace_command
+        .parse_file_patterns(ui, paths)?
+        .to_matcher();
+    let Some(configs) = read_gitmodules(repo.as_ref(), &tree)? else {
+        return Ok(vec![]);
+    };
+    let mut submodules = vec![];
+    for config in configs.into_values() {
+        if !git::is_valid_submodule_name(&config.name) {
+            writeln!(
+                ui.warning_default(),
+                "Ignoring submodule with invalid name: {}",
+                config.name
+            )?;
+            continue;
+        }
+        let Some((path, disk_path)) =
+            RepoPathBuf::from_relative_path(&config.path)
+                .ok()
+                .and_then(|path| {
+                    let disk_path = path.to_fs_path(workspace_command.workspace_root()).ok()?;
+                    Some((path, disk_path))
+                })
+        else {
+            writeln!(
+                ui.warning_default(),
+                "Ignoring submodule {} with invalid path: {}",
+                config.name,
+                config.path
+            )?;
+            continue;
+        };
+        if !matcher.matches(&path) {
+            continue;
+        }
+        let commit_id = match tree.path_value(&path)?.into_resolved() {
+            Ok(Some(TreeValue::GitSubmodule(id))) => Some(id),
+            _ => None,
+        };
+        submodules.push(Submodule {
+            config,
+            path,
+            disk_path,
+            commit_id,
+        });
+    }
+    Ok(submodules)
+}
@@ -0,0 +1,64 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io::Write as _;
+
+use jj_lib::git;
+
+use super::find_submodules;
+use crate::cli_util::short_commit_hash;
+use crate::cli_util::CommandHelper;
+use crate::command_error::CommandError;
+use crate::ui::Ui;
+
+/// Show the state of submodules
+///
+/// Each line starts with the commit recorded in the working-copy commit,
+/// prefixed with `-` if the submodule isn't initialized, or `+` if another
+/// commit is checked out in the submodule.
+#[derive(clap::Args, Clone, Debug)]
+pub struct GitSubmoduleStatusArgs {
+    /// Only show submodules matching these paths
+    #[arg(value_name = "FILESETS", value_hint = clap::ValueHint::AnyPath)]
+    paths: Vec<String>,
+}
+
+pub fn cmd_git_submodule_status(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &GitSubmoduleStatusArgs,
+) -> Result<(), CommandError> {
+    let workspace_command = command.workspace_helper(ui)?;
+    let submodules = find_submodules(ui, &workspace_command, &args.paths)?;
+    ui.request_pager();
+    let mut formatter = ui.stdout_formatter();
+    for submodule in &submodules {
+        let ui_path = workspace_command.format_file_path(&submodule.path);
+        let Some(commit_id) = &submodule.commit_id else {
+            writeln!(formatter, " {:12} {ui_path} (not in the working copy)", "")?;
+            continue;
+        };
+        let hash = short_commit_hash(commit_id);
+        match git::read_submodule_head(&submodule.disk_path) {
+            None => writeln!(formatter, "-{hash} {ui_path}")?,
+            Some(head_id) if head_id == *commit_id => writeln!(formatter, " {hash} {ui_path}")?,
+            Some(head_id) => writeln!(
+                formatter,
+                "+{hash} {ui_path} (checked out {})",
+                short_commit_hash(&head_id)
+            )?,
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,51 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io::Write as _;
+
+use jj_lib::git;
+
+use super::find_submodules;
+use crate::cli_util::CommandHelper;
+use crate::command_error::CommandError;
+use crate::ui::Ui;
+
+/// Update the remote URLs of submodules from `.gitmodules`
+///
+/// Only initialized submodules are updated.
+#[derive(clap::Args, Clone, Debug)]
+pub struct GitSubmoduleSyncArgs {
+    /// Only synchronize submodules matching these paths
+    #[arg(value_name = "FILESETS", value_hint = clap::ValueHint::AnyPath)]
+    paths: Vec<String>,
+}
+
+pub fn cmd_git_submodule_sync(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &GitSubmoduleSyncArgs,
+) -> Result<(), CommandError> {
+    let workspace_command = command.workspace_helper(ui)?;
+    let submodules = find_submodules(ui, &workspace_command, &args.paths)?;
+    for submodule in &submodules {
+        if git::sync_submodule_url(&submodule.disk_path, &submodule.config.url)? {
+            writeln!(
+                ui.status(),
+                "Synchronized URL of submodule {}",
+                workspace_command.format_file_path(&submodule.path)
+            )?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,114 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fs;
+use std::io::Write as _;
+
+use jj_lib::git;
+use jj_lib::git::GitSubmoduleError;
+use jj_lib::repo::Repo as _;
+
+use super::find_submodules;
+use crate::cli_util::short_commit_hash;
+use crate::cli_util::CommandHelper;
+use crate::command_error::user_error_with_hint;
+use crate::command_error::user_error_with_message;
+use crate::command_error::CommandError;
+use crate::git_util::with_remote_git_callbacks;
+use crate::ui::Ui;
+
+/// Check out the recorded commits in submodules
+///
+/// Submodules that aren't initialized yet are initialized with the URLs in
+/// `.gitmodules`. The recorded commits are fetched if they're missing in the
+/// submodules. Submodules with local changes that would be overwritten are
+/// skipped.
+#[derive(clap::Args, Clone, Debug)]
+pub struct GitSubmoduleUpdateArgs {
+    /// Only update submodules matching these paths
+    #[arg(value_name = "FILESETS", value_hint = clap::ValueHint::AnyPath)]
+    paths: Vec<String>,
+}
+
+pub fn cmd_git_submodule_update(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &GitSubmoduleUpdateArgs,
+) -> Result<(), CommandError> {
+    let workspace_command = command.workspace_helper(ui)?;
+    let submodule_store = workspace_command.repo().submodule_store().clone();
+    let submodules = find_submodules(ui, &workspace_command, &args.paths)?;
+    let mut num_updated = 0;
+    for submodule in &submodules {
+        let ui_path = workspace_command.format_file_path(&submodule.path);
+        let Some(commit_id) = &submodule.commit_id else {
+            writeln!(
+                ui.warning_default(),
+                "Submodule {ui_path} is not in the working copy"
+            )?;
+            continue;
+        };
+        let old_head_id = git::read_submodule_head(&submodule.disk_path);
+        if old_head_id.is_none() {
+            let url = &submodule.config.url;
+            if url.starts_with("./") || url.starts_with("../") {
+                return Err(user_error_with_hint(
+                    format!("Submodule {ui_path} has relative URL: {url}"),
+                    "Relative submodule URLs are not supported yet.",
+                ));
+            }
+            fs::create_dir_all(&submodule.disk_path)?;
+            let git_dir = submodule_store
+                .git_dir(&submodule.config.name)
+                .unwrap_or_else(|| submodule.disk_path.join(".git"));
+            git::init_submodule(&submodule.disk_path, &git_dir, url)?;
+        }
+        let result = match git::check_out_submodule(&submodule.disk_path, commit_id) {
+            Err(GitSubmoduleError::CommitNotFound(_)) => {
+                with_remote_git_callbacks(ui, |callbacks| {
+                    git::fetch_submodule(&submodule.disk_path, callbacks)
+                })?;
+                git::check_out_submodule(&submodule.disk_path, commit_id)
+            }
+            result => result,
+        };
+        match result {
+            Ok(_) if old_head_id.as_ref() == Some(commit_id) => {}
+            Ok(_) => {
+                writeln!(
+                    ui.status(),
+                    "Checked out {} in submodule {ui_path}",
+                    short_commit_hash(commit_id)
+                )?;
+                num_updated += 1;
+            }
+            Err(GitSubmoduleError::LocalChanges) => {
+                writeln!(
+                    ui.warning_default(),
+                    "Skipped submodule {ui_path} because it has local changes"
+                )?;
+            }
+            Err(err) => {
+                return Err(user_error_with_message(
+                    format!("Failed to update submodule {ui_path}"),
+                    err,
+                ));
+            }
+        }
+    }
+    if num_updated == 0 {
+        writeln!(ui.status(), "Nothing changed.")?;
+    }
+    Ok(())
+}
@@ -12,9 +12,12 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::ops::Range;
+
 use clap_complete::ArgValueCandidates;
 use clap_complete::ArgValueCompleter;
 use itertools::Itertools as _;
+use jj_lib::annotate::get_line_range_history;
 use jj_lib::backend::CommitId;
 use jj_lib::config::ConfigGetError;
 use jj_lib::config::ConfigGetResultExt as _;
@@ -34,10 +37,15 @@ use crate::cli_util::format_template;
 use crate::cli_util::CommandHelper;
 use crate::cli_util::LogContentFormat;
 use crate::cli_util::RevisionArg;
+use crate::cli_util::WorkspaceCommandHelper;
+use crate::command_error::user_error;
+use crate::command_error::user_error_with_hint;
 use crate::command_error::CommandError;
 use crate::commit_templater::CommitTemplateLanguage;
 use crate::complete;
+use crate::diff_util::show_line_range_diff;
 use crate::diff_util::DiffFormatArgs;
+use crate::diff_util::UnifiedDiffOptions;
 use crate::graphlog::get_graphlog;
 use crate::graphlog::GraphStyle;
 use crate::ui::Ui;
@@ -116,6 +124,37 @@ pub(crate) struct LogArgs {
     patch: bool,
     #[command(flatten)]
     diff_format: DiffFormatArgs,
+    /// Show revisions modifying the given range of lines in a file
+    ///
+    /// The range is specified as `<PATH>:<START>,<END>` or
+    /// `<PATH>:<START>,+<COUNT>`, where `<START>` and `<END>` are 1-based
+    /// inclusive line numbers. The lines are followed back through history
+    /// from the revision specified by `-r` (defaults to `@`), and the changes
+    /// to them are shown after each revision. Implies `--no-graph`. Can't be
+    /// combined with the diff formatting options.
+    #[arg(
+        long,
+        short = 'L',
+        value_name = "PATH:RANGE",
+        conflicts_with_all = [
+            "paths",
+            "patch",
+            "summary",
+            "stat",
+            "types",
+            "name_only",
+            "git",
+            "color_words",
+            "side_by_side",
+            "tool",
+            "context",
+            "color_moved",
+            "ignore_all_space",
+            "ignore_space_change",
+        ],
+        add = ArgValueCompleter::new(complete::all_revision_files),
+    )]
+    line_range: Option<String>,
 }
 
 #[instrument(skip_all)]
@@ -126,6 +165,9 @@ pub(crate) fn cmd_log(
 ) -> Result<(), CommandError> {
     let workspace_command = command.workspace_helper(ui)?;
     let settings = workspace_command.settings();
+    if let Some(line_range) = &args.line_range {
+        return cmd_log_line_range(ui, &workspace_command, args, line_range);
+    }
 
     let fileset_expression = workspace_command.parse_file_patterns(ui, &args.paths)?;
     let revset_expression = {
@@ -340,6 +382,104 @@ pub(crate) fn cmd_log(
     Ok(())
 }
 
+fn cmd_log_line_range(
+    ui: &mut Ui,
+    workspace_command: &WorkspaceCommandHelper,
+    args: &LogArgs,
+    line_range_arg: &str,
+) -> Result<(), CommandError> {
+    let settings = workspace_command.settings();
+    let repo = workspace_command.repo();
+    let (path, line_range) = parse_line_range_arg(line_range_arg)?;
+    let file_path = workspace_command.parse_file_path(path)?;
+    let starting_commit = match args.revisions.as_slice() {
+        [] => workspace_command.resolve_single_rev(ui, &RevisionArg::AT)?,
+        [revision] => workspace_command.resolve_single_rev(ui, revision)?,
+        _ => {
+            return Err(user_error(
+                "Only one starting revision can be specified with --line-range",
+            ))
+        }
+    };
+    let file_value = starting_commit.tree()?.path_value(&file_path)?;
+    let ui_path = workspace_command.format_file_path(&file_path);
+    if file_value.is_absent() {
+        return Err(user_error(format!("No such path: {ui_path}")));
+    }
+    if file_value.is_tree() {
+        return Err(user_error(format!(
+            "Path exists but is not a regular file: {ui_path}"
+        )));
+    }
+
+    let template = {
+        let language = workspace_command.commit_template_language();
+        let template_string = match &args.template {
+            Some(value) => value.clone(),
+            None => settings.get_string("templates.log")?,
+        };
+        workspace_command
+            .parse_template(
+                ui,
+                &language,
+                &template_string,
+                CommitTemplateLanguage::wrap_commit,
+            )?
+            .labeled("log")
+    };
+    let with_content_format = LogContentFormat::new(ui, settings)?;
+    let diff_options = UnifiedDiffOptions::from_settings(settings)?;
+
+    let domain = RevsetExpression::all();
+    let mut changes = get_line_range_history(
+        repo.as_ref(),
+        &starting_commit,
+        &domain,
+        &file_path,
+        line_range,
+    )?;
+    changes.truncate(args.limit.unwrap_or(usize::MAX));
+    if args.reversed {
+        changes.reverse();
+    }
+
+    ui.request_pager();
+    let mut formatter = ui.stdout_formatter();
+    let formatter = formatter.as_mut();
+    for change in &changes {
+        let commit = repo.store().get_commit(&change.commit_id)?;
+        with_content_format.write(formatter, |formatter| template.format(&commit, formatter))?;
+        formatter.with_label("diff", |formatter| {
+            show_line_range_diff(formatter, &file_path, change, &diff_options)
+        })?;
+    }
+    Ok(())
+}
+
+/// Parses `<PATH>:<START>,<END>` or `<PATH>:<START>,+<COUNT>` into the path and
+/// 0-based line range.
+fn parse_line_range_arg(value: &str) -> Result<(&str, Range<usize>), CommandError> {
+    let invalid_range = || {
+        user_error_with_hint(
+            format!("Invalid line range: {value}"),
+            "The line range should be specified as <PATH>:<START>,<END> or <PATH>:<START>,+<COUNT>",
+        )
+    };
+    let (path, range) = value.rsplit_once(':').ok_or_else(invalid_range)?;
+    let (start, end) = range.split_once(',').ok_or_else(invalid_range)?;
+    let start: usize = start.parse().map_err(|_| invalid_range())?;
+    let end: usize = if let Some(count) = end.strip_prefix('+') {
+        let count: usize = count.parse().map_err(|_| invalid_range())?;
+        start.saturating_add(count).saturating_sub(1)
+    } else {
+        end.parse().map_err(|_| invalid_range())?
+    };
+    if path.is_empty() || start == 0 || end < start {
+        return Err(invalid_range());
+    }
+    Ok((path, start - 1..end))
+}
+
 pub fn get_node_template(
     style: GraphStyle,
     settings: &UserSettings,
@@ -14,9 +14,11 @@
 
 mod abandon;
 mod absorb;
+mod apply;
 mod backout;
 #[cfg(feature = "bench")]
 mod bench;
+mod bisect;
 mod bookmark;
 mod commit;
 mod config;
@@ -27,6 +29,7 @@ mod diffedit;
 mod duplicate;
 mod edit;
 mod evolog;
+mod export_patch;
 mod file;
 mod fix;
 #[cfg(feature = "git")]
@@ -79,11 +82,14 @@ use crate::ui::Ui;
 enum Command {
     Abandon(abandon::AbandonArgs),
     Absorb(absorb::AbsorbArgs),
+    Apply(apply::ApplyArgs),
     Backout(backout::BackoutArgs),
     #[cfg(feature = "bench")]
     #[command(subcommand)]
     Bench(bench::BenchCommand),
     #[command(subcommand)]
+    Bisect(bisect::BisectCommand),
+    #[command(subcommand)]
     Bookmark(bookmark::BookmarkCommand),
     // TODO: Remove in jj 0.28+
     #[command(subcommand, hide = true)]
@@ -100,6 +106,7 @@ enum Command {
     Edit(edit::EditArgs),
     #[command(alias = "obslog", visible_alias = "evolution-log")]
     Evolog(evolog::EvologArgs),
+    ExportPatch(export_patch::ExportPatchArgs),
     #[command(subcommand)]
     File(file::FileCommand),
     Fix(fix::FixArgs),
@@ -126,8 +133,6 @@ enum Command {
     )]
     Revert(DummyCommandArgs),
     Root(root::RootArgs),
-    #[command(hide = true)]
-    // TODO: Flesh out.
     Run(run::RunArgs),
     Show(show::ShowArgs),
     SimplifyParents(simplify_parents::SimplifyParentsArgs),
@@ -170,9 +175,11 @@ pub fn run_command(ui: &mut Ui, command_helper: &CommandHelper) -> Result<(), Co
     match &subcommand {
         Command::Abandon(args) => abandon::cmd_abandon(ui, command_helper, args),
         Command::Absorb(args) => absorb::cmd_absorb(ui, command_helper, args),
+        Command::Apply(args) => apply::cmd_apply(ui, command_helper, args),
         Command::Backout(args) => backout::cmd_backout(ui, command_helper, args),
         #[cfg(feature = "bench")]
         Command::Bench(args) => bench::cmd_bench(ui, command_helper, args),
+        Command::Bisect(args) => bisect::cmd_bisect(ui, command_helper, args),
         Command::Bookmark(args) => bookmark::cmd_bookmark(ui, command_helper, args),
         Command::Branch(args) => {
             let cmd = renamed_cmd("branch", "bookmark", bookmark::cmd_bookmark);
@@ -197,6 +204,7 @@ pub fn run_command(ui: &mut Ui, command_helper: &CommandHelper) -> Result<(), Co
         Command::New(args) => new::cmd_new(ui, command_helper, args),
         Command::Next(args) => next::cmd_next(ui, command_helper, args),
         Command::Evolog(args) => evolog::cmd_evolog(ui, command_helper, args),
+        Command::ExportPatch(args) => export_patch::cmd_export_patch(ui, command_helper, args),
         Command::Operation(args) => operation::cmd_operation(ui, command_helper, args),
         Command::Parallelize(args) => parallelize::cmd_parallelize(ui, command_helper, args),
         Command::Prev(args) => prev::cmd_prev(ui, command_helper, args),
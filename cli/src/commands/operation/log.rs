@@ -182,6 +182,7 @@ fn do_op_log(
                     repo.as_ref(),
                     path_converter,
                     conflict_marker_style,
+                    workspace_env.copy_detection_options().clone(),
                     diff_formats.clone(),
                 )
             });
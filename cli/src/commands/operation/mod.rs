@@ -100,9 +100,11 @@ fn view_with_desired_portions_restored(
         head_ids: repo_source.head_ids.clone(),
         local_bookmarks: repo_source.local_bookmarks.clone(),
         tags: repo_source.tags.clone(),
+        tag_annotations: repo_source.tag_annotations.clone(),
         remote_views: remote_source.remote_views.clone(),
         git_refs: current_view.git_refs.clone(),
         git_head: current_view.git_head.clone(),
         wc_commit_ids: repo_source.wc_commit_ids.clone(),
+        bisect_marks: repo_source.bisect_marks.clone(),
     }
 }
@@ -80,6 +80,7 @@ pub fn cmd_op_show(
                 repo.as_ref(),
                 path_converter,
                 conflict_marker_style,
+                workspace_env.copy_detection_options().clone(),
                 formats,
             )
         })
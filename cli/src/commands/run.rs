@@ -14,33 +14,67 @@
 
 //! This file contains the internal implementation of `run`.
 
+use std::collections::HashMap;
+use std::io::Write as _;
+use std::path::Path;
+use std::process::Command;
+use std::process::ExitStatus;
+use std::sync::Arc;
+
+use clap_complete::ArgValueCandidates;
 use itertools::Itertools as _;
+use jj_lib::backend::CommitId;
+use jj_lib::backend::MergedTreeId;
+use jj_lib::commit::Commit;
+use jj_lib::commit::CommitIteratorExt as _;
+use jj_lib::fsmonitor::FsmonitorSettings;
+use jj_lib::local_working_copy::TreeState;
+use jj_lib::repo::Repo as _;
+use jj_lib::store::Store;
+use jj_lib::working_copy::CheckoutOptions;
+use jj_lib::working_copy::SnapshotOptions;
+use rayon::iter::IntoParallelRefIterator as _;
+use rayon::iter::ParallelIterator as _;
+use tracing::instrument;
 
+use crate::cli_util::short_commit_hash;
 use crate::cli_util::CommandHelper;
 use crate::cli_util::RevisionArg;
+use crate::command_error::internal_error;
 use crate::command_error::user_error;
+use crate::command_error::user_error_with_message;
 use crate::command_error::CommandError;
+use crate::complete;
+use crate::merge_tools::new_utf8_temp_dir;
 use crate::ui::Ui;
 
-/// (**Stub**, does not work yet) Run a command across a set of revisions.
+/// Run a command across a set of revisions
 ///
+/// Each selected revision is checked out into its own temporary working copy,
+/// and the command is run there through the shell. Any changes the command
+/// makes to the files are amended into that revision, and descendants are
+/// rebased on top of the result. Revisions are processed in parallel.
 ///
-/// All recorded state will be persisted in the `.jj` directory, so occasionally
-/// a `jj run --clean` is needed to clean up disk space.
+/// The output of the command is printed for each revision. If the command
+/// fails for any revision, no revision is rewritten.
 ///
 /// # Example
 ///
 /// # Run pre-commit on your local work
 /// $ jj run 'pre-commit run .github/pre-commit.yaml' -r (trunk()..@) -j 4
-///
-/// This allows pre-commit integration and other funny stuff.
 #[derive(clap::Args, Clone, Debug)]
 #[command(verbatim_doc_comment)]
 pub struct RunArgs {
     /// The command to run across all selected revisions.
     shell_command: String,
     /// The revisions to change.
-    #[arg(long, short, default_value = "@", value_name = "REVSETS")]
+    #[arg(
+        long,
+        short,
+        default_value = "@",
+        value_name = "REVSETS",
+        add = ArgValueCandidates::new(complete::mutable_revisions)
+    )]
     revisions: Vec<RevisionArg>,
     /// A no-op option to match the interface of `git rebase -x`.
     #[arg(short = 'x', hide = true)]
@@ -50,21 +84,194 @@ pub struct RunArgs {
     jobs: Option<usize>,
 }
 
+#[instrument(skip_all)]
 pub fn cmd_run(ui: &mut Ui, command: &CommandHelper, args: &RunArgs) -> Result<(), CommandError> {
-    let workspace_command = command.workspace_helper(ui)?;
-    let _resolved_commits: Vec<_> = workspace_command
+    let mut workspace_command = command.workspace_helper(ui)?;
+    let resolved_commits: Vec<Commit> = workspace_command
         .parse_union_revsets(ui, &args.revisions)?
         .evaluate_to_commits()?
         .try_collect()?;
+    workspace_command.check_rewritable(resolved_commits.iter().ids())?;
     // Jobs are resolved in this order:
     // 1. Commandline argument iff > 0.
     // 2. the amount of cores available.
     // 3. a single job, if all of the above fails.
-    let _jobs = match args.jobs {
+    let jobs = match args.jobs {
         Some(0) | None => std::thread::available_parallelism().map(|t| t.into()).ok(),
         Some(jobs) => Some(jobs),
     }
     // Fallback to a single user-visible job.
     .unwrap_or(1usize);
-    Err(user_error("This is a stub, do not use"))
+
+    let auto_tracking_matcher = workspace_command.auto_tracking_matcher(ui)?;
+    let snapshot_options = SnapshotOptions {
+        // The temporary working copies are not watched by the fsmonitor.
+        fsmonitor_settings: FsmonitorSettings::None,
+        ..workspace_command
+            .snapshot_options_with_start_tracking_matcher(auto_tracking_matcher.as_ref())?
+    };
+    let checkout_options = workspace_command.checkout_options();
+    let store = workspace_command.repo().store().clone();
+
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(jobs)
+        .build()
+        .map_err(internal_error)?;
+    let outputs: Vec<RunOutput> = pool.install(|| {
+        resolved_commits
+            .par_iter()
+            .map(|commit| {
+                run_on_commit(
+                    &store,
+                    commit,
+                    &args.shell_command,
+                    &checkout_options,
+                    &snapshot_options,
+                )
+            })
+            .collect::<Result<_, _>>()
+    })?;
+
+    for output in &outputs {
+        if !output.status.success() {
+            writeln!(
+                ui.warning_default(),
+                "Command failed on commit {} with {}:",
+                short_commit_hash(&output.commit_id),
+                output.status
+            )?;
+            ui.stderr().write_all(&output.stdout)?;
+            ui.stderr().write_all(&output.stderr)?;
+        } else if !output.stdout.is_empty() || !output.stderr.is_empty() {
+            writeln!(
+                ui.status(),
+                "Output of the command on commit {}:",
+                short_commit_hash(&output.commit_id)
+            )?;
+            ui.stdout().write_all(&output.stdout)?;
+            ui.stderr().write_all(&output.stderr)?;
+        }
+    }
+    let num_failed = outputs
+        .iter()
+        .filter(|output| !output.status.success())
+        .count();
+    if num_failed > 0 {
+        return Err(user_error(format!(
+            "Command failed on {num_failed} of {} commits; no commits were rewritten",
+            outputs.len()
+        )));
+    }
+
+    let new_tree_ids: HashMap<CommitId, MergedTreeId> = outputs
+        .into_iter()
+        .map(|output| (output.commit_id, output.tree_id))
+        .collect();
+    let mut tx = workspace_command.start_transaction();
+    let mut num_changed_commits = 0;
+    let mut num_rebased_commits = 0;
+    tx.repo_mut().transform_descendants(
+        resolved_commits.iter().ids().cloned().collect(),
+        |rewriter| {
+            let old_commit = rewriter.old_commit();
+            if let Some(new_tree_id) = new_tree_ids.get(old_commit.id()) {
+                // The command saw the full contents of the commit, so its
+                // result is used as is instead of rebasing the commit onto its
+                // rewritten parents. This avoids conflicts when the command
+                // makes the same change in a commit and its ancestors, as
+                // formatters typically do.
+                if new_tree_id != old_commit.tree_id() {
+                    let new_tree_id = new_tree_id.clone();
+                    rewriter.reparent().set_tree_id(new_tree_id).write()?;
+                    num_changed_commits += 1;
+                } else if rewriter.parents_changed() {
+                    rewriter.reparent().write()?;
+                }
+            } else if rewriter.parents_changed() {
+                rewriter.rebase()?.write()?;
+                num_rebased_commits += 1;
+            }
+            Ok(())
+        },
+    )?;
+    if num_changed_commits > 0 {
+        writeln!(
+            ui.status(),
+            "Updated {num_changed_commits} commits and rebased {num_rebased_commits} descendant \
+             commits"
+        )?;
+    }
+    tx.finish(
+        ui,
+        format!(
+            "run command '{}' on {num_changed_commits} commits",
+            args.shell_command
+        ),
+    )
+}
+
+/// The result of running the command in the working copy of a commit.
+struct RunOutput {
+    commit_id: CommitId,
+    status: ExitStatus,
+    stdout: Vec<u8>,
+    stderr: Vec<u8>,
+    /// The tree snapshotted after the command finished.
+    tree_id: MergedTreeId,
+}
+
+/// Checks out the commit into a temporary directory, runs the shell command
+/// there, and snapshots the result.
+fn run_on_commit(
+    store: &Arc<Store>,
+    commit: &Commit,
+    shell_command: &str,
+    checkout_options: &CheckoutOptions,
+    snapshot_options: &SnapshotOptions,
+) -> Result<RunOutput, CommandError> {
+    let temp_dir = new_utf8_temp_dir("jj-run-")
+        .map_err(|err| user_error_with_message("Failed to create temporary working copy", err))?;
+    let wc_dir = temp_dir.path().join("wc");
+    let state_dir = temp_dir.path().join("state");
+    std::fs::create_dir(&wc_dir)?;
+    std::fs::create_dir(&state_dir)?;
+    let mut tree_state =
+        TreeState::init(store.clone(), wc_dir.clone(), state_dir).map_err(internal_error)?;
+    tree_state
+        .check_out(&commit.tree()?, checkout_options)
+        .map_err(internal_error)?;
+
+    tracing::debug!(commit_id = ?commit.id(), shell_command, "running command");
+    let output = shell_command_in(&wc_dir, shell_command)
+        .output()
+        .map_err(|err| user_error_with_message("Failed to spawn command", err))?;
+    let status = output.status;
+    let tree_id = if status.success() {
+        tree_state.snapshot(snapshot_options)?;
+        tree_state.current_tree_id().clone()
+    } else {
+        commit.tree_id().clone()
+    };
+    Ok(RunOutput {
+        commit_id: commit.id().clone(),
+        status,
+        stdout: output.stdout,
+        stderr: output.stderr,
+        tree_id,
+    })
+}
+
+/// Builds a command that runs `shell_command` through the platform's shell.
+pub(crate) fn shell_command_in(cwd: &Path, shell_command: &str) -> Command {
+    let mut command = if cfg!(windows) {
+        let mut command = Command::new("cmd");
+        command.arg("/C");
+        command
+    } else {
+        let mut command = Command::new("sh");
+        command.arg("-c");
+        command
+    };
+    command.arg(shell_command).current_dir(cwd);
+    command
 }
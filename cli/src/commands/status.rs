@@ -24,7 +24,7 @@ use tracing::instrument;
 use crate::cli_util::print_conflicted_paths;
 use crate::cli_util::CommandHelper;
 use crate::command_error::CommandError;
-use crate::diff_util::get_copy_records;
+use crate::diff_util::add_copy_records;
 use crate::diff_util::DiffFormat;
 use crate::ui::Ui;
 
@@ -78,8 +78,14 @@ pub(crate) fn cmd_status(
                 writeln!(formatter, "Working copy changes:")?;
                 let mut copy_records = CopyRecords::default();
                 for parent in wc_commit.parent_ids() {
-                    let records = get_copy_records(repo.store(), parent, wc_commit.id(), &matcher)?;
-                    copy_records.add_records(records)?;
+                    add_copy_records(
+                        &mut copy_records,
+                        repo.store(),
+                        parent,
+                        wc_commit.id(),
+                        &matcher,
+                        workspace_command.env().copy_detection_options(),
+                    )?;
                 }
                 let diff_renderer = workspace_command.diff_renderer(vec![DiffFormat::Summary]);
                 let width = ui.term_width();
@@ -0,0 +1,109 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use clap::builder::NonEmptyStringValueParser;
+use clap_complete::ArgValueCandidates;
+use jj_lib::object_id::ObjectId as _;
+use jj_lib::op_store::RefTarget;
+use jj_lib::repo::Repo as _;
+
+use super::new_tag_annotation;
+use crate::cli_util::CommandHelper;
+use crate::cli_util::RevisionArg;
+use crate::command_error::user_error_with_hint;
+use crate::command_error::CommandError;
+use crate::complete;
+use crate::description_util::join_message_paragraphs;
+use crate::ui::Ui;
+
+/// Create a new tag
+///
+/// Without `--message`, a lightweight tag is created. With `--message`, the
+/// tag is annotated with the message and the current user as tagger.
+#[derive(clap::Args, Clone, Debug)]
+pub struct TagCreateArgs {
+    /// The tag's target revision
+    #[arg(
+        long, short,
+        visible_alias = "to",
+        value_name = "REVSET",
+        add = ArgValueCandidates::new(complete::all_revisions),
+    )]
+    revision: RevisionArg,
+
+    /// Annotate the tag with the given message
+    ///
+    /// If multiple messages are given, they are joined as separate paragraphs.
+    #[arg(long = "message", short, value_name = "MESSAGE")]
+    message_paragraphs: Vec<String>,
+
+    /// Sign the annotated tag with the configured signing backend
+    #[arg(long, requires = "message_paragraphs")]
+    sign: bool,
+
+    /// The tags to create
+    #[arg(required = true, value_parser = NonEmptyStringValueParser::new())]
+    names: Vec<String>,
+}
+
+pub fn cmd_tag_create(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &TagCreateArgs,
+) -> Result<(), CommandError> {
+    let mut workspace_command = command.workspace_helper(ui)?;
+    let target_commit = workspace_command.resolve_single_rev(ui, &args.revision)?;
+    let view = workspace_command.repo().view();
+    let tag_names = &args.names;
+    for name in tag_names {
+        if view.get_tag(name).is_present() {
+            return Err(user_error_with_hint(
+                format!("Tag already exists: {name}"),
+                "Use `jj tag set` to update it.",
+            ));
+        }
+    }
+
+    let mut tx = workspace_command.start_transaction();
+    for name in tag_names {
+        tx.repo_mut()
+            .set_tag_target(name, RefTarget::normal(target_commit.id().clone()));
+        if !args.message_paragraphs.is_empty() {
+            let annotation = new_tag_annotation(
+                tx.repo().store(),
+                tx.settings(),
+                name,
+                target_commit.id(),
+                join_message_paragraphs(&args.message_paragraphs),
+                args.sign,
+            )?;
+            tx.repo_mut().set_tag_annotation(name, Some(annotation));
+        }
+    }
+
+    if let Some(mut formatter) = ui.status_formatter() {
+        write!(formatter, "Created {} tags pointing to ", tag_names.len())?;
+        tx.write_commit_summary(formatter.as_mut(), &target_commit)?;
+        writeln!(formatter)?;
+    }
+    tx.finish(
+        ui,
+        format!(
+            "create tag {names} pointing to commit {id}",
+            names = tag_names.join(", "),
+            id = target_commit.id().hex()
+        ),
+    )?;
+    Ok(())
+}
@@ -22,13 +22,6 @@ use crate::commit_templater::RefName;
 use crate::complete;
 use crate::ui::Ui;
 
-/// Manage tags.
-#[derive(clap::Subcommand, Clone, Debug)]
-pub enum TagCommand {
-    #[command(visible_alias("l"))]
-    List(TagListArgs),
-}
-
 /// List tags.
 #[derive(clap::Args, Clone, Debug)]
 pub struct TagListArgs {
@@ -55,17 +48,7 @@ pub struct TagListArgs {
     template: Option<String>,
 }
 
-pub fn cmd_tag(
-    ui: &mut Ui,
-    command: &CommandHelper,
-    subcommand: &TagCommand,
-) -> Result<(), CommandError> {
-    match subcommand {
-        TagCommand::List(args) => cmd_tag_list(ui, command, args),
-    }
-}
-
-fn cmd_tag_list(
+pub fn cmd_tag_list(
     ui: &mut Ui,
     command: &CommandHelper,
     args: &TagListArgs,
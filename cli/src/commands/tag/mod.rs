@@ -0,0 +1,142 @@
+// Copyright 2020-2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+mod create;
+mod delete;
+mod list;
+mod r#move;
+mod set;
+
+use itertools::Itertools as _;
+use jj_lib::backend::CommitId;
+use jj_lib::git;
+use jj_lib::op_store::RefTarget;
+use jj_lib::op_store::TagAnnotation;
+use jj_lib::settings::UserSettings;
+use jj_lib::store::Store;
+use jj_lib::str_util::StringPattern;
+use jj_lib::view::View;
+
+use self::create::cmd_tag_create;
+use self::create::TagCreateArgs;
+use self::delete::cmd_tag_delete;
+use self::delete::TagDeleteArgs;
+use self::list::cmd_tag_list;
+use self::list::TagListArgs;
+use self::r#move::cmd_tag_move;
+use self::r#move::TagMoveArgs;
+use self::set::cmd_tag_set;
+use self::set::TagSetArgs;
+use crate::cli_util::CommandHelper;
+use crate::command_error::user_error;
+use crate::command_error::user_error_with_message;
+use crate::command_error::CommandError;
+use crate::ui::Ui;
+
+/// Manage tags.
+///
+/// Tags are exported to the underlying Git repo like bookmarks. Tags with a
+/// message are exported as annotated tags.
+#[derive(clap::Subcommand, Clone, Debug)]
+pub enum TagCommand {
+    #[command(visible_alias("c"))]
+    Create(TagCreateArgs),
+    #[command(visible_alias("d"))]
+    Delete(TagDeleteArgs),
+    #[command(visible_alias("l"))]
+    List(TagListArgs),
+    #[command(visible_alias("m"))]
+    Move(TagMoveArgs),
+    #[command(visible_alias("s"))]
+    Set(TagSetArgs),
+}
+
+pub fn cmd_tag(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    subcommand: &TagCommand,
+) -> Result<(), CommandError> {
+    match subcommand {
+        TagCommand::Create(args) => cmd_tag_create(ui, command, args),
+        TagCommand::Delete(args) => cmd_tag_delete(ui, command, args),
+        TagCommand::List(args) => cmd_tag_list(ui, command, args),
+        TagCommand::Move(args) => cmd_tag_move(ui, command, args),
+        TagCommand::Set(args) => cmd_tag_set(ui, command, args),
+    }
+}
+
+fn find_tags<'a>(
+    view: &'a View,
+    name_patterns: &[StringPattern],
+) -> Result<Vec<(&'a str, &'a RefTarget)>, CommandError> {
+    let mut matching_tags = vec![];
+    let mut unmatched_patterns = vec![];
+    for pattern in name_patterns {
+        let mut matches = view.tags_matching(pattern).peekable();
+        if matches.peek().is_none() {
+            unmatched_patterns.push(pattern);
+        }
+        matching_tags.extend(matches);
+    }
+    match &unmatched_patterns[..] {
+        [] => {
+            matching_tags.sort_unstable_by_key(|(name, _)| *name);
+            matching_tags.dedup_by_key(|(name, _)| *name);
+            Ok(matching_tags)
+        }
+        [pattern] if pattern.is_exact() => Err(user_error(format!("No such tag: {pattern}"))),
+        patterns => Err(user_error(format!(
+            "No matching tags for patterns: {}",
+            patterns.iter().join(", ")
+        ))),
+    }
+}
+
+/// Creates the annotation of the tag `name` pointing to `target`, tagged by
+/// the current user.
+fn new_tag_annotation(
+    store: &Store,
+    settings: &UserSettings,
+    name: &str,
+    target: &CommitId,
+    message: String,
+    sign: bool,
+) -> Result<TagAnnotation, CommandError> {
+    let mut annotation = TagAnnotation {
+        message,
+        tagger: settings.signature(),
+        signature: None,
+    };
+    if sign {
+        sign_tag_annotation(store, settings, name, target, &mut annotation)?;
+    }
+    Ok(annotation)
+}
+
+fn sign_tag_annotation(
+    store: &Store,
+    settings: &UserSettings,
+    name: &str,
+    target: &CommitId,
+    annotation: &mut TagAnnotation,
+) -> Result<(), CommandError> {
+    if !store.signer().can_sign() {
+        return Err(user_error(
+            "Cannot sign tags because no signing backend is configured",
+        ));
+    }
+    let key = settings.sign_settings().key;
+    git::sign_tag_annotation(store.signer(), name, target, annotation, key.as_deref())
+        .map_err(|err| user_error_with_message(format!("Failed to sign tag {name}"), err))
+}
@@ -0,0 +1,148 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use clap_complete::ArgValueCandidates;
+use itertools::Itertools as _;
+use jj_lib::object_id::ObjectId as _;
+use jj_lib::op_store::RefTarget;
+use jj_lib::repo::Repo as _;
+use jj_lib::str_util::StringPattern;
+
+use super::find_tags;
+use super::new_tag_annotation;
+use crate::cli_util::CommandHelper;
+use crate::cli_util::RevisionArg;
+use crate::command_error::CommandError;
+use crate::complete;
+use crate::ui::Ui;
+
+/// Move existing tags to target revision
+///
+/// If tag names are given, the specified tags will be updated to point to the
+/// target revision.
+///
+/// If `--from` options are given, tags currently pointing to the specified
+/// revisions will be updated. The tags can also be filtered by names.
+///
+/// Annotated tags keep their message, and are re-tagged by the current user.
+/// Signed tags are signed again.
+#[derive(clap::Args, Clone, Debug)]
+#[command(group(clap::ArgGroup::new("source").multiple(true).required(true)))]
+pub struct TagMoveArgs {
+    /// Move tags from the given revisions
+    #[arg(
+        long,
+        group = "source",
+        value_name = "REVSETS",
+        add = ArgValueCandidates::new(complete::all_revisions),
+    )]
+    from: Vec<RevisionArg>,
+
+    /// Move tags to this revision
+    #[arg(
+        long,
+        value_name = "REVSET",
+        add = ArgValueCandidates::new(complete::all_revisions),
+    )]
+    to: RevisionArg,
+
+    /// Move tags matching the given name patterns
+    ///
+    /// By default, the specified name matches exactly. Use `glob:` prefix to
+    /// select tags by [wildcard pattern].
+    ///
+    /// [wildcard pattern]:
+    ///     https://jj-vcs.github.io/jj/latest/revsets/#string-patterns
+    #[arg(
+        group = "source",
+        value_parser = StringPattern::parse,
+        add = ArgValueCandidates::new(complete::local_tags),
+    )]
+    names: Vec<StringPattern>,
+}
+
+pub fn cmd_tag_move(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &TagMoveArgs,
+) -> Result<(), CommandError> {
+    let mut workspace_command = command.workspace_helper(ui)?;
+    let repo = workspace_command.repo().clone();
+    let target_commit = workspace_command.resolve_single_rev(ui, &args.to)?;
+    let mut matched_tags = if !args.names.is_empty() {
+        find_tags(repo.view(), &args.names)?
+    } else {
+        repo.view()
+            .tags()
+            .iter()
+            .map(|(name, target)| (name.as_str(), target))
+            .collect()
+    };
+    if !args.from.is_empty() {
+        let is_source_commit = workspace_command
+            .parse_union_revsets(ui, &args.from)?
+            .evaluate()?
+            .containing_fn();
+        let mut source_tags = vec![];
+        for (name, target) in matched_tags {
+            for id in target.added_ids() {
+                if is_source_commit(id)? {
+                    source_tags.push((name, target));
+                    break;
+                }
+            }
+        }
+        matched_tags = source_tags;
+    }
+    // Noop matches aren't error, but should be excluded from stats.
+    matched_tags.retain(|(_, old_target)| old_target.as_normal() != Some(target_commit.id()));
+
+    if matched_tags.is_empty() {
+        writeln!(ui.status(), "No tags to update.")?;
+        return Ok(());
+    }
+
+    let mut tx = workspace_command.start_transaction();
+    for (name, _) in &matched_tags {
+        tx.repo_mut()
+            .set_tag_target(name, RefTarget::normal(target_commit.id().clone()));
+        if let Some(old_annotation) = repo.view().get_tag_annotation(name) {
+            let annotation = new_tag_annotation(
+                tx.repo().store(),
+                tx.settings(),
+                name,
+                target_commit.id(),
+                old_annotation.message.clone(),
+                old_annotation.signature.is_some(),
+            )?;
+            tx.repo_mut().set_tag_annotation(name, Some(annotation));
+        }
+    }
+
+    if let Some(mut formatter) = ui.status_formatter() {
+        write!(formatter, "Moved {} tags to ", matched_tags.len())?;
+        tx.write_commit_summary(formatter.as_mut(), &target_commit)?;
+        writeln!(formatter)?;
+    }
+
+    tx.finish(
+        ui,
+        format!(
+            "point tag {names} to commit {id}",
+            names = matched_tags.iter().map(|(name, _)| name).join(", "),
+            id = target_commit.id().hex()
+        ),
+    )?;
+    Ok(())
+}
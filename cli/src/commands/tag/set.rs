@@ -0,0 +1,153 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use clap::builder::NonEmptyStringValueParser;
+use clap_complete::ArgValueCandidates;
+use jj_lib::object_id::ObjectId as _;
+use jj_lib::op_store::RefTarget;
+use jj_lib::repo::Repo as _;
+
+use super::new_tag_annotation;
+use crate::cli_util::CommandHelper;
+use crate::cli_util::RevisionArg;
+use crate::command_error::CommandError;
+use crate::complete;
+use crate::description_util::join_message_paragraphs;
+use crate::ui::Ui;
+
+/// Create or update a tag to point to a certain commit
+///
+/// With `--message`, all the given tags are annotated with the message,
+/// including tags already pointing to the target revision. Without it, moved
+/// tags keep their annotation message, and signed tags are signed again.
+#[derive(clap::Args, Clone, Debug)]
+pub struct TagSetArgs {
+    /// The tag's target revision
+    #[arg(
+        long, short,
+        visible_alias = "to",
+        value_name = "REVSET",
+        add = ArgValueCandidates::new(complete::all_revisions),
+    )]
+    revision: RevisionArg,
+
+    /// Annotate the tag with the given message
+    ///
+    /// If multiple messages are given, they are joined as separate paragraphs.
+    #[arg(long = "message", short, value_name = "MESSAGE")]
+    message_paragraphs: Vec<String>,
+
+    /// Sign the annotated tag with the configured signing backend
+    #[arg(long, requires = "message_paragraphs")]
+    sign: bool,
+
+    /// The tags to update
+    #[arg(
+        required = true,
+        value_parser = NonEmptyStringValueParser::new(),
+        add = ArgValueCandidates::new(complete::local_tags),
+    )]
+    names: Vec<String>,
+}
+
+pub fn cmd_tag_set(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &TagSetArgs,
+) -> Result<(), CommandError> {
+    let mut workspace_command = command.workspace_helper(ui)?;
+    let target_commit = workspace_command.resolve_single_rev(ui, &args.revision)?;
+    let repo = workspace_command.repo().as_ref();
+    let mut new_tag_names = vec![];
+    let mut moved_tag_names = vec![];
+    let mut annotated_tag_names = vec![];
+    for name in &args.names {
+        let old_target = repo.view().get_tag(name);
+        if old_target.is_absent() {
+            new_tag_names.push(name);
+        } else if old_target.as_normal() != Some(target_commit.id()) {
+            moved_tag_names.push(name);
+        } else if !args.message_paragraphs.is_empty() {
+            annotated_tag_names.push(name);
+        }
+    }
+
+    let mut tx = workspace_command.start_transaction();
+    for name in itertools::chain!(&new_tag_names, &moved_tag_names, &annotated_tag_names) {
+        tx.repo_mut()
+            .set_tag_target(name, RefTarget::normal(target_commit.id().clone()));
+        let annotation = if args.message_paragraphs.is_empty() {
+            // Keep the message of moved tags, and sign them again if needed
+            let old_annotation = tx.repo().get_tag_annotation(name);
+            old_annotation
+                .map(|old_annotation| {
+                    new_tag_annotation(
+                        tx.repo().store(),
+                        tx.settings(),
+                        name,
+                        target_commit.id(),
+                        old_annotation.message,
+                        old_annotation.signature.is_some(),
+                    )
+                })
+                .transpose()?
+        } else {
+            Some(new_tag_annotation(
+                tx.repo().store(),
+                tx.settings(),
+                name,
+                target_commit.id(),
+                join_message_paragraphs(&args.message_paragraphs),
+                args.sign,
+            )?)
+        };
+        tx.repo_mut().set_tag_annotation(name, annotation);
+    }
+
+    if let Some(mut formatter) = ui.status_formatter() {
+        if !new_tag_names.is_empty() {
+            write!(
+                formatter,
+                "Created {} tags pointing to ",
+                new_tag_names.len()
+            )?;
+            tx.write_commit_summary(formatter.as_mut(), &target_commit)?;
+            writeln!(formatter)?;
+        }
+        if !moved_tag_names.is_empty() {
+            write!(formatter, "Moved {} tags to ", moved_tag_names.len())?;
+            tx.write_commit_summary(formatter.as_mut(), &target_commit)?;
+            writeln!(formatter)?;
+        }
+        if !annotated_tag_names.is_empty() {
+            write!(
+                formatter,
+                "Annotated {} tags pointing to ",
+                annotated_tag_names.len()
+            )?;
+            tx.write_commit_summary(formatter.as_mut(), &target_commit)?;
+            writeln!(formatter)?;
+        }
+    }
+
+    tx.finish(
+        ui,
+        format!(
+            "point tag {names} to commit {id}",
+            names = args.names.join(", "),
+            id = target_commit.id().hex()
+        ),
+    )?;
+    Ok(())
+}
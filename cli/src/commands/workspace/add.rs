@@ -16,19 +16,25 @@ use std::fs;
 
 use itertools::Itertools;
 use jj_lib::commit::CommitIteratorExt;
+use jj_lib::config::ConfigGetResultExt as _;
 use jj_lib::file_util;
 use jj_lib::file_util::IoResultExt;
 use jj_lib::op_store::WorkspaceId;
 use jj_lib::repo::Repo;
+use jj_lib::repo_path::RepoPathBuf;
 use jj_lib::rewrite::merge_commit_trees;
+use jj_lib::settings::UserSettings;
 use jj_lib::workspace::Workspace;
 use tracing::instrument;
 
 use crate::cli_util::CommandHelper;
 use crate::cli_util::RevisionArg;
+use crate::command_error::config_error_with_message;
 use crate::command_error::internal_error_with_message;
 use crate::command_error::user_error;
+use crate::command_error::user_error_with_hint;
 use crate::command_error::CommandError;
+use crate::config::workspace_config_path;
 use crate::ui::Ui;
 
 /// How to handle sparse patterns when creating a new workspace.
@@ -46,6 +52,10 @@ enum SparseInheritance {
 ///
 /// By default, the new workspace inherits the sparse patterns of the current
 /// workspace. You can override this with the `--sparse-patterns` option.
+///
+/// With `--profile`, the sparse patterns, parent revisions and workspace config
+/// are taken from the `workspace-profiles.<name>` config table. Options given
+/// on the command line take precedence over the profile.
 #[derive(clap::Args, Clone, Debug)]
 pub struct WorkspaceAddArgs {
     /// Where to create the new workspace
@@ -71,8 +81,42 @@ pub struct WorkspaceAddArgs {
     #[arg(long, short, value_name = "REVSETS")]
     revision: Vec<RevisionArg>,
     /// How to handle sparse patterns when creating a new workspace.
-    #[arg(long, value_enum, default_value_t = SparseInheritance::Copy)]
-    sparse_patterns: SparseInheritance,
+    ///
+    /// Defaults to `copy`, unless the profile specifies sparse patterns.
+    #[arg(long, value_enum)]
+    sparse_patterns: Option<SparseInheritance>,
+    /// Set up the workspace as defined in `workspace-profiles.<PROFILE>`
+    #[arg(long)]
+    profile: Option<String>,
+}
+
+/// Represents a `workspace-profiles.<name>` config table, except for the
+/// `config` table, which is copied to the workspace config as is.
+#[derive(Clone, Debug, Default, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct WorkspaceProfile {
+    /// Repo-relative paths to check out. The sparse patterns aren't changed if
+    /// unset.
+    sparse_patterns: Option<Vec<String>>,
+    /// Parent revisions of the working-copy commit.
+    #[serde(default)]
+    revisions: Vec<String>,
+}
+
+fn load_workspace_profile(
+    settings: &UserSettings,
+    name: &str,
+) -> Result<WorkspaceProfile, CommandError> {
+    let Some(profile) = settings
+        .get::<WorkspaceProfile>(["workspace-profiles", name])
+        .optional()?
+    else {
+        return Err(user_error_with_hint(
+            format!("Workspace profile '{name}' is not defined"),
+            "Workspace profiles are configured in the `workspace-profiles` table.",
+        ));
+    };
+    Ok(profile)
 }
 
 #[instrument(skip_all)]
@@ -82,6 +126,81 @@ pub fn cmd_workspace_add(
     args: &WorkspaceAddArgs,
 ) -> Result<(), CommandError> {
     let old_workspace_command = command.workspace_helper(ui)?;
+    let (profile, profile_config) = if let Some(profile_name) = &args.profile {
+        let profile = load_workspace_profile(command.settings(), profile_name)?;
+        let config = command
+            .settings()
+            .config()
+            .get_table(["workspace-profiles", profile_name, "config"])
+            .optional()?;
+        (profile, config)
+    } else {
+        (WorkspaceProfile::default(), None)
+    };
+    let profile_sparse_patterns: Option<Vec<RepoPathBuf>> = profile
+        .sparse_patterns
+        .as_ref()
+        .map(|patterns| {
+            patterns
+                .iter()
+                .map(RepoPathBuf::from_relative_path)
+                .process_results(|patterns| patterns.sorted_unstable().dedup().collect())
+        })
+        .transpose()
+        .map_err(|err| {
+            config_error_with_message("Invalid sparse pattern in workspace profile", err)
+        })?;
+
+    // Resolve everything that can fail before the workspace is created.
+    let sparsity = match args.sparse_patterns {
+        None if profile_sparse_patterns.is_some() => profile_sparse_patterns,
+        Some(SparseInheritance::Full) => None,
+        Some(SparseInheritance::Empty) => Some(vec![]),
+        None | Some(SparseInheritance::Copy) => {
+            let sparse_patterns = old_workspace_command
+                .working_copy()
+                .sparse_patterns()?
+                .to_vec();
+            Some(sparse_patterns)
+        }
+    };
+
+    // If no parent revisions are specified, create a working-copy commit based
+    // on the parent of the current working-copy commit.
+    let revisions = if args.revision.is_empty() {
+        profile
+            .revisions
+            .iter()
+            .cloned()
+            .map(RevisionArg::from)
+            .collect()
+    } else {
+        args.revision.clone()
+    };
+    let parents = if revisions.is_empty() {
+        // Check out parents of the current workspace's working-copy commit, or the
+        // root if there is no working-copy commit in the current workspace.
+        if let Some(old_wc_commit_id) = old_workspace_command
+            .repo()
+            .view()
+            .get_wc_commit_id(old_workspace_command.workspace_id())
+        {
+            old_workspace_command
+                .repo()
+                .store()
+                .get_commit(old_wc_commit_id)?
+                .parents()
+                .try_collect()?
+        } else {
+            vec![old_workspace_command.repo().store().root_commit()]
+        }
+    } else {
+        old_workspace_command
+            .resolve_some_revsets_default_single(ui, &revisions)?
+            .into_iter()
+            .collect_vec()
+    };
+
     let destination_path = command.cwd().join(&args.destination);
     if destination_path.exists() {
         return Err(user_error("Workspace already exists"));
@@ -108,15 +227,23 @@ pub fn cmd_workspace_add(
 
     let working_copy_factory = command.get_working_copy_factory()?;
     let repo_path = old_workspace_command.repo_path();
-    // If we add per-workspace configuration, we'll need to reload settings for
-    // the new workspace.
-    let (new_workspace, repo) = Workspace::init_workspace_with_existing_repo(
+    let (mut new_workspace, mut repo) = Workspace::init_workspace_with_existing_repo(
         &destination_path,
         repo_path,
         repo,
         working_copy_factory,
         workspace_id,
     )?;
+    if let Some(config) = profile_config {
+        let path = workspace_config_path(&destination_path);
+        let data = toml_edit::DocumentMut::from(config);
+        fs::write(&path, data.to_string()).context(&path)?;
+        // Reload the workspace so that the new config applies to the initial
+        // checkout.
+        let settings = command.settings_for_workspace_of_repo(&destination_path)?;
+        new_workspace = command.load_workspace_at(&destination_path, &settings)?;
+        repo = new_workspace.repo_loader().load_at(repo.operation())?;
+    }
     writeln!(
         ui.status(),
         "Created workspace in \"{}\"",
@@ -134,18 +261,6 @@ pub fn cmd_workspace_add(
 
     let mut new_workspace_command = command.for_workable_repo(ui, new_workspace, repo)?;
 
-    let sparsity = match args.sparse_patterns {
-        SparseInheritance::Full => None,
-        SparseInheritance::Empty => Some(vec![]),
-        SparseInheritance::Copy => {
-            let sparse_patterns = old_workspace_command
-                .working_copy()
-                .sparse_patterns()?
-                .to_vec();
-            Some(sparse_patterns)
-        }
-    };
-
     if let Some(sparse_patterns) = sparsity {
         let checkout_options = new_workspace_command.checkout_options();
         let (mut locked_ws, _wc_commit) = new_workspace_command.start_working_copy_mutation()?;
@@ -159,31 +274,6 @@ pub fn cmd_workspace_add(
 
     let mut tx = new_workspace_command.start_transaction();
 
-    // If no parent revisions are specified, create a working-copy commit based
-    // on the parent of the current working-copy commit.
-    let parents = if args.revision.is_empty() {
-        // Check out parents of the current workspace's working-copy commit, or the
-        // root if there is no working-copy commit in the current workspace.
-        if let Some(old_wc_commit_id) = tx
-            .base_repo()
-            .view()
-            .get_wc_commit_id(old_workspace_command.workspace_id())
-        {
-            tx.repo()
-                .store()
-                .get_commit(old_wc_commit_id)?
-                .parents()
-                .try_collect()?
-        } else {
-            vec![tx.repo().store().root_commit()]
-        }
-    } else {
-        old_workspace_command
-            .resolve_some_revsets_default_single(ui, &args.revision)?
-            .into_iter()
-            .collect_vec()
-    };
-
     let tree = merge_commit_trees(tx.repo(), &parents)?;
     let parent_ids = parents.iter().ids().cloned().collect_vec();
     let new_wc_commit = tx.repo_mut().new_commit(parent_ids, tree.id()).write()?;
@@ -15,6 +15,7 @@
 use clap_complete::ArgValueCandidates;
 use itertools::Itertools;
 use jj_lib::op_store::WorkspaceId;
+use jj_lib::workspace_store::SimpleWorkspaceStore;
 use tracing::instrument;
 
 use crate::cli_util::CommandHelper;
@@ -65,6 +66,7 @@ pub fn cmd_workspace_forget(
 
     // bundle every workspace forget into a single transaction, so that e.g.
     // undo correctly restores all of them at once.
+    let workspace_store = SimpleWorkspaceStore::load(workspace_command.workspace().repo_path());
     let mut tx = workspace_command.start_transaction();
     wss.iter()
         .try_for_each(|ws| tx.repo_mut().remove_wc_commit(ws))?;
@@ -77,6 +79,9 @@ pub fn cmd_workspace_forget(
         )
     };
 
+    // If the transaction fails, the workspace location will be recorded again
+    // when the workspace is used.
+    workspace_store.forget(&wss.iter().collect_vec())?;
     tx.finish(ui, description)?;
     Ok(())
 }
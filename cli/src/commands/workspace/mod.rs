@@ -15,11 +15,16 @@
 mod add;
 mod forget;
 mod list;
+mod r#move;
 mod rename;
+mod repair;
 mod root;
 mod update_stale;
 
+use std::io::Write as _;
+
 use clap::Subcommand;
+use jj_lib::workspace::RepairedWorkspacePaths;
 use tracing::instrument;
 
 use self::add::cmd_workspace_add;
@@ -28,8 +33,12 @@ use self::forget::cmd_workspace_forget;
 use self::forget::WorkspaceForgetArgs;
 use self::list::cmd_workspace_list;
 use self::list::WorkspaceListArgs;
+use self::r#move::cmd_workspace_move;
+use self::r#move::WorkspaceMoveArgs;
 use self::rename::cmd_workspace_rename;
 use self::rename::WorkspaceRenameArgs;
+use self::repair::cmd_workspace_repair;
+use self::repair::WorkspaceRepairArgs;
 use self::root::cmd_workspace_root;
 use self::root::WorkspaceRootArgs;
 use self::update_stale::cmd_workspace_update_stale;
@@ -54,7 +63,9 @@ pub(crate) enum WorkspaceCommand {
     Add(WorkspaceAddArgs),
     Forget(WorkspaceForgetArgs),
     List(WorkspaceListArgs),
+    Move(WorkspaceMoveArgs),
     Rename(WorkspaceRenameArgs),
+    Repair(WorkspaceRepairArgs),
     Root(WorkspaceRootArgs),
     UpdateStale(WorkspaceUpdateStaleArgs),
 }
@@ -69,8 +80,27 @@ pub(crate) fn cmd_workspace(
         WorkspaceCommand::Add(args) => cmd_workspace_add(ui, command, args),
         WorkspaceCommand::Forget(args) => cmd_workspace_forget(ui, command, args),
         WorkspaceCommand::List(args) => cmd_workspace_list(ui, command, args),
+        WorkspaceCommand::Move(args) => cmd_workspace_move(ui, command, args),
         WorkspaceCommand::Rename(args) => cmd_workspace_rename(ui, command, args),
+        WorkspaceCommand::Repair(args) => cmd_workspace_repair(ui, command, args),
         WorkspaceCommand::Root(args) => cmd_workspace_root(ui, command, args),
         WorkspaceCommand::UpdateStale(args) => cmd_workspace_update_stale(ui, command, args),
     }
 }
+
+fn print_repaired_workspace_paths(
+    ui: &Ui,
+    repaired: &RepairedWorkspacePaths,
+) -> Result<(), CommandError> {
+    for path in &repaired.updated_files {
+        writeln!(ui.status(), "Updated {}", path.display())?;
+    }
+    for workspace_id in &repaired.updated_workspaces {
+        writeln!(
+            ui.status(),
+            "Updated repo path of workspace {}",
+            workspace_id.as_str()
+        )?;
+    }
+    Ok(())
+}
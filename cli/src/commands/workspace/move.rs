@@ -0,0 +1,96 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io::Write as _;
+
+use itertools::Itertools as _;
+use jj_lib::workspace::move_workspace;
+use jj_lib::workspace_store::SimpleWorkspaceStore;
+use tracing::instrument;
+
+use super::print_repaired_workspace_paths;
+use crate::cli_util::CommandHelper;
+use crate::command_error::CommandError;
+use crate::ui::Ui;
+
+/// Move the current workspace to another directory
+///
+/// The workspace directory is moved to the destination, and the paths pointing
+/// to it are updated. If the workspace contains the repo, the other workspaces
+/// of the repo are updated to point to its new location.
+///
+/// To fix up a workspace which was moved by other means, use `jj workspace
+/// repair` instead.
+#[derive(clap::Args, Clone, Debug)]
+pub struct WorkspaceMoveArgs {
+    /// Where to move the workspace
+    #[arg(value_hint = clap::ValueHint::DirPath)]
+    destination: String,
+}
+
+#[instrument(skip_all)]
+pub fn cmd_workspace_move(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &WorkspaceMoveArgs,
+) -> Result<(), CommandError> {
+    let workspace_command = command.workspace_helper(ui)?;
+    let workspace = workspace_command.workspace();
+    // Workspaces created by older versions aren't recorded in the store, so
+    // they can't be updated.
+    let unknown_workspaces = if workspace
+        .repo_path()
+        .starts_with(workspace.workspace_root())
+    {
+        let known_workspaces = SimpleWorkspaceStore::load(workspace.repo_path())
+            .workspace_paths()?
+            .into_iter()
+            .map(|(workspace_id, _)| workspace_id)
+            .collect_vec();
+        workspace_command
+            .repo()
+            .view()
+            .wc_commit_ids()
+            .keys()
+            .filter(|workspace_id| !known_workspaces.contains(workspace_id))
+            .sorted()
+            .cloned()
+            .collect_vec()
+    } else {
+        vec![]
+    };
+
+    let destination = command.cwd().join(&args.destination);
+    let repaired = move_workspace(workspace, &destination)?;
+    writeln!(
+        ui.status(),
+        "Moved workspace to {}",
+        repaired.workspace_root.display()
+    )?;
+    print_repaired_workspace_paths(ui, &repaired)?;
+    if !unknown_workspaces.is_empty() {
+        writeln!(
+            ui.warning_default(),
+            "The location of these workspaces is unknown, so they still point to the old repo \
+             path: {}",
+            unknown_workspaces.iter().map(|id| id.as_str()).join(", ")
+        )?;
+        writeln!(
+            ui.hint_default(),
+            "Run `jj workspace repair --repo {}` in each of them.",
+            repaired.workspace_root.display()
+        )?;
+    }
+    Ok(())
+}
@@ -13,6 +13,7 @@
 // limitations under the License.
 
 use jj_lib::op_store::WorkspaceId;
+use jj_lib::workspace_store::SimpleWorkspaceStore;
 use tracing::instrument;
 
 use crate::cli_util::CommandHelper;
@@ -58,6 +59,7 @@ pub fn cmd_workspace_rename(
         )));
     }
 
+    let workspace_store = SimpleWorkspaceStore::load(workspace_command.workspace().repo_path());
     let mut tx = workspace_command.start_transaction().into_inner();
     let (mut locked_ws, _wc_commit) = workspace_command.start_working_copy_mutation()?;
 
@@ -66,7 +68,10 @@ pub fn cmd_workspace_rename(
         .rename_workspace(new_workspace_id.clone());
 
     tx.repo_mut()
-        .rename_workspace(&old_workspace_id, new_workspace_id)?;
+        .rename_workspace(&old_workspace_id, new_workspace_id.clone())?;
+    // If the transaction fails, the workspace location will be recorded again
+    // under the old name when the workspace is used.
+    workspace_store.rename(&old_workspace_id, &new_workspace_id)?;
     let repo = tx.commit(format!(
         "Renamed workspace '{}' to '{}'",
         old_workspace_id.as_str(),
@@ -0,0 +1,84 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io::Write as _;
+
+use jj_lib::workspace::repair_workspace_paths;
+use jj_lib::workspace::set_workspace_repo_path;
+use tracing::instrument;
+
+use super::print_repaired_workspace_paths;
+use crate::cli_util::find_workspace_dir;
+use crate::cli_util::CommandHelper;
+use crate::command_error::user_error;
+use crate::command_error::CommandError;
+use crate::ui::Ui;
+
+/// Update paths after the workspace or its repo was moved
+///
+/// Run this in a workspace which was moved without `jj workspace move`, e.g.
+/// by `mv`. If the workspace contains the repo, the other workspaces of the
+/// repo are updated to point to its new location.
+///
+/// If the repo of this workspace was moved, specify its new location with
+/// `--repo`.
+#[derive(clap::Args, Clone, Debug)]
+pub struct WorkspaceRepairArgs {
+    /// New location of the workspace containing the repo
+    #[arg(long, value_hint = clap::ValueHint::DirPath)]
+    repo: Option<String>,
+}
+
+#[instrument(skip_all)]
+pub fn cmd_workspace_repair(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &WorkspaceRepairArgs,
+) -> Result<(), CommandError> {
+    let workspace_root = if let Some(repo) = &args.repo {
+        // The workspace can't be loaded if the repo path is broken.
+        let workspace_root = find_workspace_dir(command.cwd());
+        if !workspace_root.join(".jj").is_dir() {
+            return Err(user_error(format!(
+                r#"There is no jj repo in "{}""#,
+                command.cwd().display()
+            )));
+        }
+        let repo_path = dunce::canonicalize(command.cwd().join(repo).join(".jj").join("repo"))
+            .ok()
+            .filter(|path| path.is_dir());
+        let Some(repo_path) = repo_path else {
+            return Err(user_error(format!(r#"There is no jj repo in "{repo}""#)));
+        };
+        set_workspace_repo_path(workspace_root, &repo_path)?;
+        writeln!(ui.status(), "Updated repo path to {}", repo_path.display())?;
+        workspace_root.to_owned()
+    } else {
+        command.workspace_loader()?.workspace_root().to_owned()
+    };
+    let workspace = command.load_workspace_at(&workspace_root, command.settings())?;
+    let repaired =
+        repair_workspace_paths(workspace.workspace_root(), workspace.workspace_id(), None)?;
+    if let Some(old_root) = &repaired.old_root {
+        writeln!(
+            ui.status(),
+            "Updated paths of workspace moved from {}",
+            old_root.display()
+        )?;
+    } else if args.repo.is_none() {
+        writeln!(ui.status(), "Nothing changed.")?;
+    }
+    print_repaired_workspace_paths(ui, &repaired)?;
+    Ok(())
+}
@@ -32,11 +32,13 @@ use jj_lib::commit::Commit;
 use jj_lib::conflicts::ConflictMarkerStyle;
 use jj_lib::copies::CopiesTreeDiffEntry;
 use jj_lib::copies::CopiesTreeDiffEntryPath;
+use jj_lib::copies::CopyDetectionOptions;
 use jj_lib::copies::CopyRecords;
 use jj_lib::extensions_map::ExtensionsMap;
 use jj_lib::fileset;
 use jj_lib::fileset::FilesetDiagnostics;
 use jj_lib::fileset::FilesetExpression;
+use jj_lib::gitattributes::GitAttributesLoader;
 use jj_lib::id_prefix::IdPrefixContext;
 use jj_lib::id_prefix::IdPrefixIndex;
 use jj_lib::matchers::Matcher;
@@ -91,6 +93,7 @@ use crate::templater::TemplateFormatter;
 use crate::templater::TemplateProperty;
 use crate::templater::TemplatePropertyError;
 use crate::templater::TemplatePropertyExt as _;
+use crate::templater::ToJson;
 use crate::text_util;
 
 pub trait CommitTemplateLanguageExtension {
@@ -112,6 +115,7 @@ pub struct CommitTemplateLanguage<'repo> {
     id_prefix_context: &'repo IdPrefixContext,
     immutable_expression: Rc<UserRevsetExpression>,
     conflict_marker_style: ConflictMarkerStyle,
+    copy_detection_options: CopyDetectionOptions,
     build_fn_table: CommitTemplateBuildFnTable<'repo>,
     keyword_cache: CommitKeywordCache<'repo>,
     cache_extensions: ExtensionsMap,
@@ -129,6 +133,7 @@ impl<'repo> CommitTemplateLanguage<'repo> {
         id_prefix_context: &'repo IdPrefixContext,
         immutable_expression: Rc<UserRevsetExpression>,
         conflict_marker_style: ConflictMarkerStyle,
+        copy_detection_options: CopyDetectionOptions,
         extensions: &[impl AsRef<dyn CommitTemplateLanguageExtension>],
     ) -> Self {
         let mut build_fn_table = CommitTemplateBuildFnTable::builtin();
@@ -149,6 +154,7 @@ impl<'repo> CommitTemplateLanguage<'repo> {
             id_prefix_context,
             immutable_expression,
             conflict_marker_style,
+            copy_detection_options,
             build_fn_table,
             keyword_cache: CommitKeywordCache::default(),
             cache_extensions,
@@ -588,6 +594,31 @@ impl<'repo> IntoTemplateProperty<'repo> for CommitTemplatePropertyKind<'repo> {
         }
     }
 
+    fn try_into_json(
+        self,
+    ) -> Option<Box<dyn TemplateProperty<Output = serde_json::Value> + 'repo>> {
+        match self {
+            CommitTemplatePropertyKind::Core(property) => property.try_into_json(),
+            CommitTemplatePropertyKind::Commit(property) => Some(property.into_json()),
+            CommitTemplatePropertyKind::CommitOpt(property) => Some(property.into_json()),
+            CommitTemplatePropertyKind::CommitList(property) => Some(property.into_json()),
+            CommitTemplatePropertyKind::RefName(property) => Some(property.into_json()),
+            CommitTemplatePropertyKind::RefNameOpt(property) => Some(property.into_json()),
+            CommitTemplatePropertyKind::RefNameList(property) => Some(property.into_json()),
+            CommitTemplatePropertyKind::RepoPath(property) => Some(property.into_json()),
+            CommitTemplatePropertyKind::RepoPathOpt(property) => Some(property.into_json()),
+            CommitTemplatePropertyKind::CommitOrChangeId(property) => Some(property.into_json()),
+            CommitTemplatePropertyKind::ShortestIdPrefix(property) => Some(property.into_json()),
+            CommitTemplatePropertyKind::TreeDiff(_) => None,
+            CommitTemplatePropertyKind::TreeDiffEntry(_) => None,
+            CommitTemplatePropertyKind::TreeDiffEntryList(_) => None,
+            CommitTemplatePropertyKind::TreeEntry(_) => None,
+            CommitTemplatePropertyKind::DiffStats(_) => None,
+            CommitTemplatePropertyKind::CryptographicSignatureOpt(_) => None,
+            CommitTemplatePropertyKind::AnnotationLine(_) => None,
+        }
+    }
+
     fn try_into_eq(self, other: Self) -> Option<Box<dyn TemplateProperty<Output = bool> + 'repo>> {
         match (self, other) {
             (CommitTemplatePropertyKind::Core(lhs), CommitTemplatePropertyKind::Core(rhs)) => {
@@ -782,6 +813,19 @@ impl<'repo> CommitKeywordCache<'repo> {
     }
 }
 
+impl ToJson for Commit {
+    fn to_json(&self) -> Result<serde_json::Value, TemplatePropertyError> {
+        Ok(serde_json::json!({
+            "commit_id": self.id().hex(),
+            "parents": self.parent_ids().iter().map(|id| id.hex()).collect_vec(),
+            "change_id": self.change_id().reverse_hex(),
+            "description": self.description(),
+            "author": self.author().to_json()?,
+            "committer": self.committer().to_json()?,
+        }))
+    }
+}
+
 fn builtin_commit_methods<'repo>() -> CommitTemplateBuildMethodFnMap<'repo, Commit> {
     type L<'repo> = CommitTemplateLanguage<'repo>;
     // Not using maplit::hashmap!{} or custom declarative macro here because
@@ -1068,8 +1112,15 @@ fn builtin_commit_methods<'repo>() -> CommitTemplateBuildMethodFnMap<'repo, Comm
             };
             let repo = language.repo;
             let matcher: Rc<dyn Matcher> = files.to_matcher().into();
-            let out_property = self_property
-                .and_then(move |commit| Ok(TreeDiff::from_commit(repo, &commit, matcher.clone())?));
+            let copy_detection_options = language.copy_detection_options.clone();
+            let out_property = self_property.and_then(move |commit| {
+                Ok(TreeDiff::from_commit(
+                    repo,
+                    &commit,
+                    matcher.clone(),
+                    &copy_detection_options,
+                )?)
+            });
             Ok(L::wrap_tree_diff(out_property))
         },
     );
@@ -1340,6 +1391,27 @@ impl Template for Rc<RefName> {
     }
 }
 
+impl ToJson for RefName {
+    fn to_json(&self) -> Result<serde_json::Value, TemplatePropertyError> {
+        let target_to_json = |target: &RefTarget| {
+            target
+                .as_merge()
+                .iter()
+                .map(|id| id.as_ref().map(|id| id.hex()))
+                .collect_vec()
+        };
+        Ok(serde_json::json!({
+            "name": self.name,
+            "remote": self.remote,
+            "target": target_to_json(&self.target),
+            "tracking_target": self
+                .tracking_ref
+                .as_ref()
+                .map(|tracking| target_to_json(&tracking.target)),
+        }))
+    }
+}
+
 impl Template for Vec<Rc<RefName>> {
     fn format(&self, formatter: &mut TemplateFormatter) -> io::Result<()> {
         templater::format_joined(formatter, self, " ")
@@ -1517,6 +1589,12 @@ impl Template for RepoPathBuf {
     }
 }
 
+impl ToJson for RepoPathBuf {
+    fn to_json(&self) -> Result<serde_json::Value, TemplatePropertyError> {
+        Ok(self.as_internal_file_string().into())
+    }
+}
+
 fn builtin_repo_path_methods<'repo>() -> CommitTemplateBuildMethodFnMap<'repo, RepoPathBuf> {
     type L<'repo> = CommitTemplateLanguage<'repo>;
     // Not using maplit::hashmap!{} or custom declarative macro here because
@@ -1587,6 +1665,12 @@ impl Template for CommitOrChangeId {
     }
 }
 
+impl ToJson for CommitOrChangeId {
+    fn to_json(&self) -> Result<serde_json::Value, TemplatePropertyError> {
+        Ok(self.hex().into())
+    }
+}
+
 fn builtin_commit_or_change_id_methods<'repo>(
 ) -> CommitTemplateBuildMethodFnMap<'repo, CommitOrChangeId> {
     type L<'repo> = CommitTemplateLanguage<'repo>;
@@ -1678,6 +1762,15 @@ impl Template for ShortestIdPrefix {
     }
 }
 
+impl ToJson for ShortestIdPrefix {
+    fn to_json(&self) -> Result<serde_json::Value, TemplatePropertyError> {
+        Ok(serde_json::json!({
+            "prefix": self.prefix,
+            "rest": self.rest,
+        }))
+    }
+}
+
 impl ShortestIdPrefix {
     fn to_upper(&self) -> Self {
         Self {
@@ -1748,12 +1841,18 @@ impl TreeDiff {
         repo: &dyn Repo,
         commit: &Commit,
         matcher: Rc<dyn Matcher>,
+        copy_detection_options: &CopyDetectionOptions,
     ) -> BackendResult<Self> {
         let mut copy_records = CopyRecords::default();
         for parent in commit.parent_ids() {
-            let records =
-                diff_util::get_copy_records(repo.store(), parent, commit.id(), &*matcher)?;
-            copy_records.add_records(records)?;
+            diff_util::add_copy_records(
+                &mut copy_records,
+                repo.store(),
+                parent,
+                commit.id(),
+                &*matcher,
+                copy_detection_options,
+            )?;
         }
         Ok(TreeDiff {
             from_tree: commit.parent_tree(repo)?,
@@ -1777,7 +1876,12 @@ impl TreeDiff {
 
     fn into_formatted<F, E>(self, show: F) -> TreeDiffFormatted<F>
     where
-        F: Fn(&mut dyn Formatter, &Store, BoxStream<CopiesTreeDiffEntry>) -> Result<(), E>,
+        F: Fn(
+            &mut dyn Formatter,
+            &Store,
+            BoxStream<CopiesTreeDiffEntry>,
+            &GitAttributesLoader,
+        ) -> Result<(), E>,
         E: Into<TemplatePropertyError>,
     {
         TreeDiffFormatted { diff: self, show }
@@ -1792,14 +1896,21 @@ struct TreeDiffFormatted<F> {
 
 impl<F, E> Template for TreeDiffFormatted<F>
 where
-    F: Fn(&mut dyn Formatter, &Store, BoxStream<CopiesTreeDiffEntry>) -> Result<(), E>,
+    F: Fn(
+        &mut dyn Formatter,
+        &Store,
+        BoxStream<CopiesTreeDiffEntry>,
+        &GitAttributesLoader,
+    ) -> Result<(), E>,
     E: Into<TemplatePropertyError>,
 {
     fn format(&self, formatter: &mut TemplateFormatter) -> io::Result<()> {
         let show = &self.show;
         let store = self.diff.from_tree.store();
         let tree_diff = self.diff.diff_stream();
-        show(formatter.as_mut(), store, tree_diff).or_else(|err| formatter.handle_error(err.into()))
+        let git_attributes = GitAttributesLoader::from_tree(self.diff.to_tree.clone());
+        show(formatter.as_mut(), store, tree_diff, &git_attributes)
+            .or_else(|err| formatter.handle_error(err.into()))
     }
 }
 
@@ -1845,14 +1956,17 @@ fn builtin_tree_diff_methods<'repo>() -> CommitTemplateBuildMethodFnMap<'repo, T
                     if let Some(context) = context {
                         options.context = context;
                     }
-                    diff.into_formatted(move |formatter, store, tree_diff| {
+                    diff.into_formatted(move |formatter, store, tree_diff, git_attributes| {
                         diff_util::show_color_words_diff(
                             formatter,
                             store,
                             tree_diff,
+                            &diff_util::DiffContentOptions {
+                                git_attributes,
+                                conflict_marker_style,
+                            },
                             path_converter,
                             &options,
-                            conflict_marker_style,
                         )
                     })
                 })
@@ -1886,13 +2000,16 @@ fn builtin_tree_diff_methods<'repo>() -> CommitTemplateBuildMethodFnMap<'repo, T
                     if let Some(context) = context {
                         options.context = context;
                     }
-                    diff.into_formatted(move |formatter, store, tree_diff| {
+                    diff.into_formatted(move |formatter, store, tree_diff, git_attributes| {
                         diff_util::show_git_diff(
                             formatter,
                             store,
                             tree_diff,
+                            &diff_util::DiffContentOptions {
+                                git_attributes,
+                                conflict_marker_style,
+                            },
                             &options,
-                            conflict_marker_style,
                         )
                     })
                 })
@@ -1941,7 +2058,7 @@ fn builtin_tree_diff_methods<'repo>() -> CommitTemplateBuildMethodFnMap<'repo, T
             let path_converter = language.path_converter;
             let template = self_property
                 .map(move |diff| {
-                    diff.into_formatted(move |formatter, _store, tree_diff| {
+                    diff.into_formatted(move |formatter, _store, tree_diff, _git_attributes| {
                         diff_util::show_diff_summary(formatter, tree_diff, path_converter)
                     })
                 })
@@ -78,6 +78,27 @@ pub fn local_bookmarks() -> Vec<CompletionCandidate> {
     })
 }
 
+pub fn local_tags() -> Vec<CompletionCandidate> {
+    with_jj(|jj, _| {
+        let output = jj
+            .build()
+            .arg("tag")
+            .arg("list")
+            .arg("--config")
+            .arg(BOOKMARK_HELP_TEMPLATE)
+            .arg("--template")
+            .arg(r#"name ++ bookmark_help() ++ "\n""#)
+            .output()
+            .map_err(user_error)?;
+
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(split_help_text)
+            .map(|(name, help)| CompletionCandidate::new(name).help(help))
+            .collect())
+    })
+}
+
 pub fn tracked_bookmarks() -> Vec<CompletionCandidate> {
     with_jj(|jj, _| {
         let output = jj
@@ -794,6 +815,8 @@ fn get_jj_command() -> Result<(JjBuilder, UserSettings), CommandError> {
     if let Ok(loader) = &maybe_cwd_workspace_loader {
         config_env.reset_repo_path(loader.repo_path());
         let _ = config_env.reload_repo_config(&mut raw_config);
+        config_env.reset_workspace_path(loader.workspace_root());
+        let _ = config_env.reload_workspace_config(&mut raw_config);
     }
     let mut config = config_env.resolve_config(&raw_config)?;
     // skip 2 because of the clap_complete prelude: jj -- jj <actual args...>
@@ -812,6 +835,8 @@ fn get_jj_command() -> Result<(JjBuilder, UserSettings), CommandError> {
         if let Ok(loader) = DefaultWorkspaceLoaderFactory.create(&cwd.join(&repository)) {
             config_env.reset_repo_path(loader.repo_path());
             let _ = config_env.reload_repo_config(&mut raw_config);
+            config_env.reset_workspace_path(loader.workspace_root());
+            let _ = config_env.reload_workspace_config(&mut raw_config);
             if let Ok(new_config) = config_env.resolve_config(&raw_config) {
                 config = new_config;
             }
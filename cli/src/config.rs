@@ -261,8 +261,10 @@ impl UnresolvedConfigEnv {
 pub struct ConfigEnv {
     home_dir: Option<PathBuf>,
     repo_path: Option<PathBuf>,
+    workspace_path: Option<PathBuf>,
     user_config_path: ConfigPath,
     repo_config_path: ConfigPath,
+    workspace_config_path: ConfigPath,
     command: Option<String>,
 }
 
@@ -280,8 +282,10 @@ impl ConfigEnv {
         Ok(ConfigEnv {
             home_dir,
             repo_path: None,
+            workspace_path: None,
             user_config_path: env.resolve()?,
             repo_config_path: ConfigPath::Unavailable,
+            workspace_config_path: ConfigPath::Unavailable,
             command: None,
         })
     }
@@ -398,18 +402,77 @@ impl ConfigEnv {
         Ok(())
     }
 
+    /// Sets the root of the workspace whose config file should be loaded. The
+    /// config file is stored in `.jj/working_copy`.
+    pub fn reset_workspace_path(&mut self, workspace_root: &Path) {
+        self.workspace_path = Some(workspace_root.to_owned());
+        self.workspace_config_path = ConfigPath::new(Some(workspace_config_path(workspace_root)));
+    }
+
+    /// Returns a path to the workspace-specific config file.
+    pub fn workspace_config_path(&self) -> Option<&Path> {
+        self.workspace_config_path.as_path()
+    }
+
+    /// Returns a path to the existing workspace-specific config file.
+    fn existing_workspace_config_path(&self) -> Option<&Path> {
+        match &self.workspace_config_path {
+            ConfigPath::Existing(path) => Some(path),
+            _ => None,
+        }
+    }
+
+    /// Returns workspace configuration files for modification. Instantiates one
+    /// if `config` has no workspace configuration layers.
+    ///
+    /// If the workspace path is unknown, this function returns an empty `Vec`.
+    pub fn workspace_config_files(
+        &self,
+        config: &RawConfig,
+    ) -> Result<Vec<ConfigFile>, ConfigLoadError> {
+        config_files_for(config, ConfigSource::Workspace, || {
+            self.new_workspace_config_file()
+        })
+    }
+
+    fn new_workspace_config_file(&self) -> Result<Option<ConfigFile>, ConfigLoadError> {
+        self.workspace_config_path()
+            .map(|path| ConfigFile::load_or_empty(ConfigSource::Workspace, path))
+            .transpose()
+    }
+
+    /// Loads workspace-specific config file into the given `config`. The old
+    /// workspace-config layer will be replaced if any.
+    #[instrument]
+    pub fn reload_workspace_config(&self, config: &mut RawConfig) -> Result<(), ConfigLoadError> {
+        config.as_mut().remove_layers(ConfigSource::Workspace);
+        if let Some(path) = self.existing_workspace_config_path() {
+            config.as_mut().load_file(ConfigSource::Workspace, path)?;
+        }
+        Ok(())
+    }
+
     /// Resolves conditional scopes within the current environment. Returns new
     /// resolved config.
     pub fn resolve_config(&self, config: &RawConfig) -> Result<StackedConfig, ConfigGetError> {
         let context = ConfigResolutionContext {
             home_dir: self.home_dir.as_deref(),
             repo_path: self.repo_path.as_deref(),
+            workspace_path: self.workspace_path.as_deref(),
             command: self.command.as_deref(),
         };
         jj_lib::config::resolve(config.as_ref(), &context)
     }
 }
 
+/// Returns the path to the config file of the workspace at `workspace_root`.
+pub fn workspace_config_path(workspace_root: &Path) -> PathBuf {
+    workspace_root
+        .join(".jj")
+        .join("working_copy")
+        .join("config.toml")
+}
+
 fn config_files_for(
     config: &RawConfig,
     source: ConfigSource,
@@ -435,7 +498,7 @@ fn config_files_for(
 /// 2. Base environment variables
 /// 3. [User config](https://jj-vcs.github.io/jj/latest/config/)
 /// 4. Repo config `.jj/repo/config.toml`
-/// 5. TODO: Workspace config `.jj/config.toml`
+/// 5. Workspace config `.jj/working_copy/config.toml`
 /// 6. Override environment variables
 /// 7. Command-line arguments `--config`, `--config-toml`, `--config-file`
 ///
@@ -1389,8 +1452,10 @@ mod tests {
             Ok(ConfigEnv {
                 home_dir,
                 repo_path: None,
+                workspace_path: None,
                 user_config_path: env.resolve()?,
                 repo_config_path: ConfigPath::Unavailable,
+                workspace_config_path: ConfigPath::Unavailable,
                 command: None,
             })
         }
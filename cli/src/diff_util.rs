@@ -14,19 +14,24 @@
 
 use std::borrow::Borrow;
 use std::cmp::max;
+use std::collections::HashMap;
+use std::collections::HashSet;
 use std::io;
 use std::iter;
 use std::mem;
 use std::ops::Range;
 use std::path::Path;
 use std::path::PathBuf;
+use std::sync::Arc;
 
 use bstr::BStr;
 use futures::executor::block_on_stream;
+use futures::stream;
 use futures::stream::BoxStream;
 use futures::StreamExt as _;
 use futures::TryStreamExt as _;
 use itertools::Itertools;
+use jj_lib::annotate::LineRangeChange;
 use jj_lib::backend::BackendError;
 use jj_lib::backend::BackendResult;
 use jj_lib::backend::CommitId;
@@ -40,8 +45,10 @@ use jj_lib::conflicts::materialized_diff_stream;
 use jj_lib::conflicts::ConflictMarkerStyle;
 use jj_lib::conflicts::MaterializedTreeDiffEntry;
 use jj_lib::conflicts::MaterializedTreeValue;
+use jj_lib::copies::find_similar_copies;
 use jj_lib::copies::CopiesTreeDiffEntry;
 use jj_lib::copies::CopiesTreeDiffEntryPath;
+use jj_lib::copies::CopyDetectionOptions;
 use jj_lib::copies::CopyOperation;
 use jj_lib::copies::CopyRecords;
 use jj_lib::diff::find_line_ranges;
@@ -49,16 +56,19 @@ use jj_lib::diff::CompareBytesExactly;
 use jj_lib::diff::CompareBytesIgnoreAllWhitespace;
 use jj_lib::diff::CompareBytesIgnoreWhitespaceAmount;
 use jj_lib::diff::Diff;
+use jj_lib::diff::DiffAlgorithm;
 use jj_lib::diff::DiffHunk;
 use jj_lib::diff::DiffHunkContentVec;
 use jj_lib::diff::DiffHunkKind;
 use jj_lib::files::DiffLineHunkSide;
 use jj_lib::files::DiffLineIterator;
 use jj_lib::files::DiffLineNumber;
+use jj_lib::gitattributes::GitAttributesLoader;
 use jj_lib::matchers::Matcher;
 use jj_lib::merge::MergedTreeValue;
 use jj_lib::merged_tree::MergedTree;
 use jj_lib::object_id::ObjectId;
+use jj_lib::patch::format_git_binary_patch;
 use jj_lib::repo::Repo;
 use jj_lib::repo_path::InvalidRepoPathError;
 use jj_lib::repo_path::RepoPath;
@@ -80,13 +90,15 @@ use crate::merge_tools::new_utf8_temp_dir;
 use crate::merge_tools::DiffGenerateError;
 use crate::merge_tools::DiffToolMode;
 use crate::merge_tools::ExternalMergeTool;
+#[cfg(feature = "structural-diff")]
+use crate::structural_diff;
 use crate::text_util;
 use crate::ui::Ui;
 
 #[derive(clap::Args, Clone, Debug)]
 #[command(next_help_heading = "Diff Formatting Options")]
 #[command(group(clap::ArgGroup::new("short-format").args(&["summary", "stat", "types", "name_only"])))]
-#[command(group(clap::ArgGroup::new("long-format").args(&["git", "color_words", "tool"])))]
+#[command(group(clap::ArgGroup::new("long-format").args(&["git", "color_words", "side_by_side", "tool"])))]
 pub struct DiffFormatArgs {
     /// For each path, show only whether it was modified, added, or deleted
     #[arg(long, short)]
@@ -115,12 +127,23 @@ pub struct DiffFormatArgs {
     /// Show a word-level diff with changes indicated only by color
     #[arg(long)]
     pub color_words: bool,
+    /// Show old and new contents in two columns
+    #[arg(long)]
+    pub side_by_side: bool,
+    /// Show a syntax-aware diff which ignores formatting-only changes
+    ///
+    /// Requires jj to be built with the `structural-diff` feature.
+    #[arg(long, group = "long-format")]
+    pub structural: bool,
     /// Generate diff by external command
     #[arg(long)]
     pub tool: Option<String>,
     /// Number of lines of context to show
     #[arg(long)]
     context: Option<usize>,
+    /// Highlight blocks of lines moved within or across files
+    #[arg(long)]
+    color_moved: bool,
 
     // Short flags are set by command to avoid future conflicts.
     /// Ignore whitespace when comparing lines.
@@ -140,6 +163,8 @@ pub enum DiffFormat {
     NameOnly,
     Git(Box<UnifiedDiffOptions>),
     ColorWords(Box<ColorWordsDiffOptions>),
+    SideBySide(Box<SideBySideDiffOptions>),
+    Structural(Box<StructuralDiffOptions>),
     Tool(Box<ExternalMergeTool>),
 }
 
@@ -196,6 +221,16 @@ fn diff_formats_from_args(
         options.merge_args(args);
         formats.push(DiffFormat::ColorWords(Box::new(options)));
     }
+    if args.side_by_side {
+        let mut options = SideBySideDiffOptions::from_settings(settings)?;
+        options.merge_args(args);
+        formats.push(DiffFormat::SideBySide(Box::new(options)));
+    }
+    if args.structural {
+        let mut options = StructuralDiffOptions::from_settings(settings)?;
+        options.merge_args(args);
+        formats.push(DiffFormat::Structural(Box::new(options)));
+    }
     if args.stat {
         let mut options = DiffStatOptions::default();
         options.merge_args(args);
@@ -244,6 +279,16 @@ fn default_diff_format(
             options.merge_args(args);
             Ok(DiffFormat::ColorWords(Box::new(options)))
         }
+        "side-by-side" => {
+            let mut options = SideBySideDiffOptions::from_settings(settings)?;
+            options.merge_args(args);
+            Ok(DiffFormat::SideBySide(Box::new(options)))
+        }
+        "structural" => {
+            let mut options = StructuralDiffOptions::from_settings(settings)?;
+            options.merge_args(args);
+            Ok(DiffFormat::Structural(Box::new(options)))
+        }
         "stat" => {
             let mut options = DiffStatOptions::default();
             options.merge_args(args);
@@ -272,6 +317,8 @@ pub enum DiffRenderError {
     InvalidRepoPath(#[from] InvalidRepoPathError),
     #[error(transparent)]
     Io(#[from] io::Error),
+    #[error("Structural diff requires jj to be built with the `structural-diff` feature")]
+    StructuralDiffUnsupported,
 }
 
 /// Configuration and environment to render textual diff.
@@ -279,6 +326,7 @@ pub struct DiffRenderer<'a> {
     repo: &'a dyn Repo,
     path_converter: &'a RepoPathUiConverter,
     conflict_marker_style: ConflictMarkerStyle,
+    copy_detection_options: CopyDetectionOptions,
     formats: Vec<DiffFormat>,
 }
 
@@ -287,12 +335,14 @@ impl<'a> DiffRenderer<'a> {
         repo: &'a dyn Repo,
         path_converter: &'a RepoPathUiConverter,
         conflict_marker_style: ConflictMarkerStyle,
+        copy_detection_options: CopyDetectionOptions,
         formats: Vec<DiffFormat>,
     ) -> Self {
         DiffRenderer {
             repo,
             path_converter,
             conflict_marker_style,
+            copy_detection_options,
             formats,
         }
     }
@@ -335,6 +385,11 @@ impl<'a> DiffRenderer<'a> {
     ) -> Result<(), DiffRenderError> {
         let store = self.repo.store();
         let path_converter = self.path_converter;
+        let git_attributes = GitAttributesLoader::from_tree(to_tree.clone());
+        let content_options = DiffContentOptions {
+            git_attributes: &git_attributes,
+            conflict_marker_style: self.conflict_marker_style,
+        };
         for format in &self.formats {
             match format {
                 DiffFormat::Summary => {
@@ -363,24 +418,43 @@ impl<'a> DiffRenderer<'a> {
                 DiffFormat::Git(options) => {
                     let tree_diff =
                         from_tree.diff_stream_with_copies(to_tree, matcher, copy_records);
-                    show_git_diff(
+                    show_git_diff(formatter, store, tree_diff, &content_options, options)?;
+                }
+                DiffFormat::ColorWords(options) => {
+                    let tree_diff =
+                        from_tree.diff_stream_with_copies(to_tree, matcher, copy_records);
+                    show_color_words_diff(
+                        formatter,
+                        store,
+                        tree_diff,
+                        &content_options,
+                        path_converter,
+                        options,
+                    )?;
+                }
+                DiffFormat::SideBySide(options) => {
+                    let tree_diff =
+                        from_tree.diff_stream_with_copies(to_tree, matcher, copy_records);
+                    show_side_by_side_diff(
                         formatter,
                         store,
                         tree_diff,
+                        &content_options,
+                        path_converter,
                         options,
-                        self.conflict_marker_style,
+                        width,
                     )?;
                 }
-                DiffFormat::ColorWords(options) => {
+                DiffFormat::Structural(options) => {
                     let tree_diff =
                         from_tree.diff_stream_with_copies(to_tree, matcher, copy_records);
-                    show_color_words_diff(
+                    show_structural_diff(
                         formatter,
                         store,
                         tree_diff,
+                        &content_options,
                         path_converter,
                         options,
-                        self.conflict_marker_style,
                     )?;
                 }
                 DiffFormat::Tool(tool) => {
@@ -457,8 +531,14 @@ impl<'a> DiffRenderer<'a> {
         let to_tree = commit.tree()?;
         let mut copy_records = CopyRecords::default();
         for parent_id in commit.parent_ids() {
-            let records = get_copy_records(self.repo.store(), parent_id, commit.id(), matcher)?;
-            copy_records.add_records(records)?;
+            add_copy_records(
+                &mut copy_records,
+                self.repo.store(),
+                parent_id,
+                commit.id(),
+                matcher,
+                &self.copy_detection_options,
+            )?;
         }
         self.show_diff(
             ui,
@@ -484,14 +564,71 @@ pub fn get_copy_records<'a>(
     Ok(block_on_stream(stream).filter_ok(|record| matcher.matches(&record.target)))
 }
 
+/// Adds copy records from `root` to `head` to the `copy_records`. In addition
+/// to the records reported by the backend, copies and renames are detected by
+/// content similarity.
+pub fn add_copy_records(
+    copy_records: &mut CopyRecords,
+    store: &Arc<Store>,
+    root: &CommitId,
+    head: &CommitId,
+    matcher: &dyn Matcher,
+    options: &CopyDetectionOptions,
+) -> BackendResult<()> {
+    let records = get_copy_records(store, root, head, matcher)?;
+    copy_records.add_records(records)?;
+    let root = store.get_commit(root)?;
+    let head = store.get_commit(head)?;
+    let records = find_similar_copies(&root, &head, matcher, copy_records, options)?;
+    copy_records.add_records(records.into_iter().map(Ok))
+}
+
+/// Loads options for detecting copies and renames by content similarity.
+pub fn copy_detection_options_from_settings(
+    settings: &UserSettings,
+) -> Result<CopyDetectionOptions, ConfigGetError> {
+    let enabled: bool = settings.get("diff.renames.enabled")?;
+    let threshold = {
+        let name = "diff.renames.threshold";
+        let threshold: u8 = settings.get(name)?;
+        if !(1..=100).contains(&threshold) {
+            return Err(ConfigGetError::Type {
+                name: name.to_owned(),
+                error: format!("Invalid similarity threshold: {threshold}").into(),
+                source_path: None,
+            });
+        }
+        threshold
+    };
+    Ok(CopyDetectionOptions {
+        threshold,
+        find_copies: settings.get("diff.renames.copies")?,
+        // No files are compared if the detection is disabled
+        limit: if enabled {
+            settings.get("diff.renames.limit")?
+        } else {
+            0
+        },
+    })
+}
+
 #[derive(Clone, Debug, Default, Eq, PartialEq)]
 pub struct LineDiffOptions {
     /// How equivalence of lines is tested.
     pub compare_mode: LineCompareMode,
+    /// Algorithm used to find matching lines.
+    pub algorithm: DiffAlgorithm,
     // TODO: add --ignore-blank-lines, etc. which aren't mutually exclusive.
 }
 
 impl LineDiffOptions {
+    pub fn from_settings(settings: &UserSettings) -> Result<Self, ConfigGetError> {
+        Ok(LineDiffOptions {
+            compare_mode: LineCompareMode::default(),
+            algorithm: settings.get("diff.algorithm")?,
+        })
+    }
+
     fn merge_args(&mut self, args: &DiffFormatArgs) {
         self.compare_mode = if args.ignore_all_space {
             LineCompareMode::IgnoreAllSpace
@@ -523,15 +660,211 @@ fn diff_by_line<'input, T: AsRef<[u8]> + ?Sized + 'input>(
     // post-process (similar to refine_changed_regions()) that expands unchanged
     // regions across blank lines.
     match options.compare_mode {
-        LineCompareMode::Exact => {
-            Diff::for_tokenizer(inputs, find_line_ranges, CompareBytesExactly)
+        LineCompareMode::Exact => Diff::for_tokenizer(
+            inputs,
+            find_line_ranges,
+            CompareBytesExactly,
+            options.algorithm,
+        ),
+        LineCompareMode::IgnoreAllSpace => Diff::for_tokenizer(
+            inputs,
+            find_line_ranges,
+            CompareBytesIgnoreAllWhitespace,
+            options.algorithm,
+        ),
+        LineCompareMode::IgnoreSpaceChange => Diff::for_tokenizer(
+            inputs,
+            find_line_ranges,
+            CompareBytesIgnoreWhitespaceAmount,
+            options.algorithm,
+        ),
+    }
+}
+
+/// Minimum number of alphanumeric characters in a block of moved lines. Smaller
+/// blocks such as closing braces are too common to be interesting. This is the
+/// same threshold as Git's.
+const MOVED_BLOCK_MIN_ALNUM_COUNT: usize = 20;
+
+/// Removed and added lines of a file which were detected as moved.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct FileMovedLines {
+    /// 0-based line numbers in the left content.
+    left: HashSet<usize>,
+    /// 0-based line numbers in the right content.
+    right: HashSet<usize>,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct ChangedLine<'a> {
+    file_index: usize,
+    line_number: usize,
+    content: &'a [u8],
+}
+
+impl ChangedLine<'_> {
+    fn is_followed_by(&self, other: &Self) -> bool {
+        self.file_index == other.file_index && self.line_number + 1 == other.line_number
+    }
+}
+
+/// Finds blocks of consecutive added lines which were removed somewhere else
+/// in the same order. Returns moved lines per file.
+fn detect_moved_lines(contents: &[[&[u8]; 2]], options: &LineDiffOptions) -> Vec<FileMovedLines> {
+    let mut removed_lines = vec![];
+    let mut added_lines = vec![];
+    for (file_index, inputs) in contents.iter().enumerate() {
+        let diff = diff_by_line(inputs, options);
+        let mut line_numbers = [0, 0];
+        for hunk in diff.hunks() {
+            for (side, content) in hunk.contents.iter().enumerate() {
+                for line in content.split_inclusive(|b| *b == b'\n') {
+                    if hunk.kind == DiffHunkKind::Different {
+                        let changed_lines = [&mut removed_lines, &mut added_lines];
+                        changed_lines[side].push(ChangedLine {
+                            file_index,
+                            line_number: line_numbers[side],
+                            content: line.strip_suffix(b"\n").unwrap_or(line),
+                        });
+                    }
+                    line_numbers[side] += 1;
+                }
+            }
+        }
+    }
+
+    let mut removed_positions: HashMap<&[u8], Vec<usize>> = HashMap::new();
+    for (pos, line) in removed_lines.iter().enumerate() {
+        removed_positions.entry(line.content).or_default().push(pos);
+    }
+    let mut moved_lines = vec![FileMovedLines::default(); contents.len()];
+    let mut mark_moved = |added_range: Range<usize>, removed_ends: &[usize]| {
+        let Some(&removed_end) = removed_ends.first() else {
+            return;
+        };
+        let added = &added_lines[added_range.clone()];
+        let removed = &removed_lines[removed_end + 1 - added_range.len()..=removed_end];
+        let alnum_count: usize = added
+            .iter()
+            .map(|line| {
+                line.content
+                    .iter()
+                    .filter(|b| b.is_ascii_alphanumeric())
+                    .count()
+            })
+            .sum();
+        if alnum_count < MOVED_BLOCK_MIN_ALNUM_COUNT {
+            return;
+        }
+        for line in removed {
+            moved_lines[line.file_index].left.insert(line.line_number);
+        }
+        for line in added {
+            moved_lines[line.file_index].right.insert(line.line_number);
+        }
+    };
+
+    // Extend the current block as long as the next added line follows any of
+    // the removed blocks matched so far.
+    let mut block_start = 0;
+    let mut removed_ends: Vec<usize> = vec![];
+    for (pos, line) in added_lines.iter().enumerate() {
+        if pos > 0 && added_lines[pos - 1].is_followed_by(line) {
+            let next_ends = removed_ends
+                .iter()
+                .map(|&end| end + 1)
+                .filter(|&end| {
+                    end < removed_lines.len()
+                        && removed_lines[end - 1].is_followed_by(&removed_lines[end])
+                        && removed_lines[end].content == line.content
+                })
+                .collect_vec();
+            if !next_ends.is_empty() {
+                removed_ends = next_ends;
+                continue;
+            }
         }
-        LineCompareMode::IgnoreAllSpace => {
-            Diff::for_tokenizer(inputs, find_line_ranges, CompareBytesIgnoreAllWhitespace)
+        mark_moved(block_start..pos, &removed_ends);
+        block_start = pos;
+        removed_ends = removed_positions
+            .get(line.content)
+            .cloned()
+            .unwrap_or_default();
+    }
+    mark_moved(block_start..added_lines.len(), &removed_ends);
+    moved_lines
+}
+
+/// Materializes all diff entries to detect moved lines across files.
+///
+/// File contents are read into memory so that the returned entries can still
+/// be rendered.
+async fn materialize_diff_with_moved_lines(
+    store: &Store,
+    tree_diff: BoxStream<'_, CopiesTreeDiffEntry>,
+    options: &LineDiffOptions,
+    conflict_marker_style: ConflictMarkerStyle,
+) -> Result<(Vec<MaterializedTreeDiffEntry>, Vec<FileMovedLines>), DiffRenderError> {
+    let mut entries: Vec<MaterializedTreeDiffEntry> =
+        materialized_diff_stream(store, tree_diff).collect().await;
+    let mut contents = vec![];
+    for MaterializedTreeDiffEntry { path, values } in &mut entries {
+        let [left, right] = match values {
+            Ok((left_value, right_value)) => [
+                buffer_text_content(path.source(), left_value, conflict_marker_style)?,
+                buffer_text_content(path.target(), right_value, conflict_marker_style)?,
+            ],
+            Err(_) => [vec![], vec![]],
+        };
+        contents.push([left, right]);
+    }
+    let inputs = contents
+        .iter()
+        .map(|[left, right]| [left.as_slice(), right.as_slice()])
+        .collect_vec();
+    let moved_lines = detect_moved_lines(&inputs, options);
+    Ok((entries, moved_lines))
+}
+
+/// Returns the text content of the `value`. If the `value` is a file, its
+/// reader is replaced so the content can be read again. Returns empty content
+/// if the `value` isn't a text file.
+fn buffer_text_content(
+    path: &RepoPath,
+    value: &mut MaterializedTreeValue,
+    conflict_marker_style: ConflictMarkerStyle,
+) -> BackendResult<Vec<u8>> {
+    match value {
+        MaterializedTreeValue::File { id, reader, .. } => {
+            let content = file_content_for_diff(reader).map_err(|err| BackendError::ReadFile {
+                path: path.to_owned(),
+                id: id.clone(),
+                source: err.into(),
+            })?;
+            *reader = Box::new(io::Cursor::new(content.contents.clone()));
+            if content.is_binary {
+                Ok(vec![])
+            } else {
+                Ok(content.contents)
+            }
         }
-        LineCompareMode::IgnoreSpaceChange => {
-            Diff::for_tokenizer(inputs, find_line_ranges, CompareBytesIgnoreWhitespaceAmount)
+        MaterializedTreeValue::FileConflict { contents, .. } => {
+            Ok(materialize_merge_result_to_bytes(contents, conflict_marker_style).into())
         }
+        _ => Ok(vec![]),
+    }
+}
+
+/// Calls `write_inner` with the "moved" label if `is_moved` is true.
+fn with_moved_label(
+    formatter: &mut dyn Formatter,
+    is_moved: bool,
+    write_inner: impl FnOnce(&mut dyn Formatter) -> io::Result<()>,
+) -> io::Result<()> {
+    if is_moved {
+        formatter.with_label("moved", write_inner)
+    } else {
+        write_inner(formatter)
     }
 }
 
@@ -543,6 +876,8 @@ pub struct ColorWordsDiffOptions {
     pub line_diff: LineDiffOptions,
     /// Maximum number of removed/added word alternation to inline.
     pub max_inline_alternation: Option<usize>,
+    /// Whether to highlight moved lines.
+    pub color_moved: bool,
 }
 
 impl ColorWordsDiffOptions {
@@ -560,8 +895,9 @@ impl ColorWordsDiffOptions {
         };
         Ok(ColorWordsDiffOptions {
             context: settings.get("diff.color-words.context")?,
-            line_diff: LineDiffOptions::default(),
+            line_diff: LineDiffOptions::from_settings(settings)?,
             max_inline_alternation,
+            color_moved: settings.get("diff.color-moved")?,
         })
     }
 
@@ -569,15 +905,19 @@ impl ColorWordsDiffOptions {
         if let Some(context) = args.context {
             self.context = context;
         }
+        if args.color_moved {
+            self.color_moved = true;
+        }
         self.line_diff.merge_args(args);
     }
 }
 
-fn show_color_words_diff_hunks(
+pub(crate) fn show_color_words_diff_hunks(
     formatter: &mut dyn Formatter,
     left: &[u8],
     right: &[u8],
     options: &ColorWordsDiffOptions,
+    moved_lines: &FileMovedLines,
 ) -> io::Result<()> {
     let line_diff = diff_by_line([left, right], &options.line_diff);
     let mut line_number = DiffLineNumber { left: 1, right: 1 };
@@ -602,8 +942,13 @@ fn show_color_words_diff_hunks(
                 )?;
                 contexts.clear();
                 emitted = true;
-                line_number =
-                    show_color_words_diff_lines(formatter, &hunk.contents, line_number, options)?;
+                line_number = show_color_words_diff_lines(
+                    formatter,
+                    &hunk.contents,
+                    line_number,
+                    options,
+                    moved_lines,
+                )?;
             }
         }
     }
@@ -655,6 +1000,7 @@ fn show_color_words_context_lines(
                 show_color_words_inline_hunks(
                     formatter,
                     &[(DiffLineHunkSide::Both, line.as_ref())],
+                    false,
                 )?;
                 line_number.left += 1;
                 line_number.right += 1;
@@ -663,11 +1009,13 @@ fn show_color_words_context_lines(
         } else {
             let left = left_lines.concat();
             let right = right_lines.concat();
+            // Lines matched ignoring whitespace aren't moved.
             show_color_words_diff_lines(
                 formatter,
                 &[BStr::new(&left), BStr::new(&right)],
                 line_number,
                 options,
+                &FileMovedLines::default(),
             )
         }
     };
@@ -699,7 +1047,10 @@ fn show_color_words_diff_lines(
     contents: &[&BStr],
     mut line_number: DiffLineNumber,
     options: &ColorWordsDiffOptions,
+    moved_lines: &FileMovedLines,
 ) -> io::Result<DiffLineNumber> {
+    let is_left_moved = |line_number: u32| moved_lines.left.contains(&(line_number as usize - 1));
+    let is_right_moved = |line_number: u32| moved_lines.right.contains(&(line_number as usize - 1));
     let word_diff_hunks = Diff::by_word(contents).hunks().collect_vec();
     let can_inline = match options.max_inline_alternation {
         None => true,     // unlimited
@@ -713,35 +1064,40 @@ fn show_color_words_diff_lines(
         let mut diff_line_iter =
             DiffLineIterator::with_line_number(word_diff_hunks.iter(), line_number);
         for diff_line in diff_line_iter.by_ref() {
+            let has_left_content = diff_line.has_left_content();
+            let has_right_content = diff_line.has_right_content();
             show_color_words_line_number(
                 formatter,
-                diff_line
-                    .has_left_content()
-                    .then_some(diff_line.line_number.left),
-                diff_line
-                    .has_right_content()
-                    .then_some(diff_line.line_number.right),
+                has_left_content.then_some(diff_line.line_number.left),
+                has_right_content.then_some(diff_line.line_number.right),
             )?;
-            show_color_words_inline_hunks(formatter, &diff_line.hunks)?;
+            let is_moved = match (has_left_content, has_right_content) {
+                (true, false) => is_left_moved(diff_line.line_number.left),
+                (false, true) => is_right_moved(diff_line.line_number.right),
+                _ => false,
+            };
+            show_color_words_inline_hunks(formatter, &diff_line.hunks, is_moved)?;
         }
         line_number = diff_line_iter.next_line_number();
     } else {
         let (left_lines, right_lines) = unzip_diff_hunks_to_lines(&word_diff_hunks);
         for tokens in &left_lines {
             show_color_words_line_number(formatter, Some(line_number.left), None)?;
-            show_color_words_single_sided_line(formatter, tokens, "removed")?;
+            let is_moved = is_left_moved(line_number.left);
+            show_color_words_single_sided_line(formatter, tokens, "removed", is_moved)?;
             line_number.left += 1;
         }
         for tokens in &right_lines {
             show_color_words_line_number(formatter, None, Some(line_number.right))?;
-            show_color_words_single_sided_line(formatter, tokens, "added")?;
+            let is_moved = is_right_moved(line_number.right);
+            show_color_words_single_sided_line(formatter, tokens, "added", is_moved)?;
             line_number.right += 1;
         }
     }
     Ok(line_number)
 }
 
-fn show_color_words_line_number(
+pub(crate) fn show_color_words_line_number(
     formatter: &mut dyn Formatter,
     left_line_number: Option<u32>,
     right_line_number: Option<u32>,
@@ -766,9 +1122,10 @@ fn show_color_words_line_number(
 }
 
 /// Prints line hunks which may contain tokens originating from both sides.
-fn show_color_words_inline_hunks(
+pub(crate) fn show_color_words_inline_hunks(
     formatter: &mut dyn Formatter,
     line_hunks: &[(DiffLineHunkSide, &BStr)],
+    is_moved: bool,
 ) -> io::Result<()> {
     for (side, data) in line_hunks {
         let label = match side {
@@ -778,7 +1135,9 @@ fn show_color_words_inline_hunks(
         };
         if let Some(label) = label {
             formatter.with_label(label, |formatter| {
-                formatter.with_label("token", |formatter| formatter.write_all(data))
+                with_moved_label(formatter, is_moved, |formatter| {
+                    formatter.with_label("token", |formatter| formatter.write_all(data))
+                })
             })?;
         } else {
             formatter.write_all(data)?;
@@ -796,8 +1155,13 @@ fn show_color_words_single_sided_line(
     formatter: &mut dyn Formatter,
     tokens: &[(DiffTokenType, &[u8])],
     label: &str,
+    is_moved: bool,
 ) -> io::Result<()> {
-    formatter.with_label(label, |formatter| show_diff_line_tokens(formatter, tokens))?;
+    formatter.with_label(label, |formatter| {
+        with_moved_label(formatter, is_moved, |formatter| {
+            show_diff_line_tokens(formatter, tokens)
+        })
+    })?;
     let (_, data) = tokens.last().expect("diff line must not be empty");
     if !data.ends_with(b"\n") {
         writeln!(formatter)?;
@@ -878,6 +1242,25 @@ fn file_content_for_diff(reader: &mut dyn io::Read) -> io::Result<FileContent> {
     })
 }
 
+/// Options for loading the contents of files to be diffed.
+#[derive(Clone, Copy, Debug)]
+pub struct DiffContentOptions<'a> {
+    /// Attributes of the files in the new tree.
+    pub git_attributes: &'a GitAttributesLoader,
+    /// How conflicted files are materialized.
+    pub conflict_marker_style: ConflictMarkerStyle,
+}
+
+impl DiffContentOptions<'_> {
+    /// Returns true if the `diff` attribute is unset for the file, in which
+    /// case the file should be treated as binary regardless of its contents.
+    fn is_binary(&self, path: &RepoPath) -> bool {
+        self.git_attributes
+            .attributes(path)
+            .is_ok_and(|attributes| attributes.is_binary_for_diff())
+    }
+}
+
 fn diff_content(
     path: &RepoPath,
     value: MaterializedTreeValue,
@@ -948,13 +1331,62 @@ pub fn show_color_words_diff(
     formatter: &mut dyn Formatter,
     store: &Store,
     tree_diff: BoxStream<CopiesTreeDiffEntry>,
+    content_options: &DiffContentOptions,
     path_converter: &RepoPathUiConverter,
     options: &ColorWordsDiffOptions,
-    conflict_marker_style: ConflictMarkerStyle,
 ) -> Result<(), DiffRenderError> {
-    let mut diff_stream = materialized_diff_stream(store, tree_diff);
+    show_described_file_diffs(
+        formatter,
+        store,
+        tree_diff,
+        content_options,
+        path_converter,
+        options.color_moved.then_some(&options.line_diff),
+        |formatter, _path, left, right, moved_lines| {
+            show_color_words_diff_hunks(formatter, left, right, options, moved_lines)
+        },
+    )
+}
+
+/// Shows a header describing the change of each file, followed by the content
+/// hunks rendered by `show_hunks`.
+///
+/// If `moved_line_options` is specified, moved lines are detected across files
+/// and passed to `show_hunks`.
+fn show_described_file_diffs(
+    formatter: &mut dyn Formatter,
+    store: &Store,
+    tree_diff: BoxStream<CopiesTreeDiffEntry>,
+    content_options: &DiffContentOptions,
+    path_converter: &RepoPathUiConverter,
+    moved_line_options: Option<&LineDiffOptions>,
+    mut show_hunks: impl FnMut(
+        &mut dyn Formatter,
+        &RepoPath,
+        &[u8],
+        &[u8],
+        &FileMovedLines,
+    ) -> io::Result<()>,
+) -> Result<(), DiffRenderError> {
     async {
+        let (mut diff_stream, moved_lines) = if let Some(line_diff) = moved_line_options {
+            let (entries, moved_lines) = materialize_diff_with_moved_lines(
+                store,
+                tree_diff,
+                line_diff,
+                content_options.conflict_marker_style,
+            )
+            .await?;
+            (stream::iter(entries).boxed_local(), moved_lines)
+        } else {
+            let diff_stream = materialized_diff_stream(store, tree_diff);
+            (diff_stream.boxed_local(), vec![])
+        };
+        let no_moved_lines = FileMovedLines::default();
+        let mut index = 0;
         while let Some(MaterializedTreeDiffEntry { path, values }) = diff_stream.next().await {
+            let file_moved_lines = moved_lines.get(index).unwrap_or(&no_moved_lines);
+            index += 1;
             let left_path = path.source();
             let right_path = path.target();
             let left_ui_path = path_converter.format_file_path(left_path);
@@ -986,13 +1418,24 @@ pub fn show_color_words_diff(
                     formatter.labeled("header"),
                     "Added {description} {right_ui_path}:"
                 )?;
-                let right_content = diff_content(right_path, right_value, conflict_marker_style)?;
+                let mut right_content = diff_content(
+                    right_path,
+                    right_value,
+                    content_options.conflict_marker_style,
+                )?;
+                right_content.is_binary |= content_options.is_binary(right_path);
                 if right_content.is_empty() {
                     writeln!(formatter.labeled("empty"), "    (empty)")?;
                 } else if right_content.is_binary {
                     writeln!(formatter.labeled("binary"), "    (binary)")?;
                 } else {
-                    show_color_words_diff_hunks(formatter, &[], &right_content.contents, options)?;
+                    show_hunks(
+                        formatter,
+                        right_path,
+                        &[],
+                        &right_content.contents,
+                        file_moved_lines,
+                    )?;
                 }
             } else if right_value.is_present() {
                 let description = match (&left_value, &right_value) {
@@ -1048,8 +1491,15 @@ pub fn show_color_words_diff(
                         )
                     }
                 };
-                let left_content = diff_content(left_path, left_value, conflict_marker_style)?;
-                let right_content = diff_content(right_path, right_value, conflict_marker_style)?;
+                let mut left_content =
+                    diff_content(left_path, left_value, content_options.conflict_marker_style)?;
+                let mut right_content = diff_content(
+                    right_path,
+                    right_value,
+                    content_options.conflict_marker_style,
+                )?;
+                left_content.is_binary |= content_options.is_binary(left_path);
+                right_content.is_binary |= content_options.is_binary(right_path);
                 if left_path == right_path {
                     writeln!(
                         formatter.labeled("header"),
@@ -1064,11 +1514,12 @@ pub fn show_color_words_diff(
                 if left_content.is_binary || right_content.is_binary {
                     writeln!(formatter.labeled("binary"), "    (binary)")?;
                 } else {
-                    show_color_words_diff_hunks(
+                    show_hunks(
                         formatter,
+                        right_path,
                         &left_content.contents,
                         &right_content.contents,
-                        options,
+                        file_moved_lines,
                     )?;
                 }
             } else {
@@ -1077,13 +1528,21 @@ pub fn show_color_words_diff(
                     formatter.labeled("header"),
                     "Removed {description} {right_ui_path}:"
                 )?;
-                let left_content = diff_content(left_path, left_value, conflict_marker_style)?;
+                let mut left_content =
+                    diff_content(left_path, left_value, content_options.conflict_marker_style)?;
+                left_content.is_binary |= content_options.is_binary(left_path);
                 if left_content.is_empty() {
                     writeln!(formatter.labeled("empty"), "    (empty)")?;
                 } else if left_content.is_binary {
                     writeln!(formatter.labeled("binary"), "    (binary)")?;
                 } else {
-                    show_color_words_diff_hunks(formatter, &left_content.contents, &[], options)?;
+                    show_hunks(
+                        formatter,
+                        left_path,
+                        &left_content.contents,
+                        &[],
+                        file_moved_lines,
+                    )?;
                 }
             }
         }
@@ -1092,6 +1551,356 @@ pub fn show_color_words_diff(
     .block_on()
 }
 
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StructuralDiffOptions {
+    /// Number of context lines to show.
+    pub context: usize,
+    /// Options to render files which can't be parsed.
+    pub fallback: ColorWordsDiffOptions,
+}
+
+impl StructuralDiffOptions {
+    pub fn from_settings(settings: &UserSettings) -> Result<Self, ConfigGetError> {
+        let context = settings.get("diff.structural.context")?;
+        let fallback = ColorWordsDiffOptions {
+            context,
+            ..ColorWordsDiffOptions::from_settings(settings)?
+        };
+        Ok(StructuralDiffOptions { context, fallback })
+    }
+
+    fn merge_args(&mut self, args: &DiffFormatArgs) {
+        if let Some(context) = args.context {
+            self.context = context;
+        }
+        self.fallback.merge_args(args);
+    }
+}
+
+/// Shows syntax-aware diff of supported source files. Other files are shown in
+/// color-words format.
+#[cfg(feature = "structural-diff")]
+pub fn show_structural_diff(
+    formatter: &mut dyn Formatter,
+    store: &Store,
+    tree_diff: BoxStream<CopiesTreeDiffEntry>,
+    content_options: &DiffContentOptions,
+    path_converter: &RepoPathUiConverter,
+    options: &StructuralDiffOptions,
+) -> Result<(), DiffRenderError> {
+    show_described_file_diffs(
+        formatter,
+        store,
+        tree_diff,
+        content_options,
+        path_converter,
+        None,
+        |formatter, path, left, right, _moved_lines| {
+            structural_diff::show_structural_diff_hunks(formatter, path, left, right, options)
+        },
+    )
+}
+
+#[cfg(not(feature = "structural-diff"))]
+pub fn show_structural_diff(
+    _formatter: &mut dyn Formatter,
+    _store: &Store,
+    _tree_diff: BoxStream<CopiesTreeDiffEntry>,
+    _content_options: &DiffContentOptions,
+    _path_converter: &RepoPathUiConverter,
+    _options: &StructuralDiffOptions,
+) -> Result<(), DiffRenderError> {
+    Err(DiffRenderError::StructuralDiffUnsupported)
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SideBySideDiffOptions {
+    /// Number of context lines to show.
+    pub context: usize,
+    /// How lines are tokenized and compared.
+    pub line_diff: LineDiffOptions,
+    /// Whether to highlight changed words in removed and added lines.
+    pub highlight_words: bool,
+}
+
+impl SideBySideDiffOptions {
+    pub fn from_settings(settings: &UserSettings) -> Result<Self, ConfigGetError> {
+        Ok(SideBySideDiffOptions {
+            context: settings.get("diff.side-by-side.context")?,
+            line_diff: LineDiffOptions::from_settings(settings)?,
+            highlight_words: settings.get("diff.side-by-side.highlight-words")?,
+        })
+    }
+
+    fn merge_args(&mut self, args: &DiffFormatArgs) {
+        if let Some(context) = args.context {
+            self.context = context;
+        }
+        self.line_diff.merge_args(args);
+    }
+}
+
+/// Shows old and new contents in two columns which fit in the `width`.
+pub fn show_side_by_side_diff(
+    formatter: &mut dyn Formatter,
+    store: &Store,
+    tree_diff: BoxStream<CopiesTreeDiffEntry>,
+    content_options: &DiffContentOptions,
+    path_converter: &RepoPathUiConverter,
+    options: &SideBySideDiffOptions,
+    width: usize,
+) -> Result<(), DiffRenderError> {
+    show_described_file_diffs(
+        formatter,
+        store,
+        tree_diff,
+        content_options,
+        path_converter,
+        None,
+        |formatter, _path, left, right, _moved_lines| {
+            show_side_by_side_diff_hunks(formatter, left, right, options, width)
+        },
+    )
+}
+
+fn show_side_by_side_diff_hunks(
+    formatter: &mut dyn Formatter,
+    left: &[u8],
+    right: &[u8],
+    options: &SideBySideDiffOptions,
+    width: usize,
+) -> io::Result<()> {
+    const SKIPPED_CONTEXT_LINE: &str = "    ...\n";
+    let unified_options = UnifiedDiffOptions {
+        context: options.context,
+        line_diff: options.line_diff.clone(),
+        color_moved: false,
+        highlight_words: options.highlight_words,
+        binary: false,
+    };
+    let mut left_line_end = 0;
+    for hunk in unified_diff_hunks(left, right, &unified_options) {
+        if hunk.left_line_range.start > left_line_end {
+            write!(formatter, "{SKIPPED_CONTEXT_LINE}")?;
+        }
+        left_line_end = hunk.left_line_range.end;
+        // Each column consists of a line number padded to at least 4 digits, a
+        // space, and the content. The columns are separated by " │ ".
+        let max_line_number = max(hunk.left_line_range.end, hunk.right_line_range.end);
+        let line_number_width = max(max_line_number.to_string().len(), 4);
+        let columns = ColumnWidths {
+            line_number: line_number_width,
+            content: max(width.saturating_sub(2 * (line_number_width + 1) + 3) / 2, 1),
+        };
+        let mut line_number = DiffLineNumber {
+            left: (hunk.left_line_range.start + 1).try_into().unwrap(),
+            right: (hunk.right_line_range.start + 1).try_into().unwrap(),
+        };
+        let mut lines = hunk.lines.iter().peekable();
+        while let Some((line_type, tokens)) = lines.next() {
+            if *line_type == DiffLineType::Context {
+                show_side_by_side_row(
+                    formatter,
+                    Some((line_number.left, None, tokens)),
+                    Some((line_number.right, None, tokens)),
+                    &columns,
+                )?;
+                line_number.left += 1;
+                line_number.right += 1;
+                continue;
+            }
+            // Pair up removed and added lines
+            let mut changed_lines = vec![(line_type, tokens)];
+            while let Some((line_type, tokens)) =
+                lines.next_if(|(line_type, _)| *line_type != DiffLineType::Context)
+            {
+                changed_lines.push((line_type, tokens));
+            }
+            let (removed_lines, added_lines): (Vec<_>, Vec<_>) = changed_lines
+                .into_iter()
+                .partition(|(line_type, _)| **line_type == DiffLineType::Removed);
+            let mut removed_lines = removed_lines.into_iter().map(|(_, tokens)| tokens);
+            let mut added_lines = added_lines.into_iter().map(|(_, tokens)| tokens);
+            loop {
+                let left = removed_lines.next().map(|tokens| {
+                    line_number.left += 1;
+                    (line_number.left - 1, Some("removed"), tokens)
+                });
+                let right = added_lines.next().map(|tokens| {
+                    line_number.right += 1;
+                    (line_number.right - 1, Some("added"), tokens)
+                });
+                if left.is_none() && right.is_none() {
+                    break;
+                }
+                show_side_by_side_row(formatter, left, right, &columns)?;
+            }
+        }
+    }
+    let num_left_lines = left.split_inclusive(|b| *b == b'\n').count();
+    if left_line_end > 0 && num_left_lines > left_line_end {
+        write!(formatter, "{SKIPPED_CONTEXT_LINE}")?;
+    }
+    Ok(())
+}
+
+/// Line number, label, and tokens of a line shown in a side-by-side diff.
+type SideBySideCell<'a, 'content> = (u32, Option<&'static str>, &'a DiffTokenVec<'content>);
+
+/// Widths of the parts of a side-by-side diff column.
+#[derive(Clone, Copy, Debug)]
+struct ColumnWidths {
+    line_number: usize,
+    content: usize,
+}
+
+/// Shows a pair of lines, which are wrapped at the content width.
+fn show_side_by_side_row(
+    formatter: &mut dyn Formatter,
+    left: Option<SideBySideCell>,
+    right: Option<SideBySideCell>,
+    columns: &ColumnWidths,
+) -> io::Result<()> {
+    let column_width = columns.content;
+    let left_lines = left.map(|(_, _, tokens)| SideBySideLine::new(tokens, column_width));
+    let right_lines = right.map(|(_, _, tokens)| SideBySideLine::new(tokens, column_width));
+    let num_rows = max(
+        left_lines
+            .as_ref()
+            .map_or(0, |line| line.wrapped_ranges.len()),
+        right_lines
+            .as_ref()
+            .map_or(0, |line| line.wrapped_ranges.len()),
+    );
+    for row in 0..num_rows {
+        match (left, &left_lines) {
+            (Some((line_number, label, _)), Some(line)) => {
+                show_side_by_side_line_number(formatter, "removed", line_number, columns, row)?;
+                let written_width = line.show_row(formatter, label, row)?;
+                write!(
+                    formatter,
+                    "{:1$}",
+                    "",
+                    column_width.saturating_sub(written_width)
+                )?;
+            }
+            _ => write!(
+                formatter,
+                "{:1$}",
+                "",
+                column_width + columns.line_number + 1
+            )?,
+        }
+        write!(formatter, " │")?;
+        if let (Some((line_number, label, _)), Some(line)) = (right, &right_lines) {
+            write!(formatter, " ")?;
+            show_side_by_side_line_number(formatter, "added", line_number, columns, row)?;
+            line.show_row(formatter, label, row)?;
+        }
+        writeln!(formatter)?;
+    }
+    Ok(())
+}
+
+fn show_side_by_side_line_number(
+    formatter: &mut dyn Formatter,
+    label: &str,
+    line_number: u32,
+    columns: &ColumnWidths,
+    row: usize,
+) -> io::Result<()> {
+    let width = columns.line_number;
+    if row == 0 {
+        formatter.with_label(label, |formatter| {
+            write!(formatter.labeled("line_number"), "{line_number:>width$}")
+        })?;
+        write!(formatter, " ")
+    } else {
+        write!(formatter, "{:1$}", "", width + 1)
+    }
+}
+
+/// Line content split into rows.
+struct SideBySideLine {
+    /// Content with tabs expanded and the trailing newline removed.
+    text: Vec<u8>,
+    token_ranges: Vec<(DiffTokenType, Range<usize>)>,
+    wrapped_ranges: Vec<Range<usize>>,
+}
+
+impl SideBySideLine {
+    fn new(tokens: &[(DiffTokenType, &[u8])], width: usize) -> Self {
+        const TAB_WIDTH: usize = 4;
+        let mut text = vec![];
+        let mut token_ranges = vec![];
+        for (token_type, content) in tokens {
+            let start = text.len();
+            for &b in content.strip_suffix(b"\n").unwrap_or(content) {
+                if b == b'\t' {
+                    text.resize(text.len() + TAB_WIDTH, b' ');
+                } else {
+                    text.push(b);
+                }
+            }
+            token_ranges.push((*token_type, start..text.len()));
+        }
+        let text_range = 0..text.len();
+        let wrapped_ranges = if String::from_utf8_lossy(&text).width() <= width {
+            // Don't wrap at whitespace if not needed.
+            vec![text_range]
+        } else {
+            // Each wrapped line is a sub-slice of the text.
+            text_util::wrap_bytes_breaking_words(&text, width)
+                .into_iter()
+                .map(|line| {
+                    let start = line.as_ptr() as usize - text.as_ptr() as usize;
+                    start..start + line.len()
+                })
+                .collect()
+        };
+        SideBySideLine {
+            text,
+            token_ranges,
+            wrapped_ranges,
+        }
+    }
+
+    /// Writes the `row`, and returns the display width of the written text.
+    fn show_row(
+        &self,
+        formatter: &mut dyn Formatter,
+        label: Option<&str>,
+        row: usize,
+    ) -> io::Result<usize> {
+        let Some(range) = self.wrapped_ranges.get(row) else {
+            return Ok(0);
+        };
+        let show_tokens = |formatter: &mut dyn Formatter| {
+            for (token_type, token_range) in &self.token_ranges {
+                let start = max(token_range.start, range.start);
+                let end = token_range.end.min(range.end);
+                if start >= end {
+                    continue;
+                }
+                let data = &self.text[start..end];
+                match token_type {
+                    DiffTokenType::Matching => formatter.write_all(data)?,
+                    DiffTokenType::Different => {
+                        formatter.with_label("token", |formatter| formatter.write_all(data))?;
+                    }
+                }
+            }
+            io::Result::Ok(())
+        };
+        if let Some(label) = label {
+            formatter.with_label(label, show_tokens)?;
+        } else {
+            show_tokens(formatter)?;
+        }
+        Ok(String::from_utf8_lossy(&self.text[range.clone()]).width())
+    }
+}
+
 pub fn show_file_by_file_diff(
     ui: &Ui,
     formatter: &mut dyn Formatter,
@@ -1256,13 +2065,22 @@ pub struct UnifiedDiffOptions {
     pub context: usize,
     /// How lines are tokenized and compared.
     pub line_diff: LineDiffOptions,
+    /// Whether to highlight moved lines.
+    pub color_moved: bool,
+    /// Whether to highlight changed words in removed and added lines.
+    pub highlight_words: bool,
+    /// Whether to emit the contents of binary files as Git binary patches.
+    pub binary: bool,
 }
 
 impl UnifiedDiffOptions {
     pub fn from_settings(settings: &UserSettings) -> Result<Self, ConfigGetError> {
         Ok(UnifiedDiffOptions {
             context: settings.get("diff.git.context")?,
-            line_diff: LineDiffOptions::default(),
+            line_diff: LineDiffOptions::from_settings(settings)?,
+            color_moved: settings.get("diff.color-moved")?,
+            highlight_words: settings.get("diff.git.highlight-words")?,
+            binary: false,
         })
     }
 
@@ -1270,6 +2088,9 @@ impl UnifiedDiffOptions {
         if let Some(context) = args.context {
             self.context = context;
         }
+        if args.color_moved {
+            self.color_moved = true;
+        }
         self.line_diff.merge_args(args);
     }
 }
@@ -1367,12 +2188,22 @@ fn unified_diff_hunks<'content>(
                 // The next hunk should be of DiffHunk::Different type if any.
                 current_hunk.extend_context_lines(before_lines.into_iter().rev());
             }
-            DiffHunkKind::Different => {
+            DiffHunkKind::Different if options.highlight_words => {
                 let (left_lines, right_lines) =
                     unzip_diff_hunks_to_lines(Diff::by_word(hunk.contents).hunks());
                 current_hunk.extend_removed_lines(left_lines);
                 current_hunk.extend_added_lines(right_lines);
             }
+            DiffHunkKind::Different => {
+                let [left, right] = hunk.contents[..].try_into().unwrap();
+                let to_lines = |content: &'content [u8]| {
+                    content
+                        .split_inclusive(|b| *b == b'\n')
+                        .map(|line| vec![(DiffTokenType::Matching, line)])
+                };
+                current_hunk.extend_removed_lines(to_lines(left));
+                current_hunk.extend_added_lines(to_lines(right));
+            }
         }
     }
     if !current_hunk.lines.is_empty() {
@@ -1438,11 +2269,15 @@ where
     (left_lines, right_lines)
 }
 
+/// Writes unified diff hunks. The `line_offsets` are added to the line numbers
+/// in the hunk headers, which is useful if the contents are parts of files.
 fn show_unified_diff_hunks(
     formatter: &mut dyn Formatter,
     left_content: &[u8],
     right_content: &[u8],
+    line_offsets: [usize; 2],
     options: &UnifiedDiffOptions,
+    moved_lines: &FileMovedLines,
 ) -> io::Result<()> {
     // "If the chunk size is 0, the first number is one lower than one would
     // expect." - https://www.artima.com/weblogs/viewpost.jsp?thread=164293
@@ -1459,7 +2294,14 @@ fn show_unified_diff_hunks(
         }
     }
 
-    for hunk in unified_diff_hunks(left_content, right_content, options) {
+    for mut hunk in unified_diff_hunks(left_content, right_content, options) {
+        let mut left_line_number = hunk.left_line_range.start;
+        let mut right_line_number = hunk.right_line_range.start;
+        let [left_offset, right_offset] = line_offsets;
+        hunk.left_line_range =
+            hunk.left_line_range.start + left_offset..hunk.left_line_range.end + left_offset;
+        hunk.right_line_range =
+            hunk.right_line_range.start + right_offset..hunk.right_line_range.end + right_offset;
         writeln!(
             formatter.labeled("hunk_header"),
             "@@ -{},{} +{},{} @@",
@@ -1469,14 +2311,28 @@ fn show_unified_diff_hunks(
             hunk.right_line_range.len()
         )?;
         for (line_type, tokens) in &hunk.lines {
-            let (label, sigil) = match line_type {
-                DiffLineType::Context => ("context", " "),
-                DiffLineType::Removed => ("removed", "-"),
-                DiffLineType::Added => ("added", "+"),
+            let (label, sigil, is_moved) = match line_type {
+                DiffLineType::Context => {
+                    left_line_number += 1;
+                    right_line_number += 1;
+                    ("context", " ", false)
+                }
+                DiffLineType::Removed => {
+                    left_line_number += 1;
+                    let is_moved = moved_lines.left.contains(&(left_line_number - 1));
+                    ("removed", "-", is_moved)
+                }
+                DiffLineType::Added => {
+                    right_line_number += 1;
+                    let is_moved = moved_lines.right.contains(&(right_line_number - 1));
+                    ("added", "+", is_moved)
+                }
             };
             formatter.with_label(label, |formatter| {
-                write!(formatter, "{sigil}")?;
-                show_diff_line_tokens(formatter, tokens)
+                with_moved_label(formatter, is_moved, |formatter| {
+                    write!(formatter, "{sigil}")?;
+                    show_diff_line_tokens(formatter, tokens)
+                })
             })?;
             let (_, content) = tokens.last().expect("hunk line must not be empty");
             if !content.ends_with(b"\n") {
@@ -1506,20 +2362,43 @@ pub fn show_git_diff(
     formatter: &mut dyn Formatter,
     store: &Store,
     tree_diff: BoxStream<CopiesTreeDiffEntry>,
+    content_options: &DiffContentOptions,
     options: &UnifiedDiffOptions,
-    conflict_marker_style: ConflictMarkerStyle,
 ) -> Result<(), DiffRenderError> {
-    let mut diff_stream = materialized_diff_stream(store, tree_diff);
     async {
+        let (mut diff_stream, moved_lines) = if options.color_moved {
+            let (entries, moved_lines) = materialize_diff_with_moved_lines(
+                store,
+                tree_diff,
+                &options.line_diff,
+                content_options.conflict_marker_style,
+            )
+            .await?;
+            (stream::iter(entries).boxed_local(), moved_lines)
+        } else {
+            let diff_stream = materialized_diff_stream(store, tree_diff);
+            (diff_stream.boxed_local(), vec![])
+        };
+        let no_moved_lines = FileMovedLines::default();
+        let mut index = 0;
         while let Some(MaterializedTreeDiffEntry { path, values }) = diff_stream.next().await {
+            let file_moved_lines = moved_lines.get(index).unwrap_or(&no_moved_lines);
+            index += 1;
             let left_path = path.source();
             let right_path = path.target();
             let left_path_string = left_path.as_internal_file_string();
             let right_path_string = right_path.as_internal_file_string();
             let (left_value, right_value) = values?;
 
-            let left_part = git_diff_part(left_path, left_value, conflict_marker_style)?;
-            let right_part = git_diff_part(right_path, right_value, conflict_marker_style)?;
+            let mut left_part =
+                git_diff_part(left_path, left_value, content_options.conflict_marker_style)?;
+            let mut right_part = git_diff_part(
+                right_path,
+                right_value,
+                content_options.conflict_marker_style,
+            )?;
+            left_part.content.is_binary |= content_options.is_binary(left_path);
+            right_part.content.is_binary |= content_options.is_binary(right_path);
 
             formatter.with_label("file_header", |formatter| {
                 writeln!(
@@ -1574,8 +2453,15 @@ pub fn show_git_diff(
                 Some(_) => format!("b/{right_path_string}"),
                 None => "/dev/null".to_owned(),
             };
-            if left_part.content.is_binary || right_part.content.is_binary {
-                // TODO: add option to emit Git binary diff
+            if (left_part.content.is_binary || right_part.content.is_binary) && options.binary {
+                formatter.write_all(
+                    format_git_binary_patch(
+                        &left_part.content.contents,
+                        &right_part.content.contents,
+                    )
+                    .as_bytes(),
+                )?;
+            } else if left_part.content.is_binary || right_part.content.is_binary {
                 writeln!(
                     formatter,
                     "Binary files {left_path} and {right_path} differ"
@@ -1590,7 +2476,9 @@ pub fn show_git_diff(
                     formatter,
                     &left_part.content.contents,
                     &right_part.content.contents,
+                    [0, 0],
                     options,
+                    file_moved_lines,
                 )?;
             }
         }
@@ -1599,6 +2487,38 @@ pub fn show_git_diff(
     .block_on()
 }
 
+/// Shows changes in the tracked line range of a file in Git diff format.
+///
+/// All lines in the range are displayed as context.
+pub fn show_line_range_diff(
+    formatter: &mut dyn Formatter,
+    path: &RepoPath,
+    change: &LineRangeChange,
+    options: &UnifiedDiffOptions,
+) -> io::Result<()> {
+    let path_string = path.as_internal_file_string();
+    let options = UnifiedDiffOptions {
+        context: usize::MAX,
+        line_diff: options.line_diff.clone(),
+        color_moved: false,
+        highlight_words: options.highlight_words,
+        binary: false,
+    };
+    formatter.with_label("file_header", |formatter| {
+        writeln!(formatter, "diff --git a/{path_string} b/{path_string}")?;
+        writeln!(formatter, "--- a/{path_string}")?;
+        writeln!(formatter, "+++ b/{path_string}")
+    })?;
+    show_unified_diff_hunks(
+        formatter,
+        &change.old.text,
+        &change.new.text,
+        [change.old.line_range.start, change.new.line_range.start],
+        &options,
+        &FileMovedLines::default(),
+    )
+}
+
 #[instrument(skip_all)]
 pub fn show_diff_summary(
     formatter: &mut dyn Formatter,
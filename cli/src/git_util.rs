@@ -580,21 +580,35 @@ pub fn print_failed_git_export(
     failed_refs: &[FailedRefExport],
 ) -> Result<(), std::io::Error> {
     if !failed_refs.is_empty() {
-        writeln!(ui.warning_default(), "Failed to export some bookmarks:")?;
+        if failed_refs
+            .iter()
+            .any(|failed| matches!(failed.name, RefName::Tag(_)))
+        {
+            writeln!(
+                ui.warning_default(),
+                "Failed to export some bookmarks or tags:"
+            )?;
+        } else {
+            writeln!(ui.warning_default(), "Failed to export some bookmarks:")?;
+        }
         let mut formatter = ui.stderr_formatter();
         for FailedRefExport { name, reason } in failed_refs {
             write!(formatter, "  ")?;
-            write!(formatter.labeled("bookmark"), "{name}")?;
+            let label = match name {
+                RefName::Tag(_) => "tag",
+                RefName::LocalBranch(_) | RefName::RemoteBranch { .. } => "bookmark",
+            };
+            write!(formatter.labeled(label), "{name}")?;
             for err in iter::successors(Some(reason as &dyn error::Error), |err| err.source()) {
                 write!(formatter, ": {err}")?;
             }
             writeln!(formatter)?;
         }
         drop(formatter);
-        if failed_refs
-            .iter()
-            .any(|failed| matches!(failed.reason, FailedRefExportReason::FailedToSet(_)))
-        {
+        if failed_refs.iter().any(|failed| {
+            !matches!(failed.name, RefName::Tag(_))
+                && matches!(failed.reason, FailedRefExportReason::FailedToSet(_))
+        }) {
             writeln!(
                 ui.hint_default(),
                 r#"Git doesn't allow a branch name that looks like a parent directory of
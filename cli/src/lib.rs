@@ -44,6 +44,8 @@ pub mod movement_util;
 pub mod operation_templater;
 mod progress;
 pub mod revset_util;
+#[cfg(feature = "structural-diff")]
+mod structural_diff;
 pub mod template_builder;
 pub mod template_parser;
 pub mod templater;
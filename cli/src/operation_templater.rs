@@ -41,8 +41,10 @@ use crate::templater::PlainTextFormattedProperty;
 use crate::templater::Template;
 use crate::templater::TemplateFormatter;
 use crate::templater::TemplateProperty;
+use crate::templater::TemplatePropertyError;
 use crate::templater::TemplatePropertyExt as _;
 use crate::templater::TimestampRange;
+use crate::templater::ToJson;
 
 pub trait OperationTemplateLanguageExtension {
     fn build_fn_table(&self) -> OperationTemplateBuildFnTable;
@@ -197,6 +199,14 @@ impl IntoTemplateProperty<'static> for OperationTemplatePropertyKind {
         }
     }
 
+    fn try_into_json(self) -> Option<Box<dyn TemplateProperty<Output = serde_json::Value>>> {
+        match self {
+            OperationTemplatePropertyKind::Core(property) => property.try_into_json(),
+            OperationTemplatePropertyKind::Operation(property) => Some(property.into_json()),
+            OperationTemplatePropertyKind::OperationId(property) => Some(property.into_json()),
+        }
+    }
+
     fn try_into_eq(self, other: Self) -> Option<Box<dyn TemplateProperty<Output = bool>>> {
         match (self, other) {
             (
@@ -264,6 +274,32 @@ impl OperationTemplateBuildFnTable {
     }
 }
 
+impl ToJson for Operation {
+    fn to_json(&self) -> Result<serde_json::Value, TemplatePropertyError> {
+        let metadata = self.metadata();
+        let time = TimestampRange {
+            start: metadata.start_time,
+            end: metadata.end_time,
+        };
+        let tags: serde_json::Map<_, _> = metadata
+            .tags
+            .iter()
+            .sorted_unstable()
+            .map(|(key, value)| (key.clone(), value.as_str().into()))
+            .collect();
+        Ok(serde_json::json!({
+            "id": self.id().to_json()?,
+            "parents": self.parent_ids().to_json()?,
+            "time": time.to_json()?,
+            "description": metadata.description,
+            "hostname": metadata.hostname,
+            "username": metadata.username,
+            "is_snapshot": metadata.is_snapshot,
+            "tags": tags,
+        }))
+    }
+}
+
 fn builtin_operation_methods() -> OperationTemplateBuildMethodFnMap<Operation> {
     type L = OperationTemplateLanguage;
     // Not using maplit::hashmap!{} or custom declarative macro here because
@@ -357,6 +393,12 @@ impl Template for OperationId {
     }
 }
 
+impl ToJson for OperationId {
+    fn to_json(&self) -> Result<serde_json::Value, TemplatePropertyError> {
+        Ok(self.hex().into())
+    }
+}
+
 fn builtin_operation_id_methods() -> OperationTemplateBuildMethodFnMap<OperationId> {
     type L = OperationTemplateLanguage;
     // Not using maplit::hashmap!{} or custom declarative macro here because
@@ -0,0 +1,454 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Syntax-aware diff of source files parsed by tree-sitter.
+
+use std::io;
+use std::mem;
+use std::ops::Range;
+use std::path::Path;
+
+use bstr::BStr;
+use itertools::Itertools as _;
+use jj_lib::diff::CompareBytesExactly;
+use jj_lib::diff::Diff;
+use jj_lib::diff::DiffAlgorithm;
+use jj_lib::diff::DiffHunkKind;
+use jj_lib::files::DiffLineHunkSide;
+use jj_lib::files::DiffLineNumber;
+use jj_lib::repo_path::RepoPath;
+use tree_sitter::Language;
+use tree_sitter::Parser;
+
+use crate::diff_util::show_color_words_diff_hunks;
+use crate::diff_util::show_color_words_inline_hunks;
+use crate::diff_util::show_color_words_line_number;
+use crate::diff_util::FileMovedLines;
+use crate::diff_util::StructuralDiffOptions;
+use crate::formatter::Formatter;
+
+/// Grammar and layout rules of a supported language.
+struct SyntaxLanguage {
+    grammar: Language,
+    /// Kinds of nodes whose children are laid out by indentation. The leading
+    /// indentation of these children is part of the syntax.
+    indented_block_kinds: &'static [&'static str],
+}
+
+/// Returns the language to parse the file at `path` with.
+fn language_for_path(path: &RepoPath) -> Option<SyntaxLanguage> {
+    let file_path = path.as_internal_file_string();
+    let extension = Path::new(file_path).extension()?.to_str()?;
+    match extension {
+        "py" | "pyi" => Some(SyntaxLanguage {
+            grammar: tree_sitter_python::LANGUAGE.into(),
+            indented_block_kinds: &["module", "block"],
+        }),
+        "rs" => Some(SyntaxLanguage {
+            grammar: tree_sitter_rust::LANGUAGE.into(),
+            indented_block_kinds: &[],
+        }),
+        _ => None,
+    }
+}
+
+/// Source text split into syntax tokens.
+struct SyntaxTokens<'a> {
+    text: &'a [u8],
+    /// Sorted byte ranges of the tokens. The whitespace between them isn't
+    /// significant.
+    ranges: Vec<Range<usize>>,
+}
+
+impl SyntaxTokens<'_> {
+    /// Returns true if any token overlaps with the byte `range`.
+    fn has_token_in(&self, range: Range<usize>) -> bool {
+        let pos = self
+            .ranges
+            .partition_point(|token| token.end <= range.start);
+        self.ranges
+            .get(pos)
+            .is_some_and(|token| token.start < range.end)
+    }
+}
+
+/// Parses `text` into tokens, or returns `None` if the text isn't
+/// syntactically valid.
+///
+/// The tokens are the leaf syntax nodes, which determine the tree structure
+/// unless the language is indentation-sensitive. For such languages, the
+/// leading indentation of each statement in indented blocks is also part of
+/// the token.
+fn tokenize<'a>(language: &SyntaxLanguage, text: &'a [u8]) -> Option<SyntaxTokens<'a>> {
+    let mut parser = Parser::new();
+    parser.set_language(&language.grammar).ok()?;
+    let tree = parser.parse(text, None)?;
+    if tree.root_node().has_error() {
+        return None;
+    }
+    let mut ranges = Vec::new();
+    let mut indent_start = None;
+    let mut cursor = tree.walk();
+    'walk: loop {
+        let node = cursor.node();
+        let is_indented = node
+            .parent()
+            .is_some_and(|parent| language.indented_block_kinds.contains(&parent.kind()));
+        if is_indented {
+            let line_start = node.start_byte() - node.start_position().column;
+            let indent = &text[line_start..node.start_byte()];
+            if !indent.is_empty() && indent.iter().all(|b| matches!(b, b' ' | b'\t')) {
+                indent_start = Some(line_start);
+            }
+        }
+        if node.child_count() == 0 {
+            let range = node.byte_range();
+            if !range.is_empty() {
+                // Indentation is included in the first token of the statement
+                // so the statement differs if it's moved to another block.
+                let start = indent_start.take().unwrap_or(range.start);
+                ranges.push(start..range.end);
+            }
+        } else if cursor.goto_first_child() {
+            continue;
+        }
+        while !cursor.goto_next_sibling() {
+            if !cursor.goto_parent() {
+                break 'walk;
+            }
+        }
+    }
+    Some(SyntaxTokens { text, ranges })
+}
+
+/// Line of the structural diff output. The line numbers point to the start of
+/// the line.
+struct StructuralDiffLine<'a> {
+    line_number: DiffLineNumber,
+    hunks: Vec<(DiffLineHunkSide, &'a BStr)>,
+}
+
+impl StructuralDiffLine<'_> {
+    fn has_left_content(&self) -> bool {
+        self.hunks
+            .iter()
+            .any(|&(side, _)| matches!(side, DiffLineHunkSide::Both | DiffLineHunkSide::Left))
+    }
+
+    fn has_right_content(&self) -> bool {
+        self.hunks
+            .iter()
+            .any(|&(side, _)| matches!(side, DiffLineHunkSide::Both | DiffLineHunkSide::Right))
+    }
+
+    fn is_changed(&self) -> bool {
+        self.hunks
+            .iter()
+            .any(|&(side, _)| side != DiffLineHunkSide::Both)
+    }
+}
+
+/// Diffs syntax tokens of `left` and `right`, and splits the result into
+/// lines.
+///
+/// Changes in whitespace between tokens are considered formatting changes, and
+/// the right-side layout is shown as unchanged.
+fn structural_diff_lines<'a>(
+    left: &SyntaxTokens<'a>,
+    right: &SyntaxTokens<'a>,
+    algorithm: DiffAlgorithm,
+) -> Vec<StructuralDiffLine<'a>> {
+    let diff = Diff::for_token_ranges(
+        [left.text, right.text],
+        &[left.ranges.clone(), right.ranges.clone()],
+        CompareBytesExactly,
+        algorithm,
+    );
+    let mut builder = StructuralDiffLineBuilder {
+        lines: vec![],
+        current_line: StructuralDiffLine {
+            line_number: DiffLineNumber { left: 1, right: 1 },
+            hunks: vec![],
+        },
+        next_line_number: DiffLineNumber { left: 1, right: 1 },
+    };
+    for hunk in diff.hunk_ranges() {
+        let (left_range, right_range) = hunk
+            .ranges
+            .into_iter()
+            .collect_tuple()
+            .expect("hunk should have exactly two inputs");
+        let left_text = &left.text[left_range.clone()];
+        let right_text = &right.text[right_range.clone()];
+        let is_formatting = hunk.kind == DiffHunkKind::Matching
+            || !left.has_token_in(left_range) && !right.has_token_in(right_range);
+        if is_formatting {
+            // Show the new layout, but keep track of the old line numbers.
+            let mut num_left_lines: u32 = left_text
+                .iter()
+                .filter(|&&b| b == b'\n')
+                .count()
+                .try_into()
+                .unwrap();
+            for line in right_text.split_inclusive(|b| *b == b'\n') {
+                let left_inc = if line.ends_with(b"\n") {
+                    num_left_lines.min(1)
+                } else {
+                    0
+                };
+                num_left_lines -= left_inc;
+                builder.push(DiffLineHunkSide::Both, line, left_inc);
+            }
+            builder.next_line_number.left += num_left_lines;
+        } else {
+            for line in left_text.split_inclusive(|b| *b == b'\n') {
+                builder.push(DiffLineHunkSide::Left, line, 1);
+            }
+            for line in right_text.split_inclusive(|b| *b == b'\n') {
+                builder.push(DiffLineHunkSide::Right, line, 0);
+            }
+        }
+    }
+    builder.finish()
+}
+
+struct StructuralDiffLineBuilder<'a> {
+    lines: Vec<StructuralDiffLine<'a>>,
+    current_line: StructuralDiffLine<'a>,
+    next_line_number: DiffLineNumber,
+}
+
+impl<'a> StructuralDiffLineBuilder<'a> {
+    /// Appends line `content`. If the content ends with newline, the left line
+    /// number is incremented by `left_inc`, and the right line number is
+    /// incremented if the content belongs to the right side.
+    fn push(&mut self, side: DiffLineHunkSide, content: &'a [u8], left_inc: u32) {
+        if self.current_line.hunks.is_empty() {
+            self.current_line.line_number = self.next_line_number;
+        }
+        self.current_line.hunks.push((side, BStr::new(content)));
+        if content.ends_with(b"\n") {
+            self.next_line_number.left += left_inc;
+            if side != DiffLineHunkSide::Left {
+                self.next_line_number.right += 1;
+            }
+            let hunks = mem::take(&mut self.current_line.hunks);
+            self.lines.push(StructuralDiffLine {
+                line_number: self.current_line.line_number,
+                hunks,
+            });
+        }
+    }
+
+    fn finish(mut self) -> Vec<StructuralDiffLine<'a>> {
+        if !self.current_line.hunks.is_empty() {
+            self.lines.push(self.current_line);
+        }
+        self.lines
+    }
+}
+
+/// Shows syntax-aware diff of the file at `path`. Falls back to color-words
+/// diff if the language isn't supported or if either side can't be parsed.
+pub(crate) fn show_structural_diff_hunks(
+    formatter: &mut dyn Formatter,
+    path: &RepoPath,
+    left: &[u8],
+    right: &[u8],
+    options: &StructuralDiffOptions,
+) -> io::Result<()> {
+    let tokens = language_for_path(path).and_then(|language| {
+        let left_tokens = tokenize(&language, left)?;
+        let right_tokens = tokenize(&language, right)?;
+        Some((left_tokens, right_tokens))
+    });
+    let Some((left_tokens, right_tokens)) = tokens else {
+        return show_color_words_diff_hunks(
+            formatter,
+            left,
+            right,
+            &options.fallback,
+            &FileMovedLines::default(),
+        );
+    };
+
+    const SKIPPED_CONTEXT_LINE: &str = "    ...\n";
+    let algorithm = options.fallback.line_diff.algorithm;
+    let lines = structural_diff_lines(&left_tokens, &right_tokens, algorithm);
+    let changed_indices: Vec<usize> = (0..lines.len())
+        .filter(|&i| lines[i].is_changed())
+        .collect();
+    if changed_indices.is_empty() {
+        if left != right {
+            writeln!(formatter, "    (formatting changes only)")?;
+        }
+        return Ok(());
+    }
+    let is_shown = |index: usize| {
+        let pos = changed_indices.partition_point(|&i| i + options.context < index);
+        changed_indices
+            .get(pos)
+            .is_some_and(|&i| i <= index + options.context)
+    };
+    let mut skipped = false;
+    for (index, line) in lines.iter().enumerate() {
+        if !is_shown(index) {
+            skipped = true;
+            continue;
+        }
+        if skipped {
+            write!(formatter, "{SKIPPED_CONTEXT_LINE}")?;
+            skipped = false;
+        }
+        show_color_words_line_number(
+            formatter,
+            line.has_left_content().then_some(line.line_number.left),
+            line.has_right_content().then_some(line.line_number.right),
+        )?;
+        show_color_words_inline_hunks(formatter, &line.hunks, false)?;
+    }
+    if skipped {
+        write!(formatter, "{SKIPPED_CONTEXT_LINE}")?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokenize_file<'a>(file_name: &str, text: &'a str) -> SyntaxTokens<'a> {
+        let language = language_for_path(RepoPath::from_internal_string(file_name)).unwrap();
+        tokenize(&language, text.as_bytes()).unwrap()
+    }
+
+    fn token_texts<'a>(file_name: &str, text: &'a str) -> Vec<&'a str> {
+        tokenize_file(file_name, text)
+            .ranges
+            .into_iter()
+            .map(|range| &text[range])
+            .collect()
+    }
+
+    fn diff_lines(file_name: &str, left: &str, right: &str) -> Vec<String> {
+        let left = tokenize_file(file_name, left);
+        let right = tokenize_file(file_name, right);
+        format_lines(&structural_diff_lines(
+            &left,
+            &right,
+            DiffAlgorithm::default(),
+        ))
+    }
+
+    fn format_lines(lines: &[StructuralDiffLine]) -> Vec<String> {
+        lines
+            .iter()
+            .map(|line| {
+                let mut text = format!("{}/{}:", line.line_number.left, line.line_number.right);
+                for (side, content) in &line.hunks {
+                    match side {
+                        DiffLineHunkSide::Both => text.push_str(&content.to_string()),
+                        DiffLineHunkSide::Left => text.push_str(&format!("[-{content}]")),
+                        DiffLineHunkSide::Right => text.push_str(&format!("[+{content}]")),
+                    }
+                }
+                text
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_tokenize() {
+        assert_eq!(
+            token_texts("lib.rs", "fn f(x: u32) {\n    \"a b\"\n}\n"),
+            ["fn", "f", "(", "x", ":", "u32", ")", "{", "\"", "a b", "\"", "}"]
+        );
+        // Indentation of Python statements is significant, but indentation of
+        // continuation lines isn't.
+        assert_eq!(
+            token_texts("main.py", "if x:\n    f(\n      1)\n"),
+            ["if", "x", ":", "    f", "(", "1", ")"]
+        );
+        // Invalid syntax
+        let language = language_for_path(RepoPath::from_internal_string("lib.rs")).unwrap();
+        assert!(tokenize(&language, b"fn f(").is_none());
+    }
+
+    #[test]
+    fn test_language_for_path() {
+        let path = |value| RepoPath::from_internal_string(value);
+        assert!(language_for_path(path("src/lib.rs")).is_some());
+        assert!(language_for_path(path("setup.py")).is_some());
+        assert!(language_for_path(path("README.md")).is_none());
+        assert!(language_for_path(path("Makefile")).is_none());
+    }
+
+    #[test]
+    fn test_structural_diff_lines_formatting_only() {
+        let left = "fn f(a: u32, b: u32) {}\n";
+        let right = "fn f(\n    a: u32,\n    b: u32,\n) {}\n";
+        assert_eq!(
+            diff_lines("lib.rs", left, right),
+            [
+                "1/1:fn f(\n",
+                "1/2:    a: u32,\n",
+                "1/3:    b: u32[+,\n]",
+                "1/4:) {}\n",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_structural_diff_lines_edit() {
+        let left = "fn f() {\n    g(1);\n}\n";
+        let right = "fn f() { g(2); }\n";
+        assert_eq!(
+            diff_lines("lib.rs", left, right),
+            ["1/1:fn f() { g([-1][+2]); }\n"]
+        );
+    }
+
+    #[test]
+    fn test_structural_diff_lines_whitespace_in_token() {
+        let left = "fn f() { g(\" \"); }\n";
+        let right = "fn f() { g(\"  \"); }\n";
+        assert_eq!(
+            diff_lines("lib.rs", left, right),
+            ["1/1:fn f() { g(\"[- ][+  ]\"); }\n"]
+        );
+    }
+
+    #[test]
+    fn test_structural_diff_lines_python_indentation() {
+        // Dedenting a statement moves it out of the block
+        let left = "if x:\n    f()\n    g()\n";
+        let right = "if x:\n    f()\ng()\n";
+        assert_eq!(
+            diff_lines("main.py", left, right),
+            [
+                "1/1:if x:\n",
+                "2/2:    f()[-\n]",
+                "3/2:[-    g][+\n]",
+                "3/3:[+g]()\n",
+            ]
+        );
+        // Indentation of continuation lines is formatting
+        let left = "f(1,\n  2)\n";
+        let right = "f(1,\n      2)\n";
+        assert_eq!(
+            diff_lines("main.py", left, right),
+            ["1/1:f(1,\n", "2/2:      2)\n"]
+        );
+    }
+}
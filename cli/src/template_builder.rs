@@ -180,6 +180,10 @@ pub trait IntoTemplateProperty<'a> {
     fn try_into_plain_text(self) -> Option<Box<dyn TemplateProperty<Output = String> + 'a>>;
     fn try_into_template(self) -> Option<Box<dyn Template + 'a>>;
 
+    /// Transforms into a property that will evaluate to the JSON
+    /// representation of the value.
+    fn try_into_json(self) -> Option<Box<dyn TemplateProperty<Output = serde_json::Value> + 'a>>;
+
     /// Transforms into a property that will evaluate to `self == other`.
     fn try_into_eq(self, other: Self) -> Option<Box<dyn TemplateProperty<Output = bool> + 'a>>;
 
@@ -301,6 +305,24 @@ impl<'a> IntoTemplateProperty<'a> for CoreTemplatePropertyKind<'a> {
         }
     }
 
+    fn try_into_json(self) -> Option<Box<dyn TemplateProperty<Output = serde_json::Value> + 'a>> {
+        match self {
+            CoreTemplatePropertyKind::String(property) => Some(property.into_json()),
+            CoreTemplatePropertyKind::StringList(property) => Some(property.into_json()),
+            CoreTemplatePropertyKind::Boolean(property) => Some(property.into_json()),
+            CoreTemplatePropertyKind::Integer(property) => Some(property.into_json()),
+            CoreTemplatePropertyKind::IntegerOpt(property) => Some(property.into_json()),
+            CoreTemplatePropertyKind::ConfigValue(property) => Some(property.into_json()),
+            CoreTemplatePropertyKind::Signature(property) => Some(property.into_json()),
+            CoreTemplatePropertyKind::Email(property) => Some(property.into_json()),
+            CoreTemplatePropertyKind::SizeHint(property) => Some(property.into_json()),
+            CoreTemplatePropertyKind::Timestamp(property) => Some(property.into_json()),
+            CoreTemplatePropertyKind::TimestampRange(property) => Some(property.into_json()),
+            CoreTemplatePropertyKind::Template(_) => None,
+            CoreTemplatePropertyKind::ListTemplate(_) => None,
+        }
+    }
+
     fn try_into_eq(self, other: Self) -> Option<Box<dyn TemplateProperty<Output = bool> + 'a>> {
         match (self, other) {
             (CoreTemplatePropertyKind::String(lhs), CoreTemplatePropertyKind::String(rhs)) => {
@@ -624,6 +646,12 @@ impl<'a, P: IntoTemplateProperty<'a>> Expression<P> {
         }
     }
 
+    pub fn try_into_json(
+        self,
+    ) -> Option<Box<dyn TemplateProperty<Output = serde_json::Value> + 'a>> {
+        self.property.try_into_json()
+    }
+
     pub fn try_into_eq(self, other: Self) -> Option<Box<dyn TemplateProperty<Output = bool> + 'a>> {
         self.property.try_into_eq(other.property)
     }
@@ -1558,6 +1586,11 @@ fn builtin_functions<'a, L: TemplateLanguage<'a> + ?Sized>() -> TemplateBuildFun
         let content = expect_plain_text_expression(language, diagnostics, build_ctx, content_node)?;
         Ok(L::wrap_string(content))
     });
+    map.insert("json", |language, diagnostics, build_ctx, function| {
+        let [value_node] = function.expect_exact_arguments()?;
+        let value = expect_json_expression(language, diagnostics, build_ctx, value_node)?;
+        Ok(L::wrap_string(value.map(|value| value.to_string())))
+    });
     map.insert("if", |language, diagnostics, build_ctx, function| {
         let ([condition_node, true_node], [false_node]) = function.expect_arguments()?;
         let condition =
@@ -1839,6 +1872,22 @@ pub fn expect_boolean_expression<'a, L: TemplateLanguage<'a> + ?Sized>(
     )
 }
 
+pub fn expect_json_expression<'a, L: TemplateLanguage<'a> + ?Sized>(
+    language: &L,
+    diagnostics: &mut TemplateDiagnostics,
+    build_ctx: &BuildContext<L::Property>,
+    node: &ExpressionNode,
+) -> TemplateParseResult<Box<dyn TemplateProperty<Output = serde_json::Value> + 'a>> {
+    expect_expression_of_type(
+        language,
+        diagnostics,
+        build_ctx,
+        node,
+        "Serializable",
+        |expression| expression.try_into_json(),
+    )
+}
+
 pub fn expect_integer_expression<'a, L: TemplateLanguage<'a> + ?Sized>(
     language: &L,
     diagnostics: &mut TemplateDiagnostics,
@@ -3148,6 +3197,34 @@ mod tests {
         insta::assert_snapshot!(env.render_ok("stringify(label('error', 'text'))"), @"text");
     }
 
+    #[test]
+    fn test_json_function() {
+        let mut env = TestTemplateEnv::new();
+        env.add_keyword("author", || {
+            L::wrap_signature(Literal(new_signature("Test User", "test.user@example.com")))
+        });
+        env.add_keyword("none_i64", || L::wrap_integer_opt(Literal(None)));
+        env.add_keyword("some_i64", || L::wrap_integer_opt(Literal(Some(1))));
+
+        insta::assert_snapshot!(env.render_ok(r#"json("a\"b")"#), @r#""a\"b""#);
+        insta::assert_snapshot!(env.render_ok(r#"json(42)"#), @"42");
+        insta::assert_snapshot!(env.render_ok(r#"json(true)"#), @"true");
+        insta::assert_snapshot!(env.render_ok(r#"json(none_i64)"#), @"null");
+        insta::assert_snapshot!(env.render_ok(r#"json(some_i64)"#), @"1");
+        insta::assert_snapshot!(env.render_ok(r#"json("a\nb\n".lines())"#), @r#"["a","b"]"#);
+        insta::assert_snapshot!(env.render_ok(r#"json(author)"#), @r#"{"email":"test.user@example.com","name":"Test User","timestamp":"1970-01-01T00:00:00.000+00:00"}"#);
+        insta::assert_snapshot!(env.render_ok(r#"json(author.timestamp())"#), @r#""1970-01-01T00:00:00.000+00:00""#);
+
+        insta::assert_snapshot!(env.parse_err(r#"json(label("a", "b"))"#), @r#"
+         --> 1:6
+          |
+        1 | json(label("a", "b"))
+          |      ^-------------^
+          |
+          = Expected expression of type `Serializable`, but actual type is `Template`
+        "#);
+    }
+
     #[test]
     fn test_coalesce_function() {
         let mut env = TestTemplateEnv::new();
@@ -22,6 +22,7 @@ use std::rc::Rc;
 
 use bstr::BStr;
 use bstr::BString;
+use itertools::Itertools as _;
 use jj_lib::backend::Signature;
 use jj_lib::backend::Timestamp;
 use jj_lib::config::ConfigValue;
@@ -182,6 +183,131 @@ impl Template for TimestampRange {
     }
 }
 
+/// Value that can be serialized by the `json()` template function.
+///
+/// The JSON representation is meant to be consumed by scripts, so it shouldn't
+/// depend on the user configuration. Fields may be added, but existing fields
+/// shouldn't be renamed or removed.
+pub trait ToJson {
+    fn to_json(&self) -> Result<serde_json::Value, TemplatePropertyError>;
+}
+
+impl<T: ToJson + ?Sized> ToJson for &T {
+    fn to_json(&self) -> Result<serde_json::Value, TemplatePropertyError> {
+        <T as ToJson>::to_json(self)
+    }
+}
+
+impl<T: ToJson + ?Sized> ToJson for Box<T> {
+    fn to_json(&self) -> Result<serde_json::Value, TemplatePropertyError> {
+        <T as ToJson>::to_json(self)
+    }
+}
+
+impl<T: ToJson + ?Sized> ToJson for Rc<T> {
+    fn to_json(&self) -> Result<serde_json::Value, TemplatePropertyError> {
+        <T as ToJson>::to_json(self)
+    }
+}
+
+impl<T: ToJson> ToJson for Option<T> {
+    fn to_json(&self) -> Result<serde_json::Value, TemplatePropertyError> {
+        match self {
+            Some(value) => value.to_json(),
+            None => Ok(serde_json::Value::Null),
+        }
+    }
+}
+
+impl<T: ToJson> ToJson for [T] {
+    fn to_json(&self) -> Result<serde_json::Value, TemplatePropertyError> {
+        self.iter().map(ToJson::to_json).collect()
+    }
+}
+
+impl<T: ToJson> ToJson for Vec<T> {
+    fn to_json(&self) -> Result<serde_json::Value, TemplatePropertyError> {
+        self.as_slice().to_json()
+    }
+}
+
+impl ToJson for String {
+    fn to_json(&self) -> Result<serde_json::Value, TemplatePropertyError> {
+        Ok(self.as_str().into())
+    }
+}
+
+impl ToJson for bool {
+    fn to_json(&self) -> Result<serde_json::Value, TemplatePropertyError> {
+        Ok((*self).into())
+    }
+}
+
+impl ToJson for i64 {
+    fn to_json(&self) -> Result<serde_json::Value, TemplatePropertyError> {
+        Ok((*self).into())
+    }
+}
+
+impl ToJson for ConfigValue {
+    fn to_json(&self) -> Result<serde_json::Value, TemplatePropertyError> {
+        let value = match self {
+            ConfigValue::String(v) => v.value().as_str().into(),
+            ConfigValue::Integer(v) => (*v.value()).into(),
+            ConfigValue::Float(v) => (*v.value()).into(),
+            ConfigValue::Boolean(v) => (*v.value()).into(),
+            ConfigValue::Datetime(v) => v.value().to_string().into(),
+            ConfigValue::Array(array) => array.iter().map(ToJson::to_json).try_collect()?,
+            ConfigValue::InlineTable(table) => {
+                let mut map = serde_json::Map::new();
+                for (key, value) in table {
+                    map.insert(key.to_owned(), value.to_json()?);
+                }
+                map.into()
+            }
+        };
+        Ok(value)
+    }
+}
+
+impl ToJson for Signature {
+    fn to_json(&self) -> Result<serde_json::Value, TemplatePropertyError> {
+        Ok(serde_json::json!({
+            "name": self.name,
+            "email": self.email,
+            "timestamp": self.timestamp.to_json()?,
+        }))
+    }
+}
+
+impl ToJson for Email {
+    fn to_json(&self) -> Result<serde_json::Value, TemplatePropertyError> {
+        self.0.to_json()
+    }
+}
+
+impl ToJson for SizeHint {
+    fn to_json(&self) -> Result<serde_json::Value, TemplatePropertyError> {
+        let (lower, upper) = self;
+        Ok(serde_json::json!([lower, upper]))
+    }
+}
+
+impl ToJson for Timestamp {
+    fn to_json(&self) -> Result<serde_json::Value, TemplatePropertyError> {
+        Ok(time_util::format_rfc3339_timestamp(self)?.into())
+    }
+}
+
+impl ToJson for TimestampRange {
+    fn to_json(&self) -> Result<serde_json::Value, TemplatePropertyError> {
+        Ok(serde_json::json!({
+            "start": self.start.to_json()?,
+            "end": self.end.to_json()?,
+        }))
+    }
+}
+
 impl Template for Vec<String> {
     fn format(&self, formatter: &mut TemplateFormatter) -> io::Result<()> {
         format_joined(formatter, self, " ")
@@ -435,6 +561,16 @@ pub trait TemplatePropertyExt: TemplateProperty {
     {
         Box::new(FormattablePropertyTemplate::new(self))
     }
+
+    /// Converts this property into a property that will evaluate to the JSON
+    /// representation of the extracted value.
+    fn into_json<'a>(self) -> Box<dyn TemplateProperty<Output = serde_json::Value> + 'a>
+    where
+        Self: Sized + 'a,
+        Self::Output: ToJson,
+    {
+        Box::new(self.and_then(|value| value.to_json()))
+    }
 }
 
 impl<P: TemplateProperty + ?Sized> TemplatePropertyExt for P {}
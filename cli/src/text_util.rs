@@ -501,6 +501,36 @@ pub fn wrap_bytes(text: &[u8], width: usize) -> Vec<&[u8]> {
     split_lines
 }
 
+/// Wraps lines at the given width like [`wrap_bytes()`], but also splits words
+/// wider than the `width` so no line exceeds it.
+///
+/// If `width` is 0, each character is put in a separate line.
+pub fn wrap_bytes_breaking_words(text: &[u8], width: usize) -> Vec<&[u8]> {
+    let mut split_lines = Vec::new();
+    for mut line in wrap_bytes(text, width) {
+        loop {
+            let (mut end, _) = truncate_end_pos_bytes(line, width);
+            if end == line.len() {
+                split_lines.push(line);
+                break;
+            }
+            if end == 0 {
+                // Make progress even if a character doesn't fit in the width.
+                end = line
+                    .char_indices()
+                    .next()
+                    .map_or(line.len(), |(_, end, _)| end);
+            }
+            split_lines.push(&line[..end]);
+            line = &line[end..];
+            if line.is_empty() {
+                break;
+            }
+        }
+    }
+    split_lines
+}
+
 /// Wraps lines at the given width preserving labels.
 ///
 /// `textwrap::wrap()` can also process text containing ANSI escape sequences.
@@ -1379,6 +1409,40 @@ mod tests {
         assert_eq!(wrap_bytes(b"foo\x80", 10), [b"foo\x80".as_ref()]);
     }
 
+    #[test]
+    fn test_wrap_bytes_breaking_words() {
+        assert_eq!(
+            wrap_bytes_breaking_words(b"foo bar baz", 10),
+            [b"foo bar".as_ref(), b"baz".as_ref()],
+        );
+        assert_eq!(
+            wrap_bytes_breaking_words(b"foobarbaz x", 4),
+            [
+                b"foob".as_ref(),
+                b"arba".as_ref(),
+                b"z".as_ref(),
+                b"x".as_ref()
+            ],
+        );
+        assert_eq!(wrap_bytes_breaking_words(b"", 4), [b"".as_ref()]);
+
+        // Wide characters are split at character boundary
+        assert_eq!(
+            wrap_bytes_breaking_words("一二三".as_bytes(), 5),
+            ["一二".as_bytes(), "三".as_bytes()],
+        );
+        assert_eq!(
+            wrap_bytes_breaking_words("一二".as_bytes(), 1),
+            ["一".as_bytes(), "二".as_bytes()],
+        );
+
+        // Invalid UTF-8 bytes should not cause panic
+        assert_eq!(
+            wrap_bytes_breaking_words(b"foo\x80bar", 4),
+            [b"foo\x80".as_ref(), b"bar".as_ref()],
+        );
+    }
+
     #[test]
     fn test_wrap_bytes_slice_ptr() {
         let text = b"\nfoo\n\nbar baz\n";
@@ -2,6 +2,7 @@ use chrono::format::StrftimeItems;
 use chrono::DateTime;
 use chrono::FixedOffset;
 use chrono::LocalResult;
+use chrono::SecondsFormat;
 use chrono::TimeZone;
 use chrono::Utc;
 use jj_lib::backend::Timestamp;
@@ -76,6 +77,18 @@ pub fn format_absolute_timestamp(timestamp: &Timestamp) -> Result<String, Timest
     format_absolute_timestamp_with(timestamp, &DEFAULT_FORMAT)
 }
 
+/// Formats timestamp in RFC 3339 format with millisecond precision.
+pub fn format_rfc3339_timestamp(timestamp: &Timestamp) -> Result<String, TimestampOutOfRange> {
+    let datetime = datetime_from_timestamp(timestamp)?;
+    Ok(datetime.to_rfc3339_opts(SecondsFormat::Millis, false))
+}
+
+/// Formats timestamp in RFC 2822 format as used in email headers.
+pub fn format_rfc2822_timestamp(timestamp: &Timestamp) -> Result<String, TimestampOutOfRange> {
+    let datetime = datetime_from_timestamp(timestamp)?;
+    Ok(datetime.to_rfc2822())
+}
+
 pub fn format_absolute_timestamp_with(
     timestamp: &Timestamp,
     format: &FormattingItems,
@@ -13,7 +13,9 @@ mod test_absorb_command;
 mod test_acls;
 mod test_advance_bookmarks;
 mod test_alias;
+mod test_apply_command;
 mod test_backout_command;
+mod test_bisect_command;
 mod test_bookmark_command;
 mod test_builtin_aliases;
 mod test_commit_command;
@@ -30,6 +32,7 @@ mod test_diffedit_command;
 mod test_duplicate_command;
 mod test_edit_command;
 mod test_evolog_command;
+mod test_export_patch_command;
 mod test_file_annotate_command;
 mod test_file_chmod_command;
 mod test_file_show_command;
@@ -62,6 +65,7 @@ mod test_resolve_command;
 mod test_restore_command;
 mod test_revset_output;
 mod test_root;
+mod test_run_command;
 mod test_shell_completion;
 mod test_show_command;
 mod test_simplify_parents_command;
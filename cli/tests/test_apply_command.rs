@@ -0,0 +1,316 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::path::Path;
+
+use indoc::indoc;
+
+use crate::common::TestEnvironment;
+
+#[test]
+fn test_apply_plain_diff() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    std::fs::write(repo_path.join("file"), "a\nb\n").unwrap();
+
+    std::fs::write(
+        test_env.env_root().join("diff.patch"),
+        "--- a/file\n+++ b/file\n@@ -1,2 +1,2 @@\n a\n-b\n+c\n",
+    )
+    .unwrap();
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["apply", "../diff.patch"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @"Applied patch as rlvkpnrz c52601f6 (no description set)");
+    let stdout = test_env.jj_cmd_success(&repo_path, &["file", "show", "-r", "@+", "file"]);
+    insta::assert_snapshot!(stdout, @r"
+    a
+    c
+    ");
+
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["apply", "../missing.patch"]);
+    insta::assert_snapshot!(stderr, @r"
+    Error: Failed to read ../missing.patch
+    Caused by: No such file or directory (os error 2)
+    ");
+
+    std::fs::write(
+        test_env.env_root().join("bad.patch"),
+        "--- a/file\n+++ b/file\n@@ -1 +1 @@\n",
+    )
+    .unwrap();
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["apply", "../bad.patch"]);
+    insta::assert_snapshot!(stderr, @r"
+    Error: Failed to parse ../bad.patch
+    Caused by: Invalid patch at line 4: Unexpected end of hunk
+    ");
+}
+
+#[test]
+fn test_apply_exported_series() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    std::fs::write(repo_path.join("file"), "a\nb\n").unwrap();
+    test_env.jj_cmd_ok(
+        &repo_path,
+        &[
+            "commit",
+            "--author",
+            "Jörg Person <other@example.com>",
+            "-m",
+            "add file\nwith a long subject ✓\n\nFrom now on, the body\nNote: isn't a header.",
+        ],
+    );
+    std::fs::write(repo_path.join("file"), "a\nc\n").unwrap();
+    std::fs::write(repo_path.join("binary"), b"\0\x01\x02").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m", "modify file"]);
+    let (stdout, _stderr) = test_env.jj_cmd_ok(&repo_path, &["export-patch", "@-", "@"]);
+    std::fs::write(test_env.env_root().join("series.mbox"), stdout).unwrap();
+
+    let (stdout, stderr) =
+        test_env.jj_cmd_ok(&repo_path, &["apply", "-d", "root()", "../series.mbox"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r"
+    Applied patch as mzvwutvl 3b6b7440 add file
+    Applied patch as zxsnswpr 5f3de10c modify file
+    ");
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r"
+    @  modify file Test User 2001-02-03 04:05:09.000 +07:00
+    ○  add file Jörg Person 2001-02-03 04:05:08.000 +07:00
+    │ ○  modify file Test User 2001-02-03 04:05:09.000 +07:00
+    │ ○  add file Jörg Person 2001-02-03 04:05:08.000 +07:00
+    ├─╯
+    ◆  1970-01-01 00:00:00.000 +00:00
+    ");
+    // The applied commits should have the same contents as the original ones
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &["diff", "--from", "@", "--to", "heads(~::@)", "--summary"],
+    );
+    insta::assert_snapshot!(stdout, @"");
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &[
+            "log",
+            "--no-graph",
+            "-r",
+            "@-",
+            "-T",
+            "author.name() ++ '\n' ++ description",
+        ],
+    );
+    insta::assert_snapshot!(stdout, @r"
+    Jörg Person
+    add file
+    with a long subject ✓
+
+    From now on, the body
+    Note: isn't a header.
+    ");
+}
+
+#[test]
+fn test_apply_conflict() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    std::fs::write(repo_path.join("file"), "a\nx\n").unwrap();
+
+    std::fs::write(
+        test_env.env_root().join("diff.patch"),
+        "--- a/file\n+++ b/file\n@@ -1,2 +1,2 @@\n a\n-b\n+c\n",
+    )
+    .unwrap();
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["apply", "../diff.patch"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r"
+    Applied patch as rlvkpnrz 4ef9fe26 (conflict) (no description set)
+    Warning: Some hunks did not apply cleanly and were recorded as conflicts in:
+      file
+    New conflicts appeared in these commits:
+      rlvkpnrz 4ef9fe26 (conflict) (no description set)
+    To resolve the conflicts, start by updating to it:
+      jj new rlvkpnrz
+    Then use `jj resolve`, or edit the conflict markers in the file directly.
+    Once the conflicts are resolved, you may want to inspect the result with `jj diff`.
+    Then run `jj squash` to move the resolution into the conflicted commit.
+    ");
+    test_env.jj_cmd_ok(&repo_path, &["undo"]);
+
+    // A later patch can't be applied on top of the conflict
+    std::fs::write(
+        test_env.env_root().join("series.patch"),
+        indoc! {"
+            From 0123456789abcdef Mon Sep 17 00:00:00 2001
+            From: Someone <someone@example.com>
+            Subject: [PATCH 1/2] Change b
+
+            ---
+            --- a/file
+            +++ b/file
+            @@ -1,2 +1,2 @@
+             a
+            -b
+            +c
+
+            From 0123456789abcdef Mon Sep 17 00:00:00 2001
+            From: Someone <someone@example.com>
+            Subject: [PATCH 2/2] Change c
+
+            ---
+            --- a/file
+            +++ b/file
+            @@ -1,2 +1,2 @@
+             a
+            -c
+            +d
+        "},
+    )
+    .unwrap();
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["apply", "../series.patch"]);
+    insta::assert_snapshot!(stderr, @r"
+    Applied patch as zsuskuln 0dab220a (conflict) Change b
+    Warning: Some hunks did not apply cleanly and were recorded as conflicts in:
+      file
+    Error: Failed to apply patch 2/2 (Change c)
+    Caused by: Cannot apply patch to file: File has conflicts
+    ");
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["apply", "-r", "@", "../series.patch"]);
+    insta::assert_snapshot!(stderr, @r"
+    Error: Failed to apply patch 2/2 (Change c)
+    Caused by: Cannot apply patch to file: File has conflicts
+    ");
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &["log", "--no-graph", "-T", r#"commit_id.short() ++ "\n""#],
+    );
+    insta::assert_snapshot!(stdout, @r"
+    06ad6a853432
+    000000000000
+    ");
+
+    test_env.jj_cmd_ok(&repo_path, &["apply", "../diff.patch"]);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["file", "show", "-r", "@+", "file"]);
+    insta::assert_snapshot!(stdout, @r"
+    a
+    <<<<<<< Conflict 1 of 1
+    %%%%%%% Changes from base to side #1
+    -b
+    +x
+    +++++++ Contents of side #2
+    c
+    >>>>>>> Conflict 1 of 1 ends
+    ");
+}
+
+#[test]
+fn test_apply_deletion() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    std::fs::write(repo_path.join("file"), "a\nx\n").unwrap();
+    std::fs::write(repo_path.join("other"), "1\n").unwrap();
+
+    // The file to be deleted has been modified
+    std::fs::write(
+        test_env.env_root().join("diff.patch"),
+        indoc! {"
+            diff --git a/file b/file
+            deleted file mode 100644
+            --- a/file
+            +++ /dev/null
+            @@ -1,2 +0,0 @@
+            -a
+            -b
+        "},
+    )
+    .unwrap();
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["apply", "../diff.patch"]);
+    insta::assert_snapshot!(stderr, @r"
+    Error: Failed to apply patch 1/1
+    Caused by: Cannot apply patch to file: File to be deleted doesn't match the patch
+    ");
+
+    std::fs::write(repo_path.join("file"), "a\nb\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["apply", "../diff.patch"]);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["file", "list", "-r", "@+"]);
+    insta::assert_snapshot!(stdout, @"other");
+}
+
+#[test]
+fn test_apply_to_revision() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    std::fs::write(repo_path.join("file"), "1\n2\na\nb\nc\nd\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["commit", "-m", "first"]);
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m", "second"]);
+
+    // The hunk is recorded at an older line number, and the trailing context
+    // no longer matches
+    let diff = "--- a/file\n+++ b/file\n@@ -1,3 +1,3 @@\n a\n-b\n+B\n x\n";
+    let stderr = test_env.jj_cmd_cli_error(&repo_path, &["apply", "-r", "@-", "-d", "@"]);
+    insta::assert_snapshot!(stderr.lines().next().unwrap(), @"error: the argument '--revision <REVSET>' cannot be used with '--destination <REVSET>'");
+    let (stdout, stderr) =
+        test_env.jj_cmd_stdin_ok(&repo_path, &["apply", "-r", "@-", "--fuzz", "0"], diff);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r"
+    Applied patch to qpvuntsm e6547572 (conflict) first
+    Rebased 1 descendant commits
+    Warning: Some hunks did not apply cleanly and were recorded as conflicts in:
+      file
+    Working copy now at: rlvkpnrz 23e9d6a0 (conflict) (empty) second
+    Parent commit      : qpvuntsm e6547572 (conflict) first
+    Added 0 files, modified 1 files, removed 0 files
+    There are unresolved conflicts at these paths:
+    file    2-sided conflict
+    New conflicts appeared in these commits:
+      qpvuntsm e6547572 (conflict) first
+    To resolve the conflicts, start by updating to it:
+      jj new qpvuntsm
+    Then use `jj resolve`, or edit the conflict markers in the file directly.
+    Once the conflicts are resolved, you may want to inspect the result with `jj diff`.
+    Then run `jj squash` to move the resolution into the conflicted commit.
+    ");
+    test_env.jj_cmd_ok(&repo_path, &["undo"]);
+    let (stdout, stderr) = test_env.jj_cmd_stdin_ok(&repo_path, &["apply", "-r", "@-"], diff);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r"
+    Applied patch to qpvuntsm f9393373 first
+    Rebased 1 descendant commits
+    Working copy now at: rlvkpnrz 3dd407b8 (empty) second
+    Parent commit      : qpvuntsm f9393373 first
+    Added 0 files, modified 1 files, removed 0 files
+    ");
+    let stdout = test_env.jj_cmd_success(&repo_path, &["file", "show", "file"]);
+    insta::assert_snapshot!(stdout, @r"
+    1
+    2
+    a
+    B
+    c
+    d
+    ");
+
+    let (stdout, stderr) = test_env.jj_cmd_stdin_ok(&repo_path, &["apply", "-r", "@"], "");
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @"Nothing changed.");
+}
+
+fn get_log_output(test_env: &TestEnvironment, repo_path: &Path) -> String {
+    let template = r#"separate(" ", description.first_line(), author.name(), author.timestamp())"#;
+    test_env.jj_cmd_success(repo_path, &["log", "-T", template])
+}
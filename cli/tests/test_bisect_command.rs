@@ -0,0 +1,236 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::path::Path;
+
+use crate::common::TestEnvironment;
+
+/// Creates a linear history of commits "1" through "8". The file `file`
+/// contains "bug" starting from commit "5".
+fn create_history(test_env: &TestEnvironment, repo_path: &Path) {
+    for i in 1..=8 {
+        let content = if i >= 5 { "bug\n" } else { "ok\n" };
+        std::fs::write(repo_path.join("file"), content).unwrap();
+        test_env.jj_cmd_ok(repo_path, &["commit", "-m", &i.to_string()]);
+    }
+}
+
+#[test]
+fn test_bisect_manual() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    create_history(&test_env, &repo_path);
+
+    let (stdout, stderr) = test_env.jj_cmd_ok(
+        &repo_path,
+        &["bisect", "start", "--good=description(1)", "--bad=@-"],
+    );
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r"
+    Now evaluating: zsuskuln b1ced6a7 (empty) 4
+    6 revisions left to evaluate (roughly 3 steps)
+    Working copy now at: znkkpsqq 500126e3 (empty) (no description set)
+    Parent commit      : zsuskuln b1ced6a7 (empty) 4
+    Added 0 files, modified 1 files, removed 0 files
+    ");
+
+    let (_stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["bisect", "good"]);
+    insta::assert_snapshot!(stderr, @r"
+    Now evaluating: royxmykx e05309ea (empty) 6
+    3 revisions left to evaluate (roughly 2 steps)
+    Working copy now at: kpqxywon 556c6b90 (empty) (no description set)
+    Parent commit      : royxmykx e05309ea (empty) 6
+    Added 0 files, modified 1 files, removed 0 files
+    ");
+    let (_stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["bisect", "bad"]);
+    insta::assert_snapshot!(stderr, @r"
+    Now evaluating: mzvwutvl 33ee026e 5
+    1 revisions left to evaluate (roughly 1 steps)
+    Working copy now at: kmkuslsw 44f88af8 (empty) (no description set)
+    Parent commit      : mzvwutvl 33ee026e 5
+    ");
+    let (_stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["bisect", "bad"]);
+    insta::assert_snapshot!(stderr, @"The first bad revision is: mzvwutvl 33ee026e 5");
+
+    // Undoing the last mark brings back the previous state of the bisection
+    test_env.jj_cmd_ok(&repo_path, &["undo"]);
+    let (_stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["bisect", "reset"]);
+    insta::assert_snapshot!(stderr, @"Forgot 4 marked revisions");
+    let (_stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["bisect", "reset"]);
+    insta::assert_snapshot!(stderr, @"No bisection in progress");
+}
+
+#[test]
+fn test_bisect_skip() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    create_history(&test_env, &repo_path);
+
+    test_env.jj_cmd_ok(
+        &repo_path,
+        &[
+            "bisect",
+            "start",
+            "--good=description(4)",
+            "--bad=description(6)",
+        ],
+    );
+    // The only revision in between is skipped, so the result is ambiguous
+    let (_stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["bisect", "skip"]);
+    insta::assert_snapshot!(stderr, @r"
+    Some revisions were skipped. The first bad revision could be any of:
+      royxmykx e05309ea (empty) 6
+      mzvwutvl 33ee026e 5
+    ");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_bisect_command() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    create_history(&test_env, &repo_path);
+
+    let (stdout, stderr) = test_env.jj_cmd_ok(
+        &repo_path,
+        &[
+            "bisect",
+            "start",
+            "--good=root()",
+            "--bad=@-",
+            "--command=! grep -q bug file",
+        ],
+    );
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r"
+    Now evaluating: zsuskuln b1ced6a7 (empty) 4
+    7 revisions left to evaluate (roughly 3 steps)
+    Working copy now at: znkkpsqq 500126e3 (empty) (no description set)
+    Parent commit      : zsuskuln b1ced6a7 (empty) 4
+    Added 0 files, modified 1 files, removed 0 files
+    The revision is good (exit status: 0)
+    Now evaluating: royxmykx e05309ea (empty) 6
+    3 revisions left to evaluate (roughly 2 steps)
+    Working copy now at: uuzqqzqu 027bb7f9 (empty) (no description set)
+    Parent commit      : royxmykx e05309ea (empty) 6
+    Added 0 files, modified 1 files, removed 0 files
+    The revision is bad (exit status: 1)
+    Now evaluating: mzvwutvl 33ee026e 5
+    1 revisions left to evaluate (roughly 1 steps)
+    Working copy now at: lpnsqqnl 1ac36248 (empty) (no description set)
+    Parent commit      : mzvwutvl 33ee026e 5
+    The revision is bad (exit status: 1)
+    The first bad revision is: mzvwutvl 33ee026e 5
+    ");
+
+    // Exit code 125 marks the revision as skipped
+    let (_stdout, stderr) = test_env.jj_cmd_ok(
+        &repo_path,
+        &[
+            "bisect",
+            "start",
+            "--good=root()",
+            "--bad=@-",
+            "--command=exit 125",
+        ],
+    );
+    insta::assert_snapshot!(stderr, @r"
+    Now evaluating: rlvkpnrz c1829e82 (empty) 2
+    4 revisions left to evaluate (roughly 3 steps)
+    Working copy now at: kpqxywon b340de70 (empty) (no description set)
+    Parent commit      : rlvkpnrz c1829e82 (empty) 2
+    Added 0 files, modified 1 files, removed 0 files
+    The revision is skipped (exit status: 125)
+    Now evaluating: kkmpptxz 39007e91 (empty) 3
+    3 revisions left to evaluate (roughly 2 steps)
+    Working copy now at: vzqnnsmr 901fc45c (empty) (no description set)
+    Parent commit      : kkmpptxz 39007e91 (empty) 3
+    The revision is skipped (exit status: 125)
+    Now evaluating: qpvuntsm 0c8aee0c 1
+    2 revisions left to evaluate (roughly 2 steps)
+    Working copy now at: rlvoymqp 8af348d0 (empty) (no description set)
+    Parent commit      : qpvuntsm 0c8aee0c 1
+    The revision is skipped (exit status: 125)
+    Now evaluating: zsuskuln b1ced6a7 (empty) 4
+    1 revisions left to evaluate (roughly 1 steps)
+    Working copy now at: wlzkpxyy 128c61ba (empty) (no description set)
+    Parent commit      : zsuskuln b1ced6a7 (empty) 4
+    The revision is skipped (exit status: 125)
+    Some revisions were skipped. The first bad revision could be any of:
+      mzvwutvl 33ee026e 5
+      zsuskuln b1ced6a7 (empty) 4
+      kkmpptxz 39007e91 (empty) 3
+      rlvkpnrz c1829e82 (empty) 2
+      qpvuntsm 0c8aee0c 1
+    ");
+
+    // Changes made by the command stop the bisection
+    let stderr = test_env.jj_cmd_failure(
+        &repo_path,
+        &[
+            "bisect",
+            "start",
+            "--good=root()",
+            "--bad=@-",
+            "--command=echo output >out",
+        ],
+    );
+    insta::assert_snapshot!(stderr, @r"
+    Now evaluating: rlvkpnrz c1829e82 (empty) 2
+    3 revisions left to evaluate (roughly 2 steps)
+    Working copy now at: kmkuslsw 762cf994 (empty) (no description set)
+    Parent commit      : rlvkpnrz c1829e82 (empty) 2
+    The revision is good (exit status: 0)
+    Error: Stopping the bisection because the command modified the working copy
+    Hint: Inspect or abandon the changes, then mark the revision as good.
+    ");
+}
+
+#[test]
+fn test_bisect_no_bad_revision() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["bisect", "good"]);
+    insta::assert_snapshot!(stderr, @r"
+    Error: No commit has been marked as bad
+    Hint: Mark a revision as bad with `jj bisect bad` or `jj bisect start --bad`.
+    ");
+}
+
+#[test]
+fn test_bisect_bad_revision_before_good() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    create_history(&test_env, &repo_path);
+
+    let stderr = test_env.jj_cmd_failure(
+        &repo_path,
+        &[
+            "bisect",
+            "start",
+            "--good=description(6)",
+            "--bad=description(3)",
+        ],
+    );
+    insta::assert_snapshot!(stderr, @r"
+    Error: Every commit marked as bad is an ancestor of a commit marked as good
+    Hint: Check the marks, or start over with `jj bisect reset`.
+    ");
+}
@@ -1394,6 +1394,44 @@ fn test_bookmark_list() {
     "###);
 }
 
+#[test]
+fn test_bookmark_list_json() {
+    let test_env = TestEnvironment::default();
+    test_env.add_config("git.auto-local-bookmark = true");
+
+    // Initialize remote refs
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "remote"]);
+    let remote_path = test_env.env_root().join("remote");
+    test_env.jj_cmd_ok(&remote_path, &["bookmark", "create", "-r@", "remote-sync"]);
+    test_env.jj_cmd_ok(&remote_path, &["git", "export"]);
+
+    // Initialize local refs
+    let mut remote_git_path = remote_path;
+    remote_git_path.extend([".jj", "repo", "store", "git"]);
+    test_env.jj_cmd_ok(
+        test_env.env_root(),
+        &["git", "clone", remote_git_path.to_str().unwrap(), "local"],
+    );
+    let local_path = test_env.env_root().join("local");
+    test_env.jj_cmd_ok(&local_path, &["bookmark", "create", "-r@", "local-only"]);
+
+    let stdout = test_env.jj_cmd_success(
+        &local_path,
+        &[
+            "bookmark",
+            "list",
+            "--all-remotes",
+            "-T",
+            r#"json(self) ++ "\n""#,
+        ],
+    );
+    insta::assert_snapshot!(stdout, @r#"
+    {"name":"local-only","remote":null,"target":["f652c32197cfd6978501fab60d52a8299175ffa4"],"tracking_target":null}
+    {"name":"remote-sync","remote":null,"target":["230dd059e1b059aefc0da06a2e5a7dbf22362f22"],"tracking_target":null}
+    {"name":"remote-sync","remote":"origin","target":["230dd059e1b059aefc0da06a2e5a7dbf22362f22"],"tracking_target":["230dd059e1b059aefc0da06a2e5a7dbf22362f22"]}
+    "#);
+}
+
 #[test]
 fn test_bookmark_list_filtered() {
     let test_env = TestEnvironment::default();
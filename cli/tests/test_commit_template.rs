@@ -693,6 +693,47 @@ fn test_log_git_head() {
     "#);
 }
 
+#[test]
+fn test_log_json() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    test_env.jj_cmd_ok(&repo_path, &["new", "-m", "first\n\nbody"]);
+    test_env.jj_cmd_ok(&repo_path, &["bookmark", "create", "-r@", "main"]);
+
+    let template = r#"json(self) ++ "\n""#;
+    let stdout = test_env.jj_cmd_success(&repo_path, &["log", "--no-graph", "-T", template]);
+    insta::assert_snapshot!(stdout, @r#"
+    {"author":{"email":"test.user@example.com","name":"Test User","timestamp":"2001-02-03T04:05:08.000+07:00"},"change_id":"rlvkpnrzqnoowoytxnquwvuryrwnrmlp","commit_id":"c8a7d5b578f1cd0cc94fd3e7b4b565d58a8ff6b1","committer":{"email":"test.user@example.com","name":"Test User","timestamp":"2001-02-03T04:05:08.000+07:00"},"description":"first\n\nbody\n","parents":["230dd059e1b059aefc0da06a2e5a7dbf22362f22"]}
+    {"author":{"email":"test.user@example.com","name":"Test User","timestamp":"2001-02-03T04:05:07.000+07:00"},"change_id":"qpvuntsmwlqtpsluzzsnyyzlmlwvmlnu","commit_id":"230dd059e1b059aefc0da06a2e5a7dbf22362f22","committer":{"email":"test.user@example.com","name":"Test User","timestamp":"2001-02-03T04:05:07.000+07:00"},"description":"","parents":["0000000000000000000000000000000000000000"]}
+    {"author":{"email":"","name":"","timestamp":"1970-01-01T00:00:00.000+00:00"},"change_id":"zzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzz","commit_id":"0000000000000000000000000000000000000000","committer":{"email":"","name":"","timestamp":"1970-01-01T00:00:00.000+00:00"},"description":"","parents":[]}
+    "#);
+
+    let template = r#"json(bookmarks) ++ "\n""#;
+    let stdout = test_env.jj_cmd_success(&repo_path, &["show", "-s", "-T", template]);
+    insta::assert_snapshot!(stdout, @r#"[{"name":"main","remote":null,"target":["c8a7d5b578f1cd0cc94fd3e7b4b565d58a8ff6b1"],"tracking_target":null}]"#);
+
+    let template = r#"json(change_id.shortest(8)) ++ "\n""#;
+    let stdout = test_env.jj_cmd_success(&repo_path, &["log", "--no-graph", "-r@", "-T", template]);
+    insta::assert_snapshot!(stdout, @r#"{"prefix":"r","rest":"lvkpnrz"}"#);
+
+    // Templates can't be serialized
+    let stderr = test_env.jj_cmd_failure(
+        &repo_path,
+        &["log", "-T", "json(separate(' ', change_id, commit_id))"],
+    );
+    insta::assert_snapshot!(stderr, @r"
+    Error: Failed to parse template: Expected expression of type `Serializable`, but actual type is `Template`
+    Caused by:  --> 1:6
+      |
+    1 | json(separate(' ', change_id, commit_id))
+      |      ^---------------------------------^
+      |
+      = Expected expression of type `Serializable`, but actual type is `Template`
+    ");
+}
+
 #[test]
 fn test_log_commit_id_normal_hex() {
     let test_env = TestEnvironment::default();
@@ -541,22 +541,27 @@ fn test_config() {
 
     let stdout = test_env.jj_cmd_success(dir, &["--", "jj", "config", "get", "c"]);
     insta::assert_snapshot!(stdout, @r"
-    core.fsmonitor	Whether to use an external filesystem monitor, useful for large repos
+    core.fsmonitor	Whether to use a filesystem monitor, useful for large repos
+    core.native-fsmonitor.start-daemon	Whether to start the built-in filesystem monitor in the background if it isn't running
     core.watchman.register_snapshot_trigger	Whether to use triggers to monitor for changes in the background.
     ");
 
     let stdout = test_env.jj_cmd_success(dir, &["--", "jj", "config", "list", "c"]);
     insta::assert_snapshot!(stdout, @r"
     colors	Mapping from jj formatter labels to colors
+    content-mergers	Mergers used to resolve conflicting changes to structured files, e.g. during rebase
     core
-    core.fsmonitor	Whether to use an external filesystem monitor, useful for large repos
+    core.fsmonitor	Whether to use a filesystem monitor, useful for large repos
+    core.native-fsmonitor
+    core.native-fsmonitor.start-daemon	Whether to start the built-in filesystem monitor in the background if it isn't running
     core.watchman
     core.watchman.register_snapshot_trigger	Whether to use triggers to monitor for changes in the background.
     ");
 
     let stdout = test_env.jj_cmd_success(dir, &["--", "jj", "log", "--config", "c"]);
     insta::assert_snapshot!(stdout, @r"
-    core.fsmonitor=	Whether to use an external filesystem monitor, useful for large repos
+    core.fsmonitor=	Whether to use a filesystem monitor, useful for large repos
+    core.native-fsmonitor.start-daemon=	Whether to start the built-in filesystem monitor in the background if it isn't running
     core.watchman.register_snapshot_trigger=	Whether to use triggers to monitor for changes in the background.
     ");
 
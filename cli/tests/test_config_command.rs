@@ -451,22 +451,84 @@ fn test_config_layer_workspace() {
     insta::assert_snapshot!(stdout, @r###"
     ui.editor = "main-repo"
     "###);
+
+    // Workspace
+    test_env.jj_cmd_ok(
+        &secondary_path,
+        &[
+            "config",
+            "set",
+            "--workspace",
+            config_key,
+            "secondary-workspace",
+        ],
+    );
+    let stdout = test_env.jj_cmd_success(&main_path, &["config", "list", config_key]);
+    insta::assert_snapshot!(stdout, @r#"
+    ui.editor = "main-repo"
+    "#);
+    let stdout = test_env.jj_cmd_success(&secondary_path, &["config", "list", config_key]);
+    insta::assert_snapshot!(stdout, @r#"
+    ui.editor = "secondary-workspace"
+    "#);
+    let stdout = test_env.jj_cmd_success(&secondary_path, &["config", "list", "--workspace"]);
+    insta::assert_snapshot!(stdout, @r#"
+    ui.editor = "secondary-workspace"
+    "#);
+    insta::assert_snapshot!(
+        test_env.jj_cmd_success(&secondary_path, &["config", "path", "--workspace"]),
+        @"$TEST_ENV/secondary/.jj/working_copy/config.toml");
+    insta::assert_snapshot!(
+        test_env.jj_cmd_failure(test_env.env_root(), &["config", "path", "--workspace"]),
+        @"Error: No workspace config path found");
+}
+
+#[test]
+fn test_config_conditional_workspaces() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "main"]);
+    let main_path = test_env.env_root().join("main");
+    let secondary_path = test_env.env_root().join("secondary");
+    test_env.jj_cmd_ok(
+        &main_path,
+        &["workspace", "add", "--name", "second", "../secondary"],
+    );
+    std::fs::write(
+        main_path.join(".jj/repo/config.toml"),
+        format!(
+            indoc! {"
+                foo = 'repo'
+                [[--scope]]
+                --when.workspaces = [{secondary_path}]
+                foo = 'secondary'
+            "},
+            secondary_path = to_toml_value(dunce::simplified(&secondary_path).to_str().unwrap())
+        ),
+    )
+    .unwrap();
+
+    let stdout = test_env.jj_cmd_success(&main_path, &["config", "get", "foo"]);
+    insta::assert_snapshot!(stdout, @"repo");
+    let stdout = test_env.jj_cmd_success(&secondary_path, &["config", "get", "foo"]);
+    insta::assert_snapshot!(stdout, @"secondary");
+    let stdout = test_env.jj_cmd_success(&main_path, &["config", "get", "foo", "-R../secondary"]);
+    insta::assert_snapshot!(stdout, @"secondary");
 }
 
 #[test]
 fn test_config_set_bad_opts() {
     let test_env = TestEnvironment::default();
     let stderr = test_env.jj_cmd_cli_error(test_env.env_root(), &["config", "set"]);
-    insta::assert_snapshot!(stderr, @r###"
+    insta::assert_snapshot!(stderr, @r"
     error: the following required arguments were not provided:
-      <--user|--repo>
+      <--user|--repo|--workspace>
       <NAME>
       <VALUE>
 
-    Usage: jj config set <--user|--repo> <NAME> <VALUE>
+    Usage: jj config set <--user|--repo|--workspace> <NAME> <VALUE>
 
     For more information, try '--help'.
-    "###);
+    ");
 
     let stderr =
         test_env.jj_cmd_cli_error(test_env.env_root(), &["config", "set", "--user", "", "x"]);
@@ -800,14 +862,14 @@ fn test_config_unset_for_repo() {
 fn test_config_edit_missing_opt() {
     let test_env = TestEnvironment::default();
     let stderr = test_env.jj_cmd_cli_error(test_env.env_root(), &["config", "edit"]);
-    insta::assert_snapshot!(stderr, @r###"
+    insta::assert_snapshot!(stderr, @r"
     error: the following required arguments were not provided:
-      <--user|--repo>
+      <--user|--repo|--workspace>
 
-    Usage: jj config edit <--user|--repo>
+    Usage: jj config edit <--user|--repo|--workspace>
 
     For more information, try '--help'.
-    "###);
+    ");
 }
 
 #[test]
@@ -223,6 +223,85 @@ fn test_diff_basic() {
     insta::assert_snapshot!(stderr, @"");
 }
 
+#[test]
+fn test_diff_rename_detection() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    std::fs::write(repo_path.join("file1"), "1\n2\n3\n4\n5\n").unwrap();
+    std::fs::write(repo_path.join("file2"), "a\nb\nc\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["new"]);
+    std::fs::remove_file(repo_path.join("file1")).unwrap();
+    std::fs::remove_file(repo_path.join("file2")).unwrap();
+    // 60% similar to file1
+    std::fs::write(repo_path.join("file3"), "1\n2\n3\nx\ny\n").unwrap();
+    // 33% similar to file2
+    std::fs::write(repo_path.join("file4"), "a\ny\nz\n").unwrap();
+
+    // Disabled by default
+    let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "--summary"]);
+    insta::assert_snapshot!(stdout, @r"
+    D file1
+    D file2
+    A file3
+    A file4
+    ");
+
+    test_env.add_config("diff.renames.enabled = true");
+    let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "--summary"]);
+    insta::assert_snapshot!(stdout, @r"
+    D file2
+    R {file1 => file3}
+    A file4
+    ");
+    let stdout = test_env.jj_cmd_success(&repo_path, &["status"]);
+    insta::assert_snapshot!(stdout, @r"
+    Working copy changes:
+    D file2
+    R {file1 => file3}
+    A file4
+    Working copy : rlvkpnrz 6bba5d4c (no description set)
+    Parent commit: qpvuntsm d460aca4 (no description set)
+    ");
+
+    // Lower threshold
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &["diff", "--summary", "--config=diff.renames.threshold=30"],
+    );
+    insta::assert_snapshot!(stdout, @r"
+    R {file1 => file3}
+    R {file2 => file4}
+    ");
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &[
+            "log",
+            "-r@",
+            "--no-graph",
+            "-T",
+            "diff.summary()",
+            "--config=diff.renames.threshold=30",
+        ],
+    );
+    insta::assert_snapshot!(stdout, @r"
+    R {file1 => file3}
+    R {file2 => file4}
+    ");
+
+    // Invalid threshold
+    let stderr = test_env.jj_cmd_failure(
+        &repo_path,
+        &["diff", "--summary", "--config=diff.renames.threshold=0"],
+    );
+    insta::assert_snapshot!(stderr, @r"
+    Config error: Invalid type or value for diff.renames.threshold
+    Caused by: Invalid similarity threshold: 0
+    For help, see https://jj-vcs.github.io/jj/latest/config/.
+    ");
+}
+
 #[test]
 fn test_diff_empty() {
     let test_env = TestEnvironment::default();
@@ -735,6 +814,28 @@ fn test_diff_hunks() {
     [38;5;2m<<diff added::+>>[4m<<diff added token::bar>>[24m[39m
     [38;5;2m<<diff added::+baz >>[4m<<diff added token::quux>>[24m<<diff added:: blah blah>>[39m
     ");
+
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &[
+            "diff",
+            "--git",
+            "--color=debug",
+            "--config=diff.git.highlight-words=false",
+            "file3",
+        ],
+    );
+    insta::assert_snapshot!(stdout, @r"
+    [1m<<diff file_header::diff --git a/file3 b/file3>>[0m
+    [1m<<diff file_header::index 221a95a095..a543ef3892 100644>>[0m
+    [1m<<diff file_header::--- a/file3>>[0m
+    [1m<<diff file_header::+++ b/file3>>[0m
+    [38;5;6m<<diff hunk_header::@@ -1,2 +1,3 @@>>[39m
+    <<diff context:: foo>>
+    [38;5;1m<<diff removed::-baz qux blah blah>>[39m
+    [38;5;2m<<diff added::+bar>>[39m
+    [38;5;2m<<diff added::+baz quux blah blah>>[39m
+    ");
 }
 
 #[test]
@@ -1623,6 +1724,446 @@ fn test_diff_ignore_whitespace() {
     "#);
 }
 
+#[test]
+fn test_diff_algorithm() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    std::fs::write(
+        repo_path.join("file1"),
+        "fn foo() {\n    1\n}\n\nfn bar() {\n    2\n}\n",
+    )
+    .unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["new"]);
+    std::fs::write(
+        repo_path.join("file1"),
+        "fn bar() {\n    2\n}\n\nfn foo() {\n    1\n}\n",
+    )
+    .unwrap();
+
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &["diff", "--git", "--config=diff.algorithm=myers"],
+    );
+    insta::assert_snapshot!(stdout, @r"
+    diff --git a/file1 b/file1
+    index 1f954c625d..37d1541cf7 100644
+    --- a/file1
+    +++ b/file1
+    @@ -1,7 +1,7 @@
+    +fn bar() {
+    +    2
+    +}
+    +
+     fn foo() {
+         1
+    -}
+    -
+    -fn bar() {
+    -    2
+     }
+    ");
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &["diff", "--git", "--config=diff.algorithm=histogram"],
+    );
+    insta::assert_snapshot!(stdout, @r"
+    diff --git a/file1 b/file1
+    index 1f954c625d..37d1541cf7 100644
+    --- a/file1
+    +++ b/file1
+    @@ -1,7 +1,7 @@
+    -fn foo() {
+    -    1
+    -}
+    -
+     fn bar() {
+         2
+    +}
+    +
+    +fn foo() {
+    +    1
+     }
+    ");
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &["diff", "--color-words", "--config=diff.algorithm=patience"],
+    );
+    insta::assert_snapshot!(stdout, @r"
+    Modified regular file file1:
+            1: fn bar() {
+            2:     2
+            3: }
+            4: 
+       1    5: fn foo() {
+       2    6:     1
+       3     : }
+       4     : 
+       5     : fn bar() {
+       6     :     2
+       7    7: }
+    ");
+
+    // Unknown algorithm
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["diff", "--config=diff.algorithm=foo"]);
+    insta::assert_snapshot!(stderr, @r"
+    Config error: Invalid type or value for diff.algorithm
+    Caused by: unknown variant `foo`, expected one of `lcs`, `myers`, `patience`, `histogram`
+
+    For help, see https://jj-vcs.github.io/jj/latest/config/.
+    ");
+}
+
+#[test]
+fn test_diff_side_by_side() {
+    let mut test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    std::fs::write(
+        repo_path.join("file1"),
+        "a\nb\nc\nd\ne\nf\ng\nfoo bar\nbaz\n",
+    )
+    .unwrap();
+    std::fs::write(repo_path.join("file2"), "x\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["new"]);
+    std::fs::write(
+        repo_path.join("file1"),
+        "a\nb\nc\nd\ne\nf\ng\nfoo qux\n\tindented\nbaz and a long line that needs to be wrapped\n",
+    )
+    .unwrap();
+    std::fs::remove_file(repo_path.join("file2")).unwrap();
+    std::fs::write(repo_path.join("file3"), "new\n").unwrap();
+
+    test_env.add_env_var("COLUMNS", "50");
+    let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "--side-by-side"]);
+    insta::assert_snapshot!(stdout, @r"
+    Modified regular file file1:
+        ...
+       5 e                  │    5 e
+       6 f                  │    6 f
+       7 g                  │    7 g
+       8 foo bar            │    8 foo qux
+       9 baz                │    9     indented
+                            │   10 baz and a long
+                            │      line that needs to
+                            │      be wrapped
+    Removed regular file file2:
+       1 x                  │
+    Added regular file file3:
+                            │    1 new
+    ");
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &[
+            "diff",
+            "--side-by-side",
+            "--color=debug",
+            "--context=1",
+            "file1",
+        ],
+    );
+    insta::assert_snapshot!(stdout, @r"
+    [38;5;3m<<diff header::Modified regular file file1:>>[39m
+    <<diff::    ...>>
+    [38;5;1m<<diff removed line_number::   7>>[39m<<diff:: g                  │ >>[38;5;2m<<diff added line_number::   7>>[39m<<diff:: g>>
+    [38;5;1m<<diff removed line_number::   8>>[39m<<diff:: >>[38;5;1m<<diff removed::foo >>[4m<<diff removed token::bar>>[24m[39m<<diff::            │ >>[38;5;2m<<diff added line_number::   8>>[39m<<diff:: >>[38;5;2m<<diff added::foo >>[4m<<diff added token::qux>>[24m[39m<<diff::>>
+    [38;5;1m<<diff removed line_number::   9>>[39m<<diff:: >>[38;5;1m<<diff removed::baz>>[39m<<diff::                │ >>[38;5;2m<<diff added line_number::   9>>[39m<<diff:: >>[4m[38;5;2m<<diff added token::    indented>>[24m[39m<<diff::>>
+    <<diff::                        │ >>[38;5;2m<<diff added line_number::  10>>[39m<<diff:: >>[38;5;2m<<diff added::baz>>[4m<<diff added token:: and a long>>[24m[39m<<diff::>>
+    <<diff::                        │      >>[4m[38;5;2m<<diff added token::line that needs to>>[24m[39m<<diff::>>
+    <<diff::                        │      >>[4m[38;5;2m<<diff added token::be wrapped>>[24m[39m<<diff::>>
+    ");
+
+    // Narrow terminal
+    test_env.add_env_var("COLUMNS", "20");
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &["diff", "--config=ui.diff.format=side-by-side", "file1"],
+    );
+    insta::assert_snapshot!(stdout, @r"
+    Modified regular file file1:
+        ...
+       5 e   │    5 e
+       6 f   │    6 f
+       7 g   │    7 g
+       8 foo │    8 foo
+         bar │      qux
+       9 baz │    9 
+             │      ind
+             │      ent
+             │      ed
+             │   10 baz
+             │      and
+             │      a
+             │      lon
+             │      g
+             │      lin
+             │      e
+             │      tha
+             │      t
+             │      nee
+             │      ds
+             │      to
+             │      be
+             │      wra
+             │      ppe
+             │      d
+    ");
+
+    // Changed words aren't highlighted if disabled
+    test_env.add_env_var("COLUMNS", "50");
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &[
+            "diff",
+            "--side-by-side",
+            "--color=debug",
+            "--context=0",
+            "--config=diff.side-by-side.highlight-words=false",
+            "file1",
+        ],
+    );
+    insta::assert_snapshot!(stdout, @r"
+    [38;5;3m<<diff header::Modified regular file file1:>>[39m
+    <<diff::    ...>>
+    [38;5;1m<<diff removed line_number::   8>>[39m<<diff:: >>[38;5;1m<<diff removed::foo bar>>[39m<<diff::            │ >>[38;5;2m<<diff added line_number::   8>>[39m<<diff:: >>[38;5;2m<<diff added::foo qux>>[39m<<diff::>>
+    [38;5;1m<<diff removed line_number::   9>>[39m<<diff:: >>[38;5;1m<<diff removed::baz>>[39m<<diff::                │ >>[38;5;2m<<diff added line_number::   9>>[39m<<diff:: >>[38;5;2m<<diff added::    indented>>[39m<<diff::>>
+    <<diff::                        │ >>[38;5;2m<<diff added line_number::  10>>[39m<<diff:: >>[38;5;2m<<diff added::baz and a long>>[39m<<diff::>>
+    <<diff::                        │      >>[38;5;2m<<diff added::line that needs to>>[39m<<diff::>>
+    <<diff::                        │      >>[38;5;2m<<diff added::be wrapped>>[39m<<diff::>>
+    ");
+
+    // Line numbers are padded to fit the largest one in the hunk
+    let content = (1..=10001).map(|i| format!("{i}\n")).collect::<String>();
+    std::fs::write(repo_path.join("file1"), &content).unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["new"]);
+    std::fs::write(repo_path.join("file1"), content.replace("10000\n", "x\n")).unwrap();
+    let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "--side-by-side", "--context=1"]);
+    insta::assert_snapshot!(stdout, @r"
+    Modified regular file file1:
+        ...
+     9999 9999              │  9999 9999
+    10000 10000             │ 10000 x
+    10001 10001             │ 10001 10001
+    ");
+}
+
+#[cfg(not(feature = "structural-diff"))]
+#[test]
+fn test_diff_structural_unsupported() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    std::fs::write(repo_path.join("lib.rs"), "fn f() {}\n").unwrap();
+
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["diff", "--structural"]);
+    insta::assert_snapshot!(stderr, @r"
+    Error: Structural diff requires jj to be built with the `structural-diff` feature
+    ");
+}
+
+#[cfg(feature = "structural-diff")]
+#[test]
+fn test_diff_structural() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    std::fs::write(
+        repo_path.join("lib.rs"),
+        "fn f(a: u32, b: u32) -> u32 {\n    a + b\n}\n",
+    )
+    .unwrap();
+    std::fs::write(repo_path.join("main.py"), "def f(a, b):\n    return a+b\n").unwrap();
+    std::fs::write(repo_path.join("file.txt"), "foo bar\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["new"]);
+    std::fs::write(
+        repo_path.join("lib.rs"),
+        "fn f(\n    a: u32,\n    b: u32,\n) -> u32 {\n    a * b\n}\n",
+    )
+    .unwrap();
+    std::fs::write(
+        repo_path.join("main.py"),
+        "def f(a, b):\n    return a + b\n",
+    )
+    .unwrap();
+    std::fs::write(repo_path.join("file.txt"), "foo   baz\n").unwrap();
+
+    let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "--structural"]);
+    insta::assert_snapshot!(stdout, @r"
+    Modified regular file file.txt:
+       1    1: foo bar  baz
+    Modified regular file lib.rs:
+       1    1: fn f(
+       1    2:     a: u32,
+       1    3:     b: u32,
+       1    4: ) -> u32 {
+       2    5:     a +  * b
+       3    6: }
+    Modified regular file main.py:
+        (formatting changes only)
+    ");
+
+    // The format can be set in config
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &["diff", "--config=ui.diff.format=structural", "main.py"],
+    );
+    insta::assert_snapshot!(stdout, @r"
+    Modified regular file main.py:
+        (formatting changes only)
+    ");
+
+    // Context lines
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &["diff", "--structural", "--context=0", "lib.rs"],
+    );
+    insta::assert_snapshot!(stdout, @r"
+    Modified regular file lib.rs:
+        ...
+       1    3:     b: u32,
+        ...
+       2    5:     a +  * b
+        ...
+    ");
+
+    // Indentation is significant in Python
+    std::fs::write(
+        repo_path.join("main.py"),
+        "def f(a, b):\n    pass\nreturn a+b\n",
+    )
+    .unwrap();
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &["diff", "--color=always", "--structural", "main.py"],
+    );
+    insta::assert_snapshot!(stdout, @r"
+    [38;5;3mModified regular file main.py:[39m
+    [38;5;1m   1[39m [38;5;2m   1[39m: def f(a, b):[4m[38;5;1m[24m[39m
+    [38;5;1m   2[39m [38;5;2m   1[39m: [4m[38;5;1m    return [38;5;2m[24m[39m
+         [38;5;2m   2[39m: [4m[38;5;2m    pass[24m[39m
+    [38;5;1m   2[39m [38;5;2m   3[39m: [4m[38;5;2mreturn [24m[39ma+b
+    ");
+
+    // Syntax errors fall back to color-words diff
+    std::fs::write(repo_path.join("lib.rs"), "fn f(\n").unwrap();
+    let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "--structural", "lib.rs"]);
+    insta::assert_snapshot!(stdout, @r"
+    Modified regular file lib.rs:
+       1    1: fn f(a: u32, b: u32) -> u32 {
+       2     :     a + b
+       3    1: }
+    ");
+}
+
+#[test]
+fn test_diff_color_moved() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    std::fs::write(
+        repo_path.join("file1"),
+        "fn foo() {\n    do_something(argument);\n}\n\nfn bar() {\n    other_thing();\n}\n",
+    )
+    .unwrap();
+    std::fs::write(repo_path.join("file2"), "x\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["new"]);
+    // foo() is moved to file2, and a short line isn't considered moved
+    std::fs::write(
+        repo_path.join("file1"),
+        "fn bar() {\n    other_thing();\n}\nx\n",
+    )
+    .unwrap();
+    std::fs::write(
+        repo_path.join("file2"),
+        "fn foo() {\n    do_something(argument);\n}\n",
+    )
+    .unwrap();
+
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &["diff", "--git", "--color=debug", "--color-moved"],
+    );
+    insta::assert_snapshot!(stdout, @r"
+    [1m<<diff file_header::diff --git a/file1 b/file1>>[0m
+    [1m<<diff file_header::index 2535cc8195..d301ff8fe7 100644>>[0m
+    [1m<<diff file_header::--- a/file1>>[0m
+    [1m<<diff file_header::+++ b/file1>>[0m
+    [38;5;6m<<diff hunk_header::@@ -1,7 +1,4 @@>>[39m
+    [38;5;5m<<diff removed moved::->>[4m<<diff removed moved token::fn foo() {>>[24m[39m
+    [38;5;5m<<diff removed moved::->>[4m<<diff removed moved token::    do_something(argument);>>[24m[39m
+    [38;5;5m<<diff removed moved::->>[4m<<diff removed moved token::}>>[24m[39m
+    [38;5;1m<<diff removed::->>[4m<<diff removed token::>>[24m[39m
+    <<diff context:: fn bar() {>>
+    <<diff context::     other_thing();>>
+    <<diff context:: }>>
+    [38;5;2m<<diff added::+>>[4m<<diff added token::x>>[24m[39m
+    [1m<<diff file_header::diff --git a/file2 b/file2>>[0m
+    [1m<<diff file_header::index 587be6b4c3..27447f95fe 100644>>[0m
+    [1m<<diff file_header::--- a/file2>>[0m
+    [1m<<diff file_header::+++ b/file2>>[0m
+    [38;5;6m<<diff hunk_header::@@ -1,1 +1,3 @@>>[39m
+    [38;5;1m<<diff removed::->>[4m<<diff removed token::x>>[24m<<diff removed::>>[39m
+    [38;5;6m<<diff added moved::+>>[4m<<diff added moved token::fn foo() {>>[24m[39m
+    [38;5;6m<<diff added moved::+>>[4m<<diff added moved token::    do_something(argument);>>[24m[39m
+    [38;5;6m<<diff added moved::+>>[4m<<diff added moved token::}>>[24m<<diff added moved::>>[39m
+    ");
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &[
+            "diff",
+            "--color-words",
+            "--color=debug",
+            "--config=diff.color-moved=true",
+        ],
+    );
+    insta::assert_snapshot!(stdout, @r"
+    [38;5;3m<<diff header::Modified regular file file1:>>[39m
+    [38;5;1m<<diff removed line_number::   1>>[39m<<diff::     : >>[4m[38;5;5m<<diff removed moved token::fn foo() {>>[24m[39m
+    [38;5;1m<<diff removed line_number::   2>>[39m<<diff::     : >>[4m[38;5;5m<<diff removed moved token::    do_something(argument);>>[24m[39m
+    [38;5;1m<<diff removed line_number::   3>>[39m<<diff::     : >>[4m[38;5;5m<<diff removed moved token::}>>[24m[39m
+    [38;5;1m<<diff removed line_number::   4>>[39m<<diff::     : >>[4m[38;5;1m<<diff removed token::>>[24m[39m
+    [38;5;1m<<diff removed line_number::   5>>[39m<<diff:: >>[38;5;2m<<diff added line_number::   1>>[39m<<diff::: fn bar() {>>
+    [38;5;1m<<diff removed line_number::   6>>[39m<<diff:: >>[38;5;2m<<diff added line_number::   2>>[39m<<diff:::     other_thing();>>
+    [38;5;1m<<diff removed line_number::   7>>[39m<<diff:: >>[38;5;2m<<diff added line_number::   3>>[39m<<diff::: }>>
+    <<diff::     >>[38;5;2m<<diff added line_number::   4>>[39m<<diff::: >>[4m[38;5;2m<<diff added token::x>>[24m[39m
+    [38;5;3m<<diff header::Modified regular file file2:>>[39m
+    [38;5;1m<<diff removed line_number::   1>>[39m<<diff:: >>[38;5;2m<<diff added line_number::   1>>[39m<<diff::: >>[4m[38;5;1m<<diff removed token::x>>[38;5;2m<<diff added token::fn foo() {>>[24m[39m
+    <<diff::     >>[38;5;2m<<diff added line_number::   2>>[39m<<diff::: >>[4m[38;5;6m<<diff added moved token::    do_something(argument);>>[24m[39m
+    [38;5;1m<<diff removed line_number::   1>>[39m<<diff:: >>[38;5;2m<<diff added line_number::   3>>[39m<<diff::: >>[4m[38;5;2m<<diff added token::}>>[24m[39m<<diff::>>
+    ");
+
+    // Disabled by default
+    let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "--git", "--color=debug"]);
+    insta::assert_snapshot!(stdout, @r"
+    [1m<<diff file_header::diff --git a/file1 b/file1>>[0m
+    [1m<<diff file_header::index 2535cc8195..d301ff8fe7 100644>>[0m
+    [1m<<diff file_header::--- a/file1>>[0m
+    [1m<<diff file_header::+++ b/file1>>[0m
+    [38;5;6m<<diff hunk_header::@@ -1,7 +1,4 @@>>[39m
+    [38;5;1m<<diff removed::->>[4m<<diff removed token::fn foo() {>>[24m[39m
+    [38;5;1m<<diff removed::->>[4m<<diff removed token::    do_something(argument);>>[24m[39m
+    [38;5;1m<<diff removed::->>[4m<<diff removed token::}>>[24m[39m
+    [38;5;1m<<diff removed::->>[4m<<diff removed token::>>[24m[39m
+    <<diff context:: fn bar() {>>
+    <<diff context::     other_thing();>>
+    <<diff context:: }>>
+    [38;5;2m<<diff added::+>>[4m<<diff added token::x>>[24m[39m
+    [1m<<diff file_header::diff --git a/file2 b/file2>>[0m
+    [1m<<diff file_header::index 587be6b4c3..27447f95fe 100644>>[0m
+    [1m<<diff file_header::--- a/file2>>[0m
+    [1m<<diff file_header::+++ b/file2>>[0m
+    [38;5;6m<<diff hunk_header::@@ -1,1 +1,3 @@>>[39m
+    [38;5;1m<<diff removed::->>[4m<<diff removed token::x>>[24m<<diff removed::>>[39m
+    [38;5;2m<<diff added::+>>[4m<<diff added token::fn foo() {>>[24m[39m
+    [38;5;2m<<diff added::+>>[4m<<diff added token::    do_something(argument);>>[24m[39m
+    [38;5;2m<<diff added::+>>[4m<<diff added token::}>>[24m<<diff added::>>[39m
+    ");
+}
+
 #[test]
 fn test_diff_skipped_context() {
     let test_env = TestEnvironment::default();
@@ -2599,7 +3140,7 @@ fn test_diff_binary() {
     std::fs::write(repo_path.join("file4.png"), b"\0\0\0").unwrap();
 
     let stdout = test_env.jj_cmd_success(&repo_path, &["diff"]);
-    insta::assert_snapshot!(stdout, @r###"
+    insta::assert_snapshot!(stdout, @r"
     Removed regular file file1.png:
         (binary)
     Modified regular file file2.png:
@@ -2608,10 +3149,10 @@ fn test_diff_binary() {
         (binary)
     Added regular file file4.png:
         (binary)
-    "###);
+    ");
 
     let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "--git"]);
-    insta::assert_snapshot!(stdout, @r###"
+    insta::assert_snapshot!(stdout, @r"
     diff --git a/file1.png b/file1.png
     deleted file mode 100644
     index 2b65b23c22..0000000000
@@ -2627,14 +3168,62 @@ fn test_diff_binary() {
     new file mode 100644
     index 0000000000..4227ca4e87
     Binary files /dev/null and b/file4.png differ
-    "###);
+    ");
 
     let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "--stat"]);
-    insta::assert_snapshot!(stdout, @r###"
+    insta::assert_snapshot!(stdout, @r"
     file1.png | 3 ---
     file2.png | 5 ++---
     file3.png | 3 +++
     file4.png | 1 +
     4 files changed, 6 insertions(+), 6 deletions(-)
-    "###);
+    ");
+
+    // Similar binary files are detected as renamed if enabled
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &["diff", "--stat", "--config=diff.renames.enabled=true"],
+    );
+    insta::assert_snapshot!(stdout, @r"
+    file2.png                | 5 ++---
+    {file1.png => file3.png} | 2 +-
+    file4.png                | 1 +
+    3 files changed, 4 insertions(+), 4 deletions(-)
+    ");
+}
+
+#[test]
+fn test_diff_binary_by_gitattributes() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    std::fs::write(repo_path.join(".gitattributes"), "*.min.js -diff\n").unwrap();
+    std::fs::write(repo_path.join("app.min.js"), "foo\n").unwrap();
+    std::fs::write(repo_path.join("app.js"), "foo\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["new"]);
+    std::fs::write(repo_path.join("app.min.js"), "bar\n").unwrap();
+    std::fs::write(repo_path.join("app.js"), "bar\n").unwrap();
+
+    let stdout = test_env.jj_cmd_success(&repo_path, &["diff"]);
+    insta::assert_snapshot!(stdout, @r"
+    Modified regular file app.js:
+       1    1: foobar
+    Modified regular file app.min.js:
+        (binary)
+    ");
+
+    let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "--git"]);
+    insta::assert_snapshot!(stdout, @r"
+    diff --git a/app.js b/app.js
+    index 257cc5642c..5716ca5987 100644
+    --- a/app.js
+    +++ b/app.js
+    @@ -1,1 +1,1 @@
+    -foo
+    +bar
+    diff --git a/app.min.js b/app.min.js
+    index 257cc5642c..5716ca5987 100644
+    Binary files a/app.min.js and b/app.min.js differ
+    ");
 }
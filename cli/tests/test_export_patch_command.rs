@@ -0,0 +1,101 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use regex::Regex;
+
+use crate::common::TestEnvironment;
+
+#[test]
+fn test_export_patch() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    std::fs::write(repo_path.join("file"), "a\nb\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["commit", "-m", "add file\n\nWith a body."]);
+    std::fs::write(repo_path.join("file"), "a\nc\n").unwrap();
+    std::fs::write(repo_path.join("binary"), b"\0\x01\x02").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m", "modify: file"]);
+
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["export-patch", "root()"]);
+    insta::assert_snapshot!(stderr, @"Error: Cannot export the root commit");
+
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["export-patch", "none()"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @"No revisions to export.");
+
+    let (stdout, _stderr) = test_env.jj_cmd_ok(&repo_path, &["export-patch", "::@ & ~root()"]);
+    let version_re = Regex::new(r"(?m)^jj .*$").unwrap();
+    insta::assert_snapshot!(version_re.replace_all(&stdout, "jj [VERSION]"), @r"
+    From bb0b7ce100dba59f9c74947176b522ea88f5098f Mon Sep 17 00:00:00 2001
+    From: Test User <test.user@example.com>
+    Date: Sat, 3 Feb 2001 04:05:08 +0700
+    Subject: [PATCH 1/2] add file
+    MIME-Version: 1.0
+    Content-Type: text/plain; charset=UTF-8
+    Content-Transfer-Encoding: 8bit
+
+    With a body.
+    ---
+    diff --git a/file b/file
+    new file mode 100644
+    index 0000000000..422c2b7ab3
+    --- /dev/null
+    +++ b/file
+    @@ -0,0 +1,2 @@
+    +a
+    +b
+    -- 
+    jj [VERSION]
+
+    From 0866e96dc0c80dcd6607fb8005f27c3aa175b3f5 Mon Sep 17 00:00:00 2001
+    From: Test User <test.user@example.com>
+    Date: Sat, 3 Feb 2001 04:05:09 +0700
+    Subject: [PATCH 2/2] modify: file
+    MIME-Version: 1.0
+    Content-Type: text/plain; charset=UTF-8
+    Content-Transfer-Encoding: 8bit
+
+    ---
+    diff --git a/binary b/binary
+    new file mode 100644
+    index 0000000000..8352675d67
+    GIT binary patch
+    literal 3
+    Kc${NkWC8#H2LJ>B
+
+    literal 0
+    Hc$@<O00001
+
+    diff --git a/file b/file
+    index 422c2b7ab3..0f7bc76605 100644
+    --- a/file
+    +++ b/file
+    @@ -1,2 +1,2 @@
+     a
+    -b
+    +c
+    -- 
+    jj [VERSION]
+    ");
+
+    let (stdout, _stderr) =
+        test_env.jj_cmd_ok(&repo_path, &["export-patch", "@-", "@", "-o", "out"]);
+    insta::assert_snapshot!(stdout, @r"
+    out/0001-add-file.patch
+    out/0002-modify-file.patch
+    ");
+    assert!(repo_path.join("out/0001-add-file.patch").is_file());
+    assert!(repo_path.join("out/0002-modify-file.patch").is_file());
+}
@@ -203,3 +203,102 @@ fn test_annotate_with_template() {
        6: and a third!
     "#);
 }
+
+#[test]
+fn test_annotate_renamed() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    std::fs::write(repo_path.join("file1.txt"), "line1\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["commit", "-m=initial"]);
+
+    append_to_file(&repo_path.join("file1.txt"), "new text from commit1");
+    test_env.jj_cmd_ok(&repo_path, &["commit", "-m=commit1"]);
+
+    std::fs::rename(repo_path.join("file1.txt"), repo_path.join("file2.txt")).unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["commit", "-m=renamed"]);
+
+    append_to_file(&repo_path.join("file2.txt"), "new text from commit2");
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m=commit2"]);
+
+    let stdout = test_env.jj_cmd_success(&repo_path, &["file", "annotate", "file2.txt"]);
+    insta::assert_snapshot!(stdout, @r"
+    kkmpptxz test.use 2001-02-03 08:05:10    1: line1
+    kkmpptxz test.use 2001-02-03 08:05:10    2: new text from commit1
+    zsuskuln test.use 2001-02-03 08:05:11    3: new text from commit2
+    ");
+
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &["file", "annotate", "--follow-copies", "file2.txt"],
+    );
+    insta::assert_snapshot!(stdout, @r"
+    qpvuntsm test.use 2001-02-03 08:05:08    1: line1
+    rlvkpnrz test.use 2001-02-03 08:05:09    2: new text from commit1
+    zsuskuln test.use 2001-02-03 08:05:11    3: new text from commit2
+    ");
+}
+
+#[test]
+fn test_annotate_ignore_revs() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    std::fs::write(repo_path.join("file.txt"), "line1\nline2\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["commit", "-m=initial"]);
+
+    std::fs::write(repo_path.join("file.txt"), "LINE1\nLINE2\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["commit", "-m=reformat"]);
+
+    append_to_file(&repo_path.join("file.txt"), "line3");
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m=commit1"]);
+
+    let template = r#"commit.description().first_line() ++ ": " ++ content"#;
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &["file", "annotate", "file.txt", "-T", template],
+    );
+    insta::assert_snapshot!(stdout, @r"
+    reformat: LINE1
+    reformat: LINE2
+    commit1: line3
+    ");
+
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &[
+            "file",
+            "annotate",
+            "file.txt",
+            "-T",
+            template,
+            "--ignore-revs=description(reformat)",
+        ],
+    );
+    insta::assert_snapshot!(stdout, @r"
+    initial: LINE1
+    initial: LINE2
+    commit1: line3
+    ");
+
+    // Default set of ignored revisions can be configured
+    test_env.add_config(r#"revsets.annotate-ignore = "description(reformat)""#);
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &["file", "annotate", "file.txt", "-T", template],
+    );
+    insta::assert_snapshot!(stdout, @r"
+    initial: LINE1
+    initial: LINE2
+    commit1: line3
+    ");
+
+    // Invalid revset
+    let stderr = test_env.jj_cmd_failure(
+        &repo_path,
+        &["file", "annotate", "file.txt", "--ignore-revs=nonexistent"],
+    );
+    insta::assert_snapshot!(stderr, @"Error: Revision `nonexistent` doesn't exist");
+}
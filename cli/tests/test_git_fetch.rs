@@ -133,6 +133,35 @@ fn test_git_fetch_with_default_config(subprocess: bool) {
     }
 }
 
+#[test]
+fn test_git_fetch_with_gix_transport() {
+    let test_env = TestEnvironment::default();
+    test_env.add_config("git.gix-transport = true");
+    test_env.add_config("git.auto-local-bookmark = true");
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    add_git_remote(&test_env, &repo_path, "origin");
+
+    test_env.jj_cmd_ok(&repo_path, &["git", "fetch"]);
+    insta::assert_snapshot!(get_bookmark_output(&test_env, &repo_path), @r"
+    origin: oputwtnw ffecd2d6 message
+      @origin: oputwtnw ffecd2d6 message
+    ");
+
+    // HTTP remotes are reached through the network client
+    test_env.jj_cmd_ok(
+        &repo_path,
+        &["git", "remote", "add", "web", "http://127.0.0.1:9/repo.git"],
+    );
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["git", "fetch", "--remote=web"]);
+    insta::assert_snapshot!(stderr, @r"
+    Error: Failed to communicate with the remote
+    Caused by:
+    1: An IO error occurred when talking to the server
+    2: error sending request for url (http://127.0.0.1:9/repo.git/info/refs?service=git-upload-pack)
+    ");
+}
+
 #[test_case(false; "use git2 for remote calls")]
 #[test_case(true; "spawn a git subprocess for remote calls")]
 fn test_git_fetch_default_remote(subprocess: bool) {
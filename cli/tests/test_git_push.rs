@@ -180,6 +180,166 @@ fn test_git_push_current_bookmark(subprocess: bool) {
     }
 }
 
+#[test]
+fn test_git_push_with_gix_transport() {
+    let (test_env, workspace_root) = set_up();
+    test_env.add_config("git.gix-transport = true");
+    test_env.jj_cmd_ok(&workspace_root, &["edit", "bookmark1"]);
+    test_env.jj_cmd_ok(
+        &workspace_root,
+        &["describe", "-m", "modified bookmark1 commit"],
+    );
+    std::fs::write(workspace_root.join("file"), "contents").unwrap();
+    test_env.jj_cmd_ok(&workspace_root, &["bookmark", "delete", "bookmark2"]);
+    let (stdout, stderr) = test_env.jj_cmd_ok(&workspace_root, &["git", "push", "--all"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r"
+    Changes to push to origin:
+      Move sideways bookmark bookmark1 from d13ecdbda2a2 to bc1fe5c0f669
+      Delete bookmark bookmark2 from 8476341eb395
+    ");
+    insta::assert_snapshot!(get_bookmark_output(&test_env, &workspace_root), @r"
+    bookmark1: xtvrqkyv bc1fe5c0 modified bookmark1 commit
+      @origin: xtvrqkyv bc1fe5c0 modified bookmark1 commit
+    ");
+
+    // The remote has the pushed commit and its contents
+    let origin_path = test_env.env_root().join("origin");
+    test_env.jj_cmd_ok(&origin_path, &["git", "import"]);
+    let stdout =
+        test_env.jj_cmd_success(&origin_path, &["file", "show", "-r", "bookmark1", "file"]);
+    insta::assert_snapshot!(stdout, @"contents");
+    let stdout = test_env.jj_cmd_success(&origin_path, &["bookmark", "list"]);
+    insta::assert_snapshot!(stdout, @"bookmark1: yrmuopyv bc1fe5c0 modified bookmark1 commit");
+}
+
+#[test]
+fn test_git_push_with_gix_transport_receive_settings() {
+    let (test_env, workspace_root) = set_up();
+    test_env.add_config("git.gix-transport = true");
+    let origin_git_repo_path = test_env
+        .env_root()
+        .join("origin")
+        .join(".jj")
+        .join("repo")
+        .join("store")
+        .join("git");
+    let origin_git_repo = git2::Repository::open(origin_git_repo_path).unwrap();
+    origin_git_repo
+        .config()
+        .unwrap()
+        .set_bool("receive.denyDeletes", true)
+        .unwrap();
+
+    // The remote's receive settings apply to local pushes too
+    test_env.jj_cmd_ok(&workspace_root, &["bookmark", "delete", "bookmark2"]);
+    let stderr = test_env.jj_cmd_failure(&workspace_root, &["git", "push", "--deleted"]);
+    insta::assert_snapshot!(stderr, @r"
+    Changes to push to origin:
+      Delete bookmark bookmark2 from 8476341eb395
+    Error: Remote rejected the update of refs/heads/bookmark2 (deletion prohibited)
+    ");
+    assert!(origin_git_repo
+        .find_reference("refs/heads/bookmark2")
+        .is_ok());
+
+    // Same through git-receive-pack
+    let stderr = test_env.jj_cmd_failure(
+        &workspace_root,
+        &[
+            "git",
+            "push",
+            "--deleted",
+            "--config=git.gix-local-receive-pack=true",
+        ],
+    );
+    insta::assert_snapshot!(stderr, @r"
+    Changes to push to origin:
+      Delete bookmark bookmark2 from 8476341eb395
+    Error: Remote rejected the update of refs/heads/bookmark2 (deletion prohibited)
+    ");
+    assert!(origin_git_repo
+        .find_reference("refs/heads/bookmark2")
+        .is_ok());
+
+    origin_git_repo
+        .config()
+        .unwrap()
+        .set_bool("receive.denyNonFastForwards", true)
+        .unwrap();
+    test_env.jj_cmd_ok(&workspace_root, &["edit", "bookmark1"]);
+    test_env.jj_cmd_ok(
+        &workspace_root,
+        &["describe", "-m", "modified bookmark1 commit"],
+    );
+    let stderr = test_env.jj_cmd_failure(&workspace_root, &["git", "push", "-bbookmark1"]);
+    insta::assert_snapshot!(stderr, @r"
+    Changes to push to origin:
+      Move sideways bookmark bookmark1 from d13ecdbda2a2 to 3da2a5280214
+    Error: Remote rejected the update of refs/heads/bookmark1 (non-fast-forward)
+    ");
+    let stderr = test_env.jj_cmd_failure(
+        &workspace_root,
+        &[
+            "git",
+            "push",
+            "-bbookmark1",
+            "--config=git.gix-local-receive-pack=true",
+        ],
+    );
+    insta::assert_snapshot!(stderr, @r"
+    Changes to push to origin:
+      Move sideways bookmark bookmark1 from d13ecdbda2a2 to 3da2a5280214
+    Error: Remote rejected the update of refs/heads/bookmark1 (non-fast-forward)
+    ");
+
+    // Fast-forwards are still allowed
+    test_env.jj_cmd_ok(
+        &workspace_root,
+        &["new", "bookmark2@origin", "-m", "child commit"],
+    );
+    test_env.jj_cmd_ok(&workspace_root, &["bookmark", "set", "bookmark2", "-r@"]);
+    let (_stdout, stderr) = test_env.jj_cmd_ok(&workspace_root, &["git", "push", "-bbookmark2"]);
+    insta::assert_snapshot!(stderr, @r"
+    Changes to push to origin:
+      Move forward bookmark bookmark2 from 8476341eb395 to 0b6a4d4a24d9
+    ");
+}
+
+#[test]
+fn test_git_push_with_gix_transport_checked_out_branch() {
+    let test_env = TestEnvironment::default();
+    test_env.add_config("git.gix-transport = true");
+    let git_repo = git2::Repository::init(test_env.env_root().join("source")).unwrap();
+    let signature =
+        git2::Signature::new("Some One", "some.one@example.com", &git2::Time::new(0, 0)).unwrap();
+    let tree_id = git_repo.treebuilder(None).unwrap().write().unwrap();
+    let tree = git_repo.find_tree(tree_id).unwrap();
+    git_repo
+        .commit(
+            Some("refs/heads/main"),
+            &signature,
+            &signature,
+            "initial",
+            &tree,
+            &[],
+        )
+        .unwrap();
+    git_repo.set_head("refs/heads/main").unwrap();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "clone", "source", "local"]);
+    let workspace_root = test_env.env_root().join("local");
+
+    // The remote refuses to move the branch checked out in its working copy
+    test_env.jj_cmd_ok(&workspace_root, &["describe", "-m", "new commit"]);
+    test_env.jj_cmd_ok(&workspace_root, &["bookmark", "set", "main", "-r@"]);
+    let stderr = test_env.jj_cmd_failure(&workspace_root, &["git", "push"]);
+    insta::assert_snapshot!(stderr, @r"
+    Changes to push to origin:
+      Move forward bookmark main from e1d60cf57c9f to e2d05eb9ee99
+    Error: Remote rejected the update of refs/heads/main (branch is currently checked out)
+    ");
+}
+
 #[test_case(false; "use git2 for remote calls")]
 #[test_case(true; "spawn a git subprocess for remote calls")]
 fn test_git_push_parent_bookmark(subprocess: bool) {
@@ -12,6 +12,8 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use jj_lib::backend::CommitId;
+
 use crate::common::git;
 use crate::common::TestEnvironment;
 
@@ -64,3 +66,140 @@ fn test_gitsubmodule_print_gitmodules() {
 	path:new
     "###);
 }
+
+/// Creates a superproject commit on `main` with a submodule at "sub" pointing
+/// to `submodule_id`.
+fn write_superproject_commit(
+    git_repo: &gix::Repository,
+    submodule_url: &str,
+    submodule_id: gix::ObjectId,
+    parents: &[gix::ObjectId],
+) -> gix::ObjectId {
+    let gitmodules = format!("[submodule \"sub\"]\n\tpath = sub\n\turl = {submodule_url}\n");
+    let blob_id = git_repo.write_blob(gitmodules.as_bytes()).unwrap();
+    let mut tree_editor = git_repo
+        .edit_tree(gix::ObjectId::empty_tree(git_repo.object_hash()))
+        .unwrap();
+    tree_editor
+        .upsert(".gitmodules", gix::object::tree::EntryKind::Blob, blob_id)
+        .unwrap();
+    tree_editor
+        .upsert("sub", gix::object::tree::EntryKind::Commit, submodule_id)
+        .unwrap();
+    let tree_id = tree_editor.write().unwrap().detach();
+    git::write_commit(
+        git_repo,
+        "refs/heads/main",
+        tree_id,
+        "superproject",
+        parents,
+    )
+}
+
+#[test]
+fn test_git_submodule_update_and_status() {
+    let test_env = TestEnvironment::default();
+    let origin_path = test_env.env_root().join("origin");
+    let origin_repo = git::init_bare(&origin_path);
+    let submodule_commit1 =
+        git::add_commit(&origin_repo, "refs/heads/main", "file", b"1\n", "1", &[]);
+    let submodule_commit2 = git::add_commit(
+        &origin_repo,
+        "refs/heads/main",
+        "file",
+        b"2\n",
+        "2",
+        &[submodule_commit1.commit_id],
+    );
+    let origin_url = origin_path.to_str().unwrap();
+
+    let workspace_root = test_env.env_root().join("repo");
+    let git_repo = git::init(&workspace_root);
+    let superproject_commit1 =
+        write_superproject_commit(&git_repo, origin_url, submodule_commit1.commit_id, &[]);
+    let superproject_commit2 = write_superproject_commit(
+        &git_repo,
+        origin_url,
+        submodule_commit2.commit_id,
+        &[superproject_commit1],
+    );
+    test_env.jj_cmd_ok(&workspace_root, &["git", "init", "--git-repo", "."]);
+    test_env.jj_cmd_ok(&workspace_root, &["new", &superproject_commit1.to_string()]);
+    let submodule_path = workspace_root.join("sub");
+    assert!(submodule_path.is_dir());
+
+    let stdout = test_env.jj_cmd_success(&workspace_root, &["git", "submodule", "status"]);
+    insta::assert_snapshot!(stdout, @"-741bee124c71 sub");
+
+    let (stdout, stderr) = test_env.jj_cmd_ok(&workspace_root, &["git", "submodule", "update"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @"Checked out 741bee124c71 in submodule sub");
+    assert_eq!(std::fs::read(submodule_path.join("file")).unwrap(), b"1\n");
+    let stdout = test_env.jj_cmd_success(&workspace_root, &["git", "submodule", "status"]);
+    insta::assert_snapshot!(stdout, @" 741bee124c71 sub");
+    let (_stdout, stderr) = test_env.jj_cmd_ok(&workspace_root, &["git", "submodule", "update"]);
+    insta::assert_snapshot!(stderr, @"Nothing changed.");
+
+    // Checking out another commit updates the submodule
+    test_env.jj_cmd_ok(&workspace_root, &["new", &superproject_commit2.to_string()]);
+    assert_eq!(std::fs::read(submodule_path.join("file")).unwrap(), b"2\n");
+    let stdout = test_env.jj_cmd_success(&workspace_root, &["git", "submodule", "status"]);
+    insta::assert_snapshot!(stdout, @" f84779565798 sub");
+
+    // Commit checked out in the submodule is snapshotted
+    let submodule_commit1_id = CommitId::from_bytes(submodule_commit1.commit_id.as_bytes());
+    jj_lib::git::check_out_submodule(&submodule_path, &submodule_commit1_id).unwrap();
+    let stdout = test_env.jj_cmd_success(&workspace_root, &["diff", "--summary"]);
+    insta::assert_snapshot!(stdout, @"M sub");
+    let stdout = test_env.jj_cmd_success(&workspace_root, &["git", "submodule", "status"]);
+    insta::assert_snapshot!(stdout, @" 741bee124c71 sub");
+}
+
+#[test]
+fn test_git_submodule_sync() {
+    let test_env = TestEnvironment::default();
+    let origin_path = test_env.env_root().join("origin");
+    let origin_repo = git::init_bare(&origin_path);
+    let submodule_commit =
+        git::add_commit(&origin_repo, "refs/heads/main", "file", b"1\n", "1", &[]);
+
+    let workspace_root = test_env.env_root().join("repo");
+    let git_repo = git::init(&workspace_root);
+    let superproject_commit = write_superproject_commit(
+        &git_repo,
+        origin_path.to_str().unwrap(),
+        submodule_commit.commit_id,
+        &[],
+    );
+    test_env.jj_cmd_ok(&workspace_root, &["git", "init", "--git-repo", "."]);
+    test_env.jj_cmd_ok(&workspace_root, &["new", &superproject_commit.to_string()]);
+
+    // Uninitialized submodules aren't synchronized
+    let (stdout, stderr) = test_env.jj_cmd_ok(&workspace_root, &["git", "submodule", "sync"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @"");
+
+    test_env.jj_cmd_ok(&workspace_root, &["git", "submodule", "update"]);
+    let moved_origin_path = test_env.env_root().join("moved-origin");
+    std::fs::rename(&origin_path, &moved_origin_path).unwrap();
+    std::fs::write(
+        workspace_root.join(".gitmodules"),
+        format!(
+            "[submodule \"sub\"]\n\tpath = sub\n\turl = {}\n",
+            moved_origin_path.to_str().unwrap()
+        ),
+    )
+    .unwrap();
+    let (stdout, stderr) = test_env.jj_cmd_ok(&workspace_root, &["git", "submodule", "sync"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @"Synchronized URL of submodule sub");
+    let submodule_repo = git::open(workspace_root.join("sub"));
+    let remote = submodule_repo.find_remote("origin").unwrap();
+    assert_eq!(
+        remote
+            .url(gix::remote::Direction::Fetch)
+            .unwrap()
+            .to_bstring(),
+        moved_origin_path.to_str().unwrap()
+    );
+}
@@ -907,6 +907,117 @@ fn test_log_filtered_by_path() {
     "###);
 }
 
+#[test]
+fn test_log_line_range() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    std::fs::write(repo_path.join("file1"), "a\nb\nc\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m", "add file1"]);
+    test_env.jj_cmd_ok(&repo_path, &["new", "-m", "modify b"]);
+    std::fs::write(repo_path.join("file1"), "a\nB\nc\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["new", "-m", "insert line above"]);
+    std::fs::write(repo_path.join("file1"), "x\na\nB\nc\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["new", "-m", "modify c"]);
+    std::fs::write(repo_path.join("file1"), "x\na\nB\nC\n").unwrap();
+
+    let stdout =
+        test_env.jj_cmd_success(&repo_path, &["log", "-T", "description", "-L", "file1:3,3"]);
+    insta::assert_snapshot!(stdout, @r"
+    modify b
+    diff --git a/file1 b/file1
+    --- a/file1
+    +++ b/file1
+    @@ -2,1 +2,1 @@
+    -b
+    +B
+    add file1
+    diff --git a/file1 b/file1
+    --- a/file1
+    +++ b/file1
+    @@ -0,0 +2,1 @@
+    +b
+    ");
+
+    // The range can be specified with a line count, starting at another revision
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &[
+            "log",
+            "-T",
+            "description",
+            "-L",
+            "file1:2,+2",
+            "-r@-",
+            "--reversed",
+        ],
+    );
+    insta::assert_snapshot!(stdout, @r"
+    add file1
+    diff --git a/file1 b/file1
+    --- a/file1
+    +++ b/file1
+    @@ -0,0 +1,2 @@
+    +a
+    +b
+    modify b
+    diff --git a/file1 b/file1
+    --- a/file1
+    +++ b/file1
+    @@ -1,2 +1,2 @@
+     a
+    -b
+    +B
+    ");
+
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &["log", "-T", "description", "-L", "file1:3,4", "-n1"],
+    );
+    insta::assert_snapshot!(stdout, @r"
+    modify c
+    diff --git a/file1 b/file1
+    --- a/file1
+    +++ b/file1
+    @@ -3,2 +3,2 @@
+     B
+    -c
+    +C
+    ");
+
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["log", "-L", "file1:0,1"]);
+    insta::assert_snapshot!(stderr, @r"
+    Error: Invalid line range: file1:0,1
+    Hint: The line range should be specified as <PATH>:<START>,<END> or <PATH>:<START>,+<COUNT>
+    ");
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["log", "-L", "file1"]);
+    insta::assert_snapshot!(stderr, @r"
+    Error: Invalid line range: file1
+    Hint: The line range should be specified as <PATH>:<START>,<END> or <PATH>:<START>,+<COUNT>
+    ");
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["log", "-L", "file2:1,1"]);
+    insta::assert_snapshot!(stderr, @"Error: No such path: file2");
+
+    // Diff formatting options don't apply to the line range diff
+    let stderr = test_env.jj_cmd_cli_error(&repo_path, &["log", "-L", "file1:3,3", "--stat"]);
+    insta::assert_snapshot!(stderr, @r"
+    error: the argument '--line-range <PATH:RANGE>' cannot be used with '--stat'
+
+    Usage: jj log --line-range <PATH:RANGE> [FILESETS]...
+
+    For more information, try '--help'.
+    ");
+    let stderr = test_env.jj_cmd_cli_error(&repo_path, &["log", "-L", "file1:3,3", "--context=1"]);
+    insta::assert_snapshot!(stderr, @r"
+    error: the argument '--line-range <PATH:RANGE>' cannot be used with '--context <CONTEXT>'
+
+    Usage: jj log --line-range <PATH:RANGE> [FILESETS]...
+
+    For more information, try '--help'.
+    ");
+}
+
 #[test]
 fn test_log_limit() {
     let test_env = TestEnvironment::default();
@@ -125,6 +125,24 @@ fn test_op_log_with_custom_symbols() {
     "#);
 }
 
+#[test]
+fn test_op_log_json() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m", "description 0"]);
+
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &["op", "log", "--no-graph", "-T", r#"json(self) ++ "\n""#],
+    );
+    insta::assert_snapshot!(&stdout, @r#"
+    {"description":"describe commit 230dd059e1b059aefc0da06a2e5a7dbf22362f22","hostname":"host.example.com","id":"d009cfc049934db1f8241a1eede02b3042a9a907fd8bc7c250b8325ae50dc52a879fed9a4e597c5025250e3b52a6357a28dfa74cb505b2d9f7012031c7629332","is_snapshot":false,"parents":["eac759b9ab75793fd3da96e60939fb48f2cd2b2a9c1f13ffe723cf620f3005b8d3e7e923634a07ea39513e4f2f360c87b9ad5d331cf90d7a844864b83b72eba1"],"tags":{"args":"jj describe -m 'description 0'"},"time":{"end":"2001-02-03T04:05:08.000+07:00","start":"2001-02-03T04:05:08.000+07:00"},"username":"test-username"}
+    {"description":"add workspace 'default'","hostname":"host.example.com","id":"eac759b9ab75793fd3da96e60939fb48f2cd2b2a9c1f13ffe723cf620f3005b8d3e7e923634a07ea39513e4f2f360c87b9ad5d331cf90d7a844864b83b72eba1","is_snapshot":false,"parents":["00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000"],"tags":{},"time":{"end":"2001-02-03T04:05:07.000+07:00","start":"2001-02-03T04:05:07.000+07:00"},"username":"test-username"}
+    {"description":"","hostname":"","id":"00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000","is_snapshot":false,"parents":[],"tags":{},"time":{"end":"1970-01-01T00:00:00.000+00:00","start":"1970-01-01T00:00:00.000+00:00"},"username":""}
+    "#);
+}
+
 #[test]
 fn test_op_log_with_no_template() {
     let test_env = TestEnvironment::default();
@@ -2802,6 +2802,95 @@ fn test_rebase_skip_if_on_destination() {
     "###);
 }
 
+#[test]
+fn test_rebase_with_content_merger() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    test_env.add_config(
+        r#"
+        [content-mergers.json]
+        patterns = ["glob:'**/*.json'"]
+        builtin = "json"
+        "#,
+    );
+
+    std::fs::write(
+        repo_path.join("file.json"),
+        "{\n  \"a\": 1,\n  \"b\": 1\n}\n",
+    )
+    .unwrap();
+    std::fs::write(repo_path.join("invalid.json"), "a\nb\nc\n").unwrap();
+    std::fs::write(repo_path.join("file.txt"), "a\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["commit", "-m", "base"]);
+    std::fs::write(
+        repo_path.join("file.json"),
+        "{\n  \"a\": 2,\n  \"b\": 1\n}\n",
+    )
+    .unwrap();
+    std::fs::write(repo_path.join("invalid.json"), "A\nb\nc\n").unwrap();
+    std::fs::write(repo_path.join("file.txt"), "b\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["commit", "-m", "left"]);
+    test_env.jj_cmd_ok(&repo_path, &["new", "description(base)", "-m", "right"]);
+    std::fs::write(
+        repo_path.join("file.json"),
+        "{\n  \"a\": 1,\n  \"b\": 2\n}\n",
+    )
+    .unwrap();
+    std::fs::write(repo_path.join("invalid.json"), "a\nb\nC\n").unwrap();
+    std::fs::write(repo_path.join("file.txt"), "c\n").unwrap();
+
+    // The JSON file is merged key by key, the invalid JSON file is merged line
+    // by line, but the text file conflicts
+    let (stdout, stderr) = test_env.jj_cmd_ok(
+        &repo_path,
+        &["rebase", "-r", "@", "-d", "description(left)"],
+    );
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r"
+    Rebased 1 commits onto destination
+    Working copy now at: zsuskuln c37aed0a (conflict) right
+    Parent commit      : rlvkpnrz 11be9860 left
+    Added 0 files, modified 3 files, removed 0 files
+    There are unresolved conflicts at these paths:
+    file.txt    2-sided conflict
+    New conflicts appeared in these commits:
+      zsuskuln c37aed0a (conflict) right
+    To resolve the conflicts, start by updating to it:
+      jj new zsuskuln
+    Then use `jj resolve`, or edit the conflict markers in the file directly.
+    Once the conflicts are resolved, you may want to inspect the result with `jj diff`.
+    Then run `jj squash` to move the resolution into the conflicted commit.
+    ");
+    let stdout = test_env.jj_cmd_success(&repo_path, &["file", "show", "file.json"]);
+    insta::assert_snapshot!(stdout, @r#"
+    {
+      "a": 2,
+      "b": 2
+    }
+    "#);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["file", "show", "invalid.json"]);
+    insta::assert_snapshot!(stdout, @r"
+    A
+    b
+    C
+    ");
+
+    test_env.add_config(
+        r#"
+        [content-mergers.bad]
+        patterns = ["glob:'*.json'"]
+        builtin = "yaml"
+        "#,
+    );
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["status"]);
+    insta::assert_snapshot!(stderr, @r"
+    Config error: Invalid `content-mergers` config
+    Caused by: Unknown builtin content merger 'yaml' in `content-mergers.bad`
+    For help, see https://jj-vcs.github.io/jj/latest/config/.
+    ");
+}
+
 fn get_log_output(test_env: &TestEnvironment, repo_path: &Path) -> String {
     let template = "bookmarks ++ surround(': ', '', parents.map(|c| c.bookmarks()))";
     test_env.jj_cmd_success(repo_path, &["log", "-T", template])
@@ -0,0 +1,205 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#[cfg(unix)]
+use std::path::Path;
+
+use crate::common::TestEnvironment;
+
+#[cfg(unix)]
+fn get_log_output(test_env: &TestEnvironment, repo_path: &Path) -> String {
+    let template = r#"commit_id.short() ++ " " ++ description.first_line()"#;
+    test_env.jj_cmd_success(repo_path, &["log", "-T", template])
+}
+
+#[cfg(unix)]
+#[test]
+fn test_run_modifies_each_revision() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    std::fs::write(repo_path.join("file"), "a\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["commit", "-m", "first"]);
+    std::fs::write(repo_path.join("file"), "a\nb\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["commit", "-m", "second"]);
+    std::fs::write(repo_path.join("other"), "c\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m", "third"]);
+
+    let (stdout, stderr) = test_env.jj_cmd_ok(
+        &repo_path,
+        &[
+            "run",
+            "tr a-z A-Z < file > file.new && mv file.new file",
+            "-r",
+            "::@ ~ root()",
+        ],
+    );
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r"
+    Updated 3 commits and rebased 0 descendant commits
+    Working copy now at: kkmpptxz bdadf706 third
+    Parent commit      : rlvkpnrz f60e6b9e second
+    Added 0 files, modified 1 files, removed 0 files
+    ");
+
+    let content = test_env.jj_cmd_success(&repo_path, &["file", "show", "file", "-r", "@--"]);
+    insta::assert_snapshot!(content, @"A\n");
+    let content = test_env.jj_cmd_success(&repo_path, &["file", "show", "file", "-r", "@-"]);
+    insta::assert_snapshot!(content, @"A\nB\n");
+    let content = test_env.jj_cmd_success(&repo_path, &["file", "show", "file", "-r", "@"]);
+    insta::assert_snapshot!(content, @"A\nB\n");
+    let content = std::fs::read_to_string(repo_path.join("file")).unwrap();
+    insta::assert_snapshot!(content, @"A\nB\n");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_run_rebases_descendants() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    std::fs::write(repo_path.join("file"), "a\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["commit", "-m", "first"]);
+    std::fs::write(repo_path.join("other"), "b\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m", "second"]);
+
+    // Files created by the command are tracked
+    let (_stdout, stderr) = test_env.jj_cmd_ok(
+        &repo_path,
+        &["run", "echo generated > generated", "-r", "@-"],
+    );
+    insta::assert_snapshot!(stderr, @r"
+    Updated 1 commits and rebased 1 descendant commits
+    Working copy now at: rlvkpnrz 1d590efc second
+    Parent commit      : qpvuntsm ad3f4fc3 first
+    Added 1 files, modified 0 files, removed 0 files
+    ");
+    let stdout = test_env.jj_cmd_success(&repo_path, &["file", "list", "-r", "@"]);
+    insta::assert_snapshot!(stdout, @r"
+    file
+    generated
+    other
+    ");
+    let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "--summary", "-r", "@"]);
+    insta::assert_snapshot!(stdout, @"A other");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_run_no_changes() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    std::fs::write(repo_path.join("file"), "a\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m", "first"]);
+    let log_before = get_log_output(&test_env, &repo_path);
+
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["run", "cat file"]);
+    insta::assert_snapshot!(stdout, @"a");
+    insta::assert_snapshot!(stderr, @r"
+    Output of the command on commit 2d4fa21a0946:
+    Nothing changed.
+    ");
+    assert_eq!(get_log_output(&test_env, &repo_path), log_before);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_run_command_fails() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    std::fs::write(repo_path.join("file"), "a\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["commit", "-m", "first"]);
+    std::fs::write(repo_path.join("file"), "b\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m", "second"]);
+    let log_before = get_log_output(&test_env, &repo_path);
+
+    // Nothing is rewritten if the command fails on any of the revisions
+    let stderr = test_env.jj_cmd_failure(
+        &repo_path,
+        &[
+            "run",
+            "echo changed > file; echo oops >&2; exit 3",
+            "-r",
+            "@",
+            "-j",
+            "1",
+        ],
+    );
+    insta::assert_snapshot!(stderr, @r"
+    Warning: Command failed on commit d8e152c49a45 with exit status: 3:
+    oops
+    Error: Command failed on 1 of 1 commits; no commits were rewritten
+    ");
+    assert_eq!(get_log_output(&test_env, &repo_path), log_before);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_run_prints_output() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    std::fs::write(repo_path.join("file"), "a\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["commit", "-m", "first"]);
+    std::fs::write(repo_path.join("file"), "b\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m", "second"]);
+
+    // Output of successful commands is forwarded for each revision
+    let (stdout, stderr) = test_env.jj_cmd_ok(
+        &repo_path,
+        &[
+            "run",
+            "cat file; echo warning >&2",
+            "-r",
+            "::@ ~ root()",
+            "-j",
+            "1",
+        ],
+    );
+    insta::assert_snapshot!(stdout, @r"
+    b
+    a
+    ");
+    insta::assert_snapshot!(stderr, @r"
+    Output of the command on commit d8e152c49a45:
+    warning
+    Output of the command on commit 2d4fa21a0946:
+    warning
+    Nothing changed.
+    ");
+
+    // Commands without output print nothing
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["run", "true", "-r", "@-"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r"
+    Nothing changed.
+    ");
+}
+
+#[test]
+fn test_run_immutable() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["run", "true", "-r", "root()"]);
+    insta::assert_snapshot!(stderr, @"Error: The root commit 000000000000 is immutable");
+}
@@ -12,6 +12,8 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::path::Path;
+
 use crate::common::git;
 use crate::common::TestEnvironment;
 
@@ -123,3 +125,182 @@ fn test_tag_list() {
     added_targets: commit2
     "###);
 }
+
+fn get_git_tags(repo_path: &Path) -> String {
+    let git_repo = git::open(repo_path);
+    let mut lines = vec![];
+    for git_ref in git_repo.references().unwrap().tags().unwrap() {
+        let mut git_ref = git_ref.unwrap();
+        let name = git_ref.name().shorten().to_string();
+        let ref_object = git_repo.find_object(git_ref.target().id()).unwrap();
+        let commit_id = git_ref.peel_to_id_in_place().unwrap().detach();
+        let commit_id = commit_id.to_hex_with_len(12);
+        match ref_object.try_into_tag() {
+            Ok(tag) => {
+                let tag = tag.decode().unwrap();
+                lines.push(format!(
+                    "{name}: {commit_id} annotated by {tagger}: {message:?}",
+                    tagger = tag.tagger.unwrap().name,
+                    message = tag.message,
+                ));
+            }
+            Err(_) => lines.push(format!("{name}: {commit_id}")),
+        }
+    }
+    lines.join("\n")
+}
+
+#[test]
+fn test_tag_create_set_delete() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "--colocate", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    test_env.jj_cmd_ok(&repo_path, &["commit", "-m", "commit1"]);
+    test_env.jj_cmd_ok(&repo_path, &["commit", "-m", "commit2"]);
+
+    let (_stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["tag", "create", "-r@--", "v1"]);
+    insta::assert_snapshot!(stderr, @"Created 1 tags pointing to qpvuntsm caf975d0 (empty) commit1");
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["tag", "create", "-r@-", "v1"]);
+    insta::assert_snapshot!(stderr, @r"
+    Error: Tag already exists: v1
+    Hint: Use `jj tag set` to update it.
+    ");
+    let (_stdout, stderr) = test_env.jj_cmd_ok(
+        &repo_path,
+        &["tag", "create", "-r@-", "v2", "-m", "Release 2"],
+    );
+    insta::assert_snapshot!(stderr, @"Created 1 tags pointing to rlvkpnrz d0a19ea4 (empty) commit2");
+    insta::assert_snapshot!(get_git_tags(&repo_path), @r#"
+    v1: caf975d0989a
+    v2: d0a19ea4c52e annotated by Test User: "Release 2\n"
+    "#);
+
+    // Tags are created or moved by `set`. Tags without a message are kept as
+    // they are.
+    let (_stdout, stderr) =
+        test_env.jj_cmd_ok(&repo_path, &["tag", "set", "-r@-", "v1", "v2", "v3"]);
+    insta::assert_snapshot!(stderr, @r"
+    Created 1 tags pointing to rlvkpnrz d0a19ea4 (empty) commit2
+    Moved 1 tags to rlvkpnrz d0a19ea4 (empty) commit2
+    ");
+    insta::assert_snapshot!(get_git_tags(&repo_path), @r#"
+    v1: d0a19ea4c52e
+    v2: d0a19ea4c52e annotated by Test User: "Release 2\n"
+    v3: d0a19ea4c52e
+    "#);
+    insta::assert_snapshot!(test_env.jj_cmd_success(&repo_path, &["tag", "list"]), @r"
+    v1: rlvkpnrz d0a19ea4 (empty) commit2
+    v2: rlvkpnrz d0a19ea4 (empty) commit2
+    v3: rlvkpnrz d0a19ea4 (empty) commit2
+    ");
+
+    // Tags already pointing to the target are annotated if a message is given
+    let (_stdout, stderr) =
+        test_env.jj_cmd_ok(&repo_path, &["tag", "set", "-r@-", "v1", "-m", "Release 1"]);
+    insta::assert_snapshot!(stderr, @"Annotated 1 tags pointing to rlvkpnrz d0a19ea4 (empty) commit2");
+    insta::assert_snapshot!(get_git_tags(&repo_path), @r#"
+    v1: d0a19ea4c52e annotated by Test User: "Release 1\n"
+    v2: d0a19ea4c52e annotated by Test User: "Release 2\n"
+    v3: d0a19ea4c52e
+    "#);
+
+    // Moved tags keep their annotation
+    let (_stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["tag", "set", "-r@--", "v1", "v2"]);
+    insta::assert_snapshot!(stderr, @"Moved 2 tags to qpvuntsm caf975d0 (empty) commit1");
+    insta::assert_snapshot!(get_git_tags(&repo_path), @r#"
+    v1: caf975d0989a annotated by Test User: "Release 1\n"
+    v2: caf975d0989a annotated by Test User: "Release 2\n"
+    v3: d0a19ea4c52e
+    "#);
+
+    let (_stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["tag", "delete", "glob:v*"]);
+    insta::assert_snapshot!(stderr, @"Deleted 3 tags.");
+    insta::assert_snapshot!(get_git_tags(&repo_path), @"");
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["tag", "delete", "v1"]);
+    insta::assert_snapshot!(stderr, @"Error: No such tag: v1");
+
+    // Undoing the deletion brings the tags back in Git
+    test_env.jj_cmd_ok(&repo_path, &["undo"]);
+    insta::assert_snapshot!(get_git_tags(&repo_path), @r#"
+    v1: caf975d0989a annotated by Test User: "Release 1\n"
+    v2: caf975d0989a annotated by Test User: "Release 2\n"
+    v3: d0a19ea4c52e
+    "#);
+}
+
+#[test]
+fn test_tag_move_annotated() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "--colocate", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    test_env.jj_cmd_ok(&repo_path, &["commit", "-m", "commit1"]);
+    test_env.jj_cmd_ok(&repo_path, &["commit", "-m", "commit2"]);
+    test_env.add_config(
+        r#"
+    signing.backend = "test"
+    signing.key = "impeccable"
+    "#,
+    );
+
+    test_env.jj_cmd_ok(
+        &repo_path,
+        &["tag", "create", "-r@--", "v1", "-m", "Release 1", "--sign"],
+    );
+    test_env.jj_cmd_ok(&repo_path, &["tag", "create", "-r@--", "light"]);
+    insta::assert_snapshot!(get_git_tags(&repo_path), @r#"
+    light: caf975d0989a
+    v1: caf975d0989a annotated by Test User: "Release 1\n\n--- JJ-TEST-SIGNATURE ---\nKEY: impeccable\nb4ba1edfc024c2ff4f28e8cb227d7b9bfbee96eab0146f8bfee9f82a8a33ee1c0d29d83f9e5227c13a90ee17d85ce35834de68bda3c2bda7dff4f6040db7b3ee\n"
+    "#);
+
+    let (_stdout, stderr) =
+        test_env.jj_cmd_ok(&repo_path, &["tag", "move", "--from=@--", "--to=@-"]);
+    insta::assert_snapshot!(stderr, @"Moved 2 tags to rlvkpnrz d0a19ea4 (empty) commit2");
+    insta::assert_snapshot!(get_git_tags(&repo_path), @r#"
+    light: d0a19ea4c52e
+    v1: d0a19ea4c52e annotated by Test User: "Release 1\n\n--- JJ-TEST-SIGNATURE ---\nKEY: impeccable\n18f1a6cae60d2c606ed522c203bccb50561bd74fe9741d8e4e63f44d315571307d489b78c483b21692e81814db6ea2a84fd66dce95da8c213f6f5de60c4bb133\n"
+    "#);
+    let (_stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["tag", "move", "v1", "--to=@-"]);
+    insta::assert_snapshot!(stderr, @"No tags to update.");
+}
+
+#[test]
+fn test_tag_moved_in_git() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    let git_repo_path = repo_path.join(".jj/repo/store/git");
+    test_env.jj_cmd_ok(&repo_path, &["commit", "-m", "commit1"]);
+    test_env.jj_cmd_ok(&repo_path, &["commit", "-m", "commit2"]);
+    test_env.jj_cmd_ok(&repo_path, &["tag", "create", "-r@--", "v1"]);
+    test_env.jj_cmd_ok(&repo_path, &["git", "export"]);
+
+    // Move the tag in Git behind jj's back, then move it in jj too
+    let git_repo = git::open(&git_repo_path);
+    let v1_commit = git_repo
+        .find_reference("refs/tags/v1")
+        .unwrap()
+        .id()
+        .detach();
+    let other_commit = git::add_commit(
+        &git_repo,
+        "refs/heads/other",
+        "file",
+        b"",
+        "other",
+        &[v1_commit],
+    );
+    git_repo
+        .tag_reference(
+            "v1",
+            other_commit.commit_id,
+            gix::refs::transaction::PreviousValue::Any,
+        )
+        .unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["tag", "set", "-r@-", "v1"]);
+    let (_stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["git", "export"]);
+    insta::assert_snapshot!(stderr, @r"
+    Nothing changed.
+    Warning: Failed to export some bookmarks or tags:
+      v1: Modified ref had been modified in Git
+    ");
+}
@@ -122,6 +122,145 @@ fn test_workspaces_sparse_patterns() {
     insta::assert_snapshot!(stdout, @"");
 }
 
+/// Test setting up workspaces from profiles
+#[test]
+fn test_workspaces_add_with_profile() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "main"]);
+    let main_path = test_env.env_root().join("main");
+    for dir in ["web", "shared", "server"] {
+        std::fs::create_dir(main_path.join(dir)).unwrap();
+        std::fs::write(main_path.join(dir).join("file"), "contents\n").unwrap();
+    }
+    test_env.jj_cmd_ok(&main_path, &["commit", "-m", "initial"]);
+    test_env.jj_cmd_ok(&main_path, &["bookmark", "create", "-r@-", "trunk"]);
+    test_env.jj_cmd_ok(&main_path, &["new", "root()"]);
+    test_env.add_config(
+        r#"
+        [workspace-profiles.frontend]
+        sparse-patterns = ["web", "shared"]
+        revisions = ["trunk"]
+
+        [workspace-profiles.frontend.config]
+        snapshot.max-new-file-size = "10MiB"
+        "#,
+    );
+
+    let (stdout, stderr) = test_env.jj_cmd_ok(
+        &main_path,
+        &["workspace", "add", "--profile", "frontend", "../frontend"],
+    );
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r#"
+    Created workspace in "../frontend"
+    Working copy now at: zxsnswpr 9274453c (empty) (no description set)
+    Parent commit      : qpvuntsm 25a35f85 trunk | initial
+    Added 2 files, modified 0 files, removed 0 files
+    "#);
+    let frontend_path = test_env.env_root().join("frontend");
+    let stdout = test_env.jj_cmd_success(&frontend_path, &["sparse", "list"]);
+    insta::assert_snapshot!(stdout, @r"
+    shared
+    web
+    ");
+    let stdout = test_env.jj_cmd_success(
+        &frontend_path,
+        &["config", "get", "snapshot.max-new-file-size"],
+    );
+    insta::assert_snapshot!(stdout, @"10MiB");
+    let stdout =
+        test_env.jj_cmd_success(&main_path, &["config", "get", "snapshot.max-new-file-size"]);
+    insta::assert_snapshot!(stdout, @"1MiB");
+    let config = std::fs::read_to_string(frontend_path.join(".jj/working_copy/config.toml"));
+    insta::assert_snapshot!(config.unwrap(), @r#"
+    snapshot.max-new-file-size = "10MiB"
+    "#);
+    insta::assert_snapshot!(get_log_output(&test_env, &main_path), @r"
+    @  f652c32197cf default@
+    │ ○  9274453cf8e6 frontend@
+    │ ○  25a35f858153
+    ├─╯
+    ◆  000000000000
+    ");
+
+    // Command-line options take precedence over the profile
+    test_env.jj_cmd_ok(
+        &main_path,
+        &[
+            "workspace",
+            "add",
+            "--profile=frontend",
+            "--sparse-patterns=full",
+            "-r=root()",
+            "../full",
+        ],
+    );
+    let full_path = test_env.env_root().join("full");
+    let stdout = test_env.jj_cmd_success(&full_path, &["sparse", "list"]);
+    insta::assert_snapshot!(stdout, @".");
+    insta::assert_snapshot!(get_log_output(&test_env, &main_path), @r"
+    @  f652c32197cf default@
+    │ ○  031f1f100cc2 full@
+    ├─╯
+    │ ○  9274453cf8e6 frontend@
+    │ ○  25a35f858153
+    ├─╯
+    ◆  000000000000
+    ");
+
+    // Unknown profile
+    let stderr = test_env.jj_cmd_failure(
+        &main_path,
+        &["workspace", "add", "--profile", "backend", "../backend"],
+    );
+    insta::assert_snapshot!(stderr, @r"
+    Error: Workspace profile 'backend' is not defined
+    Hint: Workspace profiles are configured in the `workspace-profiles` table.
+    ");
+
+    // Unresolvable revision in profile doesn't leave a workspace behind
+    test_env.add_config(
+        r#"
+        [workspace-profiles.broken]
+        revisions = ["missing"]
+        "#,
+    );
+    let stderr = test_env.jj_cmd_failure(
+        &main_path,
+        &["workspace", "add", "--profile", "broken", "../broken"],
+    );
+    insta::assert_snapshot!(stderr, @"Error: Revision `missing` doesn't exist");
+    assert!(!test_env.env_root().join("broken").exists());
+    let stdout = test_env.jj_cmd_success(&main_path, &["workspace", "list"]);
+    insta::assert_snapshot!(stdout, @r"
+    default: zsuskuln f652c321 (empty) (no description set)
+    frontend: zxsnswpr 9274453c (empty) (no description set)
+    full: uuzqqzqu 031f1f10 (empty) (no description set)
+    ");
+
+    // The profile's config applies to the new workspace right away
+    test_env.add_config(
+        r#"
+        [workspace-profiles.named.config]
+        user.name = "Profile User"
+        "#,
+    );
+    // The author would otherwise be overridden by $JJ_USER
+    test_env
+        .jj_cmd(
+            &main_path,
+            &["workspace", "add", "--profile", "named", "../named"],
+        )
+        .env_remove("JJ_USER")
+        .assert()
+        .success();
+    let stdout = test_env.jj_cmd_success(
+        &main_path,
+        &["log", "--no-graph", "-r=named@", "-T=author.name()"],
+    );
+    insta::assert_snapshot!(stdout, @"Profile User");
+}
+
 /// Test adding a second workspace while the current workspace is editing a
 /// merge
 #[test]
@@ -1265,6 +1404,112 @@ fn test_workspaces_rename_workspace() {
     "###);
 }
 
+/// Test moving workspaces and repairing the paths after a manual move
+#[test]
+fn test_workspaces_move() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "main"]);
+    let main_path = test_env.env_root().join("main");
+    let secondary_path = test_env.env_root().join("secondary");
+    std::fs::write(main_path.join("file"), "contents\n").unwrap();
+    test_env.jj_cmd_ok(
+        &main_path,
+        &["workspace", "add", "--name", "second", "../secondary"],
+    );
+
+    // Moving the workspace with the repo relinks the other workspaces
+    let (stdout, stderr) = test_env.jj_cmd_ok(&main_path, &["workspace", "move", "../moved"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r"
+    Moved workspace to $TEST_ENV/moved
+    Updated repo path of workspace second
+    ");
+    let moved_path = test_env.env_root().join("moved");
+    assert!(!main_path.exists());
+    insta::assert_snapshot!(get_log_output(&test_env, &secondary_path), @r"
+    @  57d63245a308 second@
+    │ ○  506f4ec3c2c6 default@
+    ├─╯
+    ◆  000000000000
+    ");
+
+    // Moving a secondary workspace
+    let (stdout, stderr) =
+        test_env.jj_cmd_ok(&secondary_path, &["workspace", "move", "../secondary2"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @"Moved workspace to $TEST_ENV/secondary2");
+    let secondary_path = test_env.env_root().join("secondary2");
+    insta::assert_snapshot!(get_log_output(&test_env, &moved_path), @r"
+    @  506f4ec3c2c6 default@
+    │ ○  57d63245a308 second@
+    ├─╯
+    ◆  000000000000
+    ");
+
+    // Cannot move to an existing path
+    let stderr = test_env.jj_cmd_failure(&moved_path, &["workspace", "move", "../secondary2"]);
+    insta::assert_snapshot!(stderr, @"Error: The destination $TEST_ENV/secondary2 already exists");
+
+    // Moving the workspace manually breaks the other workspaces
+    std::fs::rename(&moved_path, &main_path).unwrap();
+    let stderr = test_env.jj_cmd_failure(&secondary_path, &["status"]);
+    insta::assert_snapshot!(stderr.replace('\\', "/"), @r"
+    Error: The repository directory at $TEST_ENV/moved/.jj/repo is missing. Was it moved?
+    Hint: If the repo was moved, run `jj workspace repair --repo <path>` in this workspace.
+    ");
+    let (_stdout, stderr) = test_env.jj_cmd_ok(&main_path, &["status"]);
+    insta::assert_snapshot!(stderr.replace('\\', "/"), @r"
+    Warning: The workspace appears to have been moved from $TEST_ENV/moved
+    Hint: Run `jj workspace repair` to update the paths pointing to it.
+    ");
+    let (stdout, stderr) = test_env.jj_cmd_ok(&main_path, &["workspace", "repair"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr.replace('\\', "/"), @r"
+    Updated paths of workspace moved from $TEST_ENV/moved
+    Updated repo path of workspace second
+    ");
+    let (stdout, stderr) = test_env.jj_cmd_ok(&main_path, &["workspace", "repair"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @"Nothing changed.");
+    insta::assert_snapshot!(get_log_output(&test_env, &secondary_path), @r"
+    @  57d63245a308 second@
+    │ ○  506f4ec3c2c6 default@
+    ├─╯
+    ◆  000000000000
+    ");
+
+    // A broken workspace can be pointed at the moved repo
+    std::fs::rename(&main_path, &moved_path).unwrap();
+    let (stdout, stderr) = test_env.jj_cmd_ok(
+        &secondary_path,
+        &["workspace", "repair", "--repo", "../moved"],
+    );
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr.replace('\\', "/"), @"Updated repo path to $TEST_ENV/moved/.jj/repo");
+    insta::assert_snapshot!(get_log_output(&test_env, &secondary_path), @r"
+    @  57d63245a308 second@
+    │ ○  506f4ec3c2c6 default@
+    ├─╯
+    ◆  000000000000
+    ");
+}
+
+/// Test that workspaces created by older versions are recorded only by
+/// commands which update the working copy
+#[test]
+fn test_workspaces_backfill_store() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    let store_path = repo_path.join(".jj").join("repo").join("workspace_store");
+    std::fs::remove_dir_all(&store_path).unwrap();
+
+    test_env.jj_cmd_ok(&repo_path, &["log", "--ignore-working-copy"]);
+    assert!(!store_path.exists());
+    test_env.jj_cmd_ok(&repo_path, &["log"]);
+    assert!(store_path.join("index").is_file());
+}
+
 fn get_log_output(test_env: &TestEnvironment, cwd: &Path) -> String {
     let template = r#"
     separate(" ",
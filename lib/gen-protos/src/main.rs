@@ -20,6 +20,7 @@ fn main() -> Result<()> {
         "git_store.proto",
         "local_store.proto",
         "op_store.proto",
+        "simple_workspace_store.proto",
         "working_copy.proto",
     ];
 
@@ -27,6 +27,7 @@ use itertools::Itertools as _;
 use thiserror::Error;
 
 use crate::annotate::get_annotation_with_file_content;
+use crate::annotate::AnnotationOptions;
 use crate::backend::BackendError;
 use crate::backend::BackendResult;
 use crate::backend::CommitId;
@@ -145,6 +146,7 @@ pub async fn split_hunks_to_trees(
             destinations,
             left_path,
             left_text.clone(),
+            &AnnotationOptions::default(),
         )?;
         let annotation_ranges = annotation
             .compact_line_ranges()
@@ -26,6 +26,7 @@ use std::rc::Rc;
 
 use bstr::BStr;
 use bstr::BString;
+use futures::StreamExt as _;
 use itertools::Itertools as _;
 use pollster::FutureExt;
 
@@ -36,14 +37,17 @@ use crate::conflicts::materialize_merge_result_to_bytes;
 use crate::conflicts::materialize_tree_value;
 use crate::conflicts::ConflictMarkerStyle;
 use crate::conflicts::MaterializedTreeValue;
+use crate::copies::CopyRecords;
 use crate::diff::Diff;
 use crate::diff::DiffHunkKind;
 use crate::fileset::FilesetExpression;
 use crate::graph::GraphEdge;
 use crate::graph::GraphEdgeType;
+use crate::merge::MergedTreeValue;
 use crate::merged_tree::MergedTree;
 use crate::repo::Repo;
 use crate::repo_path::RepoPath;
+use crate::repo_path::RepoPathBuf;
 use crate::revset::ResolvedRevsetExpression;
 use crate::revset::RevsetEvaluationError;
 use crate::revset::RevsetExpression;
@@ -121,20 +125,62 @@ struct Source {
     line_map: Vec<(usize, usize)>,
     /// File content at the current commit.
     text: BString,
+    /// Path to the file at the current commit.
+    path: RepoPathBuf,
+    /// File value at the current commit, or `None` if the content was
+    /// provided by the caller.
+    value: Option<MergedTreeValue>,
 }
 
 impl Source {
-    fn new(text: BString) -> Self {
+    fn new(text: BString, path: RepoPathBuf) -> Self {
         Source {
             line_map: Vec::new(),
             text,
+            path,
+            value: None,
         }
     }
 
     fn load(commit: &Commit, file_path: &RepoPath) -> Result<Self, BackendError> {
         let tree = commit.tree()?;
-        let text = get_file_contents(commit.store(), file_path, &tree)?;
-        Ok(Self::new(text))
+        Self::load_from_tree(commit.store(), &tree, file_path)
+    }
+
+    fn load_from_tree(
+        store: &Store,
+        tree: &MergedTree,
+        file_path: &RepoPath,
+    ) -> Result<Self, BackendError> {
+        let value = tree.path_value(file_path)?;
+        let text = get_file_contents(store, file_path, value.clone())?;
+        Ok(Source {
+            line_map: Vec::new(),
+            text,
+            path: file_path.to_owned(),
+            value: Some(value),
+        })
+    }
+
+    fn load_parent(parent: &Commit, child: &Source) -> Result<Self, BackendError> {
+        let tree = parent.tree()?;
+        let value = tree.path_value(&child.path)?;
+        if child.value.as_ref() == Some(&value) {
+            // Unchanged. No need to read the file content.
+            return Ok(Source {
+                line_map: Vec::new(),
+                text: child.text.clone(),
+                path: child.path.clone(),
+                value: Some(value),
+            });
+        }
+        let text = get_file_contents(parent.store(), &child.path, value.clone())?;
+        Ok(Source {
+            line_map: Vec::new(),
+            text,
+            path: child.path.clone(),
+            value: Some(value),
+        })
     }
 
     fn fill_line_map(&mut self) {
@@ -147,21 +193,48 @@ impl Source {
 /// original file.
 type OriginalLineMap = Vec<Option<CommitId>>;
 
+/// Options for computing file annotation.
+#[derive(Clone, Debug)]
+pub struct AnnotationOptions {
+    /// Follow the file back through copies and renames. If disabled, only
+    /// the history of the same path is searched.
+    pub follow_copies: bool,
+    /// Revisions whose changes should be ignored. Lines modified by these
+    /// revisions are attributed to the corresponding lines in the parent
+    /// revisions if any. Lines added by these revisions are still attributed
+    /// to them.
+    pub ignore_revs: Rc<ResolvedRevsetExpression>,
+}
+
+impl Default for AnnotationOptions {
+    fn default() -> Self {
+        AnnotationOptions {
+            follow_copies: false,
+            ignore_revs: RevsetExpression::none(),
+        }
+    }
+}
+
 /// Get line by line annotations for a specific file path in the repo.
 ///
 /// The `domain` expression narrows the range of ancestors to search. It will be
 /// intersected as `domain & ::starting_commit & files(file_path)`. The
 /// `starting_commit` is assumed to be included in the `domain`.
 ///
+/// If `options.follow_copies` is enabled, the file will be followed across
+/// copies and renames. The ancestors that modified the copy source are then
+/// searched in place of `files(file_path)`.
+///
 /// If the file is not found, returns empty results.
 pub fn get_annotation_for_file(
     repo: &dyn Repo,
     starting_commit: &Commit,
     domain: &Rc<ResolvedRevsetExpression>,
     file_path: &RepoPath,
+    options: &AnnotationOptions,
 ) -> Result<FileAnnotation, RevsetEvaluationError> {
     let source = Source::load(starting_commit, file_path)?;
-    compute_file_annotation(repo, starting_commit.id(), domain, file_path, source)
+    compute_file_annotation(repo, starting_commit.id(), domain, source, options)
 }
 
 /// Get line by line annotations for a specific file path starting with the
@@ -177,61 +250,80 @@ pub fn get_annotation_with_file_content(
     domain: &Rc<ResolvedRevsetExpression>,
     file_path: &RepoPath,
     starting_text: impl Into<Vec<u8>>,
+    options: &AnnotationOptions,
 ) -> Result<FileAnnotation, RevsetEvaluationError> {
-    let source = Source::new(BString::new(starting_text.into()));
-    compute_file_annotation(repo, starting_commit_id, domain, file_path, source)
+    let source = Source::new(BString::new(starting_text.into()), file_path.to_owned());
+    compute_file_annotation(repo, starting_commit_id, domain, source, options)
 }
 
 fn compute_file_annotation(
     repo: &dyn Repo,
     starting_commit_id: &CommitId,
     domain: &Rc<ResolvedRevsetExpression>,
-    file_path: &RepoPath,
     mut source: Source,
+    options: &AnnotationOptions,
 ) -> Result<FileAnnotation, RevsetEvaluationError> {
     source.fill_line_map();
     let text = source.text.clone();
-    let line_map = process_commits(repo, starting_commit_id, source, domain, file_path)?;
+    let line_map = process_commits(repo, starting_commit_id, source, domain, options)?;
     Ok(FileAnnotation { line_map, text })
 }
 
 /// Starting at the starting commit, compute changes at that commit relative to
 /// it's direct parents, updating the mappings as we go. We return the final
 /// original line map that represents where each line of the original came from.
+///
+/// Only the ancestors that modified the file are visited. If the file is
+/// followed across a copy, the ancestors of the copy source are visited in a
+/// subsequent pass.
 fn process_commits(
     repo: &dyn Repo,
     starting_commit_id: &CommitId,
     starting_source: Source,
     domain: &Rc<ResolvedRevsetExpression>,
-    file_name: &RepoPath,
+    options: &AnnotationOptions,
 ) -> Result<OriginalLineMap, RevsetEvaluationError> {
-    let predicate = RevsetFilterPredicate::File(FilesetExpression::file_path(file_name.to_owned()));
-    // TODO: If the domain isn't a contiguous range, changes masked out by it
-    // might not be caught by the closest ancestor revision. For example,
-    // domain=merges() would pick up almost nothing because merge revisions
-    // are usually empty. Perhaps, we want to query `files(file_path,
-    // within_sub_graph=domain)`, not `domain & files(file_path)`.
     let ancestors = RevsetExpression::commit(starting_commit_id.clone()).ancestors();
-    let revset = RevsetExpression::commit(starting_commit_id.clone())
-        .union(&domain.intersection(&ancestors).filtered(predicate))
+    let ignore_revset = options
+        .ignore_revs
+        .intersection(&ancestors)
         .evaluate(repo)?;
+    let is_ignored = ignore_revset.containing_fn();
 
     let mut original_line_map = vec![None; starting_source.line_map.len()];
-    let mut commit_source_map = HashMap::from([(starting_commit_id.clone(), starting_source)]);
-
-    for node in revset.iter_graph() {
-        let (commit_id, edge_list) = node?;
-        process_commit(
-            repo,
-            file_name,
-            &mut original_line_map,
-            &mut commit_source_map,
-            &commit_id,
-            &edge_list,
-        )?;
-        if commit_source_map.is_empty() {
-            // No more lines to propagate to ancestors.
-            break;
+    let mut pending_source_map = HashMap::from([(starting_commit_id.clone(), starting_source)]);
+    while !pending_source_map.is_empty() {
+        let mut commit_source_map = std::mem::take(&mut pending_source_map);
+        let heads = RevsetExpression::commits(commit_source_map.keys().cloned().collect());
+        // TODO: If the domain isn't a contiguous range, changes masked out by
+        // it might not be caught by the closest ancestor revision. For
+        // example, domain=merges() would pick up almost nothing because merge
+        // revisions are usually empty. Perhaps, we want to query
+        // `files(file_path, within_sub_graph=domain)`, not `domain &
+        // files(file_path)`.
+        let paths = commit_source_map
+            .values()
+            .map(|source| FilesetExpression::file_path(source.path.clone()));
+        let predicate = RevsetFilterPredicate::File(FilesetExpression::union_all(paths.collect()));
+        let candidates = domain.intersection(&heads.ancestors()).filtered(predicate);
+        let revset = heads.union(&candidates).evaluate(repo)?;
+
+        for node in revset.iter_graph() {
+            let (commit_id, edge_list) = node?;
+            process_commit(
+                repo,
+                &mut original_line_map,
+                &mut commit_source_map,
+                &mut pending_source_map,
+                &commit_id,
+                &edge_list,
+                options.follow_copies,
+                is_ignored(&commit_id)?,
+            )?;
+            if commit_source_map.is_empty() {
+                // No more lines to propagate to ancestors.
+                break;
+            }
         }
     }
     Ok(original_line_map)
@@ -240,13 +332,23 @@ fn process_commits(
 /// For a given commit, for each parent, we compare the version in the parent
 /// tree with the current version, updating the mappings for any lines in
 /// common. If the parent doesn't have the file, we skip it.
+///
+/// If the commit is `ignored`, lines in modified hunks are also mapped to the
+/// parent lines at the same offsets.
+///
+/// If `follow_copies` is enabled and the file was copied from another path in
+/// a parent, the remaining lines are mapped to the copy source, which is
+/// added to the `copied_source_map` to be processed later.
+#[allow(clippy::too_many_arguments)]
 fn process_commit(
     repo: &dyn Repo,
-    file_name: &RepoPath,
     original_line_map: &mut OriginalLineMap,
     commit_source_map: &mut CommitSourceMap,
+    copied_source_map: &mut CommitSourceMap,
     current_commit_id: &CommitId,
     edges: &[GraphEdge<CommitId>],
+    follow_copies: bool,
+    ignored: bool,
 ) -> Result<(), BackendError> {
     let Some(mut current_source) = commit_source_map.remove(current_commit_id) else {
         return Ok(());
@@ -258,42 +360,10 @@ fn process_commit(
             hash_map::Entry::Occupied(entry) => entry.into_mut(),
             hash_map::Entry::Vacant(entry) => {
                 let commit = repo.store().get_commit(entry.key())?;
-                entry.insert(Source::load(&commit, file_name)?)
+                entry.insert(Source::load_parent(&commit, &current_source)?)
             }
         };
-
-        // For two versions of the same file, for all the lines in common,
-        // overwrite the new mapping in the results for the new commit. Let's
-        // say I have a file in commit A and commit B. We know that according to
-        // local line_map, in commit A, line 3 corresponds to line 7 of the
-        // original file. Now, line 3 in Commit A corresponds to line 6 in
-        // commit B. Then, we update local line_map to say that "Commit B line 6
-        // goes to line 7 of the original file". We repeat this for all lines in
-        // common in the two commits.
-        let mut current_lines = current_source.line_map.iter().copied().peekable();
-        let mut new_current_line_map = Vec::new();
-        let mut new_parent_line_map = Vec::new();
-        copy_same_lines_with(
-            &current_source.text,
-            &parent_source.text,
-            |current_start, parent_start, count| {
-                new_current_line_map
-                    .extend(current_lines.peeking_take_while(|&(cur, _)| cur < current_start));
-                while let Some((current, original)) =
-                    current_lines.next_if(|&(cur, _)| cur < current_start + count)
-                {
-                    let parent = parent_start + (current - current_start);
-                    new_parent_line_map.push((parent, original));
-                }
-            },
-        );
-        new_current_line_map.extend(current_lines);
-        current_source.line_map = new_current_line_map;
-        parent_source.line_map = if parent_source.line_map.is_empty() {
-            new_parent_line_map
-        } else {
-            itertools::merge(parent_source.line_map.iter().copied(), new_parent_line_map).collect()
-        };
+        copy_lines_to_parent(&mut current_source, parent_source, ignored);
         // If an omitted parent had the file, leave these lines unresolved.
         // TODO: These unresolved lines could be copied to the original_line_map
         // as Err(commit_id) or something instead of None.
@@ -302,6 +372,43 @@ fn process_commit(
         }
     }
 
+    if follow_copies && !current_source.line_map.is_empty() {
+        let commit = repo.store().get_commit(current_commit_id)?;
+        for parent in commit.parents() {
+            let parent = parent?;
+            let parent_tree = parent.tree()?;
+            if parent_tree.path_value(&current_source.path)?.is_present() {
+                continue;
+            }
+            let Some(source_path) = find_copy_source(
+                repo.store(),
+                parent.id(),
+                current_commit_id,
+                &current_source.path,
+            )?
+            else {
+                continue;
+            };
+            let parent_source = match copied_source_map.entry(parent.id().clone()) {
+                hash_map::Entry::Occupied(entry) if entry.get().path == source_path => {
+                    entry.into_mut()
+                }
+                // Copied from another path by another child. Leave the lines
+                // attributed to the current commit.
+                hash_map::Entry::Occupied(_) => continue,
+                hash_map::Entry::Vacant(entry) => entry.insert(Source::load_from_tree(
+                    repo.store(),
+                    &parent_tree,
+                    &source_path,
+                )?),
+            };
+            copy_lines_to_parent(&mut current_source, parent_source, ignored);
+            if parent_source.line_map.is_empty() {
+                copied_source_map.remove(parent.id());
+            }
+        }
+    }
+
     // Once we've looked at all parents of a commit, any leftover lines must be
     // original to the current commit, so we save this information in
     // original_line_map.
@@ -312,11 +419,53 @@ fn process_commit(
     Ok(())
 }
 
+/// Moves the lines of the `current_source` which are also present in the
+/// `parent_source` to the parent's line map.
+fn copy_lines_to_parent(current_source: &mut Source, parent_source: &mut Source, ignored: bool) {
+    // For two versions of the same file, for all the lines in common,
+    // overwrite the new mapping in the results for the new commit. Let's
+    // say I have a file in commit A and commit B. We know that according to
+    // local line_map, in commit A, line 3 corresponds to line 7 of the
+    // original file. Now, line 3 in Commit A corresponds to line 6 in
+    // commit B. Then, we update local line_map to say that "Commit B line 6
+    // goes to line 7 of the original file". We repeat this for all lines in
+    // common in the two commits.
+    let mut current_lines = current_source.line_map.iter().copied().peekable();
+    let mut new_current_line_map = Vec::new();
+    let mut new_parent_line_map = Vec::new();
+    copy_same_lines_with(
+        &current_source.text,
+        &parent_source.text,
+        ignored,
+        |current_start, parent_start, count| {
+            new_current_line_map
+                .extend(current_lines.peeking_take_while(|&(cur, _)| cur < current_start));
+            while let Some((current, original)) =
+                current_lines.next_if(|&(cur, _)| cur < current_start + count)
+            {
+                let parent = parent_start + (current - current_start);
+                new_parent_line_map.push((parent, original));
+            }
+        },
+    );
+    new_current_line_map.extend(current_lines);
+    current_source.line_map = new_current_line_map;
+    parent_source.line_map = if parent_source.line_map.is_empty() {
+        new_parent_line_map
+    } else {
+        itertools::merge(parent_source.line_map.iter().copied(), new_parent_line_map).collect()
+    };
+}
+
 /// For two files, calls `copy(current_start, parent_start, count)` for each
 /// range of contiguous lines in common (e.g. line 8-10 maps to line 9-11.)
+///
+/// If `copy_different_lines` is true, modified lines are also paired up with
+/// the parent lines in the same hunk.
 fn copy_same_lines_with(
     current_contents: &[u8],
     parent_contents: &[u8],
+    copy_different_lines: bool,
     mut copy: impl FnMut(usize, usize, usize),
 ) {
     let diff = Diff::by_line([current_contents, parent_contents]);
@@ -331,21 +480,256 @@ fn copy_same_lines_with(
                 parent_line_counter += count;
             }
             DiffHunkKind::Different => {
-                let current_output = hunk.contents[0];
-                let parent_output = hunk.contents[1];
-                current_line_counter += current_output.split_inclusive(|b| *b == b'\n').count();
-                parent_line_counter += parent_output.split_inclusive(|b| *b == b'\n').count();
+                let current_count = hunk.contents[0].split_inclusive(|b| *b == b'\n').count();
+                let parent_count = hunk.contents[1].split_inclusive(|b| *b == b'\n').count();
+                let count = current_count.min(parent_count);
+                if copy_different_lines && count > 0 {
+                    copy(current_line_counter, parent_line_counter, count);
+                }
+                current_line_counter += current_count;
+                parent_line_counter += parent_count;
+            }
+        }
+    }
+}
+
+/// Lines of a file in a certain commit.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LineRangeContent {
+    /// 0-based range of line numbers in the file.
+    pub line_range: Range<usize>,
+    /// Content of the lines, including newline characters.
+    pub text: BString,
+}
+
+impl LineRangeContent {
+    fn from_text(text: &[u8], line_range: Range<usize>) -> Self {
+        let text = text
+            .split_inclusive(|b| *b == b'\n')
+            .skip(line_range.start)
+            .take(line_range.len())
+            .flatten()
+            .copied()
+            .collect();
+        LineRangeContent { line_range, text }
+    }
+}
+
+/// Commit that modified lines in the tracked range.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LineRangeChange {
+    /// The commit which modified the lines.
+    pub commit_id: CommitId,
+    /// The tracked lines in the first parent of the commit. Empty if the lines
+    /// were added by the commit.
+    pub old: LineRangeContent,
+    /// The tracked lines in the commit.
+    pub new: LineRangeContent,
+}
+
+/// Tracked line range and file content at a certain commit.
+#[derive(Clone, Debug)]
+struct RangeSource {
+    line_range: Range<usize>,
+    text: BString,
+}
+
+/// Get commits that modified the given range of lines, in the order of the
+/// commit graph traversal (children first).
+///
+/// The `line_range` is a 0-based range of line numbers in the file at the
+/// `starting_commit`, which is followed back through history. The range will
+/// be clamped to the number of lines in the file. Lines moved out of the range
+/// aren't tracked, but lines inserted in the middle of the range are.
+///
+/// If the `domain` isn't a contiguous range, the lines are compared with the
+/// closest ancestors in the `domain`, so changes made by the commits masked
+/// out by it are attributed to their closest descendant in the `domain`.
+///
+/// See [`get_annotation_for_file()`] for the other arguments.
+pub fn get_line_range_history(
+    repo: &dyn Repo,
+    starting_commit: &Commit,
+    domain: &Rc<ResolvedRevsetExpression>,
+    file_path: &RepoPath,
+    line_range: Range<usize>,
+) -> Result<Vec<LineRangeChange>, RevsetEvaluationError> {
+    let Source { text, .. } = Source::load(starting_commit, file_path)?;
+    let num_lines = text.split_inclusive(|b| *b == b'\n').count();
+    let line_range = line_range.start.min(num_lines)..line_range.end.min(num_lines);
+    if line_range.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let predicate = RevsetFilterPredicate::File(FilesetExpression::file_path(file_path.to_owned()));
+    let ancestors = RevsetExpression::commit(starting_commit.id().clone()).ancestors();
+    let revset = RevsetExpression::commit(starting_commit.id().clone())
+        .union(&domain.intersection(&ancestors).filtered(predicate))
+        .evaluate(repo)?;
+
+    let mut changes = vec![];
+    let mut range_source_map = HashMap::from([(
+        starting_commit.id().clone(),
+        RangeSource { line_range, text },
+    )]);
+    for node in revset.iter_graph() {
+        let (commit_id, edges) = node?;
+        let Some(current) = range_source_map.remove(&commit_id) else {
+            continue;
+        };
+        // If no ancestors in the domain modified the file, compare with the
+        // actual parents. The parents usually don't have the file.
+        let parent_ids = if edges.is_empty() {
+            repo.store().get_commit(&commit_id)?.parent_ids().to_vec()
+        } else {
+            edges.iter().map(|edge| edge.target.clone()).collect()
+        };
+        let mut parent_ranges = Vec::with_capacity(parent_ids.len());
+        for parent_id in &parent_ids {
+            let parent_text = match range_source_map.get(parent_id) {
+                Some(source) => source.text.clone(),
+                None => {
+                    let commit = repo.store().get_commit(parent_id)?;
+                    Source::load(&commit, file_path)?.text
+                }
+            };
+            let (parent_range, modified) =
+                map_line_range(&current.text, &parent_text, current.line_range.clone());
+            parent_ranges.push((parent_range, modified, parent_text));
+        }
+
+        // A merge commit modified the lines only if they differ from all
+        // parents.
+        if parent_ranges.iter().all(|(_, modified, _)| *modified) {
+            let old = match parent_ranges.first() {
+                Some((parent_range, _, parent_text)) => {
+                    LineRangeContent::from_text(parent_text, parent_range.clone().unwrap_or(0..0))
+                }
+                None => LineRangeContent::from_text(b"", 0..0),
+            };
+            let new = LineRangeContent::from_text(&current.text, current.line_range.clone());
+            changes.push(LineRangeChange {
+                commit_id: commit_id.clone(),
+                old,
+                new,
+            });
+        }
+
+        if edges.is_empty() {
+            continue;
+        }
+        for ((edge, (parent_range, _, parent_text)), parent_id) in
+            edges.iter().zip(parent_ranges).zip(&parent_ids)
+        {
+            let Some(parent_range) = parent_range else {
+                continue;
+            };
+            if edge.edge_type == GraphEdgeType::Missing {
+                continue;
+            }
+            match range_source_map.entry(parent_id.clone()) {
+                hash_map::Entry::Occupied(entry) => {
+                    // Reached from multiple children. Track the union of the
+                    // ranges.
+                    let source = entry.into_mut();
+                    source.line_range = source.line_range.start.min(parent_range.start)
+                        ..source.line_range.end.max(parent_range.end);
+                }
+                hash_map::Entry::Vacant(entry) => {
+                    entry.insert(RangeSource {
+                        line_range: parent_range,
+                        text: parent_text,
+                    });
+                }
+            }
+        }
+        if range_source_map.is_empty() {
+            // No more lines to propagate to ancestors.
+            break;
+        }
+    }
+    Ok(changes)
+}
+
+/// Maps the `line_range` in the current file to the corresponding lines in the
+/// parent file. Returns the parent range (or `None` if all lines were added),
+/// and whether the lines in the range were modified.
+fn map_line_range(
+    current_contents: &[u8],
+    parent_contents: &[u8],
+    line_range: Range<usize>,
+) -> (Option<Range<usize>>, bool) {
+    let diff = Diff::by_line([current_contents, parent_contents]);
+    let mut current_line_counter: usize = 0;
+    let mut parent_line_counter: usize = 0;
+    let mut parent_range: Option<Range<usize>> = None;
+    let mut modified = false;
+    let mut extend_parent_range = |range: Range<usize>| {
+        parent_range = Some(match &parent_range {
+            Some(acc) => acc.start.min(range.start)..acc.end.max(range.end),
+            None => range,
+        });
+    };
+    for hunk in diff.hunks() {
+        let current_count = hunk.contents[0].split_inclusive(|b| *b == b'\n').count();
+        let parent_count = hunk.contents[1].split_inclusive(|b| *b == b'\n').count();
+        let current_end = current_line_counter + current_count;
+        match hunk.kind {
+            DiffHunkKind::Matching => {
+                let start = line_range.start.max(current_line_counter);
+                let end = line_range.end.min(current_end);
+                if start < end {
+                    let offset = parent_line_counter;
+                    extend_parent_range(
+                        offset + (start - current_line_counter)
+                            ..offset + (end - current_line_counter),
+                    );
+                }
+            }
+            DiffHunkKind::Different => {
+                let overlaps = if current_count == 0 {
+                    // Lines deleted in the middle of the range
+                    line_range.start < current_line_counter && current_line_counter < line_range.end
+                } else {
+                    current_line_counter < line_range.end && line_range.start < current_end
+                };
+                if overlaps {
+                    modified = true;
+                    if parent_count > 0 {
+                        extend_parent_range(
+                            parent_line_counter..parent_line_counter + parent_count,
+                        );
+                    }
+                }
             }
         }
+        current_line_counter = current_end;
+        parent_line_counter += parent_count;
     }
+    (parent_range, modified)
+}
+
+/// Looks up the source path of the `path` copied or renamed between the
+/// `parent_id` and `commit_id`.
+fn find_copy_source(
+    store: &Store,
+    parent_id: &CommitId,
+    commit_id: &CommitId,
+    path: &RepoPath,
+) -> Result<Option<RepoPathBuf>, BackendError> {
+    let stream = store.get_copy_records(Some(&[path.to_owned()]), parent_id, commit_id)?;
+    let mut copy_records = CopyRecords::default();
+    copy_records.add_records(stream.collect::<Vec<_>>().block_on())?;
+    Ok(copy_records
+        .for_target(path)
+        .map(|record| record.source.clone()))
 }
 
 fn get_file_contents(
     store: &Store,
     path: &RepoPath,
-    tree: &MergedTree,
+    file_value: MergedTreeValue,
 ) -> Result<BString, BackendError> {
-    let file_value = tree.path_value(path)?;
     let effective_file_value = materialize_tree_value(store, path, file_value).block_on()?;
     match effective_file_value {
         MaterializedTreeValue::File { mut reader, id, .. } => {
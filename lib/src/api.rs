@@ -0,0 +1,575 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! High-level API for programs embedding jj.
+//!
+//! The rest of `jj_lib` is shaped by the needs of the `jj` CLI and changes
+//! frequently. This module wraps the common workflow of a tool that operates
+//! on a workspace: load it, snapshot the working copy, resolve revisions, and
+//! rewrite commits in a transaction. The items defined here follow semantic
+//! versioning: they won't be changed incompatibly except in a release that
+//! bumps the minor version while the crate is at 0.x.
+//!
+//! Types re-exported from other modules (such as [`Commit`] and
+//! [`MutableRepo`]) are not covered by that guarantee, but are the stable
+//! entry points for rewriting commits.
+//!
+//! ```no_run
+//! use jj_lib::api::Session;
+//! # fn run(settings: &jj_lib::settings::UserSettings) -> Result<(), jj_lib::api::ApiError> {
+//! let mut session = Session::load(settings, std::path::Path::new("."))?;
+//! session.snapshot()?;
+//! let commit = session.resolve_single_revision("@")?;
+//! let mut tx = session.start_transaction();
+//! tx.repo_mut()
+//!     .rewrite_commit(&commit)
+//!     .set_description("new description\n")
+//!     .write()?;
+//! session.finish_transaction(tx, "describe working copy")?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Unlike the CLI, this API doesn't import or export refs from/to a colocated
+//! Git repository.
+
+use std::error::Error;
+use std::fmt;
+use std::path::Path;
+use std::sync::Arc;
+
+use itertools::Itertools as _;
+
+pub use crate::backend::BackendError;
+pub use crate::commit::Commit;
+use crate::config::ConfigGetError;
+use crate::config::ConfigGetResultExt as _;
+use crate::config::ConfigNamePathBuf;
+use crate::conflicts::ConflictMarkerStyle;
+use crate::gitignore::GitIgnoreFile;
+use crate::matchers::EverythingMatcher;
+use crate::object_id::ObjectId as _;
+pub use crate::op_store::WorkspaceId;
+pub use crate::repo::MutableRepo;
+pub use crate::repo::ReadonlyRepo;
+pub use crate::repo::Repo;
+use crate::repo::StoreFactories;
+use crate::repo_path::RepoPathUiConverter;
+use crate::revset;
+use crate::revset::DefaultSymbolResolver;
+use crate::revset::RevsetAliasesMap;
+use crate::revset::RevsetDiagnostics;
+use crate::revset::RevsetExtensions;
+use crate::revset::RevsetIteratorExt as _;
+use crate::revset::RevsetParseContext;
+use crate::revset::RevsetWorkspaceContext;
+use crate::settings::HumanByteSize;
+use crate::settings::UserSettings;
+pub use crate::transaction::Transaction;
+use crate::working_copy::CheckoutOptions;
+pub use crate::working_copy::CheckoutStats;
+use crate::working_copy::SnapshotOptions;
+pub use crate::working_copy::SnapshotStats;
+use crate::working_copy::WorkingCopyFreshness;
+use crate::workspace::default_working_copy_factories;
+use crate::workspace::WorkingCopyFactories;
+use crate::workspace::Workspace;
+
+/// Kind of an [`ApiError`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum ApiErrorKind {
+    /// Failed to load the workspace, or the repo at an operation.
+    Load,
+    /// Invalid or missing configuration, including revset aliases.
+    Config,
+    /// The revset expression couldn't be parsed.
+    RevsetParse,
+    /// A symbol in the revset expression couldn't be resolved.
+    RevsetResolution,
+    /// The revset expression couldn't be evaluated.
+    RevsetEvaluation,
+    /// The revset expression was expected to resolve to exactly one revision.
+    NotSingleRevision,
+    /// The working copy hasn't been updated to the latest operation.
+    StaleWorkingCopy,
+    /// Failed to snapshot or update the working copy, or to read or write its
+    /// state.
+    WorkingCopy,
+    /// The requested change to the commits or the view isn't allowed, such as
+    /// rewriting the root commit.
+    Rewrite,
+    /// Failed to read or write commits or operations.
+    Storage,
+}
+
+/// Error returned by the functions in this module.
+///
+/// The underlying errors are available through [`Error::source()`], but their
+/// types are not part of this API.
+#[derive(Debug)]
+pub struct ApiError {
+    kind: ApiErrorKind,
+    repr: ApiErrorRepr,
+}
+
+#[derive(Debug)]
+enum ApiErrorRepr {
+    Message(String),
+    Wrapped(Box<dyn Error + Send + Sync>),
+}
+
+impl ApiError {
+    fn new(kind: ApiErrorKind, err: impl Into<Box<dyn Error + Send + Sync>>) -> Self {
+        ApiError {
+            kind,
+            repr: ApiErrorRepr::Wrapped(err.into()),
+        }
+    }
+
+    fn with_message(kind: ApiErrorKind, message: impl Into<String>) -> Self {
+        ApiError {
+            kind,
+            repr: ApiErrorRepr::Message(message.into()),
+        }
+    }
+
+    /// The kind of this error.
+    pub fn kind(&self) -> ApiErrorKind {
+        self.kind
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.repr {
+            ApiErrorRepr::Message(message) => f.write_str(message),
+            ApiErrorRepr::Wrapped(err) => err.fmt(f),
+        }
+    }
+}
+
+impl Error for ApiError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        // Wrapped errors are transparent, so their sources come next.
+        match &self.repr {
+            ApiErrorRepr::Message(_) => None,
+            ApiErrorRepr::Wrapped(err) => err.source(),
+        }
+    }
+}
+
+/// Errors from the re-exported types can be propagated with `?`.
+impl From<BackendError> for ApiError {
+    fn from(err: BackendError) -> Self {
+        ApiError::new(ApiErrorKind::Storage, err)
+    }
+}
+
+trait ApiResultExt<T> {
+    /// Wraps the error as an [`ApiError`] of the given kind.
+    fn api_err(self, kind: ApiErrorKind) -> Result<T, ApiError>;
+}
+
+impl<T, E: Into<Box<dyn Error + Send + Sync>>> ApiResultExt<T> for Result<T, E> {
+    fn api_err(self, kind: ApiErrorKind) -> Result<T, ApiError> {
+        self.map_err(|err| ApiError::new(kind, err))
+    }
+}
+
+/// A workspace and the repo loaded at its latest operation.
+pub struct Session {
+    workspace: Workspace,
+    repo: Arc<ReadonlyRepo>,
+    revset_aliases_map: RevsetAliasesMap,
+    revset_extensions: Arc<RevsetExtensions>,
+    path_converter: RepoPathUiConverter,
+}
+
+impl Session {
+    /// Loads the workspace at `workspace_root` with the default backends.
+    pub fn load(settings: &UserSettings, workspace_root: &Path) -> Result<Self, ApiError> {
+        Self::load_with_factories(
+            settings,
+            workspace_root,
+            &StoreFactories::default(),
+            &default_working_copy_factories(),
+        )
+    }
+
+    /// Loads the workspace at `workspace_root` with custom backends.
+    pub fn load_with_factories(
+        settings: &UserSettings,
+        workspace_root: &Path,
+        store_factories: &StoreFactories,
+        working_copy_factories: &WorkingCopyFactories,
+    ) -> Result<Self, ApiError> {
+        let workspace = Workspace::load(
+            settings,
+            workspace_root,
+            store_factories,
+            working_copy_factories,
+        )
+        .api_err(ApiErrorKind::Load)?;
+        let repo = workspace
+            .repo_loader()
+            .load_at_head()
+            .api_err(ApiErrorKind::Load)?;
+        let revset_aliases_map = load_revset_aliases(settings)?;
+        let path_converter = RepoPathUiConverter::Fs {
+            cwd: workspace.workspace_root().to_owned(),
+            base: workspace.workspace_root().to_owned(),
+        };
+        Ok(Session {
+            workspace,
+            repo,
+            revset_aliases_map,
+            revset_extensions: Default::default(),
+            path_converter,
+        })
+    }
+
+    /// Sets extensions used when parsing and resolving revset expressions.
+    pub fn set_revset_extensions(&mut self, extensions: Arc<RevsetExtensions>) {
+        self.revset_extensions = extensions;
+    }
+
+    /// The underlying workspace.
+    pub fn workspace(&self) -> &Workspace {
+        &self.workspace
+    }
+
+    /// The ID of the workspace.
+    pub fn workspace_id(&self) -> &WorkspaceId {
+        self.workspace.workspace_id()
+    }
+
+    /// The repo at the latest operation known to this session.
+    pub fn repo(&self) -> &Arc<ReadonlyRepo> {
+        &self.repo
+    }
+
+    /// Settings for this workspace.
+    pub fn settings(&self) -> &UserSettings {
+        self.workspace.settings()
+    }
+
+    /// Reloads the repo at the latest operation, picking up changes made by
+    /// other processes.
+    pub fn reload(&mut self) -> Result<(), ApiError> {
+        self.repo = self.repo.reload_at_head().api_err(ApiErrorKind::Load)?;
+        Ok(())
+    }
+
+    /// The working-copy commit of this workspace, or `None` if the workspace
+    /// has been removed from the repo.
+    pub fn working_copy_commit(&self) -> Result<Option<Commit>, ApiError> {
+        let Some(id) = self.repo.view().get_wc_commit_id(self.workspace_id()) else {
+            return Ok(None);
+        };
+        Ok(Some(self.repo.store().get_commit(id)?))
+    }
+
+    /// Evaluates the revset expression, and returns the matching commits in
+    /// reverse topological order (children first).
+    ///
+    /// Revset aliases are loaded from the `revset-aliases` table of the
+    /// settings, which include the built-in aliases such as `trunk()` if the
+    /// settings are based on
+    /// [`StackedConfig::with_defaults()`](crate::config::StackedConfig::with_defaults).
+    /// Symbols are resolved in the same way as the CLI, except that
+    /// commit and change ID prefixes aren't disambiguated within
+    /// `revsets.short-prefixes`.
+    pub fn evaluate_revset(&self, revset_str: &str) -> Result<Vec<Commit>, ApiError> {
+        let workspace_context = RevsetWorkspaceContext {
+            path_converter: &self.path_converter,
+            workspace_id: self.workspace_id(),
+        };
+        let context = RevsetParseContext::new(
+            &self.revset_aliases_map,
+            self.settings().user_email(),
+            chrono::Local::now().into(),
+            &self.revset_extensions,
+            Some(workspace_context),
+        );
+        let expression = revset::parse(&mut RevsetDiagnostics::new(), revset_str, &context)
+            .api_err(ApiErrorKind::RevsetParse)?;
+        let repo = self.repo.as_ref();
+        let symbol_resolver =
+            DefaultSymbolResolver::new(repo, self.revset_extensions.symbol_resolvers());
+        let revset = expression
+            .resolve_user_expression(repo, &symbol_resolver)
+            .api_err(ApiErrorKind::RevsetResolution)?
+            .evaluate(repo)
+            .api_err(ApiErrorKind::RevsetEvaluation)?;
+        revset
+            .iter()
+            .commits(repo.store())
+            .try_collect()
+            .api_err(ApiErrorKind::RevsetEvaluation)
+    }
+
+    /// Evaluates the revset expression, which must resolve to exactly one
+    /// commit.
+    pub fn resolve_single_revision(&self, revset_str: &str) -> Result<Commit, ApiError> {
+        let mut commits = self.evaluate_revset(revset_str)?;
+        if commits.len() == 1 {
+            Ok(commits.pop().unwrap())
+        } else {
+            Err(ApiError::with_message(
+                ApiErrorKind::NotSingleRevision,
+                format!(
+                    r#"Revset "{revset_str}" resolved to {} revisions, expected exactly one"#,
+                    commits.len()
+                ),
+            ))
+        }
+    }
+
+    /// Snapshots the working copy, and records the changes in a new operation
+    /// if there are any.
+    ///
+    /// New files are tracked according to `snapshot.auto-track`, and files
+    /// ignored by `.gitignore` (including `.git/info/exclude` of the backing
+    /// Git repo) are skipped.
+    pub fn snapshot(&mut self) -> Result<SnapshotStats, ApiError> {
+        let workspace_id = self.workspace_id().clone();
+        let Some(wc_commit) = self.working_copy_commit()? else {
+            // If the workspace has been deleted, there's nothing to snapshot.
+            return Ok(SnapshotStats::default());
+        };
+        let start_tracking_matcher = self.auto_tracking_matcher()?;
+        let options = SnapshotOptions {
+            base_ignores: self.base_ignores()?,
+            fsmonitor_settings: self
+                .settings()
+                .fsmonitor_settings()
+                .api_err(ApiErrorKind::Config)?,
+            progress: None,
+            start_tracking_matcher: start_tracking_matcher.as_ref(),
+            max_new_file_size: self.max_new_file_size()?,
+            conflict_marker_style: self.conflict_marker_style()?,
+        };
+
+        let mut locked_ws = self
+            .workspace
+            .start_working_copy_mutation()
+            .api_err(ApiErrorKind::WorkingCopy)?;
+        let freshness =
+            WorkingCopyFreshness::check_stale(locked_ws.locked_wc(), &wc_commit, &self.repo)
+                .api_err(ApiErrorKind::Storage)?;
+        let (repo, wc_commit) = match freshness {
+            WorkingCopyFreshness::Fresh => (self.repo.clone(), wc_commit),
+            WorkingCopyFreshness::Updated(wc_operation) => {
+                let repo = self
+                    .repo
+                    .reload_at(&wc_operation)
+                    .api_err(ApiErrorKind::Load)?;
+                let Some(id) = repo.view().get_wc_commit_id(&workspace_id) else {
+                    return Ok(SnapshotStats::default());
+                };
+                let wc_commit = repo.store().get_commit(id)?;
+                (repo, wc_commit)
+            }
+            WorkingCopyFreshness::WorkingCopyStale | WorkingCopyFreshness::SiblingOperation => {
+                let op_id = locked_ws.locked_wc().old_operation_id();
+                return Err(ApiError::with_message(
+                    ApiErrorKind::StaleWorkingCopy,
+                    format!(
+                        "The working copy is stale (not updated since operation {})",
+                        op_id.hex()
+                    ),
+                ));
+            }
+        };
+        let (new_tree_id, stats) = locked_ws
+            .locked_wc()
+            .snapshot(&options)
+            .api_err(ApiErrorKind::WorkingCopy)?;
+        let repo = if new_tree_id != *wc_commit.tree_id() {
+            let mut tx = repo.start_transaction();
+            tx.set_is_snapshot(true);
+            let mut_repo = tx.repo_mut();
+            let commit = mut_repo
+                .rewrite_commit(&wc_commit)
+                .set_tree_id(new_tree_id)
+                .write()?;
+            mut_repo
+                .set_wc_commit(workspace_id, commit.id().clone())
+                .api_err(ApiErrorKind::Rewrite)?;
+            mut_repo.rebase_descendants()?;
+            tx.commit("snapshot working copy")
+                .api_err(ApiErrorKind::Storage)?
+        } else {
+            repo
+        };
+        locked_ws
+            .finish(repo.op_id().clone())
+            .api_err(ApiErrorKind::WorkingCopy)?;
+        self.repo = repo;
+        Ok(stats)
+    }
+
+    /// Starts a transaction on top of the current repo.
+    pub fn start_transaction(&self) -> Transaction {
+        self.repo.start_transaction()
+    }
+
+    /// Rebases descendants of rewritten commits, publishes the transaction as a
+    /// new operation, and updates the working copy if the working-copy commit
+    /// changed.
+    ///
+    /// Returns the checkout stats if the working copy was updated.
+    pub fn finish_transaction(
+        &mut self,
+        mut tx: Transaction,
+        description: impl Into<String>,
+    ) -> Result<Option<CheckoutStats>, ApiError> {
+        let workspace_id = self.workspace_id().clone();
+        let mut_repo = tx.repo_mut();
+        if mut_repo.has_rewrites() {
+            mut_repo.rebase_descendants()?;
+        }
+        let old_repo = tx.base_repo().clone();
+        let old_wc_commit_id = old_repo.view().get_wc_commit_id(&workspace_id).cloned();
+        let new_wc_commit_id = tx.repo().view().get_wc_commit_id(&workspace_id).cloned();
+        self.repo = tx.commit(description).api_err(ApiErrorKind::Storage)?;
+        let Some(new_wc_commit_id) = new_wc_commit_id else {
+            return Ok(None);
+        };
+        if old_wc_commit_id.as_ref() == Some(&new_wc_commit_id) {
+            return Ok(None);
+        }
+        let old_tree_id = old_wc_commit_id
+            .map(|id| old_repo.store().get_commit(&id))
+            .transpose()?
+            .map(|commit| commit.tree_id().clone());
+        let new_wc_commit = self.repo.store().get_commit(&new_wc_commit_id)?;
+        if old_tree_id.as_ref() == Some(new_wc_commit.tree_id()) {
+            // Record new operation id which represents the latest working-copy state
+            let locked_ws = self
+                .workspace
+                .start_working_copy_mutation()
+                .api_err(ApiErrorKind::WorkingCopy)?;
+            locked_ws
+                .finish(self.repo.op_id().clone())
+                .api_err(ApiErrorKind::WorkingCopy)?;
+            return Ok(None);
+        }
+        let options = CheckoutOptions {
+            conflict_marker_style: self.conflict_marker_style()?,
+        };
+        let stats = self
+            .workspace
+            .check_out(
+                self.repo.op_id().clone(),
+                old_tree_id.as_ref(),
+                &new_wc_commit,
+                &options,
+            )
+            .api_err(ApiErrorKind::WorkingCopy)?;
+        Ok(Some(stats))
+    }
+
+    fn auto_tracking_matcher(&self) -> Result<Box<dyn crate::matchers::Matcher>, ApiError> {
+        let Some(pattern) = self
+            .settings()
+            .get_string("snapshot.auto-track")
+            .optional()
+            .api_err(ApiErrorKind::Config)?
+        else {
+            return Ok(Box::new(EverythingMatcher));
+        };
+        let expression = crate::fileset::parse(
+            &mut crate::fileset::FilesetDiagnostics::new(),
+            &pattern,
+            &RepoPathUiConverter::Fs {
+                cwd: "".into(),
+                base: "".into(),
+            },
+        )
+        .map_err(|err| ConfigGetError::Type {
+            name: "snapshot.auto-track".to_owned(),
+            error: err.into(),
+            source_path: None,
+        })
+        .api_err(ApiErrorKind::Config)?;
+        Ok(expression.to_matcher())
+    }
+
+    fn base_ignores(&self) -> Result<Arc<GitIgnoreFile>, ApiError> {
+        #[cfg(feature = "git")]
+        if let Ok(git_backend) = crate::git::get_git_backend(self.repo.store()) {
+            let exclude_path = git_backend.git_repo_path().join("info").join("exclude");
+            return GitIgnoreFile::empty()
+                .chain_with_file("", exclude_path)
+                .api_err(ApiErrorKind::WorkingCopy);
+        }
+        Ok(GitIgnoreFile::empty())
+    }
+
+    fn max_new_file_size(&self) -> Result<u64, ApiError> {
+        let size = self
+            .settings()
+            .get_value_with("snapshot.max-new-file-size", TryInto::try_into)
+            .optional()
+            .api_err(ApiErrorKind::Config)?;
+        match size {
+            Some(HumanByteSize(0)) | None => Ok(u64::MAX),
+            Some(HumanByteSize(size)) => Ok(size),
+        }
+    }
+
+    fn conflict_marker_style(&self) -> Result<ConflictMarkerStyle, ApiError> {
+        let style = self
+            .settings()
+            .get("ui.conflict-marker-style")
+            .optional()
+            .api_err(ApiErrorKind::Config)?;
+        Ok(style.unwrap_or_default())
+    }
+}
+
+fn load_revset_aliases(settings: &UserSettings) -> Result<RevsetAliasesMap, ApiError> {
+    let table_name = ConfigNamePathBuf::from_iter(["revset-aliases"]);
+    let mut aliases_map = RevsetAliasesMap::new();
+    // Load from all config layers in order, so that aliases in later layers
+    // override the earlier ones.
+    for layer in settings.config().layers() {
+        let table = match layer.look_up_table(&table_name) {
+            Ok(Some(table)) => table,
+            Ok(None) => continue,
+            Err(item) => {
+                let err = ConfigGetError::Type {
+                    name: table_name.to_string(),
+                    error: format!("Expected a table, but is {}", item.type_name()).into(),
+                    source_path: layer.path.clone(),
+                };
+                return Err(ApiError::new(ApiErrorKind::Config, err));
+            }
+        };
+        for (decl, item) in table.iter() {
+            item.as_str()
+                .ok_or_else(|| format!("Expected a string, but is {}", item.type_name()).into())
+                .and_then(|defn| aliases_map.insert(decl, defn).map_err(Into::into))
+                .map_err(|error| ConfigGetError::Type {
+                    name: format!("{table_name}.{decl}"),
+                    error,
+                    source_path: layer.path.clone(),
+                })
+                .api_err(ApiErrorKind::Config)?;
+        }
+    }
+    Ok(aliases_map)
+}
@@ -0,0 +1,134 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Bisection of a range of commits to find the first commit that introduced
+//! some property (typically a bug).
+
+use std::collections::BTreeMap;
+use std::rc::Rc;
+
+use itertools::Itertools as _;
+use thiserror::Error;
+
+use crate::backend::CommitId;
+use crate::op_store::BisectMark;
+use crate::repo::Repo;
+use crate::revset::ResolvedRevsetExpression;
+use crate::revset::RevsetEvaluationError;
+use crate::revset::RevsetExpression;
+
+/// Error computing the next bisection step.
+#[derive(Debug, Error)]
+pub enum BisectError {
+    /// No commit has been marked as bad, so there's no range to bisect.
+    #[error("No commit has been marked as bad")]
+    NoBadCommit,
+    /// Every commit marked as bad is an ancestor of a commit marked as good.
+    #[error("Every commit marked as bad is an ancestor of a commit marked as good")]
+    InconsistentMarks,
+    /// Error evaluating the range of commits.
+    #[error(transparent)]
+    RevsetEvaluation(#[from] RevsetEvaluationError),
+}
+
+/// What to do next in a bisection.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum BisectionStep {
+    /// The commit should be evaluated and marked.
+    Evaluate {
+        /// The commit to evaluate.
+        commit_id: CommitId,
+        /// The number of commits that haven't been evaluated yet, including
+        /// `commit_id`.
+        remaining: usize,
+    },
+    /// The bisection is complete. The first bad commit is one of these
+    /// commits. There are more than one only if some commits were skipped.
+    Done(Vec<CommitId>),
+}
+
+/// Finds the next commit to evaluate from the commits marked so far.
+///
+/// The first bad commit is searched in the ancestors of the bad commits that
+/// are not ancestors of any good commit.
+#[derive(Clone, Debug)]
+pub struct Bisector {
+    good: Vec<CommitId>,
+    bad: Vec<CommitId>,
+    skipped: Vec<CommitId>,
+}
+
+impl Bisector {
+    /// Creates a bisector from the marks stored in a view.
+    pub fn from_marks(marks: &BTreeMap<CommitId, BisectMark>) -> Self {
+        let ids_with_mark = |wanted: BisectMark| {
+            marks
+                .iter()
+                .filter(|(_, &mark)| mark == wanted)
+                .map(|(id, _)| id.clone())
+                .collect_vec()
+        };
+        Bisector {
+            good: ids_with_mark(BisectMark::Good),
+            bad: ids_with_mark(BisectMark::Bad),
+            skipped: ids_with_mark(BisectMark::Skipped),
+        }
+    }
+
+    /// Returns the range of commits in which the first bad commit is searched.
+    pub fn range(&self) -> Rc<ResolvedRevsetExpression> {
+        let bad = RevsetExpression::commits(self.bad.clone());
+        let good = RevsetExpression::commits(self.good.clone());
+        bad.roots().ancestors().minus(&good.ancestors())
+    }
+
+    /// Computes the next step of the bisection.
+    ///
+    /// The commit to evaluate is picked in the middle of the remaining range
+    /// in topological order, which halves the range at each step in linear
+    /// histories.
+    pub fn next_step(&self, repo: &dyn Repo) -> Result<BisectionStep, BisectError> {
+        if self.bad.is_empty() {
+            return Err(BisectError::NoBadCommit);
+        }
+        let range = self.range();
+        let bad_roots = RevsetExpression::commits(self.bad.clone()).roots();
+        let skipped = RevsetExpression::commits(self.skipped.clone());
+        let candidates: Vec<CommitId> = range
+            .minus(&bad_roots)
+            .minus(&skipped)
+            .evaluate(repo)?
+            .iter()
+            .try_collect()?;
+        if candidates.is_empty() {
+            let first_bad: Vec<CommitId> = range
+                .intersection(&bad_roots.union(&skipped))
+                .evaluate(repo)?
+                .iter()
+                .try_collect()?;
+            // The range is empty only if the bad commits are all ancestors of
+            // good commits.
+            if first_bad.is_empty() {
+                return Err(BisectError::InconsistentMarks);
+            }
+            return Ok(BisectionStep::Done(first_bad));
+        }
+        let remaining = candidates.len();
+        let commit_id = candidates[remaining / 2].clone();
+        Ok(BisectionStep::Evaluate {
+            commit_id,
+            remaining,
+        })
+    }
+}
@@ -287,6 +287,8 @@ pub enum ConfigSource {
     User,
     /// Repo configuration files.
     Repo,
+    /// Workspace configuration files.
+    Workspace,
     /// Override environment variables.
     EnvOverrides,
     /// Command-line arguments (which has the highest precedence.)
@@ -875,9 +877,12 @@ fn merge_items(lower_item: &mut ConfigItem, upper_item: &ConfigItem) {
     }
 }
 
-static DEFAULT_CONFIG_LAYERS: Lazy<[Arc<ConfigLayer>; 1]> = Lazy::new(|| {
+static DEFAULT_CONFIG_LAYERS: Lazy<[Arc<ConfigLayer>; 2]> = Lazy::new(|| {
     let parse = |text: &str| Arc::new(ConfigLayer::parse(ConfigSource::Default, text).unwrap());
-    [parse(include_str!("config/misc.toml"))]
+    [
+        parse(include_str!("config/misc.toml")),
+        parse(include_str!("config/revsets.toml")),
+    ]
 });
 
 #[cfg(test)]
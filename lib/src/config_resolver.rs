@@ -44,6 +44,8 @@ pub struct ConfigResolutionContext<'a> {
     pub home_dir: Option<&'a Path>,
     /// Repository path, which is usually `<workspace_root>/.jj/repo`.
     pub repo_path: Option<&'a Path>,
+    /// Workspace root path.
+    pub workspace_path: Option<&'a Path>,
     /// Space-separated subcommand. `jj file show ...` should result in `"file
     /// show"`.
     pub command: Option<&'a str>,
@@ -66,7 +68,8 @@ struct ScopeCondition {
     /// - `--when.commands = ["foo bar"]` -> matches "foo bar", "foo bar baz",
     ///   NOT "foo"
     pub commands: Option<Vec<String>>,
-    // TODO: maybe add "workspaces"?
+    /// Paths to match the workspace root path prefix.
+    pub workspaces: Option<Vec<PathBuf>>,
 }
 
 impl ScopeCondition {
@@ -83,7 +86,9 @@ impl ScopeCondition {
         // It might make some sense to compare paths in canonicalized form, but
         // be careful to not resolve relative path patterns against cwd, which
         // wouldn't be what the user would expect.
-        for path in self.repositories.as_mut().into_iter().flatten() {
+        let repositories = self.repositories.as_mut().into_iter().flatten();
+        let workspaces = self.workspaces.as_mut().into_iter().flatten();
+        for path in repositories.chain(workspaces) {
             if let Some(new_path) = expand_home(path, context.home_dir)? {
                 *path = new_path;
             }
@@ -94,6 +99,7 @@ impl ScopeCondition {
     fn matches(&self, context: &ConfigResolutionContext) -> bool {
         matches_path_prefix(self.repositories.as_deref(), context.repo_path)
             && matches_command(self.commands.as_deref(), context.command)
+            && matches_path_prefix(self.workspaces.as_deref(), context.workspace_path)
     }
 }
 
@@ -427,12 +433,14 @@ mod tests {
         let context = ConfigResolutionContext {
             home_dir: None,
             repo_path: None,
+            workspace_path: None,
             command: None,
         };
         assert!(condition.matches(&context));
         let context = ConfigResolutionContext {
             home_dir: None,
             repo_path: Some(Path::new("/foo")),
+            workspace_path: None,
             command: None,
         };
         assert!(condition.matches(&context));
@@ -443,35 +451,41 @@ mod tests {
         let condition = ScopeCondition {
             repositories: Some(["/foo", "/bar"].map(PathBuf::from).into()),
             commands: None,
+            workspaces: None,
         };
 
         let context = ConfigResolutionContext {
             home_dir: None,
             repo_path: None,
+            workspace_path: None,
             command: None,
         };
         assert!(!condition.matches(&context));
         let context = ConfigResolutionContext {
             home_dir: None,
             repo_path: Some(Path::new("/foo")),
+            workspace_path: None,
             command: None,
         };
         assert!(condition.matches(&context));
         let context = ConfigResolutionContext {
             home_dir: None,
             repo_path: Some(Path::new("/fooo")),
+            workspace_path: None,
             command: None,
         };
         assert!(!condition.matches(&context));
         let context = ConfigResolutionContext {
             home_dir: None,
             repo_path: Some(Path::new("/foo/baz")),
+            workspace_path: None,
             command: None,
         };
         assert!(condition.matches(&context));
         let context = ConfigResolutionContext {
             home_dir: None,
             repo_path: Some(Path::new("/bar")),
+            workspace_path: None,
             command: None,
         };
         assert!(condition.matches(&context));
@@ -482,29 +496,34 @@ mod tests {
         let condition = ScopeCondition {
             repositories: Some(["c:/foo", r"d:\bar/baz"].map(PathBuf::from).into()),
             commands: None,
+            workspaces: None,
         };
 
         let context = ConfigResolutionContext {
             home_dir: None,
             repo_path: Some(Path::new(r"c:\foo")),
+            workspace_path: None,
             command: None,
         };
         assert_eq!(condition.matches(&context), cfg!(windows));
         let context = ConfigResolutionContext {
             home_dir: None,
             repo_path: Some(Path::new(r"c:\foo\baz")),
+            workspace_path: None,
             command: None,
         };
         assert_eq!(condition.matches(&context), cfg!(windows));
         let context = ConfigResolutionContext {
             home_dir: None,
             repo_path: Some(Path::new(r"d:\foo")),
+            workspace_path: None,
             command: None,
         };
         assert!(!condition.matches(&context));
         let context = ConfigResolutionContext {
             home_dir: None,
             repo_path: Some(Path::new(r"d:/bar\baz")),
+            workspace_path: None,
             command: None,
         };
         assert_eq!(condition.matches(&context), cfg!(windows));
@@ -523,6 +542,7 @@ mod tests {
         let context = ConfigResolutionContext {
             home_dir: None,
             repo_path: None,
+            workspace_path: None,
             command: None,
         };
         let resolved_config = resolve(&source_config, &context).unwrap();
@@ -560,6 +580,7 @@ mod tests {
         let context = ConfigResolutionContext {
             home_dir: None,
             repo_path: None,
+            workspace_path: None,
             command: None,
         };
         let resolved_config = resolve(&source_config, &context).unwrap();
@@ -599,6 +620,7 @@ mod tests {
         let context = ConfigResolutionContext {
             home_dir: Some(Path::new("/home/dir")),
             repo_path: None,
+            workspace_path: None,
             command: None,
         };
         let resolved_config = resolve(&source_config, &context).unwrap();
@@ -608,6 +630,7 @@ mod tests {
         let context = ConfigResolutionContext {
             home_dir: Some(Path::new("/home/dir")),
             repo_path: Some(Path::new("/foo/.jj/repo")),
+            workspace_path: None,
             command: None,
         };
         let resolved_config = resolve(&source_config, &context).unwrap();
@@ -619,6 +642,7 @@ mod tests {
         let context = ConfigResolutionContext {
             home_dir: Some(Path::new("/home/dir")),
             repo_path: Some(Path::new("/bar/.jj/repo")),
+            workspace_path: None,
             command: None,
         };
         let resolved_config = resolve(&source_config, &context).unwrap();
@@ -629,6 +653,7 @@ mod tests {
         let context = ConfigResolutionContext {
             home_dir: Some(Path::new("/home/dir")),
             repo_path: Some(Path::new("/home/dir/baz/.jj/repo")),
+            workspace_path: None,
             command: None,
         };
         let resolved_config = resolve(&source_config, &context).unwrap();
@@ -637,6 +662,59 @@ mod tests {
         insta::assert_snapshot!(resolved_config.layers()[1].data, @"a = 'a #1 baz'");
     }
 
+    #[test]
+    fn test_resolve_workspace_path() {
+        let mut source_config = StackedConfig::empty();
+        source_config.add_layer(new_user_layer(indoc! {"
+            a = 'a #0'
+            [[--scope]]
+            --when.workspaces = ['/repo']
+            a = 'a #0.1 repo'
+            [[--scope]]
+            --when.workspaces = ['~/build']
+            a = 'a #0.2 build'
+            [[--scope]]
+            --when.repositories = ['/repo']
+            --when.workspaces = ['~/build']
+            a = 'a #0.3 repo&build'
+        "}));
+
+        let context = ConfigResolutionContext {
+            home_dir: Some(Path::new("/home/dir")),
+            repo_path: None,
+            workspace_path: None,
+            command: None,
+        };
+        let resolved_config = resolve(&source_config, &context).unwrap();
+        assert_eq!(resolved_config.layers().len(), 1);
+        insta::assert_snapshot!(resolved_config.layers()[0].data, @"a = 'a #0'");
+
+        // main workspace
+        let context = ConfigResolutionContext {
+            home_dir: Some(Path::new("/home/dir")),
+            repo_path: Some(Path::new("/repo/.jj/repo")),
+            workspace_path: Some(Path::new("/repo")),
+            command: None,
+        };
+        let resolved_config = resolve(&source_config, &context).unwrap();
+        assert_eq!(resolved_config.layers().len(), 2);
+        insta::assert_snapshot!(resolved_config.layers()[0].data, @"a = 'a #0'");
+        insta::assert_snapshot!(resolved_config.layers()[1].data, @"a = 'a #0.1 repo'");
+
+        // secondary workspace of the same repo
+        let context = ConfigResolutionContext {
+            home_dir: Some(Path::new("/home/dir")),
+            repo_path: Some(Path::new("/repo/.jj/repo")),
+            workspace_path: Some(Path::new("/home/dir/build")),
+            command: None,
+        };
+        let resolved_config = resolve(&source_config, &context).unwrap();
+        assert_eq!(resolved_config.layers().len(), 3);
+        insta::assert_snapshot!(resolved_config.layers()[0].data, @"a = 'a #0'");
+        insta::assert_snapshot!(resolved_config.layers()[1].data, @"a = 'a #0.2 build'");
+        insta::assert_snapshot!(resolved_config.layers()[2].data, @"a = 'a #0.3 repo&build'");
+    }
+
     #[test]
     fn test_resolve_command() {
         let mut source_config = StackedConfig::empty();
@@ -659,6 +737,7 @@ mod tests {
         let context = ConfigResolutionContext {
             home_dir: None,
             repo_path: None,
+            workspace_path: None,
             command: None,
         };
         let resolved_config = resolve(&source_config, &context).unwrap();
@@ -668,6 +747,7 @@ mod tests {
         let context = ConfigResolutionContext {
             home_dir: None,
             repo_path: None,
+            workspace_path: None,
             command: Some("foo"),
         };
         let resolved_config = resolve(&source_config, &context).unwrap();
@@ -679,6 +759,7 @@ mod tests {
         let context = ConfigResolutionContext {
             home_dir: None,
             repo_path: None,
+            workspace_path: None,
             command: Some("bar"),
         };
         let resolved_config = resolve(&source_config, &context).unwrap();
@@ -689,6 +770,7 @@ mod tests {
         let context = ConfigResolutionContext {
             home_dir: None,
             repo_path: None,
+            workspace_path: None,
             command: Some("foo baz"),
         };
         let resolved_config = resolve(&source_config, &context).unwrap();
@@ -702,6 +784,7 @@ mod tests {
         let context = ConfigResolutionContext {
             home_dir: None,
             repo_path: None,
+            workspace_path: None,
             command: Some("fooqux"),
         };
         let resolved_config = resolve(&source_config, &context).unwrap();
@@ -723,6 +806,7 @@ mod tests {
         let context = ConfigResolutionContext {
             home_dir: Some(Path::new("/home/dir")),
             repo_path: None,
+            workspace_path: None,
             command: None,
         };
         let resolved_config = resolve(&source_config, &context).unwrap();
@@ -733,6 +817,7 @@ mod tests {
         let context = ConfigResolutionContext {
             home_dir: Some(Path::new("/home/dir")),
             repo_path: Some(Path::new("/foo")),
+            workspace_path: None,
             command: Some("other"),
         };
         let resolved_config = resolve(&source_config, &context).unwrap();
@@ -743,6 +828,7 @@ mod tests {
         let context = ConfigResolutionContext {
             home_dir: Some(Path::new("/home/dir")),
             repo_path: Some(Path::new("/qux")),
+            workspace_path: None,
             command: Some("ABC"),
         };
         let resolved_config = resolve(&source_config, &context).unwrap();
@@ -753,6 +839,7 @@ mod tests {
         let context = ConfigResolutionContext {
             home_dir: Some(Path::new("/home/dir")),
             repo_path: Some(Path::new("/bar")),
+            workspace_path: None,
             command: Some("DEF"),
         };
         let resolved_config = resolve(&source_config, &context).unwrap();
@@ -771,6 +858,7 @@ mod tests {
         let context = ConfigResolutionContext {
             home_dir: Some(Path::new("/home/dir")),
             repo_path: Some(Path::new("/foo/.jj/repo")),
+            workspace_path: None,
             command: None,
         };
         assert_matches!(
@@ -789,6 +877,7 @@ mod tests {
         let context = ConfigResolutionContext {
             home_dir: Some(Path::new("/home/dir")),
             repo_path: Some(Path::new("/foo/.jj/repo")),
+            workspace_path: None,
             command: None,
         };
         assert_matches!(
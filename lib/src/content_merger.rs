@@ -0,0 +1,1221 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Mergers which resolve conflicts in file contents that can't be merged line
+//! by line, such as structured data or lock files.
+//!
+//! Mergers are selected by path patterns configured in the `content-mergers`
+//! table, and are called whenever trees are merged, e.g. during rebase.
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::fmt::Debug;
+use std::fs;
+use std::process::Command;
+use std::process::Stdio;
+use std::str;
+use std::sync::Arc;
+
+use itertools::Itertools as _;
+use once_cell::sync::Lazy;
+use regex::Captures;
+use regex::Regex;
+use serde::de;
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::config::ConfigGetError;
+use crate::files;
+use crate::files::MergeResult;
+use crate::fileset;
+use crate::fileset::FilesetDiagnostics;
+use crate::fileset::FilesetExpression;
+use crate::fileset::FilesetParseError;
+use crate::merge::Merge;
+use crate::repo_path::RepoPath;
+use crate::repo_path::RepoPathUiConverter;
+use crate::settings::UserSettings;
+
+/// Error returned by a [`ContentMerger`].
+pub type ContentMergeError = Box<dyn Error + Send + Sync>;
+
+/// Merges the contents of a file which has conflicting changes.
+pub trait ContentMerger: Debug + Send + Sync {
+    /// Merges `contents`, which are simplified and have at least two sides.
+    ///
+    /// Returns `None` if the contents can't be merged, in which case the file
+    /// is merged line by line as usual. Errors are reported but otherwise
+    /// handled in the same way.
+    fn merge(
+        &self,
+        path: &RepoPath,
+        contents: &Merge<Vec<u8>>,
+    ) -> Result<Option<Vec<u8>>, ContentMergeError>;
+}
+
+/// Error while loading content mergers from the settings.
+#[derive(Debug, Error)]
+pub enum ContentMergerConfigError {
+    /// Invalid config value.
+    #[error(transparent)]
+    Config(#[from] ConfigGetError),
+    /// Invalid path pattern.
+    #[error("Invalid pattern in `content-mergers.{name}`")]
+    Pattern {
+        /// Name of the merger config.
+        name: String,
+        /// Underlying error.
+        source: FilesetParseError,
+    },
+    /// Either `builtin` or `command` should be set.
+    #[error("Exactly one of `builtin` or `command` must be set in `content-mergers.{name}`")]
+    InvalidMerger {
+        /// Name of the merger config.
+        name: String,
+    },
+    /// No merger of the name is registered.
+    #[error("Unknown builtin content merger '{builtin}' in `content-mergers.{name}`")]
+    UnknownBuiltin {
+        /// Name of the merger config.
+        name: String,
+        /// Name of the builtin merger.
+        builtin: String,
+    },
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct RawContentMergerConfig {
+    patterns: Vec<String>,
+    builtin: Option<String>,
+    command: Option<Vec<String>>,
+}
+
+/// Ordered list of path patterns and the mergers to use for them.
+#[derive(Clone, Debug, Default)]
+pub struct ContentMergers {
+    rules: Vec<(FilesetExpression, Arc<dyn ContentMerger>)>,
+    /// Mergers which can be selected by name, e.g. by `merge=<name>` in
+    /// `.gitattributes`.
+    named: HashMap<String, Arc<dyn ContentMerger>>,
+}
+
+impl ContentMergers {
+    /// Creates an empty list, which merges nothing.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a list which merges nothing by path, but provides the
+    /// `named` mergers.
+    pub fn with_named(named: HashMap<String, Arc<dyn ContentMerger>>) -> Self {
+        ContentMergers {
+            rules: vec![],
+            named,
+        }
+    }
+
+    /// Loads mergers from the `content-mergers` table. `builtin` values refer
+    /// to the `named_mergers`.
+    pub fn from_settings(
+        settings: &UserSettings,
+        named_mergers: &HashMap<String, Arc<dyn ContentMerger>>,
+    ) -> Result<Self, ContentMergerConfigError> {
+        let path_converter = RepoPathUiConverter::Fs {
+            cwd: "".into(),
+            base: "".into(),
+        };
+        let mut mergers = ContentMergers::with_named(named_mergers.clone());
+        // Sort keys so the first matching merger is deterministic.
+        for name in settings.table_keys("content-mergers").sorted() {
+            let config: RawContentMergerConfig = settings.get(["content-mergers", name])?;
+            let merger: Arc<dyn ContentMerger> = match (config.builtin, config.command) {
+                (Some(builtin), None) => named_mergers
+                    .get(&builtin)
+                    .ok_or_else(|| ContentMergerConfigError::UnknownBuiltin {
+                        name: name.to_owned(),
+                        builtin: builtin.clone(),
+                    })?
+                    .clone(),
+                (None, Some(command)) => Arc::new(ExternalContentMerger::new(command).map_err(
+                    |EmptyMergerCommandError| ContentMergerConfigError::InvalidMerger {
+                        name: name.to_owned(),
+                    },
+                )?),
+                _ => {
+                    return Err(ContentMergerConfigError::InvalidMerger {
+                        name: name.to_owned(),
+                    })
+                }
+            };
+            let expression = FilesetExpression::union_all(
+                config
+                    .patterns
+                    .iter()
+                    .map(|text| {
+                        fileset::parse(&mut FilesetDiagnostics::new(), text, &path_converter)
+                    })
+                    .try_collect()
+                    .map_err(|source| ContentMergerConfigError::Pattern {
+                        name: name.to_owned(),
+                        source,
+                    })?,
+            );
+            mergers.named.insert(name.to_owned(), merger.clone());
+            mergers.add(expression, merger);
+        }
+        Ok(mergers)
+    }
+
+    /// Adds `merger` for the files matching `expression`. Mergers added
+    /// earlier take precedence.
+    pub fn add(&mut self, expression: FilesetExpression, merger: Arc<dyn ContentMerger>) {
+        self.rules.push((expression, merger));
+    }
+
+    /// Returns the merger of the given name, which is either a builtin merger
+    /// or a table name in the `content-mergers` config.
+    pub fn get(&self, name: &str) -> Option<&dyn ContentMerger> {
+        self.named.get(name).map(|merger| merger.as_ref())
+    }
+
+    /// Returns the merger for the file at `path` if any.
+    pub fn find(&self, path: &RepoPath) -> Option<&dyn ContentMerger> {
+        self.rules
+            .iter()
+            .find(|(expression, _)| expression.to_matcher().matches(path))
+            .map(|(_, merger)| merger.as_ref())
+    }
+}
+
+/// Returns the mergers which can be referenced by `builtin` in the config.
+pub fn builtin_content_mergers() -> HashMap<String, Arc<dyn ContentMerger>> {
+    HashMap::from([
+        (
+            "json".to_owned(),
+            Arc::new(JsonContentMerger) as Arc<dyn ContentMerger>,
+        ),
+        ("toml".to_owned(), Arc::new(TomlContentMerger::new())),
+        (
+            "toml-lock".to_owned(),
+            Arc::new(TomlContentMerger::for_lock_files()),
+        ),
+        ("union".to_owned(), Arc::new(UnionContentMerger)),
+    ])
+}
+
+/// Runs an external command to merge two-sided conflicts.
+///
+/// The command arguments may contain `$base`, `$left`, `$right`, `$output`,
+/// and `$path` variables. The output file initially contains the left side,
+/// so commands that merge in place (like Git merge drivers) can pass `$output`
+/// as the left side. The merge is successful if the command exits with status
+/// 0.
+#[derive(Clone, Debug)]
+pub struct ExternalContentMerger {
+    command: Vec<String>,
+}
+
+/// The command of an [`ExternalContentMerger`] is empty.
+#[derive(Debug, Error)]
+#[error("The content merger command is empty")]
+pub struct EmptyMergerCommandError;
+
+impl ExternalContentMerger {
+    /// Creates merger which runs the `command` (the program followed by the
+    /// arguments.)
+    pub fn new(command: Vec<String>) -> Result<Self, EmptyMergerCommandError> {
+        if command.is_empty() {
+            return Err(EmptyMergerCommandError);
+        }
+        Ok(ExternalContentMerger { command })
+    }
+}
+
+/// Substitutes `$name` in `arg` with the value of the variable. Substituted
+/// values aren't substituted again.
+fn interpolate_variables(arg: &str, variables: &HashMap<&str, String>) -> String {
+    static VARIABLE_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"\$([a-z]+)\b").unwrap());
+    VARIABLE_REGEX
+        .replace_all(arg, |caps: &Captures| {
+            variables
+                .get(&caps[1])
+                .map_or_else(|| caps[0].to_owned(), |value| value.clone())
+        })
+        .into_owned()
+}
+
+impl ContentMerger for ExternalContentMerger {
+    fn merge(
+        &self,
+        path: &RepoPath,
+        contents: &Merge<Vec<u8>>,
+    ) -> Result<Option<Vec<u8>>, ContentMergeError> {
+        let [left, base, right] = contents.as_slice() else {
+            return Ok(None);
+        };
+        let temp_dir = tempfile::Builder::new().prefix("jj-merge-").tempdir()?;
+        let file_name = path
+            .components()
+            .next_back()
+            .map_or("file", |name| name.as_internal_str());
+        let mut paths = HashMap::new();
+        for (name, content) in [
+            ("base", base),
+            ("left", left),
+            ("right", right),
+            ("output", left),
+        ] {
+            let file_path = temp_dir.path().join(format!("{name}-{file_name}"));
+            fs::write(&file_path, content)?;
+            paths.insert(name, file_path.to_string_lossy().into_owned());
+        }
+        paths.insert("path", path.as_internal_file_string().to_owned());
+        let args = self.command[1..]
+            .iter()
+            .map(|arg| interpolate_variables(arg, &paths));
+        let status = Command::new(&self.command[0])
+            .args(args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .status()?;
+        if !status.success() {
+            tracing::info!(?status, ?path, "content merger command failed");
+            return Ok(None);
+        }
+        Ok(Some(fs::read(&paths["output"])?))
+    }
+}
+
+/// Merges text files line by line, keeping the lines added by all sides where
+/// they conflict. This is the same as Git's `union` merge driver.
+#[derive(Clone, Debug)]
+pub struct UnionContentMerger;
+
+impl ContentMerger for UnionContentMerger {
+    fn merge(
+        &self,
+        _path: &RepoPath,
+        contents: &Merge<Vec<u8>>,
+    ) -> Result<Option<Vec<u8>>, ContentMergeError> {
+        let hunks = match files::merge(contents) {
+            MergeResult::Resolved(content) => return Ok(Some(content.into())),
+            MergeResult::Conflict(hunks) => hunks,
+        };
+        let mut output = vec![];
+        for hunk in &hunks {
+            for content in hunk.adds() {
+                output.extend_from_slice(content);
+            }
+        }
+        Ok(Some(output))
+    }
+}
+
+/// Merges JSON files key by key, and arrays element by element.
+///
+/// The merged file is formatted with one value per line, indented in the same
+/// way as the first side. Files are merged only if every side is already
+/// formatted in that way, so that values which didn't change are kept byte for
+/// byte.
+#[derive(Clone, Debug)]
+pub struct JsonContentMerger;
+
+impl ContentMerger for JsonContentMerger {
+    fn merge(
+        &self,
+        _path: &RepoPath,
+        contents: &Merge<Vec<u8>>,
+    ) -> Result<Option<Vec<u8>>, ContentMergeError> {
+        let Ok(nodes) = contents.try_map(|content| serde_json::from_slice::<Node>(content)) else {
+            return Ok(None);
+        };
+        let Some(indent) = detect_json_indent(contents.first()) else {
+            return Ok(None);
+        };
+        // Numbers and strings are normalized when parsed, and the layout of
+        // the source isn't recorded, so the output would differ from the
+        // sides in unchanged regions unless they round-trip exactly.
+        let round_trips = contents.iter().zip(nodes.iter()).all(|(content, node)| {
+            let mut output = String::new();
+            write_json(node, indent, 0, &mut output);
+            let content = content.strip_suffix(b"\n").unwrap_or(content);
+            content == output.as_bytes()
+        });
+        if !round_trips {
+            return Ok(None);
+        }
+        let Some(Some(merged)) = merge_nodes(&nodes.map(Some), ListMerge::Positional) else {
+            return Ok(None);
+        };
+        let mut output = String::new();
+        write_json(&merged, indent, 0, &mut output);
+        if contents.first().ends_with(b"\n") {
+            output.push('\n');
+        }
+        Ok(Some(output.into_bytes()))
+    }
+}
+
+/// Returns the indentation of the first nested line of the JSON `content`, or
+/// `None` if there's no such line.
+fn detect_json_indent(content: &[u8]) -> Option<&str> {
+    let text = str::from_utf8(content).ok()?;
+    let (_, rest) = text.split_once('\n')?;
+    let len = rest.find(|c| c != ' ' && c != '\t').unwrap_or(rest.len());
+    (len > 0).then(|| &rest[..len])
+}
+
+/// Merges TOML files table by table.
+///
+/// Formatting and comments of the first side are preserved where the contents
+/// didn't change.
+#[derive(Clone, Debug)]
+pub struct TomlContentMerger {
+    list_merge: ListMerge,
+}
+
+impl TomlContentMerger {
+    /// Creates merger which merges arrays element by element.
+    pub fn new() -> Self {
+        TomlContentMerger {
+            list_merge: ListMerge::Positional,
+        }
+    }
+
+    /// Creates merger for lock files such as `Cargo.lock`, in which arrays
+    /// are sets of values, and packages are identified by their `name` and
+    /// `version`.
+    pub fn for_lock_files() -> Self {
+        TomlContentMerger {
+            list_merge: ListMerge::Set {
+                keys: &["name", "version"],
+            },
+        }
+    }
+}
+
+impl Default for TomlContentMerger {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ContentMerger for TomlContentMerger {
+    fn merge(
+        &self,
+        _path: &RepoPath,
+        contents: &Merge<Vec<u8>>,
+    ) -> Result<Option<Vec<u8>>, ContentMergeError> {
+        let Ok(documents) = contents.try_map(|content| {
+            let text = str::from_utf8(content).map_err(|_| ())?;
+            text.parse::<toml_edit::DocumentMut>().map_err(|_| ())
+        }) else {
+            return Ok(None);
+        };
+        let nodes = documents.map(|document| toml_table_to_node(document.as_table(), false));
+        let Some(Some(merged)) = merge_nodes(&nodes.map(Some), self.list_merge) else {
+            return Ok(None);
+        };
+        let first = documents.first();
+        let toml_edit::Item::Table(table) = node_to_toml_item(
+            &merged,
+            Some((
+                nodes.first(),
+                &toml_edit::Item::Table(first.as_table().clone()),
+            )),
+            self.list_merge,
+        ) else {
+            panic!("root node should be a table");
+        };
+        let mut document = toml_edit::DocumentMut::from(table);
+        renumber_toml_tables(document.as_table_mut(), &mut 0);
+        document.set_trailing(first.trailing().clone());
+        Ok(Some(document.to_string().into_bytes()))
+    }
+}
+
+/// Generic representation of structured data.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+enum Node {
+    /// Entries in the original order. If `inline` is false, this is a standard
+    /// TOML table.
+    Map {
+        entries: Vec<(String, Node)>,
+        inline: bool,
+    },
+    /// Elements of an array. If `tables` is true, this is a TOML array of
+    /// tables.
+    List { items: Vec<Node>, tables: bool },
+    /// Scalar value in the source format's syntax.
+    Leaf(String),
+}
+
+impl Node {
+    fn get(&self, key: &str) -> Option<&Node> {
+        match self {
+            Node::Map { entries, .. } => entries.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    /// Returns identity of this element in a set. Tables are identified by
+    /// the values of the `keys`, and scalars by their values. Returns `None`
+    /// if the element can't be identified.
+    fn identity(&self, keys: &[&str]) -> Option<Vec<&str>> {
+        match self {
+            Node::Map { .. } => {
+                let values: Vec<&str> = keys
+                    .iter()
+                    .map_while(|key| match self.get(key) {
+                        Some(Node::Leaf(value)) => Some(value.as_str()),
+                        _ => None,
+                    })
+                    .collect();
+                (!values.is_empty()).then_some(values)
+            }
+            Node::List { .. } => None,
+            Node::Leaf(value) => Some(vec![value]),
+        }
+    }
+}
+
+/// How arrays are merged.
+#[derive(Clone, Copy, Debug)]
+enum ListMerge {
+    /// Arrays are merged element by element. Arrays of different lengths
+    /// conflict if more than one side changed them.
+    Positional,
+    /// Arrays are merged as sets of elements, which are merged recursively if
+    /// they have the same identity. See [`Node::identity()`] for `keys`.
+    Set { keys: &'static [&'static str] },
+}
+
+/// Merges nodes recursively. Returns `None` if the nodes conflict, or
+/// `Some(None)` if the node should be removed.
+fn merge_nodes(terms: &Merge<Option<&Node>>, list_merge: ListMerge) -> Option<Option<Node>> {
+    if let Some(resolved) = terms.resolve_trivial() {
+        return Some(resolved.cloned());
+    }
+    if let Some(maps) = terms.maybe_map(|term| match term {
+        Some(Node::Map { entries, inline }) => Some((entries, *inline)),
+        _ => None,
+    }) {
+        let inline = maps.first().1;
+        let keys = maps
+            .iter()
+            .flat_map(|(entries, _)| entries.iter().map(|(key, _)| key))
+            .unique();
+        let mut entries = vec![];
+        for key in keys {
+            let values =
+                maps.map(|(entries, _)| entries.iter().find(|(k, _)| k == key).map(|(_, v)| v));
+            if let Some(value) = merge_nodes(&values, list_merge)? {
+                entries.push((key.clone(), value));
+            }
+        }
+        return Some(Some(Node::Map { entries, inline }));
+    }
+    if let Some(lists) = terms.maybe_map(|term| match term {
+        Some(Node::List { items, tables }) => Some((items, *tables)),
+        _ => None,
+    }) {
+        let tables = lists.first().1;
+        let items = match list_merge {
+            ListMerge::Positional => merge_lists_by_position(&lists.map(|(items, _)| *items))?,
+            ListMerge::Set { keys } => merge_lists_as_sets(&lists.map(|(items, _)| *items), keys)?,
+        };
+        return Some(Some(Node::List { items, tables }));
+    }
+    None
+}
+
+/// Merges lists of the same length element by element.
+fn merge_lists_by_position(lists: &Merge<&Vec<Node>>) -> Option<Vec<Node>> {
+    let len = lists.first().len();
+    if lists.iter().any(|items| items.len() != len) {
+        return None;
+    }
+    (0..len)
+        .map(|i| {
+            let values = lists.map(|items| Some(&items[i]));
+            merge_nodes(&values, ListMerge::Positional)?
+        })
+        .collect()
+}
+
+/// Merges lists as sets of uniquely identified elements.
+fn merge_lists_as_sets(
+    lists: &Merge<&Vec<Node>>,
+    keys: &'static [&'static str],
+) -> Option<Vec<Node>> {
+    let identities = lists
+        .try_map(|items| {
+            let ids: Option<Vec<_>> = items.iter().map(|item| item.identity(keys)).collect();
+            ids.filter(|ids| ids.iter().all_unique()).ok_or(())
+        })
+        .ok()?;
+    // Elements are inserted after the preceding element of the same side, or
+    // kept sorted if all sides are sorted.
+    let mut merged_ids: Vec<&Vec<&str>> = vec![];
+    for ids in identities.iter() {
+        let mut pos = 0;
+        for id in ids {
+            if let Some(i) = merged_ids.iter().position(|other| *other == id) {
+                pos = i + 1;
+            } else {
+                merged_ids.insert(pos, id);
+                pos += 1;
+            }
+        }
+    }
+    let all_sorted = identities
+        .iter()
+        .all(|ids| ids.windows(2).all(|pair| pair[0] <= pair[1]));
+    if all_sorted {
+        merged_ids.sort();
+    }
+    let mut items = vec![];
+    for id in merged_ids {
+        let values = Merge::from_vec(
+            lists
+                .iter()
+                .zip(identities.iter())
+                .map(|(items, ids)| ids.iter().position(|other| other == id).map(|i| &items[i]))
+                .collect_vec(),
+        );
+        if let Some(value) = merge_nodes(&values, ListMerge::Set { keys })? {
+            items.push(value);
+        }
+    }
+    Some(items)
+}
+
+impl<'de> Deserialize<'de> for Node {
+    fn deserialize<D: de::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct NodeVisitor;
+
+        impl<'de> de::Visitor<'de> for NodeVisitor {
+            type Value = Node;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a JSON value")
+            }
+
+            fn visit_bool<E: de::Error>(self, v: bool) -> Result<Node, E> {
+                Ok(Node::Leaf(v.to_string()))
+            }
+
+            fn visit_i64<E: de::Error>(self, v: i64) -> Result<Node, E> {
+                Ok(Node::Leaf(v.to_string()))
+            }
+
+            fn visit_u64<E: de::Error>(self, v: u64) -> Result<Node, E> {
+                Ok(Node::Leaf(v.to_string()))
+            }
+
+            fn visit_f64<E: de::Error>(self, v: f64) -> Result<Node, E> {
+                let number =
+                    serde_json::Number::from_f64(v).ok_or_else(|| E::custom("invalid number"))?;
+                Ok(Node::Leaf(number.to_string()))
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<Node, E> {
+                Ok(Node::Leaf(serde_json::to_string(v).unwrap()))
+            }
+
+            fn visit_unit<E: de::Error>(self) -> Result<Node, E> {
+                Ok(Node::Leaf("null".to_owned()))
+            }
+
+            fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<Node, A::Error> {
+                let mut items = vec![];
+                while let Some(item) = seq.next_element()? {
+                    items.push(item);
+                }
+                Ok(Node::List {
+                    items,
+                    tables: false,
+                })
+            }
+
+            fn visit_map<A: de::MapAccess<'de>>(self, mut map: A) -> Result<Node, A::Error> {
+                let mut entries: Vec<(String, Node)> = vec![];
+                while let Some((key, value)) = map.next_entry::<String, Node>()? {
+                    if entries.iter().any(|(k, _)| *k == key) {
+                        return Err(de::Error::custom(format!("duplicate key {key}")));
+                    }
+                    entries.push((key, value));
+                }
+                Ok(Node::Map {
+                    entries,
+                    inline: false,
+                })
+            }
+        }
+
+        deserializer.deserialize_any(NodeVisitor)
+    }
+}
+
+fn write_json(node: &Node, indent: &str, level: usize, output: &mut String) {
+    let write_indent = |output: &mut String, level: usize| {
+        output.push('\n');
+        output.extend(std::iter::repeat(indent).take(level));
+    };
+    match node {
+        Node::Map { entries, .. } if entries.is_empty() => output.push_str("{}"),
+        Node::Map { entries, .. } => {
+            output.push('{');
+            for (i, (key, value)) in entries.iter().enumerate() {
+                if i > 0 {
+                    output.push(',');
+                }
+                write_indent(output, level + 1);
+                output.push_str(&serde_json::to_string(key).unwrap());
+                output.push_str(": ");
+                write_json(value, indent, level + 1, output);
+            }
+            write_indent(output, level);
+            output.push('}');
+        }
+        Node::List { items, .. } if items.is_empty() => output.push_str("[]"),
+        Node::List { items, .. } => {
+            output.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    output.push(',');
+                }
+                write_indent(output, level + 1);
+                write_json(item, indent, level + 1, output);
+            }
+            write_indent(output, level);
+            output.push(']');
+        }
+        Node::Leaf(text) => output.push_str(text),
+    }
+}
+
+fn toml_table_to_node(table: &dyn toml_edit::TableLike, inline: bool) -> Node {
+    let entries = table
+        .iter()
+        .filter_map(|(key, item)| Some((key.to_owned(), toml_item_to_node(item)?)))
+        .collect();
+    Node::Map { entries, inline }
+}
+
+fn toml_item_to_node(item: &toml_edit::Item) -> Option<Node> {
+    match item {
+        toml_edit::Item::None => None,
+        toml_edit::Item::Table(table) => Some(toml_table_to_node(table, false)),
+        toml_edit::Item::ArrayOfTables(array) => Some(Node::List {
+            items: array
+                .iter()
+                .map(|table| toml_table_to_node(table, false))
+                .collect(),
+            tables: true,
+        }),
+        toml_edit::Item::Value(value) => Some(toml_value_to_node(value)),
+    }
+}
+
+fn toml_value_to_node(value: &toml_edit::Value) -> Node {
+    match value {
+        toml_edit::Value::Array(array) => Node::List {
+            items: array.iter().map(toml_value_to_node).collect(),
+            tables: false,
+        },
+        toml_edit::Value::InlineTable(table) => toml_table_to_node(table, true),
+        _ => {
+            let mut value = value.clone();
+            value.decor_mut().clear();
+            Node::Leaf(value.to_string())
+        }
+    }
+}
+
+/// Converts `node` to TOML item. The `original` node and item of the first
+/// side are reused where possible to preserve formatting. Elements of arrays
+/// are matched with the original elements as specified by `list_merge`.
+fn node_to_toml_item(
+    node: &Node,
+    original: Option<(&Node, &toml_edit::Item)>,
+    list_merge: ListMerge,
+) -> toml_edit::Item {
+    if let Some((original_node, original_item)) = original {
+        if original_node == node {
+            return original_item.clone();
+        }
+    }
+    match node {
+        Node::Map {
+            entries,
+            inline: false,
+        } => {
+            let original_table = original.and_then(|(node, item)| Some((node, item.as_table()?)));
+            let mut table = toml_edit::Table::new();
+            if let Some((_, original_table)) = original_table {
+                *table.decor_mut() = original_table.decor().clone();
+                table.set_implicit(original_table.is_implicit());
+                table.set_dotted(original_table.is_dotted());
+            }
+            for (key, value) in entries {
+                let original_entry = original_table.and_then(|(node, table)| {
+                    let (key, item) = table.get_key_value(key)?;
+                    Some((key, node.get(key)?, item))
+                });
+                let item = node_to_toml_item(
+                    value,
+                    original_entry.map(|(_, node, item)| (node, item)),
+                    list_merge,
+                );
+                match original_entry {
+                    Some((key, _, _)) => table.insert_formatted(key, item),
+                    None => table.insert(key, item),
+                };
+            }
+            toml_edit::Item::Table(table)
+        }
+        Node::List {
+            items,
+            tables: true,
+        } => {
+            let original_items = original
+                .and_then(|(node, item)| match (node, item) {
+                    (Node::List { items, .. }, toml_edit::Item::ArrayOfTables(array)) => {
+                        Some(items.iter().zip(array.iter()).collect_vec())
+                    }
+                    _ => None,
+                })
+                .unwrap_or_default();
+            let mut array = toml_edit::ArrayOfTables::new();
+            for (index, item) in items.iter().enumerate() {
+                let original_item = match list_merge {
+                    ListMerge::Positional => original_items.get(index),
+                    ListMerge::Set { keys } => {
+                        let identity = item.identity(keys);
+                        original_items
+                            .iter()
+                            .find(|(node, _)| node.identity(keys) == identity)
+                    }
+                }
+                .map(|&(node, table)| (node, toml_edit::Item::Table(table.clone())));
+                let new_item = node_to_toml_item(
+                    item,
+                    original_item.as_ref().map(|(node, item)| (*node, item)),
+                    list_merge,
+                );
+                if let toml_edit::Item::Table(table) = new_item {
+                    array.push(table);
+                }
+            }
+            toml_edit::Item::ArrayOfTables(array)
+        }
+        _ => toml_edit::Item::Value(node_to_toml_value(node)),
+    }
+}
+
+fn node_to_toml_value(node: &Node) -> toml_edit::Value {
+    match node {
+        Node::Map { entries, .. } => {
+            let mut table = toml_edit::InlineTable::new();
+            for (key, value) in entries {
+                table.insert(key, node_to_toml_value(value));
+            }
+            toml_edit::InlineTable::fmt(&mut table);
+            toml_edit::Value::InlineTable(table)
+        }
+        Node::List { items, .. } => {
+            let mut array = items
+                .iter()
+                .map(node_to_toml_value)
+                .collect::<toml_edit::Array>();
+            toml_edit::Array::fmt(&mut array);
+            toml_edit::Value::Array(array)
+        }
+        Node::Leaf(text) => text.parse().expect("leaf should be a valid TOML value"),
+    }
+}
+
+/// Sets positions of tables in the order they are stored so the document is
+/// printed in that order.
+fn renumber_toml_tables(table: &mut toml_edit::Table, position: &mut usize) {
+    for (_, item) in table.iter_mut() {
+        match item {
+            toml_edit::Item::Table(table) => {
+                table.set_position(*position);
+                *position += 1;
+                renumber_toml_tables(table, position);
+            }
+            toml_edit::Item::ArrayOfTables(array) => {
+                for table in array.iter_mut() {
+                    table.set_position(*position);
+                    *position += 1;
+                    renumber_toml_tables(table, position);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::*;
+
+    fn merge(merger: &dyn ContentMerger, left: &str, base: &str, right: &str) -> Option<String> {
+        let contents = Merge::from_vec(vec![
+            left.as_bytes().to_vec(),
+            base.as_bytes().to_vec(),
+            right.as_bytes().to_vec(),
+        ]);
+        let merged = merger
+            .merge(RepoPath::from_internal_string("file"), &contents)
+            .unwrap()?;
+        Some(String::from_utf8(merged).unwrap())
+    }
+
+    #[test]
+    fn test_json_merge() {
+        let base = indoc! {r#"
+            {
+              "name": "app",
+              "dependencies": {
+                "a": "1.0",
+                "b": "1.0"
+              },
+              "files": [
+                "a.js",
+                "b.js"
+              ]
+            }
+        "#};
+        let left = indoc! {r#"
+            {
+              "name": "app",
+              "dependencies": {
+                "a": "2.0",
+                "b": "1.0"
+              },
+              "files": [
+                "a.js",
+                "b.js",
+                "c.js"
+              ]
+            }
+        "#};
+        let right = indoc! {r#"
+            {
+              "name": "app",
+              "version": "0.1.0",
+              "dependencies": {
+                "a": "1.0",
+                "c": "1.0"
+              },
+              "files": [
+                "a.js",
+                "b.js"
+              ]
+            }
+        "#};
+        insta::assert_snapshot!(merge(&JsonContentMerger, left, base, right).unwrap(), @r#"
+        {
+          "name": "app",
+          "dependencies": {
+            "a": "2.0",
+            "c": "1.0"
+          },
+          "files": [
+            "a.js",
+            "b.js",
+            "c.js"
+          ],
+          "version": "0.1.0"
+        }
+        "#);
+
+        // Both sides changed the same value
+        let left = "{\n  \"a\": 1,\n  \"b\": 1\n}\n";
+        let base = "{\n  \"a\": 0,\n  \"b\": 0\n}\n";
+        let right = "{\n  \"a\": 2,\n  \"b\": 0\n}\n";
+        assert_eq!(merge(&JsonContentMerger, left, base, right), None);
+
+        // Arrays are merged element by element
+        let left = "[\n  1,\n  0,\n  0\n]\n";
+        let base = "[\n  0,\n  0,\n  0\n]\n";
+        let right = "[\n  0,\n  0,\n  1\n]\n";
+        assert_eq!(
+            merge(&JsonContentMerger, left, base, right).unwrap(),
+            "[\n  1,\n  0,\n  1\n]\n"
+        );
+
+        // Both sides added elements to the same array
+        let left = "{\n  \"a\": [\n    1,\n    2\n  ]\n}\n";
+        let base = "{\n  \"a\": [\n    1\n  ]\n}\n";
+        let right = "{\n  \"a\": [\n    1,\n    3\n  ]\n}\n";
+        assert_eq!(merge(&JsonContentMerger, left, base, right), None);
+
+        // Not a JSON file
+        assert_eq!(merge(&JsonContentMerger, "{", "{}", "{}"), None);
+    }
+
+    #[test]
+    fn test_json_merge_formatting() {
+        // The indentation is kept, and so are the values which didn't change
+        let base = indoc! {r#"
+            {
+                "name": "app",
+                "version": 1.0,
+                "files": [
+                    "a.js"
+                ]
+            }
+        "#};
+        let left = indoc! {r#"
+            {
+                "name": "app2",
+                "version": 1.0,
+                "files": [
+                    "a.js"
+                ]
+            }
+        "#};
+        let right = indoc! {r#"
+            {
+                "name": "app",
+                "version": 1.0,
+                "files": [
+                    "b.js"
+                ]
+            }
+        "#};
+        insta::assert_snapshot!(merge(&JsonContentMerger, left, base, right).unwrap(), @r#"
+        {
+            "name": "app2",
+            "version": 1.0,
+            "files": [
+                "b.js"
+            ]
+        }
+        "#);
+        let left = "{\n\t\"a\": 1,\n\t\"b\": 0\n}";
+        let base = "{\n\t\"a\": 0,\n\t\"b\": 0\n}";
+        let right = "{\n\t\"a\": 0,\n\t\"b\": 1\n}";
+        assert_eq!(
+            merge(&JsonContentMerger, left, base, right).unwrap(),
+            "{\n\t\"a\": 1,\n\t\"b\": 1\n}"
+        );
+
+        // Files which wouldn't be written back in the same way are merged line
+        // by line
+        let merge_with_unchanged = |value: &str| {
+            let left = format!("{{\n  \"a\": 1,\n  \"b\": {value}\n}}\n");
+            let base = format!("{{\n  \"a\": 0,\n  \"b\": {value},\n  \"c\": 0\n}}\n");
+            let right = format!("{{\n  \"a\": 0,\n  \"b\": {value}\n}}\n");
+            merge(&JsonContentMerger, &left, &base, &right)
+        };
+        assert_eq!(
+            merge_with_unchanged("1.0").unwrap(),
+            "{\n  \"a\": 1,\n  \"b\": 1.0\n}\n"
+        );
+        assert_eq!(merge_with_unchanged("1e400"), None);
+        assert_eq!(merge_with_unchanged("1e2"), None);
+        assert_eq!(merge_with_unchanged("1.50"), None);
+        assert_eq!(merge_with_unchanged(r#""\u00e9""#), None);
+        assert_eq!(merge_with_unchanged(r#""a\/b""#), None);
+        assert_eq!(merge_with_unchanged(r#"{"c": 0}"#), None);
+        let left = r#"{"a": 1, "b": 0}"#;
+        let base = r#"{"a": 0, "b": 0}"#;
+        let right = r#"{"a": 0, "b": 1}"#;
+        assert_eq!(merge(&JsonContentMerger, left, base, right), None);
+        // The sides are indented differently
+        let left = "{\n  \"a\": 1,\n  \"b\": 0\n}\n";
+        let base = "{\n  \"a\": 0,\n  \"b\": 0\n}\n";
+        let right = "{\n    \"a\": 0,\n    \"b\": 1\n}\n";
+        assert_eq!(merge(&JsonContentMerger, left, base, right), None);
+    }
+
+    #[test]
+    fn test_toml_merge_lock_file() {
+        let base = indoc! {r#"
+            # This file is generated.
+            version = 4
+
+            [[package]]
+            name = "a"
+            version = "1.0.0"
+
+            [[package]]
+            name = "c"
+            version = "1.0.0"
+            dependencies = ["a"]
+        "#};
+        let left = indoc! {r#"
+            # This file is generated.
+            version = 4
+
+            [[package]]
+            name = "a"
+            version = "1.0.0"
+
+            [[package]]
+            name = "b"
+            version = "1.0.0"
+
+            [[package]]
+            name = "c"
+            version = "1.0.0"
+            dependencies = ["a", "b"]
+        "#};
+        let right = indoc! {r#"
+            # This file is generated.
+            version = 4
+
+            [[package]]
+            name = "a"
+            version = "1.0.0"
+
+            [[package]]
+            name = "c"
+            version = "1.0.0"
+            dependencies = ["a", "d"]
+
+            [[package]]
+            name = "d"
+            version = "1.0.0"
+        "#};
+        insta::assert_snapshot!(merge(&TomlContentMerger::for_lock_files(), left, base, right).unwrap(), @r#"
+        # This file is generated.
+        version = 4
+
+        [[package]]
+        name = "a"
+        version = "1.0.0"
+
+        [[package]]
+        name = "b"
+        version = "1.0.0"
+
+        [[package]]
+        name = "c"
+        version = "1.0.0"
+        dependencies = ["a", "b", "d"]
+
+        [[package]]
+        name = "d"
+        version = "1.0.0"
+        "#);
+
+        // Both sides upgraded the same package differently
+        let left = base.replace("dependencies = [\"a\"]", "dependencies = [\"a\"]\nx = 1");
+        let right = base.replace("dependencies = [\"a\"]", "dependencies = [\"a\"]\nx = 2");
+        assert_eq!(
+            merge(&TomlContentMerger::for_lock_files(), &left, base, &right),
+            None
+        );
+
+        // Tables without identity can't be merged as sets
+        let base = "[[a]]\nx = 1\n";
+        let left = "[[a]]\nx = 1\n\n[[a]]\nx = 2\n";
+        let right = "[[a]]\nx = 1\n\n[[a]]\nx = 3\n";
+        assert_eq!(
+            merge(&TomlContentMerger::for_lock_files(), left, base, right),
+            None
+        );
+    }
+
+    #[test]
+    fn test_toml_merge() {
+        let base = indoc! {r#"
+            # Comment
+            members = ["a", "b"]
+            exclude = ["x"]
+        "#};
+        let left = indoc! {r#"
+            # Comment
+            members = ["a", "c"]
+            exclude = ["x"]
+        "#};
+        let right = indoc! {r#"
+            # Comment
+            members = ["a", "b"]
+            exclude = ["x", "y"]
+        "#};
+        insta::assert_snapshot!(merge(&TomlContentMerger::new(), left, base, right).unwrap(), @r#"
+        # Comment
+        members = ["a", "c"]
+        exclude = ["x", "y"]
+        "#);
+
+        // Both sides added elements to the same array
+        let right = base.replace(r#"["a", "b"]"#, r#"["a", "b", "d"]"#);
+        assert_eq!(merge(&TomlContentMerger::new(), left, base, &right), None);
+    }
+
+    #[test]
+    fn test_content_mergers_find() {
+        let mut mergers = ContentMergers::new();
+        assert!(mergers
+            .find(RepoPath::from_internal_string("a.json"))
+            .is_none());
+        let path_converter = RepoPathUiConverter::Fs {
+            cwd: "".into(),
+            base: "".into(),
+        };
+        let expression = fileset::parse(
+            &mut FilesetDiagnostics::new(),
+            "glob:'**/*.json'",
+            &path_converter,
+        )
+        .unwrap();
+        mergers.add(expression, Arc::new(JsonContentMerger));
+        mergers.add(FilesetExpression::all(), Arc::new(TomlContentMerger::new()));
+        assert_eq!(
+            format!(
+                "{:?}",
+                mergers.find(RepoPath::from_internal_string("a/b.json"))
+            ),
+            "Some(JsonContentMerger)"
+        );
+        assert_eq!(
+            format!(
+                "{:?}",
+                mergers.find(RepoPath::from_internal_string("a.toml"))
+            ),
+            "Some(TomlContentMerger { list_merge: Positional })"
+        );
+    }
+
+    #[test]
+    fn test_external_content_merger_empty_command() {
+        assert!(ExternalContentMerger::new(vec![]).is_err());
+        assert!(ExternalContentMerger::new(vec!["merge".to_owned()]).is_ok());
+    }
+
+    #[test]
+    fn test_interpolate_variables() {
+        let variables = HashMap::from([
+            ("output", "/tmp/output-$base".to_owned()),
+            ("path", "dir/$output".to_owned()),
+            ("base", "/tmp/base".to_owned()),
+        ]);
+        assert_eq!(
+            interpolate_variables("--out=$output $path $base $baseline $other", &variables),
+            "--out=/tmp/output-$base dir/$output /tmp/base $baseline $other"
+        );
+    }
+}
@@ -14,21 +14,33 @@
 
 //! Code for working with copies and renames.
 
+use std::cmp::Reverse;
 use std::collections::HashMap;
+use std::collections::HashSet;
+use std::io::Read as _;
 use std::pin::Pin;
 use std::task::ready;
 use std::task::Context;
 use std::task::Poll;
 
 use futures::Stream;
+use futures::StreamExt as _;
+use itertools::Itertools as _;
+use pollster::FutureExt as _;
 
+use crate::backend::BackendError;
 use crate::backend::BackendResult;
 use crate::backend::CopyRecord;
+use crate::backend::FileId;
+use crate::backend::TreeValue;
+use crate::commit::Commit;
+use crate::matchers::Matcher;
 use crate::merge::MergedTreeValue;
 use crate::merged_tree::MergedTree;
 use crate::merged_tree::TreeDiffStream;
 use crate::repo_path::RepoPath;
 use crate::repo_path::RepoPathBuf;
+use crate::store::Store;
 
 /// A collection of CopyRecords.
 #[derive(Default, Debug)]
@@ -90,6 +102,256 @@ impl CopyRecords {
     }
 }
 
+/// Options for detecting copies and renames by file content similarity.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CopyDetectionOptions {
+    /// Minimum similarity in percent (1-100) for a pair of files to be
+    /// considered a copy or rename.
+    pub threshold: u8,
+    /// Also detect copies from files modified in the same change. If
+    /// disabled, only renames from deleted files are detected.
+    pub find_copies: bool,
+    /// Maximum number of source or target files to compare. If there are
+    /// more candidates, only copies of unchanged files are detected. If zero,
+    /// no detection will be made.
+    pub limit: usize,
+}
+
+impl Default for CopyDetectionOptions {
+    fn default() -> Self {
+        CopyDetectionOptions {
+            threshold: 50,
+            find_copies: false,
+            limit: 1000,
+        }
+    }
+}
+
+/// File which may be copied or renamed.
+#[derive(Debug)]
+struct CopyCandidate {
+    path: RepoPathBuf,
+    id: FileId,
+    /// Whether the source file was deleted. Unused for targets.
+    deleted: bool,
+}
+
+/// Files larger than this are only compared by id.
+const MAX_SIMILARITY_FILE_SIZE: u64 = 1 << 20;
+/// Maximum total size of the sources or the targets read into memory for
+/// comparison. The remaining files are only compared by id.
+const MAX_SIMILARITY_TOTAL_SIZE: u64 = 64 << 20;
+
+/// Detects copies and renames from `root` to `head` by comparing contents of
+/// added files with deleted (and optionally modified) files.
+///
+/// Only sources and targets matching the `matcher` are compared. Paths already
+/// recorded in `copy_records` are excluded, so the records detected by the
+/// backend take precedence. Returns new records sorted by target path.
+pub fn find_similar_copies(
+    root: &Commit,
+    head: &Commit,
+    matcher: &dyn Matcher,
+    copy_records: &CopyRecords,
+    options: &CopyDetectionOptions,
+) -> BackendResult<Vec<CopyRecord>> {
+    if options.limit == 0 {
+        return Ok(vec![]);
+    }
+    let store = head.store();
+    let root_tree = root.tree()?;
+    let head_tree = head.tree()?;
+
+    let mut sources = vec![];
+    let mut targets = vec![];
+    let mut diff_stream = root_tree.diff_stream(&head_tree, matcher);
+    while let Some(entry) = diff_stream.next().block_on() {
+        let (before, after) = entry.values?;
+        let before = before.into_resolved().ok().flatten();
+        let after = after.into_resolved().ok().flatten();
+        match (before, after) {
+            (Some(TreeValue::File { id, .. }), None) if !copy_records.has_source(&entry.path) => {
+                sources.push(CopyCandidate {
+                    path: entry.path,
+                    id,
+                    deleted: true,
+                });
+            }
+            (None, Some(TreeValue::File { id, .. })) if !copy_records.has_target(&entry.path) => {
+                targets.push(CopyCandidate {
+                    path: entry.path,
+                    id,
+                    deleted: false,
+                });
+            }
+            (Some(TreeValue::File { id, .. }), Some(_)) if options.find_copies => {
+                sources.push(CopyCandidate {
+                    path: entry.path,
+                    id,
+                    deleted: false,
+                });
+            }
+            _ => {}
+        }
+    }
+    if sources.is_empty() || targets.is_empty() {
+        return Ok(vec![]);
+    }
+
+    // Pairs of (score, source index, target index)
+    let mut pairs = vec![];
+    let exact_only = sources.len() > options.limit || targets.len() > options.limit;
+    if exact_only {
+        let mut targets_by_id: HashMap<&FileId, Vec<usize>> = HashMap::new();
+        for (j, target) in targets.iter().enumerate() {
+            targets_by_id.entry(&target.id).or_default().push(j);
+        }
+        for (i, source) in sources.iter().enumerate() {
+            for &j in targets_by_id.get(&source.id).into_iter().flatten() {
+                pairs.push((100, i, j));
+            }
+        }
+    } else {
+        let source_contents = read_candidates(store, &sources)?;
+        let target_contents = read_candidates(store, &targets)?;
+        let source_lines = source_contents
+            .iter()
+            .map(|text| count_lines(text.as_deref().unwrap_or_default()))
+            .collect_vec();
+        let target_lines = target_contents
+            .iter()
+            .map(|text| count_lines(text.as_deref().unwrap_or_default()))
+            .collect_vec();
+        for (i, source) in sources.iter().enumerate() {
+            for (j, target) in targets.iter().enumerate() {
+                let score = if source.id == target.id {
+                    100
+                } else if let (Some(source_text), Some(target_text)) =
+                    (&source_contents[i], &target_contents[j])
+                {
+                    similarity_score(
+                        (source_text, &source_lines[i]),
+                        (target_text, &target_lines[j]),
+                        options.threshold,
+                    )
+                } else {
+                    0
+                };
+                if score >= options.threshold {
+                    pairs.push((score, i, j));
+                }
+            }
+        }
+    }
+
+    // Pick the most similar pairs first. If the scores are the same, prefer
+    // the files of the same name.
+    pairs.sort_by_key(|&(score, i, j)| {
+        let same_name =
+            sources[i].path.components().next_back() == targets[j].path.components().next_back();
+        (Reverse(score), Reverse(same_name), i, j)
+    });
+    let mut used_sources = HashSet::new();
+    let mut used_targets = HashSet::new();
+    let mut records = vec![];
+    for (_, i, j) in pairs {
+        let (source, target) = (&sources[i], &targets[j]);
+        // A deleted file can be renamed only once.
+        if used_targets.contains(&j) || (source.deleted && used_sources.contains(&i)) {
+            continue;
+        }
+        used_sources.insert(i);
+        used_targets.insert(j);
+        records.push(CopyRecord {
+            target: target.path.clone(),
+            target_commit: head.id().clone(),
+            source: source.path.clone(),
+            source_file: source.id.clone(),
+            source_commit: root.id().clone(),
+        });
+    }
+    records.sort_by(|a, b| a.target.cmp(&b.target));
+    Ok(records)
+}
+
+/// Reads the contents of the candidates in order, up to
+/// [`MAX_SIMILARITY_TOTAL_SIZE`] in total. The content is `None` for files
+/// that are too large to be compared, or that don't fit in the remainder.
+fn read_candidates(
+    store: &Store,
+    candidates: &[CopyCandidate],
+) -> BackendResult<Vec<Option<Vec<u8>>>> {
+    let mut remaining = MAX_SIMILARITY_TOTAL_SIZE;
+    candidates
+        .iter()
+        .map(|candidate| {
+            let max_size = MAX_SIMILARITY_FILE_SIZE.min(remaining);
+            let content = read_file(store, &candidate.path, &candidate.id, max_size)?;
+            if let Some(content) = &content {
+                remaining -= content.len() as u64;
+            }
+            Ok(content)
+        })
+        .collect()
+}
+
+/// Reads the file content, or returns `None` if the file is larger than
+/// `max_size`.
+fn read_file(
+    store: &Store,
+    path: &RepoPath,
+    id: &FileId,
+    max_size: u64,
+) -> BackendResult<Option<Vec<u8>>> {
+    let reader = store.read_file(path, id)?;
+    let mut content = vec![];
+    reader
+        .take(max_size + 1)
+        .read_to_end(&mut content)
+        .map_err(|err| BackendError::ReadFile {
+            path: path.to_owned(),
+            id: id.clone(),
+            source: err.into(),
+        })?;
+    if content.len() as u64 > max_size {
+        return Ok(None);
+    }
+    Ok(Some(content))
+}
+
+/// Counts occurrences of each line.
+fn count_lines(text: &[u8]) -> HashMap<&[u8], usize> {
+    let mut counts = HashMap::new();
+    for line in text.split_inclusive(|b| *b == b'\n') {
+        *counts.entry(line).or_default() += 1;
+    }
+    counts
+}
+
+/// Computes similarity of two files in percent, which is the size of the
+/// common lines divided by the size of the larger file. Returns 0 if the score
+/// can't reach the `threshold`.
+fn similarity_score(
+    (text1, lines1): (&[u8], &HashMap<&[u8], usize>),
+    (text2, lines2): (&[u8], &HashMap<&[u8], usize>),
+    threshold: u8,
+) -> u8 {
+    let max_len = text1.len().max(text2.len());
+    let min_len = text1.len().min(text2.len());
+    // Empty files are too common to be considered copies.
+    if min_len == 0 || min_len * 100 < max_len * usize::from(threshold) {
+        return 0;
+    }
+    let common_len: usize = lines1
+        .iter()
+        .filter_map(|(line, &count1)| {
+            let count2 = *lines2.get(line)?;
+            Some(line.len() * count1.min(count2))
+        })
+        .sum();
+    u8::try_from(common_len * 100 / max_len).unwrap()
+}
+
 /// Whether or not the source path was deleted.
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum CopyOperation {
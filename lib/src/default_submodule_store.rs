@@ -21,7 +21,6 @@ use crate::submodule_store::SubmoduleStore;
 
 #[derive(Debug)]
 pub struct DefaultSubmoduleStore {
-    #[allow(dead_code)]
     path: PathBuf,
 }
 
@@ -48,4 +47,8 @@ impl SubmoduleStore for DefaultSubmoduleStore {
     fn name(&self) -> &str {
         Self::name()
     }
+
+    fn git_dir(&self, name: &str) -> Option<PathBuf> {
+        Some(self.path.join("modules").join(name))
+    }
 }
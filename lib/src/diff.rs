@@ -183,6 +183,27 @@ impl CompareBytes for CompareBytesIgnoreWhitespaceAmount {
     }
 }
 
+/// Algorithm used to match unchanged words (or tokens, usually lines) between
+/// inputs.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DiffAlgorithm {
+    /// Matches the least frequent words first, then recurses into the
+    /// regions between them.
+    #[default]
+    Lcs,
+    /// Finds a minimal edit script using Myers' algorithm.
+    Myers,
+    /// Anchors on words that are unique in both inputs, then recurses into
+    /// the regions between them. Falls back to Myers' algorithm if there are
+    /// no such words.
+    Patience,
+    /// Like patience diff, but also considers words occurring a few times,
+    /// preferring the longest run of rare words. Similar to `git diff
+    /// --histogram`.
+    Histogram,
+}
+
 // Not implementing Eq because the text should be compared by WordComparator.
 #[derive(Clone, Copy, Debug)]
 struct HashedWord<'input> {
@@ -547,6 +568,388 @@ fn collect_unchanged_words_lcs<C: CompareBytes, S: BuildHasher>(
     );
 }
 
+/// Finds unchanged word (or token) positions by using the given `algorithm`.
+fn collect_unchanged_words_by<C: CompareBytes, S: BuildHasher>(
+    algorithm: DiffAlgorithm,
+    found_positions: &mut Vec<(WordPosition, WordPosition)>,
+    left: &LocalDiffSource,
+    right: &LocalDiffSource,
+    comp: &WordComparator<C, S>,
+) {
+    if algorithm == DiffAlgorithm::Lcs {
+        collect_unchanged_words(found_positions, left, right, comp);
+        return;
+    }
+    let left_words = left.hashed_words().collect_vec();
+    let right_words = right.hashed_words().collect_vec();
+    let words = WordSequences {
+        left: &left_words,
+        right: &right_words,
+        comp,
+    };
+    let left_range = 0..left_words.len();
+    let right_range = 0..right_words.len();
+    let mut matches = Vec::new();
+    match algorithm {
+        DiffAlgorithm::Lcs => unreachable!(),
+        DiffAlgorithm::Myers => words.myers(left_range, right_range, &mut matches),
+        DiffAlgorithm::Patience => words.patience(left_range, right_range, &mut matches),
+        DiffAlgorithm::Histogram => words.histogram(left_range, right_range, &mut matches),
+    }
+    found_positions.extend(matches.into_iter().map(|(left_pos, right_pos)| {
+        (
+            left.map_to_global(LocalWordPosition(left_pos)),
+            right.map_to_global(LocalWordPosition(right_pos)),
+        )
+    }));
+}
+
+/// Maximum number of occurrences of a word to be considered by the histogram
+/// algorithm. Regions with only more frequent words are diffed by Myers'
+/// algorithm instead.
+const MAX_HISTOGRAM_CHAIN_LEN: usize = 64;
+
+/// Words of two inputs to be matched by index.
+///
+/// Matched `(left, right)` index pairs are pushed in ascending order.
+struct WordSequences<'a, 'input, C, S> {
+    left: &'a [HashedWord<'input>],
+    right: &'a [HashedWord<'input>],
+    comp: &'a WordComparator<C, S>,
+}
+
+impl<'input, C: CompareBytes, S: BuildHasher> WordSequences<'_, 'input, C, S> {
+    fn eq(&self, left_pos: usize, right_pos: usize) -> bool {
+        self.comp
+            .eq_hashed(self.left[left_pos], self.right[right_pos])
+    }
+
+    fn common_prefix_len(&self, left: Range<usize>, right: Range<usize>) -> usize {
+        iter::zip(left, right)
+            .take_while(|&(l, r)| self.eq(l, r))
+            .count()
+    }
+
+    fn common_suffix_len(&self, left: Range<usize>, right: Range<usize>) -> usize {
+        iter::zip(left.rev(), right.rev())
+            .take_while(|&(l, r)| self.eq(l, r))
+            .count()
+    }
+
+    /// Calls `f` with the ranges excluding the common leading and trailing
+    /// words, which are matched as unchanged.
+    fn with_common_affixes_trimmed(
+        &self,
+        mut left: Range<usize>,
+        mut right: Range<usize>,
+        found: &mut Vec<(usize, usize)>,
+        f: impl FnOnce(Range<usize>, Range<usize>, &mut Vec<(usize, usize)>),
+    ) {
+        let prefix_len = self.common_prefix_len(left.clone(), right.clone());
+        found.extend(iter::zip(left.clone(), right.clone()).take(prefix_len));
+        left.start += prefix_len;
+        right.start += prefix_len;
+        let suffix_len = self.common_suffix_len(left.clone(), right.clone());
+        left.end -= suffix_len;
+        right.end -= suffix_len;
+        f(left.clone(), right.clone(), found);
+        found.extend(iter::zip(
+            left.end..left.end + suffix_len,
+            right.end..right.end + suffix_len,
+        ));
+    }
+
+    fn myers(&self, left: Range<usize>, right: Range<usize>, found: &mut Vec<(usize, usize)>) {
+        let max_d = myers_max_d(left.len(), right.len());
+        let mut vf = MyersV::new(max_d);
+        let mut vb = MyersV::new(max_d);
+        self.myers_conquer(left, right, &mut vf, &mut vb, found);
+    }
+
+    fn myers_conquer(
+        &self,
+        left: Range<usize>,
+        right: Range<usize>,
+        vf: &mut MyersV,
+        vb: &mut MyersV,
+        found: &mut Vec<(usize, usize)>,
+    ) {
+        self.with_common_affixes_trimmed(left, right, found, |left, right, found| {
+            if left.is_empty() || right.is_empty() {
+                return;
+            }
+            if let Some((x, y)) = self.find_middle_snake(left.clone(), right.clone(), vf, vb) {
+                self.myers_conquer(left.start..x, right.start..y, vf, vb, found);
+                self.myers_conquer(x..left.end, y..right.end, vf, vb, found);
+            }
+        });
+    }
+
+    /// Finds the start of the middle snake of the shortest edit script, which
+    /// splits the problem into two smaller ones.
+    fn find_middle_snake(
+        &self,
+        left: Range<usize>,
+        right: Range<usize>,
+        vf: &mut MyersV,
+        vb: &mut MyersV,
+    ) -> Option<(usize, usize)> {
+        let n = left.len();
+        let m = right.len();
+        let delta = n as isize - m as isize;
+        let odd = delta & 1 == 1;
+        vf[1] = 0;
+        vb[1] = 0;
+        let d_max = myers_max_d(n, m) as isize;
+        for d in 0..d_max {
+            // Forward path
+            for k in (-d..=d).rev().step_by(2) {
+                let mut x = if k == -d || (k != d && vf[k - 1] < vf[k + 1]) {
+                    vf[k + 1]
+                } else {
+                    vf[k - 1] + 1
+                };
+                let y = (x as isize - k) as usize;
+                let (x0, y0) = (x, y);
+                if x < n && y < m {
+                    x += self
+                        .common_prefix_len(left.start + x..left.end, right.start + y..right.end);
+                }
+                vf[k] = x;
+                if odd && (k - delta).abs() < d && vf[k] + vb[-(k - delta)] >= n {
+                    return Some((left.start + x0, right.start + y0));
+                }
+            }
+            // Backward path
+            for k in (-d..=d).rev().step_by(2) {
+                let mut x = if k == -d || (k != d && vb[k - 1] < vb[k + 1]) {
+                    vb[k + 1]
+                } else {
+                    vb[k - 1] + 1
+                };
+                let mut y = (x as isize - k) as usize;
+                if x < n && y < m {
+                    let advance = self
+                        .common_suffix_len(left.start..left.end - x, right.start..right.end - y);
+                    x += advance;
+                    y += advance;
+                }
+                vb[k] = x;
+                if !odd && (k - delta).abs() <= d && vb[k] + vf[-(k - delta)] >= n {
+                    return Some((left.end - x, right.end - y));
+                }
+            }
+        }
+        None
+    }
+
+    fn patience(&self, left: Range<usize>, right: Range<usize>, found: &mut Vec<(usize, usize)>) {
+        self.with_common_affixes_trimmed(left, right, found, |left, right, found| {
+            if left.is_empty() || right.is_empty() {
+                return;
+            }
+            // (word, [left position, right position], [left count, right count])
+            let mut table: HashTable<(HashedWord, [usize; 2], [usize; 2])> = HashTable::new();
+            for pos in left.clone() {
+                let word = self.left[pos];
+                table
+                    .entry(
+                        word.hash,
+                        |(w, _, _)| self.comp.eq(w.text, word.text),
+                        |(w, _, _)| w.hash,
+                    )
+                    .and_modify(|(_, _, counts)| counts[0] += 1)
+                    .or_insert((word, [pos, 0], [1, 0]));
+            }
+            for pos in right.clone() {
+                let word = self.right[pos];
+                if let Some((_, positions, counts)) =
+                    table.find_mut(word.hash, |(w, _, _)| self.comp.eq(w.text, word.text))
+                {
+                    positions[1] = pos;
+                    counts[1] += 1;
+                }
+            }
+            let mut unique_pairs = table
+                .into_iter()
+                .filter(|(_, _, counts)| *counts == [1, 1])
+                .map(|(_, [left_pos, right_pos], _)| (left_pos, right_pos))
+                .collect_vec();
+            if unique_pairs.is_empty() {
+                self.myers(left, right, found);
+                return;
+            }
+            unique_pairs.sort_unstable_by_key(|&(_, right_pos)| right_pos);
+            let mut previous_left_pos = left.start;
+            let mut previous_right_pos = right.start;
+            for (left_pos, right_pos) in find_increasing_subsequence(&unique_pairs) {
+                self.patience(
+                    previous_left_pos..left_pos,
+                    previous_right_pos..right_pos,
+                    found,
+                );
+                found.push((left_pos, right_pos));
+                previous_left_pos = left_pos + 1;
+                previous_right_pos = right_pos + 1;
+            }
+            self.patience(
+                previous_left_pos..left.end,
+                previous_right_pos..right.end,
+                found,
+            );
+        });
+    }
+
+    fn histogram(&self, left: Range<usize>, right: Range<usize>, found: &mut Vec<(usize, usize)>) {
+        self.with_common_affixes_trimmed(left, right, found, |left, right, found| {
+            if left.is_empty() || right.is_empty() {
+                return;
+            }
+            let mut table: HashTable<(HashedWord, SmallVec<[usize; 2]>)> = HashTable::new();
+            for pos in left.clone() {
+                let word = self.left[pos];
+                table
+                    .entry(
+                        word.hash,
+                        |(w, _)| self.comp.eq(w.text, word.text),
+                        |(w, _)| w.hash,
+                    )
+                    .and_modify(|(_, positions)| positions.push(pos))
+                    .or_insert((word, smallvec![pos]));
+            }
+            let mut left_counts = vec![0; left.len()];
+            for (_, positions) in &table {
+                for &pos in positions {
+                    left_counts[pos - left.start] = positions.len();
+                }
+            }
+
+            // Find the longest run of matching words that has the lowest
+            // number of occurrences in `left`.
+            let mut best: Option<(Range<usize>, Range<usize>, usize)> = None;
+            let mut has_long_chain = false;
+            let mut right_pos = right.start;
+            while right_pos < right.end {
+                let word = self.right[right_pos];
+                let mut next_right_pos = right_pos + 1;
+                let left_positions = table
+                    .find(word.hash, |(w, _)| self.comp.eq(w.text, word.text))
+                    .map(|(_, positions)| positions.as_slice())
+                    .unwrap_or_default();
+                if left_positions.len() > MAX_HISTOGRAM_CHAIN_LEN {
+                    has_long_chain = true;
+                } else if best
+                    .as_ref()
+                    .map_or(true, |(_, _, count)| left_positions.len() <= *count)
+                {
+                    for &left_pos in left_positions {
+                        let mut count = left_positions.len();
+                        let (mut left_start, mut right_start) = (left_pos, right_pos);
+                        while left_start > left.start
+                            && right_start > right.start
+                            && self.eq(left_start - 1, right_start - 1)
+                        {
+                            left_start -= 1;
+                            right_start -= 1;
+                            count = count.min(left_counts[left_start - left.start]);
+                        }
+                        let (mut left_end, mut right_end) = (left_pos + 1, right_pos + 1);
+                        while left_end < left.end
+                            && right_end < right.end
+                            && self.eq(left_end, right_end)
+                        {
+                            count = count.min(left_counts[left_end - left.start]);
+                            left_end += 1;
+                            right_end += 1;
+                        }
+                        next_right_pos = next_right_pos.max(right_end);
+                        if best.as_ref().map_or(true, |(best_left, _, best_count)| {
+                            left_end - left_start > best_left.len() || count < *best_count
+                        }) {
+                            best = Some((left_start..left_end, right_start..right_end, count));
+                        }
+                    }
+                }
+                right_pos = next_right_pos;
+            }
+
+            match best {
+                Some((best_left, best_right, _)) => {
+                    self.histogram(
+                        left.start..best_left.start,
+                        right.start..best_right.start,
+                        found,
+                    );
+                    found.extend(iter::zip(best_left.clone(), best_right.clone()));
+                    self.histogram(best_left.end..left.end, best_right.end..right.end, found);
+                }
+                None if has_long_chain => self.myers(left, right, found),
+                None => {}
+            }
+        });
+    }
+}
+
+fn myers_max_d(left_len: usize, right_len: usize) -> usize {
+    (left_len + right_len).div_ceil(2) + 1
+}
+
+/// Furthest reaching positions indexed by diagonal in Myers' algorithm.
+struct MyersV {
+    offset: isize,
+    v: Vec<usize>,
+}
+
+impl MyersV {
+    fn new(max_d: usize) -> Self {
+        MyersV {
+            offset: max_d as isize,
+            v: vec![0; 2 * max_d + 1],
+        }
+    }
+}
+
+impl std::ops::Index<isize> for MyersV {
+    type Output = usize;
+
+    fn index(&self, index: isize) -> &Self::Output {
+        &self.v[(index + self.offset) as usize]
+    }
+}
+
+impl std::ops::IndexMut<isize> for MyersV {
+    fn index_mut(&mut self, index: isize) -> &mut Self::Output {
+        &mut self.v[(index + self.offset) as usize]
+    }
+}
+
+/// Finds the longest subsequence of `pairs` in which the first elements are
+/// increasing, by patience sorting. `pairs` should be sorted by the second
+/// elements.
+fn find_increasing_subsequence(pairs: &[(usize, usize)]) -> Vec<(usize, usize)> {
+    // Index of the top card of each pile, and the top card of the previous
+    // pile at the time each card was placed.
+    let mut piles: Vec<usize> = Vec::new();
+    let mut predecessors: Vec<Option<usize>> = vec![None; pairs.len()];
+    for (i, &(left_pos, _)) in pairs.iter().enumerate() {
+        let pile = piles.partition_point(|&j| pairs[j].0 < left_pos);
+        predecessors[i] = pile.checked_sub(1).map(|prev| piles[prev]);
+        if pile == piles.len() {
+            piles.push(i);
+        } else {
+            piles[pile] = i;
+        }
+    }
+    let mut result = Vec::new();
+    let mut next = piles.last().copied();
+    while let Some(i) = next {
+        result.push(pairs[i]);
+        next = predecessors[i];
+    }
+    result.reverse();
+    result
+}
+
 /// Intersects two sorted sequences of `(base, other)` word positions by
 /// `base`. `base` positions should refer to the same source text.
 fn intersect_unchanged_words(
@@ -612,6 +1015,7 @@ impl<'input> Diff<'input> {
         inputs: impl IntoIterator<Item = &'input T>,
         tokenizer: impl Fn(&[u8]) -> Vec<Range<usize>>,
         compare: impl CompareBytes,
+        algorithm: DiffAlgorithm,
     ) -> Self {
         let mut inputs = inputs.into_iter().map(BStr::new);
         let base_input = inputs.next().expect("inputs must not be empty");
@@ -639,6 +1043,31 @@ impl<'input> Diff<'input> {
             &base_token_ranges,
             &other_token_ranges,
             compare,
+            algorithm,
+        )
+    }
+
+    /// Compares `inputs` split into the given tokens. The `token_ranges`
+    /// should be sorted ranges of non-empty tokens, one list per input.
+    pub fn for_token_ranges<T: AsRef<[u8]> + ?Sized + 'input>(
+        inputs: impl IntoIterator<Item = &'input T>,
+        token_ranges: &[Vec<Range<usize>>],
+        compare: impl CompareBytes,
+        algorithm: DiffAlgorithm,
+    ) -> Self {
+        let mut inputs = inputs.into_iter().map(BStr::new);
+        let base_input = inputs.next().expect("inputs must not be empty");
+        let other_inputs: SmallVec<[&BStr; 1]> = inputs.collect();
+        let (base_token_ranges, other_token_ranges) = token_ranges
+            .split_first()
+            .expect("token ranges must not be empty");
+        Self::with_inputs_and_token_ranges(
+            base_input,
+            other_inputs,
+            base_token_ranges,
+            other_token_ranges,
+            compare,
+            algorithm,
         )
     }
 
@@ -648,6 +1077,7 @@ impl<'input> Diff<'input> {
         base_token_ranges: &[Range<usize>],
         other_token_ranges: &[Vec<Range<usize>>],
         compare: impl CompareBytes,
+        algorithm: DiffAlgorithm,
     ) -> Self {
         assert_eq!(other_inputs.len(), other_token_ranges.len());
         let comp = WordComparator::new(compare);
@@ -675,7 +1105,8 @@ impl<'input> Diff<'input> {
                     others: smallvec![0..0; other_inputs.len()],
                 });
                 let mut first_positions = Vec::new();
-                collect_unchanged_words(
+                collect_unchanged_words_by(
+                    algorithm,
                     &mut first_positions,
                     &base_source.local(),
                     &first_other_source.local(),
@@ -701,7 +1132,8 @@ impl<'input> Diff<'input> {
                         first_positions,
                         |current_positions, other_source| {
                             let mut new_positions = Vec::new();
-                            collect_unchanged_words(
+                            collect_unchanged_words_by(
+                                algorithm,
                                 &mut new_positions,
                                 &base_source.local(),
                                 &other_source.local(),
@@ -745,14 +1177,19 @@ impl<'input> Diff<'input> {
     pub fn unrefined<T: AsRef<[u8]> + ?Sized + 'input>(
         inputs: impl IntoIterator<Item = &'input T>,
     ) -> Self {
-        Diff::for_tokenizer(inputs, |_| vec![], CompareBytesExactly)
+        Diff::for_tokenizer(inputs, |_| vec![], CompareBytesExactly, DiffAlgorithm::Lcs)
     }
 
     /// Compares `inputs` line by line.
     pub fn by_line<T: AsRef<[u8]> + ?Sized + 'input>(
         inputs: impl IntoIterator<Item = &'input T>,
     ) -> Self {
-        Diff::for_tokenizer(inputs, find_line_ranges, CompareBytesExactly)
+        Diff::for_tokenizer(
+            inputs,
+            find_line_ranges,
+            CompareBytesExactly,
+            DiffAlgorithm::Lcs,
+        )
     }
 
     /// Compares `inputs` word by word.
@@ -762,7 +1199,12 @@ impl<'input> Diff<'input> {
     pub fn by_word<T: AsRef<[u8]> + ?Sized + 'input>(
         inputs: impl IntoIterator<Item = &'input T>,
     ) -> Self {
-        let mut diff = Diff::for_tokenizer(inputs, find_word_ranges, CompareBytesExactly);
+        let mut diff = Diff::for_tokenizer(
+            inputs,
+            find_word_ranges,
+            CompareBytesExactly,
+            DiffAlgorithm::Lcs,
+        );
         diff.refine_changed_regions(find_nonword_ranges, CompareBytesExactly);
         diff
     }
@@ -813,8 +1255,12 @@ impl<'input> Diff<'input> {
             // create a new Diff instance. Then adjust the start positions and
             // offsets to be valid in the context of the larger Diff instance
             // (`self`).
-            let refined_diff =
-                Diff::for_tokenizer(self.hunk_between(previous, current), &tokenizer, &compare);
+            let refined_diff = Diff::for_tokenizer(
+                self.hunk_between(previous, current),
+                &tokenizer,
+                &compare,
+                DiffAlgorithm::Lcs,
+            );
             for refined in &refined_diff.unchanged_regions {
                 let new_base_start = refined.base.start + previous.base.end;
                 let new_base_end = refined.base.end + previous.base.end;
@@ -1013,7 +1459,12 @@ impl Iterator for DiffHunkRangeIterator<'_> {
 pub fn diff<'a, T: AsRef<[u8]> + ?Sized + 'a>(
     inputs: impl IntoIterator<Item = &'a T>,
 ) -> Vec<DiffHunk<'a>> {
-    let mut diff = Diff::for_tokenizer(inputs, find_line_ranges, CompareBytesExactly);
+    let mut diff = Diff::for_tokenizer(
+        inputs,
+        find_line_ranges,
+        CompareBytesExactly,
+        DiffAlgorithm::Lcs,
+    );
     diff.refine_changed_regions(find_word_ranges, CompareBytesExactly);
     diff.refine_changed_regions(find_nonword_ranges, CompareBytesExactly);
     diff.hunks().collect()
@@ -1413,6 +1864,7 @@ mod tests {
             ["a\nb\nc\nd\ne\nf\ng", "a\nb\nc\nX\ne\nf\ng"],
             find_line_ranges,
             CompareBytesExactly,
+            DiffAlgorithm::Lcs,
         );
         assert_eq!(
             diff.hunks().collect_vec(),
@@ -1424,6 +1876,156 @@ mod tests {
         );
     }
 
+    fn diff_lines_by<'a>(
+        algorithm: DiffAlgorithm,
+        left: &'a str,
+        right: &'a str,
+    ) -> Vec<DiffHunk<'a>> {
+        Diff::for_tokenizer(
+            [left, right],
+            find_line_ranges,
+            CompareBytesExactly,
+            algorithm,
+        )
+        .hunks()
+        .collect()
+    }
+
+    #[test]
+    fn test_find_increasing_subsequence() {
+        assert_eq!(find_increasing_subsequence(&[]), vec![]);
+        assert_eq!(
+            find_increasing_subsequence(&[(0, 0), (1, 1), (2, 2)]),
+            vec![(0, 0), (1, 1), (2, 2)]
+        );
+        assert_eq!(
+            find_increasing_subsequence(&[(2, 0), (1, 1), (0, 2)]),
+            vec![(0, 2)]
+        );
+        assert_eq!(
+            find_increasing_subsequence(&[(0, 0), (1, 1), (4, 2), (2, 3), (3, 4), (5, 5)]),
+            vec![(0, 0), (1, 1), (2, 3), (3, 4), (5, 5)]
+        );
+    }
+
+    #[test]
+    fn test_diff_algorithms_insert_in_middle() {
+        for algorithm in [
+            DiffAlgorithm::Lcs,
+            DiffAlgorithm::Myers,
+            DiffAlgorithm::Patience,
+            DiffAlgorithm::Histogram,
+        ] {
+            assert_eq!(
+                diff_lines_by(
+                    algorithm,
+                    "void a() {\n}\nvoid b() {\n}\n",
+                    "void a() {\n}\nvoid x() {\n}\nvoid b() {\n}\n",
+                ),
+                vec![
+                    DiffHunk::matching(["void a() {\n}\n"].repeat(2)),
+                    DiffHunk::different(["", "void x() {\n}\n"]),
+                    DiffHunk::matching(["void b() {\n}\n"].repeat(2)),
+                ],
+                "{algorithm:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_diff_myers_minimal() {
+        // The example from Myers' paper. The edit script has length 5.
+        assert_eq!(
+            diff_lines_by(
+                DiffAlgorithm::Myers,
+                "a\nb\nc\na\nb\nb\na\n",
+                "c\nb\na\nb\na\nc\n"
+            ),
+            vec![
+                DiffHunk::different(["a\nb\n", ""]),
+                DiffHunk::matching(["c\n"].repeat(2)),
+                DiffHunk::different(["a\n", ""]),
+                DiffHunk::matching(["b\n"].repeat(2)),
+                DiffHunk::different(["", "a\n"]),
+                DiffHunk::matching(["b\na\n"].repeat(2)),
+                DiffHunk::different(["", "c\n"]),
+            ]
+        );
+        // Non-unique lines are matched, unlike the default algorithm.
+        assert_eq!(
+            diff_lines_by(DiffAlgorithm::Myers, "a\na\nb\n", "a\nb\nb\n"),
+            vec![
+                DiffHunk::matching(["a\n"].repeat(2)),
+                DiffHunk::different(["a\n", "b\n"]),
+                DiffHunk::matching(["b\n"].repeat(2)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_patience_swapped_blocks() {
+        let left = "x\n{\na\n}\n{\nb\n}\ny\n";
+        let right = "y\n{\nb\n}\n{\na\n}\nx\n";
+        // Myers matches the braces, which produces a noisy diff.
+        assert_eq!(
+            diff_lines_by(DiffAlgorithm::Myers, left, right),
+            vec![
+                DiffHunk::different(["x\n", "y\n"]),
+                DiffHunk::matching(["{\n"].repeat(2)),
+                DiffHunk::different(["a\n", "b\n"]),
+                DiffHunk::matching(["}\n{\n"].repeat(2)),
+                DiffHunk::different(["b\n", "a\n"]),
+                DiffHunk::matching(["}\n"].repeat(2)),
+                DiffHunk::different(["y\n", "x\n"]),
+            ]
+        );
+        // Patience only matches on unique lines.
+        assert_eq!(
+            diff_lines_by(DiffAlgorithm::Patience, left, right),
+            vec![
+                DiffHunk::different(["", "y\n{\nb\n}\n{\na\n}\n"]),
+                DiffHunk::matching(["x\n"].repeat(2)),
+                DiffHunk::different(["{\na\n}\n{\nb\n}\ny\n", ""]),
+            ]
+        );
+        // Histogram keeps one of the blocks as a whole.
+        assert_eq!(
+            diff_lines_by(DiffAlgorithm::Histogram, left, right),
+            vec![
+                DiffHunk::different(["x\n{\na\n}\n", "y\n"]),
+                DiffHunk::matching(["{\nb\n}\n"].repeat(2)),
+                DiffHunk::different(["y\n", "{\na\n}\nx\n"]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_patience_falls_back_to_myers() {
+        // No lines are unique, so the regions between the anchors are diffed by
+        // Myers' algorithm.
+        assert_eq!(
+            diff_lines_by(DiffAlgorithm::Patience, "a\nb\na\nb\n", "b\na\nb\na\n"),
+            diff_lines_by(DiffAlgorithm::Myers, "a\nb\na\nb\n", "b\na\nb\na\n"),
+        );
+    }
+
+    #[test]
+    fn test_diff_histogram_multiple_inputs() {
+        let diff = Diff::for_tokenizer(
+            ["a\nb\nc\n", "a\nX\nc\n", "a\nb\nY\n"],
+            find_line_ranges,
+            CompareBytesExactly,
+            DiffAlgorithm::Histogram,
+        );
+        assert_eq!(
+            diff.hunks().collect_vec(),
+            vec![
+                DiffHunk::matching(["a\n"].repeat(3)),
+                DiffHunk::different(["b\nc\n", "X\nc\n", "b\nY\n"]),
+            ]
+        );
+    }
+
     #[test]
     fn test_diff_nothing_in_common() {
         assert_eq!(
@@ -1486,8 +2088,12 @@ mod tests {
     #[test]
     fn test_diff_ignore_all_whitespace() {
         fn diff(inputs: [&str; 2]) -> Vec<DiffHunk<'_>> {
-            let diff =
-                Diff::for_tokenizer(inputs, find_line_ranges, CompareBytesIgnoreAllWhitespace);
+            let diff = Diff::for_tokenizer(
+                inputs,
+                find_line_ranges,
+                CompareBytesIgnoreAllWhitespace,
+                DiffAlgorithm::Lcs,
+            );
             diff.hunks().collect()
         }
 
@@ -1524,8 +2130,12 @@ mod tests {
     #[test]
     fn test_diff_ignore_whitespace_amount() {
         fn diff(inputs: [&str; 2]) -> Vec<DiffHunk<'_>> {
-            let diff =
-                Diff::for_tokenizer(inputs, find_line_ranges, CompareBytesIgnoreWhitespaceAmount);
+            let diff = Diff::for_tokenizer(
+                inputs,
+                find_line_ranges,
+                CompareBytesIgnoreWhitespaceAmount,
+                DiffAlgorithm::Lcs,
+            );
             diff.hunks().collect()
         }
 
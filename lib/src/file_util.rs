@@ -74,6 +74,42 @@ pub fn remove_dir_contents(dirname: &Path) -> Result<(), PathError> {
     Ok(())
 }
 
+/// Moves the directory `from` to `to`. If they are on different file systems,
+/// the directory is copied and then removed.
+pub fn move_dir(from: &Path, to: &Path) -> Result<(), PathError> {
+    match fs::rename(from, to) {
+        Ok(()) => Ok(()),
+        Err(err) if is_cross_device_error(&err) => {
+            copy_dir_all(from, to).inspect_err(|_| {
+                let _ = fs::remove_dir_all(to);
+            })?;
+            fs::remove_dir_all(from).context(from)
+        }
+        Err(err) => Err(err).context(from),
+    }
+}
+
+/// Copies the directory `from` to `to` recursively. Symlinks are copied as
+/// symlinks.
+fn copy_dir_all(from: &Path, to: &Path) -> Result<(), PathError> {
+    fs::create_dir(to).context(to)?;
+    for entry in from.read_dir().context(from)? {
+        let entry = entry.context(from)?;
+        let from_path = entry.path();
+        let to_path = to.join(entry.file_name());
+        let file_type = entry.file_type().context(&from_path)?;
+        if file_type.is_dir() {
+            copy_dir_all(&from_path, &to_path)?;
+        } else if file_type.is_symlink() {
+            let target = fs::read_link(&from_path).context(&from_path)?;
+            try_symlink(target, &to_path).context(&to_path)?;
+        } else {
+            fs::copy(&from_path, &to_path).context(&from_path)?;
+        }
+    }
+    Ok(())
+}
+
 /// Expands "~/" to "$HOME/".
 pub fn expand_home_path(path_str: &str) -> PathBuf {
     if let Some(remainder) = path_str.strip_prefix("~/") {
@@ -177,6 +213,12 @@ mod platform {
     pub fn try_symlink<P: AsRef<Path>, Q: AsRef<Path>>(original: P, link: Q) -> io::Result<()> {
         symlink(original, link)
     }
+
+    /// Returns true if `fs::rename()` failed because the paths are on
+    /// different file systems.
+    pub fn is_cross_device_error(err: &io::Error) -> bool {
+        err.raw_os_error() == Some(rustix::io::Errno::XDEV.raw_os_error())
+    }
 }
 
 #[cfg(windows)]
@@ -207,6 +249,13 @@ mod platform {
 
         symlink_file(original, link)
     }
+
+    /// Returns true if `fs::rename()` failed because the paths are on
+    /// different file systems.
+    pub fn is_cross_device_error(err: &io::Error) -> bool {
+        // ERROR_NOT_SAME_DEVICE
+        err.raw_os_error() == Some(17)
+    }
 }
 
 #[cfg(test)]
@@ -231,6 +280,41 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_move_dir() {
+        let temp_dir = testutils::new_temp_dir();
+        let from = temp_dir.path().join("from");
+        fs::create_dir_all(from.join("sub")).unwrap();
+        fs::write(from.join("sub").join("file"), "contents").unwrap();
+        let to = temp_dir.path().join("to");
+        move_dir(&from, &to).unwrap();
+        assert!(!from.exists());
+        assert_eq!(fs::read(to.join("sub").join("file")).unwrap(), b"contents");
+    }
+
+    #[test]
+    fn test_copy_dir_all() {
+        // Used by move_dir() if the destination is on another file system
+        let temp_dir = testutils::new_temp_dir();
+        let from = temp_dir.path().join("from");
+        fs::create_dir_all(from.join("sub")).unwrap();
+        fs::write(from.join("sub").join("file"), "contents").unwrap();
+        if check_symlink_support().unwrap_or(false) {
+            try_symlink("sub/file", from.join("link")).unwrap();
+        }
+        let to = temp_dir.path().join("to");
+        copy_dir_all(&from, &to).unwrap();
+        assert_eq!(fs::read(to.join("sub").join("file")).unwrap(), b"contents");
+        if check_symlink_support().unwrap_or(false) {
+            assert_eq!(
+                fs::read_link(to.join("link")).unwrap(),
+                Path::new("sub/file")
+            );
+        }
+        // The destination must not exist
+        assert!(copy_dir_all(&from, &to).is_err());
+    }
+
     #[test]
     fn test_persist_no_existing_file() {
         let temp_dir = testutils::new_temp_dir();
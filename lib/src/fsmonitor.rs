@@ -34,12 +34,24 @@ pub struct WatchmanConfig {
     pub register_trigger: bool,
 }
 
+/// Config for the built-in filesystem monitor.
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub struct NativeConfig {
+    /// Whether to start the background watcher process if it isn't already
+    /// running for the working copy.
+    pub start_daemon: bool,
+}
+
 /// The recognized kinds of filesystem monitors.
 #[derive(Eq, PartialEq, Clone, Debug)]
 pub enum FsmonitorSettings {
     /// The Watchman filesystem monitor (<https://facebook.github.io/watchman/>).
     Watchman(WatchmanConfig),
 
+    /// The built-in filesystem monitor, which keeps a journal of changed paths
+    /// in a per-workspace background process. Only supported on Linux.
+    Native(NativeConfig),
+
     /// Only used in tests.
     Test {
         /// The set of changed files to pretend that the filesystem monitor is
@@ -63,6 +75,9 @@ impl FsmonitorSettings {
                 // TODO: rename to "register-snapshot-trigger" for consistency?
                 register_trigger: settings.get_bool("core.watchman.register_snapshot_trigger")?,
             })),
+            "native" => Ok(Self::Native(NativeConfig {
+                start_daemon: settings.get_bool("core.native-fsmonitor.start-daemon")?,
+            })),
             "test" => Err(ConfigGetError::Type {
                 name: name.to_owned(),
                 error: "Cannot use test fsmonitor in real repository".into(),
@@ -334,3 +349,600 @@ pub mod watchman {
         }
     }
 }
+
+/// Built-in filesystem monitor using inotify.
+///
+/// A background process, started with `jj debug fsmonitor run`, watches the
+/// working copy and appends the paths of changed files to a journal in the
+/// working copy state directory. Snapshots read the journal from the position
+/// recorded by the previous snapshot, so only those paths need to be stat'ed.
+#[cfg(target_os = "linux")]
+pub mod native {
+    use std::collections::HashMap;
+    use std::ffi::OsStr;
+    use std::ffi::OsString;
+    use std::fs;
+    use std::fs::File;
+    use std::io;
+    use std::io::BufRead as _;
+    use std::io::BufReader;
+    use std::io::BufWriter;
+    use std::io::Read as _;
+    use std::io::Seek as _;
+    use std::io::SeekFrom;
+    use std::io::Write as _;
+    use std::mem::MaybeUninit;
+    use std::os::fd::OwnedFd;
+    use std::os::unix::ffi::OsStrExt as _;
+    use std::path::Path;
+    use std::path::PathBuf;
+    use std::sync::atomic::AtomicU64;
+    use std::sync::atomic::Ordering;
+    use std::time::Duration;
+    use std::time::Instant;
+    use std::time::SystemTime;
+
+    use itertools::Itertools as _;
+    use rustix::event::PollFd;
+    use rustix::event::PollFlags;
+    use rustix::fs::inotify;
+    use rustix::fs::inotify::ReadFlags;
+    use rustix::fs::inotify::WatchFlags;
+    use rustix::fs::FlockOperation;
+    use rustix::io::Errno;
+    use tempfile::NamedTempFile;
+    use thiserror::Error;
+    use tracing::info;
+    use tracing::instrument;
+
+    const JOURNAL_FILE: &str = "journal";
+    const LOCK_FILE: &str = "daemon.lock";
+    const STOP_FILE: &str = "stop";
+    const COOKIE_PREFIX: &str = "cookie-";
+    /// Directories that are never watched, wherever they appear in the working
+    /// copy.
+    const IGNORED_DIR_NAMES: &[&str] = &[".git", ".jj"];
+    /// Once the journal grows past this size, a new one is started. Clients
+    /// with a clock for the old journal will crawl the working copy once.
+    const MAX_JOURNAL_SIZE: u64 = 64 << 20;
+    /// How long to wait for the background process to process pending events.
+    const SYNC_TIMEOUT: Duration = Duration::from_secs(2);
+
+    /// Represents a position in the journal of a particular instance of the
+    /// background process.
+    #[derive(Clone, Debug, Eq, PartialEq)]
+    pub struct Clock {
+        instance_id: String,
+        offset: u64,
+    }
+
+    impl From<crate::protos::working_copy::NativeFsmonitorClock> for Clock {
+        fn from(clock: crate::protos::working_copy::NativeFsmonitorClock) -> Self {
+            Clock {
+                instance_id: clock.instance_id,
+                offset: clock.offset,
+            }
+        }
+    }
+
+    impl From<Clock> for crate::protos::working_copy::NativeFsmonitorClock {
+        fn from(clock: Clock) -> Self {
+            crate::protos::working_copy::NativeFsmonitorClock {
+                instance_id: clock.instance_id,
+                offset: clock.offset,
+            }
+        }
+    }
+
+    #[allow(missing_docs)]
+    #[derive(Debug, Error)]
+    pub enum Error {
+        #[error("Failed to access filesystem monitor state in {path}")]
+        State {
+            path: PathBuf,
+            #[source]
+            source: io::Error,
+        },
+        #[error("Failed to watch directory {path}")]
+        Watch {
+            path: PathBuf,
+            #[source]
+            source: io::Error,
+        },
+        #[error("Failed to read filesystem events")]
+        ReadEvents(#[source] io::Error),
+        #[error("The filesystem monitor is already running for this working copy")]
+        AlreadyRunning,
+        #[error("Timed out waiting for the filesystem monitor to process pending events")]
+        SyncTimeout,
+    }
+
+    fn state_error(path: &Path) -> impl FnOnce(io::Error) -> Error + '_ {
+        move |source| Error::State {
+            path: path.to_owned(),
+            source,
+        }
+    }
+
+    /// Handle to the background process watching a working copy.
+    ///
+    /// The background process isn't started by this library. The embedding
+    /// application is expected to run [`Daemon`] in a separate process.
+    #[derive(Debug)]
+    pub struct Fsmonitor {
+        dir: PathBuf,
+    }
+
+    impl Fsmonitor {
+        /// Opens the filesystem monitor state stored under `state_path`.
+        pub fn new(state_path: &Path) -> Self {
+            Fsmonitor {
+                dir: state_path.join("fsmonitor"),
+            }
+        }
+
+        /// Returns whether the background process is running.
+        pub fn is_running(&self) -> Result<bool, Error> {
+            let lock_path = self.dir.join(LOCK_FILE);
+            let file = match File::open(&lock_path) {
+                Ok(file) => file,
+                Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(false),
+                Err(err) => return Err(state_error(&lock_path)(err)),
+            };
+            match rustix::fs::flock(&file, FlockOperation::NonBlockingLockShared) {
+                Ok(()) => Ok(false),
+                Err(Errno::WOULDBLOCK) => Ok(true),
+                Err(errno) => Err(state_error(&lock_path)(errno.into())),
+            }
+        }
+
+        /// Asks the background process to exit. Does nothing if it isn't
+        /// running.
+        pub fn stop(&self) -> Result<(), Error> {
+            if self.is_running()? {
+                let stop_path = self.dir.join(STOP_FILE);
+                File::create(&stop_path).map_err(state_error(&stop_path))?;
+            }
+            Ok(())
+        }
+
+        /// Query for changed files since the previous point in time.
+        ///
+        /// The returned clock is `None` if the background process isn't ready.
+        /// The returned list of paths is relative to the working copy path. If
+        /// it is `None`, then the caller must crawl the entire working copy
+        /// themselves.
+        #[instrument(skip(self))]
+        pub fn query_changed_files(
+            &self,
+            previous_clock: Option<Clock>,
+        ) -> Result<(Option<Clock>, Option<Vec<PathBuf>>), Error> {
+            info!("Querying filesystem monitor for changed files...");
+            let journal_path = self.dir.join(JOURNAL_FILE);
+            // The journal is written only once the initial crawl has finished.
+            if !self.is_running()? || !journal_path.exists() {
+                return Ok((None, None));
+            }
+            self.sync()?;
+            let file = match File::open(&journal_path) {
+                Ok(file) => file,
+                Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok((None, None)),
+                Err(err) => return Err(state_error(&journal_path)(err)),
+            };
+            let mut reader = BufReader::new(file);
+            let mut header = vec![];
+            reader
+                .read_until(b'\n', &mut header)
+                .map_err(state_error(&journal_path))?;
+            if header.pop() != Some(b'\n') {
+                return Ok((None, None));
+            }
+            let body_start = header.len() as u64 + 1;
+            let instance_id = String::from_utf8_lossy(&header).into_owned();
+            let len = reader
+                .get_ref()
+                .metadata()
+                .map_err(state_error(&journal_path))?
+                .len();
+            // A journal with another instance ID was rotated or restarted, and
+            // a shorter one was truncated, so the previous clock can't be used.
+            let Some(previous) = previous_clock.filter(|previous| {
+                previous.instance_id == instance_id && (body_start..=len).contains(&previous.offset)
+            }) else {
+                let end = find_entries_end(reader.get_mut(), body_start, len)
+                    .map_err(state_error(&journal_path))?;
+                let clock = Clock {
+                    instance_id,
+                    offset: end,
+                };
+                return Ok((Some(clock), None));
+            };
+            // Only read the entries appended since the previous query.
+            let mut tail = vec![];
+            reader
+                .seek(SeekFrom::Start(previous.offset))
+                .and_then(|_| reader.read_to_end(&mut tail))
+                .map_err(state_error(&journal_path))?;
+            // Ignore a partially written entry at the end.
+            let tail_len = tail.iter().rposition(|&b| b == 0).map_or(0, |pos| pos + 1);
+            tail.truncate(tail_len);
+            let clock = Clock {
+                instance_id,
+                offset: previous.offset + tail_len as u64,
+            };
+            let changed_files = tail
+                .split(|&b| b == 0)
+                .filter(|name| !name.is_empty())
+                .unique()
+                .map(|name| PathBuf::from(OsStr::from_bytes(name)))
+                .collect_vec();
+            Ok((Some(clock), Some(changed_files)))
+        }
+
+        /// Waits until the background process has journaled all events that
+        /// happened before this call.
+        fn sync(&self) -> Result<(), Error> {
+            static COUNTER: AtomicU64 = AtomicU64::new(0);
+            let cookie_path = self.dir.join(format!(
+                "{COOKIE_PREFIX}{}-{}",
+                std::process::id(),
+                COUNTER.fetch_add(1, Ordering::Relaxed)
+            ));
+            // The background process removes the cookie once it has seen it,
+            // so wait for the removal to be reported.
+            let inotify =
+                inotify::init(inotify::CreateFlags::CLOEXEC | inotify::CreateFlags::NONBLOCK)
+                    .map_err(|errno| Error::ReadEvents(errno.into()))?;
+            inotify::add_watch(
+                &inotify,
+                &self.dir,
+                WatchFlags::DELETE | WatchFlags::ONLYDIR,
+            )
+            .map_err(|errno| Error::Watch {
+                path: self.dir.clone(),
+                source: errno.into(),
+            })?;
+            File::create(&cookie_path).map_err(state_error(&cookie_path))?;
+            let cookie_name = cookie_path.file_name().unwrap_or_default().as_bytes();
+            let deadline = Instant::now() + SYNC_TIMEOUT;
+            let mut buf = [MaybeUninit::uninit(); 1024];
+            let mut reader = inotify::Reader::new(&inotify, &mut buf);
+            loop {
+                match reader.next() {
+                    Ok(event) => {
+                        if event
+                            .file_name()
+                            .is_some_and(|name| name.to_bytes() == cookie_name)
+                        {
+                            return Ok(());
+                        }
+                    }
+                    Err(Errno::AGAIN) => {
+                        let remaining = deadline.saturating_duration_since(Instant::now());
+                        if remaining.is_zero() {
+                            fs::remove_file(&cookie_path).ok();
+                            return Err(Error::SyncTimeout);
+                        }
+                        let timeout = i32::try_from(remaining.as_millis()).unwrap_or(i32::MAX);
+                        let mut fds = [PollFd::new(&inotify, PollFlags::IN)];
+                        match rustix::event::poll(&mut fds, timeout.max(1)) {
+                            Ok(_) | Err(Errno::INTR) => {}
+                            Err(errno) => return Err(Error::ReadEvents(errno.into())),
+                        }
+                    }
+                    Err(Errno::INTR) => {}
+                    Err(errno) => return Err(Error::ReadEvents(errno.into())),
+                }
+            }
+        }
+    }
+
+    /// The background process that watches a working copy and journals the
+    /// changed paths.
+    #[derive(Debug)]
+    pub struct Daemon {
+        working_copy_path: PathBuf,
+        dir: PathBuf,
+        inotify: OwnedFd,
+        _lock: File,
+        control_wd: i32,
+        root_wd: i32,
+        dirs: HashMap<i32, PathBuf>,
+        journal: BufWriter<File>,
+        journal_size: u64,
+    }
+
+    impl Daemon {
+        /// Takes ownership of the filesystem monitor state stored under
+        /// `state_path` and starts watching the working copy. Returns
+        /// [`Error::AlreadyRunning`] if another process is watching it.
+        #[instrument]
+        pub fn new(working_copy_path: &Path, state_path: &Path) -> Result<Self, Error> {
+            info!("Initializing filesystem monitor...");
+            let dir = state_path.join("fsmonitor");
+            fs::create_dir_all(&dir).map_err(state_error(&dir))?;
+            let lock_path = dir.join(LOCK_FILE);
+            let lock = File::create(&lock_path).map_err(state_error(&lock_path))?;
+            match rustix::fs::flock(&lock, FlockOperation::NonBlockingLockExclusive) {
+                Ok(()) => {}
+                Err(Errno::WOULDBLOCK) => return Err(Error::AlreadyRunning),
+                Err(errno) => return Err(state_error(&lock_path)(errno.into())),
+            }
+            // Clean up after a previous instance that didn't exit cleanly.
+            for entry in fs::read_dir(&dir).map_err(state_error(&dir))? {
+                let entry = entry.map_err(state_error(&dir))?;
+                let name = entry.file_name();
+                if name == JOURNAL_FILE
+                    || name == STOP_FILE
+                    || name.as_bytes().starts_with(COOKIE_PREFIX.as_bytes())
+                {
+                    fs::remove_file(entry.path()).map_err(state_error(&entry.path()))?;
+                }
+            }
+
+            let inotify = inotify::init(inotify::CreateFlags::CLOEXEC)
+                .map_err(|errno| Error::ReadEvents(errno.into()))?;
+            let control_wd = inotify::add_watch(
+                &inotify,
+                &dir,
+                WatchFlags::CREATE | WatchFlags::DELETE_SELF | WatchFlags::ONLYDIR,
+            )
+            .map_err(|errno| Error::Watch {
+                path: dir.clone(),
+                source: errno.into(),
+            })?;
+            let mut dirs = HashMap::new();
+            let root_wd = watch_tree(&inotify, working_copy_path, Path::new(""), &mut dirs, None)?
+                .ok_or_else(|| Error::Watch {
+                    path: working_copy_path.to_owned(),
+                    source: io::ErrorKind::NotFound.into(),
+                })?;
+            let (journal, journal_size) = create_journal(&dir)?;
+            Ok(Daemon {
+                working_copy_path: working_copy_path.to_owned(),
+                dir,
+                inotify,
+                _lock: lock,
+                control_wd,
+                root_wd,
+                dirs,
+                journal,
+                journal_size,
+            })
+        }
+
+        /// Processes filesystem events until the working copy or the
+        /// filesystem monitor state is removed, or until
+        /// [`Fsmonitor::stop()`] is called.
+        pub fn run(mut self) -> Result<(), Error> {
+            let inotify = self.inotify.try_clone().map_err(Error::ReadEvents)?;
+            let mut buf = [MaybeUninit::uninit(); 16384];
+            let mut reader = inotify::Reader::new(inotify, &mut buf);
+            loop {
+                let (wd, events, name) = match reader.next() {
+                    Ok(event) => (
+                        event.wd(),
+                        event.events(),
+                        event
+                            .file_name()
+                            .map(|name| OsStr::from_bytes(name.to_bytes()).to_owned()),
+                    ),
+                    Err(Errno::INTR) => continue,
+                    Err(errno) => return Err(Error::ReadEvents(errno.into())),
+                };
+                if !self.handle_event(wd, events, name)? {
+                    break;
+                }
+                if reader.is_buffer_empty() {
+                    self.flush_journal()?;
+                }
+            }
+            info!("Stopping filesystem monitor");
+            let journal_path = self.dir.join(JOURNAL_FILE);
+            fs::remove_file(&journal_path).map_err(state_error(&journal_path))?;
+            Ok(())
+        }
+
+        /// Returns `false` if the daemon should exit.
+        fn handle_event(
+            &mut self,
+            wd: i32,
+            events: ReadFlags,
+            name: Option<OsString>,
+        ) -> Result<bool, Error> {
+            if events.contains(ReadFlags::QUEUE_OVERFLOW) {
+                // Some events were lost, so the journal is incomplete.
+                (self.journal, self.journal_size) = create_journal(&self.dir)?;
+                return Ok(true);
+            }
+            if wd == self.control_wd {
+                let Some(name) = name else {
+                    // The state directory was removed.
+                    return Ok(!events.contains(ReadFlags::IGNORED));
+                };
+                if name == STOP_FILE {
+                    fs::remove_file(self.dir.join(&name)).ok();
+                    return Ok(false);
+                } else if name.as_bytes().starts_with(COOKIE_PREFIX.as_bytes()) {
+                    // All events that happened before the cookie was created
+                    // have been processed.
+                    self.flush_journal()?;
+                    fs::remove_file(self.dir.join(&name)).ok();
+                }
+                return Ok(true);
+            }
+            if wd == self.root_wd && events.intersects(ReadFlags::IGNORED | ReadFlags::MOVE_SELF) {
+                // The working copy was removed or moved away.
+                return Ok(false);
+            }
+            if events.contains(ReadFlags::IGNORED) {
+                self.dirs.remove(&wd);
+                return Ok(true);
+            }
+            let (Some(dir), Some(name)) = (self.dirs.get(&wd), name) else {
+                return Ok(true);
+            };
+            if IGNORED_DIR_NAMES.iter().any(|&ignored| name == ignored) {
+                return Ok(true);
+            }
+            let path = dir.join(name);
+            if events.contains(ReadFlags::ISDIR) {
+                if events.contains(ReadFlags::MOVED_FROM) {
+                    // Watches follow the moved directories, so forget them. They
+                    // are added back under the new path if it's inside the
+                    // working copy.
+                    let moved_wds = self
+                        .dirs
+                        .iter()
+                        .filter(|(_, dir)| dir.starts_with(&path))
+                        .map(|(&wd, _)| wd)
+                        .collect_vec();
+                    for wd in moved_wds {
+                        self.dirs.remove(&wd);
+                        inotify::remove_watch(&self.inotify, wd).ok();
+                    }
+                } else if events.intersects(ReadFlags::CREATE | ReadFlags::MOVED_TO) {
+                    // Files may have been added before the directory was
+                    // watched.
+                    let mut discovered = vec![];
+                    watch_tree(
+                        &self.inotify,
+                        &self.working_copy_path,
+                        &path,
+                        &mut self.dirs,
+                        Some(&mut discovered),
+                    )?;
+                    for path in &discovered {
+                        self.append_to_journal(path)?;
+                    }
+                }
+            }
+            self.append_to_journal(&path)?;
+            Ok(true)
+        }
+
+        fn append_to_journal(&mut self, path: &Path) -> Result<(), Error> {
+            let journal_path = self.dir.join(JOURNAL_FILE);
+            let bytes = path.as_os_str().as_bytes();
+            self.journal
+                .write_all(bytes)
+                .and_then(|()| self.journal.write_all(b"\0"))
+                .map_err(state_error(&journal_path))?;
+            self.journal_size += bytes.len() as u64 + 1;
+            if self.journal_size > MAX_JOURNAL_SIZE {
+                (self.journal, self.journal_size) = create_journal(&self.dir)?;
+            }
+            Ok(())
+        }
+
+        fn flush_journal(&mut self) -> Result<(), Error> {
+            let journal_path = self.dir.join(JOURNAL_FILE);
+            self.journal.flush().map_err(state_error(&journal_path))
+        }
+    }
+
+    const TREE_WATCH_FLAGS: WatchFlags = WatchFlags::ATTRIB
+        .union(WatchFlags::CLOSE_WRITE)
+        .union(WatchFlags::CREATE)
+        .union(WatchFlags::DELETE)
+        .union(WatchFlags::MODIFY)
+        .union(WatchFlags::MOVE_SELF)
+        .union(WatchFlags::MOVED_FROM)
+        .union(WatchFlags::MOVED_TO)
+        .union(WatchFlags::DONT_FOLLOW)
+        .union(WatchFlags::EXCL_UNLINK)
+        .union(WatchFlags::ONLYDIR);
+
+    /// Watches the directory at `dir` and everything below it. Paths of the
+    /// entries found are added to `discovered`. Returns the watch descriptor
+    /// of `dir`, or `None` if it disappeared before it could be watched.
+    fn watch_tree(
+        inotify: &OwnedFd,
+        working_copy_path: &Path,
+        dir: &Path,
+        dirs: &mut HashMap<i32, PathBuf>,
+        mut discovered: Option<&mut Vec<PathBuf>>,
+    ) -> Result<Option<i32>, Error> {
+        let disk_dir = working_copy_path.join(dir);
+        let is_root = dir.as_os_str().is_empty();
+        let watch_error = |source: io::Error| Error::Watch {
+            path: disk_dir.clone(),
+            source,
+        };
+        let wd = match inotify::add_watch(inotify, &disk_dir, TREE_WATCH_FLAGS) {
+            Ok(wd) => wd,
+            // The directory was removed or replaced before we got to it.
+            Err(Errno::NOENT | Errno::NOTDIR) if !is_root => return Ok(None),
+            Err(errno) => return Err(watch_error(errno.into())),
+        };
+        dirs.insert(wd, dir.to_owned());
+        let entries = match fs::read_dir(&disk_dir) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == io::ErrorKind::NotFound && !is_root => return Ok(None),
+            Err(err) => return Err(watch_error(err)),
+        };
+        for entry in entries {
+            let Ok(entry) = entry else {
+                continue;
+            };
+            let name = entry.file_name();
+            if IGNORED_DIR_NAMES.iter().any(|&ignored| name == ignored) {
+                continue;
+            }
+            let path = dir.join(&name);
+            let is_dir = entry.file_type().is_ok_and(|file_type| file_type.is_dir());
+            if let Some(discovered) = discovered.as_deref_mut() {
+                discovered.push(path.clone());
+            }
+            if is_dir {
+                watch_tree(
+                    inotify,
+                    working_copy_path,
+                    &path,
+                    dirs,
+                    discovered.as_deref_mut(),
+                )?;
+            }
+        }
+        Ok(Some(wd))
+    }
+
+    /// Returns the offset just past the last complete entry of the journal
+    /// whose entries start at `body_start` and which is `len` bytes long.
+    ///
+    /// The journal is scanned backwards, so only the partially written entry
+    /// at the end is read.
+    fn find_entries_end(file: &mut File, body_start: u64, len: u64) -> io::Result<u64> {
+        let mut buf = vec![0; 4096];
+        let mut end = len;
+        while end > body_start {
+            let start = end.saturating_sub(buf.len() as u64).max(body_start);
+            let chunk = &mut buf[..(end - start) as usize];
+            file.seek(SeekFrom::Start(start))?;
+            file.read_exact(chunk)?;
+            if let Some(pos) = chunk.iter().rposition(|&b| b == 0) {
+                return Ok(start + pos as u64 + 1);
+            }
+            end = start;
+        }
+        Ok(body_start)
+    }
+
+    /// Replaces the journal with an empty one for a new instance ID.
+    fn create_journal(dir: &Path) -> Result<(BufWriter<File>, u64), Error> {
+        let journal_path = dir.join(JOURNAL_FILE);
+        let timestamp = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos();
+        let header = format!("{}-{timestamp}\n", std::process::id());
+        let mut temp_file = NamedTempFile::new_in(dir).map_err(state_error(dir))?;
+        temp_file
+            .write_all(header.as_bytes())
+            .map_err(state_error(temp_file.path()))?;
+        let file = temp_file
+            .persist(&journal_path)
+            .map_err(|err| state_error(&journal_path)(err.error))?;
+        Ok((BufWriter::new(file), header.len() as u64))
+    }
+}
@@ -22,10 +22,12 @@ use std::default::Default;
 use std::fmt;
 use std::io::Read;
 use std::num::NonZeroU32;
+use std::path::Path;
 use std::path::PathBuf;
 use std::str;
 
 use bstr::BStr;
+use gix::objs::WriteTo as _;
 use itertools::Itertools;
 use tempfile::NamedTempFile;
 use thiserror::Error;
@@ -35,7 +37,10 @@ use crate::backend::BackendResult;
 use crate::backend::CommitId;
 use crate::backend::TreeValue;
 use crate::commit::Commit;
+use crate::git_backend::signature_to_git;
 use crate::git_backend::GitBackend;
+use crate::git_gix::GitGixContext;
+use crate::git_gix::GitGixError;
 use crate::git_subprocess::GitSubprocessContext;
 use crate::git_subprocess::GitSubprocessError;
 use crate::index::Index;
@@ -45,6 +50,7 @@ use crate::op_store::RefTarget;
 use crate::op_store::RefTargetOptionExt;
 use crate::op_store::RemoteRef;
 use crate::op_store::RemoteRefState;
+use crate::op_store::TagAnnotation;
 use crate::refs;
 use crate::refs::BookmarkPushUpdate;
 use crate::repo::MutableRepo;
@@ -52,6 +58,8 @@ use crate::repo::Repo;
 use crate::repo_path::RepoPath;
 use crate::revset::RevsetExpression;
 use crate::settings::GitSettings;
+use crate::signing::SignResult;
+use crate::signing::Signer;
 use crate::store::Store;
 use crate::str_util::StringPattern;
 use crate::view::View;
@@ -89,8 +97,8 @@ impl fmt::Display for RefName {
 #[derive(Debug, Hash, PartialEq, Eq)]
 pub(crate) struct RefSpec {
     forced: bool,
-    source: Option<String>,
-    destination: String,
+    pub(crate) source: Option<String>,
+    pub(crate) destination: String,
 }
 
 impl RefSpec {
@@ -735,6 +743,9 @@ pub enum FailedRefExportReason {
     /// We wanted to modify it, but Git had deleted it
     #[error("Modified ref had been deleted in Git")]
     ModifiedInJjDeletedInGit,
+    /// We wanted to modify it, but Git had modified it to a different target
+    #[error("Modified ref had been modified in Git")]
+    ModifiedInJjModifiedInGit,
     /// Failed to delete the ref from the Git repo
     #[error("Failed to delete")]
     FailedToDelete(#[source] Box<gix::reference::edit::Error>),
@@ -750,17 +761,16 @@ struct RefsToExport {
     failed_branches: HashMap<RefName, FailedRefExportReason>,
 }
 
-/// Export changes to branches made in the Jujutsu repo compared to our last
-/// seen view of the Git repo in `mut_repo.view().git_refs()`. Returns a list of
-/// refs that failed to export.
+/// Export changes to branches and tags made in the Jujutsu repo compared to our
+/// last seen view of the Git repo in `mut_repo.view().git_refs()`. Returns a
+/// list of refs that failed to export.
 ///
-/// We ignore changed branches that are conflicted (were also changed in the Git
-/// repo compared to our last remembered view of the Git repo). These will be
-/// marked conflicted by the next `jj git import`.
+/// We ignore changed branches and tags that are conflicted (were also changed
+/// in the Git repo compared to our last remembered view of the Git repo). These
+/// will be marked conflicted by the next `jj git import`.
 ///
-/// We do not export tags and other refs at the moment, since these aren't
-/// supposed to be modified by JJ. For them, the Git state is considered
-/// authoritative.
+/// Annotated tags are exported as Git tag objects. We do not export other refs
+/// at the moment. For them, the Git state is considered authoritative.
 pub fn export_refs(mut_repo: &mut MutableRepo) -> Result<Vec<FailedRefExport>, GitExportError> {
     export_some_refs(mut_repo, |_| true)
 }
@@ -842,6 +852,8 @@ pub fn export_some_refs(
         }
     }
 
+    export_tags(mut_repo, &git_repo, &git_ref_filter, &mut failed_branches)?;
+
     copy_exportable_local_branches_to_remote_view(
         mut_repo,
         REMOTE_NAME_FOR_LOCAL_GIT_REPO,
@@ -974,6 +986,184 @@ fn diff_refs_to_export(
     }
 }
 
+/// Exports tags that differ from the last seen Git tags. Unlike branches, Git
+/// tags may point to tag objects, so they are compared by the peeled commit id.
+fn export_tags(
+    mut_repo: &mut MutableRepo,
+    git_repo: &gix::Repository,
+    git_ref_filter: impl Fn(&RefName) -> bool,
+    failed_refs: &mut HashMap<RefName, FailedRefExportReason>,
+) -> Result<(), GitExportError> {
+    let view = mut_repo.view();
+    let known_git_tags = view
+        .git_refs()
+        .iter()
+        .filter_map(|(full_name, target)| Some((full_name.strip_prefix("refs/tags/")?, target)));
+    let local_tags = view
+        .tags()
+        .iter()
+        .map(|(name, target)| (name.as_str(), target));
+    let moved_tags = refs::diff_named_ref_targets(known_git_tags, local_tags)
+        .map(|(name, (old_target, new_target))| (name, old_target, new_target));
+    // Annotations aren't part of the ref target, so annotated tags are also
+    // exported if the target didn't change. Git tags that already point to
+    // the same tag object are left untouched by update_git_tag().
+    let unmoved_annotated_tags = view
+        .tag_annotations()
+        .keys()
+        .map(|name| {
+            let git_target = view.get_git_ref(&format!("refs/tags/{name}"));
+            (name.as_str(), git_target, view.get_tag(name))
+        })
+        .filter(|(_, git_target, target)| git_target == target);
+    let changed_tags = itertools::chain(moved_tags, unmoved_annotated_tags)
+        .filter(|(name, _, _)| git_ref_filter(&RefName::Tag((*name).to_owned())))
+        .map(|(name, old_target, new_target)| {
+            let annotation = view.get_tag_annotation(name).cloned();
+            (
+                name.to_owned(),
+                old_target.clone(),
+                new_target.clone(),
+                annotation,
+            )
+        })
+        .collect_vec();
+
+    let root_commit_target = RefTarget::normal(mut_repo.store().root_commit_id().clone());
+    for (name, old_target, new_target, annotation) in changed_tags {
+        let ref_name = RefName::Tag(name.clone());
+        if new_target == root_commit_target {
+            // Git doesn't have a root commit
+            failed_refs.insert(ref_name, FailedRefExportReason::OnRootCommit);
+            continue;
+        }
+        let old_oid = if let Some(id) = old_target.as_normal() {
+            Some(gix::ObjectId::try_from(id.as_bytes()).unwrap())
+        } else if old_target.has_conflict() {
+            failed_refs.insert(ref_name, FailedRefExportReason::ConflictedOldState);
+            continue;
+        } else {
+            None
+        };
+        let git_ref_name = format!("refs/tags/{name}");
+        let result = if let Some(id) = new_target.as_normal() {
+            let new_oid = gix::ObjectId::try_from(id.as_bytes()).unwrap();
+            let ref_oid = match &annotation {
+                Some(annotation) => git_repo
+                    .write_object(to_git_tag_object(&name, id, annotation))
+                    .map_err(GitExportError::from_git)?
+                    .detach(),
+                None => new_oid,
+            };
+            update_git_tag(git_repo, &git_ref_name, old_oid, new_oid, ref_oid)
+        } else if new_target.has_conflict() {
+            // Skip conflicts and leave the old value in git_refs
+            continue;
+        } else {
+            delete_git_tag(git_repo, &git_ref_name, old_oid.unwrap())
+        };
+        match result {
+            Ok(()) => mut_repo.set_git_ref_target(&git_ref_name, new_target),
+            Err(reason) => {
+                failed_refs.insert(ref_name, reason);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Returns the commit id the Git ref eventually points to.
+fn peel_git_ref(mut git_ref: gix::Reference) -> Option<gix::ObjectId> {
+    git_ref.peel_to_id_in_place().ok().map(|id| id.detach())
+}
+
+fn delete_git_tag(
+    git_repo: &gix::Repository,
+    git_ref_name: &str,
+    old_oid: gix::ObjectId,
+) -> Result<(), FailedRefExportReason> {
+    if let Ok(git_ref) = git_repo.find_reference(git_ref_name) {
+        if peel_git_ref(git_ref.clone()) == Some(old_oid) {
+            git_ref
+                .delete()
+                .map_err(|err| FailedRefExportReason::FailedToDelete(err.into()))?;
+        } else {
+            return Err(FailedRefExportReason::DeletedInJjModifiedInGit);
+        }
+    }
+    Ok(())
+}
+
+fn update_git_tag(
+    git_repo: &gix::Repository,
+    git_ref_name: &str,
+    old_oid: Option<gix::ObjectId>,
+    new_oid: gix::ObjectId,
+    ref_oid: gix::ObjectId,
+) -> Result<(), FailedRefExportReason> {
+    let git_ref = git_repo.find_reference(git_ref_name).ok();
+    let expected = match (old_oid, git_ref) {
+        (_, Some(git_ref))
+            if git_ref.inner.target.try_id() == Some(&ref_oid)
+                || (ref_oid == new_oid && peel_git_ref(git_ref.clone()) == Some(new_oid)) =>
+        {
+            // Git already has the desired target and annotation. A Git tag
+            // annotated outside of jj is kept if the jj tag isn't annotated.
+            return Ok(());
+        }
+        (None, Some(_)) => return Err(FailedRefExportReason::AddedInJjAddedInGit),
+        (None, None) => gix::refs::transaction::PreviousValue::MustNotExist,
+        (Some(_), None) => return Err(FailedRefExportReason::ModifiedInJjDeletedInGit),
+        (Some(old_oid), Some(git_ref)) => {
+            let current_target = git_ref.inner.target.clone();
+            if peel_git_ref(git_ref) != Some(old_oid) {
+                return Err(FailedRefExportReason::ModifiedInJjModifiedInGit);
+            }
+            gix::refs::transaction::PreviousValue::MustExistAndMatch(current_target)
+        }
+    };
+    git_repo
+        .reference(git_ref_name, ref_oid, expected, "export from jj")
+        .map_err(|err| FailedRefExportReason::FailedToSet(err.into()))?;
+    Ok(())
+}
+
+/// Builds the Git tag object for the annotated tag `name` pointing to `target`.
+fn to_git_tag_object(name: &str, target: &CommitId, annotation: &TagAnnotation) -> gix::objs::Tag {
+    gix::objs::Tag {
+        target: gix::ObjectId::try_from(target.as_bytes()).unwrap(),
+        target_kind: gix::objs::Kind::Commit,
+        name: name.into(),
+        tagger: Some(signature_to_git(&annotation.tagger).into()),
+        message: annotation.message.as_str().into(),
+        pgp_signature: annotation
+            .signature
+            .as_ref()
+            .map(|signature| signature.as_slice().into()),
+    }
+}
+
+/// Signs the annotated tag `name` pointing to `target`, replacing any existing
+/// signature of the annotation.
+///
+/// The signature covers the Git tag object, so the tag should be re-signed if
+/// it is moved to another commit.
+pub fn sign_tag_annotation(
+    signer: &Signer,
+    name: &str,
+    target: &CommitId,
+    annotation: &mut TagAnnotation,
+    key: Option<&str>,
+) -> SignResult<()> {
+    annotation.signature = None;
+    let mut payload = Vec::new();
+    to_git_tag_object(name, target, annotation)
+        .write_to(&mut payload)
+        .expect("writing to a Vec should not fail");
+    annotation.signature = Some(signer.sign(&payload, key)?);
+    Ok(())
+}
+
 fn delete_git_ref(
     git_repo: &gix::Repository,
     git_ref_name: &str,
@@ -1484,6 +1674,8 @@ pub enum GitFetchError {
     InternalGitError(#[from] git2::Error),
     #[error(transparent)]
     Subprocess(#[from] GitSubprocessError),
+    #[error(transparent)]
+    Gix(#[from] GitGixError),
 }
 
 // TODO: If Git2 implementation is removed, this can be replaced with
@@ -1649,12 +1841,18 @@ enum GitFetchImpl<'a> {
         git_repo: Box<gix::Repository>,
         git_ctx: GitSubprocessContext<'a>,
     },
+    Gix {
+        git_ctx: Box<GitGixContext>,
+    },
 }
 
 impl<'a> GitFetchImpl<'a> {
     fn new(store: &Store, git_settings: &'a GitSettings) -> Result<Self, GitFetchPrepareError> {
         let git_backend = get_git_backend(store)?;
-        if git_settings.subprocess {
+        if git_settings.gix_transport {
+            let git_ctx = Box::new(GitGixContext::from_git_backend(git_backend, git_settings));
+            Ok(GitFetchImpl::Gix { git_ctx })
+        } else if git_settings.subprocess {
             let git_repo = Box::new(git_backend.git_repo());
             let git_ctx =
                 GitSubprocessContext::from_git_backend(git_backend, &git_settings.executable_path);
@@ -1684,6 +1882,9 @@ impl<'a> GitFetchImpl<'a> {
                 callbacks,
                 depth,
             ),
+            GitFetchImpl::Gix { git_ctx } => {
+                gix_fetch(git_ctx, remote_name, branch_names, callbacks, depth)
+            }
         }
     }
 
@@ -1699,6 +1900,7 @@ impl<'a> GitFetchImpl<'a> {
             GitFetchImpl::Subprocess { git_repo, git_ctx } => {
                 subprocess_get_default_branch(git_repo, git_ctx, remote_name, callbacks)
             }
+            GitFetchImpl::Gix { git_ctx } => gix_get_default_branch(git_ctx, remote_name),
         }
     }
 }
@@ -1841,6 +2043,37 @@ fn subprocess_get_default_branch(
     Ok(default_branch)
 }
 
+fn gix_fetch(
+    git_ctx: &GitGixContext,
+    remote_name: &str,
+    branch_names: &[StringPattern],
+    mut callbacks: RemoteCallbacks<'_>,
+    depth: Option<NonZeroU32>,
+) -> Result<(), GitFetchError> {
+    if !git_ctx.has_remote(remote_name) {
+        return Err(GitFetchError::NoSuchRemote(remote_name.to_owned()));
+    }
+    let refspecs = expand_fetch_refspecs(remote_name, branch_names)?;
+    if refspecs.is_empty() {
+        // Don't fall back to the base refspecs.
+        return Ok(());
+    }
+    git_ctx.fetch(remote_name, &refspecs, &mut callbacks, depth)?;
+    Ok(())
+}
+
+fn gix_get_default_branch(
+    git_ctx: &GitGixContext,
+    remote_name: &str,
+) -> Result<Option<String>, GitFetchError> {
+    if !git_ctx.has_remote(remote_name) {
+        return Err(GitFetchError::NoSuchRemote(remote_name.to_owned()));
+    }
+    let default_branch = git_ctx.get_default_branch(remote_name)?;
+    tracing::debug!(default_branch = default_branch);
+    Ok(default_branch)
+}
+
 #[derive(Error, Debug)]
 pub enum GitPushError {
     #[error("No git remote named '{0}'")]
@@ -1861,6 +2094,8 @@ pub enum GitPushError {
     #[error(transparent)]
     Subprocess(#[from] GitSubprocessError),
     #[error(transparent)]
+    Gix(#[from] GitGixError),
+    #[error(transparent)]
     UnexpectedBackend(#[from] UnexpectedGitBackendError),
 }
 
@@ -1949,7 +2184,16 @@ pub fn push_updates(
     // requires adjusting some tests.
 
     let git_backend = get_git_backend(repo.store())?;
-    if git_settings.subprocess {
+    if git_settings.gix_transport {
+        let git_ctx = GitGixContext::from_git_backend(git_backend, git_settings);
+        gix_push_refs(
+            repo,
+            &git_ctx,
+            remote_name,
+            &qualified_remote_refs_expected_locations,
+            &refspecs,
+        )
+    } else if git_settings.subprocess {
         let git_repo = git_backend.git_repo();
         let git_ctx =
             GitSubprocessContext::from_git_backend(git_backend, &git_settings.executable_path);
@@ -2014,44 +2258,14 @@ fn git2_push_refs(
                 let actual_remote_location = oid_to_maybe_commitid(update.src());
                 let local_location = oid_to_maybe_commitid(update.dst());
 
-                match allow_push(
+                if !negotiate_push(
                     repo.index(),
+                    dst_refname,
                     actual_remote_location.as_ref(),
                     expected_remote_location,
                     local_location.as_ref(),
                 ) {
-                    Ok(PushAllowReason::NormalMatch) => {}
-                    Ok(PushAllowReason::UnexpectedNoop) => {
-                        tracing::info!(
-                            "The push of {dst_refname} is unexpectedly a no-op, the remote branch \
-                             is already at {actual_remote_location:?}. We expected it to be at \
-                             {expected_remote_location:?}. We don't consider this an error.",
-                        );
-                    }
-                    Ok(PushAllowReason::ExceptionalFastforward) => {
-                        // TODO(ilyagr): We could consider printing a user-facing message at
-                        // this point.
-                        tracing::info!(
-                            "We allow the push of {dst_refname} to {local_location:?}, even \
-                             though it is unexpectedly at {actual_remote_location:?} on the \
-                             server rather than the expected {expected_remote_location:?}. The \
-                             desired location is a descendant of the actual location, and the \
-                             actual location is a descendant of the expected location.",
-                        );
-                    }
-                    Err(()) => {
-                        // While we show debug info in the message with `--debug`,
-                        // there's probably no need to show the detailed commit
-                        // locations to the user normally. They should do a `jj git
-                        // fetch`, and the resulting branch conflicts should contain
-                        // all the information they need.
-                        tracing::info!(
-                            "Cannot push {dst_refname} to {local_location:?}; it is at \
-                             unexpectedly at {actual_remote_location:?} on the server as opposed \
-                             to the expected {expected_remote_location:?}",
-                        );
-                        failed_push_negotiations.push(dst_refname.to_string());
-                    }
+                    failed_push_negotiations.push(dst_refname.to_string());
                 }
             }
             if failed_push_negotiations.is_empty() {
@@ -2114,11 +2328,6 @@ fn subprocess_push_refs(
         return Err(GitPushError::NoSuchRemote(remote_name.to_owned()));
     }
 
-    let mut remaining_remote_refs: HashSet<_> = qualified_remote_refs_expected_locations
-        .keys()
-        .copied()
-        .collect();
-
     let refs_to_push: Vec<RefToPush> = refspecs
         .iter()
         .map(|full_refspec| RefToPush::new(full_refspec, qualified_remote_refs_expected_locations))
@@ -2126,7 +2335,71 @@ fn subprocess_push_refs(
 
     let (failed_ref_matches, successful_pushes) =
         git_ctx.spawn_push(remote_name, &refs_to_push, &mut callbacks)?;
+    check_push_outcome(
+        qualified_remote_refs_expected_locations,
+        failed_ref_matches,
+        successful_pushes,
+    )
+}
 
+fn gix_push_refs(
+    repo: &dyn Repo,
+    git_ctx: &GitGixContext,
+    remote_name: &str,
+    qualified_remote_refs_expected_locations: &HashMap<&str, Option<&CommitId>>,
+    refspecs: &[RefSpec],
+) -> Result<(), GitPushError> {
+    if !git_ctx.has_remote(remote_name) {
+        return Err(GitPushError::NoSuchRemote(remote_name.to_owned()));
+    }
+    let refs_to_push: Vec<RefToPush> = refspecs
+        .iter()
+        .map(|full_refspec| RefToPush::new(full_refspec, qualified_remote_refs_expected_locations))
+        .collect();
+    let negotiate = |ref_to_push: &RefToPush,
+                     actual_remote_location: Option<&CommitId>,
+                     local_location: Option<&CommitId>| {
+        negotiate_push(
+            repo.index(),
+            &ref_to_push.refspec.destination,
+            actual_remote_location,
+            ref_to_push.expected_location,
+            local_location,
+        )
+    };
+    let select_commits = |new_heads: &[CommitId], remote_heads: &[CommitId]| {
+        let remote_heads = remote_heads
+            .iter()
+            .filter(|id| repo.index().has_id(id))
+            .cloned()
+            .collect_vec();
+        RevsetExpression::commits(remote_heads)
+            .union(&RevsetExpression::root())
+            .range(&RevsetExpression::commits(new_heads.to_vec()))
+            .evaluate(repo)
+            .map_err(|err| GitGixError::Objects(err.expect_backend_error().into()))?
+            .iter()
+            .try_collect()
+            .map_err(|err| GitGixError::Objects(err.expect_backend_error().into()))
+    };
+    let (failed_ref_matches, successful_pushes) =
+        git_ctx.push(remote_name, &refs_to_push, negotiate, select_commits)?;
+    check_push_outcome(
+        qualified_remote_refs_expected_locations,
+        failed_ref_matches,
+        successful_pushes,
+    )
+}
+
+fn check_push_outcome(
+    qualified_remote_refs_expected_locations: &HashMap<&str, Option<&CommitId>>,
+    failed_ref_matches: Vec<String>,
+    successful_pushes: Vec<String>,
+) -> Result<(), GitPushError> {
+    let mut remaining_remote_refs: HashSet<_> = qualified_remote_refs_expected_locations
+        .keys()
+        .copied()
+        .collect();
     for remote_ref in successful_pushes {
         remaining_remote_refs.remove(remote_ref.as_str());
     }
@@ -2153,6 +2426,59 @@ fn subprocess_push_refs(
     }
 }
 
+/// Checks whether `dst_refname` may be moved from `actual_remote_location` to
+/// `local_location` on the remote, given that we expected it at
+/// `expected_remote_location`.
+fn negotiate_push(
+    index: &dyn Index,
+    dst_refname: &str,
+    actual_remote_location: Option<&CommitId>,
+    expected_remote_location: Option<&CommitId>,
+    local_location: Option<&CommitId>,
+) -> bool {
+    match allow_push(
+        index,
+        actual_remote_location,
+        expected_remote_location,
+        local_location,
+    ) {
+        Ok(PushAllowReason::NormalMatch) => true,
+        Ok(PushAllowReason::UnexpectedNoop) => {
+            tracing::info!(
+                "The push of {dst_refname} is unexpectedly a no-op, the remote branch is already \
+                 at {actual_remote_location:?}. We expected it to be at \
+                 {expected_remote_location:?}. We don't consider this an error.",
+            );
+            true
+        }
+        Ok(PushAllowReason::ExceptionalFastforward) => {
+            // TODO(ilyagr): We could consider printing a user-facing message at
+            // this point.
+            tracing::info!(
+                "We allow the push of {dst_refname} to {local_location:?}, even though it is \
+                 unexpectedly at {actual_remote_location:?} on the server rather than the \
+                 expected {expected_remote_location:?}. The desired location is a descendant of \
+                 the actual location, and the actual location is a descendant of the expected \
+                 location.",
+            );
+            true
+        }
+        Err(()) => {
+            // While we show debug info in the message with `--debug`,
+            // there's probably no need to show the detailed commit
+            // locations to the user normally. They should do a `jj git
+            // fetch`, and the resulting branch conflicts should contain
+            // all the information they need.
+            tracing::info!(
+                "Cannot push {dst_refname} to {local_location:?}; it is at unexpectedly at \
+                 {actual_remote_location:?} on the server as opposed to the expected \
+                 {expected_remote_location:?}",
+            );
+            false
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 enum PushAllowReason {
     NormalMatch,
@@ -2405,3 +2731,115 @@ pub fn parse_gitmodules(
         .collect();
     Ok(ret)
 }
+
+/// Returns true if `name` can be used as the name of a submodule.
+///
+/// Submodule names are used as directory names in the submodule store, so
+/// names that could escape the store are rejected like Git does.
+pub fn is_valid_submodule_name(name: &str) -> bool {
+    !name.is_empty()
+        && !Path::new(name).is_absolute()
+        && name.split(['/', '\\']).all(|component| component != "..")
+}
+
+#[derive(Error, Debug)]
+pub enum GitSubmoduleError {
+    #[error("Commit {} not found in submodule", .0.hex())]
+    CommitNotFound(CommitId),
+    #[error("Submodule has local changes that would be overwritten")]
+    LocalChanges,
+    #[error("Unexpected git error when updating submodule")]
+    InternalGitError(#[from] git2::Error),
+}
+
+/// Opens the repository of the submodule checked out at `path`, or returns
+/// `None` if the submodule isn't initialized.
+fn open_submodule_repo(path: &Path) -> Option<git2::Repository> {
+    // Don't search parent directories, which would find the superproject.
+    git2::Repository::open_ext(path, git2::RepositoryOpenFlags::NO_SEARCH, &[] as &[&str])
+        .ok()
+        .filter(|repo| !repo.is_bare())
+}
+
+/// Returns the commit checked out in the submodule at `path`, or `None` if
+/// the submodule isn't initialized.
+pub fn read_submodule_head(path: &Path) -> Option<CommitId> {
+    let repo = open_submodule_repo(path)?;
+    let oid = repo.head().ok()?.target()?;
+    Some(CommitId::from_bytes(oid.as_bytes()))
+}
+
+/// Initializes the submodule at `path` with its Git directory at `git_dir`.
+///
+/// The "origin" remote of the submodule is set to `url`. Nothing is fetched.
+pub fn init_submodule(path: &Path, git_dir: &Path, url: &str) -> Result<(), GitSubmoduleError> {
+    let mut options = git2::RepositoryInitOptions::new();
+    options.no_dotgit_dir(true).workdir_path(path);
+    let repo = git2::Repository::init_opts(git_dir, &options)?;
+    match repo.find_remote("origin") {
+        Ok(_) => repo.remote_set_url("origin", url)?,
+        Err(_) => {
+            repo.remote("origin", url)?;
+        }
+    }
+    Ok(())
+}
+
+/// Sets the URL of the "origin" remote of the submodule at `path` to `url`.
+///
+/// Returns false if the submodule isn't initialized.
+pub fn sync_submodule_url(path: &Path, url: &str) -> Result<bool, GitSubmoduleError> {
+    let Some(repo) = open_submodule_repo(path) else {
+        return Ok(false);
+    };
+    repo.remote_set_url("origin", url)?;
+    Ok(true)
+}
+
+/// Fetches branches and tags from the "origin" remote of the submodule at
+/// `path`.
+///
+/// Returns false if the submodule isn't initialized.
+pub fn fetch_submodule(
+    path: &Path,
+    callbacks: RemoteCallbacks<'_>,
+) -> Result<bool, GitSubmoduleError> {
+    let Some(repo) = open_submodule_repo(path) else {
+        return Ok(false);
+    };
+    let mut remote = repo.find_remote("origin")?;
+    let mut fetch_options = git2_fetch_options(callbacks, None);
+    fetch_options.download_tags(git2::AutotagOption::All);
+    remote.fetch(
+        &["+refs/heads/*:refs/remotes/origin/*"],
+        Some(&mut fetch_options),
+        None,
+    )?;
+    Ok(true)
+}
+
+/// Checks out `commit_id` as a detached HEAD in the submodule at `path`.
+///
+/// Local changes in the submodule are never overwritten. Returns false if the
+/// submodule isn't initialized.
+pub fn check_out_submodule(path: &Path, commit_id: &CommitId) -> Result<bool, GitSubmoduleError> {
+    let Some(repo) = open_submodule_repo(path) else {
+        return Ok(false);
+    };
+    let oid = git2::Oid::from_bytes(commit_id.as_bytes())?;
+    if repo.head().ok().and_then(|head| head.target()) == Some(oid) {
+        return Ok(true);
+    }
+    let commit = repo
+        .find_commit(oid)
+        .map_err(|_| GitSubmoduleError::CommitNotFound(commit_id.clone()))?;
+    let mut checkout_builder = git2::build::CheckoutBuilder::new();
+    checkout_builder.safe();
+    repo.checkout_tree(commit.as_object(), Some(&mut checkout_builder))
+        .map_err(|err| match err.code() {
+            git2::ErrorCode::Conflict => GitSubmoduleError::LocalChanges,
+            _ => GitSubmoduleError::InternalGitError(err),
+        })?;
+    repo.set_head_detached(oid)?;
+    Ok(true)
+}
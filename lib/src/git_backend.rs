@@ -628,7 +628,7 @@ fn signature_from_git(signature: gix::actor::SignatureRef) -> Signature {
     }
 }
 
-fn signature_to_git(signature: &Signature) -> gix::actor::SignatureRef<'_> {
+pub(crate) fn signature_to_git(signature: &Signature) -> gix::actor::SignatureRef<'_> {
     // git does not support empty names or emails
     let name = if !signature.name.is_empty() {
         &signature.name
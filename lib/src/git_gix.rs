@@ -0,0 +1,1461 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! In-process fetch and push through gitoxide.
+//!
+//! Remotes on the local filesystem (plain paths and `file://` URLs) are
+//! fetched from by copying objects directly between the two object databases.
+//! Remotes reached over `ssh://`, `git://` and `http(s)://` use the gitoxide
+//! network client for fetching, and a minimal `git-receive-pack` client for
+//! pushing. Local remotes are pushed to in-process as well, honoring their
+//! `receive.denyDeletes` and `receive.denyNonFastForwards` settings, unless
+//! `git-receive-pack` is explicitly enabled for them so that their hooks run.
+//! Objects are always transferred as packs generated by gitoxide.
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::collections::VecDeque;
+use std::fs;
+use std::io;
+use std::io::Seek as _;
+use std::io::Write as _;
+use std::num::NonZeroU32;
+use std::panic;
+use std::path::PathBuf;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::sync::mpsc;
+use std::sync::mpsc::RecvTimeoutError;
+use std::thread;
+use std::time::Duration;
+
+use bstr::ByteSlice as _;
+use gix::progress::NestedProgress;
+use gix::protocol::transport::client::ExtendedBufRead as _;
+use gix::protocol::transport::client::MessageKind;
+use gix::protocol::transport::client::WriteMode;
+use gix::refs::transaction::Change;
+use gix::refs::transaction::LogChange;
+use gix::refs::transaction::PreviousValue;
+use gix::refs::transaction::RefEdit;
+use gix::refs::transaction::RefLog;
+use gix::remote::Direction;
+use itertools::Itertools as _;
+use thiserror::Error;
+
+use crate::backend::CommitId;
+use crate::git::Progress;
+use crate::git::RefSpec;
+use crate::git::RefToPush;
+use crate::git::RemoteCallbacks;
+use crate::git_backend::GitBackend;
+use crate::object_id::ObjectId as _;
+use crate::settings::GitSettings;
+
+type BoxedError = Box<dyn std::error::Error + Send + Sync>;
+
+/// How often the progress of a fetch is reported while the transfer runs.
+const PROGRESS_REPORT_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Decides whether a ref may be moved from its actual location on the remote
+/// to the new location.
+type NegotiateFn<'a> = dyn FnMut(&RefToPush, Option<&CommitId>, Option<&CommitId>) -> bool + 'a;
+
+/// Update of a ref on the remote: its name, current location and new location
+type PushCommand<'a> = (&'a String, Option<gix::ObjectId>, Option<gix::ObjectId>);
+
+/// Refs updated on the remote along with their new locations
+type PushedRefs = Vec<(String, Option<gix::ObjectId>)>;
+
+/// Refs the remote refused to update along with the reason
+type RejectedRefs = Vec<(String, String)>;
+
+/// Result of pushing to a remote
+#[derive(Default)]
+struct PushOutcome {
+    /// Refs that failed negotiation
+    failed: Vec<String>,
+    pushed: PushedRefs,
+    rejected: RejectedRefs,
+}
+
+/// Result of copying objects between repositories
+struct CopiedObjects {
+    /// Boundary commits that were copied without their parents
+    shallow: Vec<gix::ObjectId>,
+    /// `.keep` file protecting the new pack until refs point into it
+    keep_path: Option<PathBuf>,
+}
+
+/// Error originating from the in-process Git transport
+#[derive(Error, Debug)]
+pub enum GitGixError {
+    #[error("Could not find repository at '{0}'")]
+    NoSuchRepository(String),
+    #[error("Git remote '{0}' has no URL")]
+    NoUrl(String),
+    #[error(
+        "URL scheme '{scheme}' of '{url}' is not supported without a git subprocess (supported are \
+         local paths, file://, ssh://, git://, http:// and https://)"
+    )]
+    UnsupportedUrl { url: String, scheme: String },
+    #[error("Failed to communicate with the remote")]
+    Transport(#[source] BoxedError),
+    #[error("Failed to transfer Git objects")]
+    Objects(#[source] BoxedError),
+    #[error("Failed to update Git refs")]
+    Refs(#[source] BoxedError),
+    #[error("Remote failed to unpack the pushed objects: {0}")]
+    Unpack(String),
+    #[error("Remote doesn't report the status of pushed refs")]
+    NoReportStatus,
+    #[error(
+        "Remote rejected the update of {}",
+        .0.iter().map(|(name, reason)| format!("{name} ({reason})")).join(", ")
+    )]
+    RefsRejected(Vec<(String, String)>),
+}
+
+impl GitGixError {
+    fn transport(err: impl Into<BoxedError>) -> Self {
+        GitGixError::Transport(err.into())
+    }
+
+    fn objects(err: impl Into<BoxedError>) -> Self {
+        GitGixError::Objects(err.into())
+    }
+
+    fn refs(err: impl Into<BoxedError>) -> Self {
+        GitGixError::Refs(err.into())
+    }
+}
+
+/// How a remote is reached
+enum RemoteLocation {
+    /// Repository on the local filesystem, opened directly
+    Local(Box<gix::Repository>),
+    /// Repository reached through the gitoxide network client
+    Network,
+}
+
+/// Context for fetching and pushing without a Git subprocess
+pub(crate) struct GitGixContext {
+    git_repo: gix::Repository,
+    /// Whether local remotes are pushed to through `git-receive-pack`
+    local_receive_pack: bool,
+}
+
+impl GitGixContext {
+    pub(crate) fn from_git_backend(git_backend: &GitBackend, git_settings: &GitSettings) -> Self {
+        let mut git_repo = git_backend.git_repo();
+        if let Some(ssh_command) = &git_settings.ssh_command {
+            // Only the in-memory configuration is changed.
+            let mut config = git_repo.config_snapshot_mut();
+            if let Err(err) =
+                config.set_value(&gix::config::tree::Core::SSH_COMMAND, ssh_command.as_str())
+            {
+                tracing::warn!(?err, "failed to set core.sshCommand");
+            }
+        }
+        GitGixContext {
+            git_repo,
+            local_receive_pack: git_settings.gix_local_receive_pack,
+        }
+    }
+
+    pub(crate) fn has_remote(&self, remote_name: &str) -> bool {
+        self.git_repo.try_find_remote(remote_name).is_some()
+    }
+
+    fn find_remote(&self, remote_name: &str) -> Result<gix::Remote<'_>, GitGixError> {
+        self.git_repo
+            .find_remote(remote_name)
+            .map_err(GitGixError::transport)
+    }
+
+    /// Fetches the branches matched by `refspecs` into their remote-tracking
+    /// refs, pruning remote-tracking refs of branches deleted on the remote.
+    pub(crate) fn fetch(
+        &self,
+        remote_name: &str,
+        refspecs: &[RefSpec],
+        callbacks: &mut RemoteCallbacks<'_>,
+        depth: Option<NonZeroU32>,
+    ) -> Result<(), GitGixError> {
+        let remote = self.find_remote(remote_name)?;
+        match remote_location(&remote, remote_name, Direction::Fetch)? {
+            RemoteLocation::Local(remote_repo) => {
+                self.fetch_local(&remote_repo, remote_name, refspecs, callbacks, depth)
+            }
+            RemoteLocation::Network => self.fetch_network(remote_name, refspecs, callbacks, depth),
+        }
+    }
+
+    fn fetch_local(
+        &self,
+        remote_repo: &gix::Repository,
+        remote_name: &str,
+        refspecs: &[RefSpec],
+        callbacks: &mut RemoteCallbacks<'_>,
+        depth: Option<NonZeroU32>,
+    ) -> Result<(), GitGixError> {
+        let git_repo = &self.git_repo;
+        let mut remote_branches = HashSet::new();
+        let mut updates = vec![];
+        for (name, id) in list_refs(remote_repo, "refs/heads/")? {
+            if let Some(destination) = refspecs.iter().find_map(|refspec| {
+                map_ref_name(refspec.source.as_deref()?, &refspec.destination, &name)
+            }) {
+                updates.push((destination, id));
+            }
+            remote_branches.insert(name);
+        }
+
+        let tips = updates.iter().map(|(_, id)| *id).collect::<Vec<_>>();
+        let boundary = shallow_boundary(remote_repo, &tips, depth)?;
+        let mut report_progress = |written: usize, pending: usize| {
+            if let Some(progress) = callbacks.progress.as_mut() {
+                progress(&Progress {
+                    bytes_downloaded: None,
+                    overall: written as f32 / (written + pending).max(1) as f32,
+                });
+            }
+        };
+        let copied = copy_objects(
+            remote_repo,
+            git_repo,
+            &tips,
+            &boundary,
+            &mut report_progress,
+        )?;
+        add_shallow_commits(git_repo, copied.shallow)?;
+
+        let mut edits: Vec<_> = updates
+            .into_iter()
+            .map(|(name, id)| update_ref_edit(name, id, PreviousValue::Any, "fetch"))
+            .try_collect()?;
+        let (tag_edits, tags_copied) = self.tags_to_follow(remote_repo, &mut report_progress)?;
+        edits.extend(tag_edits);
+        edits.extend(self.prune_edits(remote_name, refspecs, &remote_branches)?);
+        git_repo.edit_references(edits).map_err(GitGixError::refs)?;
+        remove_keep_files([copied.keep_path, tags_copied.keep_path]);
+        Ok(())
+    }
+
+    /// Copies tags from `remote_repo` whose targets are now available
+    /// locally, as `git fetch` does by default.
+    fn tags_to_follow(
+        &self,
+        remote_repo: &gix::Repository,
+        report_progress: &mut dyn FnMut(usize, usize),
+    ) -> Result<(Vec<RefEdit>, CopiedObjects), GitGixError> {
+        let git_repo = &self.git_repo;
+        let mut tags = vec![];
+        let platform = remote_repo.references().map_err(GitGixError::refs)?;
+        for reference in platform.tags().map_err(GitGixError::refs)? {
+            let mut reference = reference.map_err(GitGixError::refs)?;
+            let Some(target) = reference.target().try_id().map(ToOwned::to_owned) else {
+                continue;
+            };
+            let name = reference.name().as_bstr().to_owned();
+            let exists = git_repo
+                .try_find_reference(name.as_bstr())
+                .map_err(GitGixError::refs)?
+                .is_some();
+            if exists {
+                continue;
+            }
+            let peeled = reference
+                .peel_to_id_in_place()
+                .map_err(GitGixError::refs)?
+                .detach();
+            if !git_repo.has_object(peeled) {
+                continue;
+            }
+            tags.push((name.to_string(), target));
+        }
+        let targets = tags.iter().map(|(_, target)| *target).collect_vec();
+        let copied = copy_objects(
+            remote_repo,
+            git_repo,
+            &targets,
+            &HashSet::new(),
+            report_progress,
+        )?;
+        let edits = tags
+            .into_iter()
+            .map(|(name, target)| {
+                update_ref_edit(name, target, PreviousValue::MustNotExist, "fetch")
+            })
+            .try_collect()?;
+        Ok((edits, copied))
+    }
+
+    /// Deletes remote-tracking refs matched by `refspecs` whose branch no
+    /// longer exists on the remote.
+    fn prune_edits(
+        &self,
+        remote_name: &str,
+        refspecs: &[RefSpec],
+        remote_branches: &HashSet<String>,
+    ) -> Result<Vec<RefEdit>, GitGixError> {
+        let prefix = format!("refs/remotes/{remote_name}/");
+        let edits = list_refs(&self.git_repo, &prefix)?
+            .into_iter()
+            .filter(|(name, _)| {
+                refspecs.iter().any(|refspec| {
+                    refspec.source.as_deref().is_some_and(|source| {
+                        map_ref_name(&refspec.destination, source, name)
+                            .is_some_and(|branch| !remote_branches.contains(&branch))
+                    })
+                })
+            })
+            .map(|(name, _)| delete_ref_edit(name))
+            .try_collect()?;
+        Ok(edits)
+    }
+
+    fn fetch_network(
+        &self,
+        remote_name: &str,
+        refspecs: &[RefSpec],
+        callbacks: &mut RemoteCallbacks<'_>,
+        depth: Option<NonZeroU32>,
+    ) -> Result<(), GitGixError> {
+        let Some(progress_cb) = callbacks.progress.as_mut() else {
+            let remote_branches = receive_pack(
+                &self.git_repo,
+                remote_name,
+                refspecs,
+                depth,
+                gix::progress::Discard,
+            )?;
+            let edits = self.prune_edits(remote_name, refspecs, &remote_branches)?;
+            self.git_repo
+                .edit_references(edits)
+                .map_err(GitGixError::refs)?;
+            return Ok(());
+        };
+
+        // The transfer runs on a separate thread so that progress can be
+        // reported from this one.
+        let root = gix::progress::tree::Root::new();
+        let item = root.add_child("fetch");
+        let git_repo = self.git_repo.clone();
+        let remote_branches = thread::scope(|s| {
+            let (done_tx, done_rx) = mpsc::channel::<()>();
+            let handle = s.spawn(move || {
+                // The sender is dropped when the transfer finishes or panics,
+                // which wakes up the receiver.
+                let _done_tx = done_tx;
+                receive_pack(&git_repo, remote_name, refspecs, depth, item)
+            });
+            let mut tasks = vec![];
+            while let Err(RecvTimeoutError::Timeout) =
+                done_rx.recv_timeout(PROGRESS_REPORT_INTERVAL)
+            {
+                root.sorted_snapshot(&mut tasks);
+                if let Some(progress) = transfer_progress(&tasks) {
+                    progress_cb(&progress);
+                }
+            }
+            handle
+                .join()
+                .unwrap_or_else(|payload| panic::resume_unwind(payload))
+        })?;
+        let edits = self.prune_edits(remote_name, refspecs, &remote_branches)?;
+        self.git_repo
+            .edit_references(edits)
+            .map_err(GitGixError::refs)?;
+        Ok(())
+    }
+
+    /// Queries the remote for the branch its `HEAD` points to.
+    pub(crate) fn get_default_branch(
+        &self,
+        remote_name: &str,
+    ) -> Result<Option<String>, GitGixError> {
+        let mut remote = self.find_remote(remote_name)?;
+        let head_target = match remote_location(&remote, remote_name, Direction::Fetch)? {
+            RemoteLocation::Local(remote_repo) => {
+                match remote_repo.head().map_err(GitGixError::refs)?.kind {
+                    gix::head::Kind::Symbolic(reference) => Some(reference.name.into_inner()),
+                    gix::head::Kind::Unborn(_) | gix::head::Kind::Detached { .. } => None,
+                }
+            }
+            RemoteLocation::Network => {
+                remote
+                    .replace_refspecs(Some("HEAD"), Direction::Fetch)
+                    .map_err(GitGixError::transport)?;
+                let connection = remote
+                    .connect(Direction::Fetch)
+                    .map_err(GitGixError::transport)?;
+                let (ref_map, _) = connection
+                    .ref_map(gix::progress::Discard, Default::default())
+                    .map_err(GitGixError::transport)?;
+                ref_map.remote_refs.into_iter().find_map(|r| match r {
+                    gix::protocol::handshake::Ref::Symbolic {
+                        full_ref_name,
+                        target,
+                        ..
+                    } if full_ref_name == "HEAD" => Some(target),
+                    _ => None,
+                })
+            }
+        };
+        let default_branch = head_target.and_then(|target| {
+            let branch = target.strip_prefix(b"refs/heads/")?.to_str().ok()?;
+            Some(branch.to_owned())
+        });
+        Ok(default_branch)
+    }
+
+    /// Pushes `references` to the remote.
+    ///
+    /// `negotiate` is called with each ref, its current location on the remote
+    /// and its new location, and should return whether the update is allowed.
+    /// If it rejects any ref, nothing is pushed. `select_commits` is called
+    /// with the new heads and the remote heads that exist locally, and
+    /// should return the commits to send.
+    ///
+    /// Returns the refs that failed negotiation and the refs that were updated
+    /// on the remote. If the remote refuses to update some refs, the
+    /// remote-tracking refs of the updated ones are still moved, and an error
+    /// naming the refused refs is returned.
+    pub(crate) fn push(
+        &self,
+        remote_name: &str,
+        references: &[RefToPush],
+        mut negotiate: impl FnMut(&RefToPush, Option<&CommitId>, Option<&CommitId>) -> bool,
+        select_commits: impl FnOnce(&[CommitId], &[CommitId]) -> Result<Vec<CommitId>, GitGixError>,
+    ) -> Result<(Vec<String>, Vec<String>), GitGixError> {
+        let remote = self.find_remote(remote_name)?;
+        let outcome = match remote_location(&remote, remote_name, Direction::Push)? {
+            RemoteLocation::Local(remote_repo) if !self.local_receive_pack => {
+                self.push_local(&remote_repo, references, &mut negotiate)?
+            }
+            // Otherwise, `git-receive-pack` is spawned by the file transport.
+            RemoteLocation::Local(_) | RemoteLocation::Network => {
+                self.push_receive_pack(&remote, references, &mut negotiate, select_commits)?
+            }
+        };
+        let updated_refs = outcome
+            .pushed
+            .iter()
+            .map(|(name, _)| name.clone())
+            .collect();
+        self.update_tracking_refs(remote_name, outcome.pushed)?;
+        if !outcome.rejected.is_empty() {
+            return Err(GitGixError::RefsRejected(outcome.rejected));
+        }
+        Ok((outcome.failed, updated_refs))
+    }
+
+    fn push_local(
+        &self,
+        remote_repo: &gix::Repository,
+        references: &[RefToPush],
+        negotiate: &mut NegotiateFn<'_>,
+    ) -> Result<PushOutcome, GitGixError> {
+        let mut failed = vec![];
+        let mut commands = vec![];
+        for reference in references {
+            let destination = &reference.refspec.destination;
+            let actual = resolve_ref(remote_repo, destination)?;
+            let new = push_source(reference)?;
+            if negotiate(
+                reference,
+                actual.map(to_commit_id).as_ref(),
+                new.map(to_commit_id).as_ref(),
+            ) {
+                commands.push((destination, actual, new));
+            } else {
+                failed.push(destination.clone());
+            }
+        }
+        if !failed.is_empty() {
+            return Ok(PushOutcome {
+                failed,
+                ..Default::default()
+            });
+        }
+
+        let tips = commands
+            .iter()
+            .filter_map(|(_, _, new)| *new)
+            .collect::<Vec<_>>();
+        let copied = copy_objects(
+            &self.git_repo,
+            remote_repo,
+            &tips,
+            &HashSet::new(),
+            &mut |_, _| {},
+        )?;
+
+        // Like git-receive-pack, refuse to move the branch checked out in a
+        // non-bare remote, and apply the remote's restrictions.
+        let checked_out = if remote_repo.is_bare() {
+            None
+        } else {
+            remote_repo.head_name().map_err(GitGixError::refs)?
+        };
+        let config = remote_repo.config_snapshot();
+        let deny_deletes = config.boolean("receive.denyDeletes").unwrap_or(false);
+        let deny_non_fast_forwards = config
+            .boolean("receive.denyNonFastForwards")
+            .unwrap_or(false);
+        let mut edits = vec![];
+        let mut pushed = vec![];
+        let mut rejected = vec![];
+        for (name, actual, new) in commands {
+            if actual == new {
+                pushed.push((name.clone(), new));
+                continue;
+            }
+            let reason = if checked_out
+                .as_ref()
+                .is_some_and(|head| head.as_bstr() == name.as_bytes().as_bstr())
+            {
+                Some("branch is currently checked out")
+            } else {
+                match (actual, new) {
+                    (Some(_), None) if deny_deletes => Some("deletion prohibited"),
+                    (Some(old), Some(new))
+                        if deny_non_fast_forwards && !is_ancestor(remote_repo, old, new)? =>
+                    {
+                        Some("non-fast-forward")
+                    }
+                    _ => None,
+                }
+            };
+            if let Some(reason) = reason {
+                rejected.push((name.clone(), reason.to_owned()));
+                continue;
+            }
+            let expected = match actual {
+                Some(id) => PreviousValue::MustExistAndMatch(gix::refs::Target::Object(id)),
+                None => PreviousValue::MustNotExist,
+            };
+            edits.push(match new {
+                Some(id) => update_ref_edit(name.clone(), id, expected, "push")?,
+                None => RefEdit {
+                    change: Change::Delete {
+                        expected,
+                        log: RefLog::AndReference,
+                    },
+                    name: name.as_str().try_into().map_err(GitGixError::refs)?,
+                    deref: false,
+                },
+            });
+            pushed.push((name.clone(), new));
+        }
+        remote_repo
+            .edit_references(edits)
+            .map_err(GitGixError::refs)?;
+        remove_keep_files([copied.keep_path]);
+        Ok(PushOutcome {
+            pushed,
+            rejected,
+            ..Default::default()
+        })
+    }
+
+    fn push_receive_pack(
+        &self,
+        remote: &gix::Remote<'_>,
+        references: &[RefToPush],
+        negotiate: &mut NegotiateFn<'_>,
+        select_commits: impl FnOnce(&[CommitId], &[CommitId]) -> Result<Vec<CommitId>, GitGixError>,
+    ) -> Result<PushOutcome, GitGixError> {
+        let git_repo = &self.git_repo;
+        let mut connection = remote
+            .connect(Direction::Push)
+            .map_err(GitGixError::transport)?;
+        let url = connection.transport_mut().to_url().into_owned().to_string();
+        let url = gix::url::parse(url.as_bytes().as_bstr()).map_err(GitGixError::transport)?;
+        let authenticate = connection
+            .configured_credentials(url)
+            .map_err(GitGixError::transport)?;
+        let handshake = gix::protocol::handshake(
+            connection.transport_mut(),
+            gix::protocol::transport::Service::ReceivePack,
+            authenticate,
+            vec![],
+            &mut gix::progress::Discard,
+        )
+        .map_err(GitGixError::transport)?;
+        let remote_refs: HashMap<_, _> = handshake
+            .refs
+            .iter()
+            .flatten()
+            .map(|r| match r {
+                gix::protocol::handshake::Ref::Peeled {
+                    full_ref_name, tag, ..
+                } => (full_ref_name.clone(), *tag),
+                gix::protocol::handshake::Ref::Direct {
+                    full_ref_name,
+                    object,
+                } => (full_ref_name.clone(), *object),
+                gix::protocol::handshake::Ref::Symbolic {
+                    full_ref_name,
+                    tag,
+                    object,
+                    ..
+                } => (full_ref_name.clone(), tag.unwrap_or(*object)),
+                gix::protocol::handshake::Ref::Unborn { full_ref_name, .. } => (
+                    full_ref_name.clone(),
+                    gix::ObjectId::null(gix::hash::Kind::Sha1),
+                ),
+            })
+            .filter(|(_, id)| !id.is_null())
+            .collect();
+
+        let mut failed = vec![];
+        let mut pushed = vec![];
+        let mut commands = vec![];
+        for reference in references {
+            let destination = &reference.refspec.destination;
+            let actual = remote_refs.get(destination.as_bytes().as_bstr()).copied();
+            let new = push_source(reference)?;
+            if !negotiate(
+                reference,
+                actual.map(to_commit_id).as_ref(),
+                new.map(to_commit_id).as_ref(),
+            ) {
+                failed.push(destination.clone());
+            } else if actual == new {
+                pushed.push((destination.clone(), new));
+            } else {
+                commands.push((destination, actual, new));
+            }
+        }
+        if !failed.is_empty() {
+            return Ok(PushOutcome {
+                failed,
+                ..Default::default()
+            });
+        }
+        if commands.is_empty() {
+            return Ok(PushOutcome {
+                pushed,
+                ..Default::default()
+            });
+        }
+
+        let new_heads = commands
+            .iter()
+            .filter_map(|(_, _, new)| new.map(to_commit_id))
+            .collect::<Vec<_>>();
+        let remote_heads = remote_refs
+            .values()
+            .filter(|id| git_repo.has_object(id))
+            .map(|id| to_commit_id(*id))
+            .collect::<Vec<_>>();
+        let objects = if new_heads.is_empty() {
+            vec![]
+        } else {
+            let commits = select_commits(&new_heads, &remote_heads)?;
+            objects_to_send(git_repo, &commits, &remote_heads)?
+        };
+
+        let capabilities = &handshake.capabilities;
+        let (commands, mut rejected) =
+            check_push_capabilities(|name| capabilities.contains(name), commands)?;
+        if commands.is_empty() {
+            return Ok(PushOutcome {
+                pushed,
+                rejected,
+                ..Default::default()
+            });
+        }
+        let requested_capabilities = ["report-status", "delete-refs"]
+            .into_iter()
+            .filter(|name| capabilities.contains(name))
+            .collect::<Vec<_>>();
+        let null_id = gix::ObjectId::null(gix::hash::Kind::Sha1);
+        let transport = connection.transport_mut();
+        let mut writer = transport
+            .request(WriteMode::Binary, MessageKind::Flush, false)
+            .map_err(GitGixError::transport)?;
+        for (i, (name, old, new)) in commands.iter().enumerate() {
+            let mut line = format!(
+                "{} {} {name}",
+                old.unwrap_or(null_id),
+                new.unwrap_or(null_id)
+            );
+            if i == 0 {
+                line.push('\0');
+                line.push_str(&requested_capabilities.join(" "));
+            }
+            line.push('\n');
+            writer
+                .write_all(line.as_bytes())
+                .map_err(GitGixError::transport)?;
+        }
+        writer
+            .write_message(MessageKind::Flush)
+            .map_err(GitGixError::transport)?;
+        let (mut raw_writer, mut reader) = writer.into_parts();
+        if commands.iter().any(|(_, _, new)| new.is_some()) {
+            write_pack(git_repo, objects, &mut raw_writer)?;
+        }
+        raw_writer.flush().map_err(GitGixError::transport)?;
+        drop(raw_writer);
+
+        reader.reset(gix::protocol::transport::Protocol::V1);
+        let mut report = vec![];
+        let mut line = String::new();
+        loop {
+            line.clear();
+            if reader
+                .readline_str(&mut line)
+                .map_err(GitGixError::transport)?
+                == 0
+            {
+                break;
+            }
+            report.push(line.trim_end().to_owned());
+        }
+        let (report_pushed, report_rejected) = parse_push_report(&report, &commands)?;
+        pushed.extend(report_pushed);
+        rejected.extend(report_rejected);
+        Ok(PushOutcome {
+            pushed,
+            rejected,
+            ..Default::default()
+        })
+    }
+
+    /// Updates remote-tracking refs of the branches pushed to `remote_name`.
+    fn update_tracking_refs(
+        &self,
+        remote_name: &str,
+        pushed: PushedRefs,
+    ) -> Result<(), GitGixError> {
+        let mut edits = vec![];
+        for (name, new) in pushed {
+            let Some(branch) = name.strip_prefix("refs/heads/") else {
+                continue;
+            };
+            let tracking_name = format!("refs/remotes/{remote_name}/{branch}");
+            match new {
+                Some(id) => {
+                    edits.push(update_ref_edit(
+                        tracking_name,
+                        id,
+                        PreviousValue::Any,
+                        "push",
+                    )?);
+                }
+                None if resolve_ref(&self.git_repo, &tracking_name)?.is_some() => {
+                    edits.push(delete_ref_edit(tracking_name)?);
+                }
+                None => {}
+            }
+        }
+        self.git_repo
+            .edit_references(edits)
+            .map_err(GitGixError::refs)?;
+        Ok(())
+    }
+}
+
+fn remote_location(
+    remote: &gix::Remote<'_>,
+    remote_name: &str,
+    direction: Direction,
+) -> Result<RemoteLocation, GitGixError> {
+    let url = remote
+        .url(direction)
+        .ok_or_else(|| GitGixError::NoUrl(remote_name.to_owned()))?;
+    match &url.scheme {
+        gix::url::Scheme::File => {
+            let path = gix::path::from_bstr(url.path.as_bstr());
+            match gix::open(path.as_ref()) {
+                Ok(repo) => Ok(RemoteLocation::Local(Box::new(repo))),
+                Err(gix::open::Error::NotARepository { .. }) => Err(GitGixError::NoSuchRepository(
+                    path.to_string_lossy().into_owned(),
+                )),
+                Err(err) => Err(GitGixError::transport(err)),
+            }
+        }
+        gix::url::Scheme::Ssh
+        | gix::url::Scheme::Git
+        | gix::url::Scheme::Http
+        | gix::url::Scheme::Https => Ok(RemoteLocation::Network),
+        scheme => Err(GitGixError::UnsupportedUrl {
+            url: url.to_bstring().to_string(),
+            scheme: scheme.as_str().to_owned(),
+        }),
+    }
+}
+
+/// Checks that the remote supports the `commands` to push.
+///
+/// Without a status report, there would be no way to tell which refs were
+/// updated, so remotes that don't support it are refused. Deletions are
+/// returned as rejected if the remote doesn't allow them, and the other
+/// commands are returned to be sent.
+fn check_push_capabilities<'a>(
+    has_capability: impl Fn(&str) -> bool,
+    commands: Vec<PushCommand<'a>>,
+) -> Result<(Vec<PushCommand<'a>>, RejectedRefs), GitGixError> {
+    if !has_capability("report-status") {
+        return Err(GitGixError::NoReportStatus);
+    }
+    if has_capability("delete-refs") {
+        return Ok((commands, vec![]));
+    }
+    let (commands, deletions): (Vec<_>, Vec<_>) =
+        commands.into_iter().partition(|(_, _, new)| new.is_some());
+    let rejected = deletions
+        .into_iter()
+        .map(|(name, _, _)| {
+            (
+                name.clone(),
+                "remote doesn't allow deleting refs".to_owned(),
+            )
+        })
+        .collect();
+    Ok((commands, rejected))
+}
+
+/// Parses the status report of a push, returning the updated refs along with
+/// their new locations and the refused refs along with the reason.
+///
+/// Refs the report doesn't mention are considered refused.
+fn parse_push_report(
+    report: &[String],
+    commands: &[PushCommand<'_>],
+) -> Result<(PushedRefs, RejectedRefs), GitGixError> {
+    let mut statuses = HashMap::new();
+    let mut unpacked = false;
+    for line in report {
+        if let Some(status) = line.strip_prefix("unpack ") {
+            if status != "ok" {
+                return Err(GitGixError::Unpack(status.to_owned()));
+            }
+            unpacked = true;
+        } else if let Some(name) = line.strip_prefix("ok ") {
+            statuses.insert(name, Ok(()));
+        } else if let Some(rest) = line.strip_prefix("ng ") {
+            let (name, reason) = rest.split_once(' ').unwrap_or((rest, "rejected"));
+            statuses.insert(name, Err(reason));
+        }
+    }
+    if !unpacked {
+        return Err(GitGixError::Unpack("no status reported".to_owned()));
+    }
+    let mut pushed = vec![];
+    let mut rejected = vec![];
+    for (name, _, new) in commands {
+        match statuses.get(name.as_str()) {
+            Some(Ok(())) => pushed.push(((*name).clone(), *new)),
+            Some(Err(reason)) => rejected.push(((*name).clone(), (*reason).to_owned())),
+            None => rejected.push(((*name).clone(), "no status reported".to_owned())),
+        }
+    }
+    Ok((pushed, rejected))
+}
+
+/// Fetches `refspecs` from the remote over the network, returning the
+/// branches that exist on the remote.
+fn receive_pack<P>(
+    git_repo: &gix::Repository,
+    remote_name: &str,
+    refspecs: &[RefSpec],
+    depth: Option<NonZeroU32>,
+    mut progress: P,
+) -> Result<HashSet<String>, GitGixError>
+where
+    P: NestedProgress,
+    P::SubProgress: 'static,
+{
+    let mut remote = git_repo
+        .find_remote(remote_name)
+        .map_err(GitGixError::transport)?;
+    remote
+        .replace_refspecs(
+            refspecs
+                .iter()
+                .map(|refspec| bstr::BString::from(refspec.to_git_format())),
+            Direction::Fetch,
+        )
+        .map_err(GitGixError::transport)?;
+    let connection = remote
+        .connect(Direction::Fetch)
+        .map_err(GitGixError::transport)?;
+    let prepare = connection
+        .prepare_fetch(progress.add_child("list refs"), Default::default())
+        .map_err(GitGixError::transport)?;
+    let remote_branches = prepare
+        .ref_map()
+        .remote_refs
+        .iter()
+        .filter_map(|r| {
+            let (name, _, _) = r.unpack();
+            name.starts_with(b"refs/heads/")
+                .then(|| name.to_str().ok().map(ToOwned::to_owned))
+                .flatten()
+        })
+        .collect();
+    // Nothing matched, so there's nothing to receive.
+    if prepare.ref_map().mappings.is_empty() {
+        return Ok(remote_branches);
+    }
+    let mut prepare = prepare;
+    if let Some(depth) = depth {
+        prepare = prepare.with_shallow(gix::remote::fetch::Shallow::DepthAtRemote(depth));
+    }
+    prepare
+        .receive(progress, &AtomicBool::new(false))
+        .map_err(GitGixError::transport)?;
+    Ok(remote_branches)
+}
+
+/// Computes the transfer progress from a snapshot of the progress tree.
+fn transfer_progress(
+    tasks: &[(gix::progress::prodash::progress::Key, gix::progress::Task)],
+) -> Option<Progress> {
+    let find = |id: &gix::progress::Id| {
+        tasks
+            .iter()
+            .find_map(|(_, task)| (task.id == *id).then_some(task.progress.as_ref()?))
+    };
+    let indexed = find(b"IWIO")?;
+    let resolved = find(b"IWRO");
+    let step = |value: &gix::progress::Value| value.step.load(Ordering::Relaxed);
+    let total = indexed.done_at? + resolved.and_then(|value| value.done_at).unwrap_or(0);
+    let done = step(indexed) + resolved.map_or(0, step);
+    let bytes_downloaded = (step(indexed) < indexed.done_at?)
+        .then(|| find(b"BWRB").map(|value| step(value) as u64))
+        .flatten();
+    Some(Progress {
+        bytes_downloaded,
+        overall: done as f32 / total.max(1) as f32,
+    })
+}
+
+/// Lists the refs under `prefix` which resolve to an object.
+fn list_refs(
+    git_repo: &gix::Repository,
+    prefix: &str,
+) -> Result<Vec<(String, gix::ObjectId)>, GitGixError> {
+    let platform = git_repo.references().map_err(GitGixError::refs)?;
+    let mut refs = vec![];
+    for reference in platform.prefixed(prefix).map_err(GitGixError::refs)? {
+        let mut reference = reference.map_err(GitGixError::refs)?;
+        if reference.target().try_id().is_none() {
+            // Skip symbolic refs such as refs/remotes/origin/HEAD
+            continue;
+        }
+        let Ok(name) = reference.name().as_bstr().to_str().map(ToOwned::to_owned) else {
+            continue;
+        };
+        let id = reference
+            .peel_to_id_in_place()
+            .map_err(GitGixError::refs)?
+            .detach();
+        refs.push((name, id));
+    }
+    Ok(refs)
+}
+
+fn resolve_ref(
+    git_repo: &gix::Repository,
+    name: &str,
+) -> Result<Option<gix::ObjectId>, GitGixError> {
+    let Some(mut reference) = git_repo
+        .try_find_reference(name)
+        .map_err(GitGixError::refs)?
+    else {
+        return Ok(None);
+    };
+    let id = reference
+        .peel_to_id_in_place()
+        .map_err(GitGixError::refs)?
+        .detach();
+    Ok(Some(id))
+}
+
+/// Maps `name` through a refspec side `from` (which may contain one `*`) to
+/// the other side `to`.
+fn map_ref_name(from: &str, to: &str, name: &str) -> Option<String> {
+    match from.split_once('*') {
+        Some((prefix, suffix)) => {
+            let matched = name
+                .strip_prefix(prefix)?
+                .strip_suffix(suffix)
+                .filter(|matched| !matched.is_empty())?;
+            Some(to.replacen('*', matched, 1))
+        }
+        None => (name == from).then(|| to.to_owned()),
+    }
+}
+
+fn update_ref_edit(
+    name: String,
+    id: gix::ObjectId,
+    expected: PreviousValue,
+    message: &str,
+) -> Result<RefEdit, GitGixError> {
+    Ok(RefEdit {
+        change: Change::Update {
+            log: LogChange {
+                message: format!("{message}: {name}").into(),
+                ..Default::default()
+            },
+            expected,
+            new: gix::refs::Target::Object(id),
+        },
+        name: name.try_into().map_err(GitGixError::refs)?,
+        deref: false,
+    })
+}
+
+fn delete_ref_edit(name: String) -> Result<RefEdit, GitGixError> {
+    Ok(RefEdit {
+        change: Change::Delete {
+            expected: PreviousValue::Any,
+            log: RefLog::AndReference,
+        },
+        name: name.try_into().map_err(GitGixError::refs)?,
+        deref: false,
+    })
+}
+
+/// Checks whether `ancestor` is reachable from `descendant` in `git_repo`.
+fn is_ancestor(
+    git_repo: &gix::Repository,
+    ancestor: gix::ObjectId,
+    descendant: gix::ObjectId,
+) -> Result<bool, GitGixError> {
+    let walk = git_repo
+        .rev_walk([descendant])
+        .all()
+        .map_err(GitGixError::objects)?;
+    for info in walk {
+        if info.map_err(GitGixError::objects)?.id == ancestor {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+fn push_source(reference: &RefToPush) -> Result<Option<gix::ObjectId>, GitGixError> {
+    reference
+        .refspec
+        .source
+        .as_ref()
+        .map(|hex| gix::ObjectId::from_hex(hex.as_bytes()).map_err(GitGixError::objects))
+        .transpose()
+}
+
+fn to_commit_id(id: gix::ObjectId) -> CommitId {
+    CommitId::from_bytes(id.as_bytes())
+}
+
+fn to_object_id(id: &CommitId) -> gix::ObjectId {
+    gix::ObjectId::from_bytes_or_panic(id.as_bytes())
+}
+
+/// Finds the commits at which a fetch of `tips` from `git_repo` stops.
+///
+/// These are the commits `depth` generations away from the tips, and any
+/// commit `git_repo` itself only has shallowly.
+fn shallow_boundary(
+    git_repo: &gix::Repository,
+    tips: &[gix::ObjectId],
+    depth: Option<NonZeroU32>,
+) -> Result<HashSet<gix::ObjectId>, GitGixError> {
+    let mut boundary: HashSet<_> = git_repo
+        .shallow_commits()
+        .map_err(GitGixError::objects)?
+        .map(|commits| commits.iter().copied().collect())
+        .unwrap_or_default();
+    let Some(depth) = depth else {
+        return Ok(boundary);
+    };
+    let mut visited: HashSet<_> = tips.iter().copied().collect();
+    let mut queue: VecDeque<_> = tips.iter().map(|id| (*id, 1)).collect();
+    while let Some((id, generation)) = queue.pop_front() {
+        if boundary.contains(&id) {
+            continue;
+        }
+        if generation >= depth.get() {
+            boundary.insert(id);
+            continue;
+        }
+        let commit = git_repo.find_commit(id).map_err(GitGixError::objects)?;
+        for parent_id in commit.parent_ids() {
+            if visited.insert(parent_id.detach()) {
+                queue.push_back((parent_id.detach(), generation + 1));
+            }
+        }
+    }
+    Ok(boundary)
+}
+
+/// Copies the objects reachable from `tips` that `dest` doesn't have yet from
+/// `source` to `dest`.
+///
+/// The parents of commits in `boundary` aren't followed. The objects are
+/// written to `dest` as a single pack, which only becomes visible once it's
+/// complete, so `dest` stays consistent if the copy is interrupted.
+fn copy_objects(
+    source: &gix::Repository,
+    dest: &gix::Repository,
+    tips: &[gix::ObjectId],
+    boundary: &HashSet<gix::ObjectId>,
+    report_progress: &mut dyn FnMut(usize, usize),
+) -> Result<CopiedObjects, GitGixError> {
+    let mut stack = tips.to_vec();
+    let mut seen = HashSet::new();
+    let mut objects = vec![];
+    let mut shallow = vec![];
+    while let Some(id) = stack.pop() {
+        if !seen.insert(id) || dest.has_object(id) {
+            continue;
+        }
+        let object = source.find_object(id).map_err(GitGixError::objects)?;
+        objects.push(id);
+        if objects.len() % 256 == 0 {
+            report_progress(objects.len(), stack.len());
+        }
+        match object.kind {
+            gix::object::Kind::Commit => {
+                let commit = object.into_commit();
+                let decoded = commit.decode().map_err(GitGixError::objects)?;
+                stack.push(decoded.tree());
+                if boundary.contains(&id) {
+                    shallow.push(id);
+                } else {
+                    stack.extend(decoded.parents());
+                }
+            }
+            gix::object::Kind::Tree => {
+                for entry in gix::objs::TreeRefIter::from_bytes(&object.data) {
+                    let entry = entry.map_err(GitGixError::objects)?;
+                    // Submodule commits live in another repository.
+                    if !entry.mode.is_commit() {
+                        stack.push(entry.oid.to_owned());
+                    }
+                }
+            }
+            gix::object::Kind::Tag => {
+                let target = gix::objs::TagRefIter::from_bytes(&object.data)
+                    .target_id()
+                    .map_err(GitGixError::objects)?;
+                stack.push(target);
+            }
+            gix::object::Kind::Blob => {}
+        }
+    }
+    let num_objects = objects.len();
+    if objects.is_empty() {
+        return Ok(CopiedObjects {
+            shallow,
+            keep_path: None,
+        });
+    }
+
+    let pack_dir = dest.objects.store_ref().path().join("pack");
+    fs::create_dir_all(&pack_dir).map_err(GitGixError::objects)?;
+    let mut pack_file = tempfile::tempfile_in(&pack_dir).map_err(GitGixError::objects)?;
+    let mut writer = io::BufWriter::new(&mut pack_file);
+    write_pack(source, objects, &mut writer)?;
+    writer.flush().map_err(GitGixError::objects)?;
+    drop(writer);
+    pack_file.rewind().map_err(GitGixError::objects)?;
+    let outcome = gix_pack::Bundle::write_to_directory(
+        &mut io::BufReader::new(pack_file),
+        Some(&pack_dir),
+        &mut gix::progress::Discard,
+        &AtomicBool::new(false),
+        None::<gix::objs::find::Never>,
+        gix_pack::bundle::write::Options {
+            object_hash: gix::hash::Kind::Sha1,
+            ..Default::default()
+        },
+    )
+    .map_err(GitGixError::objects)?;
+    report_progress(num_objects, 0);
+    Ok(CopiedObjects {
+        shallow,
+        keep_path: outcome.keep_path,
+    })
+}
+
+/// Removes the `.keep` files of packs written by [`copy_objects()`] once refs
+/// point into them.
+fn remove_keep_files(keep_paths: impl IntoIterator<Item = Option<PathBuf>>) {
+    for path in keep_paths.into_iter().flatten() {
+        if let Err(err) = fs::remove_file(&path) {
+            tracing::warn!(?err, ?path, "failed to remove pack keep file");
+        }
+    }
+}
+
+/// Records `commits` as shallow in `git_repo`.
+fn add_shallow_commits(
+    git_repo: &gix::Repository,
+    commits: Vec<gix::ObjectId>,
+) -> Result<(), GitGixError> {
+    if commits.is_empty() {
+        return Ok(());
+    }
+    let mut shallow = git_repo
+        .shallow_commits()
+        .map_err(GitGixError::objects)?
+        .map(|existing| existing.to_vec())
+        .unwrap_or_default();
+    shallow.extend(commits);
+    shallow.sort();
+    shallow.dedup();
+    let contents: String = shallow.iter().map(|id| format!("{id}\n")).collect();
+    std::fs::write(git_repo.shallow_file(), contents).map_err(GitGixError::objects)
+}
+
+/// Lists the objects to send along with `commits`, leaving out the trees
+/// and blobs reachable from `remote_heads`.
+fn objects_to_send(
+    git_repo: &gix::Repository,
+    commits: &[CommitId],
+    remote_heads: &[CommitId],
+) -> Result<Vec<gix::ObjectId>, GitGixError> {
+    let mut seen = HashSet::new();
+    let mut stack = vec![];
+    for head in remote_heads {
+        let Ok(commit) = git_repo.find_commit(to_object_id(head)) else {
+            continue;
+        };
+        stack.push(commit.tree_id().map_err(GitGixError::objects)?.detach());
+    }
+    collect_tree_objects(git_repo, &mut stack, &mut seen, &mut |_| {})?;
+
+    let mut objects = vec![];
+    for commit_id in commits {
+        let id = to_object_id(commit_id);
+        objects.push(id);
+        let commit = git_repo.find_commit(id).map_err(GitGixError::objects)?;
+        stack.push(commit.tree_id().map_err(GitGixError::objects)?.detach());
+        collect_tree_objects(git_repo, &mut stack, &mut seen, &mut |id| objects.push(id))?;
+    }
+    Ok(objects)
+}
+
+fn collect_tree_objects(
+    git_repo: &gix::Repository,
+    stack: &mut Vec<gix::ObjectId>,
+    seen: &mut HashSet<gix::ObjectId>,
+    on_new: &mut dyn FnMut(gix::ObjectId),
+) -> Result<(), GitGixError> {
+    while let Some(id) = stack.pop() {
+        if !seen.insert(id) {
+            continue;
+        }
+        on_new(id);
+        let object = git_repo.find_object(id).map_err(GitGixError::objects)?;
+        if object.kind != gix::object::Kind::Tree {
+            continue;
+        }
+        for entry in gix::objs::TreeRefIter::from_bytes(&object.data) {
+            let entry = entry.map_err(GitGixError::objects)?;
+            if !entry.mode.is_commit() {
+                stack.push(entry.oid.to_owned());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Writes `objects` as a version 2 pack, reusing the deltas of objects that
+/// are already packed in `git_repo`.
+fn write_pack(
+    git_repo: &gix::Repository,
+    objects: Vec<gix::ObjectId>,
+    out: &mut dyn io::Write,
+) -> Result<(), GitGixError> {
+    let db = git_repo
+        .objects
+        .clone()
+        .into_arc()
+        .map_err(GitGixError::objects)?
+        .into_inner();
+    let (counts, _) = gix_pack::data::output::count::objects(
+        db.clone(),
+        Box::new(objects.into_iter().map(Ok)),
+        &gix::progress::Discard,
+        &AtomicBool::new(false),
+        Default::default(),
+    )
+    .map_err(GitGixError::objects)?;
+    let num_entries = u32::try_from(counts.len()).map_err(GitGixError::objects)?;
+    let entries = gix_pack::data::output::entry::iter_from_counts(
+        counts,
+        db,
+        Box::new(gix::progress::Discard),
+        Default::default(),
+    );
+    let pack = gix_pack::data::output::bytes::FromEntriesIter::new(
+        gix::features::parallel::InOrderIter::from(entries),
+        out,
+        num_entries,
+        gix_pack::data::Version::V2,
+        gix::hash::Kind::Sha1,
+    );
+    for written in pack {
+        written.map_err(GitGixError::transport)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use assert_matches::assert_matches;
+
+    use super::*;
+
+    #[test]
+    fn test_map_ref_name() {
+        assert_eq!(
+            map_ref_name("refs/heads/*", "refs/remotes/origin/*", "refs/heads/a/b"),
+            Some("refs/remotes/origin/a/b".to_owned())
+        );
+        assert_eq!(
+            map_ref_name(
+                "refs/heads/*-x",
+                "refs/remotes/origin/*-x",
+                "refs/heads/a-x"
+            ),
+            Some("refs/remotes/origin/a-x".to_owned())
+        );
+        assert_eq!(
+            map_ref_name(
+                "refs/heads/*-x",
+                "refs/remotes/origin/*-x",
+                "refs/heads/a-y"
+            ),
+            None
+        );
+        assert_eq!(
+            map_ref_name(
+                "refs/heads/main",
+                "refs/remotes/origin/main",
+                "refs/heads/main"
+            ),
+            Some("refs/remotes/origin/main".to_owned())
+        );
+        assert_eq!(
+            map_ref_name(
+                "refs/heads/main",
+                "refs/remotes/origin/main",
+                "refs/heads/mainx"
+            ),
+            None
+        );
+    }
+
+    fn oid(hex: &str) -> gix::ObjectId {
+        gix::ObjectId::from_hex(hex.repeat(40 / hex.len()).as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn test_check_push_capabilities() {
+        let main = "refs/heads/main".to_owned();
+        let old = "refs/heads/old".to_owned();
+        let commands = vec![
+            (&main, Some(oid("1")), Some(oid("2"))),
+            (&old, Some(oid("3")), None),
+        ];
+
+        // Without a status report, nothing can be pushed
+        assert_matches!(
+            check_push_capabilities(|name| name == "delete-refs", commands.clone()),
+            Err(GitGixError::NoReportStatus)
+        );
+
+        // Deletions are sent if the remote allows them
+        let (sent, rejected) = check_push_capabilities(
+            |name| ["report-status", "delete-refs"].contains(&name),
+            commands.clone(),
+        )
+        .unwrap();
+        assert_eq!(sent, commands);
+        assert_eq!(rejected, vec![]);
+
+        // Otherwise, they're rejected up front
+        let (sent, rejected) =
+            check_push_capabilities(|name| name == "report-status", commands.clone()).unwrap();
+        assert_eq!(sent, commands[..1]);
+        assert_eq!(
+            rejected,
+            vec![(old.clone(), "remote doesn't allow deleting refs".to_owned())]
+        );
+    }
+
+    #[test]
+    fn test_parse_push_report() {
+        let main = "refs/heads/main".to_owned();
+        let feature = "refs/heads/feature".to_owned();
+        let old = "refs/heads/old".to_owned();
+        let commands = vec![
+            (&main, Some(oid("1")), Some(oid("2"))),
+            (&feature, None, Some(oid("4"))),
+            (&old, Some(oid("3")), None),
+        ];
+        let report = |lines: &[&str]| lines.iter().map(|line| line.to_string()).collect_vec();
+
+        let (pushed, rejected) = parse_push_report(
+            &report(&[
+                "unpack ok",
+                "ok refs/heads/main",
+                "ng refs/heads/feature pre-receive hook declined",
+                "ok refs/heads/old",
+            ]),
+            &commands,
+        )
+        .unwrap();
+        assert_eq!(
+            pushed,
+            vec![(main.clone(), Some(oid("2"))), (old.clone(), None)]
+        );
+        assert_eq!(
+            rejected,
+            vec![(feature.clone(), "pre-receive hook declined".to_owned())]
+        );
+
+        // Refs missing from the report weren't updated
+        let (pushed, rejected) =
+            parse_push_report(&report(&["unpack ok", "ok refs/heads/main"]), &commands).unwrap();
+        assert_eq!(pushed, vec![(main.clone(), Some(oid("2")))]);
+        assert_eq!(
+            rejected,
+            vec![
+                (feature.clone(), "no status reported".to_owned()),
+                (old.clone(), "no status reported".to_owned()),
+            ]
+        );
+
+        // Failure to unpack fails the whole push
+        assert_matches!(
+            parse_push_report(&report(&["unpack index-pack abnormal exit"]), &commands),
+            Err(GitGixError::Unpack(status)) if status == "index-pack abnormal exit"
+        );
+        assert_matches!(
+            parse_push_report(&report(&[]), &commands),
+            Err(GitGixError::Unpack(_))
+        );
+    }
+}
@@ -0,0 +1,529 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Git LFS pointer files and the local LFS object store.
+//!
+//! Files with the `filter=lfs` attribute are stored in the repository as small
+//! pointer files, and their contents are stored separately in
+//! `<git dir>/lfs/objects`. See
+//! <https://github.com/git-lfs/git-lfs/blob/main/docs/spec.md>.
+
+#![allow(missing_docs)]
+
+use std::fmt::Debug;
+use std::fs;
+use std::fs::File;
+use std::io;
+use std::io::Read;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use sha2::Digest as _;
+use sha2::Sha256;
+use tempfile::NamedTempFile;
+use thiserror::Error;
+
+use crate::file_util::persist_content_addressed_temp_file;
+use crate::file_util::IoResultExt as _;
+use crate::file_util::PathError;
+
+const POINTER_VERSION: &str = "https://git-lfs.github.com/spec/v1";
+/// Pointer files are never larger than this.
+const MAX_POINTER_SIZE: usize = 1024;
+
+/// Reference to an LFS object, which is stored in the repository in place of
+/// the file contents.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct LfsPointer {
+    /// Hex-encoded SHA-256 hash of the contents. Always 64 lowercase hex
+    /// digits.
+    oid: String,
+    size: u64,
+}
+
+impl LfsPointer {
+    /// Creates pointer to the `content`.
+    pub fn for_content(content: &[u8]) -> Self {
+        LfsPointer {
+            oid: hex::encode(Sha256::digest(content)),
+            size: content.len() as u64,
+        }
+    }
+
+    /// Parses pointer file. Returns `None` if the `content` isn't a pointer,
+    /// or an error if it's a pointer with invalid `oid` or `size`.
+    pub fn parse(content: &[u8]) -> Result<Option<Self>, GitLfsError> {
+        if content.len() > MAX_POINTER_SIZE {
+            return Ok(None);
+        }
+        let Some(content) = std::str::from_utf8(content)
+            .ok()
+            .and_then(|content| content.strip_suffix('\n'))
+        else {
+            return Ok(None);
+        };
+        let mut lines = content.split('\n');
+        if lines.next() != Some(&format!("version {POINTER_VERSION}")) {
+            return Ok(None);
+        }
+        let invalid_pointer = |message: &str| GitLfsError::InvalidPointer(message.to_owned());
+        let mut oid = None;
+        let mut size = None;
+        for line in lines {
+            let (key, value) = line
+                .split_once(' ')
+                .ok_or_else(|| invalid_pointer("Malformed line"))?;
+            match key {
+                "oid" => {
+                    let hash = value
+                        .strip_prefix("sha256:")
+                        .ok_or_else(|| invalid_pointer("Unsupported hash algorithm"))?;
+                    if hash.len() != 64 || !hash.bytes().all(|b| b.is_ascii_hexdigit()) {
+                        return Err(invalid_pointer("The oid isn't a SHA-256 hash"));
+                    }
+                    oid = Some(hash.to_ascii_lowercase());
+                }
+                "size" => {
+                    let value = value
+                        .parse()
+                        .map_err(|_| invalid_pointer("The size isn't a number"))?;
+                    size = Some(value);
+                }
+                // Extensions aren't supported, but the keys are allowed
+                _ => {}
+            }
+        }
+        Ok(Some(LfsPointer {
+            oid: oid.ok_or_else(|| invalid_pointer("Missing oid"))?,
+            size: size.ok_or_else(|| invalid_pointer("Missing size"))?,
+        }))
+    }
+
+    /// Hex-encoded SHA-256 hash of the contents.
+    pub fn oid(&self) -> &str {
+        &self.oid
+    }
+
+    /// Size of the contents in bytes.
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// Serializes the pointer in the canonical format.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        format!(
+            "version {POINTER_VERSION}\noid sha256:{}\nsize {}\n",
+            self.oid, self.size
+        )
+        .into_bytes()
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum GitLfsError {
+    #[error(transparent)]
+    Io(#[from] PathError),
+    #[error("Failed to read file contents")]
+    Read(#[source] io::Error),
+    #[error("Invalid LFS pointer: {0}")]
+    InvalidPointer(String),
+    #[error("Failed to fetch LFS object {oid}")]
+    Fetch {
+        oid: String,
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+    #[error("Fetched LFS object doesn't match {oid}")]
+    ContentMismatch { oid: String },
+}
+
+/// Contents to be checked out for a file returned by
+/// [`GitLfsStore::smudge()`].
+pub enum SmudgedContent<'a> {
+    /// Contents of the object, or of the file itself if it isn't a pointer
+    /// file.
+    Content(Box<dyn Read + 'a>),
+    /// Pointer file which should be checked out as is because the object
+    /// couldn't be read. `error` is `None` if the object isn't available.
+    Pointer {
+        reader: Box<dyn Read + 'a>,
+        error: Option<GitLfsError>,
+    },
+}
+
+/// Downloads LFS objects which aren't in the local object store, typically
+/// from an LFS server.
+pub trait LfsFetcher: Debug + Send + Sync {
+    /// Writes the contents of the object referenced by the `pointer` to the
+    /// `output`. Returns `false` if the object doesn't exist.
+    fn fetch(
+        &self,
+        pointer: &LfsPointer,
+        output: &mut dyn Write,
+    ) -> Result<bool, Box<dyn std::error::Error + Send + Sync>>;
+}
+
+/// Writer which computes the pointer to the written contents.
+struct HashingWriter<W> {
+    inner: W,
+    hasher: Sha256,
+    size: u64,
+}
+
+impl<W: Write> HashingWriter<W> {
+    fn new(inner: W) -> Self {
+        HashingWriter {
+            inner,
+            hasher: Sha256::new(),
+            size: 0,
+        }
+    }
+
+    fn finish(self) -> (W, LfsPointer) {
+        let pointer = LfsPointer {
+            oid: hex::encode(self.hasher.finalize()),
+            size: self.size,
+        };
+        (self.inner, pointer)
+    }
+}
+
+impl<W: Write> Write for HashingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let len = self.inner.write(buf)?;
+        self.hasher.update(&buf[..len]);
+        self.size += len as u64;
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Reads the beginning of the `reader` which may be a pointer file. The
+/// returned bytes are the whole contents if they're short enough.
+fn read_pointer_candidate(reader: &mut dyn Read) -> Result<Vec<u8>, GitLfsError> {
+    let mut head = vec![];
+    reader
+        .take(MAX_POINTER_SIZE as u64 + 1)
+        .read_to_end(&mut head)
+        .map_err(GitLfsError::Read)?;
+    Ok(head)
+}
+
+/// Local LFS object store, `<git dir>/lfs/objects`.
+#[derive(Clone, Debug)]
+pub struct GitLfsStore {
+    objects_dir: PathBuf,
+    fetcher: Option<Arc<dyn LfsFetcher>>,
+}
+
+impl GitLfsStore {
+    /// Creates store for the Git repository at `git_dir`.
+    pub fn for_git_dir(git_dir: &Path) -> Self {
+        GitLfsStore {
+            objects_dir: git_dir.join("lfs").join("objects"),
+            fetcher: None,
+        }
+    }
+
+    /// Sets `fetcher` to download objects which aren't stored locally.
+    pub fn with_fetcher(mut self, fetcher: Arc<dyn LfsFetcher>) -> Self {
+        self.fetcher = Some(fetcher);
+        self
+    }
+
+    fn object_path(&self, pointer: &LfsPointer) -> PathBuf {
+        let oid = &pointer.oid;
+        self.objects_dir.join(&oid[0..2]).join(&oid[2..4]).join(oid)
+    }
+
+    fn new_temp_file(&self) -> Result<NamedTempFile, GitLfsError> {
+        fs::create_dir_all(&self.objects_dir).context(&self.objects_dir)?;
+        Ok(NamedTempFile::new_in(&self.objects_dir).context(&self.objects_dir)?)
+    }
+
+    fn persist_object(
+        &self,
+        temp_file: NamedTempFile,
+        pointer: &LfsPointer,
+    ) -> Result<PathBuf, GitLfsError> {
+        let path = self.object_path(pointer);
+        let dir = path.parent().unwrap();
+        fs::create_dir_all(dir).context(dir)?;
+        persist_content_addressed_temp_file(temp_file, &path).context(&path)?;
+        Ok(path)
+    }
+
+    /// Opens the object referenced by the `pointer`, fetching it if
+    /// necessary. Returns `None` if the object isn't available.
+    pub fn open_object(&self, pointer: &LfsPointer) -> Result<Option<File>, GitLfsError> {
+        let path = self.object_path(pointer);
+        match File::open(&path) {
+            Ok(file) => return Ok(Some(file)),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+            Err(error) => return Err(PathError { path, error }.into()),
+        }
+        let Some(fetcher) = &self.fetcher else {
+            return Ok(None);
+        };
+        let mut writer = HashingWriter::new(self.new_temp_file()?);
+        let found = fetcher
+            .fetch(pointer, &mut writer)
+            .map_err(|source| GitLfsError::Fetch {
+                oid: pointer.oid.clone(),
+                source,
+            })?;
+        if !found {
+            return Ok(None);
+        }
+        let (temp_file, fetched) = writer.finish();
+        if fetched != *pointer {
+            return Err(GitLfsError::ContentMismatch {
+                oid: pointer.oid.clone(),
+            });
+        }
+        let path = self.persist_object(temp_file, pointer)?;
+        let file = File::open(&path).context(&path)?;
+        Ok(Some(file))
+    }
+
+    /// Stores the contents read from `reader` as an LFS object, and returns
+    /// pointer to it.
+    pub fn write_object(&self, reader: &mut dyn Read) -> Result<LfsPointer, GitLfsError> {
+        let mut writer = HashingWriter::new(self.new_temp_file()?);
+        io::copy(reader, &mut writer).map_err(GitLfsError::Read)?;
+        let (temp_file, pointer) = writer.finish();
+        self.persist_object(temp_file, &pointer)?;
+        Ok(pointer)
+    }
+
+    /// Converts pointer file to the contents of the object. The pointer is
+    /// returned unchanged if the object can't be read, or if the pointer is
+    /// invalid. Only errors while reading the `reader` are returned as `Err`.
+    pub fn smudge<'a>(
+        &self,
+        mut reader: Box<dyn Read + 'a>,
+    ) -> Result<SmudgedContent<'a>, GitLfsError> {
+        let head = read_pointer_candidate(&mut reader)?;
+        let pointer = match LfsPointer::parse(&head) {
+            Ok(Some(pointer)) => pointer,
+            Ok(None) => {
+                return Ok(SmudgedContent::Content(Box::new(
+                    io::Cursor::new(head).chain(reader),
+                )));
+            }
+            Err(err) => {
+                return Ok(SmudgedContent::Pointer {
+                    reader: Box::new(io::Cursor::new(head)),
+                    error: Some(err),
+                });
+            }
+        };
+        match self.open_object(&pointer) {
+            Ok(Some(file)) => Ok(SmudgedContent::Content(Box::new(file))),
+            Ok(None) => Ok(SmudgedContent::Pointer {
+                reader: Box::new(io::Cursor::new(head)),
+                error: None,
+            }),
+            Err(err) => Ok(SmudgedContent::Pointer {
+                reader: Box::new(io::Cursor::new(head)),
+                error: Some(err),
+            }),
+        }
+    }
+
+    /// Stores the contents read from `reader` as an LFS object, and returns
+    /// pointer file to it. Pointer files are returned unchanged.
+    pub fn clean(&self, reader: &mut dyn Read) -> Result<Vec<u8>, GitLfsError> {
+        let head = read_pointer_candidate(reader)?;
+        // Invalid pointer files are stored as objects as if they were regular
+        // files.
+        if let Ok(Some(_)) = LfsPointer::parse(&head) {
+            return Ok(head);
+        }
+        let mut reader = io::Cursor::new(head).chain(reader);
+        Ok(self.write_object(&mut reader)?.to_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use assert_matches::assert_matches;
+
+    use super::*;
+
+    #[derive(Debug, Default)]
+    struct TestFetcher {
+        objects: HashMap<String, Vec<u8>>,
+    }
+
+    impl LfsFetcher for TestFetcher {
+        fn fetch(
+            &self,
+            pointer: &LfsPointer,
+            output: &mut dyn Write,
+        ) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+            let Some(content) = self.objects.get(&pointer.oid) else {
+                return Ok(false);
+            };
+            output.write_all(content)?;
+            Ok(true)
+        }
+    }
+
+    fn read_all(mut reader: impl Read) -> Vec<u8> {
+        let mut content = vec![];
+        reader.read_to_end(&mut content).unwrap();
+        content
+    }
+
+    #[test]
+    fn test_parse_pointer() {
+        let pointer = LfsPointer::for_content(b"hello\n");
+        let text = pointer.to_bytes();
+        insta::assert_snapshot!(String::from_utf8(text.clone()).unwrap(), @r"
+        version https://git-lfs.github.com/spec/v1
+        oid sha256:5891b5b522d5df086d0ff0b110fbd9d21bb4fc7163af34d08286a2e846f6be03
+        size 6
+        ");
+        assert_eq!(LfsPointer::parse(&text).unwrap(), Some(pointer.clone()));
+
+        // Extension keys are ignored
+        let text = format!(
+            "version {POINTER_VERSION}\next-0-foo sha256:0\noid sha256:{}\nsize 6\n",
+            pointer.oid
+        );
+        assert_eq!(
+            LfsPointer::parse(text.as_bytes()).unwrap(),
+            Some(pointer.clone())
+        );
+
+        assert_eq!(LfsPointer::parse(b"hello\n").unwrap(), None);
+        assert_eq!(LfsPointer::parse(b"").unwrap(), None);
+        // Missing size
+        let text = format!("version {POINTER_VERSION}\noid sha256:{}\n", pointer.oid);
+        assert_matches!(
+            LfsPointer::parse(text.as_bytes()),
+            Err(GitLfsError::InvalidPointer(_))
+        );
+        // Bad hash
+        let text = format!("version {POINTER_VERSION}\noid sha256:abc\nsize 6\n");
+        assert_matches!(
+            LfsPointer::parse(text.as_bytes()),
+            Err(GitLfsError::InvalidPointer(_))
+        );
+        let text = format!(
+            "version {POINTER_VERSION}\noid sha256:../../{}\nsize 6\n",
+            &pointer.oid[6..]
+        );
+        assert_matches!(
+            LfsPointer::parse(text.as_bytes()),
+            Err(GitLfsError::InvalidPointer(_))
+        );
+    }
+
+    #[test]
+    fn test_clean_and_smudge() {
+        let temp_dir = testutils::new_temp_dir();
+        let store = GitLfsStore::for_git_dir(temp_dir.path());
+        let smudge = |content: &[u8]| match store.smudge(Box::new(content)).unwrap() {
+            SmudgedContent::Content(reader) => read_all(reader),
+            SmudgedContent::Pointer { reader, .. } => read_all(reader),
+        };
+
+        let pointer_text = store.clean(&mut b"hello\n".as_slice()).unwrap();
+        let pointer = LfsPointer::parse(&pointer_text).unwrap().unwrap();
+        assert!(store.object_path(&pointer).is_file());
+        // Cleaning pointer file is no-op
+        assert_eq!(
+            store.clean(&mut pointer_text.as_slice()).unwrap(),
+            pointer_text
+        );
+        assert_eq!(smudge(&pointer_text), b"hello\n");
+        // Not a pointer file
+        assert_eq!(smudge(b"world\n"), b"world\n");
+        let large_content = "large\n".repeat(1000).into_bytes();
+        assert_eq!(smudge(&large_content), large_content);
+        let large_pointer_text = store.clean(&mut large_content.as_slice()).unwrap();
+        assert_eq!(smudge(&large_pointer_text), large_content);
+
+        // Missing object
+        let other_pointer_text = LfsPointer::for_content(b"world\n").to_bytes();
+        assert_eq!(smudge(&other_pointer_text), other_pointer_text);
+        assert!(matches!(
+            store.smudge(Box::new(other_pointer_text.as_slice())),
+            Ok(SmudgedContent::Pointer { error: None, .. })
+        ));
+
+        // Invalid pointer file is stored as regular file, and checked out as is
+        let invalid_pointer_text = format!("version {POINTER_VERSION}\noid sha256:abc\nsize 6\n");
+        let pointer_text = store.clean(&mut invalid_pointer_text.as_bytes()).unwrap();
+        assert_ne!(pointer_text, invalid_pointer_text.as_bytes());
+        assert_eq!(
+            smudge(invalid_pointer_text.as_bytes()),
+            invalid_pointer_text.as_bytes()
+        );
+        assert!(matches!(
+            store.smudge(Box::new(invalid_pointer_text.as_bytes())),
+            Ok(SmudgedContent::Pointer {
+                error: Some(GitLfsError::InvalidPointer(_)),
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_fetch() {
+        let temp_dir = testutils::new_temp_dir();
+        let mut fetcher = TestFetcher::default();
+        let pointer = LfsPointer::for_content(b"remote\n");
+        fetcher
+            .objects
+            .insert(pointer.oid.clone(), b"remote\n".to_vec());
+        let bad_pointer = LfsPointer::for_content(b"bad\n");
+        fetcher
+            .objects
+            .insert(bad_pointer.oid.clone(), b"corrupted\n".to_vec());
+        let store = GitLfsStore::for_git_dir(temp_dir.path()).with_fetcher(Arc::new(fetcher));
+
+        let file = store.open_object(&pointer).unwrap().unwrap();
+        assert_eq!(read_all(file), b"remote\n");
+        // Fetched object is stored locally
+        assert!(store.object_path(&pointer).is_file());
+        assert!(store
+            .open_object(&LfsPointer::for_content(b"missing\n"))
+            .unwrap()
+            .is_none());
+        assert_matches!(
+            store.open_object(&bad_pointer),
+            Err(GitLfsError::ContentMismatch { .. })
+        );
+        assert!(!store.object_path(&bad_pointer).exists());
+
+        // Pointer is checked out as is if the object can't be fetched
+        let bad_pointer_text = bad_pointer.to_bytes();
+        assert!(matches!(
+            store.smudge(Box::new(bad_pointer_text.as_slice())),
+            Ok(SmudgedContent::Pointer {
+                error: Some(GitLfsError::ContentMismatch { .. }),
+                ..
+            })
+        ));
+    }
+}
@@ -0,0 +1,710 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Parser and matcher of `.gitattributes` files.
+//!
+//! Only the attributes jj acts on are interpreted: `text` and `eol` (line
+//! ending conversion in the working copy), `diff` (whether to show textual
+//! diffs), and `merge` (how to merge conflicting changes).
+
+#![allow(missing_docs)]
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::io::Chain;
+use std::io::Cursor;
+use std::io::Read;
+use std::iter;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+use ignore::gitignore;
+use thiserror::Error;
+
+use crate::backend::BackendError;
+use crate::backend::TreeValue;
+use crate::merged_tree::MergedTree;
+use crate::repo_path::RepoPath;
+use crate::repo_path::RepoPathBuf;
+use crate::repo_path::RepoPathComponent;
+
+/// Number of bytes to look at when guessing whether a file is binary. This is
+/// the same as Git's.
+const BINARY_PEEK_SIZE: usize = 8000;
+
+#[derive(Debug, Error)]
+pub enum GitAttributesError {
+    #[error("Failed to read attributes from file {path}")]
+    ReadFile { path: PathBuf, source: io::Error },
+    #[error("Invalid UTF-8 for attributes in {path} on line #{line_num_for_display}: {line}")]
+    InvalidUtf8 {
+        path: PathBuf,
+        line_num_for_display: usize,
+        line: String,
+        source: std::str::Utf8Error,
+    },
+    #[error("Failed to parse attribute pattern in {path} on line #{line_num_for_display}")]
+    Underlying {
+        path: PathBuf,
+        line_num_for_display: usize,
+        source: ignore::Error,
+    },
+    #[error(transparent)]
+    Backend(#[from] BackendError),
+}
+
+/// State of an attribute for a path.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum GitAttributeState {
+    /// The attribute is set, e.g. `text`.
+    Set,
+    /// The attribute is unset, e.g. `-text`.
+    Unset,
+    /// The attribute is set to a value, e.g. `eol=crlf`.
+    Value(String),
+    /// The attribute is reverted to unspecified, e.g. `!text`.
+    Unspecified,
+}
+
+impl GitAttributeState {
+    fn parse(text: &str) -> (&str, Self) {
+        if let Some(name) = text.strip_prefix('-') {
+            (name, GitAttributeState::Unset)
+        } else if let Some(name) = text.strip_prefix('!') {
+            (name, GitAttributeState::Unspecified)
+        } else if let Some((name, value)) = text.split_once('=') {
+            (name, GitAttributeState::Value(value.to_owned()))
+        } else {
+            (text, GitAttributeState::Set)
+        }
+    }
+}
+
+#[derive(Debug)]
+struct GitAttributesRule {
+    matcher: gitignore::Gitignore,
+    /// Attribute assignments in the order they appear, with macros expanded.
+    assignments: Vec<(String, GitAttributeState)>,
+}
+
+/// Models the effective contents of multiple `.gitattributes` files.
+#[derive(Debug)]
+pub struct GitAttributesFile {
+    parent: Option<Arc<GitAttributesFile>>,
+    /// Directory the rules are relative to.
+    prefix: String,
+    rules: Vec<GitAttributesRule>,
+    /// Macros defined by `[attr]name ...` lines.
+    macros: HashMap<String, Vec<(String, GitAttributeState)>>,
+}
+
+impl GitAttributesFile {
+    pub fn empty() -> Arc<GitAttributesFile> {
+        Arc::new(GitAttributesFile {
+            parent: None,
+            prefix: String::new(),
+            rules: vec![],
+            macros: HashMap::new(),
+        })
+    }
+
+    /// Concatenates new `.gitattributes` content at the `prefix` directory.
+    ///
+    /// The `prefix` should be a slash-separated path relative to the workspace
+    /// root.
+    pub fn chain(
+        self: &Arc<GitAttributesFile>,
+        prefix: &str,
+        attributes_path: &Path,
+        input: &[u8],
+    ) -> Result<Arc<GitAttributesFile>, GitAttributesError> {
+        let mut rules = vec![];
+        let mut macros = HashMap::new();
+        for (i, input_line) in input.split(|b| *b == b'\n').enumerate() {
+            let line =
+                std::str::from_utf8(input_line).map_err(|err| GitAttributesError::InvalidUtf8 {
+                    path: attributes_path.to_path_buf(),
+                    line_num_for_display: i + 1,
+                    line: String::from_utf8_lossy(input_line).to_string(),
+                    source: err,
+                })?;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut fields = line.split_ascii_whitespace();
+            let pattern = fields.next().unwrap();
+            let mut assignments = vec![];
+            for field in fields {
+                let (name, state) = GitAttributeState::parse(field);
+                if state == GitAttributeState::Set {
+                    let expanded = macros
+                        .get(name)
+                        .or_else(|| self.find_macro(name))
+                        .cloned()
+                        .or_else(|| builtin_macro(name));
+                    if let Some(expanded) = expanded {
+                        assignments.extend(expanded);
+                    }
+                }
+                assignments.push((name.to_owned(), state));
+            }
+            if let Some(name) = pattern.strip_prefix("[attr]") {
+                macros.insert(name.to_owned(), assignments);
+                continue;
+            }
+            if pattern.starts_with('!') {
+                // Negative patterns are forbidden in Git. Ignore them like Git.
+                continue;
+            }
+            let mut builder = gitignore::GitignoreBuilder::new(prefix);
+            builder
+                .add_line(None, pattern)
+                .map_err(|err| GitAttributesError::Underlying {
+                    path: attributes_path.to_path_buf(),
+                    line_num_for_display: i + 1,
+                    source: err,
+                })?;
+            let matcher = builder
+                .build()
+                .map_err(|err| GitAttributesError::Underlying {
+                    path: attributes_path.to_path_buf(),
+                    line_num_for_display: i + 1,
+                    source: err,
+                })?;
+            rules.push(GitAttributesRule {
+                matcher,
+                assignments,
+            });
+        }
+        let parent = if self.rules.is_empty() && self.macros.is_empty() {
+            self.parent.clone() // omit the empty root
+        } else {
+            Some(self.clone())
+        };
+        Ok(Arc::new(GitAttributesFile {
+            parent,
+            prefix: prefix.to_owned(),
+            rules,
+            macros,
+        }))
+    }
+
+    /// Concatenates new `.gitattributes` file at the `prefix` directory.
+    ///
+    /// The `prefix` should be a slash-separated path relative to the workspace
+    /// root.
+    pub fn chain_with_file(
+        self: &Arc<GitAttributesFile>,
+        prefix: &str,
+        file: PathBuf,
+    ) -> Result<Arc<GitAttributesFile>, GitAttributesError> {
+        if file.is_file() {
+            let buf = fs::read(&file).map_err(|err| GitAttributesError::ReadFile {
+                path: file.clone(),
+                source: err,
+            })?;
+            self.chain(prefix, &file, &buf)
+        } else {
+            Ok(self.clone())
+        }
+    }
+
+    fn find_macro(&self, name: &str) -> Option<&Vec<(String, GitAttributeState)>> {
+        iter::successors(Some(self), |file| file.parent.as_deref())
+            .find_map(|file| file.macros.get(name))
+    }
+
+    /// Returns the state of the attribute `name` for the file at `path`, or
+    /// `None` if the attribute is unspecified.
+    ///
+    /// Rules in deeper directories and later lines take precedence.
+    pub fn get(&self, path: &str, name: &str) -> Option<&GitAttributeState> {
+        iter::successors(Some(self), |file| file.parent.as_deref())
+            .filter(|file| path.starts_with(&file.prefix))
+            .flat_map(|file| file.rules.iter().rev())
+            .filter(|rule| {
+                rule.assignments.iter().any(|(n, _)| n == name)
+                    && rule.matcher.matched(path, false).is_ignore()
+            })
+            .find_map(|rule| {
+                rule.assignments
+                    .iter()
+                    .rev()
+                    .find(|(n, _)| n == name)
+                    .map(|(_, state)| state)
+            })
+            .filter(|state| **state != GitAttributeState::Unspecified)
+    }
+
+    /// Returns the attributes jj acts on for the file at `path`.
+    pub fn attributes(&self, path: &str) -> GitAttributes {
+        GitAttributes {
+            text: self.get(path, "text").cloned(),
+            eol: self.get(path, "eol").cloned(),
+            diff: self.get(path, "diff").cloned(),
+            merge: self.get(path, "merge").cloned(),
+            filter: self.get(path, "filter").cloned(),
+        }
+    }
+}
+
+fn builtin_macro(name: &str) -> Option<Vec<(String, GitAttributeState)>> {
+    match name {
+        "binary" => Some(vec![
+            ("diff".to_owned(), GitAttributeState::Unset),
+            ("merge".to_owned(), GitAttributeState::Unset),
+            ("text".to_owned(), GitAttributeState::Unset),
+        ]),
+        _ => None,
+    }
+}
+
+/// Attributes of a file. `None` means the attribute is unspecified.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct GitAttributes {
+    pub text: Option<GitAttributeState>,
+    pub eol: Option<GitAttributeState>,
+    pub diff: Option<GitAttributeState>,
+    pub merge: Option<GitAttributeState>,
+    pub filter: Option<GitAttributeState>,
+}
+
+impl GitAttributes {
+    /// Returns true if the file shouldn't be diffed as text.
+    pub fn is_binary_for_diff(&self) -> bool {
+        self.diff == Some(GitAttributeState::Unset)
+    }
+
+    /// Returns true if the file is stored as a Git LFS pointer.
+    pub fn is_lfs(&self) -> bool {
+        matches!(&self.filter, Some(GitAttributeState::Value(value)) if value == "lfs")
+    }
+
+    /// Returns true if the file is converted as text only if it doesn't look
+    /// binary (`text=auto`).
+    pub fn is_text_auto(&self) -> bool {
+        matches!(&self.text, Some(GitAttributeState::Value(value)) if value == "auto")
+    }
+
+    /// Returns true if line endings of `contents` should be normalized.
+    fn is_text(&self, contents: &[u8]) -> bool {
+        match &self.text {
+            Some(GitAttributeState::Set) => true,
+            Some(GitAttributeState::Unset) => false,
+            Some(GitAttributeState::Value(value)) if value == "auto" => {
+                !contents[..BINARY_PEEK_SIZE.min(contents.len())].contains(&b'\0')
+            }
+            // Setting `eol` implies `text`
+            Some(GitAttributeState::Value(_)) | Some(GitAttributeState::Unspecified) | None => {
+                matches!(self.eol, Some(GitAttributeState::Value(_)))
+            }
+        }
+    }
+
+    fn checkout_crlf(&self) -> bool {
+        match &self.eol {
+            Some(GitAttributeState::Value(value)) if value == "crlf" => true,
+            Some(GitAttributeState::Value(value)) if value == "lf" => false,
+            _ => cfg!(windows),
+        }
+    }
+
+    /// Reads the first bytes of `reader` to decide whether its contents
+    /// should be converted as text. Returns the decision and a reader of the
+    /// whole contents.
+    fn peek_is_text<R: Read>(&self, mut reader: R) -> io::Result<(bool, PeekedReader<R>)> {
+        let mut head = vec![];
+        reader
+            .by_ref()
+            .take(BINARY_PEEK_SIZE as u64)
+            .read_to_end(&mut head)?;
+        let is_text = self.is_text(&head);
+        Ok((is_text, Cursor::new(head).chain(reader)))
+    }
+
+    /// Converts working-copy contents read from `reader` to be stored in the
+    /// repository. Only the first bytes are read before returning.
+    ///
+    /// Like Git, files with `text=auto` aren't normalized if their contents in
+    /// the repository already have CRLF line endings (`has_crlf_in_repo`), so
+    /// that touching such a file doesn't rewrite it as a whole.
+    pub fn convert_to_repo<R: Read>(
+        &self,
+        reader: R,
+        has_crlf_in_repo: bool,
+    ) -> io::Result<impl Read> {
+        if has_crlf_in_repo && self.is_text_auto() {
+            return Ok(EolReader::new(Cursor::new(vec![]).chain(reader), None));
+        }
+        let (is_text, reader) = self.peek_is_text(reader)?;
+        Ok(EolReader::new(reader, is_text.then_some(Eol::Lf)))
+    }
+
+    /// Converts contents stored in the repository read from `reader` to be
+    /// written to the working copy. Only the first bytes are read before
+    /// returning.
+    pub fn convert_to_working_copy<R: Read>(&self, reader: R) -> io::Result<impl Read> {
+        if !self.checkout_crlf() {
+            return Ok(EolReader::new(Cursor::new(vec![]).chain(reader), None));
+        }
+        let (is_text, reader) = self.peek_is_text(reader)?;
+        Ok(EolReader::new(reader, is_text.then_some(Eol::Crlf)))
+    }
+}
+
+/// Returns true if the contents read from `reader` contain CRLF.
+pub fn has_crlf<R: Read>(reader: R) -> io::Result<bool> {
+    let mut after_cr = false;
+    for byte in io::BufReader::new(reader).bytes() {
+        let byte = byte?;
+        if after_cr && byte == b'\n' {
+            return Ok(true);
+        }
+        after_cr = byte == b'\r';
+    }
+    Ok(false)
+}
+
+/// Reader of the contents peeked by [`GitAttributes::peek_is_text()`].
+type PeekedReader<R> = Chain<Cursor<Vec<u8>>, R>;
+
+#[derive(Clone, Copy, Debug)]
+enum Eol {
+    Lf,
+    Crlf,
+}
+
+/// Reader which converts line endings of the contents read from `inner`.
+struct EolReader<R> {
+    inner: R,
+    /// Line ending to convert to, or `None` to pass the contents through.
+    eol: Option<Eol>,
+    /// Whether the last byte read from `inner` was `\r`. When converting to
+    /// LF, that `\r` hasn't been output yet.
+    after_cr: bool,
+    buf: Vec<u8>,
+    pos: usize,
+}
+
+impl<R: Read> EolReader<R> {
+    fn new(inner: R, eol: Option<Eol>) -> Self {
+        EolReader {
+            inner,
+            eol,
+            after_cr: false,
+            buf: vec![],
+            pos: 0,
+        }
+    }
+
+    fn convert(&mut self, eol: Eol, chunk: &[u8]) {
+        for &b in chunk {
+            match eol {
+                Eol::Lf => {
+                    if self.after_cr && b != b'\n' {
+                        self.buf.push(b'\r');
+                    }
+                    if b != b'\r' {
+                        self.buf.push(b);
+                    }
+                }
+                Eol::Crlf => {
+                    if b == b'\n' && !self.after_cr {
+                        self.buf.push(b'\r');
+                    }
+                    self.buf.push(b);
+                }
+            }
+            self.after_cr = b == b'\r';
+        }
+    }
+}
+
+impl<R: Read> Read for EolReader<R> {
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        let Some(eol) = self.eol else {
+            return self.inner.read(out);
+        };
+        if out.is_empty() {
+            return Ok(0);
+        }
+        while self.pos == self.buf.len() {
+            self.buf.clear();
+            self.pos = 0;
+            let mut chunk = [0; 8192];
+            let len = self.inner.read(&mut chunk)?;
+            if len == 0 {
+                if matches!(eol, Eol::Lf) && self.after_cr {
+                    self.buf.push(b'\r');
+                    self.after_cr = false;
+                    continue;
+                }
+                return Ok(0);
+            }
+            self.convert(eol, &chunk[..len]);
+        }
+        let len = out.len().min(self.buf.len() - self.pos);
+        out[..len].copy_from_slice(&self.buf[self.pos..self.pos + len]);
+        self.pos += len;
+        Ok(len)
+    }
+}
+
+#[derive(Debug)]
+enum GitAttributesSource {
+    Disk(PathBuf),
+    Tree(MergedTree),
+}
+
+/// Loads `.gitattributes` files of directories on demand.
+#[derive(Debug)]
+pub struct GitAttributesLoader {
+    source: GitAttributesSource,
+    dirs: Mutex<HashMap<RepoPathBuf, Arc<GitAttributesFile>>>,
+}
+
+impl GitAttributesLoader {
+    /// Creates loader which reads files under the `working_copy_path`.
+    pub fn from_disk(working_copy_path: PathBuf) -> Self {
+        GitAttributesLoader {
+            source: GitAttributesSource::Disk(working_copy_path),
+            dirs: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Creates loader which reads files from the `tree`. Conflicted files are
+    /// ignored.
+    pub fn from_tree(tree: MergedTree) -> Self {
+        GitAttributesLoader {
+            source: GitAttributesSource::Tree(tree),
+            dirs: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Returns the effective attributes file for the `dir`.
+    pub fn load_dir(&self, dir: &RepoPath) -> Result<Arc<GitAttributesFile>, GitAttributesError> {
+        if let Some(file) = self.dirs.lock().unwrap().get(dir) {
+            return Ok(file.clone());
+        }
+        let parent = match dir.parent() {
+            Some(parent_dir) => self.load_dir(parent_dir)?,
+            None => GitAttributesFile::empty(),
+        };
+        let prefix = dir.to_internal_dir_string();
+        let file = match &self.source {
+            GitAttributesSource::Disk(root) => parent.chain_with_file(
+                &prefix,
+                dir.to_fs_path_unchecked(root).join(".gitattributes"),
+            )?,
+            GitAttributesSource::Tree(tree) => {
+                let path = dir.join(RepoPathComponent::new(".gitattributes"));
+                match tree.path_value(&path)?.into_resolved() {
+                    Ok(Some(TreeValue::File { id, .. })) => {
+                        let mut content = vec![];
+                        tree.store()
+                            .read_file(&path, &id)?
+                            .read_to_end(&mut content)
+                            .map_err(|err| GitAttributesError::ReadFile {
+                                path: path.as_internal_file_string().into(),
+                                source: err,
+                            })?;
+                        parent.chain(&prefix, path.as_internal_file_string().as_ref(), &content)?
+                    }
+                    _ => parent,
+                }
+            }
+        };
+        self.dirs
+            .lock()
+            .unwrap()
+            .insert(dir.to_owned(), file.clone());
+        Ok(file)
+    }
+
+    /// Returns the attributes of the file at `path`.
+    pub fn attributes(&self, path: &RepoPath) -> Result<GitAttributes, GitAttributesError> {
+        let Some(dir) = path.parent() else {
+            return Ok(GitAttributes::default());
+        };
+        let file = self.load_dir(dir)?;
+        Ok(file.attributes(path.as_internal_file_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn attributes(input: &[u8], path: &str) -> GitAttributes {
+        let file = GitAttributesFile::empty()
+            .chain("", Path::new(""), input)
+            .unwrap();
+        file.attributes(path)
+    }
+
+    #[test]
+    fn test_gitattributes_parse_states() {
+        let attrs = attributes(b"*.txt text -diff eol=crlf\n*.txt !diff\n", "dir/a.txt");
+        assert_eq!(attrs.text, Some(GitAttributeState::Set));
+        assert_eq!(attrs.diff, None);
+        assert_eq!(attrs.eol, Some(GitAttributeState::Value("crlf".to_owned())));
+        assert_eq!(attrs.merge, None);
+        assert_eq!(
+            attributes(b"*.txt text\n", "a.md"),
+            GitAttributes::default()
+        );
+        assert_eq!(
+            attributes(b"# comment\n\n  *.md   -text  \n", "a.md").text,
+            Some(GitAttributeState::Unset)
+        );
+        assert!(attributes(b"*.psd filter=lfs diff=lfs merge=lfs -text\n", "a.psd").is_lfs());
+    }
+
+    #[test]
+    fn test_gitattributes_macros() {
+        let attrs = attributes(b"*.png binary\n", "a.png");
+        assert_eq!(attrs.text, Some(GitAttributeState::Unset));
+        assert_eq!(attrs.diff, Some(GitAttributeState::Unset));
+        assert_eq!(attrs.merge, Some(GitAttributeState::Unset));
+        // Later assignments override the expanded macro
+        let attrs = attributes(b"*.png binary diff\n", "a.png");
+        assert_eq!(attrs.diff, Some(GitAttributeState::Set));
+
+        let attrs = attributes(
+            b"[attr]lock -diff merge=union\nCargo.lock lock\n",
+            "Cargo.lock",
+        );
+        assert_eq!(attrs.diff, Some(GitAttributeState::Unset));
+        assert_eq!(
+            attrs.merge,
+            Some(GitAttributeState::Value("union".to_owned()))
+        );
+    }
+
+    #[test]
+    fn test_gitattributes_chained() {
+        let file = GitAttributesFile::empty()
+            .chain("", Path::new(""), b"*.txt text\n*.bin binary\n")
+            .unwrap()
+            .chain("dir/", Path::new(""), b"*.txt -text\n/top.bin diff\n")
+            .unwrap();
+        assert_eq!(file.attributes("a.txt").text, Some(GitAttributeState::Set));
+        assert_eq!(
+            file.attributes("dir/a.txt").text,
+            Some(GitAttributeState::Unset)
+        );
+        assert_eq!(
+            file.attributes("dir/sub/a.txt").text,
+            Some(GitAttributeState::Unset)
+        );
+        assert_eq!(
+            file.attributes("dir/top.bin").diff,
+            Some(GitAttributeState::Set)
+        );
+        assert_eq!(
+            file.attributes("dir/sub/top.bin").diff,
+            Some(GitAttributeState::Unset)
+        );
+        // Patterns with a trailing slash match only directories
+        assert_eq!(
+            attributes(b"dir/ text\n", "dir/a.txt"),
+            GitAttributes::default()
+        );
+    }
+
+    #[test]
+    fn test_gitattributes_eol_conversion() {
+        fn to_repo(attributes: &GitAttributes, contents: &[u8]) -> Vec<u8> {
+            let mut converted = vec![];
+            let mut reader = attributes.convert_to_repo(contents, false).unwrap();
+            reader.read_to_end(&mut converted).unwrap();
+            converted
+        }
+        fn to_working_copy(attributes: &GitAttributes, contents: &[u8]) -> Vec<u8> {
+            let mut converted = vec![];
+            let mut reader = attributes.convert_to_working_copy(contents).unwrap();
+            reader.read_to_end(&mut converted).unwrap();
+            converted
+        }
+
+        let text = GitAttributes {
+            text: Some(GitAttributeState::Set),
+            eol: Some(GitAttributeState::Value("crlf".to_owned())),
+            ..Default::default()
+        };
+        assert_eq!(to_repo(&text, b"a\r\nb\nc\r\n\r"), b"a\nb\nc\n\r");
+        assert_eq!(to_repo(&text, b"a\nb\n"), b"a\nb\n");
+        assert_eq!(to_working_copy(&text, b"a\nb\r\nc"), b"a\r\nb\r\nc");
+
+        let lf = GitAttributes {
+            eol: Some(GitAttributeState::Value("lf".to_owned())),
+            ..Default::default()
+        };
+        assert_eq!(to_repo(&lf, b"a\r\n"), b"a\n");
+        assert_eq!(to_working_copy(&lf, b"a\n"), b"a\n");
+
+        let auto = GitAttributes {
+            text: Some(GitAttributeState::Value("auto".to_owned())),
+            eol: Some(GitAttributeState::Value("crlf".to_owned())),
+            ..Default::default()
+        };
+        assert_eq!(to_repo(&auto, b"a\r\n"), b"a\n");
+        assert_eq!(to_repo(&auto, b"\0a\r\n"), b"\0a\r\n");
+        assert_eq!(to_working_copy(&auto, b"\0a\n"), b"\0a\n");
+        // Only the first bytes are checked for NUL
+        let mut contents = vec![b'a'; BINARY_PEEK_SIZE];
+        contents.extend_from_slice(b"\0\r\n");
+        let mut expected = vec![b'a'; BINARY_PEEK_SIZE];
+        expected.extend_from_slice(b"\0\n");
+        assert_eq!(to_repo(&auto, &contents), expected);
+        // Files already stored with CRLF aren't normalized
+        let mut converted = vec![];
+        auto.convert_to_repo(&b"a\r\nb\n"[..], true)
+            .unwrap()
+            .read_to_end(&mut converted)
+            .unwrap();
+        assert_eq!(converted, b"a\r\nb\n");
+        let mut converted = vec![];
+        text.convert_to_repo(&b"a\r\nb\n"[..], true)
+            .unwrap()
+            .read_to_end(&mut converted)
+            .unwrap();
+        assert_eq!(converted, b"a\nb\n");
+
+        // Line endings split across reads are converted
+        let contents = [b"a\r\n".repeat(10000), b"\r".to_vec()].concat();
+        let expected = [b"a\n".repeat(10000), b"\r".to_vec()].concat();
+        assert_eq!(to_repo(&text, &contents), expected);
+        assert_eq!(
+            to_working_copy(&text, &expected),
+            [b"a\r\n".repeat(10000), b"\r".to_vec()].concat()
+        );
+
+        let unspecified = GitAttributes::default();
+        assert_eq!(to_repo(&unspecified, b"a\r\n"), b"a\r\n");
+        assert_eq!(to_working_copy(&unspecified, b"a\n"), b"a\n");
+    }
+
+    #[test]
+    fn test_has_crlf() {
+        assert!(!has_crlf(&b""[..]).unwrap());
+        assert!(!has_crlf(&b"a\nb\r"[..]).unwrap());
+        assert!(!has_crlf(&b"a\r\rb\n\r"[..]).unwrap());
+        assert!(has_crlf(&b"a\nb\r\n"[..]).unwrap());
+    }
+}
@@ -27,10 +27,13 @@ extern crate self as jj_lib;
 
 #[macro_use]
 pub mod content_hash;
+pub mod content_merger;
 
 pub mod absorb;
 pub mod annotate;
+pub mod api;
 pub mod backend;
+pub mod bisect;
 pub mod commit;
 pub mod commit_builder;
 pub mod config;
@@ -66,7 +69,11 @@ pub mod git {
 #[cfg(feature = "git")]
 pub mod git_backend;
 #[cfg(feature = "git")]
+mod git_gix;
+pub mod git_lfs;
+#[cfg(feature = "git")]
 mod git_subprocess;
+pub mod gitattributes;
 pub mod gitignore;
 pub mod gpg_signing;
 pub mod graph;
@@ -84,6 +91,7 @@ pub mod op_heads_store;
 pub mod op_store;
 pub mod op_walk;
 pub mod operation;
+pub mod patch;
 #[allow(missing_docs)]
 pub mod protos;
 pub mod refs;
@@ -113,3 +121,4 @@ pub mod union_find;
 pub mod view;
 pub mod working_copy;
 pub mod workspace;
+pub mod workspace_store;
@@ -58,6 +58,7 @@ use tracing::trace_span;
 
 use crate::backend::BackendError;
 use crate::backend::BackendResult;
+use crate::backend::CommitId;
 use crate::backend::FileId;
 use crate::backend::MergedTreeId;
 use crate::backend::MillisSinceEpoch;
@@ -74,11 +75,18 @@ use crate::conflicts::MaterializedTreeValue;
 use crate::conflicts::MIN_CONFLICT_MARKER_LEN;
 use crate::file_util::check_symlink_support;
 use crate::file_util::try_symlink;
+#[cfg(target_os = "linux")]
+use crate::fsmonitor::native;
 #[cfg(feature = "watchman")]
 use crate::fsmonitor::watchman;
 use crate::fsmonitor::FsmonitorSettings;
 #[cfg(feature = "watchman")]
 use crate::fsmonitor::WatchmanConfig;
+use crate::git_lfs::GitLfsStore;
+use crate::git_lfs::LfsFetcher;
+use crate::git_lfs::SmudgedContent;
+use crate::gitattributes;
+use crate::gitattributes::GitAttributesLoader;
 use crate::gitignore::GitIgnoreFile;
 use crate::lock::FileLock;
 use crate::matchers::DifferenceMatcher;
@@ -421,6 +429,11 @@ pub struct TreeState {
     /// the repo is configured to use the Watchman filesystem monitor and
     /// Watchman has been queried at least once.
     watchman_clock: Option<crate::protos::working_copy::WatchmanClock>,
+    /// The journal position returned by the built-in filesystem monitor at the
+    /// last snapshot.
+    native_fsmonitor_clock: Option<crate::protos::working_copy::NativeFsmonitorClock>,
+    /// Downloads LFS objects which aren't stored locally.
+    lfs_fetcher: Option<Arc<dyn LfsFetcher>>,
 }
 
 fn file_state_from_proto(proto: &crate::protos::working_copy::FileState) -> FileState {
@@ -596,6 +609,51 @@ fn remove_old_file(disk_path: &Path) -> Result<bool, CheckoutError> {
     }
 }
 
+#[cfg(feature = "git")]
+fn read_git_submodule_head(disk_path: &Path) -> Option<CommitId> {
+    crate::git::read_submodule_head(disk_path)
+}
+
+#[cfg(not(feature = "git"))]
+fn read_git_submodule_head(_disk_path: &Path) -> Option<CommitId> {
+    None
+}
+
+/// Returns the LFS object store of the underlying Git repo if the backend is
+/// Git. Missing objects will be downloaded by the `fetcher`.
+#[cfg(feature = "git")]
+fn git_lfs_store(store: &Store, fetcher: Option<&Arc<dyn LfsFetcher>>) -> Option<GitLfsStore> {
+    let git_backend = crate::git::get_git_backend(store).ok()?;
+    let lfs_store = GitLfsStore::for_git_dir(git_backend.git_repo_path());
+    Some(match fetcher {
+        Some(fetcher) => lfs_store.with_fetcher(fetcher.clone()),
+        None => lfs_store,
+    })
+}
+
+#[cfg(not(feature = "git"))]
+fn git_lfs_store(_store: &Store, _fetcher: Option<&Arc<dyn LfsFetcher>>) -> Option<GitLfsStore> {
+    None
+}
+
+/// Checks out `id` in the Git submodule at `disk_path` if it's initialized.
+/// Returns false if the submodule couldn't be updated.
+#[cfg(feature = "git")]
+fn check_out_git_submodule(disk_path: &Path, id: &CommitId) -> bool {
+    match crate::git::check_out_submodule(disk_path, id) {
+        Ok(_) => true,
+        Err(err) => {
+            tracing::info!(?err, ?disk_path, "failed to check out git submodule");
+            false
+        }
+    }
+}
+
+#[cfg(not(feature = "git"))]
+fn check_out_git_submodule(_disk_path: &Path, _id: &CommitId) -> bool {
+    true
+}
+
 /// Checks if new file or symlink named `disk_path` can be created.
 ///
 /// If the file already exists, this function return `Ok(false)` to signal
@@ -719,6 +777,7 @@ fn file_state(metadata: &Metadata) -> Option<FileState> {
 struct FsmonitorMatcher {
     matcher: Option<Box<dyn Matcher>>,
     watchman_clock: Option<crate::protos::working_copy::WatchmanClock>,
+    native_fsmonitor_clock: Option<crate::protos::working_copy::NativeFsmonitorClock>,
 }
 
 #[derive(Debug, Error)]
@@ -781,6 +840,8 @@ impl TreeState {
             own_mtime: MillisSinceEpoch(0),
             symlink_support: check_symlink_support().unwrap_or(false),
             watchman_clock: None,
+            native_fsmonitor_clock: None,
+            lfs_fetcher: None,
         }
     }
 
@@ -844,6 +905,7 @@ impl TreeState {
             FileStatesMap::from_proto(proto.file_states, proto.is_file_states_sorted);
         self.sparse_patterns = sparse_patterns_from_proto(proto.sparse_patterns.as_ref());
         self.watchman_clock = proto.watchman_clock;
+        self.native_fsmonitor_clock = proto.native_fsmonitor_clock;
         Ok(())
     }
 
@@ -871,6 +933,7 @@ impl TreeState {
         }
         proto.sparse_patterns = Some(sparse_patterns);
         proto.watchman_clock = self.watchman_clock.clone();
+        proto.native_fsmonitor_clock = self.native_fsmonitor_clock.clone();
 
         let mut temp_file = NamedTempFile::new_in(&self.state_path).unwrap();
         temp_file
@@ -923,6 +986,18 @@ impl TreeState {
         Ok(changed_files)
     }
 
+    #[cfg(target_os = "linux")]
+    #[instrument(skip(self))]
+    pub fn query_native_fsmonitor(
+        &self,
+    ) -> Result<(Option<native::Clock>, Option<Vec<PathBuf>>), TreeStateError> {
+        let fsmonitor = native::Fsmonitor::new(&self.state_path);
+        let previous_clock = self.native_fsmonitor_clock.clone().map(native::Clock::from);
+        fsmonitor
+            .query_changed_files(previous_clock)
+            .map_err(|err| TreeStateError::Fsmonitor(Box::new(err)))
+    }
+
     #[cfg(feature = "watchman")]
     #[tokio::main(flavor = "current_thread")]
     #[instrument(skip(self))]
@@ -965,6 +1040,7 @@ impl TreeState {
         let FsmonitorMatcher {
             matcher: fsmonitor_matcher,
             watchman_clock,
+            native_fsmonitor_clock,
         } = self.make_fsmonitor_matcher(fsmonitor_settings)?;
         let fsmonitor_matcher = match fsmonitor_matcher.as_ref() {
             None => &EverythingMatcher,
@@ -975,6 +1051,7 @@ impl TreeState {
         if matcher.visit(RepoPath::root()).is_nothing() {
             // No need to load the current tree, set up channels, etc.
             self.watchman_clock = watchman_clock;
+            self.native_fsmonitor_clock = native_fsmonitor_clock;
             return Ok((is_dirty, SnapshotStats::default()));
         }
 
@@ -998,6 +1075,8 @@ impl TreeState {
                 progress,
                 max_new_file_size,
                 conflict_marker_style,
+                git_attributes: GitAttributesLoader::from_disk(self.working_copy_path.clone()),
+                lfs_store: git_lfs_store(&self.store, self.lfs_fetcher.as_ref()),
             };
             let directory_to_visit = DirectoryToVisit {
                 dir: RepoPathBuf::root(),
@@ -1056,6 +1135,7 @@ impl TreeState {
             assert_eq!(state_paths, tree_paths);
         }
         self.watchman_clock = watchman_clock;
+        self.native_fsmonitor_clock = native_fsmonitor_clock;
         Ok((is_dirty, stats))
     }
 
@@ -1084,6 +1164,19 @@ impl TreeState {
                         .into(),
                 });
             }
+            #[cfg(target_os = "linux")]
+            FsmonitorSettings::Native(_) => {
+                return self.make_native_fsmonitor_matcher();
+            }
+            #[cfg(not(target_os = "linux"))]
+            FsmonitorSettings::Native(_) => {
+                return Err(SnapshotError::Other {
+                    message: "Failed to query the filesystem monitor".to_string(),
+                    err: "The built-in filesystem monitor is only supported on Linux (consider \
+                          disabling `core.fsmonitor`)"
+                        .into(),
+                });
+            }
         };
         let matcher: Option<Box<dyn Matcher>> = match changed_files {
             None => None,
@@ -1101,6 +1194,33 @@ impl TreeState {
         Ok(FsmonitorMatcher {
             matcher,
             watchman_clock,
+            native_fsmonitor_clock: None,
+        })
+    }
+
+    #[cfg(target_os = "linux")]
+    fn make_native_fsmonitor_matcher(&self) -> Result<FsmonitorMatcher, SnapshotError> {
+        let (clock, changed_files) =
+            self.query_native_fsmonitor()
+                .map_err(|err| SnapshotError::Other {
+                    message: "Failed to query the filesystem monitor".to_string(),
+                    err: err.into(),
+                })?;
+        // A journaled directory may have been moved away along with its
+        // contents, so everything below the changed paths has to be visited.
+        let matcher: Option<Box<dyn Matcher>> = changed_files.map(|changed_files| {
+            let repo_paths = trace_span!("processing fsmonitor paths").in_scope(|| {
+                changed_files
+                    .into_iter()
+                    .filter_map(|path| RepoPathBuf::from_relative_path(path).ok())
+                    .collect_vec()
+            });
+            Box::new(PrefixMatcher::new(repo_paths)) as Box<dyn Matcher>
+        });
+        Ok(FsmonitorMatcher {
+            matcher,
+            watchman_clock: None,
+            native_fsmonitor_clock: clock.map(Into::into),
         })
     }
 }
@@ -1138,6 +1258,8 @@ struct FileSnapshotter<'a> {
     progress: Option<&'a SnapshotProgress<'a>>,
     max_new_file_size: u64,
     conflict_marker_style: ConflictMarkerStyle,
+    git_attributes: GitAttributesLoader,
+    lfs_store: Option<GitLfsStore>,
 }
 
 impl FileSnapshotter<'_> {
@@ -1229,6 +1351,7 @@ impl FileSnapshotter<'_> {
         let maybe_current_file_state = file_states.get_at(dir, name);
         if let Some(file_state) = &maybe_current_file_state {
             if file_state.file_type == FileType::GitSubmodule {
+                self.process_git_submodule(&path, &entry.path())?;
                 return Ok(None);
             }
         }
@@ -1308,13 +1431,14 @@ impl FileSnapshotter<'_> {
     /// Visits only paths we're already tracking.
     fn visit_tracked_files(&self, file_states: FileStates<'_>) -> Result<(), SnapshotError> {
         for (tracked_path, current_file_state) in file_states {
-            if current_file_state.file_type == FileType::GitSubmodule {
-                continue;
-            }
             if !self.matcher.matches(tracked_path) {
                 continue;
             }
             let disk_path = tracked_path.to_fs_path(&self.tree_state.working_copy_path)?;
+            if current_file_state.file_type == FileType::GitSubmodule {
+                self.process_git_submodule(tracked_path, &disk_path)?;
+                continue;
+            }
             let metadata = match disk_path.symlink_metadata() {
                 Ok(metadata) => Some(metadata),
                 Err(err) if err.kind() == io::ErrorKind::NotFound => None,
@@ -1368,6 +1492,30 @@ impl FileSnapshotter<'_> {
         Ok(())
     }
 
+    /// Records the commit checked out in an initialized Git submodule if it
+    /// differs from the one in the current tree. The submodule contents are
+    /// never snapshotted.
+    fn process_git_submodule(
+        &self,
+        path: &RepoPath,
+        disk_path: &Path,
+    ) -> Result<(), SnapshotError> {
+        let Some(head_id) = read_git_submodule_head(disk_path) else {
+            return Ok(());
+        };
+        let current_tree_values = self.current_tree.path_value(path)?;
+        match current_tree_values.as_normal() {
+            Some(TreeValue::GitSubmodule(id)) if *id != head_id => {
+                let new_tree_values = Merge::normal(TreeValue::GitSubmodule(head_id));
+                self.tree_entries_tx
+                    .send((path.to_owned(), new_tree_values))
+                    .ok();
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
     /// Emits file paths that don't exist in the `present_entries`.
     fn emit_deleted_files(
         &self,
@@ -1474,9 +1622,13 @@ impl FileSnapshotter<'_> {
         materialized_conflict_data: Option<MaterializedConflictData>,
     ) -> Result<MergedTreeValue, SnapshotError> {
         if let Some(current_tree_value) = current_tree_values.as_resolved() {
-            #[cfg(unix)]
-            let _ = current_tree_value; // use the variable
-            let id = self.write_file_to_store(repo_path, disk_path).await?;
+            let current_file_id = match current_tree_value {
+                Some(TreeValue::File { id, .. }) => Some(id),
+                _ => None,
+            };
+            let id = self
+                .write_file_to_store(repo_path, disk_path, current_file_id)
+                .await?;
             // On Windows, we preserve the executable bit from the current tree.
             #[cfg(windows)]
             let executable = {
@@ -1541,12 +1693,47 @@ impl FileSnapshotter<'_> {
         &self,
         path: &RepoPath,
         disk_path: &Path,
+        current_file_id: Option<&FileId>,
     ) -> Result<FileId, SnapshotError> {
         let mut file = File::open(disk_path).map_err(|err| SnapshotError::Other {
             message: format!("Failed to open file {}", disk_path.display()),
             err: err.into(),
         })?;
-        Ok(self.store().write_file(path, &mut file).await?)
+        let attributes = self.git_attributes.attributes(path)?;
+        let lfs_store = self.lfs_store.as_ref().filter(|_| attributes.is_lfs());
+        let content = if let Some(lfs_store) = lfs_store {
+            lfs_store
+                .clean(&mut file)
+                .map_err(|err| SnapshotError::Other {
+                    message: format!("Failed to store LFS object for {}", disk_path.display()),
+                    err: err.into(),
+                })?
+        } else if attributes.text.is_some() || attributes.eol.is_some() {
+            let has_crlf_in_repo = match current_file_id {
+                Some(id) if attributes.is_text_auto() => {
+                    let reader = self.store().read_file_async(path, id).await?;
+                    gitattributes::has_crlf(reader).map_err(|err| BackendError::ReadFile {
+                        path: path.to_owned(),
+                        id: id.clone(),
+                        source: err.into(),
+                    })?
+                }
+                _ => false,
+            };
+            let mut reader = attributes
+                .convert_to_repo(file, has_crlf_in_repo)
+                .map_err(|err| SnapshotError::Other {
+                    message: format!("Failed to read file {}", disk_path.display()),
+                    err: err.into(),
+                })?;
+            return Ok(self.store().write_file(path, &mut reader).await?);
+        } else {
+            return Ok(self.store().write_file(path, &mut file).await?);
+        };
+        Ok(self
+            .store()
+            .write_file(path, &mut content.as_slice())
+            .await?)
     }
 
     async fn write_symlink_to_store(
@@ -1732,11 +1919,13 @@ impl TreeState {
         assert_eq!(removed_stats.updated_files, 0);
         assert_eq!(removed_stats.added_files, 0);
         assert_eq!(removed_stats.skipped_files, 0);
+        assert_eq!(removed_stats.lfs_pointer_files, 0);
         Ok(CheckoutStats {
             updated_files: 0,
             added_files: added_stats.added_files,
             removed_files: removed_stats.removed_files,
             skipped_files: added_stats.skipped_files,
+            lfs_pointer_files: added_stats.lfs_pointer_files,
         })
     }
 
@@ -1754,9 +1943,12 @@ impl TreeState {
             added_files: 0,
             removed_files: 0,
             skipped_files: 0,
+            lfs_pointer_files: 0,
         };
         let mut changed_file_states = Vec::new();
         let mut deleted_files = HashSet::new();
+        let git_attributes = GitAttributesLoader::from_tree(new_tree.clone());
+        let lfs_store = git_lfs_store(&self.store, self.lfs_fetcher.as_ref());
         let mut diff_stream = old_tree
             .diff_stream(new_tree, matcher)
             .map(|TreeDiffEntry { path, values }| async {
@@ -1786,13 +1978,25 @@ impl TreeState {
             // tracked by jj than processing submodules specially. For example,
             // paths excluded by .gitignore can be marked as such so that
             // newly-"unignored" paths won't be snapshotted automatically.
-            if matches!(before.as_normal(), Some(TreeValue::GitSubmodule(_)))
-                && matches!(after, MaterializedTreeValue::GitSubmodule(_))
-            {
-                eprintln!("ignoring git submodule at {path:?}");
-                // Not updating the file state as if there were no diffs. Leave
-                // the state type as FileType::GitSubmodule if it was before.
-                continue;
+            if matches!(before.as_normal(), Some(TreeValue::GitSubmodule(_))) {
+                let disk_path = path.to_fs_path(&self.working_copy_path)?;
+                if let MaterializedTreeValue::GitSubmodule(id) = &after {
+                    // Not updating the file state as if there were no diffs.
+                    // Leave the state type as FileType::GitSubmodule if it was
+                    // before.
+                    if !check_out_git_submodule(&disk_path, id) {
+                        stats.skipped_files += 1;
+                    }
+                    continue;
+                }
+                // The submodule directory can be removed only if it's empty.
+                // Otherwise, keep the submodule state so its contents won't be
+                // snapshotted.
+                if fs::remove_dir(&disk_path).is_err() && disk_path.exists() {
+                    changed_file_states.push((path, FileState::for_gitsubmodule()));
+                    stats.skipped_files += 1;
+                    continue;
+                }
             }
 
             // Create parent directories no matter if after.is_present(). This
@@ -1828,7 +2032,55 @@ impl TreeState {
                     executable,
                     mut reader,
                     ..
-                } => self.write_file(&disk_path, &mut reader, executable)?,
+                } => {
+                    let attributes =
+                        git_attributes
+                            .attributes(&path)
+                            .map_err(|err| CheckoutError::Other {
+                                message: "Failed to read .gitattributes".to_owned(),
+                                err: err.into(),
+                            })?;
+                    let lfs_store = lfs_store.as_ref().filter(|_| attributes.is_lfs());
+                    if let Some(lfs_store) = lfs_store {
+                        let smudged =
+                            lfs_store
+                                .smudge(reader)
+                                .map_err(|err| CheckoutError::Other {
+                                    message: format!(
+                                        "Failed to read file {}",
+                                        path.as_internal_file_string()
+                                    ),
+                                    err: err.into(),
+                                })?;
+                        // Like `GIT_LFS_SKIP_SMUDGE`, the pointer file is
+                        // checked out if the object can't be read.
+                        let mut reader = match smudged {
+                            SmudgedContent::Content(reader) => reader,
+                            SmudgedContent::Pointer { reader, error } => {
+                                if let Some(err) = error {
+                                    tracing::warn!(?err, ?path, "failed to read LFS object");
+                                }
+                                stats.lfs_pointer_files += 1;
+                                reader
+                            }
+                        };
+                        self.write_file(&disk_path, &mut reader, executable)?
+                    } else if attributes.text.is_some() || attributes.eol.is_some() {
+                        let mut reader =
+                            attributes.convert_to_working_copy(reader).map_err(|err| {
+                                CheckoutError::Other {
+                                    message: format!(
+                                        "Failed to read file {}",
+                                        path.as_internal_file_string()
+                                    ),
+                                    err: err.into(),
+                                }
+                            })?;
+                        self.write_file(&disk_path, &mut reader, executable)?
+                    } else {
+                        self.write_file(&disk_path, &mut reader, executable)?
+                    }
+                }
                 MaterializedTreeValue::Symlink { id: _, target } => {
                     if self.symlink_support {
                         self.write_symlink(&disk_path, target)?
@@ -1837,7 +2089,32 @@ impl TreeState {
                     }
                 }
                 MaterializedTreeValue::GitSubmodule(_) => {
-                    eprintln!("ignoring git submodule at {path:?}");
+                    // New submodule has to be initialized separately. Only
+                    // the directory is created here. The directory may have
+                    // been created concurrently, e.g. by `git submodule update`,
+                    // but anything else in its place is left alone.
+                    match fs::create_dir(&disk_path) {
+                        Ok(()) => {}
+                        Err(err) if err.kind() == io::ErrorKind::AlreadyExists => {
+                            let is_dir = disk_path
+                                .symlink_metadata()
+                                .is_ok_and(|metadata| metadata.is_dir());
+                            if !is_dir {
+                                changed_file_states.push((path, FileState::placeholder()));
+                                stats.skipped_files += 1;
+                                continue;
+                            }
+                        }
+                        Err(err) => {
+                            return Err(CheckoutError::Other {
+                                message: format!(
+                                    "Failed to create submodule directory {}",
+                                    disk_path.display()
+                                ),
+                                err: err.into(),
+                            });
+                        }
+                    }
                     FileState::for_gitsubmodule()
                 }
                 MaterializedTreeValue::Tree(_) => {
@@ -1907,10 +2184,7 @@ impl TreeState {
                         TreeValue::Conflict(_id) => {
                             panic!("unexpected conflict entry in diff at {path:?}");
                         }
-                        TreeValue::GitSubmodule(_id) => {
-                            eprintln!("ignoring git submodule at {path:?}");
-                            FileType::GitSubmodule
-                        }
+                        TreeValue::GitSubmodule(_id) => FileType::GitSubmodule,
                         TreeValue::Tree(_id) => {
                             panic!("unexpected tree entry in diff at {path:?}");
                         }
@@ -1964,6 +2238,7 @@ pub struct LocalWorkingCopy {
     state_path: PathBuf,
     checkout_state: OnceCell<CheckoutState>,
     tree_state: OnceCell<TreeState>,
+    lfs_fetcher: Option<Arc<dyn LfsFetcher>>,
 }
 
 impl WorkingCopy for LocalWorkingCopy {
@@ -2007,6 +2282,7 @@ impl WorkingCopy for LocalWorkingCopy {
             // TODO: It's expensive to reload the whole tree. We should copy it from `self` if it
             // hasn't changed.
             tree_state: OnceCell::new(),
+            lfs_fetcher: self.lfs_fetcher.clone(),
         };
         let old_operation_id = wc.operation_id().clone();
         let old_tree_id = wc.tree_id()?.clone();
@@ -2059,6 +2335,7 @@ impl LocalWorkingCopy {
             state_path,
             checkout_state: OnceCell::new(),
             tree_state: OnceCell::with_value(tree_state),
+            lfs_fetcher: None,
         })
     }
 
@@ -2073,9 +2350,19 @@ impl LocalWorkingCopy {
             state_path,
             checkout_state: OnceCell::new(),
             tree_state: OnceCell::new(),
+            lfs_fetcher: None,
         }
     }
 
+    /// Sets `fetcher` to download LFS objects which aren't stored locally.
+    pub fn with_lfs_fetcher(mut self, fetcher: Arc<dyn LfsFetcher>) -> Self {
+        if let Some(tree_state) = self.tree_state.get_mut() {
+            tree_state.lfs_fetcher = Some(fetcher.clone());
+        }
+        self.lfs_fetcher = Some(fetcher);
+        self
+    }
+
     pub fn state_path(&self) -> &Path {
         &self.state_path
     }
@@ -2117,11 +2404,13 @@ impl LocalWorkingCopy {
     fn tree_state(&self) -> Result<&TreeState, WorkingCopyStateError> {
         self.tree_state
             .get_or_try_init(|| {
-                TreeState::load(
+                let mut tree_state = TreeState::load(
                     self.store.clone(),
                     self.working_copy_path.clone(),
                     self.state_path.clone(),
-                )
+                )?;
+                tree_state.lfs_fetcher = self.lfs_fetcher.clone();
+                Ok::<_, TreeStateError>(tree_state)
             })
             .map_err(|err| WorkingCopyStateError {
                 message: "Failed to read working copy state".to_string(),
@@ -2159,6 +2448,18 @@ impl LocalWorkingCopy {
             })
     }
 
+    #[cfg(target_os = "linux")]
+    pub fn query_native_fsmonitor(
+        &self,
+    ) -> Result<(Option<native::Clock>, Option<Vec<PathBuf>>), WorkingCopyStateError> {
+        self.tree_state()?
+            .query_native_fsmonitor()
+            .map_err(|err| WorkingCopyStateError {
+                message: "Failed to query the filesystem monitor".to_string(),
+                err: err.into(),
+            })
+    }
+
     #[cfg(feature = "watchman")]
     pub fn is_watchman_trigger_registered(
         &self,
@@ -2173,7 +2474,29 @@ impl LocalWorkingCopy {
     }
 }
 
-pub struct LocalWorkingCopyFactory {}
+#[derive(Default)]
+pub struct LocalWorkingCopyFactory {
+    lfs_fetcher: Option<Arc<dyn LfsFetcher>>,
+}
+
+impl LocalWorkingCopyFactory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets `fetcher` to download LFS objects which aren't stored locally.
+    pub fn with_lfs_fetcher(mut self, fetcher: Arc<dyn LfsFetcher>) -> Self {
+        self.lfs_fetcher = Some(fetcher);
+        self
+    }
+
+    fn attach_lfs_fetcher(&self, wc: LocalWorkingCopy) -> LocalWorkingCopy {
+        match &self.lfs_fetcher {
+            Some(fetcher) => wc.with_lfs_fetcher(fetcher.clone()),
+            None => wc,
+        }
+    }
+}
 
 impl WorkingCopyFactory for LocalWorkingCopyFactory {
     fn init_working_copy(
@@ -2184,13 +2507,14 @@ impl WorkingCopyFactory for LocalWorkingCopyFactory {
         operation_id: OperationId,
         workspace_id: WorkspaceId,
     ) -> Result<Box<dyn WorkingCopy>, WorkingCopyStateError> {
-        Ok(Box::new(LocalWorkingCopy::init(
+        let wc = LocalWorkingCopy::init(
             store,
             working_copy_path,
             state_path,
             operation_id,
             workspace_id,
-        )?))
+        )?;
+        Ok(Box::new(self.attach_lfs_fetcher(wc)))
     }
 
     fn load_working_copy(
@@ -2199,11 +2523,8 @@ impl WorkingCopyFactory for LocalWorkingCopyFactory {
         working_copy_path: PathBuf,
         state_path: PathBuf,
     ) -> Result<Box<dyn WorkingCopy>, WorkingCopyStateError> {
-        Ok(Box::new(LocalWorkingCopy::load(
-            store,
-            working_copy_path,
-            state_path,
-        )))
+        let wc = LocalWorkingCopy::load(store, working_copy_path, state_path);
+        Ok(Box::new(self.attach_lfs_fetcher(wc)))
     }
 }
 
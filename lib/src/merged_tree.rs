@@ -45,6 +45,8 @@ use crate::backend::TreeValue;
 use crate::copies::CopiesTreeDiffEntry;
 use crate::copies::CopiesTreeDiffStream;
 use crate::copies::CopyRecords;
+use crate::gitattributes::GitAttributes;
+use crate::gitattributes::GitAttributesLoader;
 use crate::matchers::EverythingMatcher;
 use crate::matchers::Matcher;
 use crate::merge::Merge;
@@ -161,7 +163,8 @@ impl MergedTree {
     /// Tries to resolve any conflicts, resolving any conflicts that can be
     /// automatically resolved and leaving the rest unresolved.
     pub fn resolve(&self) -> BackendResult<MergedTree> {
-        let merged = merge_trees(&self.trees)?;
+        let git_attributes = git_attributes_loaders(&self.trees);
+        let merged = merge_trees(&self.trees, &git_attributes)?;
         // If the result can be resolved, then `merge_trees()` above would have returned
         // a resolved merge. However, that function will always preserve the arity of
         // conflicts it cannot resolve. So we simplify the conflict again
@@ -171,7 +174,7 @@ impl MergedTree {
         // particular,  that this last simplification doesn't enable further automatic
         // resolutions
         if cfg!(debug_assertions) {
-            let re_merged = merge_trees(&simplified).unwrap();
+            let re_merged = merge_trees(&simplified, &git_attributes_loaders(&simplified)).unwrap();
             debug_assert_eq!(re_merged, simplified);
         }
         Ok(MergedTree { trees: simplified })
@@ -436,9 +439,18 @@ fn trees_value<'a>(trees: &'a Merge<Tree>, basename: &RepoPathComponent) -> Merg
     value
 }
 
+/// Creates loaders of the `.gitattributes` files of each side of the `trees`.
+/// Attributes such as merge drivers are merged in the same way as the files.
+fn git_attributes_loaders(trees: &Merge<Tree>) -> Merge<GitAttributesLoader> {
+    trees.map(|tree| GitAttributesLoader::from_tree(MergedTree::resolved(tree.clone())))
+}
+
 /// The returned conflict will either be resolved or have the same number of
 /// sides as the input.
-fn merge_trees(merge: &Merge<Tree>) -> BackendResult<Merge<Tree>> {
+fn merge_trees(
+    merge: &Merge<Tree>,
+    git_attributes: &Merge<GitAttributesLoader>,
+) -> BackendResult<Merge<Tree>> {
     if let Some(tree) = merge.resolve_trivial() {
         return Ok(Merge::resolved(tree.clone()));
     }
@@ -454,7 +466,7 @@ fn merge_trees(merge: &Merge<Tree>) -> BackendResult<Merge<Tree>> {
     // TODO: Merge values concurrently
     for (basename, path_merge) in all_merged_tree_entries(merge) {
         let path = dir.join(basename);
-        let path_merge = merge_tree_values(store, &path, &path_merge).block_on()?;
+        let path_merge = merge_tree_values(store, &path, &path_merge, git_attributes).block_on()?;
         match path_merge.into_resolved() {
             Ok(value) => {
                 new_tree.set_or_remove(basename, value);
@@ -492,6 +504,7 @@ async fn merge_tree_values(
     store: &Arc<Store>,
     path: &RepoPath,
     values: &MergedTreeVal<'_>,
+    git_attributes: &Merge<GitAttributesLoader>,
 ) -> BackendResult<MergedTreeValue> {
     if let Some(resolved) = values.resolve_trivial() {
         return Ok(Merge::resolved(resolved.cloned()));
@@ -501,11 +514,19 @@ async fn merge_tree_values(
         // If all sides are trees or missing, merge the trees recursively, treating
         // missing trees as empty.
         let empty_tree_id = store.empty_tree_id();
-        let merged_tree = merge_trees(&trees)?;
+        let merged_tree = merge_trees(&trees, git_attributes)?;
         Ok(merged_tree
             .map(|tree| (tree.id() != empty_tree_id).then(|| TreeValue::Tree(tree.id().clone()))))
     } else {
-        let maybe_resolved = try_resolve_file_values(store, path, values).await?;
+        // Attributes which were changed differently by the sides are ignored
+        let attributes = match git_attributes.try_map(|loader| loader.attributes(path)) {
+            Ok(attributes) => attributes.resolve_trivial().cloned().unwrap_or_default(),
+            Err(err) => {
+                tracing::warn!(?err, ?path, "failed to read .gitattributes");
+                GitAttributes::default()
+            }
+        };
+        let maybe_resolved = try_resolve_file_values(store, path, values, &attributes).await?;
         Ok(maybe_resolved.unwrap_or_else(|| values.cloned()))
     }
 }
@@ -522,7 +543,8 @@ pub async fn resolve_file_values(
         return Ok(Merge::resolved(resolved.clone()));
     }
 
-    let maybe_resolved = try_resolve_file_values(store, path, &values).await?;
+    let maybe_resolved =
+        try_resolve_file_values(store, path, &values, &GitAttributes::default()).await?;
     Ok(maybe_resolved.unwrap_or(values))
 }
 
@@ -530,6 +552,7 @@ async fn try_resolve_file_values<T: Borrow<TreeValue>>(
     store: &Arc<Store>,
     path: &RepoPath,
     values: &Merge<Option<T>>,
+    attributes: &GitAttributes,
 ) -> BackendResult<Option<MergedTreeValue>> {
     // The values may contain trees canceling each other (notably padded absent
     // trees), so we need to simplify them first.
@@ -538,7 +561,7 @@ async fn try_resolve_file_values<T: Borrow<TreeValue>>(
         .simplify();
     // No fast path for simplified.is_resolved(). If it could be resolved, it would
     // have been caught by values.resolve_trivial() above.
-    if let Some(resolved) = try_resolve_file_conflict(store, path, &simplified).await? {
+    if let Some(resolved) = try_resolve_file_conflict(store, path, &simplified, attributes).await? {
         Ok(Some(Merge::normal(resolved)))
     } else {
         // Failed to merge the files, or the paths are not files
@@ -30,8 +30,10 @@ use thiserror::Error;
 
 use crate::backend::CommitId;
 use crate::backend::MillisSinceEpoch;
+use crate::backend::Signature;
 use crate::backend::Timestamp;
 use crate::content_hash::ContentHash;
+use crate::content_hash::DigestUpdate;
 use crate::merge::Merge;
 use crate::object_id::id_type;
 use crate::object_id::HexPrefix;
@@ -214,6 +216,26 @@ pub enum RemoteRefState {
     Tracking,
 }
 
+/// How a commit was classified by the user during bisection.
+#[derive(ContentHash, Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum BisectMark {
+    /// The commit doesn't have the property being searched for.
+    Good,
+    /// The commit has the property being searched for.
+    Bad,
+    /// The commit couldn't be evaluated.
+    Skipped,
+}
+
+/// Message and metadata of an annotated tag.
+#[derive(ContentHash, Clone, Debug, Eq, PartialEq)]
+pub struct TagAnnotation {
+    pub message: String,
+    pub tagger: Signature,
+    /// Signature of the tag object as exported to Git, if the tag is signed.
+    pub signature: Option<Vec<u8>>,
+}
+
 /// Helper to strip redundant `Option<T>` from `RefTarget` lookup result.
 pub trait RefTargetOptionExt {
     type Value;
@@ -264,12 +286,15 @@ pub struct BookmarkTarget<'a> {
 
 /// Represents the way the repo looks at a given time, just like how a Tree
 /// object represents how the file system looks at a given time.
-#[derive(ContentHash, PartialEq, Eq, Clone, Debug)]
+#[derive(PartialEq, Eq, Clone, Debug)]
 pub struct View {
     /// All head commits
     pub head_ids: HashSet<CommitId>,
     pub local_bookmarks: BTreeMap<String, RefTarget>,
     pub tags: BTreeMap<String, RefTarget>,
+    /// Annotations of the tags that are annotated. Each key is also present in
+    /// `tags`.
+    pub tag_annotations: BTreeMap<String, TagAnnotation>,
     pub remote_views: BTreeMap<String, RemoteView>,
     pub git_refs: BTreeMap<String, RefTarget>,
     /// The commit the Git HEAD points to.
@@ -280,6 +305,46 @@ pub struct View {
     // (.jj/working_copy/) has the source of truth about which commit *is* checked out (to be
     // precise: the commit to which we most recently completed an update to).
     pub wc_commit_ids: HashMap<WorkspaceId, CommitId>,
+    /// Commits marked by the in-progress bisection, if any.
+    pub bisect_marks: BTreeMap<CommitId, BisectMark>,
+}
+
+const VIEW_BISECT_MARKS_TAG: u8 = 1;
+const VIEW_TAG_ANNOTATIONS_TAG: u8 = 2;
+
+impl ContentHash for View {
+    fn hash(&self, state: &mut impl DigestUpdate) {
+        let View {
+            head_ids,
+            local_bookmarks,
+            tags,
+            tag_annotations,
+            remote_views,
+            git_refs,
+            git_head,
+            wc_commit_ids,
+            bisect_marks,
+        } = self;
+        head_ids.hash(state);
+        local_bookmarks.hash(state);
+        tags.hash(state);
+        remote_views.hash(state);
+        git_refs.hash(state);
+        git_head.hash(state);
+        wc_commit_ids.hash(state);
+        // Bisection marks and tag annotations were added later. They are omitted
+        // from the hash when empty so the ids of existing views don't change.
+        // Otherwise, each is preceded by its own tag so that one can't be
+        // mistaken for the other.
+        if !bisect_marks.is_empty() {
+            VIEW_BISECT_MARKS_TAG.hash(state);
+            bisect_marks.hash(state);
+        }
+        if !tag_annotations.is_empty() {
+            VIEW_TAG_ANNOTATIONS_TAG.hash(state);
+            tag_annotations.hash(state);
+        }
+    }
 }
 
 impl View {
@@ -292,10 +357,12 @@ impl View {
             head_ids: HashSet::new(),
             local_bookmarks: BTreeMap::new(),
             tags: BTreeMap::new(),
+            tag_annotations: BTreeMap::new(),
             remote_views: BTreeMap::new(),
             git_refs: BTreeMap::new(),
             git_head: RefTarget::absent(),
             wc_commit_ids: HashMap::new(),
+            bisect_marks: BTreeMap::new(),
         }
     }
 
@@ -305,10 +372,12 @@ impl View {
             head_ids: HashSet::from([root_commit_id]),
             local_bookmarks: BTreeMap::new(),
             tags: BTreeMap::new(),
+            tag_annotations: BTreeMap::new(),
             remote_views: BTreeMap::new(),
             git_refs: BTreeMap::new(),
             git_head: RefTarget::absent(),
             wc_commit_ids: HashMap::new(),
+            bisect_marks: BTreeMap::new(),
         }
     }
 }
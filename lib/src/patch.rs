@@ -0,0 +1,1618 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Parses and applies patches in Git's extended unified diff format, which may
+//! be wrapped in email messages as generated by `git format-patch`.
+
+use std::borrow::Cow;
+use std::io::Read as _;
+use std::io::Write as _;
+use std::str;
+use std::sync::Arc;
+
+use bstr::BString;
+use bstr::ByteSlice as _;
+use flate2::read::ZlibDecoder;
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+use itertools::Itertools as _;
+use thiserror::Error;
+
+use crate::backend::BackendError;
+use crate::backend::MergedTreeId;
+use crate::backend::Timestamp;
+use crate::backend::TreeValue;
+use crate::diff::Diff;
+use crate::diff::DiffHunkKind;
+use crate::merge::Merge;
+use crate::merge::MergedTreeValue;
+use crate::merged_tree::MergedTree;
+use crate::merged_tree::MergedTreeBuilder;
+use crate::repo_path::RepoPath;
+use crate::repo_path::RepoPathBuf;
+use crate::store::Store;
+
+/// Error that may occur while parsing patches.
+#[derive(Debug, Error)]
+#[error("Invalid patch at line {line}: {message}")]
+pub struct PatchParseError {
+    /// 1-based line number in the input.
+    pub line: usize,
+    /// Description of the error.
+    pub message: String,
+}
+
+/// Error that may occur while applying patches.
+#[derive(Debug, Error)]
+pub enum PatchApplyError {
+    /// The file to be patched doesn't exist or can't be patched.
+    #[error("Cannot apply patch to {}: {message}", path.as_internal_file_string())]
+    InvalidTarget {
+        /// Path to the file.
+        path: RepoPathBuf,
+        /// Description of the error.
+        message: String,
+    },
+    /// Error while reading or writing files.
+    #[error(transparent)]
+    Backend(#[from] BackendError),
+}
+
+/// Patch parsed from an email message or a plain diff.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Patch {
+    /// Commit metadata if the patch is an email message.
+    pub header: Option<PatchHeader>,
+    /// Changes to files.
+    pub files: Vec<FilePatch>,
+}
+
+/// Commit metadata parsed from email headers.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PatchHeader {
+    /// Author name and email address.
+    pub author: Option<(String, String)>,
+    /// Author date.
+    pub author_timestamp: Option<Timestamp>,
+    /// Commit description built from the subject and the message body.
+    pub description: String,
+}
+
+/// Changes to a single file.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FilePatch {
+    /// Path before the change, or `None` if the file is added.
+    pub old_path: Option<RepoPathBuf>,
+    /// Path after the change, or `None` if the file is deleted.
+    pub new_path: Option<RepoPathBuf>,
+    /// Mode before the change if specified.
+    pub old_mode: Option<FileMode>,
+    /// Mode after the change if specified.
+    pub new_mode: Option<FileMode>,
+    /// Whether the old path should be kept if the paths differ.
+    pub is_copy: bool,
+    /// Changes to the file content.
+    pub content: FilePatchContent,
+}
+
+/// Type of file as specified by Git file mode.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum FileMode {
+    /// Non-executable file (100644).
+    Regular,
+    /// Executable file (100755).
+    Executable,
+    /// Symbolic link (120000).
+    Symlink,
+}
+
+/// Changes to file content.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum FilePatchContent {
+    /// Text hunks. Empty if the content is unchanged.
+    Text(Vec<PatchHunk>),
+    /// Git binary patch.
+    Binary(BinaryPatch),
+    /// Binary change without data ("Binary files ... differ").
+    MissingBinary,
+}
+
+/// Decompressed data of Git binary patch.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum BinaryPatch {
+    /// Full contents of the new file.
+    Literal(Vec<u8>),
+    /// Delta to be applied to the old file.
+    Delta(Vec<u8>),
+}
+
+/// Hunk of text changes.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PatchHunk {
+    /// 1-based line number of the first old line. If the hunk has no old lines,
+    /// this is the number of the line preceding the change.
+    pub old_start: usize,
+    /// 1-based line number of the first new line. If the hunk has no new lines,
+    /// this is the number of the line preceding the change.
+    pub new_start: usize,
+    /// Lines including the newline characters.
+    pub lines: Vec<(PatchLineType, BString)>,
+}
+
+/// Type of line in hunk.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PatchLineType {
+    /// Line which exists in both old and new contents.
+    Context,
+    /// Line which exists only in the old contents.
+    Removed,
+    /// Line which exists only in the new contents.
+    Added,
+}
+
+impl PatchHunk {
+    /// Returns lines in the old contents.
+    pub fn old_lines(&self) -> impl Iterator<Item = &[u8]> {
+        self.lines
+            .iter()
+            .filter(|(line_type, _)| *line_type != PatchLineType::Added)
+            .map(|(_, line)| line.as_ref())
+    }
+
+    /// Returns lines in the new contents.
+    pub fn new_lines(&self) -> impl Iterator<Item = &[u8]> {
+        self.lines
+            .iter()
+            .filter(|(line_type, _)| *line_type != PatchLineType::Removed)
+            .map(|(_, line)| line.as_ref())
+    }
+}
+
+fn parse_error(index: usize, message: impl Into<String>) -> PatchParseError {
+    PatchParseError {
+        line: index + 1,
+        message: message.into(),
+    }
+}
+
+fn trim_newline(line: &[u8]) -> &[u8] {
+    let line = line.strip_suffix(b"\n").unwrap_or(line);
+    line.strip_suffix(b"\r").unwrap_or(line)
+}
+
+/// Returns true if the line looks like "Name: value" email header.
+fn is_header_line(line: &[u8]) -> bool {
+    let Some(pos) = line.find_byte(b':') else {
+        return false;
+    };
+    pos > 0
+        && line[..pos]
+            .iter()
+            .all(|&b| b.is_ascii_alphanumeric() || b == b'-')
+}
+
+/// Returns true if `lines[index]` is an mbox "From <sender> <date>" separator
+/// line followed by email headers.
+fn is_mbox_separator(lines: &[&[u8]], index: usize) -> bool {
+    let Some(rest) = lines[index].strip_prefix(b"From ") else {
+        return false;
+    };
+    let Some((sender, date)) = trim_newline(rest).split_once_str(" ") else {
+        return false;
+    };
+    // The date is in asctime() format, e.g. "Mon Sep 17 00:00:00 2001".
+    let is_date = date.to_str().is_ok_and(|date| {
+        let mut words = date.split_whitespace();
+        let weekday = words.next().unwrap_or_default();
+        let date = words.join(" ");
+        weekday.parse::<chrono::Weekday>().is_ok()
+            && chrono::NaiveDateTime::parse_from_str(&date, "%b %e %H:%M:%S %Y").is_ok()
+    });
+    !sender.is_empty()
+        && is_date
+        && lines
+            .get(index + 1)
+            .is_some_and(|line| is_header_line(line))
+}
+
+/// Parses patches from `input`, which may be an mbox containing series of
+/// email messages, a single email message, or a plain diff.
+pub fn parse_patches(input: &[u8]) -> Result<Vec<Patch>, PatchParseError> {
+    let lines = input.split_inclusive(|b| *b == b'\n').collect::<Vec<_>>();
+    let mut starts = (0..lines.len())
+        .filter(|&i| is_mbox_separator(&lines, i))
+        .map(|i| i + 1)
+        .collect::<Vec<_>>();
+    if starts.is_empty() {
+        let is_email = lines.first().is_some_and(|line| {
+            let line = line.to_ascii_lowercase();
+            line.starts_with(b"from:") || line.starts_with(b"subject:")
+        });
+        if !is_email {
+            let files = parse_diff(&lines, 0)?;
+            return Ok(vec![Patch {
+                header: None,
+                files,
+            }]);
+        }
+        starts.push(0);
+    }
+    let ends = starts
+        .iter()
+        .skip(1)
+        .map(|&start| start - 1)
+        .chain([lines.len()]);
+    starts
+        .iter()
+        .zip(ends)
+        .map(|(&start, end)| parse_email(&lines[start..end], start))
+        .collect()
+}
+
+/// Parses email message consisting of `lines`, which start at `offset` in the
+/// input.
+fn parse_email(lines: &[&[u8]], offset: usize) -> Result<Patch, PatchParseError> {
+    let mut headers: Vec<(String, String)> = vec![];
+    let mut index = 0;
+    while let Some(line) = lines.get(index) {
+        let line = trim_newline(line);
+        index += 1;
+        if line.is_empty() {
+            break;
+        } else if line.starts_with(b" ") || line.starts_with(b"\t") {
+            let Some((_, value)) = headers.last_mut() else {
+                return Err(parse_error(
+                    offset + index - 1,
+                    "Unexpected header continuation",
+                ));
+            };
+            value.push(' ');
+            value.push_str(line.trim().to_str_lossy().as_ref());
+        } else if let Some((name, value)) = line.split_once_str(":") {
+            let name = name.to_str_lossy().to_ascii_lowercase();
+            let value = value.trim().to_str_lossy().into_owned();
+            headers.push((name, value));
+        } else {
+            return Err(parse_error(offset + index - 1, "Invalid email header"));
+        }
+    }
+    let header_value = |name: &str| {
+        headers
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, value)| decode_header_value(value))
+    };
+
+    let author = header_value("from").map(|value| parse_address(&value));
+    let author_timestamp = header_value("date")
+        .map(|value| {
+            chrono::DateTime::parse_from_rfc2822(&value)
+                .map(Timestamp::from_datetime)
+                .map_err(|err| parse_error(offset, format!("Invalid date {value:?}: {err}")))
+        })
+        .transpose()?;
+    let subject = strip_subject_prefixes(&header_value("subject").unwrap_or_default()).to_owned();
+
+    let body_lines = &lines[index..];
+    let diff_start = body_lines
+        .iter()
+        .position(|line| {
+            trim_newline(line) == b"---"
+                || line.starts_with(b"diff --git ")
+                || line.starts_with(b"Index: ")
+        })
+        .unwrap_or(body_lines.len());
+    let body = body_lines[..diff_start].concat();
+    let body = body.to_str_lossy();
+    let body = body.trim();
+    let mut description = subject;
+    if !body.is_empty() {
+        if !description.is_empty() {
+            description.push_str("\n\n");
+        }
+        description.push_str(body);
+    }
+    if !description.is_empty() {
+        description.push('\n');
+    }
+    let files = parse_diff(&body_lines[diff_start..], offset + index + diff_start)?;
+    Ok(Patch {
+        header: Some(PatchHeader {
+            author,
+            author_timestamp,
+            description,
+        }),
+        files,
+    })
+}
+
+/// Decodes RFC 2047 "=?charset?Q?...?=" encoded words. Only UTF-8 and ASCII
+/// texts in Q encoding are supported.
+fn decode_header_value(value: &str) -> String {
+    let mut decoded = String::new();
+    let mut rest = value;
+    let mut last_was_encoded = false;
+    while let Some(start) = rest.find("=?") {
+        let (before, encoded) = rest.split_at(start);
+        let decoded_word = encoded[2..]
+            .split_once("?Q?")
+            .or_else(|| encoded[2..].split_once("?q?"));
+        let Some((text, remainder)) = decoded_word
+            .and_then(|(_charset, text)| text.split_once("?="))
+            .map(|(text, remainder)| (decode_q_encoding(text), remainder))
+        else {
+            break;
+        };
+        // Whitespace between encoded words is ignored.
+        if !(last_was_encoded && before.trim().is_empty()) {
+            decoded.push_str(before);
+        }
+        decoded.push_str(&text);
+        rest = remainder;
+        last_was_encoded = true;
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+/// Encodes `value` as RFC 2047 "=?UTF-8?q?...?=" words if it contains
+/// non-ASCII characters or line breaks. Long values are folded into multiple
+/// lines.
+pub fn encode_header_value(value: &str) -> String {
+    let needs_encoding =
+        value.contains("=?") || value.bytes().any(|b| !(b.is_ascii_graphic() || b == b' '));
+    if !needs_encoding {
+        return value.to_owned();
+    }
+    // Encoded words are limited to 75 characters, and must contain whole
+    // characters.
+    const PREFIX: &str = "=?UTF-8?q?";
+    const SUFFIX: &str = "?=";
+    const MAX_TEXT_LEN: usize = 75 - PREFIX.len() - SUFFIX.len();
+    let mut words = vec![];
+    let mut text = String::new();
+    for c in value.chars() {
+        let mut encoded = String::new();
+        let mut buf = [0; 4];
+        for &b in c.encode_utf8(&mut buf).as_bytes() {
+            match b {
+                b' ' => encoded.push('_'),
+                b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'!' | b'*' | b'+' | b'-' | b'/' => {
+                    encoded.push(char::from(b));
+                }
+                _ => encoded.push_str(&format!("={b:02X}")),
+            }
+        }
+        if text.len() + encoded.len() > MAX_TEXT_LEN {
+            words.push(format!("{PREFIX}{text}{SUFFIX}"));
+            text.clear();
+        }
+        text.push_str(&encoded);
+    }
+    words.push(format!("{PREFIX}{text}{SUFFIX}"));
+    words.join("\n ")
+}
+
+fn decode_q_encoding(text: &str) -> String {
+    let mut bytes = Vec::new();
+    let mut iter = text.bytes();
+    while let Some(b) = iter.next() {
+        match b {
+            b'_' => bytes.push(b' '),
+            b'=' => {
+                let hex = [iter.next().unwrap_or(b'0'), iter.next().unwrap_or(b'0')];
+                let value = str::from_utf8(&hex)
+                    .ok()
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+                bytes.extend(value);
+            }
+            _ => bytes.push(b),
+        }
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+/// Parses "Name <email>" address.
+fn parse_address(value: &str) -> (String, String) {
+    if let Some((name, email)) = value
+        .strip_suffix('>')
+        .and_then(|value| value.rsplit_once('<'))
+    {
+        let name = name.trim().trim_matches('"');
+        (name.to_owned(), email.trim().to_owned())
+    } else {
+        (String::new(), value.trim().to_owned())
+    }
+}
+
+/// Strips "[PATCH n/m]" and "Re:" prefixes from the email subject.
+fn strip_subject_prefixes(mut subject: &str) -> &str {
+    loop {
+        subject = subject.trim_start();
+        if subject.starts_with('[') {
+            if let Some((_, rest)) = subject.split_once(']') {
+                subject = rest;
+                continue;
+            }
+        } else if subject.len() >= 3 && subject[..3].eq_ignore_ascii_case("re:") {
+            subject = &subject[3..];
+            continue;
+        }
+        return subject.trim_end();
+    }
+}
+
+/// Parses changes to files in `lines`, which start at `offset` in the input.
+/// Lines which don't belong to any file are ignored.
+fn parse_diff(lines: &[&[u8]], offset: usize) -> Result<Vec<FilePatch>, PatchParseError> {
+    let mut files = vec![];
+    let mut index = 0;
+    while index < lines.len() {
+        let line = lines[index];
+        if line.starts_with(b"diff --git ") {
+            let (file, next_index) = parse_git_file_patch(lines, index, offset)?;
+            files.push(file);
+            index = next_index;
+        } else if line.starts_with(b"--- ")
+            && lines
+                .get(index + 1)
+                .is_some_and(|line| line.starts_with(b"+++ "))
+        {
+            let old_path = parse_path(&line[4..], index + offset)?;
+            let new_path = parse_path(&lines[index + 1][4..], index + 1 + offset)?;
+            let (hunks, next_index) = parse_hunks(lines, index + 2, offset)?;
+            files.push(FilePatch {
+                old_path,
+                new_path,
+                old_mode: None,
+                new_mode: None,
+                is_copy: false,
+                content: FilePatchContent::Text(hunks),
+            });
+            index = next_index;
+        } else {
+            index += 1;
+        }
+    }
+    Ok(files)
+}
+
+fn parse_git_file_patch(
+    lines: &[&[u8]],
+    start: usize,
+    offset: usize,
+) -> Result<(FilePatch, usize), PatchParseError> {
+    let header = trim_newline(&lines[start]["diff --git ".len()..]);
+    let (mut old_path, mut new_path) = parse_git_header_paths(header, start + offset)?;
+    let mut old_mode = None;
+    let mut new_mode = None;
+    let mut is_copy = false;
+    let mut is_added = false;
+    let mut is_deleted = false;
+    let mut index = start + 1;
+    while let Some(line) = lines.get(index) {
+        let line = trim_newline(line);
+        let line_offset = index + offset;
+        if let Some(mode) = line.strip_prefix(b"old mode ") {
+            old_mode = Some(parse_mode(mode, line_offset)?);
+        } else if let Some(mode) = line.strip_prefix(b"new mode ") {
+            new_mode = Some(parse_mode(mode, line_offset)?);
+        } else if let Some(mode) = line.strip_prefix(b"deleted file mode ") {
+            old_mode = Some(parse_mode(mode, line_offset)?);
+            is_deleted = true;
+        } else if let Some(mode) = line.strip_prefix(b"new file mode ") {
+            new_mode = Some(parse_mode(mode, line_offset)?);
+            is_added = true;
+        } else if let Some(path) = line
+            .strip_prefix(b"rename from ")
+            .or_else(|| line.strip_prefix(b"copy from "))
+        {
+            old_path = parse_bare_path(path, line_offset)?;
+        } else if let Some(path) = line
+            .strip_prefix(b"rename to ")
+            .or_else(|| line.strip_prefix(b"copy to "))
+        {
+            new_path = parse_bare_path(path, line_offset)?;
+            is_copy = line.starts_with(b"copy ");
+        } else if let Some(index_line) = line.strip_prefix(b"index ") {
+            // "index <old>..<new> <mode>"
+            if let Some((_, mode)) = index_line.split_once_str(" ") {
+                let mode = parse_mode(mode, line_offset)?;
+                old_mode = old_mode.or(Some(mode));
+                new_mode = new_mode.or(Some(mode));
+            }
+        } else if !(line.starts_with(b"similarity index ")
+            || line.starts_with(b"dissimilarity index "))
+        {
+            break;
+        }
+        index += 1;
+    }
+
+    let content;
+    let line = lines.get(index).copied().unwrap_or_default();
+    if line.starts_with(b"--- ")
+        && lines
+            .get(index + 1)
+            .is_some_and(|line| line.starts_with(b"+++ "))
+    {
+        // These paths are more reliable than the ones in "diff --git" header,
+        // which may be ambiguous if the paths contain spaces.
+        if let Some(path) = parse_path(&line[4..], index + offset)? {
+            old_path = Some(path);
+        }
+        if let Some(path) = parse_path(&lines[index + 1][4..], index + 1 + offset)? {
+            new_path = Some(path);
+        }
+        let (hunks, next_index) = parse_hunks(lines, index + 2, offset)?;
+        content = FilePatchContent::Text(hunks);
+        index = next_index;
+    } else if trim_newline(line) == b"GIT binary patch" {
+        let (patch, next_index) = parse_binary_patch(lines, index + 1, offset)?;
+        content = FilePatchContent::Binary(patch);
+        index = next_index;
+    } else if line.starts_with(b"Binary files ") {
+        content = FilePatchContent::MissingBinary;
+        index += 1;
+    } else {
+        content = FilePatchContent::Text(vec![]);
+    }
+
+    if is_added {
+        old_path = None;
+        old_mode = None;
+    }
+    if is_deleted {
+        new_path = None;
+        new_mode = None;
+    }
+    let file = FilePatch {
+        old_path,
+        new_path,
+        old_mode,
+        new_mode,
+        is_copy,
+        content,
+    };
+    Ok((file, index))
+}
+
+/// Parses "a/<path> b/<path>" in "diff --git" header.
+fn parse_git_header_paths(
+    header: &[u8],
+    line_offset: usize,
+) -> Result<(Option<RepoPathBuf>, Option<RepoPathBuf>), PatchParseError> {
+    if header.starts_with(b"\"") {
+        let (old, rest) =
+            unquote_path(header).ok_or_else(|| parse_error(line_offset, "Invalid quoted path"))?;
+        let old_path = parse_bare_path(strip_path_prefix(&old), line_offset)?;
+        let new_path = parse_path(rest.trim_start(), line_offset)?;
+        return Ok((old_path, new_path));
+    }
+    // If the paths are the same, the header should be split in the middle.
+    // Otherwise, the paths will be found in the other headers.
+    let middle = header.len() / 2;
+    if header.len() % 2 == 1 && header[middle] == b' ' {
+        let old = &header[..middle];
+        let new = &header[middle + 1..];
+        if strip_path_prefix(old) == strip_path_prefix(new) {
+            let path = parse_path(old, line_offset)?;
+            return Ok((path.clone(), path));
+        }
+    }
+    let (old, new) = header
+        .split_once_str(" b/")
+        .ok_or_else(|| parse_error(line_offset, "Invalid diff header"))?;
+    let old_path = parse_path(old, line_offset)?;
+    let new_path = parse_bare_path(new, line_offset)?;
+    Ok((old_path, new_path))
+}
+
+fn strip_path_prefix(path: &[u8]) -> &[u8] {
+    path.strip_prefix(b"a/")
+        .or_else(|| path.strip_prefix(b"b/"))
+        .unwrap_or(path)
+}
+
+/// Parses path in "---"/"+++" line, which may be quoted, prefixed with "a/" or
+/// "b/", or followed by a tab and timestamp. Returns `None` for "/dev/null".
+fn parse_path(text: &[u8], line_offset: usize) -> Result<Option<RepoPathBuf>, PatchParseError> {
+    let text = trim_newline(text);
+    let path: BString = if text.starts_with(b"\"") {
+        let (path, _) =
+            unquote_path(text).ok_or_else(|| parse_error(line_offset, "Invalid quoted path"))?;
+        path
+    } else {
+        let path = text.split_str("\t").next().unwrap_or_default();
+        path.trim_end().into()
+    };
+    if path == "/dev/null" {
+        return Ok(None);
+    }
+    parse_bare_path(strip_path_prefix(&path), line_offset)
+}
+
+/// Parses unprefixed path which may be quoted.
+fn parse_bare_path(
+    text: &[u8],
+    line_offset: usize,
+) -> Result<Option<RepoPathBuf>, PatchParseError> {
+    let text = trim_newline(text);
+    let path: Cow<[u8]> = if text.starts_with(b"\"") {
+        let (path, _) =
+            unquote_path(text).ok_or_else(|| parse_error(line_offset, "Invalid quoted path"))?;
+        Cow::Owned(path.into())
+    } else {
+        Cow::Borrowed(text)
+    };
+    let path = path
+        .to_str()
+        .map_err(|_| parse_error(line_offset, "Path is not valid UTF-8"))?;
+    let path = RepoPathBuf::from_relative_path(path)
+        .map_err(|err| parse_error(line_offset, format!("Invalid path {path:?}: {err}")))?;
+    Ok(Some(path))
+}
+
+/// Unquotes C-style quoted path. Returns the path and the remainder.
+fn unquote_path(text: &[u8]) -> Option<(BString, &[u8])> {
+    let mut path = Vec::new();
+    let mut iter = text.strip_prefix(b"\"")?.iter().enumerate();
+    while let Some((i, &b)) = iter.next() {
+        match b {
+            b'"' => return Some((path.into(), &text[i + 2..])),
+            b'\\' => {
+                let (_, &escaped) = iter.next()?;
+                match escaped {
+                    b'a' => path.push(0x07),
+                    b'b' => path.push(0x08),
+                    b't' => path.push(b'\t'),
+                    b'n' => path.push(b'\n'),
+                    b'v' => path.push(0x0b),
+                    b'f' => path.push(0x0c),
+                    b'r' => path.push(b'\r'),
+                    b'0'..=b'7' => {
+                        let (_, &d2) = iter.next()?;
+                        let (_, &d3) = iter.next()?;
+                        let octal = [escaped, d2, d3];
+                        let value = u8::from_str_radix(str::from_utf8(&octal).ok()?, 8).ok()?;
+                        path.push(value);
+                    }
+                    _ => path.push(escaped),
+                }
+            }
+            _ => path.push(b),
+        }
+    }
+    None
+}
+
+fn parse_mode(text: &[u8], line_offset: usize) -> Result<FileMode, PatchParseError> {
+    match text.trim() {
+        b"100644" | b"100664" => Ok(FileMode::Regular),
+        b"100755" => Ok(FileMode::Executable),
+        b"120000" => Ok(FileMode::Symlink),
+        mode => Err(parse_error(
+            line_offset,
+            format!("Unsupported file mode {}", mode.to_str_lossy()),
+        )),
+    }
+}
+
+/// Parses hunks starting at `lines[start]`. Returns the hunks and the index of
+/// the next line.
+fn parse_hunks(
+    lines: &[&[u8]],
+    start: usize,
+    offset: usize,
+) -> Result<(Vec<PatchHunk>, usize), PatchParseError> {
+    let mut hunks = vec![];
+    let mut index = start;
+    while let Some(line) = lines.get(index).filter(|line| line.starts_with(b"@@ -")) {
+        let (old_start, mut old_count, new_start, mut new_count) = parse_hunk_header(line)
+            .ok_or_else(|| parse_error(index + offset, "Invalid hunk header"))?;
+        index += 1;
+        let mut hunk = PatchHunk {
+            old_start,
+            new_start,
+            lines: vec![],
+        };
+        while old_count > 0 || new_count > 0 {
+            let Some(line) = lines.get(index) else {
+                return Err(parse_error(index + offset, "Unexpected end of hunk"));
+            };
+            let line_type = match line.first() {
+                Some(b' ') | Some(b'\n') | Some(b'\r') => PatchLineType::Context,
+                Some(b'-') => PatchLineType::Removed,
+                Some(b'+') => PatchLineType::Added,
+                Some(b'\\') => {
+                    strip_last_newline(&mut hunk, index + offset)?;
+                    index += 1;
+                    continue;
+                }
+                _ => return Err(parse_error(index + offset, "Unexpected line in hunk")),
+            };
+            let (num_old, num_new) = match line_type {
+                PatchLineType::Context => (1, 1),
+                PatchLineType::Removed => (1, 0),
+                PatchLineType::Added => (0, 1),
+            };
+            if old_count < num_old || new_count < num_new {
+                return Err(parse_error(index + offset, "Hunk is longer than specified"));
+            }
+            old_count -= num_old;
+            new_count -= num_new;
+            // An empty context line may have lost its leading space.
+            let content = if line.starts_with(b"\n") || line.starts_with(b"\r\n") {
+                line
+            } else {
+                &line[1..]
+            };
+            hunk.lines.push((line_type, content.into()));
+            index += 1;
+        }
+        if lines.get(index).is_some_and(|line| line.starts_with(b"\\")) {
+            strip_last_newline(&mut hunk, index + offset)?;
+            index += 1;
+        }
+        hunks.push(hunk);
+    }
+    Ok((hunks, index))
+}
+
+/// Processes "\ No newline at end of file" marker.
+fn strip_last_newline(hunk: &mut PatchHunk, line_offset: usize) -> Result<(), PatchParseError> {
+    let (_, line) = hunk
+        .lines
+        .last_mut()
+        .ok_or_else(|| parse_error(line_offset, "Unexpected no-newline marker"))?;
+    let len = trim_newline(line).len();
+    line.truncate(len);
+    Ok(())
+}
+
+/// Parses "@@ -l,s +l,s @@" header.
+fn parse_hunk_header(line: &[u8]) -> Option<(usize, usize, usize, usize)> {
+    let line = line.to_str().ok()?;
+    let ranges = line.strip_prefix("@@ -")?.split(" @@").next()?;
+    let (old, new) = ranges.split_once(" +")?;
+    let parse_range = |range: &str| -> Option<(usize, usize)> {
+        match range.split_once(',') {
+            Some((start, count)) => Some((start.parse().ok()?, count.parse().ok()?)),
+            None => Some((range.parse().ok()?, 1)),
+        }
+    };
+    let (old_start, old_count) = parse_range(old)?;
+    let (new_start, new_count) = parse_range(new)?;
+    Some((old_start, old_count, new_start, new_count))
+}
+
+const BASE85_ALPHABET: &[u8; 85] =
+    b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz!#$%&()*+-;<=>?@^_`{|}~";
+
+/// Parses "literal"/"delta" sections following "GIT binary patch" line. Only
+/// the first (forward) section is used.
+fn parse_binary_patch(
+    lines: &[&[u8]],
+    start: usize,
+    offset: usize,
+) -> Result<(BinaryPatch, usize), PatchParseError> {
+    let (patch, mut index) = parse_binary_section(lines, start, offset)?;
+    // Skip the reverse section if any.
+    if lines
+        .get(index)
+        .is_some_and(|line| line.starts_with(b"literal ") || line.starts_with(b"delta "))
+    {
+        (_, index) = parse_binary_section(lines, index, offset)?;
+    }
+    Ok((patch, index))
+}
+
+fn parse_binary_section(
+    lines: &[&[u8]],
+    start: usize,
+    offset: usize,
+) -> Result<(BinaryPatch, usize), PatchParseError> {
+    let header = trim_newline(lines.get(start).copied().unwrap_or_default());
+    let (kind, size) = header
+        .split_once_str(" ")
+        .ok_or_else(|| parse_error(start + offset, "Invalid binary patch header"))?;
+    let size: usize = size
+        .to_str()
+        .ok()
+        .and_then(|size| size.parse().ok())
+        .ok_or_else(|| parse_error(start + offset, "Invalid binary patch size"))?;
+    let mut compressed = Vec::new();
+    let mut index = start + 1;
+    while let Some(line) = lines.get(index) {
+        let line = trim_newline(line);
+        index += 1;
+        if line.is_empty() {
+            break;
+        }
+        decode_base85_line(line, &mut compressed)
+            .ok_or_else(|| parse_error(index - 1 + offset, "Invalid binary patch data"))?;
+    }
+    let mut data = Vec::new();
+    ZlibDecoder::new(compressed.as_slice())
+        .read_to_end(&mut data)
+        .map_err(|err| parse_error(start + offset, format!("Invalid binary patch data: {err}")))?;
+    if data.len() != size {
+        return Err(parse_error(start + offset, "Binary patch size mismatch"));
+    }
+    match kind {
+        b"literal" => Ok((BinaryPatch::Literal(data), index)),
+        b"delta" => Ok((BinaryPatch::Delta(data), index)),
+        _ => Err(parse_error(start + offset, "Invalid binary patch header")),
+    }
+}
+
+fn decode_base85_line(line: &[u8], out: &mut Vec<u8>) -> Option<()> {
+    let (&len_char, encoded) = line.split_first()?;
+    let len = match len_char {
+        b'A'..=b'Z' => len_char - b'A' + 1,
+        b'a'..=b'z' => len_char - b'a' + 27,
+        _ => return None,
+    };
+    if encoded.len() % 5 != 0 {
+        return None;
+    }
+    let mut decoded = Vec::with_capacity(encoded.len() / 5 * 4);
+    for chunk in encoded.chunks(5) {
+        let mut value: u32 = 0;
+        for &c in chunk {
+            let digit = BASE85_ALPHABET.iter().position(|&a| a == c)?;
+            value = value.checked_mul(85)?.checked_add(digit as u32)?;
+        }
+        decoded.extend_from_slice(&value.to_be_bytes());
+    }
+    out.extend_from_slice(decoded.get(..usize::from(len))?);
+    Some(())
+}
+
+fn encode_base85_line(data: &[u8], out: &mut String) {
+    let len = u8::try_from(data.len()).unwrap();
+    out.push(char::from(if len <= 26 {
+        b'A' + len - 1
+    } else {
+        b'a' + len - 27
+    }));
+    for chunk in data.chunks(4) {
+        let mut bytes = [0; 4];
+        bytes[..chunk.len()].copy_from_slice(chunk);
+        let mut value = u32::from_be_bytes(bytes);
+        let mut encoded = [0; 5];
+        for c in encoded.iter_mut().rev() {
+            *c = BASE85_ALPHABET[(value % 85) as usize];
+            value /= 85;
+        }
+        out.extend(encoded.map(char::from));
+    }
+    out.push('\n');
+}
+
+fn format_binary_literal(data: &[u8], out: &mut String) {
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(data).unwrap();
+    let compressed = encoder.finish().unwrap();
+    out.push_str(&format!("literal {}\n", data.len()));
+    for chunk in compressed.chunks(52) {
+        encode_base85_line(chunk, out);
+    }
+    out.push('\n');
+}
+
+/// Formats Git binary patch which replaces `old` contents with `new`.
+pub fn format_git_binary_patch(old: &[u8], new: &[u8]) -> String {
+    let mut out = "GIT binary patch\n".to_owned();
+    format_binary_literal(new, &mut out);
+    format_binary_literal(old, &mut out);
+    out
+}
+
+/// Applies Git delta to `base` contents.
+fn apply_delta(base: &[u8], delta: &[u8]) -> Option<Vec<u8>> {
+    fn read_size(data: &mut &[u8]) -> Option<usize> {
+        let mut size = 0;
+        let mut shift = 0;
+        loop {
+            let (&b, rest) = data.split_first()?;
+            *data = rest;
+            size |= usize::from(b & 0x7f).checked_shl(shift)?;
+            shift += 7;
+            if b & 0x80 == 0 {
+                return Some(size);
+            }
+        }
+    }
+
+    let mut data = delta;
+    let base_size = read_size(&mut data)?;
+    let result_size = read_size(&mut data)?;
+    if base_size != base.len() {
+        return None;
+    }
+    let mut result = Vec::with_capacity(result_size);
+    while let Some((&cmd, rest)) = data.split_first() {
+        data = rest;
+        if cmd & 0x80 != 0 {
+            let mut read_bytes = |mask_start: u8, count: u8| -> Option<usize> {
+                let mut value = 0;
+                for i in 0..count {
+                    if cmd & (mask_start << i) != 0 {
+                        let (&b, rest) = data.split_first()?;
+                        data = rest;
+                        value |= usize::from(b) << (8 * i);
+                    }
+                }
+                Some(value)
+            };
+            let copy_offset = read_bytes(0x01, 4)?;
+            let copy_size = match read_bytes(0x10, 3)? {
+                0 => 0x10000,
+                size => size,
+            };
+            result.extend_from_slice(base.get(copy_offset..copy_offset.checked_add(copy_size)?)?);
+        } else if cmd != 0 {
+            let inserted = data.get(..usize::from(cmd))?;
+            result.extend_from_slice(inserted);
+            data = &data[inserted.len()..];
+        } else {
+            return None;
+        }
+    }
+    (result.len() == result_size).then_some(result)
+}
+
+/// Default number of context lines which may be ignored when locating hunks.
+pub const DEFAULT_FUZZ: usize = 2;
+
+/// Finds the line index where `old_lines` appear in `lines[min..]`. The
+/// position closest to `expected` is preferred.
+fn find_hunk_position(
+    lines: &[&[u8]],
+    old_lines: &[&[u8]],
+    min: usize,
+    expected: usize,
+) -> Option<usize> {
+    let matches_at = |start: usize| {
+        start >= min
+            && start + old_lines.len() <= lines.len()
+            && lines[start..start + old_lines.len()] == *old_lines
+    };
+    if old_lines.is_empty() {
+        return Some(expected.clamp(min, lines.len()));
+    }
+    if matches_at(expected) {
+        return Some(expected);
+    }
+    // Use the line diff to find candidate offsets instead of comparing the hunk
+    // at every position.
+    let content = lines[min..].concat();
+    let old_text = old_lines.concat();
+    let content_line_starts = line_starts(&lines[min..]);
+    let old_line_starts = line_starts(old_lines);
+    let diff = Diff::by_line([&content, &old_text]);
+    diff.hunk_ranges()
+        .filter(|hunk| hunk.kind == DiffHunkKind::Matching)
+        .filter_map(|hunk| {
+            let content_line = content_line_starts
+                .binary_search(&hunk.ranges[0].start)
+                .ok()?;
+            let old_line = old_line_starts.binary_search(&hunk.ranges[1].start).ok()?;
+            (min + content_line).checked_sub(old_line)
+        })
+        .filter(|&start| matches_at(start))
+        .min_by_key(|&start| start.abs_diff(expected))
+}
+
+/// Returns byte offsets of the starts of `lines`.
+fn line_starts(lines: &[&[u8]]) -> Vec<usize> {
+    lines
+        .iter()
+        .scan(0, |offset, line| {
+            let start = *offset;
+            *offset += line.len();
+            Some(start)
+        })
+        .collect()
+}
+
+/// Applies text `hunks` to `content`.
+///
+/// If a hunk doesn't match at the line number recorded in the patch, it is
+/// searched for at other offsets, ignoring up to `fuzz` leading and trailing
+/// context lines if necessary. If a hunk doesn't match the content at all, the
+/// result is a conflict between the content and the new lines of the hunk,
+/// with the old lines of the hunk as the base.
+pub fn apply_hunks(content: &[u8], hunks: &[PatchHunk], fuzz: usize) -> Merge<BString> {
+    let lines = content.split_inclusive(|b| *b == b'\n').collect::<Vec<_>>();
+    // [current, base, patched] contents
+    let mut outputs: [Vec<u8>; 3] = Default::default();
+    let mut has_conflict = false;
+    let mut pos = 0;
+    // Difference between the actual and the recorded line numbers
+    let mut offset: isize = 0;
+    for hunk in hunks {
+        let declared_start = if hunk.lines.iter().all(|(t, _)| *t == PatchLineType::Added) {
+            hunk.old_start
+        } else {
+            hunk.old_start.saturating_sub(1)
+        };
+        let expected = declared_start.saturating_add_signed(offset);
+        let leading_context = hunk
+            .lines
+            .iter()
+            .take_while(|(t, _)| *t == PatchLineType::Context)
+            .count();
+        let trailing_context = hunk.lines[leading_context..]
+            .iter()
+            .rev()
+            .take_while(|(t, _)| *t == PatchLineType::Context)
+            .count();
+        let found = (0..=fuzz).find_map(|fuzz| {
+            let skip_start = fuzz.min(leading_context);
+            let skip_end = fuzz.min(trailing_context);
+            let trimmed = &hunk.lines[skip_start..hunk.lines.len() - skip_end];
+            let old_lines: Vec<&[u8]> = trimmed
+                .iter()
+                .filter(|(t, _)| *t != PatchLineType::Added)
+                .map(|(_, line)| line.as_ref())
+                .collect();
+            let start = find_hunk_position(&lines, &old_lines, pos, expected + skip_start)?;
+            let new_lines: Vec<&[u8]> = trimmed
+                .iter()
+                .filter(|(t, _)| *t != PatchLineType::Removed)
+                .map(|(_, line)| line.as_ref())
+                .collect();
+            Some((start, old_lines.len(), new_lines, skip_start))
+        });
+        if let Some((start, old_len, new_lines, skip_start)) = found {
+            for output in &mut outputs {
+                output.extend(lines[pos..start].concat());
+                output.extend(new_lines.concat());
+            }
+            offset = (start - skip_start) as isize - declared_start as isize;
+            pos = start + old_len;
+        } else {
+            has_conflict = true;
+            let old_lines = hunk.old_lines().collect::<Vec<_>>();
+            let new_lines = hunk.new_lines().collect::<Vec<_>>();
+            let start = expected.clamp(pos, lines.len());
+            let end = (start + old_lines.len()).min(lines.len());
+            for output in &mut outputs {
+                output.extend(lines[pos..start].concat());
+            }
+            let [current, base, patched] = &mut outputs;
+            current.extend(lines[start..end].concat());
+            base.extend(old_lines.concat());
+            patched.extend(new_lines.concat());
+            pos = end;
+        }
+    }
+    for output in &mut outputs {
+        output.extend(lines[pos..].concat());
+    }
+    let [current, base, patched] = outputs.map(BString::from);
+    if has_conflict {
+        Merge::from_vec(vec![current, base, patched])
+    } else {
+        Merge::resolved(current)
+    }
+}
+
+/// Result of [`apply_patch_to_tree()`].
+#[derive(Clone, Debug)]
+pub struct AppliedPatch {
+    /// Tree with the patch applied.
+    pub tree_id: MergedTreeId,
+    /// Paths where some hunks failed to apply and became conflicts.
+    pub conflicted_paths: Vec<RepoPathBuf>,
+}
+
+/// Reads the file at `path` in `tree`. Returns the contents and the mode.
+async fn read_file(
+    store: &Store,
+    tree: &MergedTree,
+    path: &RepoPath,
+) -> Result<(Vec<u8>, FileMode), PatchApplyError> {
+    let invalid_target = |message: &str| PatchApplyError::InvalidTarget {
+        path: path.to_owned(),
+        message: message.to_owned(),
+    };
+    let value = tree.path_value(path)?;
+    match value.into_resolved() {
+        Ok(Some(TreeValue::File { id, executable })) => {
+            let mut reader = store.read_file_async(path, &id).await?;
+            let mut contents = Vec::new();
+            reader
+                .read_to_end(&mut contents)
+                .map_err(|err| BackendError::ReadFile {
+                    path: path.to_owned(),
+                    id,
+                    source: err.into(),
+                })?;
+            let mode = if executable {
+                FileMode::Executable
+            } else {
+                FileMode::Regular
+            };
+            Ok((contents, mode))
+        }
+        Ok(Some(TreeValue::Symlink(id))) => {
+            let target = store.read_symlink_async(path, &id).await?;
+            Ok((target.into_bytes(), FileMode::Symlink))
+        }
+        Ok(None) => Err(invalid_target("No such file")),
+        Ok(Some(_)) => Err(invalid_target("Not a file")),
+        Err(_) => Err(invalid_target("File has conflicts")),
+    }
+}
+
+async fn write_file(
+    store: &Store,
+    path: &RepoPath,
+    contents: &[u8],
+    mode: FileMode,
+) -> Result<TreeValue, PatchApplyError> {
+    match mode {
+        FileMode::Regular | FileMode::Executable => {
+            let id = store.write_file(path, &mut &*contents).await?;
+            let executable = mode == FileMode::Executable;
+            Ok(TreeValue::File { id, executable })
+        }
+        FileMode::Symlink => {
+            let target = contents
+                .to_str()
+                .map_err(|_| PatchApplyError::InvalidTarget {
+                    path: path.to_owned(),
+                    message: "Symlink target is not valid UTF-8".to_owned(),
+                })?;
+            let id = store.write_symlink(path, target).await?;
+            Ok(TreeValue::Symlink(id))
+        }
+    }
+}
+
+/// Applies changes to `files` on top of `tree`, and writes the resulting tree.
+///
+/// See [`apply_hunks()`] for how text hunks are located by using `fuzz`. A
+/// file is deleted only if its contents match the removed lines.
+pub async fn apply_patch_to_tree(
+    store: &Arc<Store>,
+    tree: &MergedTree,
+    files: &[FilePatch],
+    fuzz: usize,
+) -> Result<AppliedPatch, PatchApplyError> {
+    let mut tree_builder = MergedTreeBuilder::new(tree.id());
+    let mut conflicted_paths = vec![];
+    for file in files {
+        let (old_contents, old_mode) = match &file.old_path {
+            Some(path) => {
+                let (contents, mode) = read_file(store, tree, path).await?;
+                (contents, Some(mode))
+            }
+            None => (vec![], None),
+        };
+        let Some(new_path) = &file.new_path else {
+            let old_path = file
+                .old_path
+                .as_ref()
+                .expect("either path should be present");
+            // Binary literals and missing binary data don't include the old
+            // contents, so only text hunks and deltas can be verified.
+            let matches = match &file.content {
+                FilePatchContent::Text(hunks) => apply_hunks(&old_contents, hunks, fuzz)
+                    .as_resolved()
+                    .is_some_and(|contents| contents.is_empty()),
+                FilePatchContent::Binary(BinaryPatch::Delta(delta)) => {
+                    apply_delta(&old_contents, delta).is_some_and(|data| data.is_empty())
+                }
+                FilePatchContent::Binary(BinaryPatch::Literal(_))
+                | FilePatchContent::MissingBinary => true,
+            };
+            if !matches {
+                return Err(PatchApplyError::InvalidTarget {
+                    path: old_path.clone(),
+                    message: "File to be deleted doesn't match the patch".to_owned(),
+                });
+            }
+            tree_builder.set_or_remove(old_path.clone(), Merge::absent());
+            continue;
+        };
+        let invalid_target = |message: &str| PatchApplyError::InvalidTarget {
+            path: new_path.clone(),
+            message: message.to_owned(),
+        };
+        if file.old_path.is_none() && tree.path_value(new_path)?.is_present() {
+            return Err(invalid_target("File already exists"));
+        }
+        let new_contents = match &file.content {
+            FilePatchContent::Text(hunks) if hunks.is_empty() => {
+                Merge::resolved(old_contents.into())
+            }
+            FilePatchContent::Text(hunks) => apply_hunks(&old_contents, hunks, fuzz),
+            FilePatchContent::Binary(BinaryPatch::Literal(data)) => {
+                Merge::resolved(data.clone().into())
+            }
+            FilePatchContent::Binary(BinaryPatch::Delta(delta)) => {
+                let data = apply_delta(&old_contents, delta)
+                    .ok_or_else(|| invalid_target("Binary delta doesn't match the file"))?;
+                Merge::resolved(data.into())
+            }
+            FilePatchContent::MissingBinary => {
+                return Err(invalid_target("Patch doesn't include binary data"));
+            }
+        };
+        let mode = file
+            .new_mode
+            .or(old_mode)
+            .or(file.old_mode)
+            .unwrap_or(FileMode::Regular);
+        let mut values = Vec::new();
+        for contents in new_contents.iter() {
+            values.push(Some(write_file(store, new_path, contents, mode).await?));
+        }
+        if values.len() > 1 {
+            conflicted_paths.push(new_path.clone());
+        }
+        let new_value: MergedTreeValue = Merge::from_vec(values);
+        if let Some(old_path) = &file.old_path {
+            if old_path != new_path && !file.is_copy {
+                tree_builder.set_or_remove(old_path.clone(), Merge::absent());
+            }
+        }
+        tree_builder.set_or_remove(new_path.clone(), new_value);
+    }
+    let tree_id = tree_builder.write_tree(store)?;
+    Ok(AppliedPatch {
+        tree_id,
+        conflicted_paths,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::*;
+
+    fn repo_path(value: &str) -> RepoPathBuf {
+        RepoPathBuf::from_internal_string(value)
+    }
+
+    fn hunk(old_start: usize, new_start: usize, lines: &[(PatchLineType, &str)]) -> PatchHunk {
+        PatchHunk {
+            old_start,
+            new_start,
+            lines: lines
+                .iter()
+                .map(|&(line_type, line)| (line_type, line.into()))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_parse_plain_diff() {
+        let input = indoc! {b"
+            --- a/file\t2025-01-01 00:00:00
+            +++ b/file\t2025-01-01 00:00:00
+            @@ -1,2 +1,2 @@
+             a
+            -b
+            +c
+            \\ No newline at end of file
+        "};
+        let patches = parse_patches(input).unwrap();
+        assert_eq!(
+            patches,
+            [Patch {
+                header: None,
+                files: vec![FilePatch {
+                    old_path: Some(repo_path("file")),
+                    new_path: Some(repo_path("file")),
+                    old_mode: None,
+                    new_mode: None,
+                    is_copy: false,
+                    content: FilePatchContent::Text(vec![hunk(
+                        1,
+                        1,
+                        &[
+                            (PatchLineType::Context, "a\n"),
+                            (PatchLineType::Removed, "b\n"),
+                            (PatchLineType::Added, "c"),
+                        ]
+                    )]),
+                }],
+            }]
+        );
+    }
+
+    #[test]
+    fn test_parse_git_diff_headers() {
+        let input = indoc! {b"
+            diff --git a/added b/added
+            new file mode 100755
+            index 0000000000..1111111111
+            --- /dev/null
+            +++ b/added
+            @@ -0,0 +1,1 @@
+            +x
+            diff --git a/old name b/new name
+            similarity index 100%
+            rename from old name
+            rename to new name
+            diff --git a/deleted b/deleted
+            deleted file mode 120000
+            index 2222222222..0000000000
+            --- a/deleted
+            +++ /dev/null
+            @@ -1,1 +0,0 @@
+            -target
+            \\ No newline at end of file
+            diff --git \"a/tab\\there\" \"b/tab\\there\"
+            old mode 100644
+            new mode 100755
+        "};
+        let patches = parse_patches(input).unwrap();
+        let files = &patches[0].files;
+        assert_eq!(files.len(), 4);
+        assert_eq!(files[0].old_path, None);
+        assert_eq!(files[0].new_path, Some(repo_path("added")));
+        assert_eq!(files[0].new_mode, Some(FileMode::Executable));
+        assert_eq!(files[1].old_path, Some(repo_path("old name")));
+        assert_eq!(files[1].new_path, Some(repo_path("new name")));
+        assert!(!files[1].is_copy);
+        assert_eq!(files[1].content, FilePatchContent::Text(vec![]));
+        assert_eq!(files[2].old_path, Some(repo_path("deleted")));
+        assert_eq!(files[2].new_path, None);
+        assert_eq!(files[2].old_mode, Some(FileMode::Symlink));
+        assert_eq!(files[3].old_path, Some(repo_path("tab\there")));
+        assert_eq!(files[3].old_mode, Some(FileMode::Regular));
+        assert_eq!(files[3].new_mode, Some(FileMode::Executable));
+    }
+
+    #[test]
+    fn test_parse_email_series() {
+        let input = indoc! {b"
+            From 0123456789abcdef Mon Sep 17 00:00:00 2001
+            From: =?UTF-8?q?J=C3=B6rg?= Doe <jorg@example.com>
+            Date: Tue, 4 Mar 2025 10:20:30 +0100
+            Subject: [PATCH 1/2] Fix the bug
+             in foo
+
+            Longer description.
+            ---
+            diff --git a/file b/file
+            --- a/file
+            +++ b/file
+            @@ -1 +1 @@
+            -a
+            +b
+            --
+            jj 0.26.0
+
+            From 0123456789abcdef Mon Sep 17 00:00:00 2001
+            From: Someone <someone@example.com>
+            Subject: [PATCH 2/2] Re: Add file
+
+            ---
+            diff --git a/new b/new
+            new file mode 100644
+            --- /dev/null
+            +++ b/new
+            @@ -0,0 +1 @@
+            +new
+        "};
+        let patches = parse_patches(input).unwrap();
+        assert_eq!(patches.len(), 2);
+        let header = patches[0].header.as_ref().unwrap();
+        assert_eq!(
+            header.author,
+            Some(("Jörg Doe".to_owned(), "jorg@example.com".to_owned()))
+        );
+        let timestamp = header.author_timestamp.as_ref().unwrap();
+        assert_eq!(timestamp.timestamp.0, 1741080030000);
+        assert_eq!(timestamp.tz_offset, 60);
+        assert_eq!(
+            header.description,
+            "Fix the bug in foo\n\nLonger description.\n"
+        );
+        assert_eq!(patches[0].files.len(), 1);
+        let header = patches[1].header.as_ref().unwrap();
+        assert_eq!(header.author_timestamp, None);
+        assert_eq!(header.description, "Add file\n");
+        assert_eq!(patches[1].files[0].new_path, Some(repo_path("new")));
+    }
+
+    #[test]
+    fn test_parse_email_with_from_in_body() {
+        let input = indoc! {b"
+            From 0123456789abcdef Mon Sep  7 00:00:00 2001
+            From: Someone <someone@example.com>
+            Subject: [PATCH] Subject
+
+            From now on, this is the body
+            Note: it isn't a new message.
+            ---
+        "};
+        let patches = parse_patches(input).unwrap();
+        assert_eq!(patches.len(), 1);
+        assert_eq!(
+            patches[0].header.as_ref().unwrap().description,
+            "Subject\n\nFrom now on, this is the body\nNote: it isn't a new message.\n"
+        );
+    }
+
+    #[test]
+    fn test_encode_header_value() {
+        assert_eq!(encode_header_value("Fix the bug"), "Fix the bug");
+        assert_eq!(encode_header_value("Jörg Doe"), "=?UTF-8?q?J=C3=B6rg_Doe?=");
+        assert_eq!(encode_header_value("a=?b"), "=?UTF-8?q?a=3D=3Fb?=");
+        let value = format!("{}\nsecond line ✓", "x".repeat(60));
+        let encoded = encode_header_value(&value);
+        assert!(encoded.lines().all(|line| line.trim().len() <= 75));
+        let unfolded = encoded.lines().map(str::trim).join(" ");
+        assert_eq!(decode_header_value(&unfolded), value);
+    }
+
+    #[test]
+    fn test_parse_invalid_hunk() {
+        let input = indoc! {b"
+            --- a/file
+            +++ b/file
+            @@ -1,2 +1,2 @@
+             a
+        "};
+        let err = parse_patches(input).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Invalid patch at line 5: Unexpected end of hunk"
+        );
+    }
+
+    #[test]
+    fn test_binary_patch_round_trip() {
+        let old = b"\0old contents".to_vec();
+        let new = (0..=255u8).cycle().take(1000).collect::<Vec<_>>();
+        let input = format!(
+            "diff --git a/bin b/bin\nindex 1111111111..2222222222 100644\n{}",
+            format_git_binary_patch(&old, &new)
+        );
+        let patches = parse_patches(input.as_bytes()).unwrap();
+        assert_eq!(
+            patches[0].files[0].content,
+            FilePatchContent::Binary(BinaryPatch::Literal(new))
+        );
+    }
+
+    #[test]
+    fn test_apply_delta() {
+        let base = b"hello world";
+        // base size 11, result size 12, copy "hello ", insert "there", copy "d"
+        let delta = [
+            11, 12, 0x91, 0, 6, 5, b't', b'h', b'e', b'r', b'e', 0x91, 10, 1,
+        ];
+        assert_eq!(
+            apply_delta(base, &delta).as_deref(),
+            Some(b"hello thered".as_ref())
+        );
+        // Base size mismatch
+        assert_eq!(apply_delta(b"hello", &delta), None);
+        // Copy out of range
+        assert_eq!(apply_delta(base, &[11, 1, 0x91, 11, 1]), None);
+    }
+
+    #[test]
+    fn test_apply_hunks() {
+        let content = b"a\nb\nc\nd\n";
+        let hunks = [
+            hunk(
+                1,
+                1,
+                &[
+                    (PatchLineType::Removed, "a\n"),
+                    (PatchLineType::Added, "A\n"),
+                ],
+            ),
+            hunk(4, 4, &[(PatchLineType::Added, "e\n")]),
+        ];
+        assert_eq!(
+            apply_hunks(content, &hunks, 0),
+            Merge::resolved(BString::from("A\nb\nc\nd\ne\n"))
+        );
+
+        // Mismatched hunk becomes a conflict
+        let hunks = [
+            hunk(
+                2,
+                2,
+                &[
+                    (PatchLineType::Removed, "x\n"),
+                    (PatchLineType::Added, "y\n"),
+                ],
+            ),
+            hunk(
+                4,
+                4,
+                &[
+                    (PatchLineType::Removed, "d\n"),
+                    (PatchLineType::Added, "D\n"),
+                ],
+            ),
+        ];
+        assert_eq!(
+            apply_hunks(content, &hunks, 0),
+            Merge::from_vec(vec![
+                BString::from("a\nb\nc\nD\n"),
+                BString::from("a\nx\nc\nD\n"),
+                BString::from("a\ny\nc\nD\n"),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_apply_hunks_with_offset_and_fuzz() {
+        let content = b"0\n1\na\nb\nc\nd\ne\n";
+        // Hunk recorded at line 1, but the content has 2 more lines
+        let hunks = [
+            hunk(
+                1,
+                1,
+                &[
+                    (PatchLineType::Context, "a\n"),
+                    (PatchLineType::Removed, "b\n"),
+                    (PatchLineType::Added, "B\n"),
+                    (PatchLineType::Context, "c\n"),
+                ],
+            ),
+            // Offset of the previous hunk should be carried over
+            hunk(
+                4,
+                4,
+                &[
+                    (PatchLineType::Removed, "d\n"),
+                    (PatchLineType::Added, "D\n"),
+                ],
+            ),
+        ];
+        assert_eq!(
+            apply_hunks(content, &hunks, 0),
+            Merge::resolved(BString::from("0\n1\na\nB\nc\nD\ne\n"))
+        );
+
+        // Mismatched context is ignored only if fuzz is allowed
+        let hunks = [hunk(
+            3,
+            3,
+            &[
+                (PatchLineType::Context, "x\n"),
+                (PatchLineType::Removed, "b\n"),
+                (PatchLineType::Added, "B\n"),
+                (PatchLineType::Context, "c\n"),
+            ],
+        )];
+        assert_eq!(
+            apply_hunks(content, &hunks, 0),
+            Merge::from_vec(vec![
+                BString::from("0\n1\na\nb\nc\nd\ne\n"),
+                BString::from("0\n1\nx\nb\nc\nd\ne\n"),
+                BString::from("0\n1\nx\nB\nc\nd\ne\n"),
+            ])
+        );
+        assert_eq!(
+            apply_hunks(content, &hunks, 1),
+            Merge::resolved(BString::from("0\n1\na\nB\nc\nd\ne\n"))
+        );
+    }
+}
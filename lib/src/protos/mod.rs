@@ -8,6 +8,9 @@ pub mod local_store {
 pub mod op_store {
     include!("op_store.rs");
 }
+pub mod simple_workspace_store {
+    include!("simple_workspace_store.rs");
+}
 pub mod working_copy {
     include!("working_copy.rs");
 }
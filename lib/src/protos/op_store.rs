@@ -93,6 +93,31 @@ pub struct Tag {
     pub name: ::prost::alloc::string::String,
     #[prost(message, optional, tag = "2")]
     pub target: ::core::option::Option<RefTarget>,
+    /// Introduced in jj 0.27. Unset for lightweight tags.
+    #[prost(message, optional, tag = "3")]
+    pub annotation: ::core::option::Option<TagAnnotation>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TagAnnotation {
+    #[prost(string, tag = "1")]
+    pub message: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub tagger_name: ::prost::alloc::string::String,
+    #[prost(string, tag = "3")]
+    pub tagger_email: ::prost::alloc::string::String,
+    #[prost(message, optional, tag = "4")]
+    pub tagger_timestamp: ::core::option::Option<Timestamp>,
+    #[prost(bytes = "vec", optional, tag = "5")]
+    pub signature: ::core::option::Option<::prost::alloc::vec::Vec<u8>>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BisectMarkedCommit {
+    #[prost(bytes = "vec", tag = "1")]
+    pub commit_id: ::prost::alloc::vec::Vec<u8>,
+    #[prost(enumeration = "BisectMark", tag = "2")]
+    pub mark: i32,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -122,6 +147,9 @@ pub struct View {
     pub git_head_legacy: ::prost::alloc::vec::Vec<u8>,
     #[prost(message, optional, tag = "9")]
     pub git_head: ::core::option::Option<RefTarget>,
+    /// Introduced in jj 0.27.
+    #[prost(message, repeated, tag = "11")]
+    pub bisect_marks: ::prost::alloc::vec::Vec<BisectMarkedCommit>,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -189,3 +217,32 @@ impl RemoteRefState {
         }
     }
 }
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum BisectMark {
+    Good = 0,
+    Bad = 1,
+    Skipped = 2,
+}
+impl BisectMark {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            BisectMark::Good => "Good",
+            BisectMark::Bad => "Bad",
+            BisectMark::Skipped => "Skipped",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "Good" => Some(Self::Good),
+            "Bad" => Some(Self::Bad),
+            "Skipped" => Some(Self::Skipped),
+            _ => None,
+        }
+    }
+}
@@ -53,6 +53,8 @@ pub struct TreeState {
     pub sparse_patterns: ::core::option::Option<SparsePatterns>,
     #[prost(message, optional, tag = "4")]
     pub watchman_clock: ::core::option::Option<WatchmanClock>,
+    #[prost(message, optional, tag = "7")]
+    pub native_fsmonitor_clock: ::core::option::Option<NativeFsmonitorClock>,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -73,6 +75,14 @@ pub mod watchman_clock {
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
+pub struct NativeFsmonitorClock {
+    #[prost(string, tag = "1")]
+    pub instance_id: ::prost::alloc::string::String,
+    #[prost(uint64, tag = "2")]
+    pub offset: u64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
 pub struct Checkout {
     /// The operation at which the working copy was updated.
     #[prost(bytes = "vec", tag = "2")]
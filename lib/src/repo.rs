@@ -42,6 +42,10 @@ use crate::commit::Commit;
 use crate::commit::CommitByCommitterTimestamp;
 use crate::commit_builder::CommitBuilder;
 use crate::commit_builder::DetachedCommitBuilder;
+use crate::content_merger::builtin_content_mergers;
+use crate::content_merger::ContentMerger;
+use crate::content_merger::ContentMergerConfigError;
+use crate::content_merger::ContentMergers;
 use crate::dag_walk;
 use crate::default_index::DefaultIndexStore;
 use crate::default_index::DefaultMutableIndex;
@@ -64,6 +68,7 @@ use crate::op_heads_store::OpHeadResolutionError;
 use crate::op_heads_store::OpHeadsStore;
 use crate::op_heads_store::OpHeadsStoreError;
 use crate::op_store;
+use crate::op_store::BisectMark;
 use crate::op_store::OpStore;
 use crate::op_store::OpStoreError;
 use crate::op_store::OpStoreResult;
@@ -72,6 +77,7 @@ use crate::op_store::RefTarget;
 use crate::op_store::RemoteRef;
 use crate::op_store::RemoteRefState;
 use crate::op_store::RootOperationData;
+use crate::op_store::TagAnnotation;
 use crate::op_store::WorkspaceId;
 use crate::operation::Operation;
 use crate::refs::diff_named_ref_targets;
@@ -193,7 +199,13 @@ impl ReadonlyRepo {
         let backend = backend_initializer(settings, &store_path)?;
         let backend_path = store_path.join("type");
         fs::write(&backend_path, backend.name()).context(&backend_path)?;
-        let store = Store::new(backend, signer);
+        // Path patterns aren't loaded from the settings while initializing the
+        // repo, but the builtin mergers can still be selected by name.
+        let store = Store::new(
+            backend,
+            signer,
+            ContentMergers::with_named(builtin_content_mergers()),
+        );
 
         let op_store_path = repo_path.join("op_store");
         fs::create_dir(&op_store_path).context(&op_store_path)?;
@@ -382,6 +394,7 @@ pub struct StoreFactories {
     op_heads_store_factories: HashMap<String, OpHeadsStoreFactory>,
     index_store_factories: HashMap<String, IndexStoreFactory>,
     submodule_store_factories: HashMap<String, SubmoduleStoreFactory>,
+    content_mergers: HashMap<String, Arc<dyn ContentMerger>>,
 }
 
 impl Default for StoreFactories {
@@ -438,6 +451,11 @@ impl Default for StoreFactories {
             Box::new(|_settings, store_path| Box::new(DefaultSubmoduleStore::load(store_path))),
         );
 
+        // ContentMergers
+        for (name, merger) in builtin_content_mergers() {
+            factories.add_content_merger(&name, merger);
+        }
+
         factories
     }
 }
@@ -458,6 +476,8 @@ pub enum StoreLoadError {
     Backend(#[from] BackendLoadError),
     #[error(transparent)]
     Signing(#[from] SignInitError),
+    #[error(transparent)]
+    ContentMerger(#[from] ContentMergerConfigError),
 }
 
 impl StoreFactories {
@@ -468,6 +488,7 @@ impl StoreFactories {
             op_heads_store_factories: HashMap::new(),
             index_store_factories: HashMap::new(),
             submodule_store_factories: HashMap::new(),
+            content_mergers: HashMap::new(),
         }
     }
 
@@ -478,6 +499,7 @@ impl StoreFactories {
             op_heads_store_factories,
             index_store_factories,
             submodule_store_factories,
+            content_mergers,
         } = ext;
 
         merge_factories_map(&mut self.backend_factories, backend_factories);
@@ -488,6 +510,7 @@ impl StoreFactories {
             &mut self.submodule_store_factories,
             submodule_store_factories,
         );
+        merge_factories_map(&mut self.content_mergers, content_mergers);
     }
 
     pub fn add_backend(&mut self, name: &str, factory: BackendFactory) {
@@ -591,6 +614,22 @@ impl StoreFactories {
 
         Ok(submodule_store_factory(settings, store_path))
     }
+
+    /// Registers `merger` which can be referenced by `name` from the
+    /// `content-mergers` config.
+    pub fn add_content_merger(&mut self, name: &str, merger: Arc<dyn ContentMerger>) {
+        self.content_mergers.insert(name.to_string(), merger);
+    }
+
+    pub fn load_content_mergers(
+        &self,
+        settings: &UserSettings,
+    ) -> Result<ContentMergers, StoreLoadError> {
+        Ok(ContentMergers::from_settings(
+            settings,
+            &self.content_mergers,
+        )?)
+    }
 }
 
 pub fn read_store_type(
@@ -659,6 +698,7 @@ impl RepoLoader {
         let store = Store::new(
             store_factories.load_backend(settings, &repo_path.join("store"))?,
             Signer::from_settings(settings)?,
+            store_factories.load_content_mergers(settings)?,
         );
         let root_op_data = RootOperationData {
             root_commit_id: store.root_commit_id().clone(),
@@ -1599,6 +1639,15 @@ impl MutableRepo {
         self.view_mut().set_tag_target(name, target);
     }
 
+    pub fn get_tag_annotation(&self, name: &str) -> Option<TagAnnotation> {
+        self.view.with_ref(|v| v.get_tag_annotation(name).cloned())
+    }
+
+    /// Sets or clears the annotation of an existing tag.
+    pub fn set_tag_annotation(&mut self, name: &str, annotation: Option<TagAnnotation>) {
+        self.view_mut().set_tag_annotation(name, annotation);
+    }
+
     pub fn merge_tag(&mut self, name: &str, base_target: &RefTarget, other_target: &RefTarget) {
         let view = self.view.get_mut();
         let index = self.index.as_index();
@@ -1631,6 +1680,10 @@ impl MutableRepo {
         self.view_mut().set_git_head_target(target);
     }
 
+    pub fn set_bisect_mark(&mut self, commit_id: &CommitId, mark: Option<BisectMark>) {
+        self.view_mut().set_bisect_mark(commit_id, mark);
+    }
+
     pub fn set_view(&mut self, data: op_store::View) {
         self.view_mut().set_view(data);
         self.view.mark_dirty();
@@ -1721,6 +1774,29 @@ impl MutableRepo {
             self.merge_tag(name, base_target, other_target);
         }
 
+        // If both sides changed the annotation of a tag, we keep the self side.
+        let changed_tag_annotations = itertools::chain(
+            base.tag_annotations().keys(),
+            other.tag_annotations().keys(),
+        )
+        .unique()
+        .map(|name| {
+            (
+                name,
+                base.get_tag_annotation(name),
+                other.get_tag_annotation(name),
+            )
+        })
+        .filter(|(_, base_annotation, other_annotation)| base_annotation != other_annotation)
+        .filter(|(name, base_annotation, _)| {
+            self.view().get_tag_annotation(name) == *base_annotation
+        })
+        .map(|(name, _, other_annotation)| (name.clone(), other_annotation.cloned()))
+        .collect_vec();
+        for (name, annotation) in changed_tag_annotations {
+            self.set_tag_annotation(&name, annotation);
+        }
+
         let changed_git_refs = diff_named_ref_targets(base.git_refs(), other.git_refs());
         for (name, (base_target, other_target)) in changed_git_refs {
             self.merge_git_ref(name, base_target, other_target);
@@ -1740,6 +1816,26 @@ impl MutableRepo {
         );
         self.set_git_head_target(new_git_head_target);
 
+        // Merge bisection marks. If both sides changed the mark of a commit, we
+        // keep the self side.
+        let changed_bisect_marks =
+            itertools::chain(base.bisect_marks().keys(), other.bisect_marks().keys())
+                .unique()
+                .map(|id| {
+                    (
+                        id,
+                        base.bisect_marks().get(id),
+                        other.bisect_marks().get(id),
+                    )
+                })
+                .filter(|(_, base_mark, other_mark)| base_mark != other_mark)
+                .filter(|(id, base_mark, _)| self.view().bisect_marks().get(id) == *base_mark)
+                .map(|(id, _, other_mark)| (id.clone(), other_mark.copied()))
+                .collect_vec();
+        for (commit_id, mark) in changed_bisect_marks {
+            self.set_bisect_mark(&commit_id, mark);
+        }
+
         Ok(())
     }
 
@@ -61,6 +61,9 @@ pub struct GitSettings {
     pub abandon_unreachable_commits: bool,
     pub subprocess: bool,
     pub executable_path: PathBuf,
+    pub gix_transport: bool,
+    pub gix_local_receive_pack: bool,
+    pub ssh_command: Option<String>,
 }
 
 impl GitSettings {
@@ -70,6 +73,9 @@ impl GitSettings {
             abandon_unreachable_commits: settings.get_bool("git.abandon-unreachable-commits")?,
             subprocess: settings.get_bool("git.subprocess")?,
             executable_path: settings.get("git.executable-path")?,
+            gix_transport: settings.get_bool("git.gix-transport")?,
+            gix_local_receive_pack: settings.get_bool("git.gix-local-receive-pack")?,
+            ssh_command: settings.get_string("git.ssh-command").optional()?,
         })
     }
 }
@@ -81,6 +87,9 @@ impl Default for GitSettings {
             abandon_unreachable_commits: true,
             subprocess: true,
             executable_path: PathBuf::from("git"),
+            gix_transport: false,
+            gix_local_receive_pack: false,
+            ssh_command: None,
         }
     }
 }
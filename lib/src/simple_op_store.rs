@@ -34,6 +34,7 @@ use thiserror::Error;
 
 use crate::backend::CommitId;
 use crate::backend::MillisSinceEpoch;
+use crate::backend::Signature;
 use crate::backend::Timestamp;
 use crate::content_hash::blake2b_hash;
 use crate::dag_walk;
@@ -45,6 +46,7 @@ use crate::object_id::HexPrefix;
 use crate::object_id::ObjectId;
 use crate::object_id::PrefixResolution;
 use crate::op_store;
+use crate::op_store::BisectMark;
 use crate::op_store::OpStore;
 use crate::op_store::OpStoreError;
 use crate::op_store::OpStoreResult;
@@ -56,6 +58,7 @@ use crate::op_store::RemoteRef;
 use crate::op_store::RemoteRefState;
 use crate::op_store::RemoteView;
 use crate::op_store::RootOperationData;
+use crate::op_store::TagAnnotation;
 use crate::op_store::View;
 use crate::op_store::ViewId;
 use crate::op_store::WorkspaceId;
@@ -456,6 +459,7 @@ fn view_to_proto(view: &View) -> crate::protos::op_store::View {
         proto.tags.push(crate::protos::op_store::Tag {
             name: name.clone(),
             target: ref_target_to_proto(target),
+            annotation: view.tag_annotations.get(name).map(tag_annotation_to_proto),
         });
     }
 
@@ -469,6 +473,15 @@ fn view_to_proto(view: &View) -> crate::protos::op_store::View {
 
     proto.git_head = ref_target_to_proto(&view.git_head);
 
+    for (commit_id, mark) in &view.bisect_marks {
+        proto
+            .bisect_marks
+            .push(crate::protos::op_store::BisectMarkedCommit {
+                commit_id: commit_id.to_bytes(),
+                mark: bisect_mark_to_proto(*mark),
+            });
+    }
+
     proto
 }
 
@@ -494,6 +507,12 @@ fn view_from_proto(proto: crate::protos::op_store::View) -> View {
     view.remote_views = remote_views;
 
     for tag_proto in proto.tags {
+        if let Some(annotation) = tag_proto.annotation {
+            view.tag_annotations.insert(
+                tag_proto.name.clone(),
+                tag_annotation_from_proto(annotation),
+            );
+        }
         view.tags
             .insert(tag_proto.name, ref_target_from_proto(tag_proto.target));
     }
@@ -515,9 +534,39 @@ fn view_from_proto(proto: crate::protos::op_store::View) -> View {
         view.git_head = RefTarget::normal(CommitId::new(proto.git_head_legacy));
     }
 
+    for marked_commit in proto.bisect_marks {
+        // Unknown marks are ignored.
+        if let Some(mark) = bisect_mark_from_proto(marked_commit.mark) {
+            view.bisect_marks
+                .insert(CommitId::new(marked_commit.commit_id), mark);
+        }
+    }
+
     view
 }
 
+fn tag_annotation_to_proto(annotation: &TagAnnotation) -> crate::protos::op_store::TagAnnotation {
+    crate::protos::op_store::TagAnnotation {
+        message: annotation.message.clone(),
+        tagger_name: annotation.tagger.name.clone(),
+        tagger_email: annotation.tagger.email.clone(),
+        tagger_timestamp: Some(timestamp_to_proto(&annotation.tagger.timestamp)),
+        signature: annotation.signature.clone(),
+    }
+}
+
+fn tag_annotation_from_proto(proto: crate::protos::op_store::TagAnnotation) -> TagAnnotation {
+    TagAnnotation {
+        message: proto.message,
+        tagger: Signature {
+            name: proto.tagger_name,
+            email: proto.tagger_email,
+            timestamp: timestamp_from_proto(proto.tagger_timestamp.unwrap_or_default()),
+        },
+        signature: proto.signature,
+    }
+}
+
 fn bookmark_views_to_proto_legacy(
     local_bookmarks: &BTreeMap<String, RefTarget>,
     remote_views: &BTreeMap<String, RemoteView>,
@@ -682,6 +731,25 @@ fn remote_ref_state_from_proto(proto_value: Option<i32>) -> Option<RemoteRefStat
     Some(state)
 }
 
+fn bisect_mark_to_proto(mark: BisectMark) -> i32 {
+    let proto_mark = match mark {
+        BisectMark::Good => crate::protos::op_store::BisectMark::Good,
+        BisectMark::Bad => crate::protos::op_store::BisectMark::Bad,
+        BisectMark::Skipped => crate::protos::op_store::BisectMark::Skipped,
+    };
+    proto_mark as i32
+}
+
+fn bisect_mark_from_proto(proto_value: i32) -> Option<BisectMark> {
+    let proto_mark = proto_value.try_into().ok()?;
+    let mark = match proto_mark {
+        crate::protos::op_store::BisectMark::Good => BisectMark::Good,
+        crate::protos::op_store::BisectMark::Bad => BisectMark::Bad,
+        crate::protos::op_store::BisectMark::Skipped => BisectMark::Skipped,
+    };
+    Some(mark)
+}
+
 #[cfg(test)]
 mod tests {
     use insta::assert_snapshot;
@@ -722,6 +790,7 @@ mod tests {
             tags: btreemap! {
                 "v1.0".to_string() => tag_v1_target,
             },
+            tag_annotations: BTreeMap::new(),
             remote_views: btreemap! {
                 "origin".to_string() => RemoteView {
                     bookmarks: btreemap! {
@@ -739,6 +808,7 @@ mod tests {
                 WorkspaceId::default() => default_wc_commit_id,
                 WorkspaceId::new("test".to_string()) => test_wc_commit_id,
             },
+            bisect_marks: BTreeMap::new(),
         }
     }
 
@@ -779,6 +849,48 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_hash_view_optional_fields() {
+        // Test exact output so we detect regressions in compatibility
+        let view_id = |view: &View| ViewId::new(blake2b_hash(view).to_vec()).hex();
+        let empty_view = View::empty();
+        let bisect_view = View {
+            bisect_marks: btreemap! {
+                CommitId::from_hex("aaa111") => BisectMark::Good,
+            },
+            ..View::empty()
+        };
+        let annotated_view = View {
+            tag_annotations: btreemap! {
+                "v1.0".to_string() => TagAnnotation {
+                    message: "Release 1.0\n".to_string(),
+                    tagger: Signature {
+                        name: "Someone".to_string(),
+                        email: "someone@example.com".to_string(),
+                        timestamp: Timestamp {
+                            timestamp: MillisSinceEpoch(123456789),
+                            tz_offset: 3600,
+                        },
+                    },
+                    signature: None,
+                },
+            },
+            ..View::empty()
+        };
+        assert_snapshot!(
+            view_id(&empty_view),
+            @"0b9c4d546143a10cc1f89dd34fac41009ecdcea29f3b3977fcf71359bdc92775624b20b100487e9678f254cb9a0ef805406db8a08af954f2a23e833dbc06d59c"
+        );
+        assert_snapshot!(
+            view_id(&bisect_view),
+            @"40d444465545bbf88f24f62eb545d3a75905ef022adfd09e80bd5cc287626a7e3e83ca04956489af35f8d36969d827fc35d05047b20fcf56c5da177f92d0d441"
+        );
+        assert_snapshot!(
+            view_id(&annotated_view),
+            @"07ccce3fcfba10624d7a55036cd93af4275d73824027abcdb71374f16fd9c2d3f7790529c0f427135d556079f8072749acb88745faa36d91ac1432b42a8da5ca"
+        );
+    }
+
     #[test]
     fn test_hash_operation() {
         // Test exact output so we detect regressions in compatibility
@@ -801,6 +913,63 @@ mod tests {
         assert_eq!(read_view, view);
     }
 
+    #[test]
+    fn test_read_write_view_with_bisect_marks() {
+        let temp_dir = testutils::new_temp_dir();
+        let root_data = RootOperationData {
+            root_commit_id: CommitId::from_hex("000000"),
+        };
+        let store = SimpleOpStore::init(temp_dir.path(), root_data);
+        let view_without_marks = create_view();
+        let view = View {
+            bisect_marks: btreemap! {
+                CommitId::from_hex("eee111") => BisectMark::Good,
+                CommitId::from_hex("eee222") => BisectMark::Bad,
+                CommitId::from_hex("eee333") => BisectMark::Skipped,
+            },
+            ..view_without_marks.clone()
+        };
+        let view_id = store.write_view(&view).unwrap();
+        assert_ne!(view_id, store.write_view(&view_without_marks).unwrap());
+        let read_view = store.read_view(&view_id).unwrap();
+        assert_eq!(read_view, view);
+    }
+
+    #[test]
+    fn test_read_write_view_with_tag_annotations() {
+        let temp_dir = testutils::new_temp_dir();
+        let root_data = RootOperationData {
+            root_commit_id: CommitId::from_hex("000000"),
+        };
+        let store = SimpleOpStore::init(temp_dir.path(), root_data);
+        let view_without_annotations = create_view();
+        let tagger = Signature {
+            name: "Test User".to_string(),
+            email: "test.user@example.com".to_string(),
+            timestamp: Timestamp {
+                timestamp: MillisSinceEpoch(123456789),
+                tz_offset: 3600,
+            },
+        };
+        let view = View {
+            tag_annotations: btreemap! {
+                "v1.0".to_string() => TagAnnotation {
+                    message: "Release 1.0\n".to_string(),
+                    tagger,
+                    signature: Some(b"signature".to_vec()),
+                },
+            },
+            ..view_without_annotations.clone()
+        };
+        let view_id = store.write_view(&view).unwrap();
+        assert_ne!(
+            view_id,
+            store.write_view(&view_without_annotations).unwrap()
+        );
+        let read_view = store.read_view(&view_id).unwrap();
+        assert_eq!(read_view, view);
+    }
+
     #[test]
     fn test_read_write_operation() {
         let temp_dir = testutils::new_temp_dir();
@@ -39,6 +39,7 @@ use crate::backend::SigningFn;
 use crate::backend::SymlinkId;
 use crate::backend::TreeId;
 use crate::commit::Commit;
+use crate::content_merger::ContentMergers;
 use crate::index::Index;
 use crate::merge::Merge;
 use crate::merge::MergedTreeValue;
@@ -59,6 +60,7 @@ const TREE_CACHE_CAPACITY: usize = 1000;
 pub struct Store {
     backend: Box<dyn Backend>,
     signer: Signer,
+    content_mergers: ContentMergers,
     commit_cache: Mutex<CLruCache<CommitId, Arc<backend::Commit>>>,
     tree_cache: Mutex<CLruCache<(RepoPathBuf, TreeId), Arc<backend::Tree>>>,
 }
@@ -72,10 +74,15 @@ impl Debug for Store {
 }
 
 impl Store {
-    pub fn new(backend: Box<dyn Backend>, signer: Signer) -> Arc<Self> {
+    pub fn new(
+        backend: Box<dyn Backend>,
+        signer: Signer,
+        content_mergers: ContentMergers,
+    ) -> Arc<Self> {
         Arc::new(Store {
             backend,
             signer,
+            content_mergers,
             commit_cache: Mutex::new(CLruCache::new(COMMIT_CACHE_CAPACITY.try_into().unwrap())),
             tree_cache: Mutex::new(CLruCache::new(TREE_CACHE_CAPACITY.try_into().unwrap())),
         })
@@ -89,6 +96,12 @@ impl Store {
         &self.signer
     }
 
+    /// Mergers used to resolve file conflicts that can't be merged line by
+    /// line.
+    pub fn content_mergers(&self) -> &ContentMergers {
+        &self.content_mergers
+    }
+
     pub fn get_copy_records(
         &self,
         paths: Option<&[RepoPathBuf]>,
@@ -15,7 +15,18 @@
 #![allow(missing_docs)]
 
 use std::fmt::Debug;
+use std::path::PathBuf;
 
 pub trait SubmoduleStore: Send + Sync + Debug {
     fn name(&self) -> &str;
+
+    /// Path to the Git directory of the submodule `name`. The directory may
+    /// not exist yet.
+    ///
+    /// Returns `None` if the store doesn't keep the Git directories of
+    /// submodules, in which case the Git directory is created inside the
+    /// submodule's working copy.
+    fn git_dir(&self, _name: &str) -> Option<PathBuf> {
+        None
+    }
 }
@@ -37,6 +37,8 @@ use crate::backend::TreeId;
 use crate::backend::TreeValue;
 use crate::files;
 use crate::files::MergeResult;
+use crate::gitattributes::GitAttributeState;
+use crate::gitattributes::GitAttributes;
 use crate::matchers::EverythingMatcher;
 use crate::matchers::Matcher;
 use crate::merge::trivial_merge;
@@ -397,9 +399,13 @@ fn merge_tree_value(
                 Ok(value) => value,
                 Err(conflict) => {
                     let conflict_borrowed = conflict.map(|value| value.as_ref());
-                    if let Some(tree_value) =
-                        try_resolve_file_conflict(store, &filename, &conflict_borrowed)
-                            .block_on()?
+                    if let Some(tree_value) = try_resolve_file_conflict(
+                        store,
+                        &filename,
+                        &conflict_borrowed,
+                        &GitAttributes::default(),
+                    )
+                    .block_on()?
                     {
                         Some(tree_value)
                     } else {
@@ -415,11 +421,13 @@ fn merge_tree_value(
 /// Resolves file-level conflict by merging content hunks.
 ///
 /// The input `conflict` is supposed to be simplified. It shouldn't contain
-/// non-file values that cancel each other.
+/// non-file values that cancel each other. The `merge` attribute in
+/// `attributes` selects the merger to use.
 pub async fn try_resolve_file_conflict(
     store: &Store,
     filename: &RepoPath,
     conflict: &MergedTreeVal<'_>,
+    attributes: &GitAttributes,
 ) -> BackendResult<Option<TreeValue>> {
     // If there are any non-file or any missing parts in the conflict, we can't
     // merge it. We check early so we don't waste time reading file contents if
@@ -471,7 +479,28 @@ pub async fn try_resolve_file_conflict(
         BackendResult::Ok(content)
     });
     let contents = Merge::from_vec(try_join_all(content_futures).await?);
-    let merge_result = files::merge(&contents);
+    let merger = match &attributes.merge {
+        // `-merge` or `merge=binary` leaves the file conflicted
+        Some(GitAttributeState::Unset) => return Ok(None),
+        Some(GitAttributeState::Value(name)) if name == "binary" => return Ok(None),
+        Some(GitAttributeState::Value(name)) if name != "text" => store.content_mergers().get(name),
+        Some(GitAttributeState::Set) | Some(GitAttributeState::Value(_)) => None,
+        Some(GitAttributeState::Unspecified) | None => store.content_mergers().find(filename),
+    };
+    // Fall back to the line-based merge if the content merger can't merge
+    // the file.
+    let merged_content = merger.and_then(|merger| match merger.merge(filename, &contents) {
+        Ok(merged_content) => merged_content,
+        Err(err) => {
+            tracing::warn!(?err, ?filename, "failed to merge file contents");
+            None
+        }
+    });
+    let merge_result = if let Some(merged_content) = merged_content {
+        MergeResult::Resolved(merged_content.into())
+    } else {
+        files::merge(&contents)
+    };
     match merge_result {
         MergeResult::Resolved(merged_content) => {
             let id = store
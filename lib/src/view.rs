@@ -23,10 +23,12 @@ use thiserror::Error;
 
 use crate::backend::CommitId;
 use crate::op_store;
+use crate::op_store::BisectMark;
 use crate::op_store::BookmarkTarget;
 use crate::op_store::RefTarget;
 use crate::op_store::RefTargetOptionExt as _;
 use crate::op_store::RemoteRef;
+use crate::op_store::TagAnnotation;
 use crate::op_store::WorkspaceId;
 use crate::refs;
 use crate::refs::LocalAndRemoteRef;
@@ -80,6 +82,11 @@ impl View {
         &self.data.tags
     }
 
+    /// Annotations of annotated tags by tag name.
+    pub fn tag_annotations(&self) -> &BTreeMap<String, TagAnnotation> {
+        &self.data.tag_annotations
+    }
+
     pub fn git_refs(&self) -> &BTreeMap<String, RefTarget> {
         &self.data.git_refs
     }
@@ -88,6 +95,11 @@ impl View {
         &self.data.git_head
     }
 
+    /// Commits marked by the in-progress bisection.
+    pub fn bisect_marks(&self) -> &BTreeMap<CommitId, BisectMark> {
+        &self.data.bisect_marks
+    }
+
     pub fn set_wc_commit(&mut self, workspace_id: WorkspaceId, commit_id: CommitId) {
         self.data.wc_commit_ids.insert(workspace_id, commit_id);
     }
@@ -321,12 +333,34 @@ impl View {
     }
 
     /// Sets tag to point to the given target. If the target is absent, the tag
-    /// will be removed.
+    /// will be removed along with its annotation.
     pub fn set_tag_target(&mut self, name: &str, target: RefTarget) {
         if target.is_present() {
             self.data.tags.insert(name.to_owned(), target);
         } else {
             self.data.tags.remove(name);
+            self.data.tag_annotations.remove(name);
+        }
+    }
+
+    /// Returns the annotation of the tag, or `None` if the tag is lightweight
+    /// or doesn't exist.
+    pub fn get_tag_annotation(&self, name: &str) -> Option<&TagAnnotation> {
+        self.data.tag_annotations.get(name)
+    }
+
+    /// Sets or clears the annotation of an existing tag. Annotations of absent
+    /// tags are ignored.
+    pub fn set_tag_annotation(&mut self, name: &str, annotation: Option<TagAnnotation>) {
+        match annotation {
+            Some(annotation) if self.data.tags.contains_key(name) => {
+                self.data
+                    .tag_annotations
+                    .insert(name.to_owned(), annotation);
+            }
+            _ => {
+                self.data.tag_annotations.remove(name);
+            }
         }
     }
 
@@ -350,6 +384,16 @@ impl View {
         self.data.git_head = target;
     }
 
+    /// Marks the commit for bisection. If the mark is `None`, the commit will
+    /// be unmarked.
+    pub fn set_bisect_mark(&mut self, commit_id: &CommitId, mark: Option<BisectMark>) {
+        if let Some(mark) = mark {
+            self.data.bisect_marks.insert(commit_id.clone(), mark);
+        } else {
+            self.data.bisect_marks.remove(commit_id);
+        }
+    }
+
     /// Iterates all commit ids referenced by this view.
     ///
     /// This can include hidden commits referenced by remote bookmarks, previous
@@ -371,10 +415,12 @@ impl View {
             head_ids,
             local_bookmarks,
             tags,
+            tag_annotations: _,
             remote_views,
             git_refs,
             git_head,
             wc_commit_ids,
+            bisect_marks,
         } = &self.data;
         itertools::chain!(
             head_ids,
@@ -388,7 +434,8 @@ impl View {
             }),
             git_refs.values().flat_map(ref_target_ids),
             ref_target_ids(git_head),
-            wc_commit_ids.values()
+            wc_commit_ids.values(),
+            bisect_marks.keys()
         )
     }
 
@@ -31,6 +31,7 @@ use crate::commit::Commit;
 use crate::conflicts::ConflictMarkerStyle;
 use crate::dag_walk;
 use crate::fsmonitor::FsmonitorSettings;
+use crate::gitattributes::GitAttributesError;
 use crate::gitignore::GitIgnoreError;
 use crate::gitignore::GitIgnoreFile;
 use crate::matchers::EverythingMatcher;
@@ -183,6 +184,9 @@ pub enum SnapshotError {
     /// Checking path with ignore patterns failed.
     #[error(transparent)]
     GitIgnoreError(#[from] GitIgnoreError),
+    /// Reading `.gitattributes` files failed.
+    #[error(transparent)]
+    GitAttributesError(#[from] GitAttributesError),
     /// Some other error happened while snapshotting the working copy.
     #[error("{message}")]
     Other {
@@ -292,6 +296,9 @@ pub struct CheckoutStats {
     /// working copy but were skipped because there was an untracked (probably
     /// ignored) file in its place.
     pub skipped_files: u32,
+    /// The number of Git LFS files which were written as pointer files
+    /// because their contents couldn't be read.
+    pub lfs_pointer_files: u32,
 }
 
 /// The working-copy checkout failed.
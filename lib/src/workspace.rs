@@ -28,6 +28,7 @@ use thiserror::Error;
 use crate::backend::BackendInitError;
 use crate::backend::MergedTreeId;
 use crate::commit::Commit;
+use crate::file_util::normalize_path;
 use crate::file_util::IoResultExt as _;
 use crate::file_util::PathError;
 use crate::local_backend::LocalBackend;
@@ -53,6 +54,7 @@ use crate::settings::UserSettings;
 use crate::signing::SignInitError;
 use crate::signing::Signer;
 use crate::store::Store;
+use crate::view::View;
 use crate::working_copy::CheckoutError;
 use crate::working_copy::CheckoutOptions;
 use crate::working_copy::CheckoutStats;
@@ -60,6 +62,8 @@ use crate::working_copy::LockedWorkingCopy;
 use crate::working_copy::WorkingCopy;
 use crate::working_copy::WorkingCopyFactory;
 use crate::working_copy::WorkingCopyStateError;
+use crate::workspace_store::SimpleWorkspaceStore;
+use crate::workspace_store::WorkspaceStoreError;
 
 #[derive(Error, Debug)]
 pub enum WorkspaceInitError {
@@ -79,6 +83,8 @@ pub enum WorkspaceInitError {
     Backend(#[from] BackendInitError),
     #[error(transparent)]
     SignInit(#[from] SignInitError),
+    #[error(transparent)]
+    WorkspaceStore(#[from] WorkspaceStoreError),
 }
 
 #[derive(Error, Debug)]
@@ -317,6 +323,8 @@ impl Workspace {
             )?;
             let repo_loader = repo.loader().clone();
             let workspace = Workspace::new(workspace_root, repo_dir, working_copy, repo_loader)?;
+            SimpleWorkspaceStore::load(workspace.repo_path())
+                .add(workspace.workspace_id(), workspace.workspace_root())?;
             Ok((workspace, repo))
         })()
         .inspect_err(|_err| {
@@ -378,6 +386,8 @@ impl Workspace {
             working_copy,
             repo.loader().clone(),
         )?;
+        SimpleWorkspaceStore::load(workspace.repo_path())
+            .add(workspace.workspace_id(), workspace.workspace_root())?;
         Ok((workspace, repo))
     }
 
@@ -404,6 +414,22 @@ impl Workspace {
         &self.repo_path
     }
 
+    /// Returns the previous location of the workspace if it appears to have
+    /// been moved without updating the paths pointing to it.
+    pub fn moved_from(&self) -> Result<Option<PathBuf>, WorkspaceStoreError> {
+        let store = SimpleWorkspaceStore::load(&self.repo_path);
+        let Some(recorded_root) = store.get_workspace_path(self.workspace_id())? else {
+            return Ok(None);
+        };
+        // If the old location still has a workspace, this is probably a copy.
+        if canonicalize_lenient(&recorded_root) == canonicalize_lenient(&self.workspace_root)
+            || recorded_root.join(".jj").is_dir()
+        {
+            return Ok(None);
+        }
+        Ok(Some(recorded_root))
+    }
+
     pub fn repo_loader(&self) -> &RepoLoader {
         &self.repo_loader
     }
@@ -470,6 +496,28 @@ impl LockedWorkspace<'_> {
         self.locked_wc.as_mut()
     }
 
+    /// Records the location of the workspace in the workspace store if it
+    /// isn't recorded yet. Workspaces created by older versions are recorded
+    /// this way. Nothing is recorded if the workspace isn't in the `view`,
+    /// e.g. because it was forgotten.
+    ///
+    /// This is done while the working copy is locked so that read-only
+    /// commands don't write to the repo.
+    pub fn backfill_workspace_store(&self, view: &View) -> Result<(), WorkspaceStoreError> {
+        let workspace_id = self.base.workspace_id();
+        if view.get_wc_commit_id(workspace_id).is_none() {
+            return Ok(());
+        }
+        let store = SimpleWorkspaceStore::load(&self.base.repo_path);
+        if store.get_workspace_path(workspace_id)?.is_some() {
+            return Ok(());
+        }
+        store.add(
+            workspace_id,
+            &canonicalize_lenient(&self.base.workspace_root),
+        )
+    }
+
     pub fn finish(self, operation_id: OperationId) -> Result<(), WorkingCopyStateError> {
         let new_wc = self.locked_wc.finish(operation_id)?;
         self.base.working_copy = new_wc;
@@ -477,6 +525,238 @@ impl LockedWorkspace<'_> {
     }
 }
 
+#[derive(Debug, Error)]
+pub enum WorkspaceMoveError {
+    #[error("The destination {0} already exists")]
+    DestinationExists(PathBuf),
+    #[error("Cannot move the workspace into itself")]
+    DestinationInsideWorkspace,
+    #[error("Invalid destination {0}")]
+    InvalidDestination(PathBuf),
+    #[error("The workspace contains the repo")]
+    WorkspaceHasRepo,
+    #[error("Repo path could not be interpreted as Unicode text")]
+    NonUnicodePath,
+    #[error(transparent)]
+    Path(#[from] PathError),
+    #[error(transparent)]
+    WorkspaceStore(#[from] WorkspaceStoreError),
+}
+
+/// Paths updated by [`repair_workspace_paths()`].
+#[derive(Clone, Debug, Default)]
+pub struct RepairedWorkspacePaths {
+    /// Current location of the workspace.
+    pub workspace_root: PathBuf,
+    /// Previous location of the workspace, if known.
+    pub old_root: Option<PathBuf>,
+    /// Files in the workspace which were rewritten to point to the right
+    /// location.
+    pub updated_files: Vec<PathBuf>,
+    /// Other workspaces whose repo path was updated.
+    pub updated_workspaces: Vec<WorkspaceId>,
+}
+
+/// Moves the `workspace` directory to `new_root`, and updates the paths
+/// pointing to it.
+pub fn move_workspace(
+    workspace: &Workspace,
+    new_root: &Path,
+) -> Result<RepairedWorkspacePaths, WorkspaceMoveError> {
+    let old_root = workspace.workspace_root();
+    let (Some(new_parent), Some(new_name)) = (new_root.parent(), new_root.file_name()) else {
+        return Err(WorkspaceMoveError::InvalidDestination(new_root.to_owned()));
+    };
+    let new_parent = dunce::canonicalize(new_parent).context(new_parent)?;
+    let new_root = new_parent.join(new_name);
+    if new_root.symlink_metadata().is_ok() {
+        return Err(WorkspaceMoveError::DestinationExists(new_root));
+    }
+    if new_root.starts_with(old_root) {
+        return Err(WorkspaceMoveError::DestinationInsideWorkspace);
+    }
+    crate::file_util::move_dir(old_root, &new_root)?;
+    repair_workspace_paths(&new_root, workspace.workspace_id(), Some(old_root))
+}
+
+/// Updates the paths pointing to or from the workspace at `workspace_root`
+/// after it was moved from `old_root`. If `old_root` isn't specified, the
+/// location recorded in the workspace store is used.
+///
+/// If the workspace has the repo, the other workspaces of the repo are updated
+/// to point to the new location. The workspace store is updated to record the
+/// current location in any case.
+pub fn repair_workspace_paths(
+    workspace_root: &Path,
+    workspace_id: &WorkspaceId,
+    old_root: Option<&Path>,
+) -> Result<RepairedWorkspacePaths, WorkspaceMoveError> {
+    let workspace_root = dunce::canonicalize(workspace_root).context(workspace_root)?;
+    let jj_dir = workspace_root.join(".jj");
+    let repo_link_path = jj_dir.join("repo");
+    let has_repo = repo_link_path.is_dir();
+    let mut repaired = RepairedWorkspacePaths {
+        workspace_root: workspace_root.clone(),
+        old_root: old_root.map(canonicalize_lenient),
+        ..Default::default()
+    };
+
+    let repo_path = if has_repo {
+        repo_link_path.clone()
+    } else {
+        let repo_link = read_link_file(&repo_link_path)?;
+        let relocated_link = old_root
+            .and_then(|old_root| relocate_link(&repo_link, &jj_dir, old_root, &workspace_root));
+        if let Some(new_link) = &relocated_link {
+            write_link_file(&repo_link_path, new_link)?;
+            repaired.updated_files.push(repo_link_path.clone());
+        }
+        let repo_path = jj_dir.join(relocated_link.unwrap_or(repo_link));
+        dunce::canonicalize(&repo_path).context(&repo_path)?
+    };
+
+    let store = SimpleWorkspaceStore::load(&repo_path);
+    if repaired.old_root.is_none() {
+        repaired.old_root = store
+            .get_workspace_path(workspace_id)?
+            .map(|recorded_root| canonicalize_lenient(&recorded_root))
+            .filter(|recorded_root| *recorded_root != workspace_root);
+    }
+    store.add(workspace_id, &workspace_root)?;
+    let Some(old_root) = repaired.old_root.as_deref() else {
+        return Ok(repaired);
+    };
+
+    // A colocated Git repo or submodule can have a ".git" file pointing to the
+    // Git directory.
+    let dot_git_path = workspace_root.join(".git");
+    if dot_git_path.is_file() {
+        let content = fs::read_to_string(&dot_git_path).context(&dot_git_path)?;
+        if let Some(git_dir) = content.trim_end().strip_prefix("gitdir: ") {
+            if let Some(new_git_dir) = relocate_link(
+                Path::new(git_dir),
+                &workspace_root,
+                old_root,
+                &workspace_root,
+            ) {
+                let new_git_dir = new_git_dir
+                    .to_str()
+                    .ok_or(WorkspaceMoveError::NonUnicodePath)?;
+                fs::write(&dot_git_path, format!("gitdir: {new_git_dir}\n"))
+                    .context(&dot_git_path)?;
+                repaired.updated_files.push(dot_git_path);
+            }
+        }
+    }
+
+    if !has_repo {
+        return Ok(repaired);
+    }
+    let store_path = repo_path.join("store");
+    let git_target_path = store_path.join("git_target");
+    if git_target_path.is_file() {
+        let git_target = read_link_file(&git_target_path)?;
+        if let Some(new_target) = relocate_link(&git_target, &store_path, old_root, &workspace_root)
+        {
+            write_link_file(&git_target_path, &new_target)?;
+            repaired.updated_files.push(git_target_path);
+        }
+    }
+    let old_repo_path = old_root.join(".jj").join("repo");
+    for (other_id, other_root) in store.workspace_paths()? {
+        if other_id == *workspace_id {
+            continue;
+        }
+        // Workspaces nested in this workspace were moved together.
+        let other_root = canonicalize_lenient(&other_root);
+        let other_root = match other_root.strip_prefix(old_root) {
+            Ok(relative) => {
+                let moved_root = workspace_root.join(relative);
+                store.add(&other_id, &moved_root)?;
+                moved_root
+            }
+            Err(_) => other_root,
+        };
+        let other_jj_dir = other_root.join(".jj");
+        let other_link_path = other_jj_dir.join("repo");
+        if !other_link_path.is_file() {
+            continue;
+        }
+        let other_repo_path = other_jj_dir.join(read_link_file(&other_link_path)?);
+        if canonicalize_lenient(&other_repo_path) == old_repo_path {
+            write_link_file(&other_link_path, &repo_path)?;
+            repaired.updated_workspaces.push(other_id);
+        }
+    }
+    Ok(repaired)
+}
+
+/// Points the workspace at `workspace_root` to the repo at `repo_path`. The
+/// workspace must not contain the repo.
+pub fn set_workspace_repo_path(
+    workspace_root: &Path,
+    repo_path: &Path,
+) -> Result<(), WorkspaceMoveError> {
+    let repo_link_path = workspace_root.join(".jj").join("repo");
+    if repo_link_path.is_dir() {
+        return Err(WorkspaceMoveError::WorkspaceHasRepo);
+    }
+    let repo_path = dunce::canonicalize(repo_path).context(repo_path)?;
+    write_link_file(&repo_link_path, &repo_path)
+}
+
+/// Resolves symlinks in the longest existing ancestor of the `path`. Unlike
+/// `canonicalize()`, this works for paths which no longer exist, such as the
+/// previous location of a moved workspace.
+fn canonicalize_lenient(path: &Path) -> PathBuf {
+    let path = normalize_path(path);
+    let mut existing = path.as_path();
+    loop {
+        if let Ok(canonical) = dunce::canonicalize(existing) {
+            // strip_prefix() can't fail since existing is an ancestor of path
+            return canonical.join(path.strip_prefix(existing).unwrap());
+        }
+        match existing.parent() {
+            Some(parent) => existing = parent,
+            None => return path,
+        }
+    }
+}
+
+fn read_link_file(path: &Path) -> Result<PathBuf, WorkspaceMoveError> {
+    let buf = fs::read(path).context(path)?;
+    let link = String::from_utf8(buf).map_err(|_| WorkspaceMoveError::NonUnicodePath)?;
+    Ok(PathBuf::from(link))
+}
+
+fn write_link_file(path: &Path, link: &Path) -> Result<(), WorkspaceMoveError> {
+    let link = link.to_str().ok_or(WorkspaceMoveError::NonUnicodePath)?;
+    fs::write(path, link).context(path)?;
+    Ok(())
+}
+
+/// Returns the new value of the `link` stored in `link_dir` if the link no
+/// longer points to the right location after moving `old_root` to `new_root`.
+///
+/// Absolute links into the moved directory are rewritten. Relative links are
+/// kept if both ends were moved, and made absolute otherwise.
+fn relocate_link(
+    link: &Path,
+    link_dir: &Path,
+    old_root: &Path,
+    new_root: &Path,
+) -> Option<PathBuf> {
+    if link.is_absolute() {
+        let link = canonicalize_lenient(link);
+        let relative = link.strip_prefix(old_root).ok()?;
+        Some(new_root.join(relative))
+    } else {
+        let old_link_dir = old_root.join(link_dir.strip_prefix(new_root).ok()?);
+        let target = normalize_path(&old_link_dir.join(link));
+        (!target.starts_with(old_root)).then_some(target)
+    }
+}
+
 // Factory trait to build WorkspaceLoaders given the workspace root.
 pub trait WorkspaceLoaderFactory {
     fn create(&self, workspace_root: &Path)
@@ -564,10 +844,11 @@ impl DefaultWorkspaceLoader {
             let buf = fs::read(&repo_dir).context(&repo_dir)?;
             let repo_path_str =
                 String::from_utf8(buf).map_err(|_| WorkspaceLoadError::NonUnicodePath)?;
-            repo_dir = dunce::canonicalize(jj_dir.join(&repo_path_str)).context(&repo_path_str)?;
-            if !repo_dir.is_dir() {
-                return Err(WorkspaceLoadError::RepoDoesNotExist(repo_dir));
+            let linked_repo_dir = jj_dir.join(&repo_path_str);
+            if !linked_repo_dir.is_dir() {
+                return Err(WorkspaceLoadError::RepoDoesNotExist(linked_repo_dir));
             }
+            repo_dir = dunce::canonicalize(&linked_repo_dir).context(&repo_path_str)?;
         }
         let working_copy_state_path = jj_dir.join("working_copy");
         Ok(Self {
@@ -627,11 +908,11 @@ pub fn default_working_copy_factories() -> WorkingCopyFactories {
     let mut factories = WorkingCopyFactories::new();
     factories.insert(
         LocalWorkingCopy::name().to_owned(),
-        Box::new(LocalWorkingCopyFactory {}),
+        Box::new(LocalWorkingCopyFactory::new()),
     );
     factories
 }
 
 pub fn default_working_copy_factory() -> Box<dyn WorkingCopyFactory> {
-    Box::new(LocalWorkingCopyFactory {})
+    Box::new(LocalWorkingCopyFactory::new())
 }
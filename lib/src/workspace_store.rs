@@ -0,0 +1,232 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Records where the workspaces of a repo are on disk.
+
+#![allow(missing_docs)]
+
+use std::fs;
+use std::io;
+use std::io::Write as _;
+use std::path::Path;
+use std::path::PathBuf;
+
+use prost::Message as _;
+use tempfile::NamedTempFile;
+use thiserror::Error;
+
+use crate::file_util::IoResultExt as _;
+use crate::file_util::PathError;
+use crate::lock::FileLock;
+use crate::lock::FileLockError;
+use crate::op_store::WorkspaceId;
+use crate::protos::simple_workspace_store as proto;
+
+#[derive(Debug, Error)]
+pub enum WorkspaceStoreError {
+    #[error(transparent)]
+    Path(#[from] PathError),
+    #[error(transparent)]
+    Lock(#[from] FileLockError),
+    #[error("Failed to decode workspace store {path}")]
+    Decode {
+        path: PathBuf,
+        source: prost::DecodeError,
+    },
+    #[error("Workspace path could not be interpreted as Unicode text")]
+    NonUnicodePath,
+}
+
+/// Workspace paths stored in `<repo>/workspace_store/index`.
+///
+/// The paths are only used to find the workspaces when the repo is moved, so
+/// the store may be out of date. Workspaces created by older versions aren't
+/// recorded at all.
+#[derive(Clone, Debug)]
+pub struct SimpleWorkspaceStore {
+    store_dir: PathBuf,
+}
+
+impl SimpleWorkspaceStore {
+    /// Creates store for the repo at `repo_path`. The store directory is
+    /// created on first write.
+    pub fn load(repo_path: &Path) -> Self {
+        SimpleWorkspaceStore {
+            store_dir: repo_path.join("workspace_store"),
+        }
+    }
+
+    fn index_path(&self) -> PathBuf {
+        self.store_dir.join("index")
+    }
+
+    fn lock(&self) -> Result<FileLock, WorkspaceStoreError> {
+        fs::create_dir_all(&self.store_dir).context(&self.store_dir)?;
+        Ok(FileLock::lock(self.store_dir.join("lock"))?)
+    }
+
+    fn read(&self) -> Result<Vec<proto::Workspace>, WorkspaceStoreError> {
+        let path = self.index_path();
+        let buf = match fs::read(&path) {
+            Ok(buf) => buf,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(vec![]),
+            Err(error) => return Err(PathError { path, error }.into()),
+        };
+        let workspaces = proto::Workspaces::decode(&*buf)
+            .map_err(|source| WorkspaceStoreError::Decode { path, source })?;
+        Ok(workspaces.workspaces)
+    }
+
+    fn write(&self, workspaces: Vec<proto::Workspace>) -> Result<(), WorkspaceStoreError> {
+        let proto = proto::Workspaces { workspaces };
+        let mut temp_file = NamedTempFile::new_in(&self.store_dir).context(&self.store_dir)?;
+        temp_file
+            .write_all(&proto.encode_to_vec())
+            .context(temp_file.path())?;
+        let path = self.index_path();
+        temp_file
+            .persist(&path)
+            .map_err(|err| err.error)
+            .context(&path)?;
+        Ok(())
+    }
+
+    fn update(
+        &self,
+        f: impl FnOnce(&mut Vec<proto::Workspace>),
+    ) -> Result<(), WorkspaceStoreError> {
+        let _lock = self.lock()?;
+        let mut workspaces = self.read()?;
+        f(&mut workspaces);
+        self.write(workspaces)
+    }
+
+    /// Records that the workspace `workspace_id` is at `path`, replacing the
+    /// previous path if any.
+    pub fn add(&self, workspace_id: &WorkspaceId, path: &Path) -> Result<(), WorkspaceStoreError> {
+        let path = path
+            .to_str()
+            .ok_or(WorkspaceStoreError::NonUnicodePath)?
+            .to_owned();
+        self.update(|workspaces| {
+            workspaces.retain(|workspace| workspace.name != workspace_id.as_str());
+            workspaces.push(proto::Workspace {
+                name: workspace_id.as_str().to_owned(),
+                path,
+            });
+        })
+    }
+
+    pub fn forget(&self, workspace_ids: &[&WorkspaceId]) -> Result<(), WorkspaceStoreError> {
+        self.update(|workspaces| {
+            workspaces
+                .retain(|workspace| workspace_ids.iter().all(|id| workspace.name != id.as_str()));
+        })
+    }
+
+    /// Renames the workspace `old_workspace_id`. It's no-op if the workspace
+    /// isn't recorded, so the rename can be retried.
+    pub fn rename(
+        &self,
+        old_workspace_id: &WorkspaceId,
+        new_workspace_id: &WorkspaceId,
+    ) -> Result<(), WorkspaceStoreError> {
+        self.update(|workspaces| {
+            if workspaces
+                .iter()
+                .all(|workspace| workspace.name != old_workspace_id.as_str())
+            {
+                return;
+            }
+            workspaces.retain(|workspace| workspace.name != new_workspace_id.as_str());
+            for workspace in workspaces {
+                if workspace.name == old_workspace_id.as_str() {
+                    workspace.name = new_workspace_id.as_str().to_owned();
+                }
+            }
+        })
+    }
+
+    /// Returns the recorded path of the workspace `workspace_id`.
+    pub fn get_workspace_path(
+        &self,
+        workspace_id: &WorkspaceId,
+    ) -> Result<Option<PathBuf>, WorkspaceStoreError> {
+        Ok(self
+            .read()?
+            .into_iter()
+            .find(|workspace| workspace.name == workspace_id.as_str())
+            .map(|workspace| PathBuf::from(workspace.path)))
+    }
+
+    /// Returns all recorded workspaces and their paths.
+    pub fn workspace_paths(&self) -> Result<Vec<(WorkspaceId, PathBuf)>, WorkspaceStoreError> {
+        Ok(self
+            .read()?
+            .into_iter()
+            .map(|workspace| {
+                (
+                    WorkspaceId::new(workspace.name),
+                    PathBuf::from(workspace.path),
+                )
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_workspace_store() {
+        let temp_dir = testutils::new_temp_dir();
+        let store = SimpleWorkspaceStore::load(temp_dir.path());
+        let default_id = WorkspaceId::default();
+        let other_id = WorkspaceId::new("other".to_owned());
+        let renamed_id = WorkspaceId::new("renamed".to_owned());
+        assert_eq!(store.workspace_paths().unwrap(), vec![]);
+
+        store.add(&default_id, Path::new("/repo")).unwrap();
+        store.add(&other_id, Path::new("/other")).unwrap();
+        store.add(&default_id, Path::new("/moved")).unwrap();
+        assert_eq!(
+            store.get_workspace_path(&default_id).unwrap(),
+            Some(PathBuf::from("/moved"))
+        );
+        assert_eq!(
+            store.workspace_paths().unwrap(),
+            vec![
+                (other_id.clone(), PathBuf::from("/other")),
+                (default_id.clone(), PathBuf::from("/moved")),
+            ]
+        );
+
+        store.rename(&other_id, &renamed_id).unwrap();
+        assert_eq!(store.get_workspace_path(&other_id).unwrap(), None);
+        assert_eq!(
+            store.get_workspace_path(&renamed_id).unwrap(),
+            Some(PathBuf::from("/other"))
+        );
+        // Renaming again is no-op
+        store.rename(&other_id, &renamed_id).unwrap();
+        assert_eq!(
+            store.get_workspace_path(&renamed_id).unwrap(),
+            Some(PathBuf::from("/other"))
+        );
+
+        store.forget(&[&default_id, &renamed_id]).unwrap();
+        assert_eq!(store.workspace_paths().unwrap(), vec![]);
+    }
+}
@@ -7,10 +7,13 @@ fn test_no_forgotten_test_files() {
 }
 
 mod test_annotate;
+mod test_api;
 mod test_bad_locking;
+mod test_bisect;
 mod test_commit_builder;
 mod test_commit_concurrent;
 mod test_conflicts;
+mod test_copies;
 mod test_default_revset_graph_iterator;
 mod test_git;
 mod test_git_backend;
@@ -13,10 +13,13 @@
 // limitations under the License.
 
 use std::fmt::Write as _;
+use std::ops::Range;
 use std::rc::Rc;
 
 use jj_lib::annotate::get_annotation_for_file;
 use jj_lib::annotate::get_annotation_with_file_content;
+use jj_lib::annotate::get_line_range_history;
+use jj_lib::annotate::AnnotationOptions;
 use jj_lib::annotate::FileAnnotation;
 use jj_lib::backend::CommitId;
 use jj_lib::backend::MergedTreeId;
@@ -32,6 +35,7 @@ use jj_lib::revset::ResolvedRevsetExpression;
 use jj_lib::revset::RevsetExpression;
 use testutils::create_tree;
 use testutils::TestRepo;
+use testutils::TestRepoBackend;
 
 fn create_commit_fn(
     mut_repo: &mut MutableRepo,
@@ -68,7 +72,23 @@ fn annotate_within(
     domain: &Rc<ResolvedRevsetExpression>,
     file_path: &RepoPath,
 ) -> String {
-    let annotation = get_annotation_for_file(repo, commit, domain, file_path).unwrap();
+    annotate_with_options(
+        repo,
+        commit,
+        domain,
+        file_path,
+        &AnnotationOptions::default(),
+    )
+}
+
+fn annotate_with_options(
+    repo: &dyn Repo,
+    commit: &Commit,
+    domain: &Rc<ResolvedRevsetExpression>,
+    file_path: &RepoPath,
+    options: &AnnotationOptions,
+) -> String {
+    let annotation = get_annotation_for_file(repo, commit, domain, file_path, options).unwrap();
     format_annotation(repo, &annotation)
 }
 
@@ -84,8 +104,10 @@ fn annotate_parent_tree(repo: &dyn Repo, commit: &Commit, file_path: &RepoPath)
         value => panic!("unexpected path value: {value:?}"),
     };
     let domain = RevsetExpression::all();
+    let options = AnnotationOptions::default();
     let annotation =
-        get_annotation_with_file_content(repo, commit.id(), &domain, file_path, text).unwrap();
+        get_annotation_with_file_content(repo, commit.id(), &domain, file_path, text, &options)
+            .unwrap();
     format_annotation(repo, &annotation)
 }
 
@@ -101,6 +123,41 @@ fn format_annotation(repo: &dyn Repo, annotation: &FileAnnotation) -> String {
     output
 }
 
+fn line_range_history(
+    repo: &dyn Repo,
+    commit: &Commit,
+    file_path: &RepoPath,
+    line_range: Range<usize>,
+) -> String {
+    let domain = RevsetExpression::all();
+    line_range_history_in(repo, commit, &domain, file_path, line_range)
+}
+
+fn line_range_history_in(
+    repo: &dyn Repo,
+    commit: &Commit,
+    domain: &Rc<ResolvedRevsetExpression>,
+    file_path: &RepoPath,
+    line_range: Range<usize>,
+) -> String {
+    let changes = get_line_range_history(repo, commit, domain, file_path, line_range).unwrap();
+    let mut output = String::new();
+    for change in changes {
+        let commit = repo.store().get_commit(&change.commit_id).unwrap();
+        let desc = commit.description().trim_end();
+        let old_range = change.old.line_range;
+        let new_range = change.new.line_range;
+        writeln!(output, "{desc}: {old_range:?} -> {new_range:?}").unwrap();
+        for line in change.old.text.split_inclusive(|b| *b == b'\n') {
+            write!(output, "-{}", String::from_utf8_lossy(line)).unwrap();
+        }
+        for line in change.new.text.split_inclusive(|b| *b == b'\n') {
+            write!(output, "+{}", String::from_utf8_lossy(line)).unwrap();
+        }
+    }
+    output
+}
+
 #[test]
 fn test_annotate_linear() {
     let test_repo = TestRepo::init();
@@ -377,3 +434,267 @@ fn test_annotate_file_directory_transition() {
     commit2: 2
     "#);
 }
+
+#[test]
+fn test_annotate_follow_copies() {
+    // Copy records are only supported by the Git backend
+    let test_repo = TestRepo::init_with_backend(TestRepoBackend::Git);
+    let repo = &test_repo.repo;
+
+    let root_commit_id = repo.store().root_commit_id();
+    let file_path1 = RepoPath::from_internal_string("file1");
+    let file_path2 = RepoPath::from_internal_string("file2");
+
+    let mut tx = repo.start_transaction();
+    let mut create_commit = create_commit_fn(tx.repo_mut());
+    let tree1 = create_tree(repo, &[(file_path1, "1\n")]);
+    let tree2 = create_tree(repo, &[(file_path1, "1\n2\n3\n")]);
+    let tree3 = create_tree(repo, &[(file_path2, "1\n2\n3\n")]);
+    let tree4 = create_tree(repo, &[(file_path2, "1\n4\n3\n")]);
+    let commit1 = create_commit("commit1", &[root_commit_id], tree1.id());
+    let commit2 = create_commit("commit2", &[commit1.id()], tree2.id());
+    let commit3 = create_commit("commit3", &[commit2.id()], tree3.id()); // rename
+    let commit4 = create_commit("commit4", &[commit3.id()], tree4.id());
+    drop(create_commit);
+
+    let domain = RevsetExpression::all();
+    let options = AnnotationOptions {
+        follow_copies: true,
+        ..Default::default()
+    };
+    insta::assert_snapshot!(annotate(tx.repo(), &commit4, file_path2), @r"
+    commit3: 1
+    commit4: 4
+    commit3: 3
+    ");
+    insta::assert_snapshot!(
+        annotate_with_options(tx.repo(), &commit4, &domain, file_path2, &options), @r"
+    commit1: 1
+    commit4: 4
+    commit2: 3
+    ");
+}
+
+#[test]
+fn test_annotate_follow_copies_source_modified() {
+    // Copy records are only supported by the Git backend
+    let test_repo = TestRepo::init_with_backend(TestRepoBackend::Git);
+    let repo = &test_repo.repo;
+
+    let root_commit_id = repo.store().root_commit_id();
+    let file_path1 = RepoPath::from_internal_string("file1");
+    let file_path2 = RepoPath::from_internal_string("file2");
+    let other_path = RepoPath::from_internal_string("other");
+
+    let mut tx = repo.start_transaction();
+    let mut create_commit = create_commit_fn(tx.repo_mut());
+    let tree1 = create_tree(repo, &[(file_path1, "1\n"), (file_path2, "old\n")]);
+    let tree2 = create_tree(repo, &[(file_path1, "1\n2\n")]); // delete file2
+    let tree3 = create_tree(repo, &[(file_path1, "1\n2\n")]);
+    let tree4 = create_tree(repo, &[(file_path1, "1\n2\n3\n"), (other_path, "x\n")]);
+    let tree5 = create_tree(repo, &[(file_path2, "1\n2\n3\n"), (other_path, "x\n")]);
+    let commit1 = create_commit("commit1", &[root_commit_id], tree1.id());
+    let commit2 = create_commit("commit2", &[commit1.id()], tree2.id());
+    let commit3 = create_commit("commit3", &[commit2.id()], tree3.id());
+    let commit4 = create_commit("commit4", &[commit3.id()], tree4.id());
+    let commit5 = create_commit("commit5", &[commit4.id()], tree5.id()); // rename
+    drop(create_commit);
+
+    // The changes to file1 made after file2 was deleted are followed
+    let domain = RevsetExpression::all();
+    let options = AnnotationOptions {
+        follow_copies: true,
+        ..Default::default()
+    };
+    insta::assert_snapshot!(
+        annotate_with_options(tx.repo(), &commit5, &domain, file_path2, &options), @r"
+    commit1: 1
+    commit2: 2
+    commit4: 3
+    ");
+}
+
+#[test]
+fn test_annotate_ignore_revs() {
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+
+    let root_commit_id = repo.store().root_commit_id();
+    let file_path = RepoPath::from_internal_string("file");
+
+    let mut tx = repo.start_transaction();
+    let mut create_commit = create_commit_fn(tx.repo_mut());
+    let tree1 = create_tree(repo, &[(file_path, "1a\n1b\n1c\n")]);
+    let tree2 = create_tree(repo, &[(file_path, "1a\n2b\n1c\n")]);
+    let tree3 = create_tree(repo, &[(file_path, "3a\n3b\n3x\n1c\n")]);
+    let tree4 = create_tree(repo, &[(file_path, "3a\n3b\n3x\n1c\n4\n")]);
+    let commit1 = create_commit("commit1", &[root_commit_id], tree1.id());
+    let commit2 = create_commit("commit2", &[commit1.id()], tree2.id());
+    let commit3 = create_commit("commit3", &[commit2.id()], tree3.id()); // reformat
+    let commit4 = create_commit("commit4", &[commit3.id()], tree4.id());
+    drop(create_commit);
+
+    let domain = RevsetExpression::all();
+    insta::assert_snapshot!(annotate(tx.repo(), &commit4, file_path), @r"
+    commit3: 3a
+    commit3: 3b
+    commit3: 3x
+    commit1: 1c
+    commit4: 4
+    ");
+
+    // Modified lines are attributed to the parent lines, but the added line
+    // is still attributed to the ignored commit
+    let options = AnnotationOptions {
+        ignore_revs: RevsetExpression::commit(commit3.id().clone()),
+        ..Default::default()
+    };
+    insta::assert_snapshot!(
+        annotate_with_options(tx.repo(), &commit4, &domain, file_path, &options), @r"
+    commit1: 3a
+    commit2: 3b
+    commit3: 3x
+    commit1: 1c
+    commit4: 4
+    ");
+
+    // Ignored commits which added lines
+    let options = AnnotationOptions {
+        ignore_revs: RevsetExpression::commits(vec![commit1.id().clone(), commit4.id().clone()]),
+        ..Default::default()
+    };
+    insta::assert_snapshot!(
+        annotate_with_options(tx.repo(), &commit4, &domain, file_path, &options), @r"
+    commit3: 3a
+    commit3: 3b
+    commit3: 3x
+    commit1: 1c
+    commit4: 4
+    ");
+}
+
+#[test]
+fn test_line_range_history_linear() {
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+
+    let root_commit_id = repo.store().root_commit_id();
+    let file_path = RepoPath::from_internal_string("file");
+
+    let mut tx = repo.start_transaction();
+    let mut create_commit = create_commit_fn(tx.repo_mut());
+    let tree1 = create_tree(repo, &[(file_path, "a\nb\nc\n")]);
+    let tree2 = create_tree(repo, &[(file_path, "a\nB\nc\n")]);
+    let tree3 = create_tree(repo, &[(file_path, "x\na\nB\nc\n")]);
+    let tree4 = create_tree(repo, &[(file_path, "x\na\ny\nB\nc\nd\n")]);
+    let commit1 = create_commit("commit1", &[root_commit_id], tree1.id());
+    let commit2 = create_commit("commit2", &[commit1.id()], tree2.id());
+    let commit3 = create_commit("commit3", &[commit2.id()], tree3.id());
+    let commit4 = create_commit("commit4", &[commit3.id()], tree4.id());
+    drop(create_commit);
+
+    // Lines moved by insertion above aren't modified
+    insta::assert_snapshot!(line_range_history(tx.repo(), &commit4, file_path, 3..4), @r"
+    commit2: 1..2 -> 1..2
+    -b
+    +B
+    commit1: 0..0 -> 1..2
+    +b
+    ");
+    // Line inserted in the middle of the range
+    insta::assert_snapshot!(line_range_history(tx.repo(), &commit4, file_path, 1..5), @r"
+    commit4: 1..4 -> 1..5
+    -a
+    -B
+    -c
+    +a
+    +y
+    +B
+    +c
+    commit2: 0..3 -> 0..3
+    -a
+    -b
+    -c
+    +a
+    +B
+    +c
+    commit1: 0..0 -> 0..3
+    +a
+    +b
+    +c
+    ");
+    // Range is clamped to the file size
+    insta::assert_snapshot!(line_range_history(tx.repo(), &commit4, file_path, 5..10), @r"
+    commit4: 0..0 -> 5..6
+    +d
+    ");
+    insta::assert_snapshot!(line_range_history(tx.repo(), &commit4, file_path, 6..10), @"");
+
+    // Changes made by commits excluded from the domain are attributed to the
+    // closest descendant in the domain
+    let domain = RevsetExpression::all().minus(&RevsetExpression::commit(commit2.id().clone()));
+    let output = line_range_history_in(tx.repo(), &commit4, &domain, file_path, 3..4);
+    insta::assert_snapshot!(output, @r"
+    commit3: 1..2 -> 2..3
+    -b
+    +B
+    commit1: 0..0 -> 1..2
+    +b
+    ");
+}
+
+#[test]
+fn test_line_range_history_merge() {
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+
+    let root_commit_id = repo.store().root_commit_id();
+    let file_path = RepoPath::from_internal_string("file");
+
+    // 4    "a\nB\nC\n"
+    // |\
+    // | 3  "a\nb\nC\n"
+    // 2 |  "a\nB\nc\n"
+    // |/
+    // 1    "a\nb\nc\n"
+    let mut tx = repo.start_transaction();
+    let mut create_commit = create_commit_fn(tx.repo_mut());
+    let tree1 = create_tree(repo, &[(file_path, "a\nb\nc\n")]);
+    let tree2 = create_tree(repo, &[(file_path, "a\nB\nc\n")]);
+    let tree3 = create_tree(repo, &[(file_path, "a\nb\nC\n")]);
+    let tree4 = create_tree(repo, &[(file_path, "a\nB\nC\n")]);
+    let commit1 = create_commit("commit1", &[root_commit_id], tree1.id());
+    let commit2 = create_commit("commit2", &[commit1.id()], tree2.id());
+    let commit3 = create_commit("commit3", &[commit1.id()], tree3.id());
+    let commit4 = create_commit("commit4", &[commit2.id(), commit3.id()], tree4.id());
+    drop(create_commit);
+
+    // The merge commit doesn't modify the lines compared to both parents
+    insta::assert_snapshot!(line_range_history(tx.repo(), &commit4, file_path, 1..2), @r"
+    commit2: 1..2 -> 1..2
+    -b
+    +B
+    commit1: 0..0 -> 1..2
+    +b
+    ");
+    insta::assert_snapshot!(line_range_history(tx.repo(), &commit4, file_path, 1..3), @r"
+    commit4: 1..3 -> 1..3
+    -B
+    -c
+    +B
+    +C
+    commit3: 1..3 -> 1..3
+    -b
+    -c
+    +b
+    +C
+    commit2: 1..3 -> 1..3
+    -b
+    -c
+    +B
+    +c
+    commit1: 0..0 -> 1..3
+    +b
+    +c
+    ");
+}
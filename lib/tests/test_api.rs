@@ -0,0 +1,172 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fs;
+
+use jj_lib::api::ApiErrorKind;
+use jj_lib::api::Session;
+use jj_lib::config::ConfigLayer;
+use jj_lib::config::ConfigSource;
+use jj_lib::repo_path::RepoPath;
+use jj_lib::settings::UserSettings;
+use jj_lib::workspace::default_working_copy_factories;
+use testutils::create_tree;
+use testutils::TestWorkspace;
+
+fn load_session(test_workspace: &TestWorkspace) -> Session {
+    Session::load_with_factories(
+        &testutils::user_settings(),
+        test_workspace.workspace.workspace_root(),
+        &test_workspace.env.default_store_factories(),
+        &default_working_copy_factories(),
+    )
+    .unwrap()
+}
+
+#[test]
+fn test_session_snapshot_and_evaluate_revset() {
+    let test_workspace = TestWorkspace::init();
+    let workspace_root = test_workspace.workspace.workspace_root().to_owned();
+    let mut session = load_session(&test_workspace);
+
+    let wc_commit = session.working_copy_commit().unwrap().unwrap();
+    assert_eq!(session.resolve_single_revision("@").unwrap(), wc_commit);
+
+    // Nothing changed
+    session.snapshot().unwrap();
+    assert_eq!(session.working_copy_commit().unwrap().unwrap(), wc_commit);
+    assert_eq!(session.repo().op_id(), test_workspace.repo.op_id());
+
+    // Snapshot rewrites the working-copy commit
+    fs::write(workspace_root.join("file"), "contents").unwrap();
+    session.snapshot().unwrap();
+    let new_wc_commit = session.working_copy_commit().unwrap().unwrap();
+    assert_ne!(new_wc_commit.id(), wc_commit.id());
+    assert_eq!(new_wc_commit.change_id(), wc_commit.change_id());
+    assert!(new_wc_commit
+        .tree()
+        .unwrap()
+        .path_value(RepoPath::from_internal_string("file"))
+        .unwrap()
+        .is_present());
+    assert_ne!(session.repo().op_id(), test_workspace.repo.op_id());
+
+    // The new operation is visible to other sessions
+    let other_session = load_session(&test_workspace);
+    assert_eq!(
+        other_session.resolve_single_revision("@").unwrap(),
+        new_wc_commit
+    );
+
+    let commits = session.evaluate_revset("all()").unwrap();
+    assert_eq!(
+        commits,
+        vec![
+            new_wc_commit,
+            session.resolve_single_revision("root()").unwrap()
+        ]
+    );
+    assert_eq!(
+        session
+            .resolve_single_revision("none()")
+            .unwrap_err()
+            .kind(),
+        ApiErrorKind::NotSingleRevision
+    );
+    assert_eq!(
+        session.evaluate_revset("nonexistent").unwrap_err().kind(),
+        ApiErrorKind::RevsetResolution
+    );
+    assert_eq!(
+        session.evaluate_revset("(").unwrap_err().kind(),
+        ApiErrorKind::RevsetParse
+    );
+
+    // Built-in aliases are available
+    assert_eq!(
+        session.evaluate_revset("trunk()").unwrap(),
+        vec![session.resolve_single_revision("root()").unwrap()]
+    );
+    assert_eq!(
+        session.evaluate_revset("mutable()").unwrap(),
+        vec![session.resolve_single_revision("@").unwrap()]
+    );
+}
+
+#[test]
+fn test_session_invalid_revset_alias() {
+    let test_workspace = TestWorkspace::init();
+    let mut config = testutils::base_user_config();
+    config.add_layer(ConfigLayer::parse(ConfigSource::User, "revset-aliases.'f(' = 'x'").unwrap());
+    let settings = UserSettings::from_config(config).unwrap();
+    let err = Session::load_with_factories(
+        &settings,
+        test_workspace.workspace.workspace_root(),
+        &test_workspace.env.default_store_factories(),
+        &default_working_copy_factories(),
+    )
+    .err()
+    .unwrap();
+    assert_eq!(err.kind(), ApiErrorKind::Config);
+    insta::assert_snapshot!(err, @"Invalid type or value for revset-aliases.f(");
+}
+
+#[test]
+fn test_session_finish_transaction() {
+    let test_workspace = TestWorkspace::init();
+    let workspace_root = test_workspace.workspace.workspace_root().to_owned();
+    let mut session = load_session(&test_workspace);
+    let workspace_id = session.workspace_id().clone();
+    let root_commit = session.resolve_single_revision("root()").unwrap();
+
+    // Check out a new commit
+    let tree = create_tree(
+        session.repo(),
+        &[(RepoPath::from_internal_string("file"), "contents")],
+    );
+    let mut tx = session.start_transaction();
+    let commit = tx
+        .repo_mut()
+        .new_commit(vec![root_commit.id().clone()], tree.id())
+        .write()
+        .unwrap();
+    tx.repo_mut().edit(workspace_id.clone(), &commit).unwrap();
+    let stats = session.finish_transaction(tx, "edit commit").unwrap();
+    assert_eq!(stats.unwrap().added_files, 1);
+    assert_eq!(
+        session.repo().operation().metadata().description,
+        "edit commit"
+    );
+    assert_eq!(
+        fs::read_to_string(workspace_root.join("file")).unwrap(),
+        "contents"
+    );
+
+    // Rewrite the working-copy commit
+    let mut tx = session.start_transaction();
+    let rewritten = tx
+        .repo_mut()
+        .rewrite_commit(&commit)
+        .set_description("described")
+        .write()
+        .unwrap();
+    let stats = session.finish_transaction(tx, "describe commit").unwrap();
+    // The tree is unchanged, so there's nothing to check out
+    assert!(stats.is_none());
+    assert_eq!(session.resolve_single_revision("@").unwrap(), rewritten);
+
+    // The working copy isn't considered stale
+    session.snapshot().unwrap();
+    assert_eq!(session.resolve_single_revision("@").unwrap(), rewritten);
+}
@@ -0,0 +1,225 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::BTreeMap;
+
+use assert_matches::assert_matches;
+use jj_lib::backend::CommitId;
+use jj_lib::bisect::BisectError;
+use jj_lib::bisect::BisectionStep;
+use jj_lib::bisect::Bisector;
+use jj_lib::commit::Commit;
+use jj_lib::op_store::BisectMark;
+use jj_lib::repo::Repo;
+use maplit::btreemap;
+use testutils::CommitGraphBuilder;
+use testutils::TestRepo;
+
+/// Runs the bisection to completion, marking commits bad if they are
+/// descendants of `first_bad` and good otherwise. Returns the result and the
+/// evaluated commits.
+fn bisect_to_end(
+    repo: &dyn Repo,
+    mut marks: BTreeMap<CommitId, BisectMark>,
+    first_bad: &Commit,
+) -> (Vec<CommitId>, Vec<CommitId>) {
+    let mut evaluated = vec![];
+    loop {
+        match Bisector::from_marks(&marks).next_step(repo).unwrap() {
+            BisectionStep::Evaluate { commit_id, .. } => {
+                let mark = if repo.index().is_ancestor(first_bad.id(), &commit_id) {
+                    BisectMark::Bad
+                } else {
+                    BisectMark::Good
+                };
+                assert!(!marks.contains_key(&commit_id));
+                marks.insert(commit_id.clone(), mark);
+                evaluated.push(commit_id);
+            }
+            BisectionStep::Done(first_bad) => return (first_bad, evaluated),
+        }
+    }
+}
+
+#[test]
+fn test_bisect_no_bad_commit() {
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+
+    let marks = btreemap! {
+        repo.store().root_commit_id().clone() => BisectMark::Good,
+    };
+    assert_matches!(
+        Bisector::from_marks(&marks).next_step(repo.as_ref()),
+        Err(BisectError::NoBadCommit)
+    );
+}
+
+#[test]
+fn test_bisect_bad_is_ancestor_of_good() {
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+
+    let mut tx = repo.start_transaction();
+    let mut graph_builder = CommitGraphBuilder::new(tx.repo_mut());
+    let commit_a = graph_builder.initial_commit();
+    let commit_b = graph_builder.commit_with_parents(&[&commit_a]);
+    let commit_c = graph_builder.commit_with_parents(&[&commit_b]);
+    let repo = tx.commit("test").unwrap();
+
+    let marks = btreemap! {
+        commit_a.id().clone() => BisectMark::Bad,
+        commit_c.id().clone() => BisectMark::Good,
+    };
+    assert_matches!(
+        Bisector::from_marks(&marks).next_step(repo.as_ref()),
+        Err(BisectError::InconsistentMarks)
+    );
+}
+
+#[test]
+fn test_bisect_linear() {
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+
+    let mut tx = repo.start_transaction();
+    let mut graph_builder = CommitGraphBuilder::new(tx.repo_mut());
+    let mut commits = vec![graph_builder.initial_commit()];
+    for _ in 0..15 {
+        let parent = commits.last().unwrap().clone();
+        commits.push(graph_builder.commit_with_parents(&[&parent]));
+    }
+    let repo = tx.commit("test").unwrap();
+
+    let marks = btreemap! {
+        commits[0].id().clone() => BisectMark::Good,
+        commits[15].id().clone() => BisectMark::Bad,
+    };
+    // The first step is in the middle of the range
+    assert_eq!(
+        Bisector::from_marks(&marks)
+            .next_step(repo.as_ref())
+            .unwrap(),
+        BisectionStep::Evaluate {
+            commit_id: commits[7].id().clone(),
+            remaining: 14,
+        }
+    );
+
+    for first_bad in &commits[1..] {
+        let (result, evaluated) = bisect_to_end(repo.as_ref(), marks.clone(), first_bad);
+        assert_eq!(result, vec![first_bad.id().clone()]);
+        assert!(evaluated.len() <= 4, "too many steps: {}", evaluated.len());
+    }
+}
+
+#[test]
+fn test_bisect_skipped() {
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+
+    let mut tx = repo.start_transaction();
+    let mut graph_builder = CommitGraphBuilder::new(tx.repo_mut());
+    let commit_a = graph_builder.initial_commit();
+    let commit_b = graph_builder.commit_with_parents(&[&commit_a]);
+    let commit_c = graph_builder.commit_with_parents(&[&commit_b]);
+    let commit_d = graph_builder.commit_with_parents(&[&commit_c]);
+    let repo = tx.commit("test").unwrap();
+
+    // If the commits right before the first bad commit are skipped, the result
+    // is ambiguous.
+    let marks = btreemap! {
+        commit_a.id().clone() => BisectMark::Good,
+        commit_b.id().clone() => BisectMark::Skipped,
+        commit_c.id().clone() => BisectMark::Skipped,
+        commit_d.id().clone() => BisectMark::Bad,
+    };
+    assert_eq!(
+        Bisector::from_marks(&marks)
+            .next_step(repo.as_ref())
+            .unwrap(),
+        BisectionStep::Done(vec![
+            commit_d.id().clone(),
+            commit_c.id().clone(),
+            commit_b.id().clone(),
+        ])
+    );
+}
+
+#[test]
+fn test_bisect_merge() {
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+
+    // D
+    // |\
+    // B C
+    // |/
+    // A
+    let mut tx = repo.start_transaction();
+    let mut graph_builder = CommitGraphBuilder::new(tx.repo_mut());
+    let commit_a = graph_builder.initial_commit();
+    let commit_b = graph_builder.commit_with_parents(&[&commit_a]);
+    let commit_c = graph_builder.commit_with_parents(&[&commit_a]);
+    let commit_d = graph_builder.commit_with_parents(&[&commit_b, &commit_c]);
+    let repo = tx.commit("test").unwrap();
+
+    let marks = btreemap! {
+        commit_a.id().clone() => BisectMark::Good,
+        commit_d.id().clone() => BisectMark::Bad,
+    };
+    for first_bad in [&commit_b, &commit_c, &commit_d] {
+        let (result, _evaluated) = bisect_to_end(repo.as_ref(), marks.clone(), first_bad);
+        assert_eq!(result, vec![first_bad.id().clone()]);
+    }
+}
+
+#[test]
+fn test_bisect_marks_survive_undo() {
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+
+    let mut tx = repo.start_transaction();
+    let mut graph_builder = CommitGraphBuilder::new(tx.repo_mut());
+    let commit_a = graph_builder.initial_commit();
+    let commit_b = graph_builder.commit_with_parents(&[&commit_a]);
+    let repo = tx.commit("test").unwrap();
+
+    let mut tx = repo.start_transaction();
+    tx.repo_mut()
+        .set_bisect_mark(commit_a.id(), Some(BisectMark::Good));
+    let repo_a = tx.commit("mark a").unwrap();
+    let mut tx = repo_a.start_transaction();
+    tx.repo_mut()
+        .set_bisect_mark(commit_b.id(), Some(BisectMark::Bad));
+    let repo_b = tx.commit("mark b").unwrap();
+    assert_eq!(
+        *repo_b.view().bisect_marks(),
+        btreemap! {
+            commit_a.id().clone() => BisectMark::Good,
+            commit_b.id().clone() => BisectMark::Bad,
+        }
+    );
+
+    // Undoing the last operation restores the marks of its parent
+    let mut tx = repo_b.start_transaction();
+    tx.repo_mut().merge(&repo_b, &repo_a).unwrap();
+    let repo = tx.commit("undo").unwrap();
+    assert_eq!(
+        *repo.view().bisect_marks(),
+        btreemap! {
+            commit_a.id().clone() => BisectMark::Good,
+        }
+    );
+}
@@ -0,0 +1,322 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use jj_lib::backend::CommitId;
+use jj_lib::backend::CopyRecord;
+use jj_lib::backend::FileId;
+use jj_lib::commit::Commit;
+use jj_lib::copies::find_similar_copies;
+use jj_lib::copies::CopyDetectionOptions;
+use jj_lib::copies::CopyRecords;
+use jj_lib::matchers::EverythingMatcher;
+use jj_lib::matchers::FilesMatcher;
+use jj_lib::matchers::Matcher;
+use jj_lib::repo::Repo;
+use jj_lib::repo_path::RepoPath;
+use testutils::create_tree;
+use testutils::TestRepo;
+
+fn create_commit(test_repo: &TestRepo, parent: &Commit, files: &[(&RepoPath, &str)]) -> Commit {
+    let repo = &test_repo.repo;
+    let tree = create_tree(repo, files);
+    let mut tx = repo.start_transaction();
+    let commit = tx
+        .repo_mut()
+        .new_commit(vec![parent.id().clone()], tree.id())
+        .write()
+        .unwrap();
+    tx.commit("test").unwrap();
+    commit
+}
+
+fn find_copies(
+    root: &Commit,
+    head: &Commit,
+    matcher: &dyn Matcher,
+    copy_records: &CopyRecords,
+    options: &CopyDetectionOptions,
+) -> Vec<(String, String)> {
+    find_similar_copies(root, head, matcher, copy_records, options)
+        .unwrap()
+        .into_iter()
+        .map(|record| {
+            assert_eq!(&record.source_commit, root.id());
+            assert_eq!(&record.target_commit, head.id());
+            (
+                record.source.as_internal_file_string().to_owned(),
+                record.target.as_internal_file_string().to_owned(),
+            )
+        })
+        .collect()
+}
+
+fn to_pairs(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+    pairs
+        .iter()
+        .map(|&(source, target)| (source.to_owned(), target.to_owned()))
+        .collect()
+}
+
+#[test]
+fn test_find_similar_copies_renames() {
+    let test_repo = TestRepo::init();
+    let root_commit = test_repo.repo.store().root_commit();
+
+    let deleted_path1 = RepoPath::from_internal_string("deleted1");
+    let deleted_path2 = RepoPath::from_internal_string("deleted2");
+    let added_path1 = RepoPath::from_internal_string("added1");
+    let added_path2 = RepoPath::from_internal_string("added2");
+    let added_path3 = RepoPath::from_internal_string("added3");
+    let commit1 = create_commit(
+        &test_repo,
+        &root_commit,
+        &[
+            (deleted_path1, "1\n2\n3\n4\n"),
+            (deleted_path2, "a\nb\nc\nd\ne\nf\n"),
+        ],
+    );
+    let commit2 = create_commit(
+        &test_repo,
+        &commit1,
+        &[
+            // 75% similar to deleted1
+            (added_path1, "1\n2\n3\nx\n"),
+            // 50% similar to deleted2
+            (added_path2, "a\nb\nc\nx\ny\nz\n"),
+            // Not similar to any
+            (added_path3, "foo\n"),
+        ],
+    );
+
+    let copy_records = CopyRecords::default();
+    let options = CopyDetectionOptions::default();
+    assert_eq!(
+        find_copies(
+            &commit1,
+            &commit2,
+            &EverythingMatcher,
+            &copy_records,
+            &options
+        ),
+        to_pairs(&[("deleted1", "added1"), ("deleted2", "added2")])
+    );
+
+    // Higher threshold
+    let options = CopyDetectionOptions {
+        threshold: 60,
+        ..Default::default()
+    };
+    assert_eq!(
+        find_copies(
+            &commit1,
+            &commit2,
+            &EverythingMatcher,
+            &copy_records,
+            &options
+        ),
+        to_pairs(&[("deleted1", "added1")])
+    );
+
+    // Files not matching the matcher are skipped
+    let options = CopyDetectionOptions::default();
+    let matcher = FilesMatcher::new([deleted_path2, added_path2]);
+    assert_eq!(
+        find_copies(&commit1, &commit2, &matcher, &copy_records, &options),
+        to_pairs(&[("deleted2", "added2")])
+    );
+    let matcher = FilesMatcher::new([added_path2]);
+    assert_eq!(
+        find_copies(&commit1, &commit2, &matcher, &copy_records, &options),
+        vec![]
+    );
+
+    // Paths recorded by the backend are excluded
+    let mut copy_records = CopyRecords::default();
+    copy_records
+        .add_records([Ok(CopyRecord {
+            target: added_path3.to_owned(),
+            target_commit: CommitId::new(vec![]),
+            source: deleted_path1.to_owned(),
+            source_file: FileId::new(vec![]),
+            source_commit: CommitId::new(vec![]),
+        })])
+        .unwrap();
+    assert_eq!(
+        find_copies(
+            &commit1,
+            &commit2,
+            &EverythingMatcher,
+            &copy_records,
+            &options
+        ),
+        to_pairs(&[("deleted2", "added2")])
+    );
+
+    // Disabled
+    let options = CopyDetectionOptions {
+        limit: 0,
+        ..Default::default()
+    };
+    assert_eq!(
+        find_copies(
+            &commit1,
+            &commit2,
+            &EverythingMatcher,
+            &copy_records,
+            &options
+        ),
+        vec![]
+    );
+}
+
+#[test]
+fn test_find_similar_copies_large_files() {
+    let test_repo = TestRepo::init();
+    let root_commit = test_repo.repo.store().root_commit();
+
+    let large_text = "line\n".repeat(300_000);
+    let deleted_path1 = RepoPath::from_internal_string("deleted1");
+    let deleted_path2 = RepoPath::from_internal_string("deleted2");
+    let added_path1 = RepoPath::from_internal_string("added1");
+    let added_path2 = RepoPath::from_internal_string("added2");
+    let commit1 = create_commit(
+        &test_repo,
+        &root_commit,
+        &[(deleted_path1, &large_text), (deleted_path2, &large_text)],
+    );
+    let commit2 = create_commit(
+        &test_repo,
+        &commit1,
+        &[
+            // Unchanged
+            (added_path1, &large_text),
+            // Similar, but too large to be compared
+            (added_path2, &format!("{large_text}x\n")),
+        ],
+    );
+
+    let copy_records = CopyRecords::default();
+    let options = CopyDetectionOptions::default();
+    assert_eq!(
+        find_copies(
+            &commit1,
+            &commit2,
+            &EverythingMatcher,
+            &copy_records,
+            &options
+        ),
+        to_pairs(&[("deleted1", "added1")])
+    );
+}
+
+#[test]
+fn test_find_similar_copies_ambiguous() {
+    let test_repo = TestRepo::init();
+    let root_commit = test_repo.repo.store().root_commit();
+
+    let deleted_path1 = RepoPath::from_internal_string("dir1/file");
+    let deleted_path2 = RepoPath::from_internal_string("dir1/other");
+    let added_path1 = RepoPath::from_internal_string("dir2/file");
+    let added_path2 = RepoPath::from_internal_string("dir2/other");
+    let commit1 = create_commit(
+        &test_repo,
+        &root_commit,
+        &[(deleted_path1, "1\n2\n"), (deleted_path2, "1\n2\n")],
+    );
+    let commit2 = create_commit(
+        &test_repo,
+        &commit1,
+        &[(added_path1, "1\n2\n"), (added_path2, "1\n2\n3\n")],
+    );
+
+    // The same file name is preferred, and each deleted file is renamed once
+    let copy_records = CopyRecords::default();
+    let options = CopyDetectionOptions::default();
+    assert_eq!(
+        find_copies(
+            &commit1,
+            &commit2,
+            &EverythingMatcher,
+            &copy_records,
+            &options
+        ),
+        to_pairs(&[("dir1/file", "dir2/file"), ("dir1/other", "dir2/other")])
+    );
+
+    // Only unchanged files are detected if there are too many candidates
+    let options = CopyDetectionOptions {
+        limit: 1,
+        ..Default::default()
+    };
+    assert_eq!(
+        find_copies(
+            &commit1,
+            &commit2,
+            &EverythingMatcher,
+            &copy_records,
+            &options
+        ),
+        to_pairs(&[("dir1/file", "dir2/file")])
+    );
+}
+
+#[test]
+fn test_find_similar_copies_from_modified() {
+    let test_repo = TestRepo::init();
+    let root_commit = test_repo.repo.store().root_commit();
+
+    let modified_path = RepoPath::from_internal_string("modified");
+    let added_path1 = RepoPath::from_internal_string("added1");
+    let added_path2 = RepoPath::from_internal_string("added2");
+    let commit1 = create_commit(&test_repo, &root_commit, &[(modified_path, "1\n2\n3\n")]);
+    let commit2 = create_commit(
+        &test_repo,
+        &commit1,
+        &[
+            (modified_path, "1\n2\n3\n4\n"),
+            (added_path1, "1\n2\n3\n"),
+            (added_path2, "1\n2\nx\n"),
+        ],
+    );
+
+    // Copies aren't detected by default
+    let copy_records = CopyRecords::default();
+    let options = CopyDetectionOptions::default();
+    assert_eq!(
+        find_copies(
+            &commit1,
+            &commit2,
+            &EverythingMatcher,
+            &copy_records,
+            &options
+        ),
+        vec![]
+    );
+
+    // A modified file can be copied to multiple files
+    let options = CopyDetectionOptions {
+        find_copies: true,
+        ..Default::default()
+    };
+    assert_eq!(
+        find_copies(
+            &commit1,
+            &commit2,
+            &EverythingMatcher,
+            &copy_records,
+            &options
+        ),
+        to_pairs(&[("modified", "added1"), ("modified", "added2")])
+    );
+}
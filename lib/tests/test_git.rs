@@ -17,6 +17,7 @@ use std::collections::HashSet;
 use std::fs;
 use std::io::Write;
 use std::iter;
+use std::num::NonZeroU32;
 use std::path::Path;
 use std::path::PathBuf;
 use std::sync::mpsc;
@@ -53,6 +54,7 @@ use jj_lib::op_store::BookmarkTarget;
 use jj_lib::op_store::RefTarget;
 use jj_lib::op_store::RemoteRef;
 use jj_lib::op_store::RemoteRefState;
+use jj_lib::op_store::TagAnnotation;
 use jj_lib::refs::BookmarkPushUpdate;
 use jj_lib::repo::MutableRepo;
 use jj_lib::repo::ReadonlyRepo;
@@ -124,9 +126,17 @@ fn get_git_repo(repo: &Arc<ReadonlyRepo>) -> git2::Repository {
     get_git_backend(repo).open_git_repo().unwrap()
 }
 
-fn get_git_settings(subprocess: bool) -> GitSettings {
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum RemoteTransport {
+    Git2,
+    Subprocess,
+    Gix,
+}
+
+fn get_git_settings(transport: RemoteTransport) -> GitSettings {
     GitSettings {
-        subprocess,
+        subprocess: transport == RemoteTransport::Subprocess,
+        gix_transport: transport == RemoteTransport::Gix,
         ..Default::default()
     }
 }
@@ -1865,6 +1875,73 @@ fn test_export_bookmark_on_root_commit() {
     assert_matches!(failed[0].reason, FailedRefExportReason::OnRootCommit);
 }
 
+#[test]
+fn test_export_tags() {
+    let test_data = GitRepoData::create();
+    let git_settings = GitSettings::default();
+    let git_repo = test_data.git_repo;
+    let commit1 = empty_git_commit(&git_repo, "refs/heads/main", &[]);
+    let commit2 = empty_git_commit(&git_repo, "refs/heads/main", &[&commit1]);
+    let mut tx = test_data.repo.start_transaction();
+    let mut_repo = tx.repo_mut();
+    git::import_refs(mut_repo, &git_settings).unwrap();
+
+    // Lightweight and annotated tags are created
+    let annotation = TagAnnotation {
+        message: "Release 1\n".to_string(),
+        tagger: Signature {
+            name: "Tagger".to_string(),
+            email: "tagger@example.com".to_string(),
+            timestamp: Timestamp {
+                timestamp: MillisSinceEpoch(1_000_000),
+                tz_offset: 60,
+            },
+        },
+        signature: None,
+    };
+    mut_repo.set_tag_target("light", RefTarget::normal(jj_id(&commit1)));
+    mut_repo.set_tag_target("v1", RefTarget::normal(jj_id(&commit1)));
+    mut_repo.set_tag_annotation("v1", Some(annotation));
+    assert!(git::export_refs(mut_repo).unwrap().is_empty());
+    assert_eq!(
+        mut_repo.get_git_ref("refs/tags/v1"),
+        RefTarget::normal(jj_id(&commit1))
+    );
+    let light_ref = git_repo.find_reference("refs/tags/light").unwrap();
+    assert_eq!(light_ref.target(), Some(commit1.id()));
+    let git_tag = git_repo
+        .find_reference("refs/tags/v1")
+        .unwrap()
+        .peel_to_tag()
+        .unwrap();
+    assert_eq!(git_tag.target_id(), commit1.id());
+    assert_eq!(git_tag.name(), Some("v1"));
+    assert_eq!(git_tag.message(), Some("Release 1\n"));
+    assert_eq!(git_tag.tagger().unwrap().name(), Some("Tagger"));
+
+    // Annotated tags are compared by the commit they point to
+    mut_repo.set_tag_target("v1", RefTarget::normal(jj_id(&commit2)));
+    mut_repo.set_tag_target("light", RefTarget::absent());
+    assert!(git::export_refs(mut_repo).unwrap().is_empty());
+    assert!(git_repo.find_reference("refs/tags/light").is_err());
+    assert_eq!(mut_repo.get_git_ref("refs/tags/light"), RefTarget::absent());
+    let v1_ref = git_repo.find_reference("refs/tags/v1").unwrap();
+    assert_eq!(v1_ref.peel_to_commit().unwrap().id(), commit2.id());
+    mut_repo.set_tag_target("v1", RefTarget::absent());
+    assert!(git::export_refs(mut_repo).unwrap().is_empty());
+    assert!(git_repo.find_reference("refs/tags/v1").is_err());
+
+    // Tags on the root commit can't be exported
+    mut_repo.set_tag_target(
+        "on_root",
+        RefTarget::normal(mut_repo.store().root_commit_id().clone()),
+    );
+    let failed = git::export_refs(mut_repo).unwrap();
+    assert_eq!(failed.len(), 1);
+    assert_eq!(failed[0].name, RefName::Tag("on_root".to_string()));
+    assert_matches!(failed[0].reason, FailedRefExportReason::OnRootCommit);
+}
+
 #[test]
 fn test_export_partial_failure() {
     // Check that we skip bookmarks that fail to export
@@ -2569,11 +2646,12 @@ fn test_init() {
     assert!(!repo.view().heads().contains(&jj_id(&initial_git_commit)));
 }
 
-#[test_case(false; "use git2 for remote calls")]
-#[test_case(true; "spawn a git subprocess for remote calls")]
-fn test_fetch_empty_repo(subprocess: bool) {
+#[test_case(RemoteTransport::Git2; "use git2 for remote calls")]
+#[test_case(RemoteTransport::Subprocess; "spawn a git subprocess for remote calls")]
+#[test_case(RemoteTransport::Gix; "use gix for remote calls")]
+fn test_fetch_empty_repo(transport: RemoteTransport) {
     let test_data = GitRepoData::create();
-    let git_settings = get_git_settings(subprocess);
+    let git_settings = get_git_settings(transport);
 
     let mut tx = test_data.repo.start_transaction();
     let stats = git_fetch(
@@ -2590,13 +2668,14 @@ fn test_fetch_empty_repo(subprocess: bool) {
     assert_eq!(tx.repo().view().bookmarks().count(), 0);
 }
 
-#[test_case(false; "use git2 for remote calls")]
-#[test_case(true; "spawn a git subprocess for remote calls")]
-fn test_fetch_initial_commit_head_is_not_set(subprocess: bool) {
+#[test_case(RemoteTransport::Git2; "use git2 for remote calls")]
+#[test_case(RemoteTransport::Subprocess; "spawn a git subprocess for remote calls")]
+#[test_case(RemoteTransport::Gix; "use gix for remote calls")]
+fn test_fetch_initial_commit_head_is_not_set(transport: RemoteTransport) {
     let test_data = GitRepoData::create();
     let git_settings = GitSettings {
         auto_local_bookmark: true,
-        ..get_git_settings(subprocess)
+        ..get_git_settings(transport)
     };
     let initial_git_commit = empty_git_commit(&test_data.origin_repo, "refs/heads/main", &[]);
 
@@ -2639,13 +2718,14 @@ fn test_fetch_initial_commit_head_is_not_set(subprocess: bool) {
     );
 }
 
-#[test_case(false; "use git2 for remote calls")]
-#[test_case(true; "spawn a git subprocess for remote calls")]
-fn test_fetch_initial_commit_head_is_set(subprocess: bool) {
+#[test_case(RemoteTransport::Git2; "use git2 for remote calls")]
+#[test_case(RemoteTransport::Subprocess; "spawn a git subprocess for remote calls")]
+#[test_case(RemoteTransport::Gix; "use gix for remote calls")]
+fn test_fetch_initial_commit_head_is_set(transport: RemoteTransport) {
     let test_data = GitRepoData::create();
     let git_settings = GitSettings {
         auto_local_bookmark: true,
-        ..get_git_settings(subprocess)
+        ..get_git_settings(transport)
     };
     let initial_git_commit = empty_git_commit(&test_data.origin_repo, "refs/heads/main", &[]);
     test_data.origin_repo.set_head("refs/heads/main").unwrap();
@@ -2672,13 +2752,14 @@ fn test_fetch_initial_commit_head_is_set(subprocess: bool) {
     assert!(stats.import_stats.abandoned_commits.is_empty());
 }
 
-#[test_case(false; "use git2 for remote calls")]
-#[test_case(true; "spawn a git subprocess for remote calls")]
-fn test_fetch_success(subprocess: bool) {
+#[test_case(RemoteTransport::Git2; "use git2 for remote calls")]
+#[test_case(RemoteTransport::Subprocess; "spawn a git subprocess for remote calls")]
+#[test_case(RemoteTransport::Gix; "use gix for remote calls")]
+fn test_fetch_success(transport: RemoteTransport) {
     let mut test_data = GitRepoData::create();
     let git_settings = GitSettings {
         auto_local_bookmark: true,
-        ..get_git_settings(subprocess)
+        ..get_git_settings(transport)
     };
     let initial_git_commit = empty_git_commit(&test_data.origin_repo, "refs/heads/main", &[]);
 
@@ -2749,13 +2830,14 @@ fn test_fetch_success(subprocess: bool) {
     );
 }
 
-#[test_case(false; "use git2 for remote calls")]
-#[test_case(true; "spawn a git subprocess for remote calls")]
-fn test_fetch_prune_deleted_ref(subprocess: bool) {
+#[test_case(RemoteTransport::Git2; "use git2 for remote calls")]
+#[test_case(RemoteTransport::Subprocess; "spawn a git subprocess for remote calls")]
+#[test_case(RemoteTransport::Gix; "use gix for remote calls")]
+fn test_fetch_prune_deleted_ref(transport: RemoteTransport) {
     let test_data = GitRepoData::create();
     let git_settings = GitSettings {
         auto_local_bookmark: true,
-        ..get_git_settings(subprocess)
+        ..get_git_settings(transport)
     };
     let commit = empty_git_commit(&test_data.origin_repo, "refs/heads/main", &[]);
 
@@ -2793,13 +2875,14 @@ fn test_fetch_prune_deleted_ref(subprocess: bool) {
         .is_absent());
 }
 
-#[test_case(false; "use git2 for remote calls")]
-#[test_case(true; "spawn a git subprocess for remote calls")]
-fn test_fetch_no_default_branch(subprocess: bool) {
+#[test_case(RemoteTransport::Git2; "use git2 for remote calls")]
+#[test_case(RemoteTransport::Subprocess; "spawn a git subprocess for remote calls")]
+#[test_case(RemoteTransport::Gix; "use gix for remote calls")]
+fn test_fetch_no_default_branch(transport: RemoteTransport) {
     let test_data = GitRepoData::create();
     let git_settings = GitSettings {
         auto_local_bookmark: true,
-        ..get_git_settings(subprocess)
+        ..get_git_settings(transport)
     };
     let initial_git_commit = empty_git_commit(&test_data.origin_repo, "refs/heads/main", &[]);
 
@@ -2836,11 +2919,12 @@ fn test_fetch_no_default_branch(subprocess: bool) {
     assert_eq!(stats.default_branch, None);
 }
 
-#[test_case(false; "use git2 for remote calls")]
-#[test_case(true; "spawn a git subprocess for remote calls")]
-fn test_fetch_empty_refspecs(subprocess: bool) {
+#[test_case(RemoteTransport::Git2; "use git2 for remote calls")]
+#[test_case(RemoteTransport::Subprocess; "spawn a git subprocess for remote calls")]
+#[test_case(RemoteTransport::Gix; "use gix for remote calls")]
+fn test_fetch_empty_refspecs(transport: RemoteTransport) {
     let test_data = GitRepoData::create();
-    let git_settings = get_git_settings(subprocess);
+    let git_settings = get_git_settings(transport);
     empty_git_commit(&test_data.origin_repo, "refs/heads/main", &[]);
 
     // Base refspecs shouldn't be respected
@@ -2858,11 +2942,12 @@ fn test_fetch_empty_refspecs(subprocess: bool) {
         .is_absent());
 }
 
-#[test_case(false; "use git2 for remote calls")]
-#[test_case(true; "spawn a git subprocess for remote calls")]
-fn test_fetch_no_such_remote(subprocess: bool) {
+#[test_case(RemoteTransport::Git2; "use git2 for remote calls")]
+#[test_case(RemoteTransport::Subprocess; "spawn a git subprocess for remote calls")]
+#[test_case(RemoteTransport::Gix; "use gix for remote calls")]
+fn test_fetch_no_such_remote(transport: RemoteTransport) {
     let test_data = GitRepoData::create();
-    let git_settings = get_git_settings(subprocess);
+    let git_settings = get_git_settings(transport);
     let mut tx = test_data.repo.start_transaction();
     let result = git_fetch(
         tx.repo_mut(),
@@ -2873,6 +2958,122 @@ fn test_fetch_no_such_remote(subprocess: bool) {
     assert!(matches!(result, Err(GitFetchError::NoSuchRemote(_))));
 }
 
+/// Writes a fake `ssh` command which runs the remote command locally, and
+/// returns settings to use it with the in-process transport.
+#[cfg(unix)]
+fn fake_ssh_git_settings(temp_dir: &Path) -> GitSettings {
+    use std::os::unix::fs::PermissionsExt as _;
+
+    let ssh_path = temp_dir.join("fake-ssh");
+    // Fail the `-G` feature check, then drop the host name.
+    fs::write(
+        &ssh_path,
+        "#!/bin/sh\n[ \"$1\" = -G ] && exit 1\nshift\nexec sh -c \"$*\"\n",
+    )
+    .unwrap();
+    fs::set_permissions(&ssh_path, fs::Permissions::from_mode(0o755)).unwrap();
+    GitSettings {
+        gix_transport: true,
+        ssh_command: Some(ssh_path.to_str().unwrap().to_owned()),
+        ..Default::default()
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn test_fetch_over_ssh_with_gix() {
+    let mut test_data = GitRepoData::create();
+    let git_settings = GitSettings {
+        auto_local_bookmark: true,
+        ..fake_ssh_git_settings(test_data._temp_dir.path())
+    };
+    let origin_path = test_data.origin_repo.path().to_str().unwrap().to_owned();
+    test_data
+        .git_repo
+        .remote_set_url("origin", &format!("ssh://localhost{origin_path}"))
+        .unwrap();
+    let initial_git_commit = empty_git_commit(&test_data.origin_repo, "refs/heads/main", &[]);
+    let new_git_commit = empty_git_commit(
+        &test_data.origin_repo,
+        "refs/heads/feature",
+        &[&initial_git_commit],
+    );
+    test_data.origin_repo.set_head("refs/heads/main").unwrap();
+
+    let mut tx = test_data.repo.start_transaction();
+    let stats = git_fetch(
+        tx.repo_mut(),
+        "origin",
+        &[StringPattern::everything()],
+        &git_settings,
+    )
+    .unwrap();
+    assert_eq!(stats.default_branch, Some("main".to_string()));
+    test_data.repo = tx.commit("test").unwrap();
+    let view = test_data.repo.view();
+    assert!(view.heads().contains(&jj_id(&new_git_commit)));
+    assert_eq!(
+        *view.git_refs(),
+        btreemap! {
+            "refs/remotes/origin/feature".to_string() => RefTarget::normal(jj_id(&new_git_commit)),
+            "refs/remotes/origin/main".to_string() => RefTarget::normal(jj_id(&initial_git_commit)),
+        }
+    );
+
+    // Branches deleted on the remote are pruned
+    test_data
+        .origin_repo
+        .find_reference("refs/heads/feature")
+        .unwrap()
+        .delete()
+        .unwrap();
+    // Fetch with a progress callback, which runs the transfer in a thread
+    let mut tx = test_data.repo.start_transaction();
+    let mut git_fetch = GitFetch::new(tx.repo_mut(), &git_settings).unwrap();
+    let mut progress_cb = |_: &git::Progress| {};
+    let mut callbacks = git::RemoteCallbacks::default();
+    callbacks.progress = Some(&mut progress_cb);
+    git_fetch
+        .fetch("origin", &[StringPattern::everything()], callbacks, None)
+        .unwrap();
+    git_fetch.import_refs().unwrap();
+    assert_eq!(
+        *tx.repo().view().git_refs(),
+        btreemap! {
+            "refs/remotes/origin/main".to_string() => RefTarget::normal(jj_id(&initial_git_commit)),
+        }
+    );
+}
+
+#[test]
+fn test_fetch_with_depth_with_gix() {
+    let test_data = GitRepoData::create();
+    let git_settings = get_git_settings(RemoteTransport::Gix);
+    let commit1 = empty_git_commit(&test_data.origin_repo, "refs/heads/main", &[]);
+    let commit2 = empty_git_commit(&test_data.origin_repo, "refs/heads/main", &[&commit1]);
+    let commit3 = empty_git_commit(&test_data.origin_repo, "refs/heads/main", &[&commit2]);
+
+    let mut tx = test_data.repo.start_transaction();
+    let mut git_fetch = GitFetch::new(tx.repo_mut(), &git_settings).unwrap();
+    git_fetch
+        .fetch(
+            "origin",
+            &[StringPattern::everything()],
+            git::RemoteCallbacks::default(),
+            NonZeroU32::new(2),
+        )
+        .unwrap();
+    git_fetch.import_refs().unwrap();
+    let repo = tx.commit("test").unwrap();
+
+    assert!(test_data.git_repo.is_shallow());
+    assert!(test_data.git_repo.find_commit(commit2.id()).is_ok());
+    assert!(test_data.git_repo.find_commit(commit1.id()).is_err());
+    let commit = repo.store().get_commit(&jj_id(&commit2)).unwrap();
+    assert_eq!(commit.parent_ids(), [repo.store().root_commit_id().clone()]);
+    assert!(repo.view().heads().contains(&jj_id(&commit3)));
+}
+
 #[test]
 fn test_fetch_multiple_branches() {
     let test_data = GitRepoData::create();
@@ -3001,15 +3202,16 @@ fn set_up_push_repos(settings: &UserSettings, temp_dir: &TempDir) -> PushTestSet
     }
 }
 
-#[test_case(false; "use git2 for remote calls")]
-#[test_case(true; "spawn a git subprocess for remote calls")]
-fn test_push_bookmarks_success(subprocess: bool) {
+#[test_case(RemoteTransport::Git2; "use git2 for remote calls")]
+#[test_case(RemoteTransport::Subprocess; "spawn a git subprocess for remote calls")]
+#[test_case(RemoteTransport::Gix; "use gix for remote calls")]
+fn test_push_bookmarks_success(transport: RemoteTransport) {
     let settings = testutils::user_settings();
     let temp_dir = testutils::new_temp_dir();
     let mut setup = set_up_push_repos(&settings, &temp_dir);
     let clone_repo = get_git_repo(&setup.jj_repo);
     let mut tx = setup.jj_repo.start_transaction();
-    let git_settings = get_git_settings(subprocess);
+    let git_settings = get_git_settings(transport);
 
     let targets = GitBranchPushTargets {
         branch_updates: vec![(
@@ -3068,15 +3270,16 @@ fn test_push_bookmarks_success(subprocess: bool) {
     assert!(!tx.repo().has_changes());
 }
 
-#[test_case(false; "use git2 for remote calls")]
-#[test_case(true; "spawn a git subprocess for remote calls")]
-fn test_push_bookmarks_deletion(subprocess: bool) {
+#[test_case(RemoteTransport::Git2; "use git2 for remote calls")]
+#[test_case(RemoteTransport::Subprocess; "spawn a git subprocess for remote calls")]
+#[test_case(RemoteTransport::Gix; "use gix for remote calls")]
+fn test_push_bookmarks_deletion(transport: RemoteTransport) {
     let settings = testutils::user_settings();
     let temp_dir = testutils::new_temp_dir();
     let mut setup = set_up_push_repos(&settings, &temp_dir);
     let clone_repo = get_git_repo(&setup.jj_repo);
     let mut tx = setup.jj_repo.start_transaction();
-    let git_settings = get_git_settings(subprocess);
+    let git_settings = get_git_settings(transport);
 
     let source_repo = git2::Repository::open(&setup.source_repo_dir).unwrap();
     // Test the setup
@@ -3122,14 +3325,15 @@ fn test_push_bookmarks_deletion(subprocess: bool) {
     assert!(!tx.repo().has_changes());
 }
 
-#[test_case(false; "use git2 for remote calls")]
-#[test_case(true; "spawn a git subprocess for remote calls")]
-fn test_push_bookmarks_mixed_deletion_and_addition(subprocess: bool) {
+#[test_case(RemoteTransport::Git2; "use git2 for remote calls")]
+#[test_case(RemoteTransport::Subprocess; "spawn a git subprocess for remote calls")]
+#[test_case(RemoteTransport::Gix; "use gix for remote calls")]
+fn test_push_bookmarks_mixed_deletion_and_addition(transport: RemoteTransport) {
     let settings = testutils::user_settings();
     let temp_dir = testutils::new_temp_dir();
     let mut setup = set_up_push_repos(&settings, &temp_dir);
     let mut tx = setup.jj_repo.start_transaction();
-    let git_settings = get_git_settings(subprocess);
+    let git_settings = get_git_settings(transport);
 
     let targets = GitBranchPushTargets {
         branch_updates: vec![
@@ -3192,14 +3396,15 @@ fn test_push_bookmarks_mixed_deletion_and_addition(subprocess: bool) {
     assert!(!tx.repo().has_changes());
 }
 
-#[test_case(false; "use git2 for remote calls")]
-#[test_case(true; "spawn a git subprocess for remote calls")]
-fn test_push_bookmarks_not_fast_forward(subprocess: bool) {
+#[test_case(RemoteTransport::Git2; "use git2 for remote calls")]
+#[test_case(RemoteTransport::Subprocess; "spawn a git subprocess for remote calls")]
+#[test_case(RemoteTransport::Gix; "use gix for remote calls")]
+fn test_push_bookmarks_not_fast_forward(transport: RemoteTransport) {
     let settings = testutils::user_settings();
     let temp_dir = testutils::new_temp_dir();
     let setup = set_up_push_repos(&settings, &temp_dir);
     let mut tx = setup.jj_repo.start_transaction();
-    let git_settings = get_git_settings(subprocess);
+    let git_settings = get_git_settings(transport);
 
     let targets = GitBranchPushTargets {
         branch_updates: vec![(
@@ -3232,13 +3437,14 @@ fn test_push_bookmarks_not_fast_forward(subprocess: bool) {
 // may want to add tests for when a bookmark unexpectedly moved backwards or
 // unexpectedly does not exist for bookmark deletion.
 
-#[test_case(false; "use git2 for remote calls")]
-#[test_case(true; "spawn a git subprocess for remote calls")]
-fn test_push_updates_unexpectedly_moved_sideways_on_remote(subprocess: bool) {
+#[test_case(RemoteTransport::Git2; "use git2 for remote calls")]
+#[test_case(RemoteTransport::Subprocess; "spawn a git subprocess for remote calls")]
+#[test_case(RemoteTransport::Gix; "use gix for remote calls")]
+fn test_push_updates_unexpectedly_moved_sideways_on_remote(transport: RemoteTransport) {
     let settings = testutils::user_settings();
     let temp_dir = testutils::new_temp_dir();
     let setup = set_up_push_repos(&settings, &temp_dir);
-    let git_settings = get_git_settings(subprocess);
+    let git_settings = get_git_settings(transport);
 
     // The main bookmark is actually at `main_commit` on the remote. If we expect
     // it to be at `sideways_commit`, it unexpectedly moved sideways from our
@@ -3299,13 +3505,14 @@ fn test_push_updates_unexpectedly_moved_sideways_on_remote(subprocess: bool) {
     );
 }
 
-#[test_case(false; "use git2 for remote calls")]
-#[test_case(true; "spawn a git subprocess for remote calls")]
-fn test_push_updates_unexpectedly_moved_forward_on_remote(subprocess: bool) {
+#[test_case(RemoteTransport::Git2; "use git2 for remote calls")]
+#[test_case(RemoteTransport::Subprocess; "spawn a git subprocess for remote calls")]
+#[test_case(RemoteTransport::Gix; "use gix for remote calls")]
+fn test_push_updates_unexpectedly_moved_forward_on_remote(transport: RemoteTransport) {
     let settings = testutils::user_settings();
     let temp_dir = testutils::new_temp_dir();
     let setup = set_up_push_repos(&settings, &temp_dir);
-    let git_settings = get_git_settings(subprocess);
+    let git_settings = get_git_settings(transport);
 
     // The main bookmark is actually at `main_commit` on the remote. If we
     // expected it to be at `parent_of_commit`, it unexpectedly moved forward
@@ -3355,7 +3562,7 @@ fn test_push_updates_unexpectedly_moved_forward_on_remote(subprocess: bool) {
         Err(GitPushError::RefInUnexpectedLocation(_))
     );
 
-    if subprocess {
+    if transport == RemoteTransport::Subprocess {
         // git is strict about honouring the expected location on --force-with-lease
         assert_matches!(
             attempt_push_expecting_parent(Some(setup.child_of_main_commit.id().clone())),
@@ -3371,13 +3578,14 @@ fn test_push_updates_unexpectedly_moved_forward_on_remote(subprocess: bool) {
     }
 }
 
-#[test_case(false; "use git2 for remote calls")]
-#[test_case(true; "spawn a git subprocess for remote calls")]
-fn test_push_updates_unexpectedly_exists_on_remote(subprocess: bool) {
+#[test_case(RemoteTransport::Git2; "use git2 for remote calls")]
+#[test_case(RemoteTransport::Subprocess; "spawn a git subprocess for remote calls")]
+#[test_case(RemoteTransport::Gix; "use gix for remote calls")]
+fn test_push_updates_unexpectedly_exists_on_remote(transport: RemoteTransport) {
     let settings = testutils::user_settings();
     let temp_dir = testutils::new_temp_dir();
     let setup = set_up_push_repos(&settings, &temp_dir);
-    let git_settings = get_git_settings(subprocess);
+    let git_settings = get_git_settings(transport);
 
     // The main bookmark is actually at `main_commit` on the remote. In this test,
     // we expect it to not exist on the remote at all.
@@ -3408,7 +3616,7 @@ fn test_push_updates_unexpectedly_exists_on_remote(subprocess: bool) {
         Err(GitPushError::RefInUnexpectedLocation(_))
     );
 
-    if subprocess {
+    if transport == RemoteTransport::Subprocess {
         // Git is strict with enforcing the expected location
         assert_matches!(
             attempt_push_expecting_absence(Some(setup.child_of_main_commit.id().clone())),
@@ -3424,13 +3632,14 @@ fn test_push_updates_unexpectedly_exists_on_remote(subprocess: bool) {
     }
 }
 
-#[test_case(false; "use git2 for remote calls")]
-#[test_case(true; "spawn a git subprocess for remote calls")]
-fn test_push_updates_success(subprocess: bool) {
+#[test_case(RemoteTransport::Git2; "use git2 for remote calls")]
+#[test_case(RemoteTransport::Subprocess; "spawn a git subprocess for remote calls")]
+#[test_case(RemoteTransport::Gix; "use gix for remote calls")]
+fn test_push_updates_success(transport: RemoteTransport) {
     let settings = testutils::user_settings();
     let temp_dir = testutils::new_temp_dir();
     let setup = set_up_push_repos(&settings, &temp_dir);
-    let git_settings = get_git_settings(subprocess);
+    let git_settings = get_git_settings(transport);
     let clone_repo = get_git_repo(&setup.jj_repo);
     let result = git::push_updates(
         setup.jj_repo.as_ref(),
@@ -3464,13 +3673,14 @@ fn test_push_updates_success(subprocess: bool) {
     assert_eq!(new_target, Some(new_oid));
 }
 
-#[test_case(false; "use git2 for remote calls")]
-#[test_case(true; "spawn a git subprocess for remote calls")]
-fn test_push_updates_no_such_remote(subprocess: bool) {
+#[test_case(RemoteTransport::Git2; "use git2 for remote calls")]
+#[test_case(RemoteTransport::Subprocess; "spawn a git subprocess for remote calls")]
+#[test_case(RemoteTransport::Gix; "use gix for remote calls")]
+fn test_push_updates_no_such_remote(transport: RemoteTransport) {
     let settings = testutils::user_settings();
     let temp_dir = testutils::new_temp_dir();
     let setup = set_up_push_repos(&settings, &temp_dir);
-    let git_settings = get_git_settings(subprocess);
+    let git_settings = get_git_settings(transport);
     let result = git::push_updates(
         setup.jj_repo.as_ref(),
         &git_settings,
@@ -3485,13 +3695,14 @@ fn test_push_updates_no_such_remote(subprocess: bool) {
     assert!(matches!(result, Err(GitPushError::NoSuchRemote(_))));
 }
 
-#[test_case(false; "use git2 for remote calls")]
-#[test_case(true; "spawn a git subprocess for remote calls")]
-fn test_push_updates_invalid_remote(subprocess: bool) {
+#[test_case(RemoteTransport::Git2; "use git2 for remote calls")]
+#[test_case(RemoteTransport::Subprocess; "spawn a git subprocess for remote calls")]
+#[test_case(RemoteTransport::Gix; "use gix for remote calls")]
+fn test_push_updates_invalid_remote(transport: RemoteTransport) {
     let settings = testutils::user_settings();
     let temp_dir = testutils::new_temp_dir();
     let setup = set_up_push_repos(&settings, &temp_dir);
-    let git_settings = get_git_settings(subprocess);
+    let git_settings = get_git_settings(transport);
     let result = git::push_updates(
         setup.jj_repo.as_ref(),
         &git_settings,
@@ -3506,6 +3717,103 @@ fn test_push_updates_invalid_remote(subprocess: bool) {
     assert!(matches!(result, Err(GitPushError::NoSuchRemote(_))));
 }
 
+#[cfg(unix)]
+#[test]
+fn test_push_over_ssh_with_gix() {
+    let settings = testutils::user_settings();
+    let temp_dir = testutils::new_temp_dir();
+    let setup = set_up_push_repos(&settings, &temp_dir);
+    let git_settings = fake_ssh_git_settings(temp_dir.path());
+    let clone_repo = get_git_repo(&setup.jj_repo);
+    let source_path = setup.source_repo_dir.canonicalize().unwrap();
+    clone_repo
+        .remote_set_url(
+            "origin",
+            &format!("ssh://localhost{}", source_path.to_str().unwrap()),
+        )
+        .unwrap();
+
+    let result = git::push_updates(
+        setup.jj_repo.as_ref(),
+        &git_settings,
+        "origin",
+        &[
+            GitRefUpdate {
+                qualified_name: "refs/heads/main".to_string(),
+                expected_current_target: Some(setup.main_commit.id().clone()),
+                new_target: Some(setup.child_of_main_commit.id().clone()),
+            },
+            GitRefUpdate {
+                qualified_name: "refs/heads/new".to_string(),
+                expected_current_target: None,
+                new_target: Some(setup.sideways_commit.id().clone()),
+            },
+        ],
+        git::RemoteCallbacks::default(),
+    );
+    assert_matches!(result, Ok(()));
+
+    // The refs and their objects arrived in the source repo
+    let source_repo = git2::Repository::open(&setup.source_repo_dir).unwrap();
+    let get_target = |repo: &git2::Repository, name: &str| {
+        repo.find_reference(name).ok().and_then(|r| r.target())
+    };
+    let child_oid = git_id(&setup.child_of_main_commit);
+    let sideways_oid = git_id(&setup.sideways_commit);
+    assert_eq!(get_target(&source_repo, "refs/heads/main"), Some(child_oid));
+    assert_eq!(
+        get_target(&source_repo, "refs/heads/new"),
+        Some(sideways_oid)
+    );
+    let sideways_tree = source_repo
+        .find_commit(sideways_oid)
+        .unwrap()
+        .tree()
+        .unwrap();
+    assert!(!sideways_tree.is_empty());
+    for entry in &sideways_tree {
+        assert!(source_repo.find_object(entry.id(), None).is_ok());
+    }
+    // The remote-tracking refs were updated
+    assert_eq!(
+        get_target(&clone_repo, "refs/remotes/origin/main"),
+        Some(child_oid)
+    );
+    assert_eq!(
+        get_target(&clone_repo, "refs/remotes/origin/new"),
+        Some(sideways_oid)
+    );
+
+    // A push with an unexpected remote location is refused
+    let result = git::push_updates(
+        setup.jj_repo.as_ref(),
+        &git_settings,
+        "origin",
+        &[GitRefUpdate {
+            qualified_name: "refs/heads/new".to_string(),
+            expected_current_target: Some(setup.main_commit.id().clone()),
+            new_target: None,
+        }],
+        git::RemoteCallbacks::default(),
+    );
+    assert_matches!(result, Err(GitPushError::RefInUnexpectedLocation(_)));
+
+    let result = git::push_updates(
+        setup.jj_repo.as_ref(),
+        &git_settings,
+        "origin",
+        &[GitRefUpdate {
+            qualified_name: "refs/heads/new".to_string(),
+            expected_current_target: Some(setup.sideways_commit.id().clone()),
+            new_target: None,
+        }],
+        git::RemoteCallbacks::default(),
+    );
+    assert_matches!(result, Ok(()));
+    assert_eq!(get_target(&source_repo, "refs/heads/new"), None);
+    assert_eq!(get_target(&clone_repo, "refs/remotes/origin/new"), None);
+}
+
 #[test]
 fn test_bulk_update_extra_on_import_refs() {
     let git_settings = GitSettings::default();
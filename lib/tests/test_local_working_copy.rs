@@ -12,6 +12,8 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::io::Read as _;
+use std::io::Write;
 #[cfg(unix)]
 use std::os::unix::fs::PermissionsExt;
 #[cfg(unix)]
@@ -23,14 +25,20 @@ use std::sync::Arc;
 use assert_matches::assert_matches;
 use indoc::indoc;
 use itertools::Itertools;
+use jj_lib::backend::CommitId;
 use jj_lib::backend::MergedTreeId;
 use jj_lib::backend::TreeId;
 use jj_lib::backend::TreeValue;
 use jj_lib::file_util::check_symlink_support;
 use jj_lib::file_util::try_symlink;
 use jj_lib::fsmonitor::FsmonitorSettings;
+use jj_lib::git;
+use jj_lib::git_lfs::GitLfsStore;
+use jj_lib::git_lfs::LfsFetcher;
+use jj_lib::git_lfs::LfsPointer;
 use jj_lib::gitignore::GitIgnoreFile;
 use jj_lib::local_working_copy::LocalWorkingCopy;
+use jj_lib::local_working_copy::LocalWorkingCopyFactory;
 use jj_lib::merge::Merge;
 use jj_lib::merge::MergedTreeValue;
 use jj_lib::merged_tree::MergedTree;
@@ -50,6 +58,7 @@ use jj_lib::working_copy::SnapshotOptions;
 use jj_lib::working_copy::UntrackedReason;
 use jj_lib::workspace::default_working_copy_factories;
 use jj_lib::workspace::LockedWorkspace;
+use jj_lib::workspace::WorkingCopyFactories;
 use jj_lib::workspace::Workspace;
 use pollster::FutureExt;
 use test_case::test_case;
@@ -363,8 +372,10 @@ fn test_checkout_file_transitions(backend: TestRepoBackend) {
                 assert!(metadata.is_dir(), "{path:?} should be a directory");
             }
             Kind::GitSubmodule => {
-                // Not supported for now
-                assert!(maybe_metadata.is_err(), "{path:?} should not exist");
+                // Only the directory is created for uninitialized submodule
+                assert!(maybe_metadata.is_ok(), "{path:?} should exist");
+                let metadata = maybe_metadata.unwrap();
+                assert!(metadata.is_dir(), "{path:?} should be a directory");
             }
         };
     }
@@ -602,7 +613,8 @@ fn test_conflicting_changes_on_disk() {
             updated_files: 0,
             added_files: 3,
             removed_files: 0,
-            skipped_files: 3
+            skipped_files: 3,
+            lfs_pointer_files: 0,
         }
     );
 
@@ -830,7 +842,8 @@ fn test_materialize_snapshot_conflicted_files() {
             updated_files: 0,
             added_files: 2,
             removed_files: 0,
-            skipped_files: 0
+            skipped_files: 0,
+            lfs_pointer_files: 0,
         }
     );
 
@@ -1247,6 +1260,306 @@ fn test_gitignores_ignored_directory_already_tracked() {
     assert_eq!(tree_entries(&new_tree), tree_entries(&expected_tree));
 }
 
+#[test]
+fn test_gitattributes_eol_conversion() {
+    // Tests that line endings are converted according to .gitattributes
+
+    let mut test_workspace = TestWorkspace::init();
+    let repo = &test_workspace.repo;
+    let workspace_root = test_workspace.workspace.workspace_root().to_owned();
+
+    let gitattributes_path = RepoPath::from_internal_string(".gitattributes");
+    let crlf_path = RepoPath::from_internal_string("dir/crlf.txt");
+    let plain_path = RepoPath::from_internal_string("dir/plain.dat");
+    let tree = create_tree(
+        repo,
+        &[
+            (gitattributes_path, "*.txt eol=crlf\n"),
+            (crlf_path, "a\nb\n"),
+            (plain_path, "a\nb\n"),
+        ],
+    );
+    let commit = commit_with_tree(repo.store(), tree.id());
+    let ws = &mut test_workspace.workspace;
+    ws.check_out(
+        repo.op_id().clone(),
+        None,
+        &commit,
+        &CheckoutOptions::empty_for_test(),
+    )
+    .unwrap();
+    assert_eq!(
+        std::fs::read(crlf_path.to_fs_path_unchecked(&workspace_root)).unwrap(),
+        b"a\r\nb\r\n"
+    );
+    assert_eq!(
+        std::fs::read(plain_path.to_fs_path_unchecked(&workspace_root)).unwrap(),
+        b"a\nb\n"
+    );
+
+    // Line endings are normalized when snapshotting
+    let new_tree = test_workspace.snapshot().unwrap();
+    assert_eq!(new_tree.id(), tree.id());
+    testutils::write_working_copy_file(&workspace_root, crlf_path, "a\r\nc\r\n");
+    testutils::write_working_copy_file(&workspace_root, plain_path, "a\r\nc\r\n");
+    let new_tree = test_workspace.snapshot().unwrap();
+    let expected_tree = create_tree(
+        &test_workspace.repo,
+        &[
+            (gitattributes_path, "*.txt eol=crlf\n"),
+            (crlf_path, "a\nc\n"),
+            (plain_path, "a\r\nc\r\n"),
+        ],
+    );
+    assert_eq!(new_tree.id(), expected_tree.id());
+}
+
+#[test]
+fn test_gitattributes_text_auto_crlf_in_repo() {
+    // Tests that files already stored with CRLF aren't normalized by
+    // `text=auto`
+
+    let mut test_workspace = TestWorkspace::init();
+    let repo = &test_workspace.repo;
+    let workspace_root = test_workspace.workspace.workspace_root().to_owned();
+
+    let gitattributes_path = RepoPath::from_internal_string(".gitattributes");
+    let crlf_path = RepoPath::from_internal_string("crlf.txt");
+    let new_path = RepoPath::from_internal_string("new.txt");
+    let tree = create_tree(
+        repo,
+        &[
+            (gitattributes_path, "* text=auto\n"),
+            (crlf_path, "a\r\nb\r\n"),
+        ],
+    );
+    let commit = commit_with_tree(repo.store(), tree.id());
+    let ws = &mut test_workspace.workspace;
+    ws.check_out(
+        repo.op_id().clone(),
+        None,
+        &commit,
+        &CheckoutOptions::empty_for_test(),
+    )
+    .unwrap();
+    assert_eq!(
+        std::fs::read(crlf_path.to_fs_path_unchecked(&workspace_root)).unwrap(),
+        b"a\r\nb\r\n"
+    );
+
+    // Touching the file doesn't change it
+    testutils::write_working_copy_file(&workspace_root, crlf_path, "a\r\nb\r\n");
+    let new_tree = test_workspace.snapshot().unwrap();
+    assert_eq!(new_tree.id(), tree.id());
+
+    // Modified lines keep their line endings, but new files are normalized
+    testutils::write_working_copy_file(&workspace_root, crlf_path, "a\r\nc\r\n");
+    testutils::write_working_copy_file(&workspace_root, new_path, "a\r\nc\r\n");
+    let new_tree = test_workspace.snapshot().unwrap();
+    let expected_tree = create_tree(
+        &test_workspace.repo,
+        &[
+            (gitattributes_path, "* text=auto\n"),
+            (crlf_path, "a\r\nc\r\n"),
+            (new_path, "a\nc\n"),
+        ],
+    );
+    assert_eq!(new_tree.id(), expected_tree.id());
+}
+
+#[test]
+fn test_gitattributes_lfs() {
+    // Tests that LFS pointer files are smudged on checkout and cleaned on
+    // snapshot
+
+    let mut test_workspace = TestWorkspace::init_with_backend(TestRepoBackend::Git);
+    let repo = &test_workspace.repo;
+    let workspace_root = test_workspace.workspace.workspace_root().to_owned();
+    let git_repo_path = git::get_git_backend(repo.store())
+        .unwrap()
+        .git_repo_path()
+        .to_owned();
+    let lfs_store = GitLfsStore::for_git_dir(&git_repo_path);
+
+    let gitattributes_path = RepoPath::from_internal_string(".gitattributes");
+    let stored_path = RepoPath::from_internal_string("assets/stored.bin");
+    let missing_path = RepoPath::from_internal_string("assets/missing.bin");
+    let invalid_path = RepoPath::from_internal_string("assets/invalid.bin");
+    let plain_path = RepoPath::from_internal_string("assets/plain.txt");
+    let stored_pointer = lfs_store
+        .write_object(&mut b"stored".as_slice())
+        .unwrap()
+        .to_bytes();
+    let stored_pointer = String::from_utf8(stored_pointer).unwrap();
+    let missing_pointer = LfsPointer::for_content(b"missing").to_bytes();
+    let missing_pointer = String::from_utf8(missing_pointer).unwrap();
+    let invalid_pointer = missing_pointer.replace("sha256:", "md5:");
+    let tree = create_tree(
+        repo,
+        &[
+            (
+                gitattributes_path,
+                "*.bin filter=lfs diff=lfs merge=lfs -text\n",
+            ),
+            (stored_path, &stored_pointer),
+            (missing_path, &missing_pointer),
+            (invalid_path, &invalid_pointer),
+            (plain_path, &stored_pointer),
+        ],
+    );
+    let commit = commit_with_tree(repo.store(), tree.id());
+    let ws = &mut test_workspace.workspace;
+    let stats = ws
+        .check_out(
+            repo.op_id().clone(),
+            None,
+            &commit,
+            &CheckoutOptions::empty_for_test(),
+        )
+        .unwrap();
+    assert_eq!(stats.added_files, 5);
+    assert_eq!(stats.lfs_pointer_files, 2);
+    assert_eq!(
+        std::fs::read(stored_path.to_fs_path_unchecked(&workspace_root)).unwrap(),
+        b"stored"
+    );
+    // Pointer to unavailable object and invalid pointer are checked out as is
+    assert_eq!(
+        std::fs::read_to_string(missing_path.to_fs_path_unchecked(&workspace_root)).unwrap(),
+        missing_pointer
+    );
+    assert_eq!(
+        std::fs::read_to_string(invalid_path.to_fs_path_unchecked(&workspace_root)).unwrap(),
+        invalid_pointer
+    );
+    assert_eq!(
+        std::fs::read_to_string(plain_path.to_fs_path_unchecked(&workspace_root)).unwrap(),
+        stored_pointer
+    );
+
+    let new_tree = test_workspace.snapshot().unwrap();
+    assert_eq!(new_tree.id(), tree.id());
+
+    // Modified files are stored as LFS objects
+    testutils::write_working_copy_file(&workspace_root, stored_path, "modified");
+    testutils::write_working_copy_file(&workspace_root, missing_path, "added");
+    let new_tree = test_workspace.snapshot().unwrap();
+    let modified_pointer = LfsPointer::for_content(b"modified").to_bytes();
+    let added_pointer = LfsPointer::for_content(b"added").to_bytes();
+    let expected_tree = create_tree(
+        &test_workspace.repo,
+        &[
+            (
+                gitattributes_path,
+                "*.bin filter=lfs diff=lfs merge=lfs -text\n",
+            ),
+            (stored_path, std::str::from_utf8(&modified_pointer).unwrap()),
+            (missing_path, std::str::from_utf8(&added_pointer).unwrap()),
+            (invalid_path, &invalid_pointer),
+            (plain_path, &stored_pointer),
+        ],
+    );
+    assert_eq!(new_tree.id(), expected_tree.id());
+    let pointer = LfsPointer::parse(&modified_pointer).unwrap().unwrap();
+    let mut content = vec![];
+    lfs_store
+        .open_object(&pointer)
+        .unwrap()
+        .unwrap()
+        .read_to_end(&mut content)
+        .unwrap();
+    assert_eq!(content, b"modified");
+}
+
+#[test]
+fn test_gitattributes_lfs_fetch() {
+    // Tests that LFS objects missing locally are downloaded by the fetcher
+    // passed to the working-copy factory
+
+    #[derive(Debug)]
+    struct TestFetcher;
+
+    impl LfsFetcher for TestFetcher {
+        fn fetch(
+            &self,
+            pointer: &LfsPointer,
+            output: &mut dyn Write,
+        ) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+            if *pointer == LfsPointer::for_content(b"unreachable") {
+                return Err("server unreachable".into());
+            }
+            if *pointer != LfsPointer::for_content(b"remote") {
+                return Ok(false);
+            }
+            output.write_all(b"remote")?;
+            Ok(true)
+        }
+    }
+
+    let settings = testutils::user_settings();
+    let test_workspace = TestWorkspace::init_with_backend(TestRepoBackend::Git);
+    let repo = &test_workspace.repo;
+    let workspace_root = test_workspace.workspace.workspace_root().to_owned();
+
+    let gitattributes_path = RepoPath::from_internal_string(".gitattributes");
+    let remote_path = RepoPath::from_internal_string("remote.bin");
+    let missing_path = RepoPath::from_internal_string("missing.bin");
+    let remote_pointer = LfsPointer::for_content(b"remote").to_bytes();
+    let missing_pointer = LfsPointer::for_content(b"missing").to_bytes();
+    let unreachable_path = RepoPath::from_internal_string("unreachable.bin");
+    let unreachable_pointer = LfsPointer::for_content(b"unreachable").to_bytes();
+    let tree = create_tree(
+        repo,
+        &[
+            (gitattributes_path, "*.bin filter=lfs -text\n"),
+            (remote_path, std::str::from_utf8(&remote_pointer).unwrap()),
+            (missing_path, std::str::from_utf8(&missing_pointer).unwrap()),
+            (
+                unreachable_path,
+                std::str::from_utf8(&unreachable_pointer).unwrap(),
+            ),
+        ],
+    );
+    let commit = commit_with_tree(repo.store(), tree.id());
+
+    let mut working_copy_factories = WorkingCopyFactories::new();
+    working_copy_factories.insert(
+        LocalWorkingCopy::name().to_owned(),
+        Box::new(LocalWorkingCopyFactory::new().with_lfs_fetcher(Arc::new(TestFetcher))),
+    );
+    let mut ws = Workspace::load(
+        &settings,
+        &workspace_root,
+        &test_workspace.env.default_store_factories(),
+        &working_copy_factories,
+    )
+    .unwrap();
+    let repo = ws.repo_loader().load_at(repo.operation()).unwrap();
+    let commit = repo.store().get_commit(commit.id()).unwrap();
+    let stats = ws
+        .check_out(
+            repo.op_id().clone(),
+            None,
+            &commit,
+            &CheckoutOptions::empty_for_test(),
+        )
+        .unwrap();
+    assert_eq!(stats.lfs_pointer_files, 2);
+    assert_eq!(
+        std::fs::read(remote_path.to_fs_path_unchecked(&workspace_root)).unwrap(),
+        b"remote"
+    );
+    assert_eq!(
+        std::fs::read(missing_path.to_fs_path_unchecked(&workspace_root)).unwrap(),
+        missing_pointer
+    );
+    // Fetch errors don't abort the checkout
+    assert_eq!(
+        std::fs::read(unreachable_path.to_fs_path_unchecked(&workspace_root)).unwrap(),
+        unreachable_pointer
+    );
+}
+
 #[test]
 fn test_dotgit_ignored() {
     // Tests that .git directories and files are always ignored (we could accept
@@ -1342,7 +1655,10 @@ fn test_git_submodule(gitignore_content: &str) {
     )
     .unwrap();
 
-    std::fs::create_dir(submodule_path.to_fs_path_unchecked(&workspace_root)).unwrap();
+    // The submodule directory should be created on checkout
+    assert!(submodule_path
+        .to_fs_path_unchecked(&workspace_root)
+        .is_dir());
 
     testutils::write_working_copy_file(
         &workspace_root,
@@ -1402,6 +1718,118 @@ fn test_git_submodule(gitignore_content: &str) {
     assert_eq!(stats.skipped_files, 1);
 }
 
+#[test]
+fn test_git_submodule_initialized() {
+    // Tests that initialized git submodules are checked out at the recorded
+    // commits, and that the checked-out commits are snapshotted.
+
+    let mut test_workspace = TestWorkspace::init_with_backend(TestRepoBackend::Git);
+    let repo = test_workspace.repo.clone();
+    let store = repo.store().clone();
+    let workspace_root = test_workspace.workspace.workspace_root().to_owned();
+
+    // Set up the repo the submodule is fetched from
+    let origin_path = test_workspace.env.root().join("origin");
+    let origin_repo = git2::Repository::init(&origin_path).unwrap();
+    let signature = git2::Signature::now("Someone", "someone@example.com").unwrap();
+    let create_origin_commit = |content: &str| {
+        let blob_id = origin_repo.blob(content.as_bytes()).unwrap();
+        let mut tree_builder = origin_repo.treebuilder(None).unwrap();
+        tree_builder.insert("file", blob_id, 0o100644).unwrap();
+        let tree = origin_repo
+            .find_tree(tree_builder.write().unwrap())
+            .unwrap();
+        let parent = origin_repo
+            .head()
+            .ok()
+            .map(|head| head.peel_to_commit().unwrap());
+        let oid = origin_repo
+            .commit(
+                Some("HEAD"),
+                &signature,
+                &signature,
+                content,
+                &tree,
+                &parent.iter().collect_vec(),
+            )
+            .unwrap();
+        CommitId::from_bytes(oid.as_bytes())
+    };
+    let submodule_id1 = create_origin_commit("1");
+    let submodule_id2 = create_origin_commit("2");
+
+    let submodule_path = RepoPath::from_internal_string("sub");
+    let create_tree_with_submodule = |id: &CommitId| {
+        let mut tree_builder = MergedTreeBuilder::new(store.empty_merged_tree_id());
+        tree_builder.set_or_remove(
+            submodule_path.to_owned(),
+            Merge::normal(TreeValue::GitSubmodule(id.clone())),
+        );
+        tree_builder.write_tree(&store).unwrap()
+    };
+    let tree_id1 = create_tree_with_submodule(&submodule_id1);
+    let tree_id2 = create_tree_with_submodule(&submodule_id2);
+    let commit1 = commit_with_tree(&store, tree_id1.clone());
+    let commit2 = commit_with_tree(&store, tree_id2.clone());
+
+    let ws = &mut test_workspace.workspace;
+    ws.check_out(
+        repo.op_id().clone(),
+        None,
+        &commit1,
+        &CheckoutOptions::empty_for_test(),
+    )
+    .unwrap();
+
+    // Initialize the submodule
+    let disk_path = submodule_path.to_fs_path_unchecked(&workspace_root);
+    let git_dir = repo.submodule_store().git_dir("sub").unwrap();
+    git::init_submodule(&disk_path, &git_dir, origin_path.to_str().unwrap()).unwrap();
+    assert!(git::fetch_submodule(&disk_path, Default::default()).unwrap());
+    assert!(git::check_out_submodule(&disk_path, &submodule_id1).unwrap());
+    assert_eq!(std::fs::read(disk_path.join("file")).unwrap(), b"1");
+    let new_tree = test_workspace.snapshot().unwrap();
+    assert_eq!(new_tree.id(), tree_id1);
+
+    // Checking out another commit should update the submodule
+    let ws = &mut test_workspace.workspace;
+    let stats = ws
+        .check_out(
+            repo.op_id().clone(),
+            None,
+            &commit2,
+            &CheckoutOptions::empty_for_test(),
+        )
+        .unwrap();
+    assert_eq!(stats.updated_files, 1);
+    assert_eq!(stats.skipped_files, 0);
+    assert_eq!(
+        git::read_submodule_head(&disk_path),
+        Some(submodule_id2.clone())
+    );
+    assert_eq!(std::fs::read(disk_path.join("file")).unwrap(), b"2");
+
+    // Commit checked out in the submodule should be snapshotted
+    assert!(git::check_out_submodule(&disk_path, &submodule_id1).unwrap());
+    let new_tree = test_workspace.snapshot().unwrap();
+    assert_eq!(new_tree.id(), tree_id1);
+
+    // Local changes in the submodule shouldn't be overwritten
+    std::fs::write(disk_path.join("file"), "modified").unwrap();
+    let ws = &mut test_workspace.workspace;
+    let stats = ws
+        .check_out(
+            repo.op_id().clone(),
+            None,
+            &commit2,
+            &CheckoutOptions::empty_for_test(),
+        )
+        .unwrap();
+    assert_eq!(stats.skipped_files, 1);
+    assert_eq!(git::read_submodule_head(&disk_path), Some(submodule_id1));
+    assert_eq!(std::fs::read(disk_path.join("file")).unwrap(), b"modified");
+}
+
 #[test]
 fn test_check_out_existing_file_cannot_be_removed() {
     let mut test_workspace = TestWorkspace::init();
@@ -2104,6 +2532,107 @@ fn test_fsmonitor() {
     }
 }
 
+#[cfg(target_os = "linux")]
+#[test]
+fn test_native_fsmonitor() {
+    use jj_lib::fsmonitor::native;
+    use jj_lib::fsmonitor::NativeConfig;
+
+    let mut test_workspace = TestWorkspace::init();
+    let repo = &test_workspace.repo;
+    let workspace_root = test_workspace.workspace.workspace_root().to_owned();
+    let ws = &mut test_workspace.workspace;
+    let state_path = ws
+        .working_copy()
+        .as_any()
+        .downcast_ref::<LocalWorkingCopy>()
+        .unwrap()
+        .state_path()
+        .to_owned();
+
+    let foo_path = RepoPath::from_internal_string("foo");
+    let bar_path = RepoPath::from_internal_string("bar");
+    let nested_path = RepoPath::from_internal_string("dir/nested");
+    testutils::write_working_copy_file(&workspace_root, foo_path, "foo\n");
+
+    let daemon = native::Daemon::new(&workspace_root, &state_path).unwrap();
+    assert_matches!(
+        native::Daemon::new(&workspace_root, &state_path),
+        Err(native::Error::AlreadyRunning)
+    );
+    let daemon_thread = std::thread::spawn(move || daemon.run());
+
+    let config = NativeConfig {
+        start_daemon: false,
+    };
+    let query_changed_files = |ws: &Workspace| {
+        let wc: &LocalWorkingCopy = ws.working_copy().as_any().downcast_ref().unwrap();
+        let (_clock, changed_files) = wc.query_native_fsmonitor().unwrap();
+        changed_files.map(|paths| paths.into_iter().sorted().collect_vec())
+    };
+    let snapshot = |ws: &mut Workspace| {
+        let mut locked_ws = ws.start_working_copy_mutation().unwrap();
+        let (tree_id, _stats) = locked_ws
+            .locked_wc()
+            .snapshot(&SnapshotOptions {
+                fsmonitor_settings: FsmonitorSettings::Native(config.clone()),
+                ..SnapshotOptions::empty_for_test()
+            })
+            .unwrap();
+        locked_ws.finish(repo.op_id().clone()).unwrap();
+        tree_id
+    };
+
+    // Without a previous clock, the whole working copy has to be crawled.
+    assert_eq!(query_changed_files(ws), None);
+    let tree_id = snapshot(ws);
+    insta::assert_snapshot!(testutils::dump_tree(repo.store(), &tree_id), @r#"
+    tree d5e38c0a1b0ee5de47c5
+      file "foo" (e99c2057c15160add351): "foo\n"
+    "#);
+    assert_eq!(query_changed_files(ws), Some(vec![]));
+
+    testutils::write_working_copy_file(&workspace_root, bar_path, "bar\n");
+    testutils::write_working_copy_file(&workspace_root, nested_path, "nested\n");
+    assert_eq!(
+        query_changed_files(ws),
+        Some(vec![
+            PathBuf::from("bar"),
+            PathBuf::from("dir"),
+            PathBuf::from("dir/nested"),
+        ])
+    );
+    let tree_id = snapshot(ws);
+    insta::assert_snapshot!(testutils::dump_tree(repo.store(), &tree_id), @r#"
+    tree 3d3df90101b07a6d8381
+      file "bar" (94cc973e7e1aefb7eff6): "bar\n"
+      file "dir/nested" (6209060941cd770c8d46): "nested\n"
+      file "foo" (e99c2057c15160add351): "foo\n"
+    "#);
+
+    // Files below the old directory path aren't journaled individually.
+    std::fs::rename(workspace_root.join("dir"), workspace_root.join("moved")).unwrap();
+    assert_eq!(
+        query_changed_files(ws),
+        Some(vec![
+            PathBuf::from("dir"),
+            PathBuf::from("moved"),
+            PathBuf::from("moved/nested"),
+        ])
+    );
+    let tree_id = snapshot(ws);
+    insta::assert_snapshot!(testutils::dump_tree(repo.store(), &tree_id), @r#"
+    tree 969db5b9dc90999510c6
+      file "bar" (94cc973e7e1aefb7eff6): "bar\n"
+      file "foo" (e99c2057c15160add351): "foo\n"
+      file "moved/nested" (6209060941cd770c8d46): "nested\n"
+    "#);
+
+    native::Fsmonitor::new(&state_path).stop().unwrap();
+    daemon_thread.join().unwrap().unwrap();
+    assert_eq!(query_changed_files(ws), None);
+}
+
 #[test]
 fn test_snapshot_max_new_file_size() {
     let mut test_workspace = TestWorkspace::init();
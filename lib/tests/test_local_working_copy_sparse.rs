@@ -85,6 +85,7 @@ fn test_sparse_checkout() {
             added_files: 0,
             removed_files: 3,
             skipped_files: 0,
+            lfs_pointer_files: 0,
         }
     );
     assert_eq!(
@@ -144,6 +145,7 @@ fn test_sparse_checkout() {
             added_files: 2,
             removed_files: 2,
             skipped_files: 0,
+            lfs_pointer_files: 0,
         }
     );
     assert_eq!(locked_wc.sparse_patterns().unwrap(), sparse_patterns);
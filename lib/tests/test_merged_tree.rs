@@ -1315,6 +1315,116 @@ fn test_merge_simple() {
     assert_eq!(merged, expected_merged);
 }
 
+/// Merge drivers selected by `.gitattributes` are used
+#[test]
+fn test_merge_with_gitattributes() {
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+
+    let gitattributes_path = RepoPath::from_internal_string(".gitattributes");
+    let union_path = RepoPath::from_internal_string("dir/changes.md");
+    let binary_path = RepoPath::from_internal_string("dir/data.bin");
+    let attributes = "*.md merge=union\n*.bin binary\n";
+    let base1 = create_single_tree(
+        repo,
+        &[
+            (gitattributes_path, attributes),
+            (union_path, "a\n"),
+            (binary_path, "a\nb\n"),
+        ],
+    );
+    let side1 = create_single_tree(
+        repo,
+        &[
+            (gitattributes_path, attributes),
+            (union_path, "a\nb\n"),
+            (binary_path, "a1\nb\n"),
+        ],
+    );
+    let side2 = create_single_tree(
+        repo,
+        &[
+            (gitattributes_path, attributes),
+            (union_path, "a\nc\n"),
+            (binary_path, "a\nb2\n"),
+        ],
+    );
+    let base1_merged = MergedTree::new(Merge::resolved(base1));
+    let side1_merged = MergedTree::new(Merge::resolved(side1));
+    let side2_merged = MergedTree::new(Merge::resolved(side2));
+
+    let merged = side1_merged.merge(&base1_merged, &side2_merged).unwrap();
+    let union_value = merged.path_value(union_path).unwrap();
+    let expected_id = testutils::write_file(repo.store(), union_path, "a\nb\nc\n");
+    assert_eq!(
+        union_value.as_resolved(),
+        Some(&Some(TreeValue::File {
+            id: expected_id,
+            executable: false,
+        }))
+    );
+    // The binary file would be merged cleanly without the attributes
+    assert!(!merged.path_value(binary_path).unwrap().is_resolved());
+}
+
+/// `.gitattributes` changed by a side other than the first are respected, and
+/// attributes changed differently by the sides are ignored
+#[test]
+fn test_merge_with_gitattributes_changed_by_sides() {
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+
+    let gitattributes_path = RepoPath::from_internal_string(".gitattributes");
+    let union_path = RepoPath::from_internal_string("changes.md");
+    let base1 = create_single_tree(repo, &[(union_path, "a\n")]);
+    let side1 = create_single_tree(repo, &[(union_path, "a\nb\n")]);
+    let side2 = create_single_tree(
+        repo,
+        &[
+            (gitattributes_path, "*.md merge=union\n"),
+            (union_path, "a\nc\n"),
+        ],
+    );
+    let base1_merged = MergedTree::new(Merge::resolved(base1));
+    let side1_merged = MergedTree::new(Merge::resolved(side1));
+    let side2_merged = MergedTree::new(Merge::resolved(side2));
+
+    let merged = side1_merged.merge(&base1_merged, &side2_merged).unwrap();
+    let expected_id = testutils::write_file(repo.store(), union_path, "a\nb\nc\n");
+    assert_eq!(
+        merged.path_value(union_path).unwrap().as_resolved(),
+        Some(&Some(TreeValue::File {
+            id: expected_id,
+            executable: false,
+        }))
+    );
+
+    // Either attribute would leave the file conflicted, but the sides changed
+    // them differently
+    let data_path = RepoPath::from_internal_string("data.bin");
+    let base1 = create_single_tree(repo, &[(data_path, "a\nb\nc\n")]);
+    let side1 = create_single_tree(
+        repo,
+        &[
+            (gitattributes_path, "*.bin binary\n"),
+            (data_path, "a1\nb\nc\n"),
+        ],
+    );
+    let side2 = create_single_tree(
+        repo,
+        &[
+            (gitattributes_path, "*.bin -merge\n"),
+            (data_path, "a\nb\nc2\n"),
+        ],
+    );
+    let base1_merged = MergedTree::new(Merge::resolved(base1));
+    let side1_merged = MergedTree::new(Merge::resolved(side1));
+    let side2_merged = MergedTree::new(Merge::resolved(side2));
+    let merged = side1_merged.merge(&base1_merged, &side2_merged).unwrap();
+    assert!(!merged.path_value(gitattributes_path).unwrap().is_resolved());
+    assert!(merged.path_value(data_path).unwrap().is_resolved());
+}
+
 /// Merge 3 resolved trees that can be partially resolved
 #[test]
 fn test_merge_partial_resolution() {
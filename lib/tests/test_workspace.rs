@@ -19,9 +19,13 @@ use jj_lib::op_store::WorkspaceId;
 use jj_lib::repo::Repo;
 use jj_lib::workspace::default_working_copy_factories;
 use jj_lib::workspace::default_working_copy_factory;
+use jj_lib::workspace::move_workspace;
+use jj_lib::workspace::repair_workspace_paths;
 use jj_lib::workspace::Workspace;
 use jj_lib::workspace::WorkspaceLoadError;
+use jj_lib::workspace_store::SimpleWorkspaceStore;
 use testutils::TestEnvironment;
+use testutils::TestRepoBackend;
 use testutils::TestWorkspace;
 
 #[test]
@@ -92,6 +96,144 @@ fn test_init_additional_workspace() {
     assert_eq!(same_workspace.workspace_root(), ws2.workspace_root());
 }
 
+#[test]
+fn test_move_workspace() {
+    let settings = testutils::user_settings();
+    // The test backend can't be moved
+    let test_workspace =
+        TestWorkspace::init_with_backend_and_settings(TestRepoBackend::Local, &settings);
+    let store_factories = test_workspace.env.default_store_factories();
+    let main_root = test_workspace.workspace.workspace_root().to_owned();
+    let default_id = test_workspace.workspace.workspace_id().clone();
+    let env_root = dunce::canonicalize(test_workspace.root_dir()).unwrap();
+
+    let ws2_id = WorkspaceId::new("ws2".to_string());
+    let ws2_root = env_root.join("ws2_root");
+    std::fs::create_dir(&ws2_root).unwrap();
+    let (ws2, _repo) = Workspace::init_workspace_with_existing_repo(
+        &ws2_root,
+        test_workspace.repo_path(),
+        &test_workspace.repo,
+        &*default_working_copy_factory(),
+        ws2_id.clone(),
+    )
+    .unwrap();
+    let ws2_root = ws2.workspace_root().to_owned();
+    // Git directory outside of the workspace
+    let git_dir = env_root.join("git");
+    std::fs::create_dir(&git_dir).unwrap();
+    std::fs::write(main_root.join(".git"), "gitdir: ../git\n").unwrap();
+
+    // Move the workspace containing the repo
+    let new_root = env_root.join("moved");
+    let repaired = move_workspace(&test_workspace.workspace, &new_root).unwrap();
+    assert_eq!(repaired.workspace_root, new_root);
+    assert_eq!(repaired.old_root.as_ref(), Some(&main_root));
+    assert_eq!(repaired.updated_workspaces, vec![ws2_id.clone()]);
+    assert_eq!(repaired.updated_files, vec![new_root.join(".git")]);
+    assert!(!main_root.exists());
+    assert_eq!(
+        std::fs::read_to_string(new_root.join(".git")).unwrap(),
+        format!("gitdir: {}\n", git_dir.display())
+    );
+    let ws2 = Workspace::load(
+        &settings,
+        &ws2_root,
+        &store_factories,
+        &default_working_copy_factories(),
+    )
+    .unwrap();
+    assert_eq!(ws2.repo_path(), new_root.join(".jj").join("repo"));
+    assert_eq!(ws2.moved_from().unwrap(), None);
+
+    // Move the workspace back without updating the paths
+    std::fs::rename(&new_root, &main_root).unwrap();
+    let result = Workspace::load(
+        &settings,
+        &ws2_root,
+        &store_factories,
+        &default_working_copy_factories(),
+    );
+    assert_matches!(result.err(), Some(WorkspaceLoadError::RepoDoesNotExist(_)));
+    let main = Workspace::load(
+        &settings,
+        &main_root,
+        &store_factories,
+        &default_working_copy_factories(),
+    )
+    .unwrap();
+    assert_eq!(main.moved_from().unwrap(), Some(new_root.clone()));
+    let repaired = repair_workspace_paths(&main_root, &default_id, None).unwrap();
+    assert_eq!(repaired.old_root, Some(new_root));
+    assert_eq!(repaired.updated_workspaces, vec![ws2_id]);
+    assert_eq!(main.moved_from().unwrap(), None);
+    let ws2 = Workspace::load(
+        &settings,
+        &ws2_root,
+        &store_factories,
+        &default_working_copy_factories(),
+    )
+    .unwrap();
+    assert_eq!(ws2.repo_path(), main_root.join(".jj").join("repo"));
+}
+
+#[test]
+fn test_backfill_workspace_store() {
+    let settings = testutils::user_settings();
+    let mut test_workspace = TestWorkspace::init_with_settings(&settings);
+    let workspace_root = test_workspace.workspace.workspace_root().to_owned();
+    let workspace_id = test_workspace.workspace.workspace_id().clone();
+    let store = SimpleWorkspaceStore::load(test_workspace.repo_path());
+
+    // Workspace created by an older version isn't recorded
+    std::fs::remove_dir_all(test_workspace.repo_path().join("workspace_store")).unwrap();
+    assert_eq!(store.get_workspace_path(&workspace_id).unwrap(), None);
+    let repo = test_workspace.repo.clone();
+    let locked_ws = test_workspace
+        .workspace
+        .start_working_copy_mutation()
+        .unwrap();
+    locked_ws.backfill_workspace_store(repo.view()).unwrap();
+    drop(locked_ws);
+    assert_eq!(
+        store.get_workspace_path(&workspace_id).unwrap(),
+        Some(dunce::canonicalize(&workspace_root).unwrap())
+    );
+
+    // Forgotten workspace isn't recorded again
+    store.forget(&[&workspace_id]).unwrap();
+    let mut tx = repo.start_transaction();
+    tx.repo_mut().remove_wc_commit(&workspace_id).unwrap();
+    tx.repo_mut().rebase_descendants().unwrap();
+    let repo = tx.commit("forget").unwrap();
+    let locked_ws = test_workspace
+        .workspace
+        .start_working_copy_mutation()
+        .unwrap();
+    locked_ws.backfill_workspace_store(repo.view()).unwrap();
+    assert_eq!(store.get_workspace_path(&workspace_id).unwrap(), None);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_moved_from_symlinked_path() {
+    let settings = testutils::user_settings();
+    let test_workspace = TestWorkspace::init_with_settings(&settings);
+    let workspace_root = test_workspace.workspace.workspace_root().to_owned();
+    let link_path = test_workspace.root_dir().join("link");
+    std::os::unix::fs::symlink(&workspace_root, &link_path).unwrap();
+
+    // The same workspace accessed through a symlink isn't considered moved
+    let ws = Workspace::load(
+        &settings,
+        &link_path,
+        &test_workspace.env.default_store_factories(),
+        &default_working_copy_factories(),
+    )
+    .unwrap();
+    assert_eq!(ws.moved_from().unwrap(), None);
+}
+
 /// Test cross-thread access to a workspace, which requires it to be Send
 #[test]
 fn test_sendable() {